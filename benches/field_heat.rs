@@ -0,0 +1,113 @@
+//! Dispatch cost of `#[proto(hot)]` / `#[proto(cold)]` on wide messages whose traffic is mostly
+//! one late field next to a blob: one below the dense dispatch threshold and one of 50+ fields
+//! above it, where unhinted fields go through range arms.
+
+use std::hint::black_box;
+
+use criterion::Criterion;
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::ProtoExt;
+use proto_rs::proto_message;
+
+macro_rules! wide_message {
+    ($name:ident, [$($seq_attr:tt)*], [$($blob_attr:tt)*]) => {
+        #[proto_message]
+        #[derive(Debug, Clone, PartialEq, Default)]
+        pub struct $name {
+            $($blob_attr)*
+            pub blob: Vec<u8>,
+            pub f01: u64, pub f02: u64, pub f03: u64, pub f04: u64, pub f05: u64, pub f06: u64,
+            pub f07: u64, pub f08: u64, pub f09: u64, pub f10: u64, pub f11: u64, pub f12: u64,
+            pub f13: u64, pub f14: u64, pub f15: u64, pub f16: u64, pub f17: u64, pub f18: u64,
+            pub f19: u64, pub f20: u64, pub f21: u64, pub f22: u64, pub f23: u64, pub f24: u64,
+            $($seq_attr)*
+            pub seq: u64,
+        }
+
+        impl $name {
+            fn sample(seq: u64) -> Self {
+                Self {
+                    blob: vec![0xab; 64],
+                    f03: 7,
+                    seq,
+                    ..Self::default()
+                }
+            }
+        }
+    };
+}
+
+wide_message!(Plain, [], []);
+wide_message!(Hinted, [#[proto(hot)]], [#[proto(cold)]]);
+
+macro_rules! dense_message {
+    ($name:ident, [$($seq_attr:tt)*], [$($blob_attr:tt)*]) => {
+        #[proto_message]
+        #[derive(Debug, Clone, PartialEq, Default)]
+        pub struct $name {
+            $($blob_attr)*
+            pub blob: Vec<u8>,
+            pub d01: u64, pub d02: u64, pub d03: u64, pub d04: u64, pub d05: u64, pub d06: u64,
+            pub d07: u64, pub d08: u64, pub d09: u64, pub d10: u64, pub d11: u64, pub d12: u64,
+            pub d13: u64, pub d14: u64, pub d15: u64, pub d16: u64, pub d17: u64, pub d18: u64,
+            pub d19: u64, pub d20: u64, pub d21: u64, pub d22: u64, pub d23: u64, pub d24: u64,
+            pub d25: u64, pub d26: u64, pub d27: u64, pub d28: u64, pub d29: u64, pub d30: u64,
+            pub d31: u64, pub d32: u64, pub d33: u64, pub d34: u64, pub d35: u64, pub d36: u64,
+            pub d37: u64, pub d38: u64, pub d39: u64, pub d40: u64, pub d41: u64, pub d42: u64,
+            pub d43: u64, pub d44: u64, pub d45: u64, pub d46: u64, pub d47: u64, pub d48: u64,
+            pub d49: u64, pub d50: u64,
+            $($seq_attr)*
+            pub seq: u64,
+        }
+
+        impl $name {
+            fn sample(seq: u64) -> Self {
+                Self {
+                    blob: vec![0xab; 64],
+                    d03: 7,
+                    d41: 41,
+                    seq,
+                    ..Self::default()
+                }
+            }
+        }
+    };
+}
+
+dense_message!(DensePlain, [], []);
+dense_message!(DenseHinted, [#[proto(hot)]], [#[proto(cold)]]);
+
+fn benchmark_message<T>(criterion: &mut Criterion, name: &str, sample: impl Fn(u64) -> T)
+where
+    T: ProtoEncode + ProtoDecode + ProtoExt,
+{
+    let messages: Vec<T> = (0..100).map(|seq| sample(seq * 1_000_003)).collect();
+    let encoded: Vec<Vec<u8>> = messages.iter().map(ProtoEncode::encode_to_vec).collect();
+    let mut group = criterion.benchmark_group(format!("field_heat/{name}"));
+    group.bench_function("encode", |b| {
+        b.iter(|| {
+            for message in &messages {
+                black_box(message.encode_to_vec());
+            }
+        });
+    });
+    group.bench_function("decode", |b| {
+        b.iter(|| {
+            for bytes in &encoded {
+                black_box(T::decode(bytes.as_slice(), DecodeContext::default()).expect("sample decodes"));
+            }
+        });
+    });
+    group.finish();
+}
+
+fn main() {
+    let mut criterion = Criterion::default().configure_from_args();
+    benchmark_message(&mut criterion, "plain", Plain::sample);
+    benchmark_message(&mut criterion, "hinted", Hinted::sample);
+    benchmark_message(&mut criterion, "dense_plain", DensePlain::sample);
+    benchmark_message(&mut criterion, "dense_hinted", DenseHinted::sample);
+    criterion.final_summary();
+}
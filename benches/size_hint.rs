@@ -0,0 +1,69 @@
+//! Encoding into a writer sized from `size_hint` against one that starts small and grows, for
+//! messages whose repeated fields hold bounded elements.
+//!
+//! The hint skips the reallocations of small and mid-sized messages. It reserves the worst case
+//! of every element, so for long repeated fields of small values it over-reserves and growing
+//! catches up.
+
+use std::hint::black_box;
+
+use criterion::Criterion;
+use proto_rs::ProtoArchive;
+use proto_rs::ProtoEncode;
+use proto_rs::ProtoShadowEncode;
+use proto_rs::RevVec;
+use proto_rs::RevWriter;
+use proto_rs::proto_message;
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Reading {
+    pub sensor: u32,
+    pub value: f64,
+    pub delta: i64,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Telemetry {
+    pub device: String,
+    pub readings: Vec<Reading>,
+    pub samples: Vec<u64>,
+}
+
+impl Telemetry {
+    fn sample(len: u32) -> Self {
+        Self {
+            device: "device-0042".into(),
+            readings: (0..len)
+                .map(|n| Reading {
+                    sensor: n,
+                    value: f64::from(n) / 7.0,
+                    delta: -i64::from(n),
+                })
+                .collect(),
+            samples: (0..u64::from(len)).map(|n| n * 1_000_003).collect(),
+        }
+    }
+}
+
+/// What the writer did before size hints: start at a small buffer and grow while archiving.
+fn encode_growing(message: &Telemetry) -> Vec<u8> {
+    let shadow = <Telemetry as ProtoEncode>::Shadow::from_sun(message);
+    let mut w = RevVec::with_capacity(64);
+    shadow.archive::<0>(&mut w);
+    w.finish_tight()
+}
+
+fn main() {
+    let mut criterion = Criterion::default().configure_from_args();
+    for len in [16, 256, 4096] {
+        let message = Telemetry::sample(len);
+        assert_eq!(message.encode_to_vec(), encode_growing(&message));
+        let mut group = criterion.benchmark_group(format!("size_hint/{len}"));
+        group.bench_function("hinted", |b| b.iter(|| black_box(message.encode_to_vec())));
+        group.bench_function("growing", |b| b.iter(|| black_box(encode_growing(&message))));
+        group.finish();
+    }
+    criterion.final_summary();
+}
@@ -0,0 +1,3 @@
+rustup target add wasm32-unknown-unknown
+cargo check --lib --target wasm32-unknown-unknown
+cargo check --lib --target wasm32-unknown-unknown --features grpc-web
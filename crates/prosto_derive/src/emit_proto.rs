@@ -3,6 +3,7 @@
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 
+use quote::quote;
 use syn::DataEnum;
 use syn::Field;
 use syn::Fields;
@@ -22,9 +23,9 @@ use crate::utils::proto_type_name;
 use crate::utils::resolved_field_type;
 use crate::utils::rust_type_path_ident;
 use crate::utils::strip_proto_suffix;
-use crate::utils::to_pascal_case;
 use crate::utils::to_snake_case;
 use crate::utils::to_upper_snake_case;
+use crate::write_file::is_empty_wkt_type;
 
 pub fn generate_simple_enum_proto(name: &str, data: &DataEnum) -> String {
     let marked_default = find_marked_default_variant(data).unwrap_or_else(|err| panic!("{}", err));
@@ -130,19 +131,27 @@ fn generate_named_struct_proto(
     generic_params: &[syn::Ident],
 ) -> String {
     let field_defs = generate_named_fields(fields, generic_params);
-    format!("message {name} {{\n{field_defs}\n}}\n\n")
+    let reserved: Vec<String> =
+        fields.iter().filter_map(|field| parse_field_config(field).offset_index).map(|tag| tag.to_string()).collect();
+    if reserved.is_empty() {
+        format!("message {name} {{\n{field_defs}\n}}\n\n")
+    } else {
+        format!("message {name} {{\n{field_defs}\n  reserved {};\n}}\n\n", reserved.join(", "))
+    }
 }
 
 fn generate_tuple_struct_proto(name: &str, fields: &Punctuated<Field, Comma>, generic_params: &[syn::Ident]) -> String {
     let mut proto_fields = Vec::new();
+    let mut field_num = 0;
 
-    for (idx, field) in fields.iter().enumerate() {
+    for field in fields {
         let config = parse_field_config(field);
         if config.skip {
             continue;
         }
+        field_num += 1;
 
-        let field_name = format!("field_{idx}");
+        let field_name = config.proto_name.clone().unwrap_or_else(|| format!("field_{}", field_num - 1));
         let base_ty = resolved_field_type(field, &config);
         let ty = if let Some(ref into_type) = config.into_type {
             syn::parse_str::<Type>(into_type).unwrap_or_else(|_| base_ty.clone())
@@ -154,8 +163,9 @@ fn generate_tuple_struct_proto(name: &str, fields: &Punctuated<Field, Comma>, ge
         let proto_type = resolve_proto_type(&inner_type, &config, &mut is_option, &mut is_repeated, generic_params);
 
         let modifier = field_modifier(is_option, is_repeated);
-        let tag = config.custom_tag.unwrap_or(idx + 1);
-        proto_fields.push(format!("  {modifier}{proto_type} {field_name} = {tag};"));
+        let tag = config.custom_tag.unwrap_or(field_num);
+        let comment = array_len_comment(&ty);
+        proto_fields.push(format!("  {modifier}{proto_type} {field_name} = {tag};{comment}"));
     }
 
     format!("message {} {{\n{}\n}}\n\n", name, proto_fields.join("\n"))
@@ -181,6 +191,17 @@ fn resolve_proto_type(
     determine_proto_type(inner_type, config, generic_params)
 }
 
+/// Trailing comment giving the element limit of a `[T; N]` field emitted as `repeated`.
+fn array_len_comment(ty: &Type) -> String {
+    match ty {
+        Type::Array(array) if !is_bytes_array(ty) => {
+            let len = &array.len;
+            format!(" // at most {} elements", quote!(#len))
+        }
+        _ => String::new(),
+    }
+}
+
 fn field_modifier(is_option: bool, is_repeated: bool) -> &'static str {
     match (is_option, is_repeated) {
         (true, false) => "optional ",
@@ -216,13 +237,18 @@ fn generate_named_fields(fields: &syn::punctuated::Punctuated<syn::Field, syn::t
 
         // Determine proto type string
         let proto_type = resolve_proto_type(&inner_type, &config, &mut is_option, &mut is_repeated, generic_params);
+        // Conditional fields are absent from some builds
+        if config.cfg_predicate().is_some() && !proto_type.starts_with("map<") {
+            is_option = true;
+        }
 
         // Add modifier
         let modifier = field_modifier(is_option, is_repeated);
 
         let tag = config.custom_tag.unwrap_or(field_num);
+        let comment = array_len_comment(&ty);
 
-        proto_fields.push(format!("  {modifier}{proto_type} {field_name} = {tag};"));
+        proto_fields.push(format!("  {modifier}{proto_type} {field_name} = {tag};{comment}"));
     }
 
     proto_fields.join("\n")
@@ -243,6 +269,10 @@ fn get_field_proto_type(field: &Field, generic_params: &[syn::Ident]) -> String
         return "bytes".to_string();
     }
 
+    if let Some(ref import_path) = config.import_path {
+        return format!("{import_path}.{}", proto_type_name(&ty));
+    }
+
     // Handle arrays as repeated
     if let Type::Array(type_array) = &ty {
         let elem_ty = &*type_array.elem;
@@ -313,15 +343,15 @@ fn determine_proto_type(inner_type: &Type, config: &crate::utils::FieldConfig, g
 }
 
 pub fn generate_service_content(
-    trait_name: &syn::Ident,
+    service_name: &str,
     methods: &[MethodInfo],
     proto_imports: &BTreeMap<String, BTreeSet<String>>,
     import_all_from: Option<&str>,
 ) -> String {
-    let mut lines = vec![format!("service {} {{", trait_name)];
+    let mut lines = vec![format!("service {service_name} {{")];
 
     for method in methods {
-        let method_name = to_pascal_case(&method.name.to_string());
+        let method_name = method.proto_name();
         let request_type = qualify_type_name(&method.request_type, proto_imports, import_all_from);
 
         let rpc_def = if method.is_streaming {
@@ -339,7 +369,39 @@ pub fn generate_service_content(
     lines.join("\n")
 }
 
+/// Whether an rpc signature type is `()` or a unit struct declared with `#[proto_message(empty = "wkt")]`
+pub fn is_empty_wkt(ty: &Type) -> bool {
+    match ty {
+        Type::Tuple(tuple) => tuple.elems.is_empty(),
+        Type::Paren(paren) => is_empty_wkt(&paren.elem),
+        Type::Group(group) => is_empty_wkt(&group.elem),
+        _ => is_empty_wkt_type(&extract_type_name(ty)),
+    }
+}
+
+/// Request and response (stream item) proto names of `method`, qualified with their package.
+pub fn rpc_type_names(
+    method: &MethodInfo,
+    package_name: &str,
+    proto_imports: &BTreeMap<String, BTreeSet<String>>,
+    import_all_from: Option<&str>,
+) -> (String, String) {
+    let qualify = |ty: &Type| {
+        let name = qualify_type_name(ty, proto_imports, import_all_from);
+        if name.contains('.') || package_name.is_empty() {
+            name
+        } else {
+            format!("{package_name}.{name}")
+        }
+    };
+    let response_type = method.inner_response_type.as_ref().filter(|_| method.is_streaming).unwrap_or(&method.response_type);
+    (qualify(&method.request_type), qualify(response_type))
+}
+
 fn qualify_type_name(ty: &Type, proto_imports: &BTreeMap<String, BTreeSet<String>>, import_all_from: Option<&str>) -> String {
+    if is_empty_wkt(ty) {
+        return "google.protobuf.Empty".to_string();
+    }
     let type_name = extract_type_name(ty);
     let base_name = extract_base_type_name(ty);
 
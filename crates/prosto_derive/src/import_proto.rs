@@ -0,0 +1,840 @@
+//! `import_proto!`: `#[proto_message]` types generated from an existing proto3 file.
+//!
+//! The file is parsed at compile time (path relative to the crate's `Cargo.toml`) and every
+//! message and enum becomes a Rust type with the file's tags, so the encodings match peers built
+//! from the same file. The file stays the source of truth: it is registered as external, so live
+//! emission never rewrites it and `schema-check` does not compare against it.
+//!
+//! Supported: messages (nested ones through `nest_in`), enums, `optional`, `repeated`, `map<K, V>`
+//! and messages whose body is a single `oneof`, which become complex enums. Services, options,
+//! `reserved` and `extend` blocks are skipped. Types from imported files resolve to a Rust type of
+//! the same name in scope at the call site.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::format_ident;
+use quote::quote;
+use syn::LitStr;
+use syn::parse_macro_input;
+
+use crate::utils::to_pascal_case;
+use crate::utils::to_upper_snake_case;
+use crate::write_file::register_external_file;
+
+pub fn import_proto_impl(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as LitStr);
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+    let full_path = Path::new(&manifest_dir).join(path.value());
+    let expanded = std::fs::read_to_string(&full_path)
+        .map_err(|err| format!("cannot read {}: {err}", full_path.display()))
+        .and_then(|source| parse_file(&source))
+        .and_then(|file| generate(&file, &path.value()));
+    match expanded {
+        Ok(items) => {
+            register_external_file(&path.value());
+            let tracked = full_path.to_string_lossy().into_owned();
+            quote! {
+                const _: &str = ::core::include_str!(#tracked);
+                #items
+            }
+            .into()
+        }
+        Err(message) => syn::Error::new(path.span(), format!("import_proto!: {message}")).to_compile_error().into(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Tok {
+    Ident(String),
+    Int(String),
+    Str(String),
+    Symbol(char),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    tok: Tok,
+    line: usize,
+    /// Comments between the previous token and this one.
+    docs: Vec<String>,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut docs = Vec::new();
+    let mut line = 1;
+    let mut idx = 0;
+    while idx < chars.len() {
+        let ch = chars[idx];
+        if ch == '\n' {
+            line += 1;
+            idx += 1;
+        } else if ch.is_whitespace() {
+            idx += 1;
+        } else if ch == '/' && chars.get(idx + 1) == Some(&'/') {
+            let end = chars[idx..].iter().position(|&ch| ch == '\n').map_or(chars.len(), |offset| idx + offset);
+            // Trailing comments belong to the line they end, not to the next token.
+            if tokens.last().is_none_or(|token: &Token| token.line != line) {
+                docs.push(chars[idx + 2..end].iter().collect::<String>().trim().to_string());
+            }
+            idx = end;
+        } else if ch == '/' && chars.get(idx + 1) == Some(&'*') {
+            let mut end = idx + 2;
+            while end + 1 < chars.len() && !(chars[end] == '*' && chars[end + 1] == '/') {
+                end += 1;
+            }
+            if end + 1 >= chars.len() {
+                return Err(format!("line {line}: unterminated block comment"));
+            }
+            let text: String = chars[idx + 2..end].iter().collect();
+            line += text.matches('\n').count();
+            docs.extend(text.lines().map(|text| text.trim().trim_start_matches('*').trim().to_string()));
+            idx = end + 2;
+        } else if ch == '"' || ch == '\'' {
+            let (value, end) = string_literal(&chars, idx).ok_or_else(|| format!("line {line}: unterminated string"))?;
+            tokens.push(Token {
+                tok: Tok::Str(value),
+                line,
+                docs: core::mem::take(&mut docs),
+            });
+            idx = end;
+        } else if ch.is_ascii_alphabetic() || ch == '_' || ch.is_ascii_digit() {
+            let end =
+                chars[idx..].iter().position(|&ch| !(ch.is_ascii_alphanumeric() || ch == '_')).map_or(chars.len(), |offset| idx + offset);
+            let word: String = chars[idx..end].iter().collect();
+            let tok = if ch.is_ascii_digit() { Tok::Int(word) } else { Tok::Ident(word) };
+            tokens.push(Token {
+                tok,
+                line,
+                docs: core::mem::take(&mut docs),
+            });
+            idx = end;
+        } else {
+            tokens.push(Token {
+                tok: Tok::Symbol(ch),
+                line,
+                docs: core::mem::take(&mut docs),
+            });
+            idx += 1;
+        }
+    }
+    Ok(tokens)
+}
+
+/// Value of the string literal opening at `start` and the index after it.
+fn string_literal(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let quote = chars[start];
+    let mut value = String::new();
+    let mut idx = start + 1;
+    while idx < chars.len() {
+        match chars[idx] {
+            ch if ch == quote => return Some((value, idx + 1)),
+            '\\' => {
+                idx += 1;
+                value.push(match *chars.get(idx)? {
+                    'n' => '\n',
+                    't' => '\t',
+                    other => other,
+                });
+            }
+            ch => value.push(ch),
+        }
+        idx += 1;
+    }
+    None
+}
+
+/// A parsed `.proto` file, also read by `schema_check` for the recorded snapshot.
+#[derive(Debug, Default)]
+pub struct ProtoFile {
+    package: Option<String>,
+    pub messages: Vec<MessageDef>,
+    pub enums: Vec<EnumDef>,
+}
+
+#[derive(Debug)]
+pub struct MessageDef {
+    /// Dotted path inside the file, e.g. `Invoice.LineItem`.
+    pub path: String,
+    docs: Vec<String>,
+    pub fields: Vec<FieldDef>,
+    pub oneofs: Vec<(String, Vec<FieldDef>)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Label {
+    Singular,
+    Optional,
+    Repeated,
+}
+
+#[derive(Debug, Clone)]
+pub enum FieldType {
+    Named(String),
+    Map(String, String),
+}
+
+#[derive(Debug)]
+pub struct FieldDef {
+    pub name: String,
+    pub label: Label,
+    pub ty: FieldType,
+    pub tag: u32,
+    docs: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct EnumDef {
+    pub path: String,
+    docs: Vec<String>,
+    pub values: Vec<(String, i32, Vec<String>)>,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos).map(|token| &token.tok)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens.get(self.pos).or_else(|| self.tokens.last()).map_or(0, |token| token.line)
+    }
+
+    fn docs(&self) -> Vec<String> {
+        self.tokens.get(self.pos).map(|token| token.docs.clone()).unwrap_or_default()
+    }
+
+    fn error<T>(&self, message: impl core::fmt::Display) -> Result<T, String> {
+        Err(format!("line {}: {message}", self.line()))
+    }
+
+    fn next(&mut self) -> Result<Tok, String> {
+        let Some(token) = self.tokens.get(self.pos) else {
+            return self.error("unexpected end of file");
+        };
+        self.pos += 1;
+        Ok(token.tok.clone())
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Tok::Symbol(symbol)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), String> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            self.error(format!("expected `{symbol}`"))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        match self.next()? {
+            Tok::Ident(ident) => Ok(ident),
+            other => {
+                self.pos -= 1;
+                self.error(format!("expected an identifier, found {other:?}"))
+            }
+        }
+    }
+
+    /// `a.b.C` or `.a.b.C`.
+    fn dotted(&mut self) -> Result<String, String> {
+        let mut name = if self.eat('.') { ".".to_string() } else { String::new() };
+        name.push_str(&self.ident()?);
+        while self.eat('.') {
+            name.push('.');
+            name.push_str(&self.ident()?);
+        }
+        Ok(name)
+    }
+
+    fn integer(&mut self) -> Result<i64, String> {
+        let negative = self.eat('-');
+        let Tok::Int(text) = self.next()? else {
+            self.pos -= 1;
+            return self.error("expected an integer");
+        };
+        let parsed = if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+            i64::from_str_radix(hex, 16)
+        } else if text.len() > 1 && text.starts_with('0') {
+            i64::from_str_radix(&text[1..], 8)
+        } else {
+            text.parse()
+        };
+        let value = parsed.or_else(|_| self.error(format!("invalid integer `{text}`")))?;
+        Ok(if negative { -value } else { value })
+    }
+
+    /// Skips to the end of the current statement or block, whichever closes first.
+    fn skip_statement(&mut self) -> Result<(), String> {
+        let mut depth = 0usize;
+        loop {
+            match self.next()? {
+                Tok::Symbol(';') if depth == 0 => return Ok(()),
+                Tok::Symbol('{' | '[' | '(' | '<') => depth += 1,
+                Tok::Symbol('}' | ']' | ')' | '>') => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 && self.tokens[self.pos - 1].tok == Tok::Symbol('}') {
+                        self.eat(';');
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn file(&mut self) -> Result<ProtoFile, String> {
+        let mut file = ProtoFile::default();
+        while let Some(tok) = self.peek().cloned() {
+            let Tok::Ident(keyword) = tok else {
+                if self.eat(';') {
+                    continue;
+                }
+                return self.error(format!("unexpected {tok:?}"));
+            };
+            match keyword.as_str() {
+                "syntax" => {
+                    self.pos += 1;
+                    self.expect('=')?;
+                    match self.next()? {
+                        Tok::Str(syntax) if syntax == "proto3" => {}
+                        Tok::Str(syntax) => return self.error(format!("only proto3 files are supported, found `{syntax}`")),
+                        _ => return self.error("expected the syntax string"),
+                    }
+                    self.expect(';')?;
+                }
+                "edition" => return self.error("only proto3 files are supported, found an edition"),
+                "package" => {
+                    self.pos += 1;
+                    file.package = Some(self.dotted()?);
+                    self.expect(';')?;
+                }
+                "message" => self.message(None, &mut file)?,
+                "enum" => self.enumeration(None, &mut file)?,
+                _ => self.skip_statement()?,
+            }
+        }
+        Ok(file)
+    }
+
+    fn message(&mut self, parent: Option<&str>, file: &mut ProtoFile) -> Result<(), String> {
+        let docs = self.docs();
+        self.pos += 1;
+        let name = self.ident()?;
+        let path = parent.map_or_else(|| name.clone(), |parent| format!("{parent}.{name}"));
+        self.expect('{')?;
+        let mut message = MessageDef {
+            path: path.clone(),
+            docs,
+            fields: Vec::new(),
+            oneofs: Vec::new(),
+        };
+        while !self.eat('}') {
+            match self.peek().cloned() {
+                Some(Tok::Ident(keyword)) => match keyword.as_str() {
+                    "message" => self.message(Some(&path), file)?,
+                    "enum" => self.enumeration(Some(&path), file)?,
+                    "oneof" => {
+                        self.pos += 1;
+                        let oneof = self.ident()?;
+                        self.expect('{')?;
+                        let mut fields = Vec::new();
+                        while !self.eat('}') {
+                            if matches!(self.peek(), Some(Tok::Ident(keyword)) if keyword == "option") {
+                                self.skip_statement()?;
+                            } else {
+                                fields.push(self.field()?);
+                            }
+                        }
+                        message.oneofs.push((oneof, fields));
+                    }
+                    "option" | "reserved" | "extensions" | "extend" => self.skip_statement()?,
+                    "required" | "group" => return self.error(format!("`{keyword}` is not proto3")),
+                    _ => message.fields.push(self.field()?),
+                },
+                Some(Tok::Symbol(';')) => self.pos += 1,
+                _ => return self.error("expected a field"),
+            }
+        }
+        file.messages.push(message);
+        Ok(())
+    }
+
+    fn field(&mut self) -> Result<FieldDef, String> {
+        let docs = self.docs();
+        let label = match self.peek() {
+            Some(Tok::Ident(label)) if label == "optional" => Label::Optional,
+            Some(Tok::Ident(label)) if label == "repeated" => Label::Repeated,
+            _ => Label::Singular,
+        };
+        if label != Label::Singular {
+            self.pos += 1;
+        }
+        let ty = if matches!(self.peek(), Some(Tok::Ident(map)) if map == "map")
+            && self.tokens.get(self.pos + 1).is_some_and(|token| token.tok == Tok::Symbol('<'))
+        {
+            self.pos += 2;
+            let key = self.dotted()?;
+            self.expect(',')?;
+            let value = self.dotted()?;
+            self.expect('>')?;
+            FieldType::Map(key, value)
+        } else {
+            FieldType::Named(self.dotted()?)
+        };
+        let name = self.ident()?;
+        self.expect('=')?;
+        let tag = self.integer()?;
+        let tag = u32::try_from(tag).ok().filter(|&tag| tag > 0).map_or_else(|| self.error(format!("invalid tag {tag}")), Ok)?;
+        if self.eat('[') {
+            let mut depth = 1;
+            while depth > 0 {
+                match self.next()? {
+                    Tok::Symbol('[') => depth += 1,
+                    Tok::Symbol(']') => depth -= 1,
+                    _ => {}
+                }
+            }
+        }
+        self.expect(';')?;
+        Ok(FieldDef {
+            name,
+            label,
+            ty,
+            tag,
+            docs,
+        })
+    }
+
+    fn enumeration(&mut self, parent: Option<&str>, file: &mut ProtoFile) -> Result<(), String> {
+        let docs = self.docs();
+        self.pos += 1;
+        let name = self.ident()?;
+        let path = parent.map_or_else(|| name.clone(), |parent| format!("{parent}.{name}"));
+        self.expect('{')?;
+        let mut values: Vec<(String, i32, Vec<String>)> = Vec::new();
+        while !self.eat('}') {
+            match self.peek().cloned() {
+                Some(Tok::Ident(keyword)) if keyword == "option" || keyword == "reserved" => self.skip_statement()?,
+                Some(Tok::Ident(_)) => {
+                    let docs = self.docs();
+                    let value_name = self.ident()?;
+                    self.expect('=')?;
+                    let number = self.integer()?;
+                    let number = i32::try_from(number).or_else(|_| self.error(format!("enum value {number} does not fit in int32")))?;
+                    if self.eat('[') {
+                        while !self.eat(']') {
+                            self.next()?;
+                        }
+                    }
+                    self.expect(';')?;
+                    // Aliases (`allow_alias`) keep the first name of a number.
+                    if values.iter().all(|(_, existing, _)| *existing != number) {
+                        values.push((value_name, number, docs));
+                    }
+                }
+                Some(Tok::Symbol(';')) => self.pos += 1,
+                _ => return self.error("expected an enum value"),
+            }
+        }
+        file.enums.push(EnumDef { path, docs, values });
+        Ok(())
+    }
+}
+
+pub fn parse_file(source: &str) -> Result<ProtoFile, String> {
+    Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+    }
+    .file()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DefKind {
+    Message,
+    Enum,
+}
+
+struct Generator<'a> {
+    file: &'a ProtoFile,
+    path: &'a str,
+    /// Dotted path inside the file -> kind of the definition.
+    defs: BTreeMap<String, DefKind>,
+}
+
+/// Rust type of a proto scalar, or `None` for message and enum names.
+fn scalar_type(name: &str) -> Result<Option<TokenStream2>, String> {
+    Ok(Some(match name {
+        "double" => quote! { f64 },
+        "float" => quote! { f32 },
+        "int32" => quote! { i32 },
+        "int64" => quote! { i64 },
+        "uint32" => quote! { u32 },
+        "uint64" => quote! { u64 },
+        "bool" => quote! { bool },
+        "string" => quote! { ::std::string::String },
+        "bytes" => quote! { ::std::vec::Vec<u8> },
+        "sint32" | "sint64" | "fixed32" | "fixed64" | "sfixed32" | "sfixed64" => {
+            return Err(format!("`{name}` fields have no #[proto_message] equivalent"));
+        }
+        _ => return Ok(None),
+    }))
+}
+
+fn last_segment(path: &str) -> &str {
+    path.rsplit('.').next().unwrap_or(path)
+}
+
+fn doc_attrs(docs: &[String]) -> TokenStream2 {
+    let docs = docs.iter().map(|line| format!(" {line}"));
+    quote! { #(#[doc = #docs])* }
+}
+
+fn rust_ident(name: &str) -> syn::Ident {
+    format_ident!("{}", name)
+}
+
+impl Generator<'_> {
+    /// Definition in this file that `reference`, used inside `scope`, names.
+    fn resolve(&self, reference: &str, scope: &str) -> Option<(String, DefKind)> {
+        let mut reference = reference.trim_start_matches('.');
+        if let Some(package) = self.file.package.as_deref() {
+            reference = reference.strip_prefix(package).and_then(|rest| rest.strip_prefix('.')).unwrap_or(reference);
+        }
+        let mut scope = Some(scope);
+        while let Some(current) = scope {
+            let candidate = if current.is_empty() {
+                reference.to_string()
+            } else {
+                format!("{current}.{reference}")
+            };
+            if let Some(kind) = self.defs.get(&candidate) {
+                return Some((candidate, *kind));
+            }
+            scope = current.rsplit_once('.').map(|(parent, _)| parent).or((!current.is_empty()).then_some(""));
+        }
+        None
+    }
+
+    /// Whether a value of message `from` can contain a message `target` without a collection in
+    /// between, which makes a plain field of `from` in `target` an infinitely sized type.
+    fn contains(&self, from: &str, target: &str, seen: &mut BTreeSet<String>) -> bool {
+        if from == target {
+            return true;
+        }
+        if !seen.insert(from.to_string()) {
+            return false;
+        }
+        let Some(message) = self.file.messages.iter().find(|message| message.path == from) else {
+            return false;
+        };
+        let singular = message
+            .fields
+            .iter()
+            .filter(|field| field.label != Label::Repeated)
+            .chain(message.oneofs.iter().flat_map(|(_, fields)| fields));
+        for field in singular {
+            if let FieldType::Named(name) = &field.ty
+                && let Some((path, DefKind::Message)) = self.resolve(name, &message.path)
+                && self.contains(&path, target, seen)
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Element type of a field, boxed when it would make `owner` infinitely sized.
+    fn element_type(&self, name: &str, owner: &str, boxed: bool) -> Result<(TokenStream2, Option<DefKind>), String> {
+        if let Some(scalar) = scalar_type(name)? {
+            return Ok((scalar, None));
+        }
+        // Types from other files are taken to be messages.
+        let (path, kind) = self.resolve(name, owner).unwrap_or_else(|| (name.trim_start_matches('.').to_string(), DefKind::Message));
+        let ident = rust_ident(last_segment(&path));
+        if boxed && kind == DefKind::Message && self.contains(&path, owner, &mut BTreeSet::new()) {
+            return Ok((quote! { ::std::boxed::Box<#ident> }, Some(kind)));
+        }
+        Ok((quote! { #ident }, Some(kind)))
+    }
+
+    fn field_type(&self, field: &FieldDef, owner: &str) -> Result<TokenStream2, String> {
+        match &field.ty {
+            FieldType::Map(key, value) => {
+                let (key, _) = self.element_type(key, owner, false)?;
+                let (value, _) = self.element_type(value, owner, false)?;
+                Ok(quote! { ::std::collections::HashMap<#key, #value> })
+            }
+            FieldType::Named(name) => {
+                let (ty, kind) = self.element_type(name, owner, field.label != Label::Repeated)?;
+                Ok(match (field.label, kind) {
+                    (Label::Repeated, _) => quote! { ::std::vec::Vec<#ty> },
+                    (Label::Optional, _) | (Label::Singular, Some(DefKind::Message)) => quote! { ::core::option::Option<#ty> },
+                    (Label::Singular, _) => ty,
+                })
+            }
+        }
+    }
+
+    fn attributes(&self, path: &str) -> TokenStream2 {
+        let proto_path = self.path;
+        let package = self.file.package.as_deref().map(|package| quote! { , proto_package = #package });
+        let nest_in = path.rsplit_once('.').map(|(parent, _)| {
+            let parent = last_segment(parent);
+            quote! { , nest_in = #parent }
+        });
+        quote! { #[::proto_rs::proto_message(proto_path = #proto_path #package #nest_in)] }
+    }
+
+    fn message(&self, message: &MessageDef) -> Result<TokenStream2, String> {
+        let ident = rust_ident(last_segment(&message.path));
+        let attributes = self.attributes(&message.path);
+        let docs = doc_attrs(&message.docs);
+        if let [(_, variants)] = message.oneofs.as_slice()
+            && message.fields.is_empty()
+        {
+            let variants = variants
+                .iter()
+                .map(|field| {
+                    let FieldType::Named(name) = &field.ty else {
+                        return Err(format!("map field `{}` inside a oneof", field.name));
+                    };
+                    let (ty, _) = self.element_type(name, &message.path, true)?;
+                    let variant = rust_ident(&to_pascal_case(&field.name));
+                    let tag = field.tag as usize;
+                    let docs = doc_attrs(&field.docs);
+                    Ok(quote! {
+                        #docs
+                        #[proto(tag = #tag)]
+                        #variant(#ty)
+                    })
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            return Ok(quote! {
+                #docs
+                #attributes
+                #[derive(Debug, Clone, PartialEq)]
+                pub enum #ident {
+                    #(#variants,)*
+                }
+            });
+        }
+        if let Some((oneof, _)) = message.oneofs.first() {
+            return Err(format!(
+                "oneof `{oneof}` in `{}` is not the only member of its message; only messages whose body is a single oneof are supported",
+                message.path
+            ));
+        }
+        let fields = message
+            .fields
+            .iter()
+            .map(|field| {
+                let ty = self.field_type(field, &message.path)?;
+                let tag = field.tag as usize;
+                let docs = doc_attrs(&field.docs);
+                // Keywords get a trailing underscore, which the schema registry sees as well.
+                let name = if syn::parse_str::<syn::Ident>(&field.name).is_ok() {
+                    rust_ident(&field.name)
+                } else {
+                    format_ident!("{}_", field.name)
+                };
+                Ok(quote! {
+                    #docs
+                    #[proto(tag = #tag)]
+                    pub #name: #ty
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(quote! {
+            #docs
+            #attributes
+            #[derive(Debug, Clone, PartialEq, Default)]
+            pub struct #ident {
+                #(#fields,)*
+            }
+        })
+    }
+
+    fn enumeration(&self, enumeration: &EnumDef) -> Result<TokenStream2, String> {
+        let name = last_segment(&enumeration.path);
+        let ident = rust_ident(name);
+        let attributes = self.attributes(&enumeration.path);
+        let docs = doc_attrs(&enumeration.docs);
+        if enumeration.values.is_empty() {
+            return Err(format!("enum `{}` has no values", enumeration.path));
+        }
+        // `STATUS_ACTIVE` in `Status` becomes `Active`, unless stripping the prefix breaks a name.
+        let prefix = format!("{}_", to_upper_snake_case(name));
+        let strip = enumeration
+            .values
+            .iter()
+            .all(|(value, _, _)| value.strip_prefix(&prefix).is_some_and(|rest| rest.starts_with(|ch: char| ch.is_ascii_alphabetic())));
+        let default = enumeration.values.iter().position(|(_, number, _)| *number == 0).unwrap_or(0);
+        let mut seen = BTreeSet::new();
+        let variants = enumeration
+            .values
+            .iter()
+            .enumerate()
+            .map(|(idx, (value, number, docs))| {
+                let value = if strip { &value[prefix.len()..] } else { value.as_str() };
+                let variant = to_pascal_case(&value.to_ascii_lowercase());
+                if !seen.insert(variant.clone()) {
+                    return Err(format!("enum `{}` has two values named `{variant}` in Rust", enumeration.path));
+                }
+                let variant = rust_ident(&variant);
+                let docs = doc_attrs(docs);
+                let default = (idx == default).then(|| quote! { #[default] });
+                Ok(quote! {
+                    #docs
+                    #default
+                    #variant = #number
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(quote! {
+            #docs
+            #attributes
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+            pub enum #ident {
+                #(#variants,)*
+            }
+        })
+    }
+}
+
+fn generate(file: &ProtoFile, path: &str) -> Result<TokenStream2, String> {
+    let mut defs = BTreeMap::new();
+    let mut rust_names = BTreeMap::new();
+    let all = file
+        .messages
+        .iter()
+        .map(|message| (&message.path, DefKind::Message))
+        .chain(file.enums.iter().map(|enumeration| (&enumeration.path, DefKind::Enum)));
+    for (def_path, kind) in all {
+        if let Some(other) = rust_names.insert(last_segment(def_path).to_string(), def_path.clone()) {
+            return Err(format!("`{other}` and `{def_path}` would both become `{}`", last_segment(def_path)));
+        }
+        defs.insert(def_path.clone(), kind);
+    }
+    let generator = Generator { file, path, defs };
+    let messages = file.messages.iter().map(|message| generator.message(message)).collect::<Result<Vec<_>, String>>()?;
+    let enums = file.enums.iter().map(|enumeration| generator.enumeration(enumeration)).collect::<Result<Vec<_>, String>>()?;
+    Ok(quote! {
+        #(#enums)*
+        #(#messages)*
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHOP: &str = r#"
+        syntax = "proto3";
+        package shop.v1;
+        import "google/protobuf/timestamp.proto";
+        option java_package = "com.example.shop";
+
+        // A placed order.
+        message Order {
+          uint64 id = 1;
+          Status status = 2;
+          repeated Line lines = 4 [packed = true];
+          optional string note = 7;
+          map<string, int64> totals = 8;
+          message Line { string sku = 1; uint32 quantity = 2; }
+          reserved 3, 5 to 6;
+        }
+
+        enum Status {
+          option allow_alias = true;
+          STATUS_UNKNOWN = 0;
+          STATUS_PAID = 1;
+          STATUS_SETTLED = 1;
+          STATUS_REFUNDED = -2;
+        }
+
+        message Payment {
+          oneof method { string voucher = 3; Order.Line card = 5; }
+        }
+
+        service Orders { rpc Place(Order) returns (Order); }
+    "#;
+
+    #[test]
+    fn parses_messages_enums_and_oneofs() {
+        let file = parse_file(SHOP).unwrap();
+        assert_eq!(file.package.as_deref(), Some("shop.v1"));
+        let paths: Vec<_> = file.messages.iter().map(|message| message.path.as_str()).collect();
+        assert_eq!(paths, ["Order.Line", "Order", "Payment"]);
+
+        let order = &file.messages[1];
+        assert_eq!(order.docs, ["A placed order."]);
+        let fields: Vec<_> = order.fields.iter().map(|field| (field.name.as_str(), field.tag, field.label)).collect();
+        assert_eq!(
+            fields,
+            [
+                ("id", 1, Label::Singular),
+                ("status", 2, Label::Singular),
+                ("lines", 4, Label::Repeated),
+                ("note", 7, Label::Optional),
+                ("totals", 8, Label::Singular),
+            ]
+        );
+        assert!(matches!(&order.fields[4].ty, FieldType::Map(key, value) if key == "string" && value == "int64"));
+
+        let status = &file.enums[0];
+        let values: Vec<_> = status.values.iter().map(|(name, number, _)| (name.as_str(), *number)).collect();
+        assert_eq!(values, [("STATUS_UNKNOWN", 0), ("STATUS_PAID", 1), ("STATUS_REFUNDED", -2)]);
+        assert_eq!(file.messages[2].oneofs[0].1.len(), 2);
+    }
+
+    #[test]
+    fn generates_types_with_file_tags() {
+        let file = parse_file(SHOP).unwrap();
+        let tokens = generate(&file, "protos/shop.proto").unwrap().to_string();
+        assert!(tokens.contains("pub enum Status"), "{tokens}");
+        assert!(tokens.contains("# [default] Unknown = 0i32"), "{tokens}");
+        assert!(tokens.contains("Refunded = - 2i32"), "{tokens}");
+        assert!(tokens.contains("proto_package = \"shop.v1\" , nest_in = \"Order\""), "{tokens}");
+        assert!(
+            tokens.contains("# [proto (tag = 4usize)] pub lines : :: std :: vec :: Vec < Line >"),
+            "{tokens}"
+        );
+        assert!(tokens.contains("# [proto (tag = 5usize)] Card (Line)"), "{tokens}");
+    }
+
+    #[test]
+    fn rejects_unsupported_input() {
+        let proto2 = parse_file("syntax = \"proto2\"; message A { required int32 a = 1; }").unwrap_err();
+        assert!(proto2.contains("only proto3"), "{proto2}");
+
+        let mixed = parse_file("syntax = \"proto3\"; message A { int32 a = 1; oneof b { int32 c = 2; } }").unwrap();
+        assert!(generate(&mixed, "a.proto").unwrap_err().contains("single oneof"));
+
+        let fixed = parse_file("syntax = \"proto3\"; message A { fixed64 a = 1; }").unwrap();
+        assert!(generate(&fixed, "a.proto").unwrap_err().contains("fixed64"));
+
+        let recursive = parse_file("syntax = \"proto3\"; message Node { Node next = 1; repeated Node children = 2; }").unwrap();
+        let tokens = generate(&recursive, "a.proto").unwrap().to_string();
+        assert!(tokens.contains("Option < :: std :: boxed :: Box < Node > >"), "{tokens}");
+        assert!(tokens.contains("Vec < Node >"), "{tokens}");
+    }
+}
@@ -7,12 +7,15 @@ use proc_macro::TokenStream;
 mod emit_proto;
 mod generic_substitutions;
 mod impl_proto_ident;
+mod import_proto;
 mod parse;
 mod proto_dump;
 mod proto_import;
 mod proto_message;
 mod proto_rpc;
 mod schema;
+mod schema_check;
+mod treat_as;
 mod utils;
 mod write_file;
 
@@ -36,32 +39,39 @@ pub fn inject_proto_import(input: TokenStream) -> TokenStream {
 ///
 /// # Arguments
 ///
-/// - `package` (optional) - The gRPC package name. Defaults to the trait name if not provided.
+/// - `rpc_package` - The gRPC package name (`proto_package` sets it together with the `.proto` package).
+/// - `rpc_server` / `rpc_client` - Whether to generate the server and client halves.
 ///
 /// # Example
 ///
 /// ```rust
-/// use ftl_proto::{proto_message, proto_rpc, HasProto};
-/// use tonic::{Request, Response, Status};
+/// # #![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+/// use proto_rs::proto_message;
+/// use proto_rs::proto_rpc;
+/// use tonic::Request;
+/// use tonic::Response;
+/// use tonic::Status;
 ///
-/// // Without package name (defaults to "TestRpc")
-/// #[proto_rpc]
-/// pub trait TestRpc {
-///     async fn ping(&self, request: Request<Ping>)
-///         -> Result<Response<Pong>, Status>;
+/// #[proto_message]
+/// #[derive(Debug, Clone, Default, PartialEq)]
+/// pub struct Ping {
+///     pub id: u64,
 /// }
 ///
-/// // With custom package name
-/// #[proto_rpc(my_package)]
+/// #[proto_message]
+/// #[derive(Debug, Clone, Default, PartialEq)]
+/// pub struct Pong {
+///     pub id: u64,
+/// }
+///
+/// #[proto_rpc(rpc_package = "my_package", rpc_server = true, rpc_client = true)]
 /// pub trait MyService {
-///     async fn ping(&self, request: Request<Ping>)
-///         -> Result<Response<Pong>, Status>;
+///     async fn ping(&self, request: Request<Ping>) -> Result<Response<Pong>, Status>;
 /// }
+/// # fn main() {}
 /// ```
 ///
-/// The generated service will have routes like:
-/// - Without package: `/TestRpc/Ping`
-/// - With package: `/my_package.MyService/Ping`
+/// The generated service has routes like `/my_package.MyService/Ping`.
 #[proc_macro_attribute]
 pub fn proto_rpc(attr: TokenStream, item: TokenStream) -> TokenStream {
     let output = proto_rpc::proto_rpc_impl(attr, item);
@@ -73,6 +83,38 @@ pub fn proto_dump(attr: TokenStream, item: TokenStream) -> TokenStream {
     proto_dump::proto_dump_impl(attr, item)
 }
 
+/// Crate-wide `#[proto(treat_as = ...)]` for a type, usually one from another crate.
+///
+/// ```rust,ignore
+/// proto_rs::register_treat_as!(uuid::Uuid as String via crate::ids::uuid_to_string / try crate::ids::uuid_from_string);
+/// proto_rs::register_treat_as!(ComplexMap as std::collections::BTreeMap<u64, u64>);
+/// ```
+///
+/// With `via`, fields of the type are encoded as the proxy type through the two functions, like
+/// `#[proto(into = ..., into_fn = ..., from_fn = ...)]` (`try_from_fn` with `try`). Without it,
+/// the type is encoded as the proxy type directly, like `#[proto(treat_as = ...)]`. Fields with a
+/// conversion of their own keep it. Function paths are resolved where each field is declared.
+/// Registrations only apply to types declared after them, so keep them at the top of the crate root.
+#[proc_macro]
+pub fn register_treat_as(input: TokenStream) -> TokenStream {
+    treat_as::register_treat_as_impl(input)
+}
+
+/// `#[proto_message]` types for every message and enum of an existing proto3 file.
+///
+/// ```rust,ignore
+/// mod shop {
+///     proto_rs::import_proto!("protos/external/shop.proto");
+/// }
+/// ```
+///
+/// The path is relative to the crate's `Cargo.toml`. Field tags, enum numbers and the package come
+/// from the file, which is never rewritten by `.proto` emission.
+#[proc_macro]
+pub fn import_proto(input: TokenStream) -> TokenStream {
+    import_proto::import_proto_impl(input)
+}
+
 #[proc_macro]
 pub fn impl_proto_ident(input: TokenStream) -> TokenStream {
     impl_proto_ident::impl_proto_ident(input)
@@ -16,9 +16,15 @@ use syn::LitStr;
 use syn::Type;
 use syn::parse::Parse;
 
+use crate::schema_check::schema_change_warnings;
+use crate::schema_check::should_check_schema;
+use crate::utils::ProtoOption;
+use crate::utils::derive_package_name;
 use crate::utils::parse_field_config;
+use crate::utils::push_proto_option;
 use crate::utils::rust_type_path_ident;
 use crate::utils::type_name_with_generics_for_path;
+use crate::write_file::is_external_file;
 use crate::write_file::register_and_emit_proto_inner;
 use crate::write_file::register_imports;
 use crate::write_file::register_package;
@@ -63,13 +69,45 @@ pub struct InterceptorConfig {
     //pub ctx_ident: syn::Ident,
 }
 
+/// Authentication scheme baked into generated clients and servers.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AuthScheme {
+    /// `authorization: Bearer <token>`, verified with `proto_rs::auth::BearerAuth`.
+    Bearer,
+    /// Headers handed to `proto_rs::auth::AuthProvider`.
+    Provider,
+}
+
+/// Capacity controls baked into the generated server.
+#[derive(Clone, Copy, Default)]
+pub struct ServerLimits {
+    pub concurrency: Option<usize>,
+    /// `(calls, window in milliseconds)`
+    pub rate: Option<(u64, u64)>,
+}
+
+impl ServerLimits {
+    pub fn is_empty(&self) -> bool {
+        self.concurrency.is_none() && self.rate.is_none()
+    }
+}
+
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Clone, Default)]
 pub struct UnifiedProtoConfig {
     pub proto_path: Option<String>,
     pub rpc_server: bool,
     pub rpc_client: bool,
+    /// `#[cfg]` predicate wrapped around the generated server module.
+    pub rpc_server_cfg: Option<String>,
+    /// `#[cfg]` predicate wrapped around the generated client module.
+    pub rpc_client_cfg: Option<String>,
     rpc_package: Option<String>,
+    proto_package: Option<String>,
+    rpc_version: Option<String>,
     pub rpc_client_ctx: Option<InterceptorConfig>,
+    pub auth: Option<AuthScheme>,
+    pub limits: ServerLimits,
     pub import_all_from: Option<String>,
     pub type_imports: BTreeMap<String, BTreeSet<String>>,
     file_imports: BTreeMap<String, BTreeSet<String>>,
@@ -77,18 +115,35 @@ pub struct UnifiedProtoConfig {
     pub suns: Vec<SunConfig>,
     pub sun_ir_types: Vec<Type>,
     pub transparent: bool,
+    // `string_enum` is a transparent enum whose unit variants travel as `string` names
+    pub string_enum: bool,
+    pub empty_wkt: bool,
+    pub explicit_tags: bool,
+    pub kv_key: bool,
+    pub nest_in: Option<String>,
+    // `range = "..."` and `unit = "..."` of a transparent newtype
+    pub range: Option<String>,
+    pub unit: Option<String>,
     pub validator: Option<String>,
     pub validator_with_ext: Option<String>,
+    pub response_validator_with_ext: Option<String>,
     pub generic_types: Vec<GenericTypeEntry>,
     pub item_generics: syn::Generics,
     pub item_attrs: Vec<Attribute>,
+    // item-level #[proto(option("pkg.name") = value)]
+    pub options: Vec<ProtoOption>,
+    // item-level #[proto(soa)]: a struct of `Vec` columns encoded as rows
+    pub soa: bool,
+    // `serde`: add serde derives through the `proto_rs::serde` re-export
+    pub serde: bool,
+    // `graphql`: add async-graphql derives through the `proto_rs::async_graphql` re-export
+    pub graphql: bool,
 }
 
 #[derive(Clone)]
 pub struct SunConfig {
     pub ty: Type,
     pub message_ident: String,
-    pub by_ref: bool,
     pub ir_ty: Option<Type>,
 }
 
@@ -111,9 +166,14 @@ impl UnifiedProtoConfig {
             if let Some(rpc_package) = self.rpc_package.as_deref() {
                 register_package(proto_path, rpc_package);
             }
+            let warnings = if should_check_schema() && !is_external_file(proto_path) {
+                schema_change_warnings(proto_path, content)
+            } else {
+                TokenStream2::new()
+            };
             register_and_emit_proto_inner(proto_path, content);
             let imports = &self.imports_mat;
-            self.imports_mat = quote::quote! { #imports };
+            self.imports_mat = quote::quote! { #imports #warnings };
         } else if self.transparent {
             let imports = &self.imports_mat;
             self.imports_mat = quote::quote! { #imports };
@@ -135,6 +195,8 @@ impl UnifiedProtoConfig {
             parse_attr_params(attr, &mut config);
         }
         config.apply_sun_ir();
+        config.apply_proto_package();
+        config.apply_rpc_version();
 
         config.item_generics = generics;
         config.item_attrs = item_attrs.to_vec();
@@ -142,7 +204,10 @@ impl UnifiedProtoConfig {
         let item_validators = extract_item_validators(item_attrs);
         config.validator = item_validators.validator;
         config.validator_with_ext = item_validators.validator_with_ext;
+        config.response_validator_with_ext = item_validators.response_validator_with_ext;
         config.generic_types = extract_item_generic_types(item_attrs);
+        config.options = extract_item_options(item_attrs);
+        config.soa = extract_item_soa(item_attrs);
 
         // Extract imports from item-level attributes
         let mut all_imports = extract_item_imports(item_attrs);
@@ -173,7 +238,7 @@ impl UnifiedProtoConfig {
 
     /// Get the RPC package name
     pub fn get_rpc_package(&self) -> &str {
-        self.rpc_package.as_ref().expect("RPC package name required: use rpc_package = \"name\"")
+        self.rpc_package.as_ref().expect("RPC package name required: use rpc_package = \"name\" or proto_package = \"name\"")
     }
 
     /// Get the RPC package name if set
@@ -211,6 +276,54 @@ fn parse_attr_params(attr: TokenStream, config: &mut UnifiedProtoConfig) {
         if meta.path.is_ident("transparent") {
             config.transparent = true;
             return Ok(());
+        } else if meta.path.is_ident("string_enum") {
+            config.transparent = true;
+            config.string_enum = true;
+            return Ok(());
+        } else if meta.path.is_ident("explicit_tags") {
+            config.explicit_tags = true;
+            return Ok(());
+        } else if meta.path.is_ident("kv_key") {
+            config.kv_key = true;
+            return Ok(());
+        } else if meta.path.is_ident("serde") {
+            config.serde = true;
+            return Ok(());
+        } else if meta.path.is_ident("graphql") {
+            config.graphql = true;
+            return Ok(());
+        } else if meta.path.is_ident("nest_in") {
+            let lit_str = meta.value()?.parse::<syn::LitStr>()?;
+            if syn::parse_str::<syn::Ident>(&lit_str.value()).is_err() {
+                return Err(syn::Error::new(
+                    lit_str.span(),
+                    "nest_in must name a message, e.g. nest_in = \"Parent\"",
+                ));
+            }
+            config.nest_in = Some(lit_str.value());
+        } else if meta.path.is_ident("range") {
+            let lit_str = meta.value()?.parse::<syn::LitStr>()?;
+            let is_bounded_range =
+                syn::parse_str::<syn::ExprRange>(&lit_str.value()).is_ok_and(|range| range.start.is_some() || range.end.is_some());
+            if !is_bounded_range {
+                return Err(syn::Error::new(
+                    lit_str.span(),
+                    "range must be a Rust range like \"1..=86_400_000\" or \"0..\"",
+                ));
+            }
+            config.range = Some(lit_str.value());
+        } else if meta.path.is_ident("unit") {
+            let lit_str = meta.value()?.parse::<syn::LitStr>()?;
+            if lit_str.value().trim().is_empty() || lit_str.value().contains('"') {
+                return Err(syn::Error::new(lit_str.span(), "unit must be a non-empty name like \"ms\""));
+            }
+            config.unit = Some(lit_str.value());
+        } else if meta.path.is_ident("empty") {
+            let lit_str = meta.value()?.parse::<syn::LitStr>()?;
+            if lit_str.value() != "wkt" {
+                return Err(syn::Error::new(lit_str.span(), "empty must be \"wkt\""));
+            }
+            config.empty_wkt = true;
         } else if meta.path.is_ident("proto_path") {
             if let Ok(lit_str) = meta.value()?.parse::<syn::LitStr>() {
                 config.proto_path = Some(lit_str.value());
@@ -256,14 +369,64 @@ fn parse_attr_params(attr: TokenStream, config: &mut UnifiedProtoConfig) {
             if let Ok(lit_bool) = meta.value()?.parse::<syn::LitBool>() {
                 config.rpc_client = lit_bool.value;
             }
+        } else if meta.path.is_ident("rpc_server_cfg") || meta.path.is_ident("rpc_client_cfg") {
+            let lit_str = meta.value()?.parse::<syn::LitStr>()?;
+            let predicate = lit_str.value();
+            if syn::parse_str::<syn::Meta>(&predicate).is_err() {
+                return Err(syn::Error::new(
+                    lit_str.span(),
+                    "expected a cfg predicate such as \"feature = \\\"server\\\"\"",
+                ));
+            }
+            if meta.path.is_ident("rpc_server_cfg") {
+                config.rpc_server_cfg = Some(predicate);
+            } else {
+                config.rpc_client_cfg = Some(predicate);
+            }
         } else if meta.path.is_ident("rpc_package")
             && let Ok(lit_str) = meta.value()?.parse::<syn::LitStr>()
         {
             config.rpc_package = Some(lit_str.value());
+        } else if meta.path.is_ident("proto_package") {
+            let lit_str = meta.value()?.parse::<syn::LitStr>()?;
+            let package = lit_str.value();
+            if package.split('.').any(|segment| syn::parse_str::<syn::Ident>(segment).is_err()) {
+                return Err(syn::Error::new(
+                    lit_str.span(),
+                    "proto_package must be a dotted package like \"acme.billing.v1\"",
+                ));
+            }
+            config.proto_package = Some(package);
+        } else if meta.path.is_ident("rpc_version") {
+            let lit_str = meta.value()?.parse::<syn::LitStr>()?;
+            let version = lit_str.value();
+            if version.is_empty() || !version.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_') {
+                return Err(syn::Error::new(lit_str.span(), "rpc_version must be a package segment like \"v1\""));
+            }
+            config.rpc_version = Some(version);
         } else if meta.path.is_ident("rpc_client_ctx") {
             if let Ok(lit_str) = meta.value()?.parse::<syn::LitStr>() {
                 config.rpc_client_ctx = parse_interceptor_config(&lit_str.value());
             }
+        } else if meta.path.is_ident("auth") {
+            let lit_str = meta.value()?.parse::<syn::LitStr>()?;
+            config.auth = Some(match lit_str.value().as_str() {
+                "bearer" => AuthScheme::Bearer,
+                "provider" => AuthScheme::Provider,
+                _ => return Err(syn::Error::new(lit_str.span(), "auth must be \"bearer\" or \"provider\"")),
+            });
+        } else if meta.path.is_ident("concurrency_limit") {
+            let lit_int = meta.value()?.parse::<syn::LitInt>()?;
+            let limit = lit_int.base10_parse::<usize>()?;
+            if limit == 0 {
+                return Err(syn::Error::new(lit_int.span(), "concurrency_limit must be greater than zero"));
+            }
+            config.limits.concurrency = Some(limit);
+        } else if meta.path.is_ident("rate_limit") {
+            let lit_str = meta.value()?.parse::<syn::LitStr>()?;
+            let rate = parse_rate_limit(&lit_str.value())
+                .ok_or_else(|| syn::Error::new(lit_str.span(), "rate_limit must look like \"1000/s\" (units: ms, s, m, h)"))?;
+            config.limits.rate = Some(rate);
         } else if meta.path.is_ident("proto_import_all_from") {
             if meta.input.peek(syn::token::Paren) {
                 let mut import_path = None;
@@ -301,6 +464,29 @@ fn parse_attr_params(attr: TokenStream, config: &mut UnifiedProtoConfig) {
     syn::parse::Parser::parse(parser, attr).expect("failed to parse proto_message attributes");
 }
 
+/// Parses `"<calls>/<window>"`, see [`parse_duration_millis`] for the window.
+fn parse_rate_limit(value: &str) -> Option<(u64, u64)> {
+    let (calls, window) = value.split_once('/')?;
+    let calls = calls.trim().parse::<u64>().ok().filter(|calls| *calls > 0)?;
+    Some((calls, parse_duration_millis(window)?))
+}
+
+/// Parses a non-zero duration written as an optional count followed by `ms`, `s`, `m` or `h`.
+pub fn parse_duration_millis(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let unit_start = value.find(|ch: char| !ch.is_ascii_digit())?;
+    let (count, unit) = value.split_at(unit_start);
+    let count = if count.is_empty() { 1 } else { count.parse::<u64>().ok()? };
+    let unit_millis = match unit {
+        "ms" => 1,
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        _ => return None,
+    };
+    count.checked_mul(unit_millis).filter(|millis| *millis > 0)
+}
+
 fn extract_type_ident(ty: &Type) -> Option<String> {
     match ty {
         Type::Path(path) => path.path.segments.last().map(|segment| segment.ident.to_string()),
@@ -347,12 +533,6 @@ impl UnifiedProtoConfig {
             substitutions: BTreeMap::new(),
         }];
 
-        // Keep the base variant for Rust client code generation
-        let base_variant = GenericTypeVariant {
-            suffix: String::new(),
-            substitutions: BTreeMap::new(),
-        };
-
         for param in type_params {
             let Some(types) = generic_map.get(&param.to_string()) else {
                 return Err(syn::Error::new_spanned(
@@ -380,21 +560,32 @@ impl UnifiedProtoConfig {
             variants = next_variants;
         }
 
-        // Add the base generic variant at the beginning for Rust client generation
-        // This ensures we have a schema for Envelope<T>, not just concrete variants
-        variants.insert(0, base_variant);
+        Ok(variants)
+    }
 
+    /// Variants to emit schemas for: the concrete combinations, preceded by the
+    /// unsubstituted base variant when `generic_types` is set.
+    pub fn schema_variants(&self, generics: &syn::Generics) -> Result<Vec<GenericTypeVariant>, syn::Error> {
+        let mut variants = self.generic_type_variants(generics)?;
+        if !self.generic_types.is_empty() {
+            // Keep the base variant for Rust client generation, so Envelope<T> gets a schema too
+            variants.insert(
+                0,
+                GenericTypeVariant {
+                    suffix: String::new(),
+                    substitutions: BTreeMap::new(),
+                },
+            );
+        }
         Ok(variants)
     }
 
     fn push_sun(&mut self, ty: Type) {
-        let by_ref = is_reference_sun(&ty);
         let ty = normalize_sun_type(ty);
         let message_ident = extract_type_ident(&ty).expect("sun attribute expects a type path");
         self.suns.push(SunConfig {
             ty,
             message_ident,
-            by_ref,
             ir_ty: None,
         });
     }
@@ -425,6 +616,46 @@ impl UnifiedProtoConfig {
             sun.ir_ty = Some(ir_ty.clone());
         }
     }
+
+    /// Use `proto_package` as the package of the file and, for services, as the RPC package.
+    fn apply_proto_package(&mut self) {
+        let Some(package) = self.proto_package.as_deref() else {
+            return;
+        };
+        if let Some(rpc_package) = self.rpc_package.as_deref() {
+            assert!(
+                rpc_package == package,
+                "proto_package = \"{package}\" conflicts with rpc_package = \"{rpc_package}\""
+            );
+        }
+        self.rpc_package = Some(package.to_string());
+    }
+
+    /// Fold `rpc_version` into the package (`pkg.v1`) and the proto path (`dir/v1/file.proto`).
+    fn apply_rpc_version(&mut self) {
+        let Some(version) = self.rpc_version.as_deref() else {
+            return;
+        };
+
+        let base_package = self.rpc_package.clone().or_else(|| {
+            self.proto_path.as_deref().map(|path| {
+                let file_name = std::path::Path::new(path).file_name().and_then(|name| name.to_str()).unwrap_or(path);
+                derive_package_name(file_name)
+            })
+        });
+        let base_package = base_package.expect("rpc_version requires rpc_package or proto_path");
+        self.rpc_package = Some(format!("{base_package}.{version}"));
+
+        if let Some(path) = self.proto_path.as_deref() {
+            let path = std::path::Path::new(path);
+            let file_name = path.file_name().and_then(|name| name.to_str()).expect("proto_path must name a file");
+            let versioned = match path.parent().and_then(|parent| parent.to_str()).filter(|parent| !parent.is_empty()) {
+                Some(parent) => format!("{parent}/{version}/{file_name}"),
+                None => format!("{version}/{file_name}"),
+            };
+            self.proto_path = Some(versioned);
+        }
+    }
 }
 
 fn normalize_sun_type(ty: Type) -> Type {
@@ -436,18 +667,10 @@ fn normalize_sun_type(ty: Type) -> Type {
     }
 }
 
-fn is_reference_sun(ty: &Type) -> bool {
-    match ty {
-        Type::Reference(_) => true,
-        Type::Group(group) => is_reference_sun(&group.elem),
-        Type::Paren(paren) => is_reference_sun(&paren.elem),
-        _ => false,
-    }
-}
-
 pub struct ItemValidators {
     pub validator: Option<String>,
     pub validator_with_ext: Option<String>,
+    pub response_validator_with_ext: Option<String>,
 }
 
 /// Extract validators from item-level #[proto(...)] attributes
@@ -455,6 +678,7 @@ pub fn extract_item_validators(item_attrs: &[Attribute]) -> ItemValidators {
     let mut validators = ItemValidators {
         validator: None,
         validator_with_ext: None,
+        response_validator_with_ext: None,
     };
 
     for attr in item_attrs {
@@ -463,56 +687,43 @@ pub fn extract_item_validators(item_attrs: &[Attribute]) -> ItemValidators {
         }
 
         attr.parse_nested_meta(|meta| {
-            if meta.path.is_ident("validator") {
+            let slot = if meta.path.is_ident("validator") {
+                &mut validators.validator
+            } else if meta.path.is_ident("validator_with_ext") {
+                &mut validators.validator_with_ext
+            } else if meta.path.is_ident("response_validator_with_ext") {
+                &mut validators.response_validator_with_ext
+            } else if meta.path.is_ident("generic_types") {
                 let value_parser = meta.value()?;
-
-                // Try parsing as Expr which can be either a Lit or a Path
-                if let Ok(expr) = value_parser.parse::<syn::Expr>() {
-                    match expr {
-                        // Handle string literals: validator = "validate_fn"
-                        syn::Expr::Lit(expr_lit) => {
-                            if let syn::Lit::Str(s) = expr_lit.lit {
-                                validators.validator = Some(s.value());
-                            }
-                        }
-                        // Handle paths: validator = validate_fn
-                        syn::Expr::Path(expr_path) => {
-                            let path_str = expr_path.path.segments.iter().map(|seg| seg.ident.to_string()).collect::<Vec<_>>().join("::");
-                            validators.validator = Some(path_str);
-                        }
-                        _ => {}
-                    }
-                }
+                let _: Expr = value_parser.parse()?;
                 return Ok(());
-            }
-
-            if meta.path.is_ident("validator_with_ext") {
-                let value_parser = meta.value()?;
+            } else if meta.path.is_ident("option") {
+                // collected by `extract_item_options`
+                return push_proto_option(&mut Vec::new(), &meta);
+            } else if meta.path.is_ident("soa") {
+                // read by `extract_item_soa`
+                return Ok(());
+            } else {
+                return Err(meta.error("unknown #[proto(...)] attribute"));
+            };
 
-                if let Ok(expr) = value_parser.parse::<syn::Expr>() {
-                    match expr {
-                        syn::Expr::Lit(expr_lit) => {
-                            if let syn::Lit::Str(s) = expr_lit.lit {
-                                validators.validator_with_ext = Some(s.value());
-                            }
-                        }
-                        syn::Expr::Path(expr_path) => {
-                            let path_str = expr_path.path.segments.iter().map(|seg| seg.ident.to_string()).collect::<Vec<_>>().join("::");
-                            validators.validator_with_ext = Some(path_str);
+            // Accept both `validator = validate_fn` and `validator = "validate_fn"`
+            let value_parser = meta.value()?;
+            if let Ok(expr) = value_parser.parse::<syn::Expr>() {
+                match expr {
+                    syn::Expr::Lit(expr_lit) => {
+                        if let syn::Lit::Str(s) = expr_lit.lit {
+                            *slot = Some(s.value());
                         }
-                        _ => {}
                     }
+                    syn::Expr::Path(expr_path) => {
+                        let path_str = expr_path.path.segments.iter().map(|seg| seg.ident.to_string()).collect::<Vec<_>>().join("::");
+                        *slot = Some(path_str);
+                    }
+                    _ => {}
                 }
-                return Ok(());
             }
-
-            if meta.path.is_ident("generic_types") {
-                let value_parser = meta.value()?;
-                let _: Expr = value_parser.parse()?;
-                return Ok(());
-            }
-
-            Err(meta.error("unknown #[proto(...)] attribute"))
+            Ok(())
         })
         .expect("failed to parse #[proto(...)] attributes");
     }
@@ -529,6 +740,9 @@ pub fn extract_item_generic_types(item_attrs: &[Attribute]) -> Vec<GenericTypeEn
         }
 
         let result = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("option") {
+                return push_proto_option(&mut Vec::new(), &meta);
+            }
             if !meta.path.is_ident("generic_types") {
                 if meta.input.peek(syn::Token![=]) {
                     let value = meta.value()?;
@@ -579,6 +793,53 @@ pub fn extract_item_generic_types(item_attrs: &[Attribute]) -> Vec<GenericTypeEn
     entries
 }
 
+/// Extract custom options from item-level `#[proto(option("pkg.name") = value)]` attributes
+pub fn extract_item_options(item_attrs: &[Attribute]) -> Vec<ProtoOption> {
+    let mut options = Vec::new();
+
+    for attr in item_attrs {
+        if !attr.path().is_ident("proto") {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("option") {
+                return push_proto_option(&mut options, &meta);
+            }
+            if meta.path.is_ident("soa") {
+                return Ok(());
+            }
+            // Other keys are parsed by `extract_item_validators` and `extract_item_generic_types`
+            let _: Expr = meta.value()?.parse()?;
+            Ok(())
+        })
+        .expect("failed to parse #[proto(option(...))] attributes");
+    }
+
+    options
+}
+
+/// Whether the item carries `#[proto(soa)]`
+pub fn extract_item_soa(item_attrs: &[Attribute]) -> bool {
+    let mut soa = false;
+    for attr in item_attrs {
+        if !attr.path().is_ident("proto") {
+            continue;
+        }
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("soa") {
+                soa = true;
+            } else if meta.path.is_ident("option") {
+                return push_proto_option(&mut Vec::new(), &meta);
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        });
+    }
+    soa
+}
+
 /// Extract `proto_imports` from item attributes
 pub fn extract_item_imports(item_attrs: &[Attribute]) -> BTreeMap<String, BTreeSet<String>> {
     let mut imports = BTreeMap::new();
@@ -659,17 +920,19 @@ fn path_to_proto_package(path: &syn::Path) -> String {
 }
 
 fn extract_field_type_name(ty: &syn::Type) -> String {
-    // Handle Option<T> and Vec<T>
+    // Handle Option<T>, Vec<T> and pointer wrappers
     if let syn::Type::Path(type_path) = ty
         && let Some(segment) = type_path.path.segments.last()
     {
         let ident = &segment.ident;
 
-        if (ident == "Option" || ident == "Vec" || ident == "ArcSwap" || ident == "ArcSwapOption")
-            && let syn::PathArguments::AngleBracketed(args) = &segment.arguments
+        if matches!(
+            ident.to_string().as_str(),
+            "Option" | "Vec" | "Arc" | "Weak" | "ArcSwap" | "ArcSwapOption" | "ArcSwapAny"
+        ) && let syn::PathArguments::AngleBracketed(args) = &segment.arguments
             && let Some(syn::GenericArgument::Type(inner_ty)) = args.args.first()
         {
-            return rust_type_path_ident(inner_ty).to_string();
+            return extract_field_type_name(inner_ty);
         }
 
         if matches!(ident.to_string().as_str(), "HashMap" | "BTreeMap")
@@ -684,7 +947,7 @@ fn extract_field_type_name(ty: &syn::Type) -> String {
             return rust_type_path_ident(&value_ty).to_string();
         }
 
-        if matches!(ident.to_string().as_str(), "HashSet" | "BTreeSet")
+        if matches!(ident.to_string().as_str(), "HashSet" | "BTreeSet" | "BinaryHeap" | "LinkedList")
             && let syn::PathArguments::AngleBracketed(args) = &segment.arguments
             && let Some(syn::GenericArgument::Type(inner_ty)) = args.args.first()
         {
@@ -738,6 +1001,12 @@ mod tests {
 
         let ty: syn::Type = parse_quote! { arc_swap::ArcSwapOption<MyType> };
         assert_eq!(extract_field_type_name(&ty), "MyType");
+
+        let ty: syn::Type = parse_quote! { arc_swap::ArcSwapAny<Option<Arc<MyType>>, MyStrategy> };
+        assert_eq!(extract_field_type_name(&ty), "MyType");
+
+        let ty: syn::Type = parse_quote! { std::sync::Weak<MyType> };
+        assert_eq!(extract_field_type_name(&ty), "MyType");
     }
 
     #[test]
@@ -746,9 +1015,45 @@ mod tests {
         assert_eq!(config.proto_path(), None);
         assert!(!config.rpc_server);
         assert!(!config.rpc_client);
+        assert!(config.rpc_server_cfg.is_none());
+        assert!(config.rpc_client_cfg.is_none());
         assert!(!config.transparent);
     }
 
+    #[test]
+    fn rpc_version_folds_into_package_and_path() {
+        let mut config = UnifiedProtoConfig {
+            proto_path: Some("protos/sigma_rpc.proto".to_string()),
+            rpc_package: Some("sigma_rpc".to_string()),
+            rpc_version: Some("v1".to_string()),
+            ..Default::default()
+        };
+        config.apply_rpc_version();
+        assert_eq!(config.get_rpc_package(), "sigma_rpc.v1");
+        assert_eq!(config.proto_path(), Some("protos/v1/sigma_rpc.proto"));
+
+        let mut config = UnifiedProtoConfig {
+            proto_path: Some("sigma_rpc.proto".to_string()),
+            rpc_version: Some("v2".to_string()),
+            ..Default::default()
+        };
+        config.apply_rpc_version();
+        assert_eq!(config.get_rpc_package(), "sigma_rpc.v2");
+        assert_eq!(config.proto_path(), Some("v2/sigma_rpc.proto"));
+    }
+
+    #[test]
+    fn parses_rate_limit_windows() {
+        assert_eq!(parse_rate_limit("1000/s"), Some((1000, 1_000)));
+        assert_eq!(parse_rate_limit("5 / 10s"), Some((5, 10_000)));
+        assert_eq!(parse_rate_limit("60/m"), Some((60, 60_000)));
+        assert_eq!(parse_rate_limit("2/250ms"), Some((2, 250)));
+        assert_eq!(parse_rate_limit("1000"), None);
+        assert_eq!(parse_rate_limit("0/s"), None);
+        assert_eq!(parse_rate_limit("10/0s"), None);
+        assert_eq!(parse_rate_limit("10/day"), None);
+    }
+
     #[test]
     fn parses_owned_sun_type() {
         let ty: Type = parse_quote!(OwnedType);
@@ -13,6 +13,7 @@ use crate::emit_proto::generate_struct_proto;
 use crate::generic_substitutions::apply_generic_substitutions_enum;
 use crate::generic_substitutions::apply_generic_substitutions_fields;
 use crate::parse::UnifiedProtoConfig;
+use crate::proto_rpc::rpc_common::generate_proto_service_name;
 use crate::proto_rpc::utils::extract_methods_and_types;
 use crate::schema::SchemaTokens;
 use crate::schema::schema_tokens_for_complex_enum;
@@ -40,7 +41,7 @@ fn struct_or_enum(mut input: DeriveInput, mut config: UnifiedProtoConfig) -> Tok
     let clean_name = proto_name.strip_suffix("Proto").unwrap_or(&proto_name);
 
     let generic_params: Vec<syn::Ident> = input.generics.type_params().map(|param| param.ident.clone()).collect();
-    let generic_variants = match config.generic_type_variants(&input.generics) {
+    let generic_variants = match config.schema_variants(&input.generics) {
         Ok(variants) => variants,
         Err(err) => return err.to_compile_error().into(),
     };
@@ -131,9 +132,16 @@ fn struct_or_enum(mut input: DeriveInput, mut config: UnifiedProtoConfig) -> Tok
 fn trait_service(mut input: ItemTrait, mut config: UnifiedProtoConfig) -> TokenStream {
     let proto_name = input.ident.to_string();
     let clean_name = proto_name.strip_suffix("Proto").unwrap_or(&proto_name);
-    let (methods, _) = extract_methods_and_types(&input);
-    let proto_def = generate_service_content(&input.ident, &methods, &config.type_imports, config.import_all_from.as_deref());
+    let (methods, _) = match extract_methods_and_types(&input) {
+        Ok(extracted) => extracted,
+        Err(err) => return err.to_compile_error().into(),
+    };
     let rpc_package = config.get_rpc_package();
+    let service_name = match generate_proto_service_name(rpc_package, &input.ident, &methods) {
+        Ok(name) => name,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let proto_def = generate_service_content(&service_name, &methods, &config.type_imports, config.import_all_from.as_deref());
     let schema_tokens = schema_tokens_for_service(&input.ident, clean_name, &methods, rpc_package, &config, clean_name);
     config.register_and_emit_proto(&proto_def);
     strip_proto_attributes_from_trait(&mut input);
@@ -11,14 +11,19 @@ use syn::spanned::Spanned;
 
 use super::build_validate_with_ext_impl;
 use super::generic_bounds::add_proto_wire_bounds;
+use super::structs::add_transparent_bounds;
+use super::structs::generate_transparent_impl;
 use super::unified_field_handler::FieldAccess;
 use super::unified_field_handler::FieldInfo;
 use super::unified_field_handler::assign_tags;
 use super::unified_field_handler::compute_decode_ty;
 use super::unified_field_handler::compute_proto_ty;
 use super::unified_field_handler::decode_conversion_assign;
+use super::unified_field_handler::direct_merge_stmt;
+use super::unified_field_handler::direct_shadow_ty;
 use super::unified_field_handler::encode_conversion_expr;
 use super::unified_field_handler::field_proto_default_expr;
+use super::unified_field_handler::in_heat_order;
 use super::unified_field_handler::needs_decode_conversion;
 use super::unified_field_handler::needs_encode_conversion;
 use super::unified_field_handler::parse_path_string;
@@ -28,6 +33,59 @@ use crate::utils::parse_field_config;
 use crate::utils::parse_field_type;
 use crate::utils::resolved_field_type;
 
+/// Variant and field of an enum with a single one-field variant, the enums `transparent` applies to.
+pub(super) fn transparent_enum_field(data: &syn::DataEnum) -> Option<(&syn::Variant, &syn::Field)> {
+    let [variant] = data.variants.iter().collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    match &variant.fields {
+        syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Some((variant, &fields.unnamed[0])),
+        syn::Fields::Named(fields) if fields.named.len() == 1 => Some((variant, &fields.named[0])),
+        _ => None,
+    }
+}
+
+/// `#[proto_message(transparent)]` on an enum: its single variant is encoded as the field it holds.
+pub(super) fn generate_transparent_enum_impl(
+    input: &DeriveInput,
+    item_enum: &ItemEnum,
+    variant: &syn::Variant,
+    field: &syn::Field,
+) -> TokenStream2 {
+    let enum_item = sanitize_enum(item_enum.clone());
+    let name = &input.ident;
+    let variant_ident = &variant.ident;
+    let bounded_generics = add_transparent_bounds(&input.generics, &field.ty);
+    let (impl_generics, ty_generics, where_clause) = bounded_generics.split_for_impl();
+
+    let wrap = |inner: TokenStream2| {
+        if let Some(ident) = &field.ident {
+            quote! { #name::#variant_ident { #ident: #inner } }
+        } else {
+            quote! { #name::#variant_ident(#inner) }
+        }
+    };
+    let pattern = wrap(quote! { inner });
+    let access = |base: TokenStream2| quote! { (*match #base { #pattern => inner }) };
+    let transparent_impl = generate_transparent_impl(
+        name,
+        &item_enum.vis,
+        &bounded_generics,
+        &impl_generics,
+        &ty_generics,
+        where_clause,
+        &field.ty,
+        &access,
+        &wrap,
+        None,
+    );
+
+    quote! {
+        #enum_item
+        #transparent_impl
+    }
+}
+
 pub(super) fn generate_complex_enum_impl(
     input: &DeriveInput,
     item_enum: &ItemEnum,
@@ -60,6 +118,7 @@ pub(super) fn generate_complex_enum_impl(
     let default_expr = build_variant_default_expr(&variants[default_index], name);
     let is_default_arms = variants.iter().map(|variant| build_variant_is_default_arm(variant, name)).collect::<Vec<_>>();
     let encode_arms = variants.iter().map(|variant| build_variant_encode_arm(variant, name)).collect::<Vec<_>>();
+    let size_hint_arms = variants.iter().map(|variant| build_variant_size_hint_arm(variant, name)).collect::<Vec<_>>();
 
     let validate_with_ext_impl = build_validate_with_ext_impl(config);
     let validate_with_ext_proto_impl = if config.has_suns() {
@@ -231,6 +290,14 @@ pub(super) fn generate_complex_enum_impl(
                     ::proto_rs::ArchivedProtoField::<TAG, Self>::put_key(w);
                 }
             }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                let payload = match *self {
+                    #(#size_hint_arms,)*
+                };
+                Some(::proto_rs::ArchivedProtoField::<TAG, Self>::hint_delimited(payload))
+            }
         }
 
         impl #impl_generics ::proto_rs::ProtoArchive for #name #ty_generics #where_clause {
@@ -243,6 +310,11 @@ pub(super) fn generate_complex_enum_impl(
             fn archive<const TAG: u32>(&self, w: &mut impl ::proto_rs::RevWriter) {
                 <&Self as ::proto_rs::ProtoArchive>::archive::<TAG>(&self, w)
             }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                <&Self as ::proto_rs::ProtoArchive>::size_hint::<TAG>(&self)
+            }
         }
 
         impl #impl_generics ::proto_rs::ProtoEncode for #name #ty_generics #where_clause {
@@ -300,6 +372,9 @@ fn collect_variant_infos<'a>(data: &'a syn::DataEnum, _config: &'a UnifiedProtoC
 
                 let field = &fields.unnamed[0];
                 let config = parse_field_config(field);
+                if config.proto_name.is_some() {
+                    return Err(syn::Error::new(field.span(), "#[proto(name = ...)] only names tuple struct fields"));
+                }
                 let effective_ty = resolved_field_type(field, &config);
                 let parsed = parse_field_type(&effective_ty);
                 let proto_ty = compute_proto_ty(field, &config, &parsed, &effective_ty);
@@ -393,7 +468,8 @@ fn collect_variant_fields<'a>(variants: &'a [VariantInfo<'a>]) -> Vec<&'a FieldI
     fields
 }
 
-fn resolve_variant_tag(variant: &syn::Variant, default: usize) -> syn::Result<u32> {
+/// Tag written with `#[proto(tag = N)]` on the variant itself
+pub(super) fn variant_custom_tag(variant: &syn::Variant) -> syn::Result<Option<usize>> {
     let mut custom_tag = None;
 
     for attr in &variant.attrs {
@@ -425,7 +501,11 @@ fn resolve_variant_tag(variant: &syn::Variant, default: usize) -> syn::Result<u3
         })?;
     }
 
-    let tag = custom_tag.unwrap_or(default);
+    Ok(custom_tag)
+}
+
+fn resolve_variant_tag(variant: &syn::Variant, default: usize) -> syn::Result<u32> {
+    let tag = variant_custom_tag(variant)?.unwrap_or(default);
     if tag == 0 {
         return Err(syn::Error::new(
             variant.ident.span(),
@@ -504,7 +584,7 @@ fn build_variant_is_default_arm(variant: &VariantInfo<'_>, enum_ident: &Ident) -
                     let converted = encode_conversion_expr(&field.field, &ref_expr);
                     quote! { ::proto_rs::ProtoArchive::is_default(&#converted) }
                 } else {
-                    let shadow_ty = quote! { <#field_ty as ::proto_rs::ProtoEncode>::Shadow<'_> };
+                    let shadow_ty = direct_shadow_ty(&field.field, &quote! { '_ });
                     quote! {
                         {
                             let shadow = <#shadow_ty as ::proto_rs::ProtoShadowEncode<'_, #field_ty>>::from_sun(#ref_expr);
@@ -536,7 +616,7 @@ fn build_variant_is_default_arm(variant: &VariantInfo<'_>, enum_ident: &Ident) -
                         let converted = encode_conversion_expr(info, &ref_expr);
                         quote! { ::proto_rs::ProtoArchive::is_default(&#converted) }
                     } else {
-                        let shadow_ty = quote! { <#field_ty as ::proto_rs::ProtoEncode>::Shadow<'_> };
+                        let shadow_ty = direct_shadow_ty(info, &quote! { '_ });
                         quote! {
                             {
                                 let shadow = <#shadow_ty as ::proto_rs::ProtoShadowEncode<'_, #field_ty>>::from_sun(#ref_expr);
@@ -581,7 +661,7 @@ fn build_variant_encode_arm(variant: &VariantInfo<'_>, enum_ident: &Ident) -> To
                 let proto_ty = &field.field.proto_ty;
                 quote! { #proto_ty }
             } else {
-                quote! { <#field_ty as ::proto_rs::ProtoEncode>::Shadow<'_> }
+                direct_shadow_ty(&field.field, &quote! { '_ })
             };
             let shadow_expr = if needs_encode_conversion(&field.field.config, &field.field.parsed) {
                 encode_conversion_expr(&field.field, &ref_expr)
@@ -598,7 +678,7 @@ fn build_variant_encode_arm(variant: &VariantInfo<'_>, enum_ident: &Ident) -> To
         }
         VariantKind::Struct { fields } => {
             let bindings = build_struct_field_bindings(fields);
-            let field_encodes = fields.iter().rev().filter_map(|info| {
+            let field_encodes = in_heat_order(fields).into_iter().rev().filter_map(|info| {
                 if info.config.skip {
                     return None;
                 }
@@ -610,7 +690,7 @@ fn build_variant_encode_arm(variant: &VariantInfo<'_>, enum_ident: &Ident) -> To
                     let proto_ty = &info.proto_ty;
                     quote! { #proto_ty }
                 } else {
-                    quote! { <#field_ty as ::proto_rs::ProtoEncode>::Shadow<'_> }
+                    direct_shadow_ty(info, &quote! { '_ })
                 };
                 let shadow_expr = if needs_encode_conversion(&info.config, &info.parsed) {
                     encode_conversion_expr(info, &ref_expr)
@@ -639,6 +719,56 @@ fn build_variant_encode_arm(variant: &VariantInfo<'_>, enum_ident: &Ident) -> To
     }
 }
 
+// Variants whose fields go through a user conversion give no hint: converting is not cheap.
+fn build_variant_size_hint_arm(variant: &VariantInfo<'_>, enum_ident: &Ident) -> TokenStream2 {
+    let ident = variant.ident;
+    let tag = variant.tag;
+    match &variant.kind {
+        VariantKind::Unit => quote! {
+            #enum_ident::#ident => ::proto_rs::ArchivedProtoField::<#tag, ()>::hint_delimited(0)
+        },
+        VariantKind::Tuple { field } => {
+            let binding_ident = &field.binding_ident;
+            if field.field.config.skip {
+                return quote! { #enum_ident::#ident(..) => 0 };
+            }
+            if needs_encode_conversion(&field.field.config, &field.field.parsed) {
+                return quote! { #enum_ident::#ident(..) => return None };
+            }
+            let field_ty = &field.field.field.ty;
+            let shadow_ty = direct_shadow_ty(&field.field, &quote! { '_ });
+            quote! {
+                #enum_ident::#ident(#binding_ident) => {
+                    let __proto_rs_shadow = <#shadow_ty as ::proto_rs::ProtoShadowEncode<'_, #field_ty>>::from_sun(#binding_ident);
+                    <#shadow_ty as ::proto_rs::ProtoArchive>::size_hint::<#tag>(&__proto_rs_shadow)?
+                }
+            }
+        }
+        VariantKind::Struct { fields } => {
+            if fields.iter().any(|info| !info.config.skip && needs_encode_conversion(&info.config, &info.parsed)) {
+                return quote! { #enum_ident::#ident { .. } => return None };
+            }
+            let bindings = build_struct_field_bindings(fields);
+            let field_hints = fields.iter().filter(|info| !info.config.skip).map(|info| {
+                let field_ident = info.field.ident.as_ref().expect("named field");
+                let field_tag = info.tag.expect("tag required");
+                let field_ty = &info.field.ty;
+                let shadow_ty = direct_shadow_ty(info, &quote! { '_ });
+                quote! {
+                    + ::proto_rs::ArchivedProtoField::<#field_tag, #shadow_ty>::size_hint(
+                        &<#shadow_ty as ::proto_rs::ProtoShadowEncode<'_, #field_ty>>::from_sun(#field_ident),
+                    )?
+                }
+            });
+            quote! {
+                #enum_ident::#ident { #(#bindings),* } => {
+                    ::proto_rs::ArchivedProtoField::<#tag, ()>::hint_delimited(0 #(#field_hints)*)
+                }
+            }
+        }
+    }
+}
+
 fn build_variant_merge_arm(name: &Ident, variant: &VariantInfo<'_>) -> TokenStream2 {
     let ident = variant.ident;
     let tag = variant.tag;
@@ -683,10 +813,7 @@ fn build_variant_merge_arm(name: &Ident, variant: &VariantInfo<'_>) -> TokenStre
                     #assign
                 }
             } else {
-                let ty = &field.field.field.ty;
-                quote! {
-                    <#ty as ::proto_rs::ProtoFieldMerge>::merge_value(&mut #binding_ident, wire_type, buf, ctx)?;
-                }
+                direct_merge_stmt(&field.field, &quote! { #binding_ident }, &quote! { wire_type }, &quote! { ctx })
             };
 
             let post_hook = if field.field.config.skip {
@@ -735,8 +862,8 @@ fn build_variant_merge_arm(name: &Ident, variant: &VariantInfo<'_>) -> TokenStre
                 let init = field_proto_default_expr(info);
                 quote! { let mut #field_ident = #init; }
             });
-            let decode_match = fields
-                .iter()
+            let decode_match = in_heat_order(fields)
+                .into_iter()
                 .filter_map(|info| {
                     let field_tag = info.tag?;
                     let field_ident = info.field.ident.as_ref().expect("named field");
@@ -753,10 +880,10 @@ fn build_variant_merge_arm(name: &Ident, variant: &VariantInfo<'_>) -> TokenStre
                             }
                         })
                     } else {
-                        let ty = &info.field.ty;
+                        let merge = direct_merge_stmt(info, &quote! { #field_ident }, &quote! { field_wire_type }, &quote! { inner_ctx });
                         Some(quote! {
                             #field_tag => {
-                                <#ty as ::proto_rs::ProtoFieldMerge>::merge_value(&mut #field_ident, field_wire_type, buf, inner_ctx)?;
+                                #merge
                             }
                         })
                     }
@@ -0,0 +1,147 @@
+//! Fields that only exist under a `cfg` predicate, written `#[proto(cfg = "...")]` or as a plain
+//! `#[cfg(...)]` on the field.
+//!
+//! The macro sees every field whatever the enabled features, so a conditional field keeps its tag
+//! in every build and is described as `optional` in the schema. Code touching it is gated on the
+//! predicate; builds without it skip the field's tag on decode like any unknown field.
+//! `#[cfg_attr(..., proto(...))]` would change tags or types per feature set, so it is rejected.
+
+use syn::Data;
+use syn::Meta;
+use syn::Token;
+use syn::punctuated::Punctuated;
+
+use crate::parse::UnifiedProtoConfig;
+use crate::utils::parse_field_config;
+
+pub fn check_conditional_fields(data: &Data, config: &UnifiedProtoConfig) -> syn::Result<()> {
+    let mut errors = Vec::new();
+    match data {
+        Data::Struct(data) => {
+            for field in &data.fields {
+                conditional_proto_attrs(field, &mut errors);
+                if parse_field_config(field).cfg_predicate().is_none() {
+                    continue;
+                }
+                let unsupported = if field.ident.is_none() {
+                    Some("conditional fields are only supported on structs with named fields")
+                } else if config.transparent {
+                    Some("conditional fields are not supported on transparent types")
+                } else if config.has_suns() {
+                    Some("conditional fields are not supported on types with `sun`")
+                } else {
+                    None
+                };
+                if let Some(message) = unsupported {
+                    errors.push(syn::Error::new_spanned(field, message));
+                }
+            }
+        }
+        Data::Enum(data) => {
+            for variant in &data.variants {
+                for field in &variant.fields {
+                    conditional_proto_attrs(field, &mut errors);
+                    if parse_field_config(field).cfg_predicate().is_some() {
+                        errors.push(syn::Error::new_spanned(
+                            field,
+                            "conditional fields are not supported in enum variants",
+                        ));
+                    }
+                }
+            }
+        }
+        Data::Union(_) => {}
+    }
+
+    let mut errors = errors.into_iter();
+    match errors.next() {
+        Some(mut first) => {
+            first.extend(errors);
+            Err(first)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Reports `#[cfg_attr(predicate, proto(...))]`, which would give the message a different wire
+/// format in each feature set.
+fn conditional_proto_attrs(field: &syn::Field, errors: &mut Vec<syn::Error>) {
+    for attr in &field.attrs {
+        if !attr.path().is_ident("cfg_attr") {
+            continue;
+        }
+        let Ok(args) = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) else {
+            continue;
+        };
+        if args.iter().skip(1).any(|meta| meta.path().is_ident("proto")) {
+            errors.push(syn::Error::new_spanned(
+                attr,
+                "#[proto(...)] under cfg_attr changes the wire format per feature set; use #[proto(cfg = \"...\")] to make the field conditional",
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::DeriveInput;
+    use syn::parse_quote;
+
+    use super::*;
+
+    fn messages(input: &DeriveInput) -> Vec<String> {
+        check_conditional_fields(&input.data, &UnifiedProtoConfig::default())
+            .err()
+            .map_or_else(Vec::new, |err| err.into_iter().map(|err| err.to_string()).collect())
+    }
+
+    #[test]
+    fn accepts_conditional_named_fields() {
+        let input: DeriveInput = parse_quote! {
+            struct Profile {
+                id: u64,
+                #[cfg(feature = "audit")]
+                audit: u32,
+                #[proto(cfg = "feature = \"audit\"")]
+                region: String,
+            }
+        };
+        assert!(messages(&input).is_empty());
+    }
+
+    #[test]
+    fn rejects_proto_options_under_cfg_attr() {
+        let input: DeriveInput = parse_quote! {
+            struct Profile {
+                #[cfg_attr(feature = "audit", proto(skip))]
+                audit: u32,
+                #[cfg_attr(feature = "serde", serde(default))]
+                region: String,
+            }
+        };
+        let messages = messages(&input);
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].contains("use #[proto(cfg = \"...\")]"), "{messages:?}");
+    }
+
+    #[test]
+    fn rejects_conditional_fields_outside_named_structs() {
+        let tuple: DeriveInput = parse_quote! {
+            struct Pair(u64, #[cfg(feature = "audit")] u32);
+        };
+        assert_eq!(
+            messages(&tuple),
+            ["conditional fields are only supported on structs with named fields"]
+        );
+
+        let variant: DeriveInput = parse_quote! {
+            enum Event {
+                Login {
+                    #[proto(cfg = "feature = \"audit\"")]
+                    ip: String,
+                },
+            }
+        };
+        assert_eq!(messages(&variant), ["conditional fields are not supported in enum variants"]);
+    }
+}
@@ -9,7 +9,9 @@ use syn::spanned::Spanned;
 use super::build_validate_with_ext_impl;
 use super::unified_field_handler::sanitize_enum;
 use crate::parse::UnifiedProtoConfig;
-use crate::utils::collect_discriminants_for_variants;
+use crate::utils::EnumRepr;
+use crate::utils::collect_repr_discriminants;
+use crate::utils::enum_repr;
 use crate::utils::find_marked_default_variant;
 
 pub(super) fn generate_simple_enum_impl(
@@ -24,8 +26,14 @@ pub(super) fn generate_simple_enum_impl(
     let generics = &input.generics;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
+    let repr = match enum_repr(&input.attrs) {
+        Ok(repr) => repr,
+        Err(err) => return err.to_compile_error(),
+    };
+    let raw_ty = repr.ident();
+
     let ordered_variants: Vec<&syn::Variant> = (0..data.variants.len()).map(|idx| &data.variants[idx]).collect();
-    let mut discriminants = match collect_discriminants_for_variants(&ordered_variants) {
+    let mut discriminants = match collect_repr_discriminants(&ordered_variants, repr) {
         Ok(values) => values,
         Err(err) => return err.to_compile_error(),
     };
@@ -35,20 +43,39 @@ pub(super) fn generate_simple_enum_impl(
         Err(err) => return err.to_compile_error(),
     };
 
-    let Some(zero_index) = discriminants.iter().position(|&value| value == 0) else {
-        return syn::Error::new(data.variants.span(), "proto enums must contain a variant with discriminant 0").to_compile_error();
+    let zero_index = discriminants.iter().position(|&value| value == 0);
+    let default_index = if repr == EnumRepr::I32 {
+        let Some(zero_index) = zero_index else {
+            return syn::Error::new(data.variants.span(), "proto enums must contain a variant with discriminant 0").to_compile_error();
+        };
+        let default_index = marked_default.unwrap_or(zero_index);
+        if default_index != zero_index {
+            discriminants.swap(default_index, zero_index);
+        }
+        enum_item.attrs.push(parse_quote!(#[repr(i32)]));
+        default_index
+    } else {
+        // 64-bit discriminants are usually hashes or ids shared with other systems, so they are
+        // kept as written instead of moving the default variant to 0.
+        let Some(default_index) = marked_default.or(zero_index) else {
+            return syn::Error::new(
+                data.variants.span(),
+                "proto enums with a 64-bit repr must contain a variant with discriminant 0 or mark one #[default]",
+            )
+            .to_compile_error();
+        };
+        default_index
     };
-
-    let default_index = marked_default.unwrap_or(zero_index);
-    if default_index != zero_index {
-        let default_value = discriminants[default_index];
-        discriminants[default_index] = 0;
-        discriminants[zero_index] = default_value;
-    }
     let default_ident = &data.variants[default_index].ident;
+    // Only a zero value may be left off the wire; any other default is always written.
+    let is_default_body = if discriminants[default_index] == 0 {
+        quote! { matches!(*self, Self::#default_ident) }
+    } else {
+        quote! { false }
+    };
 
-    enum_item.attrs.push(parse_quote!(#[repr(i32)]));
-    for (variant, value) in enum_item.variants.iter_mut().zip(discriminants.iter()) {
+    let literals: Vec<TokenStream2> = discriminants.iter().map(|&value| repr.literal(value)).collect();
+    for (variant, value) in enum_item.variants.iter_mut().zip(literals.iter()) {
         let expr: syn::Expr = parse_quote!(#value);
         variant.discriminant = Some((
             syn::token::Eq {
@@ -60,7 +87,7 @@ pub(super) fn generate_simple_enum_impl(
 
     let raw_from_variant: Vec<_> = ordered_variants
         .iter()
-        .zip(discriminants.iter())
+        .zip(literals.iter())
         .map(|(variant, value)| {
             let ident = &variant.ident;
             quote! { #name::#ident => #value }
@@ -69,7 +96,7 @@ pub(super) fn generate_simple_enum_impl(
 
     let try_from_arms: Vec<_> = ordered_variants
         .iter()
-        .zip(discriminants.iter())
+        .zip(literals.iter())
         .map(|(variant, value)| {
             let ident = &variant.ident;
             quote! { #value => Ok(Self::#ident) }
@@ -154,14 +181,22 @@ pub(super) fn generate_simple_enum_impl(
         quote! {}
     };
 
+    let name_str = name.to_string();
+    // Unknown enum hooks carry the raw value as `i64`
+    let hook_value = if repr == EnumRepr::U64 {
+        quote! { value.cast_signed() }
+    } else {
+        quote! { i64::from(value) }
+    };
+    let unknown_value = quote! { ::proto_rs::DecodeError::unknown_enum_value(#name_str, #hook_value) };
     let try_from_impl = quote! {
-        impl #impl_generics ::core::convert::TryFrom<i32> for #name #ty_generics #where_clause {
+        impl #impl_generics ::core::convert::TryFrom<#raw_ty> for #name #ty_generics #where_clause {
             type Error = ::proto_rs::DecodeError;
 
-            fn try_from(value: i32) -> Result<Self, Self::Error> {
+            fn try_from(value: #raw_ty) -> Result<Self, Self::Error> {
                 match value {
                     #(#try_from_arms,)*
-                    _ => Err(::proto_rs::DecodeError::new("invalid enum value")),
+                    _ => Err(#unknown_value),
                 }
             }
         }
@@ -175,7 +210,7 @@ pub(super) fn generate_simple_enum_impl(
             const KIND: ::proto_rs::ProtoKind = ::proto_rs::ProtoKind::SimpleEnum;
         }
 
-        impl #shadow_impl_generics ::proto_rs::ProtoShadowEncode<'a, #name #ty_generics> for i32 #shadow_where_clause {
+        impl #shadow_impl_generics ::proto_rs::ProtoShadowEncode<'a, #name #ty_generics> for #raw_ty #shadow_where_clause {
             #[inline]
             fn from_sun(value: &'a #name #ty_generics) -> Self {
                 match *value {
@@ -187,20 +222,28 @@ pub(super) fn generate_simple_enum_impl(
         impl #impl_generics ::proto_rs::ProtoArchive for #name #ty_generics #where_clause {
             #[inline]
             fn is_default(&self) -> bool {
-                matches!(*self, Self::#default_ident)
+                #is_default_body
             }
 
             #[inline]
             fn archive<const TAG: u32>(&self, w: &mut impl ::proto_rs::RevWriter) {
-                let value: i32 = match *self {
+                let value: #raw_ty = match *self {
+                    #(#raw_from_variant,)*
+                };
+                <#raw_ty as ::proto_rs::ProtoArchive>::archive::<TAG>(&value, w);
+            }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                let value: #raw_ty = match *self {
                     #(#raw_from_variant,)*
                 };
-                <i32 as ::proto_rs::ProtoArchive>::archive::<TAG>(&value, w);
+                <#raw_ty as ::proto_rs::ProtoArchive>::size_hint::<TAG>(&value)
             }
         }
 
         impl #impl_generics ::proto_rs::ProtoEncode for #name #ty_generics #where_clause {
-            type Shadow<'a> = i32;
+            type Shadow<'a> = #raw_ty;
         }
 
         impl #impl_generics ::proto_rs::ProtoDecoder for #name #ty_generics #where_clause {
@@ -221,8 +264,8 @@ pub(super) fn generate_simple_enum_impl(
 
             #[inline]
             fn merge(&mut self, wire_type: ::proto_rs::encoding::WireType, buf: &mut impl ::proto_rs::bytes::Buf, ctx: ::proto_rs::encoding::DecodeContext) -> Result<(), ::proto_rs::DecodeError> {
-                let mut raw = 0i32;
-                <i32 as ::proto_rs::ProtoDecoder>::merge(&mut raw, wire_type, buf, ctx)?;
+                let mut raw: #raw_ty = 0;
+                <#raw_ty as ::proto_rs::ProtoDecoder>::merge(&mut raw, wire_type, buf, ctx)?;
                 *self = Self::try_from(raw)?;
                 Ok(())
             }
@@ -250,3 +293,171 @@ pub(super) fn generate_simple_enum_impl(
         #sun_impls
     }
 }
+
+/// `#[proto_message(string_enum)]`: unit variants travel as a `string` holding their name.
+pub(super) fn generate_string_enum_impl(
+    input: &DeriveInput,
+    item_enum: &ItemEnum,
+    data: &syn::DataEnum,
+    config: &UnifiedProtoConfig,
+) -> syn::Result<TokenStream2> {
+    if config.has_suns() {
+        return Err(syn::Error::new(input.ident.span(), "string_enum does not support sun types"));
+    }
+    let enum_item = sanitize_enum(item_enum.clone());
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let name_str = name.to_string();
+    let unknown_name = format!("unknown {name_str} name");
+
+    let names = string_enum_names(data)?;
+    let default_index = find_marked_default_variant(data)?.unwrap_or(0);
+    let default_ident = &data.variants[default_index].ident;
+    let idents: Vec<_> = data.variants.iter().map(|variant| &variant.ident).collect();
+
+    let validate_with_ext_impl = build_validate_with_ext_impl(config);
+
+    let mut shadow_generics = input.generics.clone();
+    shadow_generics.params.insert(0, parse_quote!('a));
+    let (shadow_impl_generics, _shadow_ty_generics, shadow_where_clause) = shadow_generics.split_for_impl();
+
+    Ok(quote! {
+        #enum_item
+
+        impl #impl_generics ::proto_rs::ProtoExt for #name #ty_generics #where_clause {
+            const KIND: ::proto_rs::ProtoKind = ::proto_rs::ProtoKind::String;
+        }
+
+        impl #shadow_impl_generics ::proto_rs::ProtoShadowEncode<'a, #name #ty_generics> for &'a #name #ty_generics #shadow_where_clause {
+            #[inline]
+            fn from_sun(value: &'a #name #ty_generics) -> Self {
+                value
+            }
+        }
+
+        // Always written: peers that model the enum as a string cannot tell an absent field from a
+        // default variant.
+        impl #impl_generics ::proto_rs::ProtoArchive for &#name #ty_generics #where_clause {
+            #[inline]
+            fn is_default(&self) -> bool {
+                false
+            }
+
+            #[inline]
+            fn archive<const TAG: u32>(&self, w: &mut impl ::proto_rs::RevWriter) {
+                let bytes = match **self {
+                    #( #name::#idents => #names.as_bytes(), )*
+                };
+                w.put_slice(bytes);
+                if TAG != 0 {
+                    w.put_varint(bytes.len() as u64);
+                    ::proto_rs::ArchivedProtoField::<TAG, Self>::put_key(w);
+                }
+            }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                let len = match **self {
+                    #( #name::#idents => #names.len(), )*
+                };
+                Some(::proto_rs::ArchivedProtoField::<TAG, Self>::hint_delimited(len))
+            }
+        }
+
+        impl #impl_generics ::proto_rs::ProtoArchive for #name #ty_generics #where_clause {
+            #[inline]
+            fn is_default(&self) -> bool {
+                false
+            }
+
+            #[inline]
+            fn archive<const TAG: u32>(&self, w: &mut impl ::proto_rs::RevWriter) {
+                <&Self as ::proto_rs::ProtoArchive>::archive::<TAG>(&self, w);
+            }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                <&Self as ::proto_rs::ProtoArchive>::size_hint::<TAG>(&self)
+            }
+        }
+
+        impl #impl_generics ::proto_rs::ProtoEncode for #name #ty_generics #where_clause {
+            type Shadow<'a> = &'a #name #ty_generics;
+        }
+
+        impl #impl_generics ::proto_rs::ProtoDecoder for #name #ty_generics #where_clause {
+            #[inline]
+            fn merge_field(
+                value: &mut Self,
+                tag: u32,
+                wire_type: ::proto_rs::encoding::WireType,
+                buf: &mut impl ::proto_rs::bytes::Buf,
+                ctx: ::proto_rs::encoding::DecodeContext,
+            ) -> Result<(), ::proto_rs::DecodeError> {
+                if tag == 1 {
+                    Self::merge(value, wire_type, buf, ctx)
+                } else {
+                    ::proto_rs::encoding::skip_field(wire_type, tag, buf, ctx)
+                }
+            }
+
+            #[inline]
+            fn merge(&mut self, wire_type: ::proto_rs::encoding::WireType, buf: &mut impl ::proto_rs::bytes::Buf, ctx: ::proto_rs::encoding::DecodeContext) -> Result<(), ::proto_rs::DecodeError> {
+                let mut raw = ::proto_rs::alloc::string::String::new();
+                <::proto_rs::alloc::string::String as ::proto_rs::ProtoDecoder>::merge(&mut raw, wire_type, buf, ctx)?;
+                *self = match raw.as_str() {
+                    #( #names => Self::#idents, )*
+                    "" => Self::#default_ident,
+                    _ => return Err(::proto_rs::DecodeError::new(#unknown_name)),
+                };
+                Ok(())
+            }
+        }
+
+        impl #impl_generics ::proto_rs::ProtoDefault for #name #ty_generics #where_clause {
+            #[inline]
+            fn proto_default() -> Self {
+                Self::#default_ident
+            }
+        }
+
+        impl #impl_generics ::proto_rs::ProtoDecode for #name #ty_generics #where_clause {
+            type ShadowDecoded = Self;
+            #validate_with_ext_impl
+        }
+
+        impl #impl_generics ::proto_rs::ProtoShadowDecode<#name #ty_generics> for #name #ty_generics #where_clause {
+            #[inline]
+            fn to_sun(self) -> Result<#name #ty_generics, ::proto_rs::DecodeError> {
+                Ok(self)
+            }
+        }
+    })
+}
+
+/// Wire names of string enum variants: the variant name, or `#[proto(name = "...")]`.
+fn string_enum_names(data: &syn::DataEnum) -> syn::Result<Vec<String>> {
+    let mut names: Vec<String> = Vec::with_capacity(data.variants.len());
+    for variant in &data.variants {
+        let mut name = variant.ident.to_string();
+        for attr in variant.attrs.iter().filter(|attr| attr.path().is_ident("proto")) {
+            attr.parse_nested_meta(|meta| {
+                if !meta.path.is_ident("name") {
+                    return Err(meta.error("string_enum variants only take #[proto(name = \"...\")]"));
+                }
+                let value: syn::LitStr = meta.value()?.parse()?;
+                if value.value().is_empty() {
+                    return Err(meta.error("the empty string is read as the default variant"));
+                }
+                name = value.value();
+                Ok(())
+            })?;
+        }
+        if names.contains(&name) {
+            return Err(syn::Error::new(variant.span(), format!("duplicate string_enum name: {name}")));
+        }
+        names.push(name);
+    }
+    Ok(names)
+}
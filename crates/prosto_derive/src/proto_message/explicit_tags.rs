@@ -0,0 +1,155 @@
+//! `#[proto_message(explicit_tags)]`: every encoded field, oneof variant and enum value must spell
+//! out its number, so inserting a field can never shift the wire format.
+//!
+//! Each error names the attribute that keeps the current implicit assignment, which makes turning
+//! the mode on for an existing type a copy-and-paste migration.
+
+use std::collections::BTreeSet;
+
+use syn::Data;
+use syn::Fields;
+
+use super::complex_enums::variant_custom_tag;
+use crate::utils::collect_discriminants_for_variants;
+use crate::utils::parse_field_config;
+
+pub fn check_explicit_tags(data: &Data) -> syn::Result<()> {
+    let mut errors = Vec::new();
+    match data {
+        Data::Struct(data) => missing_field_tags(&data.fields, &mut errors),
+        Data::Enum(data) if data.variants.iter().all(|variant| matches!(variant.fields, Fields::Unit)) => {
+            let variants: Vec<&syn::Variant> = data.variants.iter().collect();
+            let values = collect_discriminants_for_variants(&variants)?;
+            for (variant, value) in variants.into_iter().zip(values) {
+                if variant.discriminant.is_none() {
+                    errors.push(syn::Error::new_spanned(
+                        &variant.ident,
+                        format!("explicit_tags requires a discriminant; add `= {value}` to keep the current value"),
+                    ));
+                }
+            }
+        }
+        Data::Enum(data) => {
+            for (idx, variant) in data.variants.iter().enumerate() {
+                if variant_custom_tag(variant)?.is_none() {
+                    errors.push(missing_tag(&variant.ident, idx + 1));
+                }
+                // Tuple variants carry their value under the variant tag
+                if let Fields::Named(_) = variant.fields {
+                    missing_field_tags(&variant.fields, &mut errors);
+                }
+            }
+        }
+        Data::Union(_) => {}
+    }
+
+    let mut errors = errors.into_iter();
+    match errors.next() {
+        Some(mut first) => {
+            first.extend(errors);
+            Err(first)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Reports fields without `#[proto(tag = N)]`, with the tag `assign_tags` gives them today.
+fn missing_field_tags(fields: &Fields, errors: &mut Vec<syn::Error>) {
+    let mut used = BTreeSet::new();
+    let mut next = 1usize;
+    let mut implicit = Vec::new();
+    for field in fields {
+        let config = parse_field_config(field);
+        if config.skip {
+            continue;
+        }
+        if let Some(custom) = config.custom_tag {
+            used.insert(custom);
+            continue;
+        }
+        while used.contains(&next) {
+            next += 1;
+        }
+        used.insert(next);
+        implicit.push((field, next));
+        next += 1;
+    }
+
+    for (field, tag) in implicit {
+        match &field.ident {
+            Some(ident) => errors.push(missing_tag(ident, tag)),
+            None => errors.push(missing_tag(&field.ty, tag)),
+        }
+    }
+}
+
+fn missing_tag(span: impl quote::ToTokens, tag: usize) -> syn::Error {
+    syn::Error::new_spanned(
+        span,
+        format!("explicit_tags requires a tag; add `#[proto(tag = {tag})]` to keep the current one"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::DeriveInput;
+    use syn::parse_quote;
+
+    use super::*;
+
+    fn messages(input: &DeriveInput) -> Vec<String> {
+        check_explicit_tags(&input.data).err().map_or_else(Vec::new, |err| err.into_iter().map(|err| err.to_string()).collect())
+    }
+
+    #[test]
+    fn suggests_the_current_implicit_tags() {
+        let input: DeriveInput = parse_quote! {
+            struct Order {
+                #[proto(tag = 2)]
+                id: u64,
+                note: String,
+                #[proto(skip)]
+                cache: u32,
+                total: u64,
+            }
+        };
+        assert_eq!(
+            messages(&input),
+            [
+                "explicit_tags requires a tag; add `#[proto(tag = 1)]` to keep the current one",
+                "explicit_tags requires a tag; add `#[proto(tag = 3)]` to keep the current one",
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_variants_and_enum_values() {
+        let complex: DeriveInput = parse_quote! {
+            enum Payment {
+                #[proto(tag = 1)]
+                Card(String),
+                Cash { #[proto(tag = 1)] amount: u64 },
+            }
+        };
+        assert_eq!(
+            messages(&complex),
+            ["explicit_tags requires a tag; add `#[proto(tag = 2)]` to keep the current one"]
+        );
+
+        let simple: DeriveInput = parse_quote! {
+            enum Status { Active = 1, Closed }
+        };
+        assert_eq!(
+            messages(&simple),
+            ["explicit_tags requires a discriminant; add `= 2` to keep the current value"]
+        );
+    }
+
+    #[test]
+    fn fully_tagged_types_pass() {
+        let input: DeriveInput = parse_quote! {
+            struct Pair(#[proto(tag = 1)] u32, #[proto(tag = 7)] u32);
+        };
+        assert!(messages(&input).is_empty());
+    }
+}
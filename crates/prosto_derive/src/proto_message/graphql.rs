@@ -0,0 +1,92 @@
+//! `#[proto_message(graphql)]`: async-graphql derives through the `proto_rs::async_graphql`
+//! re-export, so a message can be served over GraphQL without a parallel struct.
+//!
+//! Structs become both an output object and an input object named `{Name}Input`; fields the
+//! protobuf encoding skips, and `ExtensionSet` fields, are hidden from both. Simple enums become
+//! GraphQL enums, whose values are the variant names in screaming snake case as in proto3 JSON.
+
+use syn::Data;
+use syn::DeriveInput;
+use syn::Fields;
+use syn::parse_quote;
+
+use crate::parse::UnifiedProtoConfig;
+use crate::utils::parse_field_config;
+
+pub fn apply_graphql_attrs(input: &mut DeriveInput, config: &UnifiedProtoConfig) -> syn::Result<()> {
+    if !config.graphql {
+        return Ok(());
+    }
+    if config.transparent || config.soa {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "graphql is not supported on transparent or #[proto(soa)] types",
+        ));
+    }
+    match &mut input.data {
+        Data::Struct(data) => {
+            let input_name = format!("{}Input", input.ident);
+            for field in &mut data.fields {
+                let proto = parse_field_config(field);
+                if proto.skip || !proto.extensions.is_empty() {
+                    field.attrs.push(parse_quote! { #[graphql(skip)] });
+                }
+            }
+            input.attrs.push(parse_quote! {
+                #[derive(::proto_rs::async_graphql::SimpleObject, ::proto_rs::async_graphql::InputObject)]
+            });
+            input.attrs.push(parse_quote! { #[graphql(crate = "::proto_rs::async_graphql", input_name = #input_name)] });
+        }
+        Data::Enum(data) if data.variants.iter().all(|variant| matches!(variant.fields, Fields::Unit)) => {
+            input.attrs.push(parse_quote! { #[derive(::proto_rs::async_graphql::Enum)] });
+            input.attrs.push(parse_quote! { #[graphql(crate = "::proto_rs::async_graphql")] });
+        }
+        Data::Enum(_) | Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "graphql is supported on structs and enums of unit variants",
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::*;
+
+    fn graphql_config() -> UnifiedProtoConfig {
+        let mut config = UnifiedProtoConfig::default();
+        config.graphql = true;
+        config
+    }
+
+    #[test]
+    fn skipped_fields_are_hidden_from_graphql() {
+        let mut input: DeriveInput = parse_quote! {
+            struct Order {
+                id: u64,
+                #[proto(skip)]
+                cache: u32,
+            }
+        };
+        apply_graphql_attrs(&mut input, &graphql_config()).unwrap();
+        assert_eq!(input.attrs.len(), 2);
+        let Data::Struct(data) = &input.data else { unreachable!() };
+        let hidden: Vec<bool> = data.fields.iter().map(|field| field.attrs.iter().any(|attr| attr.path().is_ident("graphql"))).collect();
+        assert_eq!(hidden, [false, true]);
+    }
+
+    #[test]
+    fn oneof_enums_are_rejected() {
+        let mut input: DeriveInput = parse_quote! {
+            enum Payment {
+                Cash,
+                Card(String),
+            }
+        };
+        assert!(apply_graphql_attrs(&mut input, &graphql_config()).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+//! `#[proto(header)]` fields: a `{Name}Header` struct holding only those fields, decoded from an
+//! encoding of the full message while every other field is skipped unread.
+//!
+//! Routers that only need an id and a kind out of large messages decode the header instead of the
+//! message, so no strings, vectors or nested messages of the body are allocated.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use super::unified_field_handler::FieldInfo;
+use super::unified_field_handler::build_decode_match_arms;
+use super::unified_field_handler::build_proto_default_expr;
+
+pub(super) fn generate_header_impl(input: &syn::DeriveInput, item_struct: &syn::ItemStruct, fields: &[FieldInfo<'_>]) -> TokenStream2 {
+    let header_fields: Vec<FieldInfo<'_>> = fields.iter().filter(|info| info.config.header).cloned().collect();
+    if header_fields.is_empty() {
+        return TokenStream2::new();
+    }
+    let name = &input.ident;
+    assert!(
+        matches!(item_struct.fields, syn::Fields::Named(_)),
+        "#[proto(header)] needs a struct with named fields"
+    );
+    assert!(
+        input.generics.params.is_empty(),
+        "#[proto(header)] is not supported on generic structs"
+    );
+    for info in &header_fields {
+        assert!(info.tag.is_some(), "#[proto(header)] fields must be encoded; drop `skip`");
+    }
+
+    let header_ident = syn::Ident::new(&format!("{name}Header"), name.span());
+    let vis = &item_struct.vis;
+    let derives = item_struct.attrs.iter().filter(|attr| attr.path().is_ident("derive"));
+    let field_defs = header_fields.iter().map(|info| {
+        let ident = info.access.ident().expect("header fields are named");
+        let field_vis = &info.field.vis;
+        let ty = &info.field.ty;
+        let cfg = info.cfg_attr();
+        quote! { #cfg #field_vis #ident: #ty }
+    });
+    let decode_arms = build_decode_match_arms(&header_fields, &quote! { value });
+    let proto_default_expr = build_proto_default_expr(&header_fields, &item_struct.fields);
+    let doc = format!(" The `#[proto(header)]` fields of [`{name}`], decoded from its encoding without the other fields.");
+
+    quote! {
+        #[doc = #doc]
+        #( #derives )*
+        #vis struct #header_ident {
+            #( #field_defs, )*
+        }
+
+        impl ::proto_rs::ProtoExt for #header_ident {
+            const KIND: ::proto_rs::ProtoKind = ::proto_rs::ProtoKind::Message;
+        }
+
+        impl ::proto_rs::ProtoDecoder for #header_ident {
+            #[inline]
+            fn merge_field(
+                value: &mut Self,
+                tag: u32,
+                wire_type: ::proto_rs::encoding::WireType,
+                buf: &mut impl ::proto_rs::bytes::Buf,
+                ctx: ::proto_rs::encoding::DecodeContext,
+            ) -> Result<(), ::proto_rs::DecodeError> {
+                match tag {
+                    #(#decode_arms,)*
+                    _ => ::proto_rs::encoding::skip_field(wire_type, tag, buf, ctx),
+                }
+            }
+        }
+
+        impl ::proto_rs::ProtoDefault for #header_ident {
+            #[inline]
+            fn proto_default() -> Self {
+                #proto_default_expr
+            }
+        }
+
+        impl ::proto_rs::ProtoDecode for #header_ident {
+            type ShadowDecoded = Self;
+        }
+
+        impl ::proto_rs::ProtoShadowDecode<#header_ident> for #header_ident {
+            #[inline]
+            fn to_sun(self) -> Result<#header_ident, ::proto_rs::DecodeError> {
+                Ok(self)
+            }
+        }
+
+        impl #name {
+            /// Decodes only the `#[proto(header)]` fields of an encoded message.
+            #[inline]
+            pub fn decode_header(
+                buf: impl ::proto_rs::bytes::Buf,
+                ctx: ::proto_rs::encoding::DecodeContext,
+            ) -> Result<#header_ident, ::proto_rs::DecodeError> {
+                <#header_ident as ::proto_rs::ProtoDecode>::decode(buf, ctx)
+            }
+        }
+    }
+}
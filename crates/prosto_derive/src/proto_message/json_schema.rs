@@ -0,0 +1,30 @@
+//! `schemars::JsonSchema` impls, emitted when `proto_rs` enables `prosto_derive/schemars`.
+//!
+//! The schema comes from the registry at runtime (`proto_rs::json_schema`), so the impl sits
+//! under the same `build-schemas` cfg as `ProtoIdentifiable`, and only types with a registered
+//! schema of their own get one: generic, transparent, empty and `#[proto(soa)]` types are left out.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::DeriveInput;
+
+use crate::parse::UnifiedProtoConfig;
+
+pub fn generate_json_schema_impl(input: &DeriveInput, config: &UnifiedProtoConfig) -> TokenStream2 {
+    if !cfg!(feature = "schemars") || !input.generics.params.is_empty() || config.transparent || config.empty_wkt || config.soa {
+        return quote! {};
+    }
+    let name = &input.ident;
+    quote! {
+        #[cfg(feature = "build-schemas")]
+        impl ::proto_rs::schemars::JsonSchema for #name {
+            fn schema_name() -> ::proto_rs::alloc::borrow::Cow<'static, str> {
+                ::proto_rs::json_schema::schema_name(&<Self as ::proto_rs::schemas::ProtoIdentifiable>::PROTO_IDENT)
+            }
+
+            fn json_schema(generator: &mut ::proto_rs::schemars::SchemaGenerator) -> ::proto_rs::schemars::Schema {
+                ::proto_rs::json_schema::message_schema(&<Self as ::proto_rs::schemas::ProtoIdentifiable>::PROTO_IDENT, generator)
+            }
+        }
+    }
+}
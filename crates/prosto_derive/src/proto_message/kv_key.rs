@@ -0,0 +1,98 @@
+//! `#[proto_message(kv_key)]`: an order-preserving `proto_rs::kv::KvKey` impl next to the protobuf
+//! one, so the type can be used as a key-value store key.
+//!
+//! Struct fields are written in declaration order, so keys sort by the first field, then the
+//! second, and so on; skipped fields are left out and come back as `Default`. Simple enums are
+//! written as their discriminant. Complex enums and conditional fields have no fixed layout and are
+//! rejected.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::format_ident;
+use quote::quote;
+use syn::Data;
+use syn::DeriveInput;
+use syn::Fields;
+use syn::Index;
+
+use crate::utils::parse_field_config;
+
+pub fn generate_kv_key_impl(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let (write, read, field_types) = match &input.data {
+        Data::Struct(data) => struct_body(&data.fields)?,
+        Data::Enum(data) if data.variants.iter().all(|variant| matches!(variant.fields, Fields::Unit)) => (
+            quote! {
+                ::proto_rs::kv::KvKey::write_key(&<i32 as ::proto_rs::ProtoShadowEncode<'_, Self>>::from_sun(self), out);
+            },
+            quote! {
+                <Self as ::core::convert::TryFrom<i32>>::try_from(<i32 as ::proto_rs::kv::KvKey>::read_key(buf)?)
+            },
+            Vec::new(),
+        ),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "kv_key is only supported on structs and simple enums",
+            ));
+        }
+    };
+
+    let mut where_clause = where_clause.cloned().unwrap_or_else(|| syn::parse_quote! { where });
+    for ty in field_types {
+        where_clause.predicates.push(syn::parse_quote! { #ty: ::proto_rs::kv::KvKey });
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::proto_rs::kv::KvKey for #name #ty_generics #where_clause {
+            #[inline]
+            #[allow(unused_variables)]
+            fn write_key(&self, out: &mut ::proto_rs::alloc::vec::Vec<u8>) {
+                #write
+            }
+
+            #[inline]
+            #[allow(unused_variables)]
+            fn read_key(buf: &mut &[u8]) -> ::core::result::Result<Self, ::proto_rs::DecodeError> {
+                #read
+            }
+        }
+    })
+}
+
+fn struct_body(fields: &Fields) -> syn::Result<(TokenStream2, TokenStream2, Vec<syn::Type>)> {
+    let mut writes = Vec::new();
+    let mut reads = Vec::new();
+    let mut types = Vec::new();
+    for (idx, field) in fields.iter().enumerate() {
+        let config = parse_field_config(field);
+        if config.cfg_predicate().is_some() {
+            return Err(syn::Error::new_spanned(field, "kv_key does not support conditional fields"));
+        }
+        let member = field.ident.as_ref().map_or_else(
+            || {
+                let index = Index::from(idx);
+                quote! { #index }
+            },
+            |ident| quote! { #ident },
+        );
+        let binding = format_ident!("field_{idx}");
+        if config.skip {
+            reads.push((member, binding, quote! { ::core::default::Default::default() }));
+            continue;
+        }
+        let ty = &field.ty;
+        writes.push(quote! { <#ty as ::proto_rs::kv::KvKey>::write_key(&self.#member, out); });
+        reads.push((member, binding, quote! { <#ty as ::proto_rs::kv::KvKey>::read_key(buf)? }));
+        types.push(ty.clone());
+    }
+
+    let read_fields = reads.iter().map(|(_, binding, read)| quote! { let #binding = #read; });
+    let members = reads.iter().map(|(member, binding, _)| quote! { #member: #binding });
+    let read = quote! {
+        #( #read_fields )*
+        ::core::result::Result::Ok(Self { #( #members ),* })
+    };
+    Ok((quote! { #( #writes )* }, read, types))
+}
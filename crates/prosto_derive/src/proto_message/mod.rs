@@ -1,5 +1,6 @@
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
 use quote::quote;
 use syn::Data;
 use syn::DeriveInput;
@@ -16,30 +17,43 @@ use crate::schema::SchemaTokens;
 use crate::schema::assoc_proto_ident_const;
 use crate::schema::schema_tokens_for_complex_enum;
 use crate::schema::schema_tokens_for_simple_enum;
+use crate::utils::EnumRepr;
+use crate::utils::enum_repr;
 
 pub(crate) fn build_validate_with_ext_impl(config: &UnifiedProtoConfig) -> TokenStream2 {
     let validate_with_ext_tokens: proc_macro2::TokenStream;
     #[cfg(feature = "tonic")]
     {
         validate_with_ext_tokens = {
-            let Some(validator_fn) = &config.validator_with_ext else {
-                // no validator => generate nothing (or generate const false, your choice)
-                return quote! {};
-            };
-
-            let validator_path: syn::Path = syn::parse_str(validator_fn).expect("invalid validator_with_ext function path");
+            let request = config.validator_with_ext.as_ref().map(|validator_fn| {
+                let validator_path: syn::Path = syn::parse_str(validator_fn).expect("invalid validator_with_ext function path");
+                quote! {
+                    const VALIDATE_WITH_EXT: bool = true;
 
-            quote! {
-                const VALIDATE_WITH_EXT: bool = true;
+                    #[inline]
+                    fn validate_with_ext(
+                        value: &mut Self,
+                        ext: &::tonic::Extensions,
+                    ) -> Result<(), ::proto_rs::DecodeError> {
+                        #validator_path(value, ext)
+                    }
+                }
+            });
+            let response = config.response_validator_with_ext.as_ref().map(|validator_fn| {
+                let validator_path: syn::Path = syn::parse_str(validator_fn).expect("invalid response_validator_with_ext function path");
+                quote! {
+                    const VALIDATE_RESPONSE_WITH_EXT: bool = true;
 
-                #[inline]
-                fn validate_with_ext(
-                    value: &mut Self,
-                    ext: &::tonic::Extensions,
-                ) -> Result<(), ::proto_rs::DecodeError> {
-                    #validator_path(value, ext)
+                    #[inline]
+                    fn validate_response_with_ext(
+                        value: &mut Self,
+                        ext: &::tonic::Extensions,
+                    ) -> Result<(), ::proto_rs::DecodeError> {
+                        #validator_path(value, ext)
+                    }
                 }
-            }
+            });
+            quote! { #request #response }
         };
     }
 
@@ -80,18 +94,39 @@ fn concrete_type_tokens(
 }
 
 mod complex_enums;
+mod conditional_fields;
 mod enums;
+mod explicit_tags;
 mod generic_bounds;
+mod graphql;
+mod header;
+mod json_schema;
+mod kv_key;
+mod pagination;
+mod serde_compat;
+mod soa;
 mod structs;
 mod unified_field_handler;
 
 use complex_enums::generate_complex_enum_impl;
+use complex_enums::generate_transparent_enum_impl;
+use complex_enums::transparent_enum_field;
+use conditional_fields::check_conditional_fields;
 use enums::generate_simple_enum_impl;
+use enums::generate_string_enum_impl;
+use explicit_tags::check_explicit_tags;
+use graphql::apply_graphql_attrs;
+use json_schema::generate_json_schema_impl;
+use kv_key::generate_kv_key_impl;
+use pagination::generate_pagination_impls;
+use serde_compat::apply_serde_attrs;
+use serde_compat::check_serde_fields;
+use soa::soa_row_fields;
 use structs::generate_struct_impl;
 
 pub fn proto_message_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let item_ts: TokenStream2 = item.clone().into();
-    let input: DeriveInput = syn::parse2(item_ts.clone()).expect("proto_message expects a type definition");
+    let item_ts: TokenStream2 = item.into();
+    let input: DeriveInput = syn::parse2(item_ts).expect("proto_message expects a type definition");
 
     let type_ident = input.ident.to_string();
     let mut config = UnifiedProtoConfig::from_attributes(attr, &type_ident, &input.attrs, &input.data, input.generics.clone());
@@ -103,8 +138,64 @@ pub fn proto_message_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
             .into();
     }
 
+    if config.soa && (config.transparent || !matches!(input.data, Data::Struct(_))) {
+        return Error::new_spanned(&input.ident, "#[proto(soa)] is only supported on non-transparent structs").to_compile_error().into();
+    }
+
+    if config.string_enum && !matches!(input.data, Data::Enum(_)) {
+        return Error::new_spanned(&input.ident, "string_enum is only supported on enums").to_compile_error().into();
+    }
+
+    if (config.range.is_some() || config.unit.is_some()) && !(config.transparent && matches!(input.data, Data::Struct(_))) {
+        return Error::new_spanned(
+            &input.ident,
+            "range and unit are only supported on #[proto_message(transparent)] structs",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    if config.explicit_tags
+        && let Err(err) = check_explicit_tags(&input.data)
+    {
+        return err.to_compile_error().into();
+    }
+    if let Err(err) = check_conditional_fields(&input.data, &config) {
+        return err.to_compile_error().into();
+    }
+    if let Err(err) = check_serde_fields(&input, &config) {
+        return err.to_compile_error().into();
+    }
+    let mut input = input;
+    apply_serde_attrs(&mut input, &config);
+    if let Err(err) = apply_graphql_attrs(&mut input, &config) {
+        return err.to_compile_error().into();
+    }
+    let item_ts = input.to_token_stream();
+    if config.empty_wkt {
+        if !matches!(&input.data, Data::Struct(data) if data.fields.is_empty()) {
+            return Error::new_spanned(&input.ident, "empty = \"wkt\" is only supported on unit structs").to_compile_error().into();
+        }
+        crate::write_file::register_empty_wkt_type(&type_ident);
+    }
+    if let Some(parent) = config.nest_in.as_deref() {
+        if config.transparent || config.empty_wkt || input.generics.type_params().next().is_some() {
+            return Error::new_spanned(&input.ident, "nest_in is not supported on transparent, empty or generic types")
+                .to_compile_error()
+                .into();
+        }
+        if proto_names.iter().any(|name| name == parent) {
+            return Error::new_spanned(&input.ident, "a message cannot be nested in itself").to_compile_error().into();
+        }
+        if let Some(proto_path) = config.proto_path() {
+            for proto_name in &proto_names {
+                crate::write_file::register_nested_type(proto_path, proto_name, parent);
+            }
+        }
+    }
+
     // Get generic type variants (concrete type combinations)
-    let generic_variants = match config.generic_type_variants(&input.generics) {
+    let generic_variants = match config.schema_variants(&input.generics) {
         Ok(variants) => variants,
         Err(err) => return err.to_compile_error().into(),
     };
@@ -119,6 +210,10 @@ pub fn proto_message_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
             for proto_name in &proto_names {
                 // For each proto name, iterate over generic variants
                 for variant in &generic_variants {
+                    // Unit structs mapped to google.protobuf.Empty get no message of their own
+                    if config.empty_wkt {
+                        continue;
+                    }
                     let message_name = if variant.suffix.is_empty() {
                         proto_name.clone()
                     } else {
@@ -131,6 +226,8 @@ pub fn proto_message_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
                     } else {
                         crate::generic_substitutions::apply_generic_substitutions_fields(&data.fields, &variant.substitutions)
                     };
+                    // The message of a soa container is its row
+                    let fields = if config.soa { soa_row_fields(&fields) } else { fields };
 
                     let proto = generate_struct_proto(&message_name, &fields, &generic_params);
                     // Use _concrete version if we have substitutions
@@ -163,6 +260,24 @@ pub fn proto_message_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
         }
         Data::Enum(ref data) => {
             let is_simple_enum = data.variants.iter().all(|variant| matches!(variant.fields, Fields::Unit));
+            let repr = if is_simple_enum {
+                match enum_repr(&input.attrs) {
+                    Ok(repr) => repr,
+                    Err(err) => return err.to_compile_error().into(),
+                }
+            } else {
+                EnumRepr::I32
+            };
+            // Proto enum numbers are 32-bit, so 64-bit enums only exist as int64/uint64 fields.
+            let wide_enum = repr != EnumRepr::I32 && !config.string_enum;
+            if wide_enum && config.proto_path().is_some() {
+                return Error::new_spanned(
+                    &input.ident,
+                    "enums with a 64-bit repr are written as int64/uint64 fields and cannot have a proto_path",
+                )
+                .to_compile_error()
+                .into();
+            }
             let mut schema_tokens_col = quote! {};
             let mut inventory_tokens_col = quote! {};
             let mut validator_tokens_col = quote! {};
@@ -184,7 +299,9 @@ pub fn proto_message_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
                         crate::generic_substitutions::apply_generic_substitutions_enum(data, &variant.substitutions)
                     };
 
-                    let proto = if is_simple_enum {
+                    let proto = if wide_enum {
+                        String::new()
+                    } else if is_simple_enum {
                         generate_simple_enum_proto(&message_name, &enum_data)
                     } else {
                         generate_complex_enum_proto(&message_name, &enum_data, &generic_params)
@@ -234,7 +351,19 @@ pub fn proto_message_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
             }
 
             let item_enum: ItemEnum = syn::parse2(item_ts).expect("failed to parse enum");
-            let type_tokens = if is_simple_enum {
+            let type_tokens = if config.string_enum {
+                if !is_simple_enum {
+                    return Error::new_spanned(&input.ident, "string_enum requires an enum of unit variants").to_compile_error().into();
+                }
+                match generate_string_enum_impl(&input, &item_enum, data, &config) {
+                    Ok(tokens) => tokens,
+                    Err(err) => return err.to_compile_error().into(),
+                }
+            } else if config.transparent
+                && let Some((variant, field)) = transparent_enum_field(data)
+            {
+                generate_transparent_enum_impl(&input, &item_enum, variant, field)
+            } else if is_simple_enum {
                 generate_simple_enum_impl(&input, &item_enum, data, &config)
             } else {
                 match generate_complex_enum_impl(&input, &item_enum, data, &config) {
@@ -251,11 +380,26 @@ pub fn proto_message_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
     // Concrete variant schemas are registered separately with inventory for proto file generation
     let proto_ident_const = assoc_proto_ident_const(&config, &input.ident, &input.generics, &proto_names);
 
+    let kv_key_impl = if config.kv_key {
+        match generate_kv_key_impl(&input) {
+            Ok(tokens) => tokens,
+            Err(err) => return err.to_compile_error().into(),
+        }
+    } else {
+        quote! {}
+    };
+
+    let pagination_impls = generate_pagination_impls(&input);
+    let json_schema_impl = generate_json_schema_impl(&input, &config);
+
     let proto_imports = config.imports_mat;
     quote! {
         #proto_imports
         #tokens
         #proto_ident_const
+        #kv_key_impl
+        #pagination_impls
+        #json_schema_impl
     }
     .into()
 }
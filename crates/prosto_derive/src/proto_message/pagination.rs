@@ -0,0 +1,83 @@
+//! AIP-158 list shapes: `proto_rs::pagination::PageRequest` for structs with a `page_token: String`
+//! field, and `PageResponse` for structs with a `next_page_token: String` field and exactly one
+//! `Vec<T>` field holding the page's items.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::Data;
+use syn::DeriveInput;
+use syn::Fields;
+use syn::GenericArgument;
+use syn::PathArguments;
+use syn::Type;
+
+fn last_segment(ty: &Type) -> Option<&syn::PathSegment> {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last(),
+        _ => None,
+    }
+}
+
+fn is_string(ty: &Type) -> bool {
+    last_segment(ty).is_some_and(|segment| segment.ident == "String" && segment.arguments.is_empty())
+}
+
+fn vec_item(ty: &Type) -> Option<&Type> {
+    let segment = last_segment(ty).filter(|segment| segment.ident == "Vec")?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(item)) if args.args.len() == 1 => Some(item),
+        _ => None,
+    }
+}
+
+pub fn generate_pagination_impls(input: &DeriveInput) -> TokenStream2 {
+    let Data::Struct(data) = &input.data else {
+        return quote! {};
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return quote! {};
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let field =
+        |wanted: &str| fields.named.iter().find(|field| field.ident.as_ref().is_some_and(|ident| ident == wanted) && is_string(&field.ty));
+
+    let request = field("page_token").map(|_| {
+        quote! {
+            impl #impl_generics ::proto_rs::pagination::PageRequest for #name #ty_generics #where_clause {
+                fn set_page_token(&mut self, token: ::proto_rs::alloc::string::String) {
+                    self.page_token = token;
+                }
+            }
+        }
+    });
+
+    let mut repeated = fields.named.iter().filter_map(|field| Some((field.ident.as_ref()?, vec_item(&field.ty)?)));
+    let items = match (repeated.next(), repeated.next()) {
+        (Some(items), None) => Some(items),
+        _ => None,
+    };
+    let response = field("next_page_token").zip(items).map(|(_, (items, item_ty))| {
+        quote! {
+            impl #impl_generics ::proto_rs::pagination::PageResponse for #name #ty_generics #where_clause {
+                type Item = #item_ty;
+
+                fn next_page_token(&self) -> &str {
+                    &self.next_page_token
+                }
+
+                fn into_items(self) -> ::proto_rs::alloc::vec::Vec<Self::Item> {
+                    self.#items
+                }
+            }
+        }
+    });
+
+    quote! {
+        #request
+        #response
+    }
+}
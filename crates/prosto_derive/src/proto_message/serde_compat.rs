@@ -0,0 +1,179 @@
+//! Messages that also derive serde's `Serialize`/`Deserialize`.
+//!
+//! Both formats should carry the same fields, so a field skipped by one and kept by the other is
+//! rejected. `#[proto_message(serde)]` adds the derives, through the `proto_rs::serde` re-export, and
+//! marks `#[proto(skip)]` fields `#[serde(skip)]` itself. `ExtensionSet` fields hold encoded
+//! protobuf and are always serde-skipped.
+
+use syn::Attribute;
+use syn::Data;
+use syn::DeriveInput;
+use syn::Field;
+use syn::Meta;
+use syn::Token;
+use syn::parse_quote;
+use syn::punctuated::Punctuated;
+
+use crate::parse::UnifiedProtoConfig;
+use crate::utils::parse_field_config;
+
+/// Whether `attrs` derive `Serialize` or `Deserialize`.
+pub fn derives_serde(attrs: &[Attribute]) -> bool {
+    attrs.iter().filter(|attr| attr.path().is_ident("derive")).any(|attr| {
+        attr.parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated).is_ok_and(|paths| {
+            paths
+                .iter()
+                .filter_map(|path| path.segments.last())
+                .any(|segment| segment.ident == "Serialize" || segment.ident == "Deserialize")
+        })
+    })
+}
+
+pub fn check_serde_fields(input: &DeriveInput, config: &UnifiedProtoConfig) -> syn::Result<()> {
+    if !config.serde && !derives_serde(&input.attrs) {
+        return Ok(());
+    }
+    let mut errors = Vec::new();
+    for field in fields(&input.data) {
+        let proto = parse_field_config(field);
+        let serde_skipped = serde_skips(&field.attrs);
+        if !proto.extensions.is_empty() {
+            continue;
+        }
+        if proto.skip && !serde_skipped && !config.serde {
+            errors.push(syn::Error::new_spanned(
+                field,
+                "field is skipped by #[proto(skip)] but kept by serde; add #[serde(skip)] or use #[proto_message(serde)]",
+            ));
+        } else if !proto.skip && serde_skipped {
+            errors.push(syn::Error::new_spanned(
+                field,
+                "field is skipped by serde but encoded by proto_message; add #[proto(skip)] or drop the serde skip",
+            ));
+        }
+    }
+
+    let mut errors = errors.into_iter();
+    match errors.next() {
+        Some(mut first) => {
+            first.extend(errors);
+            Err(first)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Adds the serde derives of `#[proto_message(serde)]` and the `#[serde(skip)]` of fields serde
+/// must not see.
+pub fn apply_serde_attrs(input: &mut DeriveInput, config: &UnifiedProtoConfig) {
+    let derives = derives_serde(&input.attrs);
+    if !config.serde && !derives {
+        return;
+    }
+    if config.serde {
+        input.attrs.push(parse_quote! { #[derive(::proto_rs::serde::Serialize, ::proto_rs::serde::Deserialize)] });
+        input.attrs.push(parse_quote! { #[serde(crate = "::proto_rs::serde")] });
+    }
+    for field in fields_mut(&mut input.data) {
+        if serde_skips(&field.attrs) {
+            continue;
+        }
+        let proto = parse_field_config(field);
+        if !proto.extensions.is_empty() || (config.serde && proto.skip) {
+            field.attrs.push(parse_quote! { #[serde(skip)] });
+        }
+    }
+}
+
+/// Whether `attrs` hold `#[serde(skip)]`, `skip_serializing` or `skip_deserializing`.
+fn serde_skips(attrs: &[Attribute]) -> bool {
+    attrs.iter().filter(|attr| attr.path().is_ident("serde")).any(|attr| {
+        attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated).is_ok_and(|metas| {
+            metas.iter().any(|meta| {
+                let path = meta.path();
+                path.is_ident("skip") || path.is_ident("skip_serializing") || path.is_ident("skip_deserializing")
+            })
+        })
+    })
+}
+
+fn fields(data: &Data) -> Vec<&Field> {
+    match data {
+        Data::Struct(data) => data.fields.iter().collect(),
+        Data::Enum(data) => data.variants.iter().flat_map(|variant| &variant.fields).collect(),
+        Data::Union(_) => Vec::new(),
+    }
+}
+
+fn fields_mut(data: &mut Data) -> Vec<&mut Field> {
+    match data {
+        Data::Struct(data) => data.fields.iter_mut().collect(),
+        Data::Enum(data) => data.variants.iter_mut().flat_map(|variant| &mut variant.fields).collect(),
+        Data::Union(_) => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::DeriveInput;
+    use syn::parse_quote;
+
+    use super::*;
+
+    fn messages(input: &DeriveInput, config: &UnifiedProtoConfig) -> Vec<String> {
+        check_serde_fields(input, config).err().map_or_else(Vec::new, |err| err.into_iter().map(|err| err.to_string()).collect())
+    }
+
+    #[test]
+    fn skips_must_match_between_formats() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Serialize, serde::Deserialize)]
+            struct Profile {
+                id: u64,
+                #[proto(skip)]
+                cache: u32,
+                #[serde(skip_serializing)]
+                token: String,
+                #[proto(skip)]
+                #[serde(skip)]
+                local: u8,
+            }
+        };
+        let messages = messages(&input, &UnifiedProtoConfig::default());
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].contains("skipped by #[proto(skip)]"), "{messages:?}");
+        assert!(messages[1].contains("skipped by serde"), "{messages:?}");
+    }
+
+    #[test]
+    fn types_without_serde_are_not_checked() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Clone)]
+            struct Profile {
+                #[proto(skip)]
+                cache: u32,
+            }
+        };
+        assert!(messages(&input, &UnifiedProtoConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn serde_flag_skips_proto_skipped_and_extension_fields() {
+        let mut input: DeriveInput = parse_quote! {
+            struct Order {
+                id: u64,
+                #[proto(skip)]
+                cache: u32,
+                #[proto(extensions = "100..200")]
+                extensions: ExtensionSet,
+            }
+        };
+        let mut config = UnifiedProtoConfig::default();
+        config.serde = true;
+        assert!(messages(&input, &config).is_empty());
+        apply_serde_attrs(&mut input, &config);
+        assert!(derives_serde(&input.attrs));
+        let skipped: Vec<bool> = fields(&input.data).iter().map(|field| serde_skips(&field.attrs)).collect();
+        assert_eq!(skipped, [false, true, true]);
+    }
+}
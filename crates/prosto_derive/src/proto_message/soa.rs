@@ -0,0 +1,278 @@
+//! `#[proto(soa)]` containers: a struct of `Vec` columns encoded as the rows of a repeated message
+//! field, without a row struct per element.
+//!
+//! The derive generates a `{Name}Row<'a>` holding the shadows of one element per column, archived
+//! as the row message, and implements `SoaColumns` over it. Decoding a row merges every field into
+//! a local and then pushes one element onto each column.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use super::unified_field_handler::FieldInfo;
+use crate::parse::UnifiedProtoConfig;
+use crate::utils::vec_inner_type;
+
+pub(super) fn generate_soa_impl(
+    input: &syn::DeriveInput,
+    item_struct: &syn::ItemStruct,
+    struct_item: &syn::ItemStruct,
+    fields: &[FieldInfo<'_>],
+    config: &UnifiedProtoConfig,
+) -> TokenStream2 {
+    let name = &input.ident;
+    assert!(
+        matches!(item_struct.fields, syn::Fields::Named(_)),
+        "#[proto(soa)] needs a struct with named fields"
+    );
+    assert!(
+        input.generics.params.is_empty(),
+        "#[proto(soa)] is not supported on generic structs"
+    );
+    assert!(config.suns.is_empty(), "#[proto(soa)] cannot be combined with `sun`");
+    assert!(!fields.is_empty(), "#[proto(soa)] needs at least one column");
+
+    let columns: Vec<(&syn::Ident, u32, syn::Type)> = fields
+        .iter()
+        .map(|info| {
+            let ident = info.access.ident().expect("soa columns are named");
+            let tag = info.tag.expect("#[proto(soa)] columns are all encoded; drop `skip`");
+            let elem = vec_inner_type(&info.field.ty).unwrap_or_else(|| panic!("#[proto(soa)] column `{ident}` must be a Vec"));
+            (ident, tag, elem)
+        })
+        .collect();
+
+    let vis = &item_struct.vis;
+    let row_ident = syn::Ident::new(&format!("{name}Row"), name.span());
+    let shadow_ident = syn::Ident::new(&format!("{name}Shadow"), name.span());
+    let row_doc = format!(" One row of [`{name}`], borrowed from its columns.");
+
+    let row_fields = columns.iter().map(|(ident, _, elem)| {
+        quote! { #ident: <#elem as ::proto_rs::ProtoEncode>::Shadow<'a> }
+    });
+    let row_inits = columns.iter().map(|(ident, _, elem)| {
+        quote! {
+            #ident: <<#elem as ::proto_rs::ProtoEncode>::Shadow<'_> as ::proto_rs::ProtoShadowEncode<'_, #elem>>::from_sun(&self.#ident[idx])
+        }
+    });
+    let archive_fields = columns.iter().rev().map(|(ident, tag, elem)| {
+        quote! { ::proto_rs::ArchivedProtoField::<#tag, <#elem as ::proto_rs::ProtoEncode>::Shadow<'a>>::archive(&self.#ident, w); }
+    });
+    let size_hint_fields = columns.iter().map(|(ident, tag, elem)| {
+        quote! { + ::proto_rs::ArchivedProtoField::<#tag, <#elem as ::proto_rs::ProtoEncode>::Shadow<'a>>::size_hint(&self.#ident)? }
+    });
+    let is_default_checks = columns.iter().map(|(ident, _, _)| quote! { ::proto_rs::ProtoArchive::is_default(&self.#ident) });
+
+    let (first, rest) = columns.split_first().expect("at least one column");
+    let first_ident = first.0;
+    let rest_idents = rest.iter().map(|(ident, _, _)| ident);
+
+    let row_locals = columns.iter().map(|(ident, _, elem)| {
+        quote! {
+            let mut #ident = <<#elem as ::proto_rs::ProtoDecode>::ShadowDecoded as ::proto_rs::ProtoDefault>::proto_default();
+        }
+    });
+    let row_arms = columns.iter().map(|(ident, tag, _)| {
+        quote! { #tag => ::proto_rs::ProtoFieldMerge::merge_value(&mut #ident, wire_type, buf, ctx)? }
+    });
+    let row_pushes = columns.iter().map(|(ident, _, elem)| {
+        quote! { self.#ident.push(<#elem as ::proto_rs::ProtoDecode>::post_decode(#ident)?); }
+    });
+    let default_fields = columns.iter().map(|(ident, _, _)| quote! { #ident: ::proto_rs::alloc::vec::Vec::new() });
+
+    quote! {
+        #struct_item
+
+        #[doc = #row_doc]
+        #vis struct #row_ident<'a> {
+            #( #row_fields, )*
+        }
+
+        impl<'a> ::proto_rs::ProtoExt for #row_ident<'a> {
+            const KIND: ::proto_rs::ProtoKind = ::proto_rs::ProtoKind::Message;
+        }
+
+        impl<'a> ::proto_rs::ProtoArchive for #row_ident<'a> {
+            #[inline]
+            fn is_default(&self) -> bool {
+                #( #is_default_checks )&&*
+            }
+
+            #[inline]
+            fn archive<const TAG: u32>(&self, w: &mut impl ::proto_rs::RevWriter) {
+                let mark = w.mark();
+                #( #archive_fields )*
+                if TAG != 0 {
+                    let payload_len = w.written_since(mark);
+                    w.put_varint(payload_len as u64);
+                    ::proto_rs::ArchivedProtoField::<TAG, Self>::put_key(w);
+                }
+            }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                let payload = 0 #( #size_hint_fields )*;
+                Some(::proto_rs::ArchivedProtoField::<TAG, Self>::hint_delimited(payload))
+            }
+        }
+
+        impl ::proto_rs::SoaColumns for #name {
+            type Row<'a> = #row_ident<'a>;
+
+            #[inline]
+            fn rows(&self) -> usize {
+                let rows = self.#first_ident.len();
+                #( let rows = rows.min(self.#rest_idents.len()); )*
+                rows
+            }
+
+            #[inline]
+            fn row(&self, idx: usize) -> #row_ident<'_> {
+                #row_ident {
+                    #( #row_inits, )*
+                }
+            }
+        }
+
+        #vis struct #shadow_ident<'a>(&'a #name);
+
+        impl<'a> ::proto_rs::ProtoExt for #shadow_ident<'a> {
+            const KIND: ::proto_rs::ProtoKind = ::proto_rs::ProtoKind::Message;
+            const MAX_PAYLOAD_LEN: Option<usize> = None;
+        }
+
+        impl<'a> ::proto_rs::ProtoShadowEncode<'a, #name> for #shadow_ident<'a> {
+            #[inline]
+            fn from_sun(value: &'a #name) -> Self {
+                Self(value)
+            }
+        }
+
+        impl<'a> ::proto_rs::ProtoArchive for #shadow_ident<'a> {
+            #[inline]
+            fn is_default(&self) -> bool {
+                ::proto_rs::SoaColumns::rows(self.0) == 0
+            }
+
+            // As a field every row is an occurrence of it; on its own the rows go to field 1.
+            #[inline]
+            fn archive<const TAG: u32>(&self, w: &mut impl ::proto_rs::RevWriter) {
+                if TAG == 0 {
+                    ::proto_rs::soa::archive_rows::<1, _>(self.0, w);
+                } else {
+                    ::proto_rs::soa::archive_rows::<TAG, _>(self.0, w);
+                }
+            }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                if TAG == 0 {
+                    ::proto_rs::soa::rows_size_hint::<1, _>(self.0)
+                } else {
+                    ::proto_rs::soa::rows_size_hint::<TAG, _>(self.0)
+                }
+            }
+        }
+
+        impl ::proto_rs::ProtoExt for #name {
+            const KIND: ::proto_rs::ProtoKind = ::proto_rs::ProtoKind::Message;
+            const MAX_PAYLOAD_LEN: Option<usize> = None;
+        }
+
+        impl ::proto_rs::ProtoEncode for #name {
+            type Shadow<'a> = #shadow_ident<'a>;
+        }
+
+        impl ::proto_rs::ProtoArchive for #name {
+            #[inline]
+            fn is_default(&self) -> bool {
+                ::proto_rs::ProtoArchive::is_default(&#shadow_ident(self))
+            }
+
+            #[inline]
+            fn archive<const TAG: u32>(&self, w: &mut impl ::proto_rs::RevWriter) {
+                ::proto_rs::ProtoArchive::archive::<TAG>(&#shadow_ident(self), w);
+            }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                ::proto_rs::ProtoArchive::size_hint::<TAG>(&#shadow_ident(self))
+            }
+        }
+
+        impl ::proto_rs::ProtoDecoder for #name {
+            #[inline]
+            fn merge_field(
+                value: &mut Self,
+                tag: u32,
+                wire_type: ::proto_rs::encoding::WireType,
+                buf: &mut impl ::proto_rs::bytes::Buf,
+                ctx: ::proto_rs::encoding::DecodeContext,
+            ) -> Result<(), ::proto_rs::DecodeError> {
+                match tag {
+                    1 => ::proto_rs::ProtoDecoder::merge(value, wire_type, buf, ctx),
+                    _ => ::proto_rs::encoding::skip_field(wire_type, tag, buf, ctx),
+                }
+            }
+
+            // Each occurrence is one row.
+            fn merge(
+                &mut self,
+                wire_type: ::proto_rs::encoding::WireType,
+                buf: &mut impl ::proto_rs::bytes::Buf,
+                ctx: ::proto_rs::encoding::DecodeContext,
+            ) -> Result<(), ::proto_rs::DecodeError> {
+                if wire_type != ::proto_rs::encoding::WireType::LengthDelimited {
+                    return Err(::proto_rs::DecodeError::new("invalid wire type for a row"));
+                }
+                ctx.limit_reached()?;
+                let len = usize::try_from(::proto_rs::encoding::decode_varint(buf)?).unwrap_or(usize::MAX);
+                let remaining = ::proto_rs::bytes::Buf::remaining(buf);
+                if len > remaining {
+                    return Err(::proto_rs::DecodeError::new("buffer underflow"));
+                }
+                let limit = remaining - len;
+                #( #row_locals )*
+                while ::proto_rs::bytes::Buf::remaining(buf) > limit {
+                    let (tag, wire_type) = ::proto_rs::encoding::decode_key(buf)?;
+                    match tag {
+                        #( #row_arms, )*
+                        _ => ::proto_rs::encoding::skip_field(wire_type, tag, buf, ctx)?,
+                    }
+                }
+                #( #row_pushes )*
+                Ok(())
+            }
+        }
+
+        impl ::proto_rs::ProtoDefault for #name {
+            #[inline]
+            fn proto_default() -> Self {
+                Self {
+                    #( #default_fields, )*
+                }
+            }
+        }
+
+        impl ::proto_rs::ProtoDecode for #name {
+            type ShadowDecoded = Self;
+        }
+
+        impl ::proto_rs::ProtoShadowDecode<#name> for #name {
+            #[inline]
+            fn to_sun(self) -> Result<#name, ::proto_rs::DecodeError> {
+                Ok(self)
+            }
+        }
+    }
+}
+
+/// Fields of the row message: each `Vec<T>` column as a singular `T`.
+pub(super) fn soa_row_fields(fields: &syn::Fields) -> syn::Fields {
+    let mut fields = fields.clone();
+    for field in &mut fields {
+        if let Some(elem) = vec_inner_type(&field.ty) {
+            field.ty = elem;
+        }
+    }
+    fields
+}
@@ -12,6 +12,8 @@ use syn::visit_mut::VisitMut;
 
 use super::build_validate_with_ext_impl;
 use super::generic_bounds::add_proto_wire_bounds;
+use super::header::generate_header_impl;
+use super::soa::generate_soa_impl;
 use super::unified_field_handler::FieldAccess;
 use super::unified_field_handler::FieldInfo;
 use super::unified_field_handler::assign_tags;
@@ -20,14 +22,18 @@ use super::unified_field_handler::build_post_decode_hooks;
 use super::unified_field_handler::build_proto_default_expr;
 use super::unified_field_handler::compute_decode_ty;
 use super::unified_field_handler::compute_proto_ty;
+use super::unified_field_handler::direct_shadow_ty;
 use super::unified_field_handler::encode_conversion_expr;
 use super::unified_field_handler::encode_conversion_expr_direct;
+use super::unified_field_handler::in_heat_order;
 use super::unified_field_handler::needs_encode_conversion;
 use super::unified_field_handler::strip_proto_attrs;
+use super::unified_field_handler::uses_proto_wire_directly;
 use crate::parse::UnifiedProtoConfig;
 use crate::utils::parse_field_config;
 use crate::utils::parse_field_type;
 use crate::utils::resolved_field_type;
+use crate::utils::vec_inner_type;
 
 pub(super) fn generate_struct_impl(
     input: &DeriveInput,
@@ -87,6 +93,7 @@ pub(super) fn generate_struct_impl(
             .collect::<Vec<_>>(),
         syn::Fields::Unit => Vec::new(),
     };
+    check_field_names(&fields);
 
     if config.transparent {
         assert!(fields.len() == 1, "#[proto_message(transparent)] requires a single-field struct");
@@ -104,6 +111,7 @@ pub(super) fn generate_struct_impl(
             where_clause,
             &field,
             &data.fields,
+            config.range.as_deref(),
         );
 
         return quote! {
@@ -116,11 +124,20 @@ pub(super) fn generate_struct_impl(
     let (impl_generics, ty_generics, where_clause) = bounded_generics.split_for_impl();
 
     let fields = assign_tags(fields);
+    if config.soa {
+        return generate_soa_impl(input, item_struct, &struct_item, &fields, config);
+    }
+    let extension_field = extension_field(&fields, config);
+    check_offset_indexes(&fields, config);
 
     let shadow_ident = syn::Ident::new(&format!("{name}Shadow"), name.span());
     let archived_ident = syn::Ident::new(&format!("{name}Archived"), name.span());
 
     let has_sun_ir = config.suns.iter().any(|sun| sun.ir_ty.is_some());
+    assert!(
+        has_sun_ir || !fields.iter().any(|info| info.config.is_view),
+        "#[proto(view)] fields are read from a `sun_ir` type; add `sun_ir = ...` to #[proto_message]"
+    );
     let shadow_impls = if has_sun_ir {
         TokenStream2::new()
     } else {
@@ -131,6 +148,7 @@ pub(super) fn generate_struct_impl(
             &item_struct.vis,
             &data.fields,
             &fields,
+            extension_field,
             &bounded_generics,
             &ty_generics,
             config.suns.is_empty(),
@@ -145,18 +163,21 @@ pub(super) fn generate_struct_impl(
         &ty_generics,
         where_clause,
         &fields,
+        extension_field,
         &data.fields,
         config,
     );
+    let header_impl = generate_header_impl(input, item_struct, &fields);
 
     quote! {
         #struct_item
         #shadow_impls
         #proto_impls
+        #header_impl
     }
 }
 
-fn add_transparent_bounds(generics: &syn::Generics, inner_ty: &Type) -> syn::Generics {
+pub(super) fn add_transparent_bounds(generics: &syn::Generics, inner_ty: &Type) -> syn::Generics {
     let mut generics = generics.clone();
     let type_params: BTreeSet<_> = generics.type_params().map(|param| param.ident.clone()).collect();
     let where_clause = generics.make_where_clause();
@@ -291,6 +312,68 @@ fn collect_type_params(ty: &Type, params: &BTreeSet<syn::Ident>, used: &mut BTre
     }
 }
 
+/// Schema names of tuple struct fields are `field_N` unless renamed, and must stay unique.
+fn check_field_names(fields: &[FieldInfo<'_>]) {
+    let mut names = BTreeSet::new();
+    for (position, info) in fields.iter().filter(|info| !info.config.skip).enumerate() {
+        let name = match (&info.access, &info.config.proto_name) {
+            (FieldAccess::Tuple(_), Some(name)) => name.clone(),
+            (FieldAccess::Tuple(_), None) => format!("field_{position}"),
+            (_, Some(_)) => panic!("#[proto(name = ...)] only names tuple struct fields; named fields use their identifier"),
+            (_, None) => continue,
+        };
+        assert!(names.insert(name.clone()), "duplicate proto field name: {name}");
+    }
+}
+
+/// The `#[proto(extensions = "...")]` field, whose ranges must not hold the tag of another field.
+fn extension_field<'b, 'a>(fields: &'b [FieldInfo<'a>], config: &UnifiedProtoConfig) -> Option<&'b FieldInfo<'a>> {
+    let mut extension_fields = fields.iter().filter(|info| !info.config.extensions.is_empty());
+    let extension_field = extension_fields.next()?;
+    assert!(
+        extension_fields.next().is_none(),
+        "a message has at most one #[proto(extensions)] field"
+    );
+    assert!(
+        matches!(extension_field.access, FieldAccess::Named(_)),
+        "#[proto(extensions)] needs a named field"
+    );
+    assert!(config.suns.is_empty(), "#[proto(extensions)] is not supported together with `sun`");
+    for info in fields {
+        let Some(tag) = info.tag else { continue };
+        assert!(
+            !extension_field.config.extensions.iter().any(|&(start, end)| (start..=end).contains(&tag)),
+            "proto field tag {tag} falls in the extension ranges; give the field a tag outside them"
+        );
+    }
+    Some(extension_field)
+}
+
+/// `#[proto(offset_index = N)]` fields are `Vec`s of messages encoded as they are, and `N` is free.
+fn check_offset_indexes(fields: &[FieldInfo<'_>], config: &UnifiedProtoConfig) {
+    let tags: BTreeSet<u32> = fields.iter().filter_map(|info| info.tag).collect();
+    let mut index_tags = BTreeSet::new();
+    for info in fields {
+        let Some(index_tag) = info.config.offset_index else { continue };
+        assert!(
+            info.tag.is_some() && vec_inner_type(&info.field.ty).is_some() && uses_proto_wire_directly(info),
+            "#[proto(offset_index)] needs a `Vec` of messages encoded without conversions"
+        );
+        assert!(
+            info.config.cfg_predicate().is_none(),
+            "#[proto(offset_index)] fields cannot be conditional"
+        );
+        assert!(
+            !config.suns.iter().any(|sun| sun.ir_ty.is_some()),
+            "#[proto(offset_index)] is not supported together with `sun_ir`"
+        );
+        assert!(
+            !tags.contains(&index_tag) && index_tags.insert(index_tag),
+            "offset_index {index_tag} is already the tag of another field or index"
+        );
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn generate_transparent_struct_impl(
     name: &syn::Ident,
@@ -301,29 +384,66 @@ fn generate_transparent_struct_impl(
     where_clause: Option<&syn::WhereClause>,
     field: &FieldInfo<'_>,
     original_fields: &syn::Fields,
+    range: Option<&str>,
 ) -> TokenStream2 {
-    let inner_ty = &field.field.ty;
-    let mut_value_access = field.access.access_tokens(quote! { value });
-    let mut_self_access = field.access.access_tokens(quote! { self });
-    let shadow_ident = syn::Ident::new(&format!("{name}Shadow"), name.span());
-
-    let wrap_expr = match original_fields {
-        syn::Fields::Unnamed(_) => quote! { Self(inner) },
+    let wrap = |inner: TokenStream2| match original_fields {
+        syn::Fields::Unnamed(_) => quote! { Self(#inner) },
         syn::Fields::Named(_) => {
             let ident = field.access.ident().expect("expected named field ident for transparent struct");
-            quote! { Self { #ident: inner } }
+            quote! { Self { #ident: #inner } }
         }
         syn::Fields::Unit => quote! { Self },
     };
+    generate_transparent_impl(
+        name,
+        vis,
+        generics,
+        impl_generics,
+        ty_generics,
+        where_clause,
+        &field.field.ty,
+        &|base| field.access.access_tokens(base),
+        &wrap,
+        range,
+    )
+}
 
-    let default_expr = match original_fields {
-        syn::Fields::Unnamed(_) => quote! { Self(<#inner_ty as ::proto_rs::ProtoDefault>::proto_default()) },
-        syn::Fields::Named(_) => {
-            let ident = field.access.ident().expect("expected named field ident for transparent struct");
-            quote! { Self { #ident: <#inner_ty as ::proto_rs::ProtoDefault>::proto_default() } }
-        }
-        syn::Fields::Unit => quote! { Self },
+/// Impls for a type encoded exactly like the single value it wraps. `access` projects the value
+/// out of a `Self` place and `wrap` builds `Self` around one. Decoded values outside `range` are
+/// rejected.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn generate_transparent_impl(
+    name: &syn::Ident,
+    vis: &syn::Visibility,
+    generics: &syn::Generics,
+    impl_generics: &syn::ImplGenerics,
+    ty_generics: &syn::TypeGenerics,
+    where_clause: Option<&syn::WhereClause>,
+    inner_ty: &Type,
+    access: &dyn Fn(TokenStream2) -> TokenStream2,
+    wrap: &dyn Fn(TokenStream2) -> TokenStream2,
+    range: Option<&str>,
+) -> TokenStream2 {
+    let mut_value_access = access(quote! { value });
+    let mut_self_access = access(quote! { self });
+    let range_check = |target: &TokenStream2| {
+        range.map(|range| {
+            let range_expr: syn::ExprRange = syn::parse_str(range).expect("range is checked when parsing attributes");
+            let message = format!("{name} value is out of range {range}");
+            quote! {
+                if !(#range_expr).contains(&#target) {
+                    return Err(::proto_rs::DecodeError::new(#message));
+                }
+            }
+        })
     };
+    let value_check = range_check(&mut_value_access);
+    let self_check = range_check(&mut_self_access);
+    let inner_check = range_check(&quote! { inner });
+    let shadow_ident = syn::Ident::new(&format!("{name}Shadow"), name.span());
+
+    let wrap_expr = wrap(quote! { inner });
+    let default_expr = wrap(quote! { <#inner_ty as ::proto_rs::ProtoDefault>::proto_default() });
 
     let shadow_ty = quote! { <#inner_ty as ::proto_rs::ProtoEncode>::Shadow<'a> };
     let mut shadow_generics = generics.clone();
@@ -334,6 +454,7 @@ fn generate_transparent_struct_impl(
 
         impl #shadow_impl_generics ::proto_rs::ProtoExt for #shadow_ident #shadow_ty_generics #shadow_where_clause {
             const KIND: ::proto_rs::ProtoKind = <#shadow_ty as ::proto_rs::ProtoExt>::KIND;
+            const MAX_PAYLOAD_LEN: Option<usize> = <#shadow_ty as ::proto_rs::ProtoExt>::MAX_PAYLOAD_LEN;
         }
 
         impl #shadow_impl_generics ::proto_rs::ProtoShadowEncode<'a, #name #ty_generics> for #shadow_ident #shadow_ty_generics #shadow_where_clause {
@@ -353,10 +474,16 @@ fn generate_transparent_struct_impl(
             fn archive<const TAG: u32>(&self, w: &mut impl ::proto_rs::RevWriter) {
                 <#shadow_ty as ::proto_rs::ProtoArchive>::archive::<TAG>(&self.0, w);
             }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                <#shadow_ty as ::proto_rs::ProtoArchive>::size_hint::<TAG>(&self.0)
+            }
         }
 
         impl #impl_generics ::proto_rs::ProtoExt for #name #ty_generics #where_clause {
             const KIND: ::proto_rs::ProtoKind = <#inner_ty as ::proto_rs::ProtoExt>::KIND;
+            const MAX_PAYLOAD_LEN: Option<usize> = <#inner_ty as ::proto_rs::ProtoExt>::MAX_PAYLOAD_LEN;
         }
 
         impl #impl_generics ::proto_rs::ProtoDecoder for #name #ty_generics #where_clause {
@@ -368,12 +495,16 @@ fn generate_transparent_struct_impl(
                 buf: &mut impl ::proto_rs::bytes::Buf,
                 ctx: ::proto_rs::encoding::DecodeContext,
             ) -> Result<(), ::proto_rs::DecodeError> {
-                <#inner_ty as ::proto_rs::ProtoDecoder>::merge_field(&mut #mut_value_access, tag, wire_type, buf, ctx)
+                <#inner_ty as ::proto_rs::ProtoDecoder>::merge_field(&mut #mut_value_access, tag, wire_type, buf, ctx)?;
+                #value_check
+                Ok(())
             }
 
             #[inline]
             fn merge(&mut self, wire_type: ::proto_rs::encoding::WireType, buf: &mut impl ::proto_rs::bytes::Buf, ctx: ::proto_rs::encoding::DecodeContext) -> Result<(), ::proto_rs::DecodeError> {
-                <#inner_ty as ::proto_rs::ProtoDecoder>::merge(&mut #mut_self_access, wire_type, buf, ctx)
+                <#inner_ty as ::proto_rs::ProtoDecoder>::merge(&mut #mut_self_access, wire_type, buf, ctx)?;
+                #self_check
+                Ok(())
             }
         }
 
@@ -395,11 +526,13 @@ fn generate_transparent_struct_impl(
                 if <#inner_ty as ::proto_rs::ProtoExt>::WIRE_TYPE.is_length_delimited() {
                     // Message type - decode using standard message decoding
                     let inner = <#inner_ty as ::proto_rs::ProtoDecode>::decode(buf, ctx)?;
+                    #inner_check
                     Ok(#wrap_expr)
                 } else {
                     // Primitive type - read raw value using merge
                     let mut inner = <#inner_ty as ::proto_rs::ProtoDefault>::proto_default();
                     <#inner_ty as ::proto_rs::ProtoDecoder>::merge(&mut inner, <#inner_ty as ::proto_rs::ProtoExt>::WIRE_TYPE, &mut buf, ctx)?;
+                    #inner_check
                     Ok(#wrap_expr)
                 }
             }
@@ -415,6 +548,26 @@ fn generate_transparent_struct_impl(
         impl #impl_generics ::proto_rs::ProtoEncode for #name #ty_generics #where_clause {
             type Shadow<'a> = #shadow_ident #shadow_ty_generics;
         }
+
+        impl #impl_generics ::proto_rs::ProtoArchive for #name #ty_generics #where_clause {
+            #[inline]
+            fn is_default(&self) -> bool {
+                let shadow = <<Self as ::proto_rs::ProtoEncode>::Shadow<'_> as ::proto_rs::ProtoShadowEncode<'_, Self>>::from_sun(self);
+                ::proto_rs::ProtoArchive::is_default(&shadow)
+            }
+
+            #[inline]
+            fn archive<const TAG: u32>(&self, w: &mut impl ::proto_rs::RevWriter) {
+                let shadow = <<Self as ::proto_rs::ProtoEncode>::Shadow<'_> as ::proto_rs::ProtoShadowEncode<'_, Self>>::from_sun(self);
+                ::proto_rs::ProtoArchive::archive::<TAG>(&shadow, w);
+            }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                let shadow = <<Self as ::proto_rs::ProtoEncode>::Shadow<'_> as ::proto_rs::ProtoShadowEncode<'_, Self>>::from_sun(self);
+                ::proto_rs::ProtoArchive::size_hint::<TAG>(&shadow)
+            }
+        }
     }
 }
 
@@ -426,6 +579,7 @@ fn generate_shadow_impls(
     vis: &syn::Visibility,
     original_fields: &syn::Fields,
     fields: &[FieldInfo<'_>],
+    extension_field: Option<&FieldInfo<'_>>,
     generics: &syn::Generics,
     ty_generics: &syn::TypeGenerics,
     use_getters: bool,
@@ -441,8 +595,9 @@ fn generate_shadow_impls(
         .iter()
         .map(|info| {
             let shadow_ty = shadow_field_ty(info);
+            let cfg = info.cfg_attr();
             match info.access {
-                FieldAccess::Named(ident) => quote! { #ident: #shadow_ty },
+                FieldAccess::Named(ident) => quote! { #cfg #ident: #shadow_ty },
                 FieldAccess::Direct(_) | FieldAccess::Tuple(_) => quote! { #shadow_ty },
             }
         })
@@ -452,13 +607,21 @@ fn generate_shadow_impls(
         .iter()
         .map(|info| {
             let init = shadow_field_init(info, use_getters);
+            let cfg = info.cfg_attr();
             match info.access {
-                FieldAccess::Named(ident) => quote! { #ident: #init },
+                FieldAccess::Named(ident) => quote! { #cfg #ident: #init },
                 FieldAccess::Direct(_) | FieldAccess::Tuple(_) => quote! { #init },
             }
         })
         .collect::<Vec<_>>();
 
+    // Extension data is kept as encoded, so the shadow borrows the set as is.
+    let extension_ident = extension_field.and_then(|info| info.access.ident());
+    if let Some(ident) = extension_ident {
+        shadow_field_defs.push(quote! { #ident: &'a ::proto_rs::ExtensionSet });
+        shadow_init_fields.push(quote! { #ident: &value.#ident });
+    }
+
     let shadow_struct = match original_fields {
         syn::Fields::Named(_) => {
             shadow_field_defs.push(quote! { #phantom_ident: ::core::marker::PhantomData<&'a ()> });
@@ -493,29 +656,107 @@ fn generate_shadow_impls(
         syn::Fields::Unit => quote! { Self { #phantom_ident: ::core::marker::PhantomData } },
     };
 
-    let archive_fields = encoded_fields.iter().rev().map(|info| {
+    // A tuple shadow only holds the encoded fields, so skipped fields shift the positions after them.
+    let shadow_access = |info: &FieldInfo<'_>| match info.access {
+        FieldAccess::Tuple(_) => {
+            let position = encoded_fields.iter().position(|encoded| encoded.index == info.index).expect("encoded field");
+            let index = syn::Index::from(position);
+            quote! { self.#index }
+        }
+        _ => info.access.access_tokens(quote! { self }),
+    };
+
+    // Written back to front, so hot fields lead the message and cold ones trail it.
+    let archive_fields = in_heat_order(encoded_fields.iter().copied()).into_iter().rev().map(|info| {
+        let tag = info.tag.expect("tag required");
+        let shadow_ty = shadow_field_ty(info);
+        let access = shadow_access(info);
+        let cfg = info.cfg_attr();
+        if info.config.offset_index.is_some() {
+            let distances = offset_distances_ident(info);
+            return quote! { let #distances = ::proto_rs::indexed::archive_indexed::<#tag, _, _>(#access, mark, w); };
+        }
+        quote! { #cfg ::proto_rs::ArchivedProtoField::<#tag, #shadow_ty>::archive(&#access, w); }
+    });
+
+    // Offsets indexes lead the message, so readers find them first.
+    let indexed_fields: Vec<&FieldInfo<'_>> = encoded_fields.iter().copied().filter(|info| info.config.offset_index.is_some()).collect();
+    let archive_offsets = indexed_fields.iter().map(|info| {
+        let index_tag = info.config.offset_index.expect("indexed field");
+        let distances = offset_distances_ident(info);
+        quote! { ::proto_rs::indexed::archive_offsets(#index_tag, &#distances, w); }
+    });
+    let offsets_size_hints = indexed_fields.iter().map(|info| {
+        let index_tag = info.config.offset_index.expect("indexed field");
+        let access = shadow_access(info);
+        quote! { + ::proto_rs::indexed::offsets_size_hint(#index_tag, #access.len()) }
+    });
+
+    // Conditional fields cannot sit inside an expression, so they are checked and summed in
+    // statements of their own.
+    let (conditional_fields, unconditional_fields): (Vec<&FieldInfo<'_>>, Vec<&FieldInfo<'_>>) =
+        encoded_fields.iter().copied().partition(|info| info.config.cfg_predicate().is_some());
+
+    let size_hint_fields = unconditional_fields.iter().map(|info| {
         let tag = info.tag.expect("tag required");
         let shadow_ty = shadow_field_ty(info);
-        let access = info.access.access_tokens(quote! { self });
-        quote! { ::proto_rs::ArchivedProtoField::<#tag, #shadow_ty>::archive(&#access, w); }
+        let access = shadow_access(info);
+        quote! { + ::proto_rs::ArchivedProtoField::<#tag, #shadow_ty>::size_hint(&#access)? }
     });
 
-    let is_default_checks = encoded_fields.iter().map(|info| {
-        let access = info.access.access_tokens(quote! { self });
+    let conditional_size_hints = conditional_fields.iter().map(|info| {
+        let tag = info.tag.expect("tag required");
+        let shadow_ty = shadow_field_ty(info);
+        let access = shadow_access(info);
+        let cfg = info.cfg_attr();
+        quote! {
+            #cfg
+            let payload = payload + ::proto_rs::ArchivedProtoField::<#tag, #shadow_ty>::size_hint(&#access)?;
+        }
+    });
+
+    let is_default_checks = unconditional_fields.iter().map(|info| {
+        let access = shadow_access(info);
         quote! { ::proto_rs::ProtoArchive::is_default(&#access) }
     });
 
-    let is_default_expr = if encoded_fields.is_empty() {
+    let conditional_is_default_checks = conditional_fields.iter().map(|info| {
+        let access = shadow_access(info);
+        let cfg = info.cfg_attr();
+        quote! {
+            #cfg
+            if !::proto_rs::ProtoArchive::is_default(&#access) {
+                return false;
+            }
+        }
+    });
+
+    let is_default_expr = if unconditional_fields.is_empty() {
         quote! { true }
     } else {
         quote! { #( #is_default_checks )&&* }
     };
 
+    // Extension fields are written first, so they trail the declared ones in the encoding.
+    let (archive_extensions, extensions_size_hint, extensions_is_default) = match extension_ident {
+        Some(ident) => (
+            quote! { self.#ident.archive(w); },
+            quote! { let payload = payload + self.#ident.encoded_len(); },
+            quote! {
+                if !self.#ident.is_empty() {
+                    return false;
+                }
+            },
+        ),
+        None => (quote! {}, quote! {}, quote! {}),
+    };
+
     quote! {
         #shadow_struct
 
         impl #shadow_impl_generics ::proto_rs::ProtoExt for #shadow_ident #shadow_ty_generics #shadow_where_clause {
             const KIND: ::proto_rs::ProtoKind = ::proto_rs::ProtoKind::Message;
+            const MAX_PAYLOAD_LEN: Option<usize> = <#proto_ident #ty_generics as ::proto_rs::ProtoExt>::MAX_PAYLOAD_LEN;
         }
 
         impl #shadow_impl_generics ::proto_rs::ProtoShadowEncode<'a, #proto_ident #ty_generics> for #shadow_ident #shadow_ty_generics #shadow_where_clause {
@@ -528,19 +769,31 @@ fn generate_shadow_impls(
         impl #shadow_impl_generics ::proto_rs::ProtoArchive for #shadow_ident #shadow_ty_generics #shadow_where_clause {
             #[inline]
             fn is_default(&self) -> bool {
+                #extensions_is_default
+                #( #conditional_is_default_checks )*
                 #is_default_expr
             }
 
             #[inline]
             fn archive<const TAG: u32>(&self, w: &mut impl ::proto_rs::RevWriter) {
                 let mark = w.mark();
+                #archive_extensions
                 #( #archive_fields )*
+                #( #archive_offsets )*
                 if TAG != 0 {
                     let payload_len = w.written_since(mark);
                     w.put_varint(payload_len as u64);
                     ::proto_rs::ArchivedProtoField::<TAG, Self>::put_key(w);
                 }
             }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                let payload = 0 #( #size_hint_fields )* #( #offsets_size_hints )*;
+                #( #conditional_size_hints )*
+                #extensions_size_hint
+                Some(::proto_rs::ArchivedProtoField::<TAG, Self>::hint_delimited(payload))
+            }
         }
     }
 }
@@ -554,10 +807,39 @@ fn generate_proto_impls(
     ty_generics: &syn::TypeGenerics,
     where_clause: Option<&syn::WhereClause>,
     fields: &[FieldInfo<'_>],
+    extension_field: Option<&FieldInfo<'_>>,
     original_fields: &syn::Fields,
     config: &UnifiedProtoConfig,
 ) -> TokenStream2 {
-    let decode_arms = build_decode_match_arms(fields, &quote! { value });
+    let mut decode_arms = build_decode_match_arms(fields, &quote! { value });
+    let extendable_impl = extension_field.map(|info| {
+        let ident = info.access.ident().expect("extension fields are named");
+        let ranges: Vec<TokenStream2> = info.config.extensions.iter().map(|(start, end)| quote! { (#start, #end) }).collect();
+        decode_arms.extend(info.config.extensions.iter().map(|(start, end)| {
+            quote! { #start..=#end => value.#ident.merge_field(tag, wire_type, buf, ctx) }
+        }));
+        quote! {
+            impl #impl_generics ::proto_rs::Extendable for #name #ty_generics #where_clause {
+                const EXTENSION_RANGES: &'static [(u32, u32)] = &[#( #ranges ),*];
+
+                #[inline]
+                fn extensions(&self) -> &::proto_rs::ExtensionSet {
+                    &self.#ident
+                }
+
+                #[inline]
+                fn extensions_mut(&mut self) -> &mut ::proto_rs::ExtensionSet {
+                    &mut self.#ident
+                }
+            }
+        }
+    });
+    let name_str = name.to_string();
+    let field_name_arms = fields.iter().filter_map(|info| {
+        let tag = info.tag?;
+        let field_name = info.access.ident().map_or_else(|| info.index.to_string(), ToString::to_string);
+        Some(quote! { #tag => #field_name })
+    });
     let proto_default_expr = build_proto_default_expr(fields, original_fields);
     let post_decode_hooks = build_post_decode_hooks(fields);
     let validate_with_ext_impl = build_validate_with_ext_impl(config);
@@ -608,6 +890,12 @@ fn generate_proto_impls(
                     let shadow = <#shadow_ty_short as ::proto_rs::ProtoShadowEncode<'_, #name #ty_generics>>::from_sun(self);
                     <#shadow_ty_short as ::proto_rs::ProtoArchive>::archive::<TAG>(&shadow, w);
                 }
+
+                #[inline]
+                fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                    let shadow = <#shadow_ty_short as ::proto_rs::ProtoShadowEncode<'_, #name #ty_generics>>::from_sun(self);
+                    <#shadow_ty_short as ::proto_rs::ProtoArchive>::size_hint::<TAG>(&shadow)
+                }
             }
         }
     };
@@ -638,13 +926,15 @@ fn generate_proto_impls(
                     let encoded_fields: Vec<_> = fields.iter().filter(|info| info.tag.is_some()).collect();
                     let is_default_checks = encoded_fields.iter().map(|info| {
                         let base = quote! { self };
-                        let (access_expr, getter_is_ref) = if has_getters && let Some(get) = &info.config.getter {
+                        let (access_expr, _getter_is_ref) = if has_getters && let Some(get) = &info.config.getter {
                             parse_getter_expr(get, &base, info.field)
                         } else {
                             (info.access.access_tokens(base), false)
                         };
                         let shadow_ty = shadow_field_ty_with_lifetime(info, &shadow_lifetime);
-                        let shadow_init = if needs_encode_conversion(&info.config, &info.parsed) {
+                        let shadow_init = if info.config.is_view {
+                            view_shadow_init(info, &access_expr)
+                        } else if needs_encode_conversion(&info.config, &info.parsed) {
                             let ref_expr = quote! { #access_expr };
                             let converted = encode_conversion_expr(info, &ref_expr);
                             quote! { let __proto_shadow = #converted; }
@@ -656,25 +946,33 @@ fn generate_proto_impls(
                                     <#shadow_ty as ::proto_rs::ProtoShadowEncode<#shadow_lifetime, #field_ty>>::from_sun(&__proto_value);
                             }
                         };
+                        // A view is already borrowed
+                        let shadow_ref = if info.config.is_view {
+                            quote! { __proto_shadow }
+                        } else {
+                            quote! { &__proto_shadow }
+                        };
                         quote! {
                             {
                                 #shadow_init
-                                if !::proto_rs::ProtoArchive::is_default(&__proto_shadow) {
+                                if !::proto_rs::ProtoArchive::is_default(#shadow_ref) {
                                     return false;
                                 }
                             }
                         }
                     });
-                    let archive_fields = encoded_fields.iter().rev().map(|info| {
+                    let archive_fields = in_heat_order(encoded_fields.iter().copied()).into_iter().rev().map(|info| {
                         let tag = info.tag.expect("tag required");
                         let base = quote! { self };
-                        let (access_expr, getter_is_ref) = if has_getters && let Some(get) = &info.config.getter {
+                        let (access_expr, _getter_is_ref) = if has_getters && let Some(get) = &info.config.getter {
                             parse_getter_expr(get, &base, info.field)
                         } else {
                             (info.access.access_tokens(base), false)
                         };
                         let shadow_ty = shadow_field_ty_with_lifetime(info, &shadow_lifetime);
-                        let shadow_init = if needs_encode_conversion(&info.config, &info.parsed) {
+                        let shadow_init = if info.config.is_view {
+                            view_shadow_init(info, &access_expr)
+                        } else if needs_encode_conversion(&info.config, &info.parsed) {
                             let ref_expr = quote! { #access_expr };
                             let converted = encode_conversion_expr(info, &ref_expr);
                             quote! { let __proto_shadow = #converted; }
@@ -686,10 +984,15 @@ fn generate_proto_impls(
                                     <#shadow_ty as ::proto_rs::ProtoShadowEncode<#shadow_lifetime, #field_ty>>::from_sun(&__proto_value);
                             }
                         };
+                        let archive = if info.config.is_view {
+                            quote! { ::proto_rs::ArchivedProtoField::<#tag, _>::archive(__proto_shadow, w); }
+                        } else {
+                            quote! { ::proto_rs::ArchivedProtoField::<#tag, #shadow_ty>::archive(&__proto_shadow, w); }
+                        };
                         quote! {
                             {
                                 #shadow_init
-                                ::proto_rs::ArchivedProtoField::<#tag, #shadow_ty>::archive(&__proto_shadow, w);
+                                #archive
                             }
                         }
                     });
@@ -834,9 +1137,37 @@ fn generate_proto_impls(
         quote! {}
     };
 
+    let offset_index_accessors = offset_index_accessors(fields);
+    let offset_index_impl = if offset_index_accessors.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            impl #impl_generics #name #ty_generics #where_clause {
+                #( #offset_index_accessors )*
+            }
+        }
+    };
+
+    // Extension data has no bound
+    let max_payload_len = if extension_field.is_some() {
+        quote! { None }
+    } else {
+        max_payload_len_expr(fields)
+    };
+
     quote! {
         impl #impl_generics ::proto_rs::ProtoExt for #name #ty_generics #where_clause {
             const KIND: ::proto_rs::ProtoKind = ::proto_rs::ProtoKind::Message;
+            const MAX_PAYLOAD_LEN: Option<usize> = #max_payload_len;
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Largest encoding of any value of this message, for sizing stack buffers. Using it
+            /// fails to compile when a field has no bound, such as a string or a `Vec`.
+            pub const MAX_ENCODED_LEN: usize = match <Self as ::proto_rs::ProtoExt>::MAX_PAYLOAD_LEN {
+                Some(len) => len,
+                None => panic!(concat!(#name_str, " has fields without a maximum encoded length")),
+            };
         }
 
         impl #impl_generics ::proto_rs::ProtoDecoder for #name #ty_generics #where_clause {
@@ -848,10 +1179,19 @@ fn generate_proto_impls(
                 buf: &mut impl ::proto_rs::bytes::Buf,
                 ctx: ::proto_rs::encoding::DecodeContext,
             ) -> Result<(), ::proto_rs::DecodeError> {
-                match tag {
-                    #(#decode_arms,)*
-                    _ => ::proto_rs::encoding::skip_field(wire_type, tag, buf, ctx),
-                }
+                // Arms return early with `?`, so they run in a closure to let the field name reach
+                // unknown enum values.
+                #[allow(clippy::redundant_closure_call)]
+                let result = (|| -> Result<(), ::proto_rs::DecodeError> {
+                    match tag {
+                        #(#decode_arms,)*
+                        _ => ::proto_rs::encoding::skip_field(wire_type, tag, buf, ctx),
+                    }
+                })();
+                result.map_err(|err| err.report_unknown_enum(#name_str, match tag {
+                    #(#field_name_arms,)*
+                    _ => "",
+                }))
             }
         }
 
@@ -880,9 +1220,68 @@ fn generate_proto_impls(
         #proto_archive_impl
 
         #sun_impls
+
+        #extendable_impl
+
+        #offset_index_impl
     }
 }
 
+/// Sum of the largest encodings of the encoded fields, evaluated at compile time.
+fn max_payload_len_expr(fields: &[FieldInfo<'_>]) -> TokenStream2 {
+    let field_lens = fields.iter().filter_map(|info| {
+        let tag = info.tag?;
+        let ty = if needs_encode_conversion(&info.config, &info.parsed) {
+            &info.proto_ty
+        } else {
+            &info.field.ty
+        };
+        let cfg = info.cfg_attr();
+        Some(quote! {
+            #cfg
+            let len = ::proto_rs::add_field_max_len::<#ty>(len, #tag);
+        })
+    });
+    quote! {{
+        let len = Some(0);
+        #( #field_lens )*
+        len
+    }}
+}
+
+fn offset_distances_ident(info: &FieldInfo<'_>) -> syn::Ident {
+    syn::Ident::new(&format!("__proto_offsets_{}", info.index), proc_macro2::Span::call_site())
+}
+
+/// `{field}_at(buf, idx)` and `{field}_len(buf)` reading `#[proto(offset_index)]` fields of an
+/// encoded message.
+fn offset_index_accessors(fields: &[FieldInfo<'_>]) -> Vec<TokenStream2> {
+    fields
+        .iter()
+        .filter_map(|info| {
+            let index_tag = info.config.offset_index?;
+            let tag = info.tag.expect("indexed fields are encoded");
+            let element_ty = vec_inner_type(&info.field.ty).expect("indexed fields are vectors");
+            let name = info.access.ident().map_or_else(|| format!("field_{}", info.index), ToString::to_string);
+            let at = syn::Ident::new(&format!("{name}_at"), proc_macro2::Span::call_site());
+            let len = syn::Ident::new(&format!("{name}_len"), proc_macro2::Span::call_site());
+            let at_doc = format!(" Element `idx` of `{name}` in an encoded message, decoding only that element.");
+            let len_doc = format!(" Number of elements of `{name}` in an encoded message.");
+            Some(quote! {
+                #[doc = #at_doc]
+                pub fn #at(buf: &[u8], idx: usize) -> Result<Option<#element_ty>, ::proto_rs::DecodeError> {
+                    ::proto_rs::indexed::get_repeated_element::<#element_ty>(buf, #tag, #index_tag, idx)
+                }
+
+                #[doc = #len_doc]
+                pub fn #len(buf: &[u8]) -> Result<usize, ::proto_rs::DecodeError> {
+                    ::proto_rs::indexed::repeated_len(buf, #tag, #index_tag)
+                }
+            })
+        })
+        .collect()
+}
+
 fn shadow_field_ty(info: &FieldInfo<'_>) -> TokenStream2 {
     shadow_field_ty_with_lifetime(info, &quote! { 'a })
 }
@@ -892,8 +1291,16 @@ fn shadow_field_ty_with_lifetime(info: &FieldInfo<'_>, lifetime: &TokenStream2)
         let proto_ty = &info.proto_ty;
         quote! { #proto_ty }
     } else {
-        let field_ty = &info.field.ty;
-        quote! { <#field_ty as ::proto_rs::ProtoEncode>::Shadow<#lifetime> }
+        direct_shadow_ty(info, lifetime)
+    }
+}
+
+/// `sun_ir` read of a `#[proto(view)]` field: borrows the view, checked against the field type.
+fn view_shadow_init(info: &FieldInfo<'_>, access_expr: &TokenStream2) -> TokenStream2 {
+    let field_ty = &info.field.ty;
+    quote! {
+        let __proto_value = &#access_expr;
+        let __proto_shadow = <_ as ::proto_rs::ProtoView<#field_ty>>::as_view(__proto_value);
     }
 }
 
@@ -1034,18 +1441,13 @@ fn shadow_type_tokens_with_lifetime(generics: &syn::Generics, shadow_ident: &syn
 
 fn sanitize_struct(mut item: ItemStruct) -> ItemStruct {
     item.attrs = strip_proto_attrs(&item.attrs);
-    match &mut item.fields {
-        syn::Fields::Named(named) => {
-            for field in &mut named.named {
-                field.attrs = strip_proto_attrs(&field.attrs);
-            }
-        }
-        syn::Fields::Unnamed(unnamed) => {
-            for field in &mut unnamed.unnamed {
-                field.attrs = strip_proto_attrs(&field.attrs);
-            }
+    for field in &mut item.fields {
+        let proto_cfg = parse_field_config(field).cfg;
+        field.attrs = strip_proto_attrs(&field.attrs);
+        if let Some(predicate) = proto_cfg {
+            let predicate: TokenStream2 = predicate.parse().expect("cfg predicates are validated when parsed");
+            field.attrs.push(parse_quote! { #[cfg(#predicate)] });
         }
-        syn::Fields::Unit => {}
     }
     item
 }
@@ -11,8 +11,14 @@ use syn::Type;
 use syn::parse_quote;
 use syn::spanned::Spanned;
 
+use crate::utils::DuplicatePolicy;
 use crate::utils::FieldConfig;
+use crate::utils::FieldHeat;
+use crate::utils::OverflowPolicy;
 use crate::utils::ParsedFieldType;
+use crate::utils::Utf8Policy;
+use crate::utils::is_bytes_array;
+use crate::utils::set_inner_type;
 
 #[derive(Clone)]
 pub struct FieldInfo<'a> {
@@ -26,6 +32,13 @@ pub struct FieldInfo<'a> {
     pub decode_ty: Type,
 }
 
+impl FieldInfo<'_> {
+    /// `#[cfg(...)]` for code touching a conditional field; empty for unconditional fields.
+    pub fn cfg_attr(&self) -> TokenStream2 {
+        self.config.cfg_predicate().map(|predicate| quote! { #[cfg(#predicate)] }).unwrap_or_default()
+    }
+}
+
 #[derive(Clone)]
 pub enum FieldAccess<'a> {
     Named(&'a Ident),
@@ -113,6 +126,70 @@ pub(super) fn uses_proto_wire_directly(info: &FieldInfo<'_>) -> bool {
         && info.config.try_from_fn.is_none()
 }
 
+/// Shadow type used to encode a field that goes over the wire as-is.
+pub fn direct_shadow_ty(info: &FieldInfo<'_>, lifetime: &TokenStream2) -> TokenStream2 {
+    let field_ty = &info.field.ty;
+    if info.config.on_duplicate == DuplicatePolicy::Collect {
+        quote! { ::proto_rs::CollectedMap<#lifetime, #field_ty> }
+    } else {
+        quote! { <#field_ty as ::proto_rs::ProtoEncode>::Shadow<#lifetime> }
+    }
+}
+
+/// Merge statement for a field decoded straight into its storage, honoring `on_duplicate`, `utf8` and `on_overflow`.
+/// Fixed arrays read their whole run of elements at once, which needs the field tag; maps and sets get the tag to do the same.
+pub fn direct_merge_stmt(info: &FieldInfo<'_>, access: &TokenStream2, wire_type: &TokenStream2, ctx: &TokenStream2) -> TokenStream2 {
+    let field_ty = &info.field.ty;
+    if let Some(conflict) = info.config.merge_policy_conflict() {
+        return syn::Error::new_spanned(info.field, conflict).to_compile_error();
+    }
+    if info.config.utf8 == Utf8Policy::Lossy {
+        return quote! {
+            <#field_ty as ::proto_rs::ProtoLossyMerge>::merge_lossy(&mut #access, #wire_type, buf, #ctx)?;
+        };
+    }
+    match info.config.on_overflow {
+        OverflowPolicy::Error => {}
+        OverflowPolicy::Saturate => {
+            return quote! {
+                <#field_ty as ::proto_rs::ProtoNarrowMerge>::merge_saturating(&mut #access, #wire_type, buf, #ctx)?;
+            };
+        }
+        OverflowPolicy::Wrap => {
+            return quote! {
+                <#field_ty as ::proto_rs::ProtoNarrowMerge>::merge_wrapping(&mut #access, #wire_type, buf, #ctx)?;
+            };
+        }
+    }
+    if let (Type::Array(_), Some(tag)) = (field_ty, info.tag)
+        && info.config.on_duplicate == DuplicatePolicy::LastWins
+        && !is_bytes_array(field_ty)
+    {
+        return quote! {
+            <#field_ty as ::proto_rs::ProtoArrayMerge>::merge_array::<#tag>(&mut #access, #wire_type, buf, #ctx)?;
+        };
+    }
+    if let Some(tag) = info.tag
+        && info.config.on_duplicate == DuplicatePolicy::LastWins
+        && (info.parsed.map_kind.is_some() || set_inner_type(field_ty).is_some())
+    {
+        return quote! {
+            <#field_ty as ::proto_rs::ProtoFieldMerge>::merge_run::<#tag>(&mut #access, #wire_type, buf, #ctx)?;
+        };
+    }
+    match info.config.on_duplicate {
+        DuplicatePolicy::LastWins => quote! {
+            <#field_ty as ::proto_rs::ProtoFieldMerge>::merge_value(&mut #access, #wire_type, buf, #ctx)?;
+        },
+        DuplicatePolicy::Error => quote! {
+            <#field_ty as ::proto_rs::ProtoUniqueMerge>::merge_unique(&mut #access, #wire_type, buf, #ctx)?;
+        },
+        DuplicatePolicy::Collect => quote! {
+            <#field_ty as ::proto_rs::ProtoCollectMerge>::merge_collect(&mut #access, #wire_type, buf, #ctx)?;
+        },
+    }
+}
+
 pub fn strip_proto_attrs(attrs: &[Attribute]) -> Vec<Attribute> {
     attrs.iter().filter(|attr| !attr.path().is_ident("proto_message") && !attr.path().is_ident("proto")).cloned().collect()
 }
@@ -186,7 +263,8 @@ pub fn build_proto_default_expr(fields: &[FieldInfo<'_>], original: &syn::Fields
                 let defaults = fields.iter().map(|info| {
                     let ident = info.access.ident().expect("expected named field ident");
                     let expr = field_proto_default_expr(info);
-                    quote! { #ident: #expr }
+                    let cfg = info.cfg_attr();
+                    quote! { #cfg #ident: #expr }
                 });
                 quote! { Self { #(#defaults),* } }
             }
@@ -198,6 +276,10 @@ pub fn field_proto_default_expr(info: &FieldInfo<'_>) -> TokenStream2 {
     if uses_proto_wire_directly(info) {
         let ty = &info.field.ty;
         quote! { <#ty as ::proto_rs::ProtoDefault>::proto_default() }
+    } else if info.config.is_result {
+        let ty = &info.field.ty;
+        let proto_ty = &info.proto_ty;
+        quote! { <#ty as ::core::convert::From<#proto_ty>>::from(<#proto_ty as ::proto_rs::ProtoDefault>::proto_default()) }
     } else {
         quote! { ::core::default::Default::default() }
     }
@@ -266,7 +348,9 @@ pub fn build_post_decode_hooks(fields: &[FieldInfo<'_>]) -> Vec<TokenStream2> {
             }
             let fun_path = parse_path_string(info.field, fun);
             let access = info.access.access_tokens(quote! { shadow });
+            let cfg = info.cfg_attr();
             Some(quote! {
+                #cfg
                 {
                     let __proto_rs_tmp = #fun_path(&mut shadow);
                     #access = __proto_rs_tmp;
@@ -276,11 +360,77 @@ pub fn build_post_decode_hooks(fields: &[FieldInfo<'_>]) -> Vec<TokenStream2> {
         .collect()
 }
 
-pub fn build_decode_match_arms(fields: &[FieldInfo<'_>], base: &TokenStream2) -> Vec<TokenStream2> {
+/// `fields` with `#[proto(hot)]` ones first and `#[proto(cold)]` ones last, otherwise in declaration order.
+pub fn in_heat_order<'a, 'b>(fields: impl IntoIterator<Item = &'b FieldInfo<'a>>) -> Vec<&'b FieldInfo<'a>> {
+    let mut fields: Vec<_> = fields.into_iter().collect();
+    fields.sort_by_key(|info| info.config.heat);
     fields
-        .iter()
-        .filter_map(|info| {
-            let tag = info.tag?;
+}
+
+/// Messages with at least this many tagged fields dispatch contiguous tag runs through dense inner matches.
+const DENSE_DISPATCH_MIN_FIELDS: usize = 32;
+/// Shortest run of consecutive tags worth its own dense match.
+const DENSE_RUN_MIN_LEN: usize = 4;
+
+pub fn build_decode_match_arms(fields: &[FieldInfo<'_>], base: &TokenStream2) -> Vec<TokenStream2> {
+    let arm = |info: &FieldInfo<'_>, body: &TokenStream2| {
+        let tag = info.tag.expect("decoded fields have tags");
+        let cfg = info.cfg_attr();
+        quote! {
+            #cfg
+            #tag => #body
+        }
+    };
+    let bodies = build_decode_arm_bodies(fields, base);
+    if bodies.len() < DENSE_DISPATCH_MIN_FIELDS {
+        return bodies.iter().map(|(info, body)| arm(info, body)).collect();
+    }
+
+    // Wide messages: each run of consecutive tags becomes one range arm over a match in tag order,
+    // which the backend lowers to a jump table, so the outer match only has to pick a run. Hot fields
+    // stay out of the runs and keep their own arms ahead of them; cold fields outside a run stay last.
+    let (hot, mut bodies): (Vec<_>, Vec<_>) = bodies.into_iter().partition(|(info, _)| info.config.heat == FieldHeat::Hot);
+    bodies.sort_by_key(|(info, _)| info.tag);
+    let mut arms: Vec<_> = hot.iter().map(|(info, body)| arm(info, body)).collect();
+    let mut cold_arms = Vec::new();
+    let mut start = 0;
+    while start < bodies.len() {
+        let mut end = start + 1;
+        while end < bodies.len() && bodies[end].0.tag == bodies[end - 1].0.tag.map(|tag| tag + 1) {
+            end += 1;
+        }
+        let run = &bodies[start..end];
+        if run.len() < DENSE_RUN_MIN_LEN {
+            for (info, body) in run {
+                if info.config.heat == FieldHeat::Cold {
+                    cold_arms.push(arm(info, body));
+                } else {
+                    arms.push(arm(info, body));
+                }
+            }
+        } else {
+            let first = run[0].0.tag.expect("decoded fields have tags");
+            let last = run[run.len() - 1].0.tag.expect("decoded fields have tags");
+            let inner = run.iter().map(|(info, body)| arm(info, body));
+            arms.push(quote! {
+                #first..=#last => match tag {
+                    #(#inner,)*
+                    _ => ::proto_rs::encoding::skip_field(wire_type, tag, buf, ctx),
+                }
+            });
+        }
+        start = end;
+    }
+    arms.extend(cold_arms);
+    arms
+}
+
+/// Block merging one field into `base`, reading `wire_type`, `buf` and `ctx`, for every field with a tag.
+fn build_decode_arm_bodies<'a, 'b>(fields: &'b [FieldInfo<'a>], base: &TokenStream2) -> Vec<(&'b FieldInfo<'a>, TokenStream2)> {
+    in_heat_order(fields)
+        .into_iter()
+        .filter(|info| info.tag.is_some())
+        .map(|info| {
             let access = info.access.access_tokens(base.clone());
 
             // Generate field validation if validator is specified
@@ -293,29 +443,30 @@ pub fn build_decode_match_arms(fields: &[FieldInfo<'_>], base: &TokenStream2) ->
                 quote! {}
             };
 
-            if needs_decode_conversion(&info.config, &info.parsed) {
+            let body = if needs_decode_conversion(&info.config, &info.parsed) {
                 let tmp_ident = Ident::new(&format!("__proto_rs_field_{}_tmp", info.index), info.field.span());
                 let decode_ty = &info.decode_ty;
                 let assign = decode_conversion_assign(info, &access, &tmp_ident);
-                Some(quote! {
-                    #tag => {
+                quote! {
+                    {
                         let mut #tmp_ident: #decode_ty = <#decode_ty as ::proto_rs::ProtoDefault>::proto_default();
                         <#decode_ty as ::proto_rs::ProtoFieldMerge>::merge_value(&mut #tmp_ident, wire_type, buf, ctx)?;
                         #assign
                         #validation
                         Ok(())
                     }
-                })
+                }
             } else {
-                let field_ty = &info.field.ty;
-                Some(quote! {
-                    #tag => {
-                        <#field_ty as ::proto_rs::ProtoFieldMerge>::merge_value(&mut #access, wire_type, buf, ctx)?;
+                let merge = direct_merge_stmt(info, &access, &quote! { wire_type }, &quote! { ctx });
+                quote! {
+                    {
+                        #merge
                         #validation
                         Ok(())
                     }
-                })
-            }
+                }
+            };
+            (info, body)
         })
         .collect()
 }
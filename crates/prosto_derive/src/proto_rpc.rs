@@ -9,14 +9,21 @@ mod server;
 pub mod utils; // Add this
 
 use client::generate_client_module;
+use rpc_common::generate_proto_service_name;
+use rpc_common::generate_types_module;
 use server::generate_server_module;
+use utils::extract_helper_methods;
 use utils::extract_methods_and_types; // Add this import
 
 use crate::emit_proto::generate_service_content;
+use crate::emit_proto::is_empty_wkt;
+use crate::emit_proto::rpc_type_names;
 use crate::parse::UnifiedProtoConfig;
 use crate::schema::SchemaTokens;
 use crate::schema::schema_tokens_for_service;
 use crate::schema::type_references_generic_params;
+use crate::write_file::EMPTY_WKT_IMPORT;
+use crate::write_file::register_emitted_import;
 
 pub fn proto_rpc_impl(args: TokenStream, item: TokenStream) -> TokenStream2 {
     let input: ItemTrait = syn::parse(item).expect("Failed to parse trait");
@@ -27,13 +34,33 @@ pub fn proto_rpc_impl(args: TokenStream, item: TokenStream) -> TokenStream2 {
     let package_name = config.get_rpc_package().to_owned();
 
     // Extract methods, types, and imports
-    let (methods, user_associated_types) = extract_methods_and_types(&input);
+    let (methods, user_associated_types) = match extract_methods_and_types(&input) {
+        Ok(extracted) => extracted,
+        Err(err) => return err.to_compile_error(),
+    };
+    let helper_methods = extract_helper_methods(&input);
 
     // Generate .proto file if requested
-    let service_content = generate_service_content(trait_name, &methods, &config.type_imports, config.import_all_from.as_deref());
+    let proto_service_name = match generate_proto_service_name(&package_name, trait_name, &methods) {
+        Ok(name) => name,
+        Err(err) => return err.to_compile_error(),
+    };
+    let service_content = generate_service_content(
+        &proto_service_name,
+        &methods,
+        &config.type_imports,
+        config.import_all_from.as_deref(),
+    );
     let SchemaTokens { schema, inventory_submit } =
         schema_tokens_for_service(&input.ident, &ty_ident, &methods, &package_name, &config, &ty_ident);
     config.register_and_emit_proto(&service_content);
+    if let Some(proto_path) = config.proto_path()
+        && methods.iter().any(|method| {
+            is_empty_wkt(&method.request_type) || is_empty_wkt(method.inner_response_type.as_ref().unwrap_or(&method.response_type))
+        })
+    {
+        register_emitted_import(proto_path, EMPTY_WKT_IMPORT);
+    }
     let proto = config.imports_mat.clone();
 
     let mut validator_consts = Vec::new();
@@ -49,17 +76,43 @@ pub fn proto_rpc_impl(args: TokenStream, item: TokenStream) -> TokenStream2 {
 
     // Generate user-facing trait
     let user_methods: Vec<_> = methods.iter().map(|m| &m.user_method_signature).collect();
+    let trait_generics = &input.generics;
+    let trait_where_clause = &input.generics.where_clause;
+
+    let types_module = generate_types_module(trait_name, vis, &methods, &input.generics);
 
     // Generate client module if requested
     let client_module = if config.rpc_client {
-        generate_client_module(trait_name, vis, &package_name, &methods, config.rpc_client_ctx.as_ref())
+        let module = generate_client_module(
+            trait_name,
+            vis,
+            &package_name,
+            &methods,
+            config.rpc_client_ctx.as_ref(),
+            config.auth,
+        );
+        cfg_gated(config.rpc_client_cfg.as_deref(), module)
     } else {
         quote! {}
     };
 
     // Generate server module if requested
     let server_module = if config.rpc_server {
-        generate_server_module(trait_name, vis, &package_name, &methods)
+        let type_names = methods
+            .iter()
+            .map(|method| rpc_type_names(method, &package_name, &config.type_imports, config.import_all_from.as_deref()))
+            .collect::<Vec<_>>();
+        let module = generate_server_module(
+            trait_name,
+            vis,
+            &package_name,
+            &methods,
+            &type_names,
+            &input.generics,
+            config.auth,
+            config.limits,
+        );
+        cfg_gated(config.rpc_server_cfg.as_deref(), module)
     } else {
         quote! {}
     };
@@ -70,17 +123,32 @@ pub fn proto_rpc_impl(args: TokenStream, item: TokenStream) -> TokenStream2 {
         #proto
         #(#validator_consts)*
         //#interceptor_trait
-        #vis trait #trait_name {
+        #vis trait #trait_name #trait_generics #trait_where_clause {
             #(#user_associated_types)*
             #(#user_methods)*
-
+            #(#helper_methods)*
         }
 
+        #types_module
         #client_module
         #server_module
     }
 }
 
+/// Puts `module` behind `#[cfg(predicate)]` so consumers without the feature skip its tonic machinery.
+fn cfg_gated(predicate: Option<&str>, module: TokenStream2) -> TokenStream2 {
+    match predicate {
+        Some(predicate) => {
+            let predicate: TokenStream2 = predicate.parse().expect("cfg predicates are validated when parsed");
+            quote! {
+                #[cfg(#predicate)]
+                #module
+            }
+        }
+        None => module,
+    }
+}
+
 fn build_validator_const(ty: &syn::Type) -> TokenStream2 {
     quote! {
         #[cfg(feature = "build-schemas")]
@@ -3,7 +3,9 @@
 use proc_macro2::TokenStream;
 use quote::quote;
 
+use crate::parse::AuthScheme;
 use crate::parse::InterceptorConfig;
+use crate::proto_rpc::rpc_common::client_api_trait_name;
 use crate::proto_rpc::rpc_common::client_module_name;
 use crate::proto_rpc::rpc_common::client_struct_name;
 use crate::proto_rpc::rpc_common::generate_client_with_interceptor;
@@ -14,6 +16,8 @@ use crate::proto_rpc::rpc_common::generate_ready_check;
 use crate::proto_rpc::rpc_common::generate_route_path;
 use crate::proto_rpc::rpc_common::generate_stream_conversion;
 use crate::proto_rpc::rpc_common::is_streaming_method;
+use crate::proto_rpc::rpc_common::types_module_name;
+use crate::proto_rpc::rpc_common::types_reexport_vis;
 use crate::utils::MethodInfo;
 
 // ============================================================================
@@ -26,15 +30,47 @@ pub fn generate_client_module(
     package_name: &str,
     methods: &[MethodInfo],
     interceptor_config: Option<&InterceptorConfig>,
+    auth: Option<AuthScheme>,
 ) -> TokenStream {
     let client_module = client_module_name(trait_name);
+    let types_module = types_module_name(trait_name);
+    let types_vis = types_reexport_vis(vis);
     let client_struct = client_struct_name(trait_name);
 
-    let client_methods =
-        methods.iter().map(|m| generate_client_method(m, package_name, trait_name, interceptor_config)).collect::<Vec<_>>();
+    let client_methods = methods
+        .iter()
+        .map(|m| generate_client_method(m, package_name, trait_name, interceptor_config, auth.is_some()))
+        .collect::<Vec<_>>();
+
+    let paginate_methods = if interceptor_config.is_none() {
+        methods.iter().filter(|m| !is_streaming_method(m)).map(generate_auto_paginate_method).collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+
+    let client_api = if interceptor_config.is_none() {
+        generate_client_api(trait_name, methods)
+    } else {
+        quote! {}
+    };
+
+    let stream_ext = if methods.iter().any(is_streaming_method) {
+        quote! { pub use ::proto_rs::response_stream::ResponseStreamExt; }
+    } else {
+        quote! {}
+    };
 
     let compression_methods = generate_client_compression_methods();
     let with_interceptor = generate_client_with_interceptor(&client_struct, interceptor_config.is_some());
+    let (auth_field, auth_init, auth_methods) = if auth.is_some() {
+        (
+            quote! { , authorization: ::core::option::Option<tonic::metadata::AsciiMetadataValue> },
+            quote! { authorization: ::core::option::Option::None, },
+            generate_client_auth_methods(),
+        )
+    } else {
+        (quote! {}, quote! {}, quote! {})
+    };
     let (
         client_struct_generics,
         client_struct_fields,
@@ -45,8 +81,8 @@ pub fn generate_client_module(
     ) = if interceptor_config.is_some() {
         (
             quote! { <T, Ctx> },
-            quote! { inner: tonic::client::Grpc<T>, _ctx: ::core::marker::PhantomData<Ctx> },
-            quote! { Self { inner, _ctx: ::core::marker::PhantomData } },
+            quote! { inner: tonic::client::Grpc<T>, _ctx: ::core::marker::PhantomData<Ctx> #auth_field },
+            quote! { Self { inner, #auth_init _ctx: ::core::marker::PhantomData } },
             quote! { <T, Ctx> },
             quote! { <Ctx> },
             quote! { <tonic::transport::Channel, Ctx> },
@@ -54,8 +90,8 @@ pub fn generate_client_module(
     } else {
         (
             quote! { <T> },
-            quote! { inner: tonic::client::Grpc<T> },
-            quote! { Self { inner } },
+            quote! { inner: tonic::client::Grpc<T> #auth_field },
+            quote! { Self { inner, #auth_init } },
             quote! { <T> },
             quote! {},
             quote! { <tonic::transport::Channel> },
@@ -73,12 +109,15 @@ pub fn generate_client_module(
             )]
             use tonic::codegen::*;
             use super::*;
+            #types_vis use super::#types_module as types;
+            #stream_ext
 
             #[derive(Debug, Clone)]
             pub struct #client_struct #client_struct_generics {
                 #client_struct_fields,
             }
 
+            #[cfg(not(target_arch = "wasm32"))]
             impl #client_connect_impl_generics #client_struct #client_connect_type_args {
                 pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
                 where
@@ -88,6 +127,26 @@ pub fn generate_client_module(
                     let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
                     Ok(Self::new(conn))
                 }
+
+                pub async fn connect_with<D, C>(dst: D, connector: C) -> Result<Self, tonic::transport::Error>
+                where
+                    D: TryInto<tonic::transport::Endpoint>,
+                    D::Error: Into<StdError>,
+                    C: ::proto_rs::transport::Connector,
+                {
+                    let endpoint = tonic::transport::Endpoint::new(dst)?;
+                    let conn = ::proto_rs::transport::Connector::connect(connector, endpoint).await?;
+                    Ok(Self::new(conn))
+                }
+
+                #[cfg(unix)]
+                pub async fn connect_uds<P>(path: P) -> Result<Self, tonic::transport::Error>
+                where
+                    P: AsRef<::std::path::Path>,
+                {
+                    let conn = ::proto_rs::transport::connect_uds(path).await?;
+                    Ok(Self::new(conn))
+                }
             }
 
             impl #client_impl_generics #client_struct #client_struct_generics
@@ -109,10 +168,70 @@ pub fn generate_client_module(
 
                 #with_interceptor
 
+                #auth_methods
+
                 #compression_methods
 
                 #(#client_methods)*
+
+                #(#paginate_methods)*
             }
+
+            #client_api
+        }
+    }
+}
+
+// ============================================================================
+// CLIENT API TRAIT GENERATION
+// ============================================================================
+
+/// Generate `{Trait}ClientApi`, implemented by the channel-backed client so callers can depend on
+/// the trait and swap in a test double.
+fn generate_client_api(trait_name: &syn::Ident, methods: &[MethodInfo]) -> TokenStream {
+    let api_trait = client_api_trait_name(trait_name);
+    let client_struct = client_struct_name(trait_name);
+
+    let mut signatures = Vec::with_capacity(methods.len());
+    let mut impls = Vec::with_capacity(methods.len());
+    for method in methods {
+        let method_name = &method.name;
+        let request_type = &method.request_type;
+        let response = if let Some(inner_response_type) = &method.inner_response_type {
+            quote! { BoxStream<#inner_response_type> }
+        } else {
+            let response_type = &method.response_type;
+            quote! { #response_type }
+        };
+        let signature = quote! {
+            fn #method_name(
+                &mut self,
+                request: tonic::Request<#request_type>,
+            ) -> impl ::core::future::Future<Output = ::core::result::Result<tonic::Response<#response>, tonic::Status>> + ::core::marker::Send
+        };
+        let body = if is_streaming_method(method) {
+            quote! {
+                async move {
+                    let response = #client_struct::#method_name(self, request).await?;
+                    Ok(response.map(|stream| -> BoxStream<_> { ::proto_rs::alloc::boxed::Box::pin(stream) }))
+                }
+            }
+        } else {
+            quote! { #client_struct::#method_name(self, request) }
+        };
+
+        signatures.push(quote! { #signature; });
+        impls.push(quote! { #signature { #body } });
+    }
+
+    quote! {
+        pub trait #api_trait {
+            #(#signatures)*
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        impl #api_trait for #client_struct<tonic::transport::Channel> {
+            #(#impls)*
         }
     }
 }
@@ -126,11 +245,21 @@ fn generate_client_method(
     package_name: &str,
     trait_name: &syn::Ident,
     interceptor_config: Option<&InterceptorConfig>,
+    has_auth: bool,
 ) -> TokenStream {
+    let auth_injection = if has_auth {
+        quote! {
+            if let ::core::option::Option::Some(value) = &self.authorization {
+                request.metadata_mut().insert("authorization", value.clone());
+            }
+        }
+    } else {
+        quote! {}
+    };
     if is_streaming_method(method) {
-        generate_streaming_client_method(method, package_name, trait_name, interceptor_config)
+        generate_streaming_client_method(method, package_name, trait_name, interceptor_config, &auth_injection)
     } else {
-        generate_unary_client_method(method, package_name, trait_name, interceptor_config)
+        generate_unary_client_method(method, package_name, trait_name, interceptor_config, &auth_injection)
     }
 }
 
@@ -139,11 +268,12 @@ fn generate_unary_client_method(
     package_name: &str,
     trait_name: &syn::Ident,
     interceptor_config: Option<&InterceptorConfig>,
+    auth_injection: &TokenStream,
 ) -> TokenStream {
     let method_name = &method.name;
     let request_type = &method.request_type;
     let response_type = &method.response_type;
-    let route_path = generate_route_path(package_name, trait_name, method_name);
+    let route_path = generate_route_path(package_name, trait_name, method);
 
     let ready_check = generate_ready_check();
     let request_conversion = generate_native_to_proto_request_unary(request_type);
@@ -182,12 +312,13 @@ fn generate_unary_client_method(
             #request_conversion
             #ready_check
             let mut request = request.into_request();
+            #auth_injection
             #interceptor_call
             request.extensions_mut().insert(
                 tonic::codegen::GrpcMethod::new(#package_name, stringify!(#method_name))
             );
 
-            let codec = ::proto_rs::ProtoCodec::<R::Encode, #response_type, R::Mode>::default();
+            let codec = ::proto_rs::ProtoCodec::<R::Encode, #response_type, R::Mode>::client(#route_path);
             let path = http::uri::PathAndQuery::from_static(#route_path);
             let response = self.inner.unary(request, path, codec).await?;
 
@@ -201,11 +332,12 @@ fn generate_streaming_client_method(
     package_name: &str,
     trait_name: &syn::Ident,
     interceptor_config: Option<&InterceptorConfig>,
+    auth_injection: &TokenStream,
 ) -> TokenStream {
     let method_name = &method.name;
     let request_type = &method.request_type;
     let inner_response_type = method.inner_response_type.as_ref().unwrap();
-    let route_path = generate_route_path(package_name, trait_name, method_name);
+    let route_path = generate_route_path(package_name, trait_name, method);
 
     let ready_check = generate_ready_check();
     let request_conversion = generate_native_to_proto_request_streaming(request_type);
@@ -244,8 +376,9 @@ fn generate_streaming_client_method(
             #request_conversion
             #ready_check
             let mut request = request.into_request();
+            #auth_injection
             #interceptor_call
-            let codec = ::proto_rs::ProtoCodec::<R::Encode, #inner_response_type, R::Mode>::default();
+            let codec = ::proto_rs::ProtoCodec::<R::Encode, #inner_response_type, R::Mode>::client(#route_path);
             let path = http::uri::PathAndQuery::from_static(#route_path);
             let response = self.inner.server_streaming(request, path, codec).await?;
 
@@ -254,6 +387,63 @@ fn generate_streaming_client_method(
     }
 }
 
+/// `<method>_auto_paginate`, usable when the request and response are an AIP-158 page pair.
+///
+/// The bounds name concrete message types, so they are written under `for<'a>` to be checked at
+/// the call site instead of rejecting the client for every rpc that does not paginate.
+fn generate_auto_paginate_method(method: &MethodInfo) -> TokenStream {
+    let method_name = &method.name;
+    let paginate_name = syn::Ident::new(&format!("{method_name}_auto_paginate"), method_name.span());
+    let request_type = &method.request_type;
+    let response_type = &method.response_type;
+    let doc = format!(" Calls `{method_name}` page by page, following `next_page_token`, and streams the items of every page.");
+
+    quote! {
+        #[doc = #doc]
+        pub fn #paginate_name(
+            &self,
+            request: #request_type,
+        ) -> impl tonic::codegen::tokio_stream::Stream<
+            Item = ::core::result::Result<<#response_type as ::proto_rs::pagination::PageResponse>::Item, tonic::Status>,
+        >
+        where
+            Self: ::core::clone::Clone,
+            for<'a> #request_type: ::proto_rs::pagination::PageRequest + ::core::clone::Clone,
+            for<'a> #response_type: ::proto_rs::pagination::PageResponse,
+        {
+            let client = ::core::clone::Clone::clone(self);
+            ::proto_rs::pagination::auto_paginate(request, move |page: #request_type| {
+                let mut client = ::core::clone::Clone::clone(&client);
+                async move { client.#method_name(page).await }
+            })
+        }
+    }
+}
+
+// ============================================================================
+// CLIENT AUTH METHODS
+// ============================================================================
+
+fn generate_client_auth_methods() -> TokenStream {
+    quote! {
+        /// Sends `authorization: Bearer <token>` with every request.
+        pub fn with_bearer_token(
+            mut self,
+            token: &str,
+        ) -> ::core::result::Result<Self, tonic::metadata::errors::InvalidMetadataValue> {
+            self.authorization = ::core::option::Option::Some(::proto_rs::auth::bearer_value(token)?);
+            Ok(self)
+        }
+
+        /// Sends `value` as the `authorization` header with every request.
+        #[must_use]
+        pub fn with_authorization(mut self, value: tonic::metadata::AsciiMetadataValue) -> Self {
+            self.authorization = ::core::option::Option::Some(value);
+            self
+        }
+    }
+}
+
 // ============================================================================
 // CLIENT COMPRESSION METHODS
 // ============================================================================
@@ -301,10 +491,11 @@ mod tests {
         let vis: syn::Visibility = parse_quote! { pub };
         let methods = vec![];
 
-        let module = generate_client_module(&trait_name, &vis, "test_package", &methods, None);
+        let module = generate_client_module(&trait_name, &vis, "test_package", &methods, None, None);
 
         let module_str = module.to_string();
         assert!(module_str.contains("test_service_client"));
         assert!(module_str.contains("TestServiceClient"));
+        assert!(module_str.contains("TestServiceClientApi"));
     }
 }
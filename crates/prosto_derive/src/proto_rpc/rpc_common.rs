@@ -5,7 +5,6 @@ use quote::quote;
 use syn::Type;
 
 use crate::utils::MethodInfo;
-use crate::utils::to_pascal_case;
 
 /// Generate native-to-proto request conversion (used in client - unary)
 pub fn generate_native_to_proto_request_unary(request_type: &Type) -> TokenStream {
@@ -19,9 +18,18 @@ pub fn generate_native_to_proto_request_streaming(request_type: &Type) -> TokenS
     quote! {}
 }
 
-/// Generate proto-to-native response conversion (used in client)
-pub fn generate_proto_to_native_response(_response_type: &Type) -> TokenStream {
-    quote! { Ok(response) }
+/// Generate proto-to-native response conversion (used in client), running `validate_response_with_ext`
+pub fn generate_proto_to_native_response(response_type: &Type) -> TokenStream {
+    quote! {
+        if <#response_type as ::proto_rs::ProtoDecode>::VALIDATE_RESPONSE_WITH_EXT {
+            let (metadata, mut message, extensions) = response.into_parts();
+            <#response_type as ::proto_rs::ProtoDecode>::validate_response_with_ext(&mut message, &extensions)
+                .map_err(|err| tonic::Status::from(::proto_rs::ProtoError::Validation(err)))?;
+            Ok(tonic::Response::from_parts(metadata, message, extensions))
+        } else {
+            Ok(response)
+        }
+    }
 }
 
 // ============================================================================
@@ -42,17 +50,68 @@ pub fn generate_response_proto_type(response_type: &Type) -> TokenStream {
 // ROUTE AND CODEC
 // ============================================================================
 
-/// Generate RPC route path
-pub fn generate_route_path(package_name: &str, trait_name: &syn::Ident, method_name: &syn::Ident) -> String {
-    format!("/{}.{}/{}", package_name, trait_name, to_pascal_case(&method_name.to_string()))
+/// Generate the message-bus subject for a method: the route with `/` replaced by `.`
+pub fn generate_bus_subject(package_name: &str, trait_name: &syn::Ident, method: &MethodInfo) -> String {
+    let route = generate_route_path(package_name, trait_name, method);
+    route.trim_start_matches('/').replacen('/', ".", 1)
+}
+
+/// Generate RPC route path, honoring `#[proto(route = ...)]` and `#[proto(rpc_name = ...)]`
+pub fn generate_route_path(package_name: &str, trait_name: &syn::Ident, method: &MethodInfo) -> String {
+    method.route.clone().unwrap_or_else(|| format!("/{}.{}/{}", package_name, trait_name, method.proto_name()))
 }
 
-/// Generate codec initialization
-pub fn generate_codec_init(encode: TokenStream, decode: TokenStream, mode: Option<TokenStream>) -> TokenStream {
+/// Generate the tonic service name (`NamedService::NAME`) shared by every route of the service.
+///
+/// tonic dispatches requests by service name, so overridden routes must all live under one
+/// `/service/` prefix.
+pub fn generate_service_name(package_name: &str, trait_name: &syn::Ident, methods: &[MethodInfo]) -> syn::Result<String> {
+    let mut service_name: Option<String> = None;
+    for method in methods {
+        let route = generate_route_path(package_name, trait_name, method);
+        let Some((service, _)) = route.strip_prefix('/').and_then(|path| path.rsplit_once('/')) else {
+            return Err(syn::Error::new_spanned(
+                &method.name,
+                "route must look like \"/package.Service/Method\"",
+            ));
+        };
+        match &service_name {
+            None => service_name = Some(service.to_string()),
+            Some(existing) if existing != service => {
+                return Err(syn::Error::new_spanned(
+                    &method.name,
+                    format!("all rpc routes of {trait_name} must share one service path, found `{existing}` and `{service}`"),
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(service_name.unwrap_or_else(|| format!("{package_name}.{trait_name}")))
+}
+
+/// Generate the service name of the `.proto` definition: the last segment of the service path.
+///
+/// The `.proto` file declares the rpc package, so routes under another package are rejected.
+pub fn generate_proto_service_name(package_name: &str, trait_name: &syn::Ident, methods: &[MethodInfo]) -> syn::Result<String> {
+    let service_name = generate_service_name(package_name, trait_name, methods)?;
+    let (package, service) = service_name.rsplit_once('.').unwrap_or(("", &service_name));
+    if package != package_name
+        && let Some(method) = methods.iter().find(|method| method.route.is_some())
+    {
+        return Err(syn::Error::new_spanned(
+            &method.name,
+            format!("route service `{service_name}` is outside the rpc package `{package_name}`, set rpc_package = \"{package}\""),
+        ));
+    }
+    Ok(service.to_string())
+}
+
+/// Generate server codec initialization; the route is reported to the size hooks
+pub fn generate_codec_init(encode: TokenStream, decode: TokenStream, mode: Option<TokenStream>, route_path: &str) -> TokenStream {
     if let Some(mode) = mode {
-        quote! { let codec = ::proto_rs::ProtoCodec::<#encode, #decode, #mode>::default(); }
+        quote! { let codec = ::proto_rs::ProtoCodec::<#encode, #decode, #mode>::server(#route_path); }
     } else {
-        quote! { let codec = ::proto_rs::ProtoCodec::<#encode, #decode>::default(); }
+        quote! { let codec = ::proto_rs::ProtoCodec::<#encode, #decode>::server(#route_path); }
     }
 }
 
@@ -60,9 +119,25 @@ pub fn generate_codec_init(encode: TokenStream, decode: TokenStream, mode: Optio
 // STREAMING HELPERS
 // ============================================================================
 
-/// Generate stream conversion for streaming responses (client side)
-pub fn generate_stream_conversion(_inner_response_type: &Type) -> TokenStream {
-    quote! { Ok(response) }
+/// Generate stream conversion for streaming responses (client side), validating every item
+pub fn generate_stream_conversion(inner_response_type: &Type) -> TokenStream {
+    quote! {
+        let (metadata, stream, extensions) = response.into_parts();
+        let validation_ext = if <#inner_response_type as ::proto_rs::ProtoDecode>::VALIDATE_RESPONSE_WITH_EXT {
+            extensions.clone()
+        } else {
+            tonic::Extensions::default()
+        };
+        let stream = tonic::codegen::tokio_stream::StreamExt::map(stream, move |item| {
+            let mut message = item?;
+            if <#inner_response_type as ::proto_rs::ProtoDecode>::VALIDATE_RESPONSE_WITH_EXT {
+                <#inner_response_type as ::proto_rs::ProtoDecode>::validate_response_with_ext(&mut message, &validation_ext)
+                    .map_err(|err| tonic::Status::from(::proto_rs::ProtoError::Validation(err)))?;
+            }
+            Ok(message)
+        });
+        Ok(tonic::Response::from_parts(metadata, stream, extensions))
+    }
 }
 
 /// Check if method is streaming
@@ -100,33 +175,109 @@ pub fn server_module_name(trait_name: &syn::Ident) -> syn::Ident {
     syn::Ident::new(&format!("{}_server", to_snake_case(&trait_name.to_string())), trait_name.span())
 }
 
+/// Generate the method IO type-alias module name from trait
+pub fn types_module_name(trait_name: &syn::Ident) -> syn::Ident {
+    use crate::utils::to_snake_case;
+    syn::Ident::new(&format!("{}_types", to_snake_case(&trait_name.to_string())), trait_name.span())
+}
+
 /// Generate client struct name from trait
 pub fn client_struct_name(trait_name: &syn::Ident) -> syn::Ident {
     syn::Ident::new(&format!("{trait_name}Client"), trait_name.span())
 }
 
+/// Generate client API trait name from trait
+pub fn client_api_trait_name(trait_name: &syn::Ident) -> syn::Ident {
+    syn::Ident::new(&format!("{trait_name}ClientApi"), trait_name.span())
+}
+
 /// Generate server struct name from trait
 pub fn server_struct_name(trait_name: &syn::Ident) -> syn::Ident {
     syn::Ident::new(&format!("{trait_name}Server"), trait_name.span())
 }
 
+// ============================================================================
+// METHOD IO TYPE ALIASES
+// ============================================================================
+
+/// Generate the `<service>_types` module with `<RpcName>Request` / `<RpcName>Response` aliases.
+///
+/// Server-streaming responses alias the stream item. Methods whose IO types mention the trait's
+/// generic parameters get no aliases, since a plain alias cannot name them. The types are resolved
+/// through hidden aliases in the trait's scope, because an alias such as `BuildRequest` would
+/// otherwise shadow the `BuildRequest` message it points to.
+pub fn generate_types_module(
+    trait_name: &syn::Ident,
+    vis: &syn::Visibility,
+    methods: &[MethodInfo],
+    generics: &syn::Generics,
+) -> TokenStream {
+    use crate::schema::type_references_generic_params;
+
+    let types_module = types_module_name(trait_name);
+    let mut hidden = Vec::new();
+    let mut aliases = Vec::new();
+    for method in methods {
+        let rpc_name = method.proto_name();
+        let response_type = method.inner_response_type.as_ref().unwrap_or(&method.response_type);
+        for (suffix, ty) in [("Request", &method.request_type), ("Response", response_type)] {
+            if type_references_generic_params(ty, generics) {
+                continue;
+            }
+            let alias = syn::Ident::new(&format!("{rpc_name}{suffix}"), method.name.span());
+            let hidden_alias = syn::Ident::new(&format!("__{trait_name}{rpc_name}{suffix}"), method.name.span());
+            hidden.push(quote! {
+                #[doc(hidden)]
+                #[allow(dead_code)]
+                type #hidden_alias = #ty;
+            });
+            aliases.push(quote! { pub type #alias = super::#hidden_alias; });
+        }
+    }
+
+    quote! {
+        #(#hidden)*
+
+        /// Request and response types of every rpc, named `<RpcName>Request` / `<RpcName>Response`.
+        #vis mod #types_module {
+            #![allow(dead_code)]
+
+            #(#aliases)*
+        }
+    }
+}
+
+/// Visibility of the `types` re-export inside the client and server modules.
+///
+/// A re-export cannot be more visible than the `<service>_types` module itself, which shares the
+/// trait's visibility; restricted and private traits therefore re-export only to the parent.
+pub fn types_reexport_vis(vis: &syn::Visibility) -> TokenStream {
+    match vis {
+        syn::Visibility::Public(_) => quote! { pub },
+        syn::Visibility::Restricted(restricted) if restricted.path.is_ident("crate") => quote! { pub(crate) },
+        syn::Visibility::Restricted(_) | syn::Visibility::Inherited => quote! { pub(super) },
+    }
+}
+
 // ============================================================================
 // SERVICE STRUCT GENERATION
 // ============================================================================
 
 /// Generate common service struct fields (used by server)
-pub fn generate_service_struct_fields() -> TokenStream {
+pub fn generate_service_struct_fields(extra_fields: &TokenStream) -> TokenStream {
     quote! {
         inner: ::proto_rs::alloc::sync::Arc<T>,
         accept_compression_encodings: EnabledCompressionEncodings,
         send_compression_encodings: EnabledCompressionEncodings,
         max_decoding_message_size: Option<usize>,
         max_encoding_message_size: Option<usize>,
+        drain: ::proto_rs::shutdown::Drain,
+        #extra_fields
     }
 }
 
 /// Generate service struct constructors
-pub fn generate_service_constructors() -> TokenStream {
+pub fn generate_service_constructors(extra_init: &TokenStream) -> TokenStream {
     quote! {
         pub fn new(inner: T) -> Self {
             Self::from_arc(::proto_rs::alloc::sync::Arc::new(inner))
@@ -139,6 +290,8 @@ pub fn generate_service_constructors() -> TokenStream {
                 send_compression_encodings: Default::default(),
                 max_decoding_message_size: None,
                 max_encoding_message_size: None,
+                drain: ::core::default::Default::default(),
+                #extra_init
             }
         }
     }
@@ -177,14 +330,127 @@ mod tests {
     use syn::parse_quote;
 
     use super::*;
+    use crate::proto_rpc::utils::extract_methods_and_types;
 
     #[test]
     fn test_generate_route_path() {
-        let trait_name: syn::Ident = parse_quote! { TestService };
-        let method_name: syn::Ident = parse_quote! { test_method };
+        let trait_input: syn::ItemTrait = parse_quote! {
+            trait TestService {
+                async fn test_method(&self, request: tonic::Request<MyRequest>) -> Result<tonic::Response<MyResponse>, tonic::Status>;
+
+                #[proto(rpc_name = "LookupOwner")]
+                async fn owner(&self, request: tonic::Request<MyRequest>) -> Result<tonic::Response<MyResponse>, tonic::Status>;
+
+                #[proto(route = "/custom.v1.Svc/Lookup")]
+                async fn routed(&self, request: tonic::Request<MyRequest>) -> Result<tonic::Response<MyResponse>, tonic::Status>;
+            }
+        };
+        let (methods, _) = extract_methods_and_types(&trait_input).unwrap();
 
-        let path = generate_route_path("my_package", &trait_name, &method_name);
+        let path = generate_route_path("my_package", &trait_input.ident, &methods[0]);
         assert_eq!(path, "/my_package.TestService/TestMethod");
+
+        let path = generate_route_path("my_package", &trait_input.ident, &methods[1]);
+        assert_eq!(path, "/my_package.TestService/LookupOwner");
+
+        let path = generate_route_path("my_package", &trait_input.ident, &methods[2]);
+        assert_eq!(path, "/custom.v1.Svc/Lookup");
+        assert_eq!(methods[2].proto_name(), "Lookup");
+
+        assert_eq!(
+            generate_bus_subject("my_package", &trait_input.ident, &methods[0]),
+            "my_package.TestService.TestMethod"
+        );
+        assert_eq!(
+            generate_bus_subject("my_package", &trait_input.ident, &methods[2]),
+            "custom.v1.Svc.Lookup"
+        );
+    }
+
+    #[test]
+    fn test_generate_service_name() {
+        let trait_input: syn::ItemTrait = parse_quote! {
+            trait TestService {
+                #[proto(route = "/custom.v1.Svc/Lookup")]
+                async fn lookup(&self, request: tonic::Request<MyRequest>) -> Result<tonic::Response<MyResponse>, tonic::Status>;
+
+                #[proto(route = "/custom.v1.Svc/Store")]
+                async fn store(&self, request: tonic::Request<MyRequest>) -> Result<tonic::Response<MyResponse>, tonic::Status>;
+            }
+        };
+        let (methods, _) = extract_methods_and_types(&trait_input).unwrap();
+        assert_eq!(
+            generate_service_name("my_package", &trait_input.ident, &methods).unwrap(),
+            "custom.v1.Svc"
+        );
+        assert_eq!(
+            generate_service_name("my_package", &trait_input.ident, &[]).unwrap(),
+            "my_package.TestService"
+        );
+
+        let trait_input: syn::ItemTrait = parse_quote! {
+            trait TestService {
+                #[proto(route = "/custom.v1.Svc/Lookup")]
+                async fn lookup(&self, request: tonic::Request<MyRequest>) -> Result<tonic::Response<MyResponse>, tonic::Status>;
+
+                async fn store(&self, request: tonic::Request<MyRequest>) -> Result<tonic::Response<MyResponse>, tonic::Status>;
+            }
+        };
+        let (methods, _) = extract_methods_and_types(&trait_input).unwrap();
+        let err = generate_service_name("my_package", &trait_input.ident, &methods).unwrap_err();
+        assert!(err.to_string().contains("must share one service path"));
+    }
+
+    #[test]
+    fn test_generate_proto_service_name() {
+        let trait_input: syn::ItemTrait = parse_quote! {
+            trait TestService {
+                #[proto(route = "/custom.v1.Svc/Lookup")]
+                async fn lookup(&self, request: tonic::Request<MyRequest>) -> Result<tonic::Response<MyResponse>, tonic::Status>;
+            }
+        };
+        let (methods, _) = extract_methods_and_types(&trait_input).unwrap();
+        assert_eq!(
+            generate_proto_service_name("custom.v1", &trait_input.ident, &methods).unwrap(),
+            "Svc"
+        );
+        assert_eq!(
+            generate_proto_service_name("custom.v1", &trait_input.ident, &[]).unwrap(),
+            "TestService"
+        );
+
+        let err = generate_proto_service_name("my_package", &trait_input.ident, &methods).unwrap_err();
+        assert!(err.to_string().contains("set rpc_package = \"custom.v1\""));
+    }
+
+    #[test]
+    fn test_bad_method_attributes_are_errors() {
+        let trait_input: syn::ItemTrait = parse_quote! {
+            trait TestService {
+                #[proto(route = "Lookup")]
+                async fn lookup(&self, request: tonic::Request<MyRequest>) -> Result<tonic::Response<MyResponse>, tonic::Status>;
+            }
+        };
+        let err = extract_methods_and_types(&trait_input).err().expect("bad route accepted");
+        assert!(err.to_string().contains("route must look like"));
+
+        let trait_input: syn::ItemTrait = parse_quote! {
+            trait TestService {
+                #[proto(rpc_name = "Find", route = "/custom.v1.Svc/Lookup")]
+                async fn lookup(&self, request: tonic::Request<MyRequest>) -> Result<tonic::Response<MyResponse>, tonic::Status>;
+            }
+        };
+        let err = extract_methods_and_types(&trait_input).err().expect("conflicting rpc_name accepted");
+        assert!(err.to_string().contains("disagrees with the rpc of route"));
+
+        let trait_input: syn::ItemTrait = parse_quote! {
+            trait TestService {
+                #[proto(rpc_title = "Lookup")]
+                async fn lookup(&self, request: tonic::Request<MyRequest>) -> Result<tonic::Response<MyResponse>, tonic::Status>;
+            }
+        };
+        let err = extract_methods_and_types(&trait_input).err().expect("unknown attribute accepted");
+        assert!(err.to_string().contains("unknown #[proto(...)] method attribute"));
     }
 
     #[test]
@@ -196,6 +462,9 @@ mod tests {
 
         let server_mod = server_module_name(&trait_name);
         assert_eq!(server_mod.to_string(), "test_service_server");
+
+        let api_trait = client_api_trait_name(&trait_name);
+        assert_eq!(api_trait.to_string(), "TestServiceClientApi");
     }
 
     #[test]
@@ -3,18 +3,25 @@
 use std::collections::HashSet;
 
 use proc_macro2::TokenStream;
+use quote::ToTokens;
 use quote::quote;
 use syn::Type;
 
+use crate::parse::AuthScheme;
+use crate::parse::ServerLimits;
+use crate::proto_rpc::rpc_common::generate_bus_subject;
 use crate::proto_rpc::rpc_common::generate_codec_init;
 use crate::proto_rpc::rpc_common::generate_request_proto_type;
 use crate::proto_rpc::rpc_common::generate_response_proto_type;
 use crate::proto_rpc::rpc_common::generate_route_path;
 use crate::proto_rpc::rpc_common::generate_service_constructors;
+use crate::proto_rpc::rpc_common::generate_service_name;
 use crate::proto_rpc::rpc_common::generate_service_struct_fields;
 use crate::proto_rpc::rpc_common::is_streaming_method;
 use crate::proto_rpc::rpc_common::server_module_name;
 use crate::proto_rpc::rpc_common::server_struct_name;
+use crate::proto_rpc::rpc_common::types_module_name;
+use crate::proto_rpc::rpc_common::types_reexport_vis;
 use crate::proto_rpc::utils::associated_future_type;
 use crate::proto_rpc::utils::is_response_wrapper;
 use crate::proto_rpc::utils::method_future_return_type;
@@ -42,14 +49,14 @@ fn generate_proto_to_native_request(request_type: &Type, fallible: bool, request
             quote! {
                 let (metadata, extensions, mut message) = request.into_parts();
                 <#request_type as ::proto_rs::ProtoDecode>::validate_with_ext(&mut message, &extensions)
-                    .map_err(|err| tonic::Status::invalid_argument(format!("failed to validate request: {err}")))?;
+                    .map_err(|err| tonic::Status::from(::proto_rs::ProtoError::Validation(err)))?;
                 let native_request = tonic::Request::from_parts(metadata, extensions, message);
             }
         } else {
             quote! {
                 let (metadata, extensions, mut message) = request.into_parts();
                 <#request_type as ::proto_rs::ProtoDecode>::validate_with_ext(&mut message, &extensions)
-                    .map_err(|err| tonic::Status::invalid_argument(format!("failed to validate request: {err}")))?;
+                    .map_err(|err| tonic::Status::from(::proto_rs::ProtoError::Validation(err)))?;
                 let native_request = message;
                 let _ = metadata;
             }
@@ -83,27 +90,144 @@ fn wrap_call_future(is_async: bool, body: TokenStream) -> TokenStream {
     }
 }
 
+// ============================================================================
+// TRAIT GENERICS
+// ============================================================================
+
+/// Generic parameters of the annotated trait, threaded through every generated impl.
+///
+/// Type parameters only show up in the server through the trait bound, so the server and the
+/// per-method service structs carry them in a `PhantomData<fn() -> (..)>` marker.
+struct TraitGenerics {
+    /// `Trait<A, B>`
+    trait_ref: TokenStream,
+    /// `A: Bound, B,` (defaults stripped)
+    params: TokenStream,
+    /// `A, B,`
+    args: TokenStream,
+    /// where-clause predicates of the trait, comma terminated
+    predicates: TokenStream,
+    marker_field: TokenStream,
+    marker_init: TokenStream,
+    svc_marker_ty: TokenStream,
+    svc_marker_init: TokenStream,
+}
+
+impl TraitGenerics {
+    fn new(trait_name: &syn::Ident, generics: &syn::Generics) -> Self {
+        assert!(
+            generics.lifetimes().next().is_none(),
+            "proto_rpc traits cannot have lifetime parameters"
+        );
+
+        let params = generics.params.iter().map(|param| {
+            let mut param = param.clone();
+            match &mut param {
+                syn::GenericParam::Type(ty) => {
+                    ty.eq_token = None;
+                    ty.default = None;
+                }
+                syn::GenericParam::Const(konst) => {
+                    konst.eq_token = None;
+                    konst.default = None;
+                }
+                syn::GenericParam::Lifetime(_) => {}
+            }
+            param
+        });
+        let args = generics.params.iter().map(|param| match param {
+            syn::GenericParam::Type(ty) => ty.ident.to_token_stream(),
+            syn::GenericParam::Const(konst) => konst.ident.to_token_stream(),
+            syn::GenericParam::Lifetime(lifetime) => lifetime.lifetime.to_token_stream(),
+        });
+        let predicates = generics.where_clause.iter().flat_map(|clause| clause.predicates.iter());
+        let type_params = generics.type_params().map(|param| &param.ident).collect::<Vec<_>>();
+        let (_, ty_generics, _) = generics.split_for_impl();
+
+        let (marker_field, marker_init, svc_marker_ty, svc_marker_init) = if type_params.is_empty() {
+            (quote! {}, quote! {}, quote! {}, quote! {})
+        } else {
+            (
+                quote! { _marker: ::core::marker::PhantomData<fn() -> (#(#type_params,)*)>, },
+                quote! { _marker: ::core::marker::PhantomData, },
+                quote! { , ::core::marker::PhantomData<fn() -> (#(#type_params,)*)> },
+                quote! { , ::core::marker::PhantomData },
+            )
+        };
+
+        Self {
+            trait_ref: quote! { #trait_name #ty_generics },
+            params: quote! { #(#params,)* },
+            args: quote! { #(#args,)* },
+            predicates: quote! { #(#predicates,)* },
+            marker_field,
+            marker_init,
+            svc_marker_ty,
+            svc_marker_init,
+        }
+    }
+}
+
 // ============================================================================
 // SERVER MODULE GENERATION
 // ============================================================================
 
-pub fn generate_server_module(trait_name: &syn::Ident, vis: &syn::Visibility, package_name: &str, methods: &[MethodInfo]) -> TokenStream {
+#[allow(clippy::too_many_arguments)]
+pub fn generate_server_module(
+    trait_name: &syn::Ident,
+    vis: &syn::Visibility,
+    package_name: &str,
+    methods: &[MethodInfo],
+    type_names: &[(String, String)],
+    trait_generics: &syn::Generics,
+    auth: Option<AuthScheme>,
+    limits: ServerLimits,
+) -> TokenStream {
     let server_module = server_module_name(trait_name);
+    let types_module = types_module_name(trait_name);
+    let types_vis = types_reexport_vis(vis);
     let server_struct = server_struct_name(trait_name);
+    let generics = TraitGenerics::new(trait_name, trait_generics);
+    let TraitGenerics {
+        trait_ref,
+        params,
+        args,
+        predicates,
+        marker_field,
+        marker_init,
+        ..
+    } = &generics;
 
     let (trait_methods, associated_types) = generate_trait_components(methods);
-    let (blanket_types, blanket_methods) = generate_blanket_impl_components(methods, trait_name);
-    let route_handlers = methods.iter().map(|m| generate_route_handler(m, package_name, trait_name)).collect::<Vec<_>>();
-
-    let service_name_value = format!("{package_name}.{trait_name}");
+    let (blanket_types, blanket_methods) = generate_blanket_impl_components(methods, trait_ref);
+    let route_handlers =
+        methods.iter().map(|m| generate_route_handler(m, package_name, trait_name, &generics, auth.is_some())).collect::<Vec<_>>();
+    let dispatch_arms = methods.iter().map(|m| generate_dispatch_arm(m, trait_ref)).collect::<Vec<_>>();
+
+    let service_name_value = match generate_service_name(package_name, trait_name, methods) {
+        Ok(service_name) => service_name,
+        Err(err) => return err.to_compile_error(),
+    };
+    let subjects = methods.iter().map(|m| generate_bus_subject(package_name, trait_name, m)).collect::<Vec<_>>();
+    let method_descriptors = generate_method_descriptors(package_name, trait_name, methods, type_names);
     let compression_methods = generate_server_compression_methods();
-    let service_fields = generate_service_struct_fields();
-    let service_constructors = generate_service_constructors();
+    let (limits_field, limits_init, limits_clone, limits_capture, limits_acquire) = generate_limits(limits);
+    let (cache_field, cache_init, cache_clone, cache_capture, cache_accessor) = generate_cache_state(methods);
+    let (dedup_field, dedup_init, dedup_clone, dedup_capture, dedup_accessor) = generate_dedup_state(methods);
+    let service_fields = generate_service_struct_fields(&quote! { #marker_field #limits_field #cache_field #dedup_field });
+    let service_constructors = generate_service_constructors(&quote! { #marker_init #limits_init #cache_init #dedup_init });
+    let (auth_bound, auth_check) = generate_auth_check(auth);
+    let dispatch_auth_check = generate_dispatch_auth_check(auth);
     let service_future_type = associated_future_type(quote! { ::core::result::Result<Self::Response, Self::Error> }, false);
     let call_future_body = wrap_async_block(
         quote! {
             async move {
-                match req.uri().path() {
+                let ::core::option::Option::Some(in_flight) = in_flight else {
+                    return Ok(tonic::Status::unavailable("server is shutting down").into_http());
+                };
+                #auth_check
+                #limits_acquire
+                let response = match req.uri().path() {
                     #(#route_handlers)*
                     _ =>  {
                         let mut response = http::Response::new(tonic::body::Body::default());
@@ -118,7 +242,9 @@ pub fn generate_server_module(trait_name: &syn::Ident, vis: &syn::Visibility, pa
                         );
                         Ok(response)
                     },
-                }
+                };
+                // Streams keep the call in flight until their last message is sent.
+                response.map(|response| response.map(|body| ::proto_rs::shutdown::guard_body(body, in_flight)))
             }
         },
         true,
@@ -135,26 +261,31 @@ pub fn generate_server_module(trait_name: &syn::Ident, vis: &syn::Visibility, pa
             )]
             use tonic::codegen::*;
             use super::*;
+            #types_vis use super::#types_module as types;
 
-            pub trait #trait_name: ::core::marker::Send + ::core::marker::Sync + 'static {
+            pub trait #trait_name<#params>: ::core::marker::Send + ::core::marker::Sync + 'static
+            where
+                #predicates
+            {
                 #(#associated_types)*
                 #(#trait_methods)*
             }
 
-            impl<T> #trait_name for T
+            impl<T, #params> #trait_ref for T
             where
-                T: super::#trait_name + ::core::marker::Send + ::core::marker::Sync + 'static,
+                T: super::#trait_ref + ::core::marker::Send + ::core::marker::Sync + 'static,
+                #predicates
             {
                 #(#blanket_types)*
                 #(#blanket_methods)*
             }
 
             #[derive(Debug)]
-            pub struct #server_struct<T> {
+            pub struct #server_struct<T, #params> {
                 #service_fields
             }
 
-            impl<T> #server_struct<T> {
+            impl<T, #params> #server_struct<T, #args> {
                 #service_constructors
 
                 pub fn with_interceptor<F>(
@@ -167,14 +298,67 @@ pub fn generate_server_module(trait_name: &syn::Ident, vis: &syn::Visibility, pa
                     InterceptedService::new(Self::new(inner), interceptor)
                 }
 
+                #cache_accessor
+
+                #dedup_accessor
+
+                /// gRPC service name (`package.Service`).
+                pub const SERVICE_NAME: &'static str = SERVICE_NAME;
+
+                /// Metadata of every rpc served by this service.
+                pub const METHODS: &'static [::proto_rs::MethodDescriptor] = METHODS;
+
+                /// Shutdown state shared by every clone of this server.
+                pub fn drain_handle(&self) -> ::proto_rs::shutdown::Drain {
+                    self.drain.clone()
+                }
+
+                /// Serves only this service on `addr` until `signal` resolves, then cancels
+                /// in-flight handlers via the drain, rejects new calls and waits for running
+                /// calls and streams to finish.
+                #[cfg(not(target_arch = "wasm32"))]
+                pub async fn serve_with_shutdown<F>(
+                    self,
+                    addr: ::std::net::SocketAddr,
+                    signal: F,
+                ) -> ::core::result::Result<(), tonic::transport::Error>
+                where
+                    F: ::core::future::Future<Output = ()>,
+                    Self: tonic::codegen::Service<
+                            http::Request<tonic::body::Body>,
+                            Response = http::Response<tonic::body::Body>,
+                            Error = ::core::convert::Infallible,
+                        > + tonic::server::NamedService
+                        + Clone
+                        + ::core::marker::Send
+                        + ::core::marker::Sync
+                        + 'static,
+                    <Self as tonic::codegen::Service<http::Request<tonic::body::Body>>>::Future: ::core::marker::Send + 'static,
+                {
+                    let drain = self.drain.clone();
+                    let cancel = drain.clone();
+                    // tonic stops accepting connections as soon as the signal resolves and waits
+                    // for open streams; the drain then covers calls that outlive them.
+                    let served = tonic::transport::Server::builder()
+                        .add_service(self)
+                        .serve_with_shutdown(addr, async move {
+                            signal.await;
+                            cancel.cancel();
+                        })
+                        .await;
+                    drain.drain().await;
+                    served
+                }
+
                 #compression_methods
             }
 
-            impl<T, B> tonic::codegen::Service<http::Request<B>> for #server_struct<T>
+            impl<T, #params B> tonic::codegen::Service<http::Request<B>> for #server_struct<T, #args>
             where
-                T: #trait_name,
+                T: #trait_ref #auth_bound,
                 B: Body + ::core::marker::Send + 'static,
                 B::Error: Into<StdError> + ::core::marker::Send + 'static,
+                #predicates
             {
                 type Response = http::Response<tonic::body::Body>;
                 type Error = ::core::convert::Infallible;
@@ -187,17 +371,22 @@ pub fn generate_server_module(trait_name: &syn::Ident, vis: &syn::Visibility, pa
                     Poll::Ready(Ok(()))
                 }
 
-                fn call(&mut self, req: http::Request<B>) -> Self::Future {
+                fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+                    req.extensions_mut().insert(self.drain.clone());
+                    let in_flight = self.drain.try_guard();
                     let accept_compression_encodings = self.accept_compression_encodings;
                     let send_compression_encodings = self.send_compression_encodings;
                     let max_decoding_message_size = self.max_decoding_message_size;
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
+                    #limits_capture
+                    #cache_capture
+                    #dedup_capture
                     #call_future_body
                 }
             }
 
-            impl<T> Clone for #server_struct<T> {
+            impl<T, #params> Clone for #server_struct<T, #args> {
                 fn clone(&self) -> Self {
                     Self {
                         inner: self.inner.clone(),
@@ -205,19 +394,246 @@ pub fn generate_server_module(trait_name: &syn::Ident, vis: &syn::Visibility, pa
                         send_compression_encodings: self.send_compression_encodings,
                         max_decoding_message_size: self.max_decoding_message_size,
                         max_encoding_message_size: self.max_encoding_message_size,
+                        drain: self.drain.clone(),
+                        #marker_init
+                        #limits_clone
+                        #cache_clone
+                        #dedup_clone
                     }
                 }
             }
 
             pub const SERVICE_NAME: &str = #service_name_value;
 
-            impl<T> tonic::server::NamedService for #server_struct<T> {
+            /// Message-bus subjects (`package.Service.Method`) served by this service.
+            pub const SUBJECTS: &[&str] = &[#(#subjects),*];
+
+            /// Name, route, streaming shape and message types of every rpc, in declaration order.
+            pub const METHODS: &[::proto_rs::MethodDescriptor] = &[#(#method_descriptors),*];
+
+            impl<T, #params> tonic::server::NamedService for #server_struct<T, #args> {
                 const NAME: &'static str = SERVICE_NAME;
             }
+
+            impl<T, #params> ::proto_rs::dispatch::ServiceDispatcher for #server_struct<T, #args>
+            where
+                T: #trait_ref #auth_bound,
+                #predicates
+            {
+                fn service_name(&self) -> &'static str {
+                    SERVICE_NAME
+                }
+
+                fn methods(&self) -> &'static [::proto_rs::MethodDescriptor] {
+                    METHODS
+                }
+
+                fn dispatch(
+                    &self,
+                    method: &str,
+                    mut request: tonic::Request<Bytes>,
+                ) -> BoxFuture<tonic::Response<::proto_rs::dispatch::DispatchReply>, tonic::Status> {
+                    request.extensions_mut().insert(self.drain.clone());
+                    let in_flight = self.drain.try_guard();
+                    let inner = self.inner.clone();
+                    let method = ::std::string::String::from(method);
+                    #limits_capture
+                    ::std::boxed::Box::pin(async move {
+                        let ::core::option::Option::Some(in_flight) = in_flight else {
+                            return Err(tonic::Status::unavailable("server is shutting down"));
+                        };
+                        #dispatch_auth_check
+                        #limits_acquire
+                        let reply = match method.as_str() {
+                            #(#dispatch_arms)*
+                            _ => Err(tonic::Status::unimplemented(::std::format!("unknown method `{method}`"))),
+                        };
+                        reply.map(|reply| reply.map(|reply| ::proto_rs::shutdown::guard_reply(reply, in_flight)))
+                    })
+                }
+            }
         }
     }
 }
 
+/// `match` arm of the generated `ServiceDispatcher::dispatch`, keyed by the proto method name.
+fn generate_dispatch_arm(method: &MethodInfo, trait_ref: &TokenStream) -> TokenStream {
+    let name = method.proto_name();
+    let method_name = &method.name;
+    let request_proto = generate_request_proto_type(&method.request_type);
+    let await_suffix = if method.is_async {
+        quote! { .await }
+    } else {
+        quote! {}
+    };
+    let call = quote! {
+        let request = ::proto_rs::dispatch::decode_request::<#request_proto>(request)?;
+        let response = <T as #trait_ref>::#method_name(&inner, request)#await_suffix
+    };
+
+    if is_streaming_method(method) {
+        let item_type = method.stream_item_type.as_ref().unwrap();
+        let response_proto = generate_response_proto_type(method.inner_response_type.as_ref().unwrap());
+        let question = if method.response_is_result {
+            quote! { ? }
+        } else {
+            quote! {}
+        };
+        quote! {
+            #name => {
+                #call #question;
+                let response = response.map(|stream| {
+                    ::tonic::codegen::tokio_stream::StreamExt::map(
+                        stream,
+                        ::proto_rs::map_proto_stream_result::<#item_type, #response_proto>,
+                    )
+                });
+                Ok(::proto_rs::dispatch::stream_reply::<
+                    <#item_type as ::proto_rs::ProtoResponse<#response_proto>>::Encode,
+                    <#item_type as ::proto_rs::ProtoResponse<#response_proto>>::Mode,
+                    _,
+                >(response))
+            }
+        }
+    } else {
+        let response_return_type = &method.response_return_type;
+        let response_proto = generate_response_proto_type(&method.response_type);
+        quote! {
+            #name => {
+                #call?;
+                ::proto_rs::dispatch::unary_reply::<
+                    <#response_return_type as ::proto_rs::ProtoResponse<#response_proto>>::Encode,
+                    <#response_return_type as ::proto_rs::ProtoResponse<#response_proto>>::Mode,
+                >(response)
+            }
+        }
+    }
+}
+
+/// `MethodDescriptor` literals for the `METHODS` table.
+fn generate_method_descriptors(
+    package_name: &str,
+    trait_name: &syn::Ident,
+    methods: &[MethodInfo],
+    type_names: &[(String, String)],
+) -> Vec<TokenStream> {
+    methods
+        .iter()
+        .zip(type_names)
+        .map(|(method, (request_type, response_type))| {
+            let name = method.proto_name();
+            let route = generate_route_path(package_name, trait_name, method);
+            let server_streaming = is_streaming_method(method);
+            quote! {
+                ::proto_rs::MethodDescriptor {
+                    name: #name,
+                    route: #route,
+                    client_streaming: false,
+                    server_streaming: #server_streaming,
+                    request_type: #request_type,
+                    response_type: #response_type,
+                }
+            }
+        })
+        .collect()
+}
+
+/// Field, constructor init, clone, call capture and pre-dispatch wait for `concurrency_limit` / `rate_limit`.
+fn generate_limits(limits: ServerLimits) -> (TokenStream, TokenStream, TokenStream, TokenStream, TokenStream) {
+    if limits.is_empty() {
+        return (quote! {}, quote! {}, quote! {}, quote! {}, quote! {});
+    }
+    let concurrency = limits.concurrency.map_or_else(
+        || quote! { ::core::option::Option::None },
+        |limit| quote! { ::core::option::Option::Some(#limit) },
+    );
+    let rate = limits.rate.map_or_else(
+        || quote! { ::core::option::Option::None },
+        |(calls, millis)| quote! { ::core::option::Option::Some((#calls, ::core::time::Duration::from_millis(#millis))) },
+    );
+    (
+        quote! { limits: ::proto_rs::limit::ServiceLimits, },
+        quote! { limits: ::proto_rs::limit::ServiceLimits::new(#concurrency, #rate), },
+        quote! { limits: self.limits.clone(), },
+        quote! { let limits = self.limits.clone(); },
+        quote! { let _permit = limits.acquire().await; },
+    )
+}
+
+/// Field, constructor init, clone, call capture and accessor for the response cache of `#[proto(cache(...))]` methods.
+fn generate_cache_state(methods: &[MethodInfo]) -> (TokenStream, TokenStream, TokenStream, TokenStream, TokenStream) {
+    if methods.iter().all(|method| method.cache.is_none()) {
+        return (quote! {}, quote! {}, quote! {}, quote! {}, quote! {});
+    }
+    (
+        quote! { cache: ::proto_rs::cache::ResponseCache, },
+        quote! { cache: ::core::default::Default::default(), },
+        quote! { cache: self.cache.clone(), },
+        quote! { let cache = self.cache.clone(); },
+        quote! {
+            /// Cache shared by every clone of this server; exposes per-route hit/miss counters.
+            pub fn response_cache(&self) -> &::proto_rs::cache::ResponseCache {
+                &self.cache
+            }
+        },
+    )
+}
+
+/// Field, constructor init, clone, call capture and accessor for the call sharing of `#[proto(idempotent)]` methods.
+fn generate_dedup_state(methods: &[MethodInfo]) -> (TokenStream, TokenStream, TokenStream, TokenStream, TokenStream) {
+    if !methods.iter().any(|method| method.idempotent) {
+        return (quote! {}, quote! {}, quote! {}, quote! {}, quote! {});
+    }
+    (
+        quote! { dedup: ::proto_rs::dedup::InFlight, },
+        quote! { dedup: ::core::default::Default::default(), },
+        quote! { dedup: self.dedup.clone(), },
+        quote! { let dedup = self.dedup.clone(); },
+        quote! {
+            /// Identical in-flight calls shared by every clone of this server; exposes per-route counters.
+            pub fn in_flight(&self) -> &::proto_rs::dedup::InFlight {
+                &self.dedup
+            }
+        },
+    )
+}
+
+/// Extra service bound and the pre-dispatch check rejecting unauthenticated requests.
+fn generate_auth_check(auth: Option<AuthScheme>) -> (TokenStream, TokenStream) {
+    let (bound, check) = match auth {
+        None => return (quote! {}, quote! {}),
+        Some(AuthScheme::Bearer) => (quote! { ::proto_rs::auth::BearerAuth }, quote! { ::proto_rs::auth::check_bearer }),
+        Some(AuthScheme::Provider) => (
+            quote! { ::proto_rs::auth::AuthProvider },
+            quote! { ::proto_rs::auth::check_provider },
+        ),
+    };
+    (
+        quote! { + #bound },
+        quote! {
+            match #check(&*inner, req.headers()) {
+                ::core::result::Result::Ok(::core::option::Option::Some(principal)) => {
+                    req.extensions_mut().insert(principal);
+                }
+                ::core::result::Result::Ok(::core::option::Option::None) => {}
+                ::core::result::Result::Err(status) => return Ok(status.into_http()),
+            }
+        },
+    )
+}
+
+/// Authentication check of `ServiceDispatcher::dispatch`, run against the request metadata.
+fn generate_dispatch_auth_check(auth: Option<AuthScheme>) -> TokenStream {
+    let check = match auth {
+        None => return quote! {},
+        Some(AuthScheme::Bearer) => quote! { ::proto_rs::auth::check_bearer },
+        Some(AuthScheme::Provider) => quote! { ::proto_rs::auth::check_provider },
+    };
+    quote! {
+        #check(&*inner, request.metadata().as_ref())?;
+    }
+}
+
 // ============================================================================
 // TRAIT COMPONENTS
 // ============================================================================
@@ -309,7 +725,7 @@ fn generate_stream_associated_type(method: &MethodInfo) -> TokenStream {
 // BLANKET IMPL COMPONENTS
 // ============================================================================
 
-fn generate_blanket_impl_components(methods: &[MethodInfo], trait_name: &syn::Ident) -> (Vec<TokenStream>, Vec<TokenStream>) {
+fn generate_blanket_impl_components(methods: &[MethodInfo], trait_name: &TokenStream) -> (Vec<TokenStream>, Vec<TokenStream>) {
     let mut blanket_types = Vec::new();
     let mut blanket_methods = Vec::new();
     let mut seen_streams = HashSet::new();
@@ -327,13 +743,13 @@ fn generate_blanket_impl_components(methods: &[MethodInfo], trait_name: &syn::Id
     (blanket_types, blanket_methods)
 }
 
-fn generate_blanket_stream_type(method: &MethodInfo, trait_name: &syn::Ident) -> TokenStream {
+fn generate_blanket_stream_type(method: &MethodInfo, trait_name: &TokenStream) -> TokenStream {
     let stream_name = method.stream_type_name.as_ref().unwrap();
 
     quote! { type #stream_name = <Self as super::#trait_name>::#stream_name; }
 }
 
-fn generate_blanket_method(method: &MethodInfo, trait_name: &syn::Ident) -> TokenStream {
+fn generate_blanket_method(method: &MethodInfo, trait_name: &TokenStream) -> TokenStream {
     if is_streaming_method(method) {
         generate_blanket_streaming_method(method, trait_name)
     } else {
@@ -341,7 +757,7 @@ fn generate_blanket_method(method: &MethodInfo, trait_name: &syn::Ident) -> Toke
     }
 }
 
-fn generate_blanket_unary_method(method: &MethodInfo, trait_name: &syn::Ident) -> TokenStream {
+fn generate_blanket_unary_method(method: &MethodInfo, trait_name: &TokenStream) -> TokenStream {
     let method_name = &method.name;
     let request_type = &method.request_type;
     let response_type = &method.response_type;
@@ -421,7 +837,7 @@ fn generate_blanket_unary_method(method: &MethodInfo, trait_name: &syn::Ident) -
     }
 }
 
-fn generate_blanket_streaming_method(method: &MethodInfo, trait_name: &syn::Ident) -> TokenStream {
+fn generate_blanket_streaming_method(method: &MethodInfo, trait_name: &TokenStream) -> TokenStream {
     let method_name = &method.name;
     let request_type = &method.request_type;
     let stream_name = method.stream_type_name.as_ref().unwrap();
@@ -574,19 +990,40 @@ fn generate_blanket_streaming_method(method: &MethodInfo, trait_name: &syn::Iden
 // ROUTE HANDLER GENERATION
 // ============================================================================
 
-fn generate_route_handler(method: &MethodInfo, package_name: &str, trait_name: &syn::Ident) -> TokenStream {
+fn generate_route_handler(
+    method: &MethodInfo,
+    package_name: &str,
+    trait_name: &syn::Ident,
+    generics: &TraitGenerics,
+    authenticated: bool,
+) -> TokenStream {
     let method_name = &method.name;
-    let route_path = generate_route_path(package_name, trait_name, method_name);
+    let route_path = generate_route_path(package_name, trait_name, method);
     let svc_name = syn::Ident::new(&format!("{}Svc", to_pascal_case(&method_name.to_string())), method_name.span());
 
     if is_streaming_method(method) {
-        generate_streaming_route_handler(method, &route_path, &svc_name, trait_name)
+        generate_streaming_route_handler(method, &route_path, &svc_name, generics)
     } else {
-        generate_unary_route_handler(method, &route_path, &svc_name, trait_name)
+        generate_unary_route_handler(method, &route_path, &svc_name, generics, authenticated)
     }
 }
 
-fn generate_unary_route_handler(method: &MethodInfo, route_path: &str, svc_name: &syn::Ident, trait_name: &syn::Ident) -> TokenStream {
+fn generate_unary_route_handler(
+    method: &MethodInfo,
+    route_path: &str,
+    svc_name: &syn::Ident,
+    generics: &TraitGenerics,
+    authenticated: bool,
+) -> TokenStream {
+    let TraitGenerics {
+        trait_ref: trait_name,
+        params,
+        args,
+        predicates,
+        svc_marker_ty,
+        svc_marker_init,
+        ..
+    } = generics;
     let method_name = &method.name;
     let request_type = &method.request_type;
     let response_type = &method.response_type;
@@ -601,7 +1038,7 @@ fn generate_unary_route_handler(method: &MethodInfo, route_path: &str, svc_name:
         <#response_return_type as ::proto_rs::ProtoResponse<#response_proto>>::Mode
     };
     let decode_type = quote! { #request_proto };
-    let codec_init = generate_codec_init(encode_type.clone(), decode_type, Some(mode_type));
+    let codec_init = generate_codec_init(encode_type.clone(), decode_type, Some(mode_type), route_path);
     let await_suffix = if method.is_async {
         quote! { .await }
     } else {
@@ -611,19 +1048,84 @@ fn generate_unary_route_handler(method: &MethodInfo, route_path: &str, svc_name:
         quote! { ::core::result::Result<tonic::Response<Self::Response>, tonic::Status> },
         true,
     );
-    let call_future = wrap_call_future(
-        method.is_async,
-        quote! {
-            <T as #trait_name>::#method_name(&inner, request)#await_suffix
-        },
-    );
+    let mut invoke = quote! { <T as #trait_name>::#method_name(&inner, request)#await_suffix };
+    let mut shared_field_ty = quote! {};
+    let mut shared_field_init = quote! {};
+    let mut shared_capture = quote! {};
+    let mut vary = Vec::new();
+    if let Some(cache) = &method.cache {
+        let ttl_millis = cache.ttl_millis;
+        vary.clone_from(&cache.vary);
+        shared_field_ty.extend(quote! { , ::proto_rs::cache::ResponseCache });
+        shared_field_init.extend(quote! { , cache });
+        shared_capture.extend(quote! { let cache = self.1.clone(); });
+        // Without a key (authenticated service, no principal) the call bypasses the cache.
+        invoke = quote! {
+            let ::core::option::Option::Some(key) = key else {
+                return #invoke;
+            };
+            if let ::core::option::Option::Some((metadata, message)) =
+                cache.get::<(tonic::metadata::MetadataMap, Self::Response)>(&key)
+            {
+                return Ok(tonic::Response::from_parts(metadata, message, ::core::default::Default::default()));
+            }
+            let response = #invoke?;
+            let (metadata, message, extensions) = response.into_parts();
+            cache.insert(
+                key,
+                (metadata.clone(), message.clone()),
+                ::core::time::Duration::from_millis(#ttl_millis),
+            );
+            Ok::<_, tonic::Status>(tonic::Response::from_parts(metadata, message, extensions))
+        };
+    }
+    if method.idempotent {
+        let index = syn::Index::from(if method.cache.is_some() { 2 } else { 1 });
+        shared_field_ty.extend(quote! { , ::proto_rs::dedup::InFlight });
+        shared_field_init.extend(quote! { , dedup });
+        shared_capture.extend(quote! { let dedup = self.#index.clone(); });
+        let shared_key = if method.cache.is_some() {
+            quote! { key.clone() }
+        } else {
+            quote! { key }
+        };
+        // Identical requests wait for the call already running, cache lookup included.
+        invoke = quote! {
+            dedup
+                .run(#shared_key, move || async move {
+                    let (metadata, message, _) = async move { #invoke }.await?.into_parts();
+                    Ok::<_, tonic::Status>((metadata, message))
+                })
+                .await
+                .map(|(metadata, message)| tonic::Response::from_parts(metadata, message, ::core::default::Default::default()))
+        };
+    }
+    let (cache_predicates, call_future) = if method.cache.is_some() || method.idempotent {
+        let call_future = wrap_async_block(quote! { async move { #invoke } }, true);
+        (
+            quote! { #encode_type: ::core::clone::Clone + ::core::marker::Send + ::core::marker::Sync + 'static, },
+            quote! {
+                #shared_capture
+                let key = ::proto_rs::cache::request_key(#route_path, &request, #authenticated, &[#(#vary),*]);
+                #call_future
+            },
+        )
+    } else {
+        (quote! {}, wrap_call_future(method.is_async, invoke))
+    };
 
     quote! {
         #route_path => {
             #[allow(non_camel_case_types)]
-            struct #svc_name<T: #trait_name>(pub Arc<T>);
+            struct #svc_name<T: #trait_name, #params>(pub Arc<T> #shared_field_ty #svc_marker_ty)
+            where
+                #predicates;
 
-            impl<T: #trait_name> tonic::server::UnaryService<#request_proto> for #svc_name<T> {
+            impl<T: #trait_name, #params> tonic::server::UnaryService<#request_proto> for #svc_name<T, #args>
+            where
+                #cache_predicates
+                #predicates
+            {
                 type Response = <#response_return_type as ::proto_rs::ProtoResponse<#response_proto>>::Encode;
                 type Future = #future_type;
 
@@ -633,7 +1135,7 @@ fn generate_unary_route_handler(method: &MethodInfo, route_path: &str, svc_name:
                 }
             }
 
-            let method = #svc_name(inner);
+            let method = #svc_name(inner #shared_field_init #svc_marker_init);
             #codec_init
             let mut grpc = tonic::server::Grpc::new(codec)
                 .apply_compression_config(
@@ -650,7 +1152,16 @@ fn generate_unary_route_handler(method: &MethodInfo, route_path: &str, svc_name:
     }
 }
 
-fn generate_streaming_route_handler(method: &MethodInfo, route_path: &str, svc_name: &syn::Ident, trait_name: &syn::Ident) -> TokenStream {
+fn generate_streaming_route_handler(method: &MethodInfo, route_path: &str, svc_name: &syn::Ident, generics: &TraitGenerics) -> TokenStream {
+    let TraitGenerics {
+        trait_ref: trait_name,
+        params,
+        args,
+        predicates,
+        svc_marker_ty,
+        svc_marker_init,
+        ..
+    } = generics;
     let method_name = &method.name;
     let request_type = &method.request_type;
     let inner_type = method.inner_response_type.as_ref().unwrap();
@@ -662,7 +1173,7 @@ fn generate_streaming_route_handler(method: &MethodInfo, route_path: &str, svc_n
     let encode_type = quote! { <#item_type as ::proto_rs::ProtoResponse<#response_proto>>::Encode };
     let decode_type = quote! { #request_proto };
     let mode_type = quote! { <#item_type as ::proto_rs::ProtoResponse<#response_proto>>::Mode };
-    let codec_init = generate_codec_init(encode_type, decode_type, Some(mode_type));
+    let codec_init = generate_codec_init(encode_type, decode_type, Some(mode_type), route_path);
     let await_suffix = if method.is_async {
         quote! { .await }
     } else {
@@ -723,9 +1234,14 @@ fn generate_streaming_route_handler(method: &MethodInfo, route_path: &str, svc_n
     quote! {
         #route_path => {
             #[allow(non_camel_case_types)]
-            struct #svc_name<T: #trait_name>(pub Arc<T>);
+            struct #svc_name<T: #trait_name, #params>(pub Arc<T> #svc_marker_ty)
+            where
+                #predicates;
 
-            impl<T: #trait_name> tonic::server::ServerStreamingService<#request_proto> for #svc_name<T> {
+            impl<T: #trait_name, #params> tonic::server::ServerStreamingService<#request_proto> for #svc_name<T, #args>
+            where
+                #predicates
+            {
                 type Response = <#item_type as ::proto_rs::ProtoResponse<#response_proto>>::Encode;
                 type ResponseStream = ::tonic::codegen::tokio_stream::adapters::Map<
                     T::#stream_name,
@@ -746,7 +1262,7 @@ fn generate_streaming_route_handler(method: &MethodInfo, route_path: &str, svc_n
 
 
 
-            let method = #svc_name(inner);
+            let method = #svc_name(inner #svc_marker_init);
             #codec_init
             let mut grpc = tonic::server::Grpc::new(codec)
                 .apply_compression_config(
@@ -818,6 +1334,11 @@ mod tests {
                 inner_response_type: Some(parse_quote!(FooResponse)),
                 stream_item_type: Some(parse_quote!(FooResponse)),
                 user_method_signature: TokenStream::default(),
+                rpc_name: None,
+                route: None,
+                cache: None,
+                idempotent: false,
+                options: Vec::new(),
             },
             MethodInfo {
                 name: parse_quote!(rizz_uni_other),
@@ -833,6 +1354,11 @@ mod tests {
                 inner_response_type: Some(parse_quote!(FooResponse)),
                 stream_item_type: Some(parse_quote!(FooResponse)),
                 user_method_signature: TokenStream::default(),
+                rpc_name: None,
+                route: None,
+                cache: None,
+                idempotent: false,
+                options: Vec::new(),
             },
         ];
 
@@ -11,7 +11,11 @@ use syn::TraitItemType;
 use syn::Type;
 use syn::TypePath;
 
+use crate::parse::parse_duration_millis;
+use crate::utils::MethodCache;
 use crate::utils::MethodInfo;
+use crate::utils::ProtoOption;
+use crate::utils::push_proto_option;
 
 pub(crate) fn is_response_wrapper(ty: &Type) -> bool {
     matches!(
@@ -66,17 +70,45 @@ impl ParsedMethodSignature {
 }
 
 /// Extract methods and associated types from the trait definition
-pub fn extract_methods_and_types(input: &ItemTrait) -> (Vec<MethodInfo>, Vec<TokenStream>) {
+pub fn extract_methods_and_types(input: &ItemTrait) -> syn::Result<(Vec<MethodInfo>, Vec<TokenStream>)> {
     let mut methods = Vec::with_capacity(input.items.len());
     let mut user_associated_types = Vec::new();
 
     for item in &input.items {
         match item {
-            TraitItem::Fn(method) => {
+            TraitItem::Fn(method) if method.default.is_none() => {
                 let method_name = method.sig.ident.clone();
                 let signature = ParsedMethodSignature::new(&method.sig, &input.items);
+                let MethodProtoAttrs {
+                    rpc_name,
+                    route,
+                    cache,
+                    idempotent,
+                    options,
+                } = parse_method_proto_attrs(&method.attrs)?;
+                if let (Some(rpc_name), Some(route)) = (&rpc_name, &route)
+                    && route.rsplit_once('/').is_some_and(|(_, rpc)| rpc != rpc_name)
+                {
+                    return Err(syn::Error::new_spanned(
+                        &method.sig,
+                        format!("rpc_name `{rpc_name}` disagrees with the rpc of route `{route}`"),
+                    ));
+                }
+                if cache.is_some() && signature.is_streaming {
+                    return Err(syn::Error::new_spanned(
+                        &method.sig,
+                        format!("#[proto(cache(...))] is only supported on unary methods, `{method_name}` is streaming"),
+                    ));
+                }
+                if idempotent && signature.is_streaming {
+                    return Err(syn::Error::new_spanned(
+                        &method.sig,
+                        format!("#[proto(idempotent)] is only supported on unary methods, `{method_name}` is streaming"),
+                    ));
+                }
+                let user_attrs: Vec<_> = method.attrs.iter().filter(|attr| !attr.path().is_ident("proto")).cloned().collect();
 
-                let user_method_signature = generate_user_method_signature(&method.attrs, &method_name, &signature);
+                let user_method_signature = generate_user_method_signature(&user_attrs, &method_name, &signature);
 
                 methods.push(MethodInfo {
                     name: method_name,
@@ -92,6 +124,11 @@ pub fn extract_methods_and_types(input: &ItemTrait) -> (Vec<MethodInfo>, Vec<Tok
                     inner_response_type: signature.inner_response_type,
                     stream_item_type: signature.stream_item_type,
                     user_method_signature,
+                    rpc_name,
+                    route,
+                    cache,
+                    idempotent,
+                    options,
                 });
             }
             TraitItem::Type(type_item) => {
@@ -108,7 +145,101 @@ pub fn extract_methods_and_types(input: &ItemTrait) -> (Vec<MethodInfo>, Vec<Tok
         }
     }
 
-    (methods, user_associated_types)
+    Ok((methods, user_associated_types))
+}
+
+/// Extract methods with a default body; they are helpers, not RPCs, and are passed through untouched
+pub fn extract_helper_methods(input: &ItemTrait) -> Vec<&syn::TraitItemFn> {
+    input
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            TraitItem::Fn(method) if method.default.is_some() => Some(method),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Method-level `#[proto(...)]` options
+#[derive(Default)]
+struct MethodProtoAttrs {
+    rpc_name: Option<String>,
+    route: Option<String>,
+    cache: Option<MethodCache>,
+    idempotent: bool,
+    options: Vec<ProtoOption>,
+}
+
+/// Parse `#[proto(rpc_name = "...", route = "...", cache(ttl = "...", key = "request"), idempotent, option("pkg.name") = ...)]`
+/// on a trait method
+fn parse_method_proto_attrs(attrs: &[syn::Attribute]) -> syn::Result<MethodProtoAttrs> {
+    let mut parsed = MethodProtoAttrs::default();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("proto")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("cache") {
+                parsed.cache = Some(parse_method_cache(&meta)?);
+                return Ok(());
+            }
+            if meta.path.is_ident("idempotent") {
+                parsed.idempotent = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("option") {
+                return push_proto_option(&mut parsed.options, &meta);
+            }
+            let value: syn::LitStr = meta.value()?.parse()?;
+            if meta.path.is_ident("rpc_name") {
+                parsed.rpc_name = Some(value.value());
+            } else if meta.path.is_ident("route") {
+                let route = value.value();
+                let rpc = route.strip_prefix('/').and_then(|path| path.split_once('/')).map(|(_, rpc)| rpc);
+                if rpc.is_none_or(|rpc| syn::parse_str::<syn::Ident>(rpc).is_err()) {
+                    return Err(syn::Error::new(value.span(), "route must look like \"/package.Service/Method\""));
+                }
+                parsed.route = Some(value.value());
+            } else {
+                return Err(
+                    meta.error("unknown #[proto(...)] method attribute, expected `rpc_name`, `route`, `cache`, `idempotent` or `option`")
+                );
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(parsed)
+}
+
+/// Parse `cache(ttl = "30s", key = "request", vary = "x-tenant, accept-language")`
+fn parse_method_cache(meta: &syn::meta::ParseNestedMeta) -> syn::Result<MethodCache> {
+    let mut ttl_millis = None;
+    let mut vary = Vec::new();
+    meta.parse_nested_meta(|nested| {
+        let value: syn::LitStr = nested.value()?.parse()?;
+        if nested.path.is_ident("ttl") {
+            ttl_millis = Some(
+                parse_duration_millis(&value.value())
+                    .ok_or_else(|| syn::Error::new(value.span(), "ttl must look like \"30s\" (units: ms, s, m, h)"))?,
+            );
+        } else if nested.path.is_ident("key") {
+            if value.value() != "request" {
+                return Err(syn::Error::new(value.span(), "only key = \"request\" is supported"));
+            }
+        } else if nested.path.is_ident("vary") {
+            for name in value.value().split(',').map(str::trim) {
+                let valid = name.bytes().all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.'));
+                if name.is_empty() || !valid || name.ends_with("-bin") {
+                    return Err(syn::Error::new(value.span(), format!("`{name}` is not an ascii metadata key")));
+                }
+                vary.push(name.to_ascii_lowercase());
+            }
+        } else {
+            return Err(nested.error("unknown cache option, expected `ttl`, `key` or `vary`"));
+        }
+        Ok(())
+    })?;
+    let ttl_millis = ttl_millis.ok_or_else(|| meta.error("cache(...) requires a ttl"))?;
+    Ok(MethodCache { ttl_millis, vary })
 }
 
 /// Generate user-facing method signature for the trait
@@ -21,16 +21,17 @@ enum GenericArgKind {
 use crate::parse::UnifiedProtoConfig;
 use crate::utils::MethodInfo;
 use crate::utils::ParsedFieldType;
-use crate::utils::collect_discriminants_for_variants;
+use crate::utils::collect_repr_discriminants;
 use crate::utils::derive_package_name;
+use crate::utils::enum_repr;
 use crate::utils::extract_field_wrapper_info;
 use crate::utils::find_marked_default_variant;
 use crate::utils::parse_field_config;
 use crate::utils::parse_field_type;
+use crate::utils::proto_options_tokens;
 use crate::utils::proto_type_name;
 use crate::utils::resolved_field_type;
 use crate::utils::rust_type_path_ident;
-use crate::utils::to_pascal_case;
 use crate::utils::to_upper_snake_case;
 
 pub fn assoc_proto_ident_const(
@@ -40,16 +41,7 @@ pub fn assoc_proto_ident_const(
     proto_names: &[String],
 ) -> TokenStream2 {
     let proto_name_base = proto_names.first().map_or_else(|| type_ident.to_string(), ToString::to_string);
-    let (proto_package, proto_file_path) = config.proto_path().map_or_else(
-        || (String::new(), String::new()),
-        |path| {
-            let file_name = std::path::Path::new(path).file_name().and_then(|name| name.to_str()).unwrap_or(path);
-            (derive_package_name(file_name), path.to_string())
-        },
-    );
-
-    let proto_package = proto_package.clone();
-    let proto_file_path = proto_file_path.clone();
+    let (proto_package, proto_file_path) = proto_path_info(config);
     let type_name_literal = type_ident.to_string();
     let proto_ident_literal = |proto_name_literal: &String| {
         quote! {
@@ -63,8 +55,18 @@ pub fn assoc_proto_ident_const(
             }
         }
     };
+    let empty_wkt = config.empty_wkt;
     let trait_impl =
         |impl_generics: &TokenStream2, type_tokens: &TokenStream2, where_clause: &TokenStream2, proto_name_literal: &String| {
+            if empty_wkt {
+                return quote! {
+                    #[cfg(feature = "build-schemas")]
+                    impl #impl_generics ::proto_rs::schemas::ProtoIdentifiable for #type_tokens #where_clause {
+                        const PROTO_IDENT: ::proto_rs::schemas::ProtoIdent = ::proto_rs::schemas::EMPTY_PROTO_IDENT;
+                        const PROTO_TYPE: ::proto_rs::schemas::ProtoType = ::proto_rs::schemas::EMPTY_PROTO_IDENT.proto_type;
+                    }
+                };
+            }
             let proto_ident = proto_ident_literal(proto_name_literal);
             quote! {
                 #[cfg(feature = "build-schemas")]
@@ -156,6 +158,8 @@ fn schema_tokens_for_struct_impl(
     is_concrete: bool,
 ) -> SchemaTokens {
     let fields_tokens = build_fields_tokens(type_ident, const_suffix, fields, config, is_concrete);
+    let extension_ranges: Vec<(u32, u32)> = fields.iter().flat_map(|field| parse_field_config(field).extensions).collect();
+    let reserved_tags: Vec<u32> = fields.iter().filter_map(|field| parse_field_config(field).offset_index).collect();
     let field_consts = fields_tokens.consts;
     let field_refs = fields_tokens.refs;
     let entry_tokens = quote! {
@@ -174,6 +178,8 @@ fn schema_tokens_for_struct_impl(
         &config.item_generics,
         SchemaKind::Message,
         is_concrete,
+        &extension_ranges,
+        &reserved_tags,
     )
 }
 
@@ -214,7 +220,8 @@ fn schema_tokens_for_simple_enum_impl(
         order.insert(0, idx);
     }
     let ordered_variants: Vec<&syn::Variant> = order.iter().map(|&idx| &data.variants[idx]).collect();
-    let ordered_discriminants = collect_discriminants_for_variants(&ordered_variants).unwrap_or_else(|err| panic!("{err}"));
+    let repr = enum_repr(&config.item_attrs).unwrap_or_else(|err| panic!("{err}"));
+    let ordered_discriminants = collect_repr_discriminants(&ordered_variants, repr).unwrap_or_else(|err| panic!("{err}"));
 
     let mut variant_consts = Vec::new();
     let mut variant_refs = Vec::new();
@@ -227,13 +234,18 @@ fn schema_tokens_for_simple_enum_impl(
     for (idx, (variant, value)) in ordered_variants.iter().zip(ordered_discriminants.iter()).enumerate() {
         let variant_const = variant_const_ident(type_ident, const_suffix, idx);
         let name = to_upper_snake_case(&variant.ident.to_string());
-        let value = *value;
+        // Values of 64-bit enums that do not fit a proto enum number are left out of the schema.
+        let value = if let Ok(value) = i32::try_from(*value) {
+            quote! { Some(#value) }
+        } else {
+            quote! { None }
+        };
         variant_consts.push(quote! {
             #[cfg(feature = "build-schemas")]
             const #variant_const: ::proto_rs::schemas::Variant = ::proto_rs::schemas::Variant {
                 name: #name,
                 fields: &[],
-                discriminant: Some(#value),
+                discriminant: #value,
             };
         });
 
@@ -263,6 +275,8 @@ fn schema_tokens_for_simple_enum_impl(
         &config.item_generics,
         SchemaKind::Message,
         is_concrete,
+        &[],
+        &[],
     )
 }
 
@@ -345,6 +359,8 @@ fn schema_tokens_for_complex_enum_impl(
         &config.item_generics,
         SchemaKind::Message,
         is_concrete,
+        &[],
+        &[],
     )
 }
 
@@ -407,6 +423,9 @@ pub fn schema_tokens_for_imports(type_ident: &str, file_name: &str, imports: &[S
             generics: &[],
             lifetimes: &[],
             top_level_attributes: &[],
+            options: &[],
+            extension_ranges: &[],
+            reserved_tags: &[],
             content: ::proto_rs::schemas::ProtoEntry::Import {
                 paths: #const_name,
             },
@@ -481,6 +500,8 @@ fn build_schema_tokens(
         generics,
         kind,
         false,
+        &[],
+        &[],
     )
 }
 
@@ -495,6 +516,8 @@ fn build_schema_tokens_impl(
     generics: &syn::Generics,
     kind: SchemaKind,
     is_concrete: bool,
+    extension_ranges: &[(u32, u32)],
+    reserved_tags: &[u32],
 ) -> SchemaTokens {
     let (proto_package, proto_file_path) = proto_path_info(config);
     let schema_ident = schema_ident(type_ident, const_suffix);
@@ -546,7 +569,14 @@ fn build_schema_tokens_impl(
         config.item_attrs.clone()
     };
 
-    let attrs_tokens = build_attribute_tokens(type_ident, const_suffix, &filtered_attrs, config.transparent, assoc_for_schema);
+    let attrs_tokens = build_attribute_tokens(
+        type_ident,
+        const_suffix,
+        &filtered_attrs,
+        config.transparent.then_some(if config.string_enum { "string_enum" } else { "transparent" }),
+        &proto_message_settings(config),
+        assoc_for_schema,
+    );
 
     let generics_consts = generics_tokens.consts;
     let generics_refs = generics_tokens.refs;
@@ -554,6 +584,10 @@ fn build_schema_tokens_impl(
     let lifetime_refs = lifetimes_tokens.refs;
     let attrs_consts = attrs_tokens.consts;
     let attrs_refs = attrs_tokens.refs;
+    let options = proto_options_tokens(&config.options);
+    let extension_ranges = extension_ranges.iter().map(|(start, end)| quote! { (#start, #end) });
+    let extension_ranges = quote! { &[#( #extension_ranges ),*] };
+    let reserved_tags = quote! { &[#( #reserved_tags ),*] };
 
     let schema = match kind {
         SchemaKind::Message if has_type_params => {
@@ -577,6 +611,9 @@ fn build_schema_tokens_impl(
                     generics: #generics_refs,
                     lifetimes: #lifetime_refs,
                     top_level_attributes: #attrs_refs,
+                    options: #options,
+                    extension_ranges: #extension_ranges,
+                    reserved_tags: #reserved_tags,
                     content: #entry_tokens,
                 };
             }
@@ -602,6 +639,9 @@ fn build_schema_tokens_impl(
                         generics: #generics_refs,
                         lifetimes: #lifetime_refs,
                         top_level_attributes: #attrs_refs,
+                        options: #options,
+                        extension_ranges: #extension_ranges,
+                        reserved_tags: #reserved_tags,
                         content: #entry_tokens,
                     };
                 }
@@ -626,6 +666,9 @@ fn build_schema_tokens_impl(
                     generics: #generics_refs,
                     lifetimes: #lifetime_refs,
                     top_level_attributes: #attrs_refs,
+                    options: #options,
+                    extension_ranges: #extension_ranges,
+                    reserved_tags: #reserved_tags,
                     content: #entry_tokens,
                 };
             }
@@ -696,7 +739,11 @@ fn build_service_method_tokens(
 
     for (idx, method) in methods.iter().enumerate() {
         let method_ident = service_method_const_ident(type_ident, suffix, idx);
-        let method_name = to_pascal_case(&method.name.to_string());
+        let method_name = method.proto_name();
+        let route = method.route.as_ref().map_or_else(
+            || quote! { ::core::option::Option::None },
+            |route| quote! { ::core::option::Option::Some(#route) },
+        );
         let request_ident = proto_ident_tokens_from_type(&method.request_type);
         let request_wrapper = if type_references_generic_params(&method.request_type, generics) {
             quote! { ::core::option::Option::None }
@@ -715,11 +762,13 @@ fn build_service_method_tokens(
         let (response_generic_consts, response_generic_args) =
             generic_args_tokens_from_type(type_ident, suffix, idx, "RESPONSE", response_type, generics, false);
         let server_streaming = method.is_streaming;
+        let options = proto_options_tokens(&method.options);
 
         method_consts.push(quote! {
             #[cfg(feature = "build-schemas")]
             const #method_ident: ::proto_rs::schemas::ServiceMethod = ::proto_rs::schemas::ServiceMethod {
                 name: #method_name,
+                route: #route,
                 request: #request_ident,
                 request_generic_args: #request_generic_args,
                 request_wrapper: #request_wrapper,
@@ -728,6 +777,7 @@ fn build_service_method_tokens(
                 response_wrapper: #response_wrapper,
                 client_streaming: false,
                 server_streaming: #server_streaming,
+                options: #options,
             };
             #request_generic_consts
             #response_generic_consts
@@ -841,11 +891,18 @@ fn build_lifetime_tokens(type_ident: &syn::Ident, suffix: &str, config: &Unified
     }
 }
 
+/// `key = "value"` settings of `#[proto_message(...)]` that schema consumers read back.
+fn proto_message_settings(config: &UnifiedProtoConfig) -> Vec<(&'static str, &str)> {
+    let settings = [("nest_in", &config.nest_in), ("unit", &config.unit), ("range", &config.range)];
+    settings.into_iter().filter_map(|(key, value)| Some((key, value.as_deref()?))).collect()
+}
+
 fn build_attribute_tokens(
     type_ident: &syn::Ident,
     suffix: &str,
     attrs: &[syn::Attribute],
-    include_transparent: bool,
+    transparent: Option<&str>,
+    settings: &[(&str, &str)],
     assoc: bool,
 ) -> AttributeTokens {
     let mut attr_consts = Vec::new();
@@ -869,14 +926,32 @@ fn build_attribute_tokens(
         }
     }
 
-    if include_transparent {
+    if let Some(transparent) = transparent {
+        let idx = attr_refs.len();
+        let attr_ident = attribute_const_ident(type_ident, suffix, idx);
+        attr_consts.push(quote! {
+            #[cfg(feature = "build-schemas")]
+            const #attr_ident: ::proto_rs::schemas::Attribute = ::proto_rs::schemas::Attribute {
+                path: "proto_message",
+                tokens: #transparent,
+            };
+        });
+        if assoc {
+            attr_refs.push(quote! { Self::#attr_ident });
+        } else {
+            attr_refs.push(quote! { #attr_ident });
+        }
+    }
+
+    for (key, value) in settings {
         let idx = attr_refs.len();
         let attr_ident = attribute_const_ident(type_ident, suffix, idx);
+        let tokens = format!("#[proto_message({key} = \"{value}\")]");
         attr_consts.push(quote! {
             #[cfg(feature = "build-schemas")]
             const #attr_ident: ::proto_rs::schemas::Attribute = ::proto_rs::schemas::Attribute {
                 path: "proto_message",
-                tokens: "transparent",
+                tokens: #tokens,
             };
         });
         if assoc {
@@ -941,24 +1016,19 @@ fn build_unnamed_fields_tokens(
 ) -> FieldTokens {
     let mut field_consts = Vec::new();
     let mut field_refs = Vec::new();
+    let mut field_num = 0;
 
     for (idx, field) in fields.iter().enumerate() {
         let field_config = parse_field_config(field);
         if field_config.skip {
             continue;
         }
-        let tag: u32 = field_config.custom_tag.unwrap_or(idx + 1).try_into().unwrap();
-        let FieldConstTokens { consts, refs } = build_field_const_tokens(
-            type_ident,
-            suffix,
-            idx,
-            field,
-            &field_config,
-            tag,
-            FieldName::Unnamed,
-            config,
-            is_concrete,
-        );
+        field_num += 1;
+        let tag: u32 = field_config.custom_tag.unwrap_or(field_num).try_into().unwrap();
+        // Unnamed fields are emitted as `field_N`
+        let name = field_config.proto_name.clone().map_or(FieldName::Unnamed, FieldName::Named);
+        let FieldConstTokens { consts, refs } =
+            build_field_const_tokens(type_ident, suffix, idx, field, &field_config, tag, name, config, is_concrete);
         field_consts.push(consts);
         field_refs.push(refs);
     }
@@ -1062,6 +1132,12 @@ fn field_info_tokens(
         }
     }
 
+    let parsed = parse_field_type(&inner_type);
+    // A conditional field is absent from some builds, so readers must not rely on its presence
+    if config.cfg_predicate().is_some() && parsed.map_kind.is_none() {
+        is_option = true;
+    }
+
     let label = if is_repeated {
         quote! { ::proto_rs::schemas::ProtoLabel::Repeated }
     } else if is_option {
@@ -1070,7 +1146,6 @@ fn field_info_tokens(
         quote! { ::proto_rs::schemas::ProtoLabel::None }
     };
 
-    let parsed = parse_field_type(&inner_type);
     let proto_ident = proto_ident_tokens(&inner_type, config, &parsed, item_generics);
     let rust_proto_ident = rust_proto_ident_tokens(&inner_type, config, &parsed, item_generics);
     let wrapper = if type_references_generic_params(&ty, item_generics) {
@@ -1201,7 +1276,14 @@ fn build_field_const_tokens(
     let has_type_params = item_config.item_generics.type_params().next().is_some();
     let use_self_prefix = !has_type_params;
 
-    let attrs_tokens = build_attribute_tokens(type_ident, &format!("{suffix}_FIELD_{idx}"), &field.attrs, false, use_self_prefix);
+    let attrs_tokens = build_attribute_tokens(
+        type_ident,
+        &format!("{suffix}_FIELD_{idx}"),
+        &field.attrs,
+        None,
+        &[],
+        use_self_prefix,
+    );
     let attr_consts = attrs_tokens.consts;
     let attr_refs = attrs_tokens.refs;
 
@@ -1220,6 +1302,11 @@ fn build_field_const_tokens(
         FieldName::Named(name) => quote! { ::core::option::Option::Some(#name) },
         FieldName::Unnamed => quote! { ::core::option::Option::None },
     };
+    let labels = &config.labels;
+    let annotations = config.annotations.iter().map(|(key, value)| {
+        quote! { ::proto_rs::schemas::Annotation { key: #key, value: #value } }
+    });
+    let options = proto_options_tokens(&config.options);
 
     let field_ref = if use_self_prefix {
         quote! { &Self::#field_ident }
@@ -1242,6 +1329,9 @@ fn build_field_const_tokens(
                 array_len: #array_len,
                 array_is_bytes: #array_is_bytes,
                 array_elem: #array_elem,
+                labels: &[#(#labels),*],
+                annotations: &[#(#annotations),*],
+                options: #options,
             };
             #attr_consts
             #extra_consts
@@ -1318,8 +1408,14 @@ fn generic_args_tokens_from_type(
     let mut arg_consts = Vec::new();
     let mut arg_refs = Vec::new();
     let mut arg_idx = 0usize;
+    // The allocator of `Vec<T, A>` and `Box<T, A>` is not part of the schema
+    let arg_count = if last.ident == "Vec" || last.ident == "Box" {
+        1
+    } else {
+        args.args.len()
+    };
 
-    for arg in &args.args {
+    for arg in args.args.iter().take(arg_count) {
         match arg {
             syn::GenericArgument::Type(arg_ty) => {
                 let kind = classify_generic_arg(arg, generics);
@@ -0,0 +1,267 @@
+//! Compile-time check of generated definitions against the `.proto` file already at
+//! `proto_path`, which serves as the recorded schema snapshot.
+//!
+//! A field (or enum value) that keeps its name but changes tag or type breaks every peer still
+//! using the old file. Each such change becomes a deprecation warning on the type, so it shows up
+//! in the build (and fails it under `-D warnings`).
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use proc_macro2::TokenStream;
+use quote::format_ident;
+use quote::quote;
+
+use crate::import_proto::FieldDef;
+use crate::import_proto::FieldType;
+use crate::import_proto::Label;
+use crate::import_proto::parse_file;
+
+/// File contents as they were before this process first wrote them
+static SNAPSHOTS: LazyLock<Mutex<HashMap<String, Snapshot>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+struct Snapshot {
+    contents: Option<String>,
+    /// Modification time the file has while `contents` is current: when it was read, or after
+    /// live emission last wrote it
+    mtime: Option<SystemTime>,
+}
+
+fn modified(file_name: &str) -> Option<SystemTime> {
+    std::fs::metadata(Path::new(".").join(file_name)).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Determine if generated definitions are checked against the snapshot
+/// Priority: env var > feature flag > default (false)
+pub fn should_check_schema() -> bool {
+    match std::env::var("PROTO_SCHEMA_CHECK").ok().as_deref() {
+        Some("0" | "false" | "False" | "FALSE") => false,
+        Some("1" | "true" | "True" | "TRUE") => true,
+        _ => cfg!(feature = "schema-check"),
+    }
+}
+
+/// Reads the snapshot of `file_name` before live emission overwrites it.
+///
+/// Long-lived macro servers such as rust-analyzer keep this process across rebuilds, so the
+/// snapshot is read again whenever the file changed other than through [`remember_write`].
+pub fn remember_snapshot(file_name: &str) {
+    let mut snapshots = SNAPSHOTS.lock().unwrap();
+    let mtime = modified(file_name);
+    if snapshots.get(file_name).is_some_and(|snapshot| snapshot.mtime == mtime) {
+        return;
+    }
+    let contents = std::fs::read_to_string(Path::new(".").join(file_name)).ok();
+    snapshots.insert(file_name.to_string(), Snapshot { contents, mtime });
+}
+
+/// Records that live emission wrote `file_name`, so its own output is not taken as a new snapshot
+pub fn remember_write(file_name: &str) {
+    if let Some(snapshot) = SNAPSHOTS.lock().unwrap().get_mut(file_name) {
+        snapshot.mtime = modified(file_name);
+    }
+}
+
+/// Warnings for every definition in `content` that disagrees with the snapshot of `file_name`
+pub fn schema_change_warnings(file_name: &str, content: &str) -> TokenStream {
+    remember_snapshot(file_name);
+    let snapshots = SNAPSHOTS.lock().unwrap();
+    let Some(snapshot) = snapshots.get(file_name).and_then(|snapshot| snapshot.contents.as_ref()) else {
+        return TokenStream::new();
+    };
+
+    let recorded = parse_definitions(snapshot);
+    let warnings = schema_changes(&recorded, &parse_definitions(content)).into_iter().enumerate().map(|(idx, change)| {
+        let note = format!("proto schema change against {file_name}: {change}");
+        let ident = format_ident!("PROTO_SCHEMA_CHANGE_{}", idx);
+        quote! {
+            const _: () = {
+                #[deprecated(note = #note)]
+                #[allow(non_upper_case_globals)]
+                const #ident: () = ();
+                #ident
+            };
+        }
+    });
+    quote! { #(#warnings)* }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct FieldShape {
+    ty: String,
+    tag: String,
+}
+
+/// `message`/`enum` name -> field name -> shape, read with the `import_proto!` parser.
+///
+/// Nested definitions are keyed by their own name, since the generated content of a nested
+/// message is checked before `nest_in` moves it into its parent. Fields of a `oneof` belong to
+/// the enclosing message. Text the parser rejects has no definitions, so it is never compared.
+fn parse_definitions(text: &str) -> BTreeMap<String, BTreeMap<String, FieldShape>> {
+    let Ok(file) = parse_file(text) else {
+        return BTreeMap::new();
+    };
+    let own_name = |path: &str| path.rsplit('.').next().unwrap_or(path).to_string();
+    let mut definitions: BTreeMap<String, BTreeMap<String, FieldShape>> = BTreeMap::new();
+    for message in &file.messages {
+        let fields = definitions.entry(own_name(&message.path)).or_default();
+        for field in message.fields.iter().chain(message.oneofs.iter().flat_map(|(_, fields)| fields)) {
+            fields.insert(
+                field.name.clone(),
+                FieldShape {
+                    ty: field_type(field),
+                    tag: field.tag.to_string(),
+                },
+            );
+        }
+    }
+    for enumeration in &file.enums {
+        let values = definitions.entry(own_name(&enumeration.path)).or_default();
+        for (name, number, _) in &enumeration.values {
+            values.insert(
+                name.clone(),
+                FieldShape {
+                    ty: String::new(),
+                    tag: number.to_string(),
+                },
+            );
+        }
+    }
+    definitions
+}
+
+/// Type of a field as written in the file, with `repeated` but without `optional`
+fn field_type(field: &FieldDef) -> String {
+    let ty = match &field.ty {
+        FieldType::Named(name) => name.clone(),
+        FieldType::Map(key, value) => format!("map<{key}, {value}>"),
+    };
+    if field.label == Label::Repeated {
+        format!("repeated {ty}")
+    } else {
+        ty
+    }
+}
+
+fn schema_changes(
+    recorded: &BTreeMap<String, BTreeMap<String, FieldShape>>,
+    current: &BTreeMap<String, BTreeMap<String, FieldShape>>,
+) -> Vec<String> {
+    let mut changes = Vec::new();
+    for (definition, fields) in current {
+        let Some(recorded_fields) = recorded.get(definition) else {
+            continue;
+        };
+        for (name, shape) in fields {
+            let Some(old) = recorded_fields.get(name) else {
+                continue;
+            };
+            if old.tag != shape.tag {
+                changes.push(format!("`{definition}.{name}` moved from tag {} to tag {}", old.tag, shape.tag));
+            }
+            if without_nesting(&old.ty) != without_nesting(&shape.ty) {
+                changes.push(format!("`{definition}.{name}` changed type from `{}` to `{}`", old.ty, shape.ty));
+            }
+        }
+    }
+    changes
+}
+
+/// Type with its enclosing message names dropped (`pkg.Order.Line` -> `pkg.Line`), since
+/// `nest_in` only qualifies references once both messages are known
+fn without_nesting(ty: &str) -> String {
+    let mut output = String::with_capacity(ty.len());
+    let mut rest = ty;
+    while let Some(start) = rest.find(|ch: char| ch.is_ascii_alphanumeric() || ch == '_') {
+        let (separator, tail) = rest.split_at(start);
+        output.push_str(separator);
+        let end = tail.find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_' || ch == '.')).unwrap_or(tail.len());
+        let (path, after) = tail.split_at(end);
+        let segments: Vec<&str> = path.split('.').collect();
+        let last = segments.len() - 1;
+        let kept: Vec<&str> = segments
+            .iter()
+            .enumerate()
+            .filter(|(pos, segment)| *pos == last || !segment.starts_with(|ch: char| ch.is_ascii_uppercase()))
+            .map(|(_, segment)| *segment)
+            .collect();
+        output.push_str(&kept.join("."));
+        rest = after;
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SNAPSHOT: &str = "syntax = \"proto3\";\npackage shop;\n\nmessage Order {\n  uint64 id = 1;\n  optional string note = 2;\n  repeated uint32 items = 3;\n}\n\nenum Status {\n  ACTIVE = 0;\n  CLOSED = 1;\n}\n\nservice Shop {\n  rpc Get(Order) returns (Order) {}\n}\n";
+
+    #[test]
+    fn reports_tag_and_type_changes_of_kept_names() {
+        let current = "message Order {\n  string note = 1;\n  uint64 id = 2;\n  uint32 items = 3; // at most 4 elements\n  bool paid = 4;\n}\n\nenum Status {\n  ACTIVE = 0;\n  CLOSED = 2;\n}\n";
+        assert_eq!(
+            schema_changes(&parse_definitions(SNAPSHOT), &parse_definitions(current)),
+            [
+                "`Order.id` moved from tag 1 to tag 2",
+                "`Order.items` changed type from `repeated uint32` to `uint32`",
+                "`Order.note` moved from tag 2 to tag 1",
+                "`Status.CLOSED` moved from tag 1 to tag 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn nesting_qualifiers_are_not_type_changes() {
+        let recorded = "message Order {\n  repeated Order.Line lines = 1;\n  map<string, Order.Line> by_sku = 2;\n  other.Order.Line external = 3;\n}\n";
+        let current = "message Order {\n  repeated Line lines = 1;\n  map<string, Line> by_sku = 2;\n  shop.Line external = 3;\n}\n";
+        assert_eq!(
+            schema_changes(&parse_definitions(recorded), &parse_definitions(current)),
+            ["`Order.external` changed type from `other.Order.Line` to `shop.Line`"]
+        );
+    }
+
+    #[test]
+    fn nested_messages_and_oneofs_are_parsed() {
+        let recorded = "message Order {\n  message Line { uint64 sku = 1; uint32 qty = 2; }\n  oneof payment {\n    string card = 3;\n    string iban = 4;\n  }\n  /* total; in cents */ uint64 total = 5;\n  enum Kind { KIND_RETAIL = 0; }\n  string memo = 6 [json_name = \"a;b{\"];\n}\nmessage Empty {}\n";
+        let definitions = parse_definitions(recorded);
+        assert_eq!(definitions.keys().collect::<Vec<_>>(), ["Empty", "Kind", "Line", "Order"]);
+        assert_eq!(definitions["Order"].keys().collect::<Vec<_>>(), ["card", "iban", "memo", "total"]);
+        assert_eq!(
+            definitions["Line"]["qty"],
+            FieldShape {
+                ty: "uint32".into(),
+                tag: "2".into()
+            }
+        );
+
+        let current = "message Line {\n  uint64 sku = 1;\n  uint64 qty = 2;\n}\n";
+        assert_eq!(
+            schema_changes(&definitions, &parse_definitions(current)),
+            ["`Line.qty` changed type from `uint32` to `uint64`"]
+        );
+        let current = "message Order {\n  oneof payment {\n    string card = 4;\n    string iban = 3;\n  }\n  uint64 total = 5;\n  string memo = 6;\n}\n";
+        assert_eq!(
+            schema_changes(&definitions, &parse_definitions(current)),
+            ["`Order.card` moved from tag 3 to tag 4", "`Order.iban` moved from tag 4 to tag 3"]
+        );
+    }
+
+    #[test]
+    fn unchanged_and_new_definitions_are_quiet() {
+        let current = "message Order {\n  uint64 id = 1;\n  string note = 2;\n  repeated uint32 items = 3;\n}\n\nmessage Refund {\n  uint64 id = 9;\n}\n";
+        assert!(schema_changes(&parse_definitions(SNAPSHOT), &parse_definitions(current)).is_empty());
+    }
+
+    #[test]
+    fn files_the_parser_rejects_are_not_compared() {
+        let recorded = "syntax = \"proto2\";\nmessage Order {\n  required uint64 id = 1;\n}\n";
+        assert!(parse_definitions(recorded).is_empty());
+        assert!(schema_changes(&parse_definitions(recorded), &parse_definitions(SNAPSHOT)).is_empty());
+    }
+}
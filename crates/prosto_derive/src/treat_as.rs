@@ -0,0 +1,178 @@
+//! Crate-wide `treat_as` declarations from `register_treat_as!`.
+//!
+//! A registration covers every field whose type is exactly the registered type and that carries no
+//! conversion of its own (`treat_as`, `into`, `from`, `into_fn`, `from_fn`, `try_from_fn`,
+//! `rename`). Types are matched by their last path segment, so `uuid::Uuid` and `Uuid` are the
+//! same type. Like the other registries of this crate, it only sees registrations expanded before
+//! the field, so they belong at the top of the crate root.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+
+use proc_macro::TokenStream;
+use quote::ToTokens;
+use quote::quote;
+use syn::Path;
+use syn::Token;
+use syn::Type;
+use syn::parse::Parse;
+use syn::parse::ParseStream;
+
+use crate::utils::FieldConfig;
+
+mod kw {
+    syn::custom_keyword!(via);
+}
+
+/// Registry: last path segment of the registered type -> how its fields are encoded
+static REGISTRY: LazyLock<Mutex<HashMap<String, TreatAs>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Clone, PartialEq, Eq)]
+struct TreatAs {
+    proxy: String,
+    via: Option<Via>,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+struct Via {
+    into_fn: String,
+    from_fn: String,
+    fallible: bool,
+}
+
+/// `Type as Proxy` or `Type as Proxy via into_fn / [try] from_fn`
+struct Registration {
+    ty: Type,
+    proxy: Type,
+    via: Option<(Path, Path, bool)>,
+}
+
+impl Parse for Registration {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let ty: Type = input.parse()?;
+        input.parse::<Token![as]>()?;
+        let proxy: Type = input.parse()?;
+        let via = if input.peek(kw::via) {
+            input.parse::<kw::via>()?;
+            let into_fn: Path = input.parse()?;
+            input.parse::<Token![/]>()?;
+            let fallible = input.parse::<Option<Token![try]>>()?.is_some();
+            let from_fn: Path = input.parse()?;
+            Some((into_fn, from_fn, fallible))
+        } else {
+            None
+        };
+        Ok(Self { ty, proxy, via })
+    }
+}
+
+struct Registrations(Vec<Registration>);
+
+impl Parse for Registrations {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let entries = input.parse_terminated(Registration::parse, Token![;])?;
+        Ok(Self(entries.into_iter().collect()))
+    }
+}
+
+fn registry_key(ty: &Type) -> Option<String> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    if path.qself.is_some() {
+        return None;
+    }
+    let segment = path.path.segments.last()?;
+    Some(segment.to_token_stream().to_string().replace(' ', ""))
+}
+
+fn tokens_string(tokens: &impl ToTokens) -> String {
+    tokens.to_token_stream().to_string()
+}
+
+pub fn register_treat_as_impl(input: TokenStream) -> TokenStream {
+    let Registrations(entries) = syn::parse_macro_input!(input as Registrations);
+
+    let mut output = proc_macro2::TokenStream::new();
+    for Registration { ty, proxy, via } in entries {
+        let Some(key) = registry_key(&ty) else {
+            return syn::Error::new_spanned(&ty, "register_treat_as! expects a named type such as `uuid::Uuid`").to_compile_error().into();
+        };
+        let entry = TreatAs {
+            proxy: tokens_string(&proxy),
+            via: via.as_ref().map(|(into_fn, from_fn, fallible)| Via {
+                into_fn: tokens_string(into_fn),
+                from_fn: tokens_string(from_fn),
+                fallible: *fallible,
+            }),
+        };
+
+        let mut registry = REGISTRY.lock().unwrap();
+        if let Some(existing) = registry.get(&key)
+            && *existing != entry
+        {
+            return syn::Error::new_spanned(&ty, format!("`{key}` is already registered with a different treat_as"))
+                .to_compile_error()
+                .into();
+        }
+        registry.insert(key, entry);
+        drop(registry);
+
+        // Type-check the declaration where it is written instead of at every field
+        output.extend(if let Some((into_fn, from_fn, fallible)) = via {
+            let decoded = if fallible {
+                quote! { #from_fn(proxy).map_err(::core::convert::Into::into) }
+            } else {
+                quote! { ::core::result::Result::Ok(#from_fn(proxy)) }
+            };
+            quote! {
+                const _: () = {
+                    #[allow(dead_code)]
+                    fn check(value: &#ty, proxy: #proxy) -> ::core::result::Result<#ty, ::proto_rs::DecodeError> {
+                        let _: #proxy = #into_fn(value);
+                        #decoded
+                    }
+                };
+            }
+        } else {
+            quote! {
+                const _: ::core::marker::PhantomData<(#ty, #proxy)> = ::core::marker::PhantomData;
+            }
+        });
+    }
+    output.into()
+}
+
+/// Fills in the registered conversion of the field type, unless the field has its own
+pub fn apply_registered(ty: &Type, config: &mut FieldConfig) {
+    if config.skip
+        || config.treat_as.is_some()
+        || config.into_type.is_some()
+        || config.from_type.is_some()
+        || config.into_fn.is_some()
+        || config.from_fn.is_some()
+        || config.try_from_fn.is_some()
+        || config.rename.is_some()
+    {
+        return;
+    }
+    let Some(key) = registry_key(ty) else {
+        return;
+    };
+    let Some(entry) = REGISTRY.lock().unwrap().get(&key).cloned() else {
+        return;
+    };
+
+    if let Some(via) = entry.via {
+        config.into_type = Some(entry.proxy);
+        config.into_fn = Some(via.into_fn);
+        if via.fallible {
+            config.try_from_fn = Some(via.from_fn);
+        } else {
+            config.from_fn = Some(via.from_fn);
+        }
+    } else {
+        config.treat_as = Some(entry.proxy);
+    }
+}
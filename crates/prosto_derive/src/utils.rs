@@ -1,7 +1,10 @@
 //! Centralized utilities for proto macro code generation
 
+use std::fmt::Write;
+
 use proc_macro2::Span;
 use proc_macro2::TokenStream;
+use quote::quote;
 use syn::DataEnum;
 use syn::Expr;
 use syn::Field;
@@ -65,7 +68,7 @@ pub fn cache_padded_inner_type(ty: &Type) -> Option<Type> {
 pub fn arc_swap_inner_type(ty: &Type) -> Option<Type> {
     if let Type::Path(type_path) = ty
         && let Some(segment) = type_path.path.segments.last()
-        && segment.ident == "ArcSwap"
+        && (segment.ident == "ArcSwap" || segment.ident == "ArcSwapAny")
         && let PathArguments::AngleBracketed(args) = &segment.arguments
         && let Some(GenericArgument::Type(inner)) = args.args.first()
     {
@@ -78,7 +81,7 @@ pub fn arc_swap_inner_type(ty: &Type) -> Option<Type> {
 pub fn box_like_inner_type(ty: &Type) -> Option<Type> {
     if let Type::Path(type_path) = ty
         && let Some(segment) = type_path.path.segments.last()
-        && matches!(segment.ident.to_string().as_str(), "Box" | "Arc" | "Mutex")
+        && matches!(segment.ident.to_string().as_str(), "Box" | "Arc" | "Weak" | "Mutex")
         && let PathArguments::AngleBracketed(args) = &segment.arguments
         && let Some(GenericArgument::Type(inner)) = args.args.first()
     {
@@ -88,6 +91,42 @@ pub fn box_like_inner_type(ty: &Type) -> Option<Type> {
     None
 }
 
+/// Decode behavior for repeated map keys and set elements, see `#[proto(on_duplicate = ...)]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    #[default]
+    LastWins,
+    Error,
+    Collect,
+}
+
+/// UTF-8 handling for `string` fields, see `#[proto(utf8 = ...)]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Utf8Policy {
+    #[default]
+    Strict,
+    Lossy,
+}
+
+/// Out-of-range handling for integers narrower than their wire type, see `#[proto(on_overflow = ...)]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    #[default]
+    Error,
+    Saturate,
+    Wrap,
+}
+
+/// Where a field goes in the generated decode dispatch and encode order, see `#[proto(hot)]` and
+/// `#[proto(cold)]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FieldHeat {
+    Hot,
+    #[default]
+    Normal,
+    Cold,
+}
+
 #[derive(Debug, Clone, Default)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct FieldConfig {
@@ -102,17 +141,79 @@ pub struct FieldConfig {
     pub is_rust_enum: bool,            // treat T as Rust enum -> i32 on wire
     pub is_message: bool,              // force message semantics
     pub is_proto_enum: bool,           // prost-like enum (i32 backing)
+    pub is_result: bool,               // Result<T, E> carried as ProtoResult<T, E>
+    pub is_view: bool,                 // sun_ir reads a ProtoView of the field type
     pub import_path: Option<String>,
     pub getter: Option<String>,
     pub custom_tag: Option<usize>,
     pub rename: Option<ProtoRename>,
+    // #[proto(name = "...")]: schema name of a tuple struct field, `field_N` otherwise
+    pub proto_name: Option<String>,
     pub validator: Option<String>, // field-level validation function
+    pub on_duplicate: DuplicatePolicy,
+    pub utf8: Utf8Policy,
+    pub on_overflow: OverflowPolicy,
+    pub heat: FieldHeat,
+    pub cfg: Option<String>,   // #[proto(cfg = "...")]: the field only exists when the predicate holds
+    pub rust_cfg: Vec<String>, // predicates of `#[cfg(...)]` attributes already on the field
+    // #[proto(label = "...")] and #[proto(annotate(key = "value"))], surfaced on `schemas::Field`
+    pub labels: Vec<String>,
+    pub annotations: Vec<(String, String)>,
+    // #[proto(option("pkg.name") = value)], written as `[(pkg.name) = value]` by `schemas::write_all`
+    pub options: Vec<ProtoOption>,
+    // #[proto(extensions = "100..200")]: inclusive field number ranges kept in an `ExtensionSet` field
+    pub extensions: Vec<(u32, u32)>,
+    // #[proto(header)]: also a field of the generated `{Name}Header` projection
+    pub header: bool,
+    // #[proto(offset_index = N)]: element offsets of a repeated message field written under tag N
+    pub offset_index: Option<u32>,
+}
+
+impl FieldConfig {
+    fn add_label(&mut self, label: &str) {
+        if !self.labels.iter().any(|existing| existing == label) {
+            self.labels.push(label.to_string());
+        }
+    }
+
+    /// Message for decode policies that select different merges and so cannot be combined.
+    pub fn merge_policy_conflict(&self) -> Option<&'static str> {
+        let lossy = self.utf8 == Utf8Policy::Lossy;
+        let overflow = self.on_overflow != OverflowPolicy::Error;
+        let duplicate = self.on_duplicate != DuplicatePolicy::LastWins;
+        match (lossy, overflow, duplicate) {
+            (true, true, _) => Some("#[proto(utf8 = \"lossy\")] cannot be combined with #[proto(on_overflow = ...)]"),
+            (true, _, true) => Some("#[proto(utf8 = \"lossy\")] cannot be combined with #[proto(on_duplicate = ...)]"),
+            (_, true, true) => Some("#[proto(on_overflow = ...)] cannot be combined with #[proto(on_duplicate = ...)]"),
+            _ => None,
+        }
+    }
+
+    /// Predicate under which the field exists, or `None` for unconditional fields.
+    pub fn cfg_predicate(&self) -> Option<TokenStream> {
+        let predicates: Vec<TokenStream> = self
+            .rust_cfg
+            .iter()
+            .chain(&self.cfg)
+            .map(|predicate| predicate.parse().expect("cfg predicates are validated when parsed"))
+            .collect();
+        match predicates.as_slice() {
+            [] => None,
+            [single] => Some(single.clone()),
+            _ => Some(quote! { all(#(#predicates),*) }),
+        }
+    }
 }
 
 pub fn parse_field_config(field: &Field) -> FieldConfig {
     let mut cfg = FieldConfig::default();
 
     for attr in &field.attrs {
+        if attr.path().is_ident("cfg") {
+            let predicate = attr.meta.require_list().expect("#[cfg] expects a predicate").tokens.to_string();
+            cfg.rust_cfg.push(predicate);
+            continue;
+        }
         if !attr.path().is_ident("proto") {
             continue;
         }
@@ -133,6 +234,8 @@ pub fn parse_field_config(field: &Field) -> FieldConfig {
                 Some("rust_enum") => cfg.is_rust_enum = true,
                 Some("enum") => cfg.is_proto_enum = true,
                 Some("message") => cfg.is_message = true,
+                Some("view") => cfg.is_view = true,
+                Some("header") => cfg.header = true,
                 Some("getter") => cfg.getter = parse_string_value(&meta),
                 Some("into") => cfg.into_type = parse_string_value(&meta),
                 Some("from") => cfg.from_type = parse_string_value(&meta),
@@ -140,14 +243,103 @@ pub fn parse_field_config(field: &Field) -> FieldConfig {
                 Some("from_fn") => cfg.from_fn = parse_string_value(&meta),
                 Some("try_from_fn") => cfg.try_from_fn = parse_string_value(&meta),
                 Some("treat_as") => cfg.treat_as = parse_string_value(&meta),
+                Some("result") => {
+                    let Some((ok, err)) = result_type_args(&field.ty) else {
+                        return Err(meta.error("result expects a `Result<T, E>` field"));
+                    };
+                    cfg.into_type = Some(format!("::proto_rs::ProtoResult<{}, {}>", quote!(#ok), quote!(#err)));
+                    cfg.is_result = true;
+                }
                 Some("import_path") => cfg.import_path = parse_string_value(&meta),
                 Some("tag") => cfg.custom_tag = parse_usize_value(&meta),
+                Some("offset_index") => {
+                    let Some(tag) = parse_usize_value(&meta).and_then(|tag| u32::try_from(tag).ok()).filter(|tag| *tag != 0) else {
+                        return Err(meta.error("offset_index expects the field number of the index, such as 15"));
+                    };
+                    cfg.offset_index = Some(tag);
+                }
                 Some("rename") => {
                     let tokens: TokenStream =
                         meta.value().expect("rename expects a value").parse().expect("failed to parse rename attribute");
                     cfg.rename = Some(parse_proto_rename(field, tokens));
                 }
+                Some("name") => {
+                    let Some(name) = parse_string_value(&meta).filter(|name| is_proto_field_name(name)) else {
+                        return Err(meta.error("name expects a proto field name such as \"sensor_id\""));
+                    };
+                    cfg.proto_name = Some(name);
+                }
                 Some("validator") => cfg.validator = parse_string_or_path_value(&meta),
+                // Encoding is unaffected; `schemas::scrub` finds the field through its label.
+                Some("sensitive") => cfg.add_label("sensitive"),
+                Some("label") => {
+                    let Some(label) = parse_string_value(&meta).filter(|label| !label.is_empty()) else {
+                        return Err(meta.error("label expects a non-empty string"));
+                    };
+                    cfg.add_label(&label);
+                }
+                Some("annotate") => {
+                    meta.parse_nested_meta(|inner| {
+                        let Some(key) = inner.path.get_ident().map(ToString::to_string) else {
+                            return Err(inner.error("annotation keys must be identifiers"));
+                        };
+                        let Some(value) = parse_string_value(&inner) else {
+                            return Err(inner.error("annotations expect `key = \"value\"`"));
+                        };
+                        if cfg.annotations.iter().any(|(existing, _)| *existing == key) {
+                            return Err(inner.error("duplicate annotation key"));
+                        }
+                        cfg.annotations.push((key, value));
+                        Ok(())
+                    })?;
+                }
+                Some("option") => push_proto_option(&mut cfg.options, &meta)?,
+                Some("extensions") => {
+                    let ranges = parse_string_value(&meta).unwrap_or_default();
+                    let Some(ranges) = parse_extension_ranges(&ranges) else {
+                        return Err(meta.error("extensions expects field number ranges such as \"100..200\" or \"100..=199, 1000..=1999\""));
+                    };
+                    // The field is not encoded under a tag of its own
+                    cfg.skip = true;
+                    cfg.extensions = ranges;
+                }
+                Some("on_duplicate") => {
+                    cfg.on_duplicate = match parse_string_value(&meta).as_deref() {
+                        Some("last_wins" | "last-wins") => DuplicatePolicy::LastWins,
+                        Some("error") => DuplicatePolicy::Error,
+                        Some("collect") => DuplicatePolicy::Collect,
+                        _ => return Err(meta.error("on_duplicate expects \"last_wins\", \"error\" or \"collect\"")),
+                    };
+                }
+                Some("utf8") => {
+                    cfg.utf8 = match parse_string_value(&meta).as_deref() {
+                        Some("strict") => Utf8Policy::Strict,
+                        Some("lossy") => Utf8Policy::Lossy,
+                        _ => return Err(meta.error("utf8 expects \"strict\" or \"lossy\"")),
+                    };
+                }
+                Some("on_overflow") => {
+                    cfg.on_overflow = match parse_string_value(&meta).as_deref() {
+                        Some("error") => OverflowPolicy::Error,
+                        Some("saturate") => OverflowPolicy::Saturate,
+                        Some("wrap") => OverflowPolicy::Wrap,
+                        _ => return Err(meta.error("on_overflow expects \"error\", \"saturate\" or \"wrap\"")),
+                    };
+                }
+                Some(heat @ ("hot" | "cold")) => {
+                    let heat = if heat == "hot" { FieldHeat::Hot } else { FieldHeat::Cold };
+                    if cfg.heat != FieldHeat::Normal && cfg.heat != heat {
+                        return Err(meta.error("a field cannot be both hot and cold"));
+                    }
+                    cfg.heat = heat;
+                }
+                Some("cfg") => {
+                    let predicate = parse_string_value(&meta).unwrap_or_default();
+                    if syn::parse_str::<syn::Meta>(&predicate).is_err() {
+                        return Err(meta.error("cfg expects a predicate such as \"feature = \\\"x\\\"\""));
+                    }
+                    cfg.cfg = Some(predicate);
+                }
                 _ => return Err(meta.error("unknown #[proto(...)] attribute")),
             }
             Ok(())
@@ -155,9 +347,59 @@ pub fn parse_field_config(field: &Field) -> FieldConfig {
         .expect("failed to parse #[proto(...)] attributes");
     }
 
+    crate::treat_as::apply_registered(&field.ty, &mut cfg);
     cfg
 }
 
+/// Inclusive ranges of `"100..200, 1000..=1999"`, or `None` when a range is empty, overlaps another
+/// or holds numbers proto does not allow.
+fn parse_extension_ranges(ranges: &str) -> Option<Vec<(u32, u32)>> {
+    const MAX_FIELD_NUMBER: u32 = 536_870_911;
+    let mut parsed: Vec<(u32, u32)> = Vec::new();
+    for range in ranges.split(',') {
+        let (start, end) = range.trim().split_once("..")?;
+        let start: u32 = start.trim().parse().ok()?;
+        let end = match end.strip_prefix('=') {
+            Some(end) => end.trim().parse().ok()?,
+            None => end.trim().parse::<u32>().ok()?.checked_sub(1)?,
+        };
+        let reserved = end >= 19_000 && start <= 19_999;
+        if start == 0 || start > end || end > MAX_FIELD_NUMBER || reserved {
+            return None;
+        }
+        if parsed.iter().any(|&(other_start, other_end)| start <= other_end && other_start <= end) {
+            return None;
+        }
+        parsed.push((start, end));
+    }
+    parsed.sort_unstable();
+    Some(parsed)
+}
+
+fn is_proto_field_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_') && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+/// `T` and `E` of a `Result<T, E>` field type
+fn result_type_args(ty: &Type) -> Option<(&Type, &Type)> {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    let segment = path.segments.last().filter(|segment| segment.ident == "Result")?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    let mut types = args.args.iter().filter_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    });
+    match (types.next(), types.next(), types.next()) {
+        (Some(ok), Some(err), None) => Some((ok, err)),
+        _ => None,
+    }
+}
+
 fn parse_proto_rename(field: &Field, tokens: TokenStream) -> ProtoRename {
     use proc_macro2::TokenStream as TokenStream2;
 
@@ -327,6 +569,102 @@ fn parse_string_or_path_value(meta: &syn::meta::ParseNestedMeta) -> Option<Strin
     None
 }
 
+/// A custom option from `#[proto(option("acme.priority") = 3)]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtoOption {
+    pub name: String,
+    /// The value as a proto literal, e.g. `3`, `true` or `"gold"`.
+    pub value: String,
+    /// Scalar type of the extension declared for the option.
+    pub value_type: &'static str,
+}
+
+/// Parse `option("pkg.name") = <literal>` and add it to `options`.
+pub fn push_proto_option(options: &mut Vec<ProtoOption>, meta: &syn::meta::ParseNestedMeta) -> syn::Result<()> {
+    let content;
+    syn::parenthesized!(content in meta.input);
+    let name: syn::LitStr = content.parse()?;
+    let segments: Vec<String> = name.value().split('.').map(str::to_owned).collect();
+    if segments.len() < 2 || segments.iter().any(|segment| syn::parse_str::<syn::Ident>(segment).is_err()) {
+        return Err(syn::Error::new(
+            name.span(),
+            "option names must be qualified by a package, e.g. option(\"acme.priority\")",
+        ));
+    }
+    if options.iter().any(|option| option.name == name.value()) {
+        return Err(syn::Error::new(name.span(), "duplicate option"));
+    }
+
+    let expr: Expr = meta.value()?.parse()?;
+    let (negative, lit) = match expr {
+        Expr::Lit(expr_lit) => (false, expr_lit.lit),
+        Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) if matches!(&*expr, Expr::Lit(_)) => {
+            let Expr::Lit(expr_lit) = *expr else { unreachable!() };
+            (true, expr_lit.lit)
+        }
+        other => {
+            return Err(syn::Error::new(
+                other.span(),
+                "option values must be integer, float, bool or string literals",
+            ));
+        }
+    };
+    let sign = if negative { "-" } else { "" };
+    let (value, value_type) = match lit {
+        Lit::Int(int) => (format!("{sign}{}", int.base10_parse::<i64>()?), "int64"),
+        Lit::Float(float) => (format!("{sign}{:?}", float.base10_parse::<f64>()?), "double"),
+        Lit::Bool(flag) if !negative => (flag.value.to_string(), "bool"),
+        Lit::Str(text) if !negative => (proto_string_literal(&text.value()), "string"),
+        other => {
+            return Err(syn::Error::new(
+                other.span(),
+                "option values must be integer, float, bool or string literals",
+            ));
+        }
+    };
+    options.push(ProtoOption {
+        name: name.value(),
+        value,
+        value_type,
+    });
+    Ok(())
+}
+
+/// `&[ProtoOption]` literal for a `schemas` const.
+pub fn proto_options_tokens(options: &[ProtoOption]) -> TokenStream {
+    let options = options.iter().map(|ProtoOption { name, value, value_type }| {
+        quote! { ::proto_rs::schemas::ProtoOption { name: #name, value: #value, value_type: #value_type } }
+    });
+    quote! { &[#(#options),*] }
+}
+
+fn proto_string_literal(value: &str) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            ch if ch.is_control() => {
+                let mut buf = [0; 4];
+                for byte in ch.encode_utf8(&mut buf).bytes() {
+                    write!(literal, "\\x{byte:02x}").unwrap();
+                }
+            }
+            ch => literal.push(ch),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
 fn parse_usize_value(meta: &syn::meta::ParseNestedMeta) -> Option<usize> {
     meta.value().ok().and_then(|v| v.parse::<Lit>().ok()).and_then(|lit| match lit {
         syn::Lit::Int(i) => i.base10_parse::<usize>().ok(),
@@ -336,14 +674,45 @@ fn parse_usize_value(meta: &syn::meta::ParseNestedMeta) -> Option<usize> {
 }
 
 pub fn resolved_field_type(field: &Field, config: &FieldConfig) -> Type {
-    if let Some(treat_as) = &config.treat_as {
+    let ty = if let Some(treat_as) = &config.treat_as {
         syn::parse_str::<Type>(treat_as).unwrap_or_else(|_| {
             let name = field.ident.as_ref().map_or_else(|| "<tuple field>".to_string(), ToString::to_string);
             panic!("invalid type in #[proto(treat_as = ...)] on field {name}");
         })
     } else {
         field.ty.clone()
+    };
+    if config.on_duplicate == DuplicatePolicy::Collect {
+        collected_map_wire_type(&ty).unwrap_or_else(|| {
+            let name = field.ident.as_ref().map_or_else(|| "<tuple field>".to_string(), ToString::to_string);
+            panic!("#[proto(on_duplicate = \"collect\")] on field {name} requires a HashMap<K, Vec<V>> or BTreeMap<K, Vec<V>>");
+        })
+    } else {
+        ty
+    }
+}
+
+/// `Map<K, Vec<V>>` -> `Map<K, V>`: the wire shape of a collecting map.
+fn collected_map_wire_type(ty: &Type) -> Option<Type> {
+    let mut ty = ty.clone();
+    let Type::Path(type_path) = &mut ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last_mut()?;
+    if !matches!(segment.ident.to_string().as_str(), "HashMap" | "BTreeMap") {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &mut segment.arguments else {
+        return None;
+    };
+    let GenericArgument::Type(value_ty) = args.args.iter_mut().nth(1)? else {
+        return None;
+    };
+    if is_bytes_vec(value_ty) {
+        return None;
     }
+    *value_ty = vec_inner_type(value_ty)?;
+    Some(ty)
 }
 
 fn last_path_segment(ty: &Type) -> Option<&syn::PathSegment> {
@@ -447,6 +816,18 @@ pub fn vec_deque_inner_type(ty: &Type) -> Option<Type> {
     None
 }
 
+pub fn list_inner_type(ty: &Type) -> Option<Type> {
+    if let Type::Path(path) = ty
+        && let Some(seg) = path.path.segments.last()
+        && (seg.ident == "BinaryHeap" || seg.ident == "LinkedList")
+        && let PathArguments::AngleBracketed(args) = &seg.arguments
+        && let Some(GenericArgument::Type(inner)) = args.args.first()
+    {
+        return Some(inner.clone());
+    }
+    None
+}
+
 pub fn extract_field_wrapper_info(ty: &Type) -> (bool, bool, Type) {
     if is_option_type(ty) || is_arc_swap_option_type(ty) {
         if let Type::Path(type_path) = ty
@@ -474,6 +855,11 @@ pub fn extract_field_wrapper_info(ty: &Type) -> (bool, bool, Type) {
         return (false, true, inner_ty);
     }
 
+    if let Some(inner) = list_inner_type(ty) {
+        let (_, _, inner_ty) = extract_field_wrapper_info(&inner);
+        return (false, true, inner_ty);
+    }
+
     if let Some((inner, _)) = set_inner_type(ty) {
         let (_, _, inner_ty) = extract_field_wrapper_info(&inner);
         return (false, true, inner_ty);
@@ -520,25 +906,126 @@ pub struct MethodInfo {
     pub inner_response_type: Option<Type>,
     pub stream_item_type: Option<Type>,
     pub user_method_signature: TokenStream,
+    /// `#[proto(rpc_name = "...")]`: rpc name in the `.proto` and default route.
+    pub rpc_name: Option<String>,
+    /// `#[proto(route = "...")]`: full gRPC path used instead of `/package.Service/RpcName`.
+    pub route: Option<String>,
+    /// `#[proto(cache(ttl = "..."))]`: serve repeated requests from the server's response cache.
+    pub cache: Option<MethodCache>,
+    /// `#[proto(idempotent)]`: concurrent identical requests share one in-flight call.
+    pub idempotent: bool,
+    /// `#[proto(option("pkg.name") = value)]`: custom method options in the `.proto`.
+    pub options: Vec<ProtoOption>,
+}
+
+#[derive(Clone)]
+pub struct MethodCache {
+    pub ttl_millis: u64,
+    /// `vary = "..."`: metadata keys whose values are part of the cache key.
+    pub vary: Vec<String>,
 }
 
-fn collect_discriminants_impl(variants: &[&syn::Variant]) -> Result<Vec<i32>, syn::Error> {
+impl MethodInfo {
+    /// Name of the rpc as it appears in the `.proto` service definition: the last segment of the
+    /// route when it is overridden.
+    pub fn proto_name(&self) -> String {
+        if let Some((_, rpc)) = self.route.as_deref().and_then(|route| route.rsplit_once('/')) {
+            return rpc.to_string();
+        }
+        self.rpc_name.clone().unwrap_or_else(|| to_pascal_case(&self.name.to_string()))
+    }
+}
+
+/// Integer type backing a simple enum on the wire, taken from its `#[repr(..)]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnumRepr {
+    I32,
+    I64,
+    U64,
+}
+
+impl EnumRepr {
+    pub fn ident(self) -> syn::Ident {
+        syn::Ident::new(self.name(), Span::call_site())
+    }
+
+    /// Typed literal of a discriminant already checked to fit this repr.
+    pub fn literal(self, value: i128) -> TokenStream {
+        let literal = match self {
+            EnumRepr::I32 => proc_macro2::Literal::i32_suffixed(i32::try_from(value).expect("discriminant checked against repr")),
+            EnumRepr::I64 => proc_macro2::Literal::i64_suffixed(i64::try_from(value).expect("discriminant checked against repr")),
+            EnumRepr::U64 => proc_macro2::Literal::u64_suffixed(u64::try_from(value).expect("discriminant checked against repr")),
+        };
+        quote! { #literal }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            EnumRepr::I32 => "i32",
+            EnumRepr::I64 => "i64",
+            EnumRepr::U64 => "u64",
+        }
+    }
+
+    fn range(self) -> (i128, i128) {
+        match self {
+            EnumRepr::I32 => (i128::from(i32::MIN), i128::from(i32::MAX)),
+            EnumRepr::I64 => (i128::from(i64::MIN), i128::from(i64::MAX)),
+            EnumRepr::U64 => (0, i128::from(u64::MAX)),
+        }
+    }
+}
+
+/// Reads the integer `#[repr(..)]` of a simple enum; enums without one are `i32`.
+pub fn enum_repr(attrs: &[syn::Attribute]) -> syn::Result<EnumRepr> {
+    let mut repr = EnumRepr::I32;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            let Some(ident) = meta.path.get_ident() else {
+                return Ok(());
+            };
+            repr = match ident.to_string().as_str() {
+                "i32" => EnumRepr::I32,
+                "i64" => EnumRepr::I64,
+                "u64" => EnumRepr::U64,
+                "i8" | "i16" | "i128" | "isize" | "u8" | "u16" | "u32" | "u128" | "usize" => {
+                    return Err(meta.error("proto enums support #[repr(i32)], #[repr(i64)] and #[repr(u64)]"));
+                }
+                _ => repr,
+            };
+            if meta.input.peek(syn::token::Paren) {
+                meta.input.parse::<proc_macro2::Group>()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(repr)
+}
+
+fn collect_discriminants_impl(variants: &[&syn::Variant], repr: EnumRepr) -> Result<Vec<i128>, syn::Error> {
+    let (min, max) = repr.range();
     let mut values = Vec::with_capacity(variants.len());
-    let mut next_value: i32 = 0;
+    let mut next_value: i128 = 0;
 
     for variant in variants {
         let value = if let Some((_, expr)) = &variant.discriminant {
             let parsed = eval_discriminant(expr)?;
-            next_value =
-                parsed.checked_add(1).ok_or_else(|| syn::Error::new_spanned(&variant.ident, "enum discriminant overflowed i32 range"))?;
+            if parsed < min || parsed > max {
+                return Err(syn::Error::new(
+                    expr.span(),
+                    format!("enum discriminant must fit in {}", repr.name()),
+                ));
+            }
             parsed
+        } else if next_value > max {
+            return Err(syn::Error::new_spanned(
+                &variant.ident,
+                format!("enum discriminant overflowed {} range", repr.name()),
+            ));
         } else {
-            let value = next_value;
-            next_value = next_value
-                .checked_add(1)
-                .ok_or_else(|| syn::Error::new_spanned(&variant.ident, "enum discriminant overflowed i32 range"))?;
-            value
+            next_value
         };
+        next_value = value + 1;
 
         values.push(value);
     }
@@ -547,7 +1034,13 @@ fn collect_discriminants_impl(variants: &[&syn::Variant]) -> Result<Vec<i32>, sy
 }
 
 pub fn collect_discriminants_for_variants(variants: &[&syn::Variant]) -> Result<Vec<i32>, syn::Error> {
-    collect_discriminants_impl(variants)
+    collect_discriminants_impl(variants, EnumRepr::I32)
+        .map(|values| values.into_iter().map(|value| i32::try_from(value).expect("discriminant checked against repr")).collect())
+}
+
+/// Discriminants of `variants` checked against `repr`, widened to `i128`.
+pub fn collect_repr_discriminants(variants: &[&syn::Variant], repr: EnumRepr) -> Result<Vec<i128>, syn::Error> {
+    collect_discriminants_impl(variants, repr)
 }
 
 pub fn find_marked_default_variant(data: &DataEnum) -> syn::Result<Option<usize>> {
@@ -565,11 +1058,11 @@ pub fn find_marked_default_variant(data: &DataEnum) -> syn::Result<Option<usize>
     Ok(default_index)
 }
 
-fn eval_discriminant(expr: &Expr) -> Result<i32, syn::Error> {
+fn eval_discriminant(expr: &Expr) -> Result<i128, syn::Error> {
     match expr {
         Expr::Lit(expr_lit) => match &expr_lit.lit {
             Lit::Int(lit_int) => {
-                lit_int.base10_parse::<i32>().map_err(|_| syn::Error::new(lit_int.span(), "enum discriminant must fit in i32"))
+                lit_int.base10_parse::<i128>().map_err(|_| syn::Error::new(lit_int.span(), "enum discriminant must fit in u64"))
             }
             _ => Err(syn::Error::new(expr.span(), "unsupported enum discriminant literal")),
         },
@@ -578,7 +1071,7 @@ fn eval_discriminant(expr: &Expr) -> Result<i32, syn::Error> {
             match expr_unary.op {
                 UnOp::Neg(_) => {
                     let value = eval_discriminant(&expr_unary.expr)?;
-                    value.checked_neg().ok_or_else(|| syn::Error::new(expr.span(), "enum discriminant must fit in i32"))
+                    Ok(-value)
                 }
                 _ => Err(syn::Error::new(expr.span(), "unsupported enum discriminant expression")),
             }
@@ -605,4 +1098,16 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn merge_policies_cannot_be_combined() {
+        let field: syn::Field = parse_quote! { #[proto(utf8 = "lossy")] value: String };
+        assert_eq!(parse_field_config(&field).merge_policy_conflict(), None);
+
+        let field: syn::Field = parse_quote! { #[proto(utf8 = "lossy", on_overflow = "saturate")] value: String };
+        assert!(parse_field_config(&field).merge_policy_conflict().unwrap().contains("on_overflow"));
+
+        let field: syn::Field = parse_quote! { #[proto(on_overflow = "wrap", on_duplicate = "error")] value: u8 };
+        assert!(parse_field_config(&field).merge_policy_conflict().unwrap().contains("on_duplicate"));
+    }
 }
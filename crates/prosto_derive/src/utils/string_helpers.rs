@@ -70,9 +70,20 @@ pub fn derive_package_name(file_path: &str) -> String {
     file_path.trim_end_matches(".proto").replace(['/', '\\', '-', '.'], "_").to_lowercase()
 }
 
-/// Format import statement
+/// Format import statement, applying the `PROTO_IMPORT_REMAP` table
 pub fn format_import(import_path: &str) -> String {
-    format!("import \"{import_path}.proto\";\n")
+    let import = format!("{import_path}.proto");
+    let remapped = std::env::var("PROTO_IMPORT_REMAP").ok().and_then(|table| remap_import(&table, &import));
+    format!("import \"{}\";\n", remapped.unwrap_or(import))
+}
+
+/// Target of `import` in a comma separated `from=to` table
+fn remap_import(table: &str, import: &str) -> Option<String> {
+    table
+        .split(',')
+        .filter_map(|entry| entry.split_once('='))
+        .find(|(from, _)| from.trim() == import)
+        .map(|(_, to)| to.trim().to_string())
 }
 
 #[cfg(test)]
@@ -106,6 +117,17 @@ mod tests {
         assert_eq!(strip_proto_suffix("NoSuffix"), "NoSuffix");
     }
 
+    #[test]
+    fn test_remap_import() {
+        let table = "google/protobuf/timestamp.proto=third_party/google/protobuf/timestamp.proto, common.proto = vendor/common.proto";
+        assert_eq!(
+            remap_import(table, "google/protobuf/timestamp.proto").as_deref(),
+            Some("third_party/google/protobuf/timestamp.proto")
+        );
+        assert_eq!(remap_import(table, "common.proto").as_deref(), Some("vendor/common.proto"));
+        assert_eq!(remap_import(table, "google/protobuf/empty.proto"), None);
+    }
+
     #[test]
     fn test_derive_package_name() {
         assert_eq!(derive_package_name("path/to/file.proto"), "path_to_file");
@@ -154,12 +154,13 @@ fn parse_path_type(path: &TypePath, ty: &Type) -> ParsedFieldType {
         match id.to_string().as_str() {
             "Option" => return parse_option_type(path, ty),
             "ArcSwapOption" => return parse_arc_swap_option_type(path, ty),
+            "ArcSwapAny" => return parse_arc_swap_any_type(path, ty),
             "Vec" => return parse_vec_type(path, ty),
             "VecDeque" => return parse_vec_deque_type(path, ty),
             "HashMap" => return parse_map_type(path, ty, MapKind::HashMap),
             "BTreeMap" => return parse_map_type(path, ty, MapKind::BTreeMap),
-            "HashSet" | "BTreeSet" => return parse_set_type(path, ty),
-            "ArcSwap" | "Box" | "Arc" | "CachePadded" | "Mutex" => return parse_box_like_type(path, ty),
+            "HashSet" | "BTreeSet" | "BinaryHeap" | "LinkedList" => return parse_set_type(path, ty),
+            "ArcSwap" | "Box" | "Arc" | "Weak" | "CachePadded" | "Mutex" => return parse_box_like_type(path, ty),
             _ => {}
         }
     }
@@ -188,6 +189,16 @@ fn parse_arc_swap_option_type(path: &TypePath, ty: &Type) -> ParsedFieldType {
     inner
 }
 
+fn parse_arc_swap_any_type(path: &TypePath, ty: &Type) -> ParsedFieldType {
+    // The first generic is the stored pointer (`Arc<T>`, `Option<Arc<T>>`, ...), which decides the field
+    let Some(pointer_ty) = single_generic(path) else {
+        panic!("ArcSwapAny must specify the stored pointer type");
+    };
+    let mut inner = parse_field_type(pointer_ty);
+    inner.rust_type = ty.clone();
+    inner
+}
+
 fn parse_vec_type(path: &TypePath, ty: &Type) -> ParsedFieldType {
     let Some(inner_ty) = single_generic(path) else {
         panic!("Vec must have a single generic argument");
@@ -307,7 +318,8 @@ fn parse_primitive_or_custom(ty: &Type) -> ParsedFieldType {
                         false,
                     ),
                     "bool" => numeric_scalar(ty.clone(), parse_quote! { bool }, "bool"),
-                    "String" => ParsedFieldType::new(
+                    // `str` only appears behind a pointer, like `Arc<str>`.
+                    "String" | "str" => ParsedFieldType::new(
                         ty.clone(),
                         "string",
                         quote! { string },
@@ -317,6 +329,16 @@ fn parse_primitive_or_custom(ty: &Type) -> ParsedFieldType {
                         ty.clone(),
                         false,
                     ),
+                    "MaybeUtf8" => ParsedFieldType::new(
+                        ty.clone(),
+                        "string",
+                        quote! { string },
+                        false,
+                        false,
+                        parse_quote! { ::proto_rs::MaybeUtf8 },
+                        ty.clone(),
+                        false,
+                    ),
                     "Bytes" => ParsedFieldType::new(
                         ty.clone(),
                         "bytes",
@@ -387,7 +409,7 @@ fn parse_map_type(path: &TypePath, ty: &Type, kind: MapKind) -> ParsedFieldType
 
 fn parse_set_type(path: &TypePath, ty: &Type) -> ParsedFieldType {
     let syn::PathArguments::AngleBracketed(args) = &path.path.segments.last().unwrap().arguments else {
-        panic!("Set types must specify element generics");
+        panic!("Collection types must specify element generics");
     };
 
     let elem_ty = args
@@ -27,6 +27,18 @@ static PACKAGE_REGISTRY: LazyLock<Mutex<HashMap<String, String>>> = LazyLock::ne
 /// Track initialized files
 static INITIALIZED_FILES: LazyLock<Mutex<BTreeSet<String>>> = LazyLock::new(|| Mutex::new(BTreeSet::new()));
 
+/// Unit structs declared with `#[proto_message(empty = "wkt")]`
+static EMPTY_WKT_TYPES: LazyLock<Mutex<BTreeSet<String>>> = LazyLock::new(|| Mutex::new(BTreeSet::new()));
+
+/// Registry: filename -> (nested message -> enclosing message), from `#[proto_message(nest_in = "...")]`
+static NESTED_TYPES: LazyLock<Mutex<HashMap<String, BTreeMap<String, String>>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Files read by `import_proto!`, which are never written
+static EXTERNAL_FILES: LazyLock<Mutex<BTreeSet<String>>> = LazyLock::new(|| Mutex::new(BTreeSet::new()));
+
+/// Import (without `.proto`) that brings `google.protobuf.Empty` into scope
+pub const EMPTY_WKT_IMPORT: &str = "google/protobuf/empty";
+
 /// Determine if we should emit .proto files
 /// Priority: env var > feature flag > default (false)
 pub fn should_emit_file() -> bool {
@@ -44,6 +56,16 @@ pub fn register_and_emit_proto_inner(file_name: &str, content: &str) {
     }
 }
 
+/// Mark `file_name` as maintained outside this crate, so emission leaves it alone
+pub fn register_external_file(file_name: &str) {
+    EXTERNAL_FILES.lock().unwrap().insert(file_name.to_string());
+}
+
+/// Whether `file_name` was read by `import_proto!`
+pub fn is_external_file(file_name: &str) -> bool {
+    EXTERNAL_FILES.lock().unwrap().contains(file_name)
+}
+
 /// Register a package name override for a proto file
 pub fn register_package(file_name: &str, package_name: &str) {
     let mut registry = PACKAGE_REGISTRY.lock().unwrap();
@@ -83,6 +105,30 @@ fn register_imports_in_registry(file: &str, imports: &BTreeSet<String>) {
     }
 }
 
+/// Remember a unit struct that is emitted as `google.protobuf.Empty`
+pub fn register_empty_wkt_type(type_name: &str) {
+    EMPTY_WKT_TYPES.lock().unwrap().insert(type_name.to_string());
+}
+
+/// Whether `type_name` was declared with `#[proto_message(empty = "wkt")]`
+pub fn is_empty_wkt_type(type_name: &str) -> bool {
+    EMPTY_WKT_TYPES.lock().unwrap().contains(type_name)
+}
+
+/// Emit `child` as a nested message of `parent` in `file_name`
+pub fn register_nested_type(file_name: &str, child: &str, parent: &str) {
+    let mut registry = NESTED_TYPES.lock().unwrap();
+    registry.entry(file_name.to_string()).or_default().insert(child.to_string(), parent.to_string());
+}
+
+/// Register an import for emitted files only; schema output derives it from the type's `ProtoIdent`
+pub fn register_emitted_import(file: &str, import: &str) {
+    register_imports_in_registry(file, &BTreeSet::from([import.to_string()]));
+    if should_emit_file() {
+        write_proto_file_internal(file);
+    }
+}
+
 /// Register single import
 pub fn register_import(file: &str, imports: &[String]) -> TokenStream {
     {
@@ -118,6 +164,10 @@ fn write_proto_file(file_name_path: &str, content: &str) {
 
 /// Internal file writing implementation
 fn write_proto_file_internal(file_name_path: &str) {
+    if is_external_file(file_name_path) {
+        return;
+    }
+    crate::schema_check::remember_snapshot(file_name_path);
     let path = Path::new(".").join(file_name_path);
 
     // Ensure parent directory exists
@@ -140,10 +190,15 @@ fn write_proto_file_internal(file_name_path: &str) {
         registry.get(file_name_path).cloned()
     };
     let package_name = package_override.unwrap_or_else(|| derive_package_name(file_name));
+    let content_items = match NESTED_TYPES.lock().unwrap().get(file_name_path) {
+        Some(nested) => nest_content_items(&content_items, nested),
+        None => content_items,
+    };
     let file_content = build_complete_proto_file(&package_name, &imports, &content_items);
 
     // Write atomically
     write_file_atomically(&path, &file_content);
+    crate::schema_check::remember_write(file_name_path);
 
     // Mark as initialized
     drop(registry);
@@ -165,6 +220,111 @@ fn separate_imports_and_content(defs: &BTreeSet<String>) -> (Vec<String>, Vec<St
     (imports, content)
 }
 
+/// Move nested messages into their parents and qualify every reference to them.
+///
+/// Nesting only applies when both messages live in the same file; anything else stays top-level.
+fn nest_content_items(items: &[String], nested: &BTreeMap<String, String>) -> Vec<String> {
+    let defined: BTreeMap<&str, usize> =
+        items.iter().enumerate().filter_map(|(idx, item)| defined_name(item).map(|name| (name, idx))).collect();
+    let parents: BTreeMap<&str, &str> = nested
+        .iter()
+        .map(|(child, parent)| (child.as_str(), parent.as_str()))
+        .filter(|(child, parent)| defined.contains_key(child) && defined.contains_key(parent))
+        .collect();
+
+    let mut qualified = BTreeMap::new();
+    for &child in parents.keys() {
+        let mut path = child.to_string();
+        let mut current = child;
+        let mut depth = 0;
+        while let Some(parent) = parents.get(current) {
+            path = format!("{parent}.{path}");
+            current = parent;
+            depth += 1;
+            if depth > parents.len() {
+                break;
+            }
+        }
+        // Cycles never reach the top level, so their members stay unnested
+        if !parents.contains_key(current) {
+            qualified.insert(child, path);
+        }
+    }
+    if qualified.is_empty() {
+        return items.to_vec();
+    }
+
+    let rewritten: Vec<String> = items.iter().map(|item| qualify_references(item, &qualified)).collect();
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| defined_name(item).is_none_or(|name| !qualified.contains_key(name)))
+        .map(|(idx, _)| assemble_nested(idx, &rewritten, &defined, &parents, &qualified))
+        .collect()
+}
+
+fn assemble_nested(
+    idx: usize,
+    rewritten: &[String],
+    defined: &BTreeMap<&str, usize>,
+    parents: &BTreeMap<&str, &str>,
+    qualified: &BTreeMap<&str, String>,
+) -> String {
+    let item = &rewritten[idx];
+    let Some(name) = defined_name(item) else {
+        return item.clone();
+    };
+    let mut children: Vec<usize> = parents
+        .iter()
+        .filter(|(child, parent)| **parent == name && qualified.contains_key(*child))
+        .map(|(child, _)| defined[child])
+        .collect();
+    if children.is_empty() {
+        return item.clone();
+    }
+    children.sort_unstable();
+
+    let body = item.trim_end();
+    let mut output = body.strip_suffix('}').unwrap_or(body).trim_end().to_string();
+    for child in children {
+        let child = assemble_nested(child, rewritten, defined, parents, qualified);
+        output.push_str(if output.ends_with('{') { "\n" } else { "\n\n" });
+        let indented: Vec<String> =
+            child.trim().lines().map(|line| if line.is_empty() { String::new() } else { format!("  {line}") }).collect();
+        output.push_str(&indented.join("\n"));
+    }
+    output.push_str("\n}\n");
+    output
+}
+
+/// Name of the last top-level `message` or `enum` an emitted item declares
+fn defined_name(item: &str) -> Option<&str> {
+    item.lines()
+        .filter_map(|line| line.strip_prefix("message ").or_else(|| line.strip_prefix("enum ")))
+        .filter_map(|rest| rest.split(|ch: char| ch.is_whitespace() || ch == '{').next())
+        .next_back()
+}
+
+/// Replace unqualified references to nested messages with their dotted path
+fn qualify_references(item: &str, qualified: &BTreeMap<&str, String>) -> String {
+    let mut output = String::with_capacity(item.len());
+    let mut rest = item;
+    while let Some(start) = rest.find(|ch: char| ch.is_ascii_alphabetic() || ch == '_') {
+        let (before, tail) = rest.split_at(start);
+        output.push_str(before);
+        let end = tail.find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_')).unwrap_or(tail.len());
+        let (word, after) = tail.split_at(end);
+        let declaration = output.ends_with("message ") || output.ends_with("enum ") || output.ends_with('.');
+        match qualified.get(word) {
+            Some(path) if !declaration => output.push_str(path),
+            _ => output.push_str(word),
+        }
+        rest = after;
+    }
+    output.push_str(rest);
+    output
+}
+
 fn build_complete_proto_file(package_name: &str, imports: &[String], content_items: &[String]) -> String {
     use std::fmt::Write;
     let mut output = String::new();
@@ -227,6 +387,30 @@ mod tests {
         assert_eq!(content[0], "message Foo {}");
     }
 
+    #[test]
+    fn test_nest_content_items() {
+        let items = vec![
+            "enum Kind {\n  KIND_A = 0;\n}\n".to_string(),
+            "message Line {\n  Kind kind = 1;\n}\n".to_string(),
+            "message Order {\n  repeated Line lines = 1;\n}\n".to_string(),
+            "message Summary {\n  Line first = 1;\n}\n".to_string(),
+        ];
+        let nested = BTreeMap::from([
+            ("Line".to_string(), "Order".to_string()),
+            ("Kind".to_string(), "Line".to_string()),
+            ("Missing".to_string(), "Order".to_string()),
+        ]);
+
+        let content = nest_content_items(&items, &nested);
+
+        assert_eq!(content.len(), 2);
+        assert_eq!(
+            content[0],
+            "message Order {\n  repeated Order.Line lines = 1;\n\n  message Line {\n    Order.Line.Kind kind = 1;\n\n    enum Kind {\n      KIND_A = 0;\n    }\n  }\n}\n"
+        );
+        assert_eq!(content[1], "message Summary {\n  Order.Line first = 1;\n}\n");
+    }
+
     #[test]
     fn test_should_emit_file() {
         // Test depends on env var and feature flag
@@ -30,6 +30,7 @@ inject_proto_import!("protos/test.proto", "google.protobuf.timestamp", "common.t
 pub struct ValueCanBeFolded {
     a: u64,
     b: u64,
+    #[expect(dead_code)]
     c: u64,
 }
 
@@ -575,6 +576,7 @@ pub enum VeryComplex {
 
 #[proto_message(proto_path = "protos/showcase_proto/show.proto")]
 pub struct Attr {
+    #[expect(dead_code)]
     #[proto(skip)]
     id_skip: Vec<i64>,
     id_vec: Vec<String>,
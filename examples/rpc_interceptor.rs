@@ -61,6 +61,7 @@ impl UserAdvancedInterceptor for UserCtx {
 }
 
 // A dummy server impl
+#[expect(dead_code)]
 struct S;
 
 impl InterceptorRpc for S {
@@ -0,0 +1,182 @@
+//! Conversions between repeated numeric fields and Arrow arrays.
+//!
+//! Decoded `Vec` fields move into a [`PrimitiveArray`] and back without copying the values:
+//!
+//! ```rust,ignore
+//! let prices: Float64Array = proto_rs::arrow::vec_to_array(frame.prices);
+//! frame.prices = proto_rs::arrow::array_to_vec(batch_column)?;
+//! ```
+//!
+//! On the wire, [`field_to_array`] reads a packed field straight out of an encoded message. A
+//! `float` or `double` field packed in one run is little-endian like an Arrow buffer, so when it is
+//! aligned the array shares the message's bytes; other fields are decoded into a new buffer.
+//! [`write_packed_array`] writes an array as a packed field.
+
+use arrow_array::Array;
+use arrow_array::ArrowPrimitiveType;
+use arrow_array::PrimitiveArray;
+use arrow_array::types::Float32Type;
+use arrow_array::types::Float64Type;
+use arrow_array::types::Int32Type;
+use arrow_array::types::Int64Type;
+use arrow_array::types::UInt32Type;
+use arrow_array::types::UInt64Type;
+use arrow_buffer::ArrowNativeType;
+use arrow_buffer::Buffer;
+use arrow_buffer::ScalarBuffer;
+use bytes::Buf;
+use bytes::BufMut;
+use bytes::Bytes;
+
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::EncodeError;
+use crate::ProtoArchive;
+use crate::ProtoDecoder;
+use crate::ProtoDefault;
+use crate::ProtoExt;
+use crate::ProtoFieldMerge;
+use crate::RevVec;
+use crate::RevWriter;
+use crate::alloc::vec::Vec;
+use crate::encoding::WireType;
+use crate::encoding::decode_key;
+use crate::encoding::decode_varint;
+use crate::encoding::encode_varint;
+use crate::encoding::encoded_len_varint;
+use crate::encoding::skip_field;
+
+/// Scalar of a repeated numeric field with an Arrow counterpart.
+pub trait ArrowScalar: ArrowNativeType + ProtoExt + ProtoArchive + ProtoFieldMerge + ProtoDefault + sealed::Sealed {
+    /// Arrow type whose native values are `Self`.
+    type Arrow: ArrowPrimitiveType<Native = Self>;
+    /// Values are little-endian fixed-width on the wire, laid out like an Arrow buffer.
+    const FIXED_WIDTH: bool;
+}
+
+macro_rules! impl_arrow_scalar {
+    ($($ty:ty => $arrow:ty, $fixed:literal;)*) => {
+        $(
+            impl sealed::Sealed for $ty {}
+
+            impl ArrowScalar for $ty {
+                type Arrow = $arrow;
+                const FIXED_WIDTH: bool = $fixed;
+            }
+        )*
+    };
+}
+
+impl_arrow_scalar! {
+    i32 => Int32Type, false;
+    i64 => Int64Type, false;
+    u32 => UInt32Type, false;
+    u64 => UInt64Type, false;
+    f32 => Float32Type, true;
+    f64 => Float64Type, true;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Moves the values of a `Vec` field into an Arrow array without copying them.
+#[inline]
+pub fn vec_to_array<T: ArrowScalar>(values: Vec<T>) -> PrimitiveArray<T::Arrow> {
+    PrimitiveArray::new(ScalarBuffer::from(values), None)
+}
+
+/// Takes the values of an Arrow array as a `Vec`.
+///
+/// The array's buffer is reused when the array is its only owner and starts at the beginning of
+/// it; otherwise the values are copied. Repeated fields hold no nulls, so an array with nulls is
+/// rejected.
+pub fn array_to_vec<T: ArrowScalar>(array: PrimitiveArray<T::Arrow>) -> Result<Vec<T>, DecodeError> {
+    let (_, values, nulls) = array.into_parts();
+    if nulls.is_some_and(|nulls| nulls.null_count() > 0) {
+        return Err(DecodeError::new("repeated fields cannot hold null values"));
+    }
+    let len = values.len();
+    match values.into_inner().into_vec::<T>() {
+        Ok(mut values) => {
+            values.truncate(len);
+            Ok(values)
+        }
+        Err(buffer) => Ok(ScalarBuffer::<T>::new(buffer, 0, len).to_vec()),
+    }
+}
+
+/// Reads the repeated numeric field `tag` of the encoded message `message` into an Arrow array.
+///
+/// A fixed-width field written as one aligned packed run shares `message`'s memory; anything else,
+/// varints, several runs or unpacked values, is decoded into a new buffer.
+pub fn field_to_array<T: ArrowScalar>(message: &Bytes, tag: u32) -> Result<PrimitiveArray<T::Arrow>, DecodeError> {
+    let ctx = DecodeContext::default();
+    let mut runs = Vec::new();
+    let mut rest = message.as_ref();
+    while rest.has_remaining() {
+        let (field_tag, wire_type) = decode_key(&mut rest)?;
+        if field_tag == tag {
+            let start = message.len() - rest.len();
+            skip_field(wire_type, field_tag, &mut rest, ctx)?;
+            runs.push((wire_type, start..message.len() - rest.len()));
+        } else {
+            skip_field(wire_type, field_tag, &mut rest, ctx)?;
+        }
+    }
+
+    if T::FIXED_WIDTH
+        && cfg!(target_endian = "little")
+        && let [(WireType::LengthDelimited, range)] = runs.as_slice()
+    {
+        let mut payload = &message[range.clone()];
+        let len = usize::try_from(decode_varint(&mut payload)?).unwrap_or(usize::MAX);
+        let payload = message.slice(range.end - len..range.end);
+        let width = core::mem::size_of::<T>();
+        if len % width != 0 {
+            return Err(DecodeError::new("packed field length is not a multiple of its value size"));
+        }
+        if payload.as_ptr().align_offset(core::mem::align_of::<T>()) == 0 {
+            return Ok(PrimitiveArray::new(ScalarBuffer::new(Buffer::from(payload), 0, len / width), None));
+        }
+    }
+
+    let mut values: Vec<T> = Vec::new();
+    for (wire_type, range) in runs {
+        let mut payload = &message[range];
+        ProtoDecoder::merge(&mut values, wire_type, &mut payload, ctx)?;
+    }
+    Ok(vec_to_array(values))
+}
+
+/// Appends `array` to an encoded message as the packed repeated field `tag`; empty arrays are not
+/// written. Fixed-width values are copied as one block.
+///
+/// # Panics
+///
+/// Panics if `array` holds nulls, which a repeated field cannot carry.
+pub fn write_packed_array<T: ArrowScalar>(tag: u32, array: &PrimitiveArray<T::Arrow>, buf: &mut impl BufMut) -> Result<(), EncodeError> {
+    assert!(array.null_count() == 0, "repeated fields cannot hold null values");
+    let values = array.values();
+    if values.is_empty() {
+        return Ok(());
+    }
+    let mut varints = RevVec::with_capacity(0);
+    let payload = if T::FIXED_WIDTH && cfg!(target_endian = "little") {
+        values.inner().as_slice()
+    } else {
+        for value in values.iter().rev() {
+            value.archive::<0>(&mut varints);
+        }
+        varints.as_written_slice()
+    };
+    let key = u64::from(tag << 3 | WireType::LengthDelimited as u32);
+    let required = encoded_len_varint(key) + encoded_len_varint(payload.len() as u64) + payload.len();
+    if buf.remaining_mut() < required {
+        return Err(EncodeError::new(required, buf.remaining_mut()));
+    }
+    encode_varint(key, buf);
+    encode_varint(payload.len() as u64, buf);
+    buf.put_slice(payload);
+    Ok(())
+}
@@ -0,0 +1,104 @@
+//! Messages in caches.
+//!
+//! [`Codec`] stores messages in external caches as their length-delimited encoding. With the
+//! `tonic` feature this module also holds the in-memory [`ResponseCache`] of generated servers.
+
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::ProtoDecode;
+use crate::ProtoEncode;
+use crate::ProtoExt;
+use crate::decode_length_delimiter;
+use crate::encoding::encode_varint;
+use crate::length_delimiter_len;
+#[cfg(feature = "tonic")]
+pub use crate::tonic::cache::CacheStats;
+#[cfg(feature = "tonic")]
+pub use crate::tonic::cache::DEFAULT_MAX_ENTRIES;
+#[cfg(feature = "tonic")]
+pub use crate::tonic::cache::RequestKey;
+#[cfg(feature = "tonic")]
+pub use crate::tonic::cache::ResponseCache;
+#[cfg(feature = "tonic")]
+pub use crate::tonic::cache::request_key;
+
+/// Message stored in an external cache as its length-delimited encoding: the varint length of
+/// the encoded message followed by the message.
+///
+/// With the `redis` feature it implements `ToRedisArgs` and `FromRedisValue`, so it can be
+/// passed to and read from redis commands directly; a missing key reads as `None` through
+/// `Option<Codec<T>>`. For in-process caches such as `moka`, [`weigh`](Self::weigh) sizes
+/// entries by their encoding and [`to_bytes`](Self::to_bytes) and
+/// [`from_bytes`](Self::from_bytes) convert entries for a persistence layer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Codec<T>(pub T);
+
+impl<T> Codec<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: ProtoEncode + ProtoExt> Codec<T> {
+    /// Length-delimited encoding of the message.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let payload = self.0.encode_to_vec();
+        let mut buf = Vec::with_capacity(length_delimiter_len(payload.len()) + payload.len());
+        encode_varint(payload.len() as u64, &mut buf);
+        buf.extend_from_slice(&payload);
+        buf
+    }
+
+    /// Encoded size of `value` as a cache weight, for `moka`'s `weigher`. Saturates at
+    /// `u32::MAX`.
+    pub fn weigh<K>(_key: &K, value: &T) -> u32 {
+        let len = value.encode_to_vec().len();
+        u32::try_from(length_delimiter_len(len) + len).unwrap_or(u32::MAX)
+    }
+}
+
+impl<T: ProtoDecode> Codec<T> {
+    /// Message from its length-delimited encoding.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError`] if `bytes` are not exactly one delimited message.
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, DecodeError> {
+        let len = decode_length_delimiter(&mut bytes)?;
+        if bytes.len() != len {
+            return Err(DecodeError::new("delimited length does not match the cached value"));
+        }
+        T::decode(bytes, DecodeContext::default()).map(Codec)
+    }
+}
+
+impl<T> From<T> for Codec<T> {
+    fn from(value: T) -> Self {
+        Codec(value)
+    }
+}
+
+#[cfg(feature = "redis")]
+impl<T: ProtoEncode + ProtoExt> redis::ToRedisArgs for Codec<T> {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + redis::RedisWrite,
+    {
+        out.write_arg(&self.to_bytes());
+    }
+}
+
+#[cfg(feature = "redis")]
+impl<T: ProtoDecode> redis::FromRedisValue for Codec<T> {
+    fn from_redis_value(value: &redis::Value) -> redis::RedisResult<Self> {
+        let redis::Value::BulkString(bytes) = value else {
+            return Err(redis::RedisError::from((
+                redis::ErrorKind::TypeError,
+                "expected a bulk string",
+                format!("{value:?}"),
+            )));
+        };
+        Self::from_bytes(bytes)
+            .map_err(|error| redis::RedisError::from((redis::ErrorKind::TypeError, "invalid cached message", error.to_string())))
+    }
+}
@@ -0,0 +1,78 @@
+//! CBOR encoding of registered messages, for peers that share the message definitions but do
+//! not speak protobuf.
+//!
+//! A message is a CBOR map from field number to value, so `Order { id: 7, .. }` starts with
+//! `a1 01 07`. Fields left out of the protobuf encoding are left out of the map, repeated fields
+//! are arrays, map fields are maps with their native keys, `bytes` are byte strings, enums are
+//! their numbers and complex enums are maps holding the set variant under its oneof tag. Decoding
+//! skips field numbers the message does not know.
+//!
+//! ```rust,ignore
+//! let bytes = proto_rs::cbor::to_cbor(&order)?;
+//! let order: Order = proto_rs::cbor::from_cbor(&bytes)?;
+//! ```
+
+use ciborium::Value;
+
+use crate::ProtoDecode;
+use crate::ProtoEncode;
+use crate::keyed::Keyed;
+use crate::keyed::from_keyed;
+use crate::keyed::to_keyed;
+use crate::schemas::ProtoIdentifiable;
+use crate::traits::ProtoExt;
+use crate::transcode::TranscodeError;
+
+/// CBOR encoding of `value`.
+///
+/// # Errors
+///
+/// Returns [`TranscodeError::UnknownMessage`] if `T` has no registered message schema.
+pub fn to_cbor<T: ProtoEncode + ProtoExt + ProtoIdentifiable>(value: &T) -> Result<Vec<u8>, TranscodeError> {
+    let mut buf = Vec::new();
+    ciborium::into_writer(&to_value(to_keyed(value)?), &mut buf).map_err(|error| TranscodeError::Malformed(error.to_string()))?;
+    Ok(buf)
+}
+
+/// Message of type `T` from its CBOR encoding.
+///
+/// # Errors
+///
+/// Returns [`TranscodeError::Malformed`] if `bytes` are not CBOR and
+/// [`TranscodeError::Invalid`] if the value does not match the schema.
+pub fn from_cbor<T: ProtoDecode + ProtoIdentifiable>(bytes: &[u8]) -> Result<T, TranscodeError> {
+    let value: Value = ciborium::from_reader(bytes).map_err(|error| TranscodeError::Malformed(error.to_string()))?;
+    from_keyed(&from_value(value)?)
+}
+
+fn to_value(keyed: Keyed) -> Value {
+    match keyed {
+        Keyed::Null => Value::Null,
+        Keyed::Bool(value) => Value::Bool(value),
+        // Field values fit in 64 bits
+        Keyed::Int(value) => Value::Integer(value.try_into().unwrap_or_else(|_| 0.into())),
+        Keyed::F32(value) => Value::Float(value.into()),
+        Keyed::F64(value) => Value::Float(value),
+        Keyed::Text(value) => Value::Text(value),
+        Keyed::Bytes(value) => Value::Bytes(value),
+        Keyed::Array(items) => Value::Array(items.into_iter().map(to_value).collect()),
+        Keyed::Map(entries) => Value::Map(entries.into_iter().map(|(key, value)| (to_value(key), to_value(value))).collect()),
+    }
+}
+
+fn from_value(value: Value) -> Result<Keyed, TranscodeError> {
+    Ok(match value {
+        Value::Null => Keyed::Null,
+        Value::Bool(value) => Keyed::Bool(value),
+        Value::Integer(value) => Keyed::Int(value.into()),
+        Value::Float(value) => Keyed::F64(value),
+        Value::Text(value) => Keyed::Text(value),
+        Value::Bytes(value) => Keyed::Bytes(value),
+        Value::Tag(_, value) => from_value(*value)?,
+        Value::Array(items) => Keyed::Array(items.into_iter().map(from_value).collect::<Result<_, _>>()?),
+        Value::Map(entries) => Keyed::Map(
+            entries.into_iter().map(|(key, value)| Ok((from_value(key)?, from_value(value)?))).collect::<Result<_, TranscodeError>>()?,
+        ),
+        _ => return Err(TranscodeError::Malformed("unsupported CBOR value".to_string())),
+    })
+}
@@ -3,6 +3,10 @@
 use core::marker::PhantomData;
 
 use crate::alloc::vec::Vec;
+#[cfg(feature = "capture")]
+use crate::tonic::capture::Tap;
+#[cfg(feature = "tonic")]
+use crate::tonic::sizes::MessageKind;
 
 pub trait AsBytes {
     fn as_bytes(&self) -> &[u8];
@@ -39,39 +43,154 @@ pub struct SunByRefDeref; // Sun<'a> = &'a T::Target
 
 #[derive(Debug, Clone)]
 pub struct ProtoCodec<Encode = (), Decode = (), Mode = SunByRef> {
+    /// Route and side reported to the size hooks; unset codecs are not accounted.
+    #[cfg(feature = "tonic")]
+    pub(crate) accounting: Option<(&'static str, CodecSide)>,
+    /// Capture call number, assigned when the first encoder or decoder is built during a capture.
+    #[cfg(feature = "capture")]
+    pub(crate) call: u64,
     _marker: PhantomData<(Encode, Decode, Mode)>,
 }
 
+/// Whether a codec encodes requests (client) or responses (server).
+#[cfg(feature = "tonic")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CodecSide {
+    Client,
+    Server,
+}
+
+#[cfg(feature = "tonic")]
+impl CodecSide {
+    pub(crate) const fn encoded_kind(self) -> MessageKind {
+        match self {
+            CodecSide::Client => MessageKind::Request,
+            CodecSide::Server => MessageKind::Response,
+        }
+    }
+
+    pub(crate) const fn decoded_kind(self) -> MessageKind {
+        match self {
+            CodecSide::Client => MessageKind::Response,
+            CodecSide::Server => MessageKind::Request,
+        }
+    }
+}
+
 impl<Encode, Decode, Mode> Default for ProtoCodec<Encode, Decode, Mode> {
     fn default() -> Self {
-        Self { _marker: PhantomData }
+        Self::new()
     }
 }
 
 impl<Encode, Decode, Mode> ProtoCodec<Encode, Decode, Mode> {
     pub const fn new() -> Self {
-        Self { _marker: PhantomData }
+        Self {
+            #[cfg(feature = "tonic")]
+            accounting: None,
+            #[cfg(feature = "capture")]
+            call: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Codec of a generated client method; message sizes are reported for `route`.
+    #[cfg(feature = "tonic")]
+    pub const fn client(route: &'static str) -> Self {
+        Self {
+            accounting: Some((route, CodecSide::Client)),
+            #[cfg(feature = "capture")]
+            call: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Codec of a generated server method; message sizes are reported for `route`.
+    #[cfg(feature = "tonic")]
+    pub const fn server(route: &'static str) -> Self {
+        Self {
+            accounting: Some((route, CodecSide::Server)),
+            #[cfg(feature = "capture")]
+            call: 0,
+            _marker: PhantomData,
+        }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct ProtoEncoder<T, Mode> {
+    #[cfg(feature = "tonic")]
+    pub(crate) accounting: Option<(&'static str, MessageKind)>,
+    #[cfg(feature = "capture")]
+    pub(crate) tap: Option<Tap>,
     _marker: core::marker::PhantomData<(T, Mode)>,
 }
 
+impl<T, Mode> ProtoEncoder<T, Mode> {
+    #[cfg(feature = "tonic")]
+    pub(crate) const fn accounted(accounting: Option<(&'static str, MessageKind)>) -> Self {
+        Self {
+            accounting,
+            #[cfg(feature = "capture")]
+            tap: None,
+            _marker: PhantomData,
+        }
+    }
+
+    #[cfg(feature = "capture")]
+    pub(crate) const fn tapped(mut self, tap: Option<Tap>) -> Self {
+        self.tap = tap;
+        self
+    }
+}
+
 impl<T, Mode> Default for ProtoEncoder<T, Mode> {
     fn default() -> Self {
-        Self { _marker: PhantomData }
+        Self {
+            #[cfg(feature = "tonic")]
+            accounting: None,
+            #[cfg(feature = "capture")]
+            tap: None,
+            _marker: PhantomData,
+        }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct ProtoDecoder<T> {
+    #[cfg(feature = "tonic")]
+    pub(crate) accounting: Option<(&'static str, MessageKind)>,
+    #[cfg(feature = "capture")]
+    pub(crate) tap: Option<Tap>,
     _marker: PhantomData<T>,
 }
 
+impl<T> ProtoDecoder<T> {
+    #[cfg(feature = "tonic")]
+    pub(crate) const fn accounted(accounting: Option<(&'static str, MessageKind)>) -> Self {
+        Self {
+            accounting,
+            #[cfg(feature = "capture")]
+            tap: None,
+            _marker: PhantomData,
+        }
+    }
+
+    #[cfg(feature = "capture")]
+    pub(crate) const fn tapped(mut self, tap: Option<Tap>) -> Self {
+        self.tap = tap;
+        self
+    }
+}
+
 impl<T> Default for ProtoDecoder<T> {
     fn default() -> Self {
-        Self { _marker: PhantomData }
+        Self {
+            #[cfg(feature = "tonic")]
+            accounting: None,
+            #[cfg(feature = "capture")]
+            tap: None,
+            _marker: PhantomData,
+        }
     }
 }
@@ -0,0 +1,345 @@
+//! Compact positional encoding of registered messages for local persistence.
+//!
+//! On-disk caches and spill files that only this program reads back do not need protobuf's
+//! field keys. [`to_compact`] writes the fields of a message in declaration order without keys:
+//! integers are varints (signed ones zigzag-encoded), fixed-width types and floats are
+//! little-endian, strings, bytes, repeated and map fields are prefixed with their length, and
+//! optional and message fields with a presence byte. A complex enum is the varint of its oneof
+//! tag, `0` when no variant is set, followed by the variant.
+//!
+//! The layout only holds for the exact message definitions it was written with, so it is not an
+//! interchange format: send the protobuf encoding to other programs. Every buffer starts with an
+//! 8-byte hash of the definitions of the message and the messages it contains, and
+//! [`from_compact`] refuses buffers written against others with
+//! [`TranscodeError::SchemaChanged`], at which point a cache rebuilds the entry from its source.
+//!
+//! ```rust,ignore
+//! let cached = proto_rs::compact::to_compact(&order)?;
+//! let order: Order = proto_rs::compact::from_compact(&cached)?;
+//! ```
+
+use std::collections::BTreeSet;
+
+use bytes::Buf;
+
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::ProtoDecode;
+use crate::ProtoEncode;
+use crate::encoding::decode_varint;
+use crate::encoding::encode_varint;
+use crate::keyed::Keyed;
+use crate::keyed::default_keyed;
+use crate::keyed::from_keyed;
+use crate::keyed::message_ident;
+use crate::keyed::tag_of;
+use crate::keyed::to_keyed;
+use crate::schemas::Field;
+use crate::schemas::ProtoEntry;
+use crate::schemas::ProtoIdent;
+use crate::schemas::ProtoIdentifiable;
+use crate::schemas::ProtoLabel;
+use crate::schemas::ProtoType;
+use crate::schemas::schema_for;
+use crate::schemas::schema_hash;
+use crate::traits::ProtoExt;
+use crate::transcode::Kind;
+use crate::transcode::TranscodeError;
+use crate::transcode::kind;
+use crate::transcode::zigzag;
+
+/// Compact encoding of `value`, starting with the hash of its layout.
+///
+/// # Errors
+///
+/// Returns [`TranscodeError::UnknownMessage`] if `T` or a message it contains has no registered
+/// message schema.
+pub fn to_compact<T: ProtoEncode + ProtoExt + ProtoIdentifiable>(value: &T) -> Result<Vec<u8>, TranscodeError> {
+    let keyed = to_keyed(value)?;
+    let mut buf = layout_hash(&T::PROTO_IDENT)?.to_le_bytes().to_vec();
+    write_message(&T::PROTO_IDENT, &keyed, &mut buf)?;
+    Ok(buf)
+}
+
+/// Message of type `T` from its compact encoding.
+///
+/// # Errors
+///
+/// Returns [`TranscodeError::SchemaChanged`] if `bytes` were written against other definitions,
+/// [`TranscodeError::Decode`] if they are cut short and [`TranscodeError::Malformed`] if bytes
+/// are left over.
+pub fn from_compact<T: ProtoDecode + ProtoIdentifiable>(bytes: &[u8]) -> Result<T, TranscodeError> {
+    let ident = message_ident::<T>()?;
+    let Some((header, mut body)) = bytes.split_first_chunk::<8>() else {
+        return Err(TranscodeError::Malformed("missing layout hash".to_string()));
+    };
+    let expected = layout_hash(&ident)?;
+    let found = u64::from_le_bytes(*header);
+    if found != expected {
+        return Err(TranscodeError::SchemaChanged { expected, found });
+    }
+    let keyed = read_message(&ident, &mut body, DecodeContext::default())?;
+    if !body.is_empty() {
+        return Err(TranscodeError::Malformed("trailing bytes after the message".to_string()));
+    }
+    from_keyed(&keyed)
+}
+
+/// Hash of the definitions of `ident` and of every message reachable from its fields.
+fn layout_hash(ident: &ProtoIdent) -> Result<u64, TranscodeError> {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    let mut seen = BTreeSet::new();
+    let mut pending = vec![*ident];
+    while let Some(ident) = pending.pop() {
+        if !seen.insert(ident) {
+            continue;
+        }
+        hash = (hash ^ schema_hash(&ident).unwrap_or_default()).wrapping_mul(0x0100_0000_01b3);
+        let fields: Vec<&Field> = match schema_for(&ident).map(|schema| schema.content) {
+            Some(ProtoEntry::Struct { fields }) => fields.to_vec(),
+            Some(ProtoEntry::ComplexEnum { variants }) => variants.iter().flat_map(|variant| variant.fields.iter().copied()).collect(),
+            _ => return Err(TranscodeError::UnknownMessage(ident.name.to_string())),
+        };
+        // Reversed, so fields are visited in declaration order
+        for field in fields.into_iter().rev() {
+            let (key, value) = field.map_idents();
+            let nested = [field.array_elem, key, value, Some(field.proto_ident)];
+            for nested in nested.into_iter().flatten() {
+                if let Ok(Kind::Message(nested)) = kind(&nested) {
+                    pending.push(nested);
+                }
+            }
+        }
+    }
+    Ok(hash)
+}
+
+fn map_entries(keyed: &Keyed) -> Result<&[(Keyed, Keyed)], TranscodeError> {
+    match keyed {
+        Keyed::Map(entries) => Ok(entries),
+        _ => Err(TranscodeError::invalid("expected a map")),
+    }
+}
+
+fn write_message(ident: &ProtoIdent, keyed: &Keyed, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+    let entries = map_entries(keyed)?;
+    match schema_for(ident).map(|schema| schema.content) {
+        Some(ProtoEntry::Struct { fields }) => write_fields(fields, entries, buf),
+        Some(ProtoEntry::ComplexEnum { variants }) => {
+            let Some((key, value)) = entries.first() else {
+                encode_varint(0, buf);
+                return Ok(());
+            };
+            let tag = tag_of(key)?;
+            let variant = (tag as usize)
+                .checked_sub(1)
+                .and_then(|idx| variants.get(idx))
+                .ok_or_else(|| TranscodeError::invalid("unknown variant"))?;
+            encode_varint(tag.into(), buf);
+            let written = match variant.fields {
+                [field] if field.name.is_none() => write_value(&kind(&field.proto_ident)?, value, buf),
+                fields => map_entries(value).and_then(|entries| write_fields(fields, entries, buf)),
+            };
+            written.map_err(|error| error.at(&tag.to_string()))
+        }
+        _ => Err(TranscodeError::UnknownMessage(ident.name.to_string())),
+    }
+}
+
+fn write_fields(fields: &[&Field], entries: &[(Keyed, Keyed)], buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+    for field in fields {
+        let key = Keyed::Int(field.tag.into());
+        let value = entries.iter().find(|(tag, _)| *tag == key).map(|(_, value)| value);
+        write_field(field, value, buf).map_err(|error| error.at(&field.tag.to_string()))?;
+    }
+    Ok(())
+}
+
+fn write_field(field: &Field, value: Option<&Keyed>, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+    if field.is_bytes() {
+        return write_scalar(ProtoType::Bytes, value.unwrap_or(&Keyed::Bytes(Vec::new())), buf);
+    }
+    if let ProtoType::Map { key, value: value_type } = field.proto_ident.proto_type {
+        let (key_ident, value_ident) = field.map_idents();
+        let key_kind = key_ident.map_or(Ok(Kind::Scalar(*key)), |ident| kind(&ident))?;
+        let value_kind = value_ident.map_or(Ok(Kind::Scalar(*value_type)), |ident| kind(&ident))?;
+        let entries = value.map_or(Ok(&[][..]), map_entries)?;
+        encode_varint(entries.len() as u64, buf);
+        for (map_key, map_value) in entries {
+            write_value(&key_kind, map_key, buf)?;
+            write_value(&value_kind, map_value, buf)?;
+        }
+        return Ok(());
+    }
+    if field.proto_label == ProtoLabel::Repeated {
+        let elem = kind(&field.array_elem.unwrap_or(field.proto_ident))?;
+        let items = match value {
+            Some(Keyed::Array(items)) => items.as_slice(),
+            None => &[],
+            Some(_) => return Err(TranscodeError::invalid("expected an array")),
+        };
+        encode_varint(items.len() as u64, buf);
+        for (idx, item) in items.iter().enumerate() {
+            write_value(&elem, item, buf).map_err(|error| error.at(&format!("[{idx}]")))?;
+        }
+        return Ok(());
+    }
+    let kind = kind(&field.proto_ident)?;
+    if field.proto_label == ProtoLabel::Optional || matches!(kind, Kind::Message(_)) {
+        let Some(value) = value else {
+            buf.push(0);
+            return Ok(());
+        };
+        buf.push(1);
+        return write_value(&kind, value, buf);
+    }
+    match value {
+        Some(value) => write_value(&kind, value, buf),
+        None => write_value(&kind, &default_keyed(&kind), buf),
+    }
+}
+
+fn write_value(kind: &Kind, value: &Keyed, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+    match kind {
+        Kind::Scalar(ty) => write_scalar(*ty, value, buf),
+        Kind::Enum(_) => write_scalar(ProtoType::Sint32, value, buf),
+        Kind::Message(ident) => write_message(ident, value, buf),
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn write_scalar(ty: ProtoType, value: &Keyed, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+    match (ty, value) {
+        (ProtoType::Optional(inner) | ProtoType::Repeated(inner), value) => return write_scalar(*inner, value, buf),
+        (ProtoType::Bool, Keyed::Bool(value)) => buf.push(u8::from(*value)),
+        (ProtoType::Int32 | ProtoType::Int64 | ProtoType::Sint32 | ProtoType::Sint64 | ProtoType::Enum, Keyed::Int(value)) => {
+            let value = *value as i64;
+            encode_varint(((value << 1) ^ (value >> 63)) as u64, buf);
+        }
+        (ProtoType::Uint32 | ProtoType::Uint64, Keyed::Int(value)) => encode_varint(*value as u64, buf),
+        (ProtoType::Fixed32 | ProtoType::Sfixed32, Keyed::Int(value)) => buf.extend_from_slice(&(*value as u32).to_le_bytes()),
+        (ProtoType::Fixed64 | ProtoType::Sfixed64, Keyed::Int(value)) => buf.extend_from_slice(&(*value as u64).to_le_bytes()),
+        (ProtoType::Float, Keyed::F32(value)) => buf.extend_from_slice(&value.to_le_bytes()),
+        (ProtoType::Double, Keyed::F64(value)) => buf.extend_from_slice(&value.to_le_bytes()),
+        (ProtoType::String, Keyed::Text(text)) => {
+            encode_varint(text.len() as u64, buf);
+            buf.extend_from_slice(text.as_bytes());
+        }
+        (ProtoType::Bytes, Keyed::Bytes(bytes)) => {
+            encode_varint(bytes.len() as u64, buf);
+            buf.extend_from_slice(bytes);
+        }
+        _ => return Err(TranscodeError::invalid("value does not match the field type")),
+    }
+    Ok(())
+}
+
+fn read_message(ident: &ProtoIdent, buf: &mut &[u8], ctx: DecodeContext) -> Result<Keyed, TranscodeError> {
+    ctx.limit_reached()?;
+    match schema_for(ident).map(|schema| schema.content) {
+        Some(ProtoEntry::Struct { fields }) => read_fields(fields, buf, ctx),
+        Some(ProtoEntry::ComplexEnum { variants }) => {
+            let tag = u32::try_from(decode_varint(buf)?).map_err(|_| DecodeError::new("invalid variant"))?;
+            if tag == 0 {
+                return Ok(Keyed::Map(Vec::new()));
+            }
+            let variant = variants.get(tag as usize - 1).ok_or_else(|| DecodeError::new("invalid variant"))?;
+            let value = match variant.fields {
+                [field] if field.name.is_none() => read_value(&kind(&field.proto_ident)?, buf, ctx)?,
+                fields => read_fields(fields, buf, ctx)?,
+            };
+            Ok(Keyed::Map(vec![(Keyed::Int(tag.into()), value)]))
+        }
+        _ => Err(TranscodeError::UnknownMessage(ident.name.to_string())),
+    }
+}
+
+fn read_fields(fields: &[&Field], buf: &mut &[u8], ctx: DecodeContext) -> Result<Keyed, TranscodeError> {
+    let mut entries = Vec::with_capacity(fields.len());
+    for field in fields {
+        if let Some(value) = read_field(field, buf, ctx)? {
+            entries.push((Keyed::Int(field.tag.into()), value));
+        }
+    }
+    Ok(Keyed::Map(entries))
+}
+
+fn read_field(field: &Field, buf: &mut &[u8], ctx: DecodeContext) -> Result<Option<Keyed>, TranscodeError> {
+    if field.is_bytes() {
+        return read_scalar(ProtoType::Bytes, buf).map(Some);
+    }
+    if let ProtoType::Map { key, value } = field.proto_ident.proto_type {
+        let (key_ident, value_ident) = field.map_idents();
+        let key_kind = key_ident.map_or(Ok(Kind::Scalar(*key)), |ident| kind(&ident))?;
+        let value_kind = value_ident.map_or(Ok(Kind::Scalar(*value)), |ident| kind(&ident))?;
+        let mut entries = Vec::new();
+        for _ in 0..decode_varint(buf)? {
+            entries.push((read_value(&key_kind, buf, ctx)?, read_value(&value_kind, buf, ctx)?));
+        }
+        return Ok(Some(Keyed::Map(entries)));
+    }
+    if field.proto_label == ProtoLabel::Repeated {
+        let elem = kind(&field.array_elem.unwrap_or(field.proto_ident))?;
+        let mut items = Vec::new();
+        for _ in 0..decode_varint(buf)? {
+            items.push(read_value(&elem, buf, ctx)?);
+        }
+        return Ok(Some(Keyed::Array(items)));
+    }
+    let kind = kind(&field.proto_ident)?;
+    if field.proto_label == ProtoLabel::Optional || matches!(kind, Kind::Message(_)) {
+        return match take(buf, 1)?[0] {
+            0 => Ok(None),
+            1 => read_value(&kind, buf, ctx).map(Some),
+            _ => Err(TranscodeError::Malformed("invalid presence byte".to_string())),
+        };
+    }
+    read_value(&kind, buf, ctx).map(Some)
+}
+
+fn read_value(kind: &Kind, buf: &mut &[u8], ctx: DecodeContext) -> Result<Keyed, TranscodeError> {
+    match kind {
+        Kind::Scalar(ty) => read_scalar(*ty, buf),
+        Kind::Enum(_) => read_scalar(ProtoType::Sint32, buf),
+        Kind::Message(ident) => read_message(ident, buf, ctx.enter_recursion()),
+    }
+}
+
+fn read_scalar(ty: ProtoType, buf: &mut &[u8]) -> Result<Keyed, TranscodeError> {
+    let value = match ty {
+        ProtoType::Optional(inner) | ProtoType::Repeated(inner) => return read_scalar(*inner, buf),
+        ProtoType::Bool => Keyed::Bool(take(buf, 1)?[0] != 0),
+        ProtoType::Int32 | ProtoType::Int64 | ProtoType::Sint32 | ProtoType::Sint64 | ProtoType::Enum => {
+            Keyed::Int(zigzag(decode_varint(buf)?).into())
+        }
+        ProtoType::Uint32 | ProtoType::Uint64 => Keyed::Int(decode_varint(buf)?.into()),
+        ProtoType::Fixed32 => Keyed::Int(take(buf, 4)?.get_u32_le().into()),
+        ProtoType::Sfixed32 => Keyed::Int(take(buf, 4)?.get_i32_le().into()),
+        ProtoType::Fixed64 => Keyed::Int(take(buf, 8)?.get_u64_le().into()),
+        ProtoType::Sfixed64 => Keyed::Int(take(buf, 8)?.get_i64_le().into()),
+        ProtoType::Float => Keyed::F32(take(buf, 4)?.get_f32_le()),
+        ProtoType::Double => Keyed::F64(take(buf, 8)?.get_f64_le()),
+        ProtoType::String => {
+            let bytes = take_len_prefixed(buf)?;
+            let text = core::str::from_utf8(bytes).map_err(|_| DecodeError::new("invalid string value: data is not UTF-8 encoded"))?;
+            Keyed::Text(text.to_string())
+        }
+        ProtoType::Bytes => Keyed::Bytes(take_len_prefixed(buf)?.to_vec()),
+        ProtoType::Message(_) | ProtoType::Map { .. } | ProtoType::None => return Err(TranscodeError::invalid("unsupported field type")),
+    };
+    Ok(value)
+}
+
+fn take_len_prefixed<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], TranscodeError> {
+    let len = usize::try_from(decode_varint(buf)?).map_err(|_| DecodeError::new("buffer underflow"))?;
+    take(buf, len)
+}
+
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8], TranscodeError> {
+    if buf.len() < len {
+        return Err(DecodeError::new("buffer underflow").into());
+    }
+    let (head, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(head)
+}
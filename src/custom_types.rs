@@ -10,6 +10,15 @@ pub mod chrono;
 #[cfg(feature = "teloxide")]
 mod teloxide;
 
+mod attribute_map;
 mod hashers;
+mod maybe_utf8;
+mod proto_result;
+
+pub use attribute_map::AttributeMap;
+pub use attribute_map::AttributeValue;
+pub use attribute_map::FromAttribute;
+pub use maybe_utf8::MaybeUtf8;
+pub use proto_result::ProtoResult;
 
 pub mod well_known;
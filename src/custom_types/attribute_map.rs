@@ -0,0 +1,197 @@
+//! String-keyed bag of loosely typed values, for "extensions"-style fields.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use prosto_derive::proto_message;
+
+use super::well_known::Any;
+use crate::DecodeError;
+use crate::Name;
+use crate::ProtoDecode;
+use crate::ProtoDefault;
+use crate::ProtoEncode;
+use crate::ProtoExt;
+
+/// One value in an [`AttributeMap`].
+#[proto_message(proto_path = "protos/attribute_map.proto")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    Bool(bool),
+    Int(i64),
+    Uint(u64),
+    Double(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+    Message(#[proto(import_path = "well_known")] Any),
+}
+
+/// Map from string keys to [`AttributeValue`]s with typed accessors.
+///
+/// ```
+/// use proto_rs::AttributeMap;
+///
+/// let mut attrs = AttributeMap::new();
+/// attrs.set("retries", 3u64);
+/// attrs.set("region", "eu-west");
+/// assert_eq!(attrs.get::<u64>("retries"), Some(3));
+/// assert_eq!(attrs.get::<&str>("region"), Some("eu-west"));
+/// assert_eq!(attrs.get::<bool>("region"), None);
+/// ```
+#[proto_message(proto_path = "protos/attribute_map.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AttributeMap {
+    pub entries: BTreeMap<String, AttributeValue>,
+}
+
+impl AttributeMap {
+    pub const fn new() -> Self {
+        Self { entries: BTreeMap::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Stores `value` under `key`, returning the value it replaced.
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<AttributeValue>) -> Option<AttributeValue> {
+        self.entries.insert(key.into(), value.into())
+    }
+
+    /// Returns the value under `key` if it holds a `V`.
+    pub fn get<'a, V: FromAttribute<'a>>(&'a self, key: &str) -> Option<V> {
+        self.entries.get(key).and_then(V::from_attribute)
+    }
+
+    pub fn value(&self, key: &str) -> Option<&AttributeValue> {
+        self.entries.get(key)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<AttributeValue> {
+        self.entries.remove(key)
+    }
+
+    /// Packs `message` into an [`Any`] stored under `key`.
+    pub fn set_message<M: ProtoEncode + ProtoExt + Name>(&mut self, key: impl Into<String>, message: &M) -> Option<AttributeValue> {
+        self.set(key, Any::pack(message))
+    }
+
+    /// Unpacks the message under `key`. Returns `None` if the key is missing or holds anything
+    /// other than an `M`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is an `M` whose payload does not decode.
+    pub fn get_message<M: ProtoDecode + ProtoDefault + Name>(&self, key: &str) -> Result<Option<M>, DecodeError> {
+        match self.entries.get(key) {
+            Some(AttributeValue::Message(any)) => any.unpack(),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &AttributeValue)> {
+        self.entries.iter().map(|(key, value)| (key.as_str(), value))
+    }
+}
+
+impl<K: Into<String>, V: Into<AttributeValue>> FromIterator<(K, V)> for AttributeMap {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            entries: iter.into_iter().map(|(key, value)| (key.into(), value.into())).collect(),
+        }
+    }
+}
+
+/// Types that can be read out of an [`AttributeValue`] by [`AttributeMap::get`].
+pub trait FromAttribute<'a>: Sized {
+    fn from_attribute(value: &'a AttributeValue) -> Option<Self>;
+}
+
+macro_rules! impl_attribute_value {
+    ($variant:ident, $ty:ty $(, $narrow:ty)*) => {
+        impl From<$ty> for AttributeValue {
+            fn from(value: $ty) -> Self {
+                Self::$variant(value)
+            }
+        }
+
+        impl FromAttribute<'_> for $ty {
+            fn from_attribute(value: &AttributeValue) -> Option<Self> {
+                match value {
+                    AttributeValue::$variant(inner) => Some(inner.clone()),
+                    _ => None,
+                }
+            }
+        }
+
+        $(
+            impl From<$narrow> for AttributeValue {
+                fn from(value: $narrow) -> Self {
+                    Self::$variant(value.into())
+                }
+            }
+
+            impl FromAttribute<'_> for $narrow {
+                fn from_attribute(value: &AttributeValue) -> Option<Self> {
+                    match value {
+                        AttributeValue::$variant(inner) => (*inner).try_into().ok(),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_attribute_value!(Bool, bool);
+impl_attribute_value!(Int, i64, i32, i16, i8);
+impl_attribute_value!(Uint, u64, u32, u16, u8);
+impl_attribute_value!(Double, f64);
+impl_attribute_value!(Text, String);
+impl_attribute_value!(Bytes, Vec<u8>);
+impl_attribute_value!(Message, Any);
+
+impl From<f32> for AttributeValue {
+    fn from(value: f32) -> Self {
+        Self::Double(value.into())
+    }
+}
+
+impl From<&str> for AttributeValue {
+    fn from(value: &str) -> Self {
+        Self::Text(value.into())
+    }
+}
+
+impl From<&[u8]> for AttributeValue {
+    fn from(value: &[u8]) -> Self {
+        Self::Bytes(value.into())
+    }
+}
+
+impl<'a> FromAttribute<'a> for &'a str {
+    fn from_attribute(value: &'a AttributeValue) -> Option<Self> {
+        match value {
+            AttributeValue::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> FromAttribute<'a> for &'a [u8] {
+    fn from_attribute(value: &'a AttributeValue) -> Option<Self> {
+        match value {
+            AttributeValue::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+}
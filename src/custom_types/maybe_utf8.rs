@@ -0,0 +1,164 @@
+//! `string` field that keeps the raw payload when it is not valid UTF-8.
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+
+use bytes::Buf;
+use bytes::Bytes;
+
+use crate::DecodeError;
+use crate::encoding::DecodeContext;
+use crate::encoding::WireType;
+use crate::encoding::skip_field;
+use crate::traits::ArchivedProtoField;
+use crate::traits::ProtoArchive;
+use crate::traits::ProtoDecode;
+use crate::traits::ProtoDecoder;
+use crate::traits::ProtoDefault;
+use crate::traits::ProtoEncode;
+use crate::traits::ProtoExt;
+use crate::traits::ProtoKind;
+use crate::traits::ProtoShadowDecode;
+use crate::traits::ProtoShadowEncode;
+use crate::traits::buffer::RevWriter;
+
+/// A protobuf `string` that does not reject invalid UTF-8.
+///
+/// Valid payloads decode into [`MaybeUtf8::Utf8`]; anything else is kept verbatim in
+/// [`MaybeUtf8::Bytes`] so the surrounding message still decodes. Both variants encode back
+/// to the exact bytes they hold.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MaybeUtf8 {
+    Utf8(String),
+    Bytes(Bytes),
+}
+
+impl MaybeUtf8 {
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Utf8(value) => value.as_bytes(),
+            Self::Bytes(value) => value,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Utf8(value) => Some(value),
+            Self::Bytes(_) => None,
+        }
+    }
+
+    pub const fn is_utf8(&self) -> bool {
+        matches!(self, Self::Utf8(_))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.as_bytes().is_empty()
+    }
+
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        match self {
+            Self::Utf8(value) => Cow::Borrowed(value),
+            Self::Bytes(value) => String::from_utf8_lossy(value),
+        }
+    }
+
+    fn from_raw(raw: Bytes) -> Self {
+        match core::str::from_utf8(&raw) {
+            Ok(valid) => Self::Utf8(String::from(valid)),
+            Err(_) => Self::Bytes(raw),
+        }
+    }
+}
+
+impl Default for MaybeUtf8 {
+    fn default() -> Self {
+        Self::Utf8(String::new())
+    }
+}
+
+impl From<String> for MaybeUtf8 {
+    fn from(value: String) -> Self {
+        Self::Utf8(value)
+    }
+}
+
+impl From<&str> for MaybeUtf8 {
+    fn from(value: &str) -> Self {
+        Self::Utf8(String::from(value))
+    }
+}
+
+impl ProtoExt for MaybeUtf8 {
+    const KIND: ProtoKind = ProtoKind::String;
+}
+
+impl ProtoDecoder for MaybeUtf8 {
+    #[inline]
+    fn merge_field(value: &mut Self, tag: u32, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        if tag == 1 {
+            value.merge(wire_type, buf, ctx)
+        } else {
+            skip_field(wire_type, tag, buf, ctx)
+        }
+    }
+
+    #[inline]
+    fn merge(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        let mut raw = Bytes::new();
+        crate::encoding::bytes::merge(wire_type, &mut raw, buf, ctx)?;
+        *self = Self::from_raw(raw);
+        Ok(())
+    }
+}
+
+impl ProtoDefault for MaybeUtf8 {
+    #[inline]
+    fn proto_default() -> Self {
+        Self::default()
+    }
+}
+
+impl ProtoShadowDecode<MaybeUtf8> for MaybeUtf8 {
+    #[inline]
+    fn to_sun(self) -> Result<MaybeUtf8, DecodeError> {
+        Ok(self)
+    }
+}
+
+impl ProtoDecode for MaybeUtf8 {
+    type ShadowDecoded = Self;
+}
+
+impl<'a> ProtoShadowEncode<'a, MaybeUtf8> for &'a MaybeUtf8 {
+    #[inline]
+    fn from_sun(value: &'a MaybeUtf8) -> Self {
+        value
+    }
+}
+
+impl ProtoArchive for &MaybeUtf8 {
+    #[inline]
+    fn is_default(&self) -> bool {
+        (*self).is_empty()
+    }
+
+    #[inline]
+    fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
+        let bytes = self.as_bytes();
+        w.put_slice(bytes);
+        if TAG != 0 {
+            w.put_varint(bytes.len() as u64);
+            ArchivedProtoField::<TAG, Self>::put_key(w);
+        }
+    }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        Some(ArchivedProtoField::<TAG, Self>::hint_delimited(self.as_bytes().len()))
+    }
+}
+
+impl ProtoEncode for MaybeUtf8 {
+    type Shadow<'a> = &'a MaybeUtf8;
+}
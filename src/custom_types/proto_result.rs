@@ -0,0 +1,48 @@
+//! Message form of `Result`, so application errors can travel in the payload.
+
+use prosto_derive::proto_message;
+
+/// A `oneof` holding either a successful value or an application-level error.
+///
+/// Services can return `Response<ProtoResult<T, E>>` instead of mapping every domain error onto a
+/// gRPC status code. Each concrete `ProtoResult<T, E>` is emitted as its own message with an
+/// `ok`/`err` oneof. Fields of type `Result<T, E>` can use `#[proto(result)]` to be encoded the
+/// same way without changing their Rust type.
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProtoResult<T, E> {
+    Ok(T),
+    Err(E),
+}
+
+impl<T, E> ProtoResult<T, E> {
+    pub const fn is_ok(&self) -> bool {
+        matches!(self, Self::Ok(_))
+    }
+
+    pub const fn is_err(&self) -> bool {
+        matches!(self, Self::Err(_))
+    }
+
+    pub fn into_result(self) -> Result<T, E> {
+        self.into()
+    }
+}
+
+impl<T, E> From<Result<T, E>> for ProtoResult<T, E> {
+    fn from(result: Result<T, E>) -> Self {
+        match result {
+            Ok(value) => Self::Ok(value),
+            Err(error) => Self::Err(error),
+        }
+    }
+}
+
+impl<T, E> From<ProtoResult<T, E>> for Result<T, E> {
+    fn from(result: ProtoResult<T, E>) -> Self {
+        match result {
+            ProtoResult::Ok(value) => Ok(value),
+            ProtoResult::Err(error) => Err(error),
+        }
+    }
+}
@@ -1,5 +1,13 @@
 use prosto_derive::proto_message;
 
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::Name;
+use crate::ProtoDecode;
+use crate::ProtoDefault;
+use crate::ProtoEncode;
+use crate::ProtoExt;
+
 #[proto_message(proto_path = "protos/well_known.proto")]
 pub struct Timestamp {
     /// Represents seconds of UTC time since Unix epoch
@@ -12,3 +20,43 @@ pub struct Timestamp {
     /// inclusive.
     pub nanos: i32,
 }
+
+/// An encoded message together with a URL naming its type, wire-compatible with
+/// `google.protobuf.Any`.
+#[proto_message(proto_path = "protos/well_known.proto")]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Any {
+    /// Identifies the packed message, e.g. `/shop.Order`. Only the part after the last `/` is
+    /// compared when unpacking.
+    pub type_url: String,
+    /// The encoded message.
+    pub value: Vec<u8>,
+}
+
+impl Any {
+    /// Packs `message` under its [`Name::type_url`].
+    pub fn pack<M: ProtoEncode + ProtoExt + Name>(message: &M) -> Self {
+        Self {
+            type_url: M::type_url(),
+            value: message.encode_to_vec(),
+        }
+    }
+
+    /// Whether the packed message is an `M`.
+    pub fn is<M: Name>(&self) -> bool {
+        let name = self.type_url.rsplit_once('/').map_or(self.type_url.as_str(), |(_, name)| name);
+        name == M::full_name()
+    }
+
+    /// Decodes the packed message, or returns `Ok(None)` if it is not an `M`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the type matches but the payload does not decode.
+    pub fn unpack<M: ProtoDecode + ProtoDefault + Name>(&self) -> Result<Option<M>, DecodeError> {
+        if !self.is::<M>() {
+            return Ok(None);
+        }
+        M::decode(self.value.as_slice(), DecodeContext::default()).map(Some)
+    }
+}
@@ -0,0 +1,148 @@
+//! Field-level diffs between two values of a message type.
+//!
+//! [`ProtoDiff::diff`] compares the encodings of two values field by field and records, for every
+//! tag whose encoding differs, the field as the second value encodes it. [`ProtoDiff::apply`]
+//! replaces those fields in a value, so `a.apply(&a.diff(&b))` turns `a` into `b`. A field is the
+//! unit of change: a nested message, repeated field or map that differs in one element is recorded
+//! whole. The diff is itself a message, so it can be stored in an audit log or sent over the wire.
+//!
+//! ```rust,ignore
+//! use proto_rs::diff::ProtoDiff;
+//!
+//! let diff = before.diff(&after);
+//! audit_log.write(&diff)?;
+//! for tag in diff.changed_tags() { /* ... */ }
+//!
+//! let mut replayed = before.clone();
+//! replayed.apply(&diff)?;
+//! assert_eq!(replayed, after);
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use bytes::Buf;
+use prosto_derive::proto_message;
+
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::ProtoDecode;
+use crate::ProtoEncode;
+use crate::ProtoExt;
+use crate::encoding::decode_key;
+use crate::encoding::skip_field;
+
+/// The fields that differ between two values of a message, as the newer value encodes them.
+#[proto_message(proto_path = "protos/diff.proto")]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MessageDiff {
+    /// One entry per changed tag, in tag order.
+    pub changes: Vec<FieldChange>,
+}
+
+/// One changed field of a [`MessageDiff`].
+#[proto_message(proto_path = "protos/diff.proto")]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FieldChange {
+    pub tag: u32,
+    /// Every occurrence of the field in the newer value, keys included. Empty when the newer value
+    /// does not write the field, i.e. it was cleared to its default.
+    pub encoded: Vec<u8>,
+}
+
+impl MessageDiff {
+    /// Whether the two values encode the same.
+    pub const fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Tags of the changed fields, in ascending order.
+    pub fn changed_tags(&self) -> impl Iterator<Item = u32> + '_ {
+        self.changes.iter().map(|change| change.tag)
+    }
+
+    /// The change to field `tag`, if it changed.
+    pub fn change(&self, tag: u32) -> Option<&FieldChange> {
+        self.changes.iter().find(|change| change.tag == tag)
+    }
+}
+
+impl FieldChange {
+    /// Whether the newer value leaves the field at its default.
+    pub const fn is_cleared(&self) -> bool {
+        self.encoded.is_empty()
+    }
+}
+
+/// Field-level diff and patch, implemented for every message type.
+pub trait ProtoDiff: Sized {
+    /// The fields of `other` that differ from `self`.
+    fn diff(&self, other: &Self) -> MessageDiff;
+
+    /// Replaces the fields recorded in `diff`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DecodeError`] if a change holds anything but occurrences of its own tag, or if
+    /// the patched encoding does not decode; `self` is left unchanged then.
+    fn apply(&mut self, diff: &MessageDiff) -> Result<(), DecodeError>;
+}
+
+impl<T> ProtoDiff for T
+where
+    T: ProtoEncode + ProtoDecode + ProtoExt,
+{
+    fn diff(&self, other: &Self) -> MessageDiff {
+        let old = self.encode_to_vec();
+        let new = other.encode_to_vec();
+        // Both are our own encodings, so they always split into fields
+        let old = fields_by_tag(&old).unwrap_or_default();
+        let mut new = fields_by_tag(&new).unwrap_or_default();
+
+        let mut changes: Vec<FieldChange> = old
+            .into_iter()
+            .filter_map(|(tag, encoded)| {
+                let newer = new.remove(&tag).unwrap_or_default();
+                (newer != encoded).then_some(FieldChange { tag, encoded: newer })
+            })
+            .collect();
+        changes.extend(new.into_iter().map(|(tag, encoded)| FieldChange { tag, encoded }));
+        changes.sort_by_key(|change| change.tag);
+        MessageDiff { changes }
+    }
+
+    fn apply(&mut self, diff: &MessageDiff) -> Result<(), DecodeError> {
+        if diff.is_empty() {
+            return Ok(());
+        }
+        let mut fields = fields_by_tag(&self.encode_to_vec())?;
+        for change in &diff.changes {
+            let written = fields_by_tag(&change.encoded)?;
+            if written.keys().any(|tag| *tag != change.tag) {
+                return Err(DecodeError::new(format!("change to field {} writes other fields", change.tag)));
+            }
+            if change.is_cleared() {
+                fields.remove(&change.tag);
+            } else {
+                fields.insert(change.tag, change.encoded.clone());
+            }
+        }
+
+        let patched: Vec<u8> = fields.into_values().flatten().collect();
+        *self = <T as ProtoDecode>::decode(patched.as_slice(), DecodeContext::default())?;
+        Ok(())
+    }
+}
+
+/// Splits an encoded message into the encoded occurrences of each tag, keys included.
+fn fields_by_tag(bytes: &[u8]) -> Result<BTreeMap<u32, Vec<u8>>, DecodeError> {
+    let mut fields: BTreeMap<u32, Vec<u8>> = BTreeMap::new();
+    let mut buf = bytes;
+    while buf.has_remaining() {
+        let field = buf;
+        let (tag, wire_type) = decode_key(&mut buf)?;
+        skip_field(wire_type, tag, &mut buf, DecodeContext::default())?;
+        fields.entry(tag).or_default().extend_from_slice(&field[..field.len() - buf.len()]);
+    }
+    Ok(fields)
+}
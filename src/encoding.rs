@@ -6,6 +6,11 @@
 use alloc::collections::BTreeMap;
 use alloc::format;
 use alloc::vec::Vec;
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
 
 use ::bytes::Buf;
 use ::bytes::BufMut;
@@ -22,15 +27,19 @@ pub use varint::encode_varint;
 pub use varint::encoded_len_varint;
 
 pub mod length_delimiter;
+pub use length_delimiter::MAX_HEADER_LEN;
 pub use length_delimiter::decode_length_delimiter;
 pub use length_delimiter::encode_length_delimiter;
 pub use length_delimiter::length_delimiter_len;
+pub use length_delimiter::peek_len;
 
 pub mod wire_type;
 pub use wire_type::WireType;
 pub use wire_type::check_wire_type;
 
+use crate::error::DecodeAbort;
 use crate::error::DecodeError;
+use crate::intern::StringInterner;
 
 pub const MIN_TAG: u32 = 1;
 pub const MAX_TAG: u32 = (1 << 29) - 1;
@@ -39,9 +48,9 @@ pub const MAX_TAG: u32 = (1 << 29) - 1;
 ///
 /// The context should be passed by value and can be freely cloned. When passing
 /// to a function which is decoding a nested object, then use `enter_recursion`.
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Copy)]
 #[cfg_attr(feature = "no-recursion-limit", derive(Default))]
-pub struct DecodeContext {
+pub struct DecodeContext<'a> {
     /// How many times we can recurse in the current decode stack before we hit
     /// the recursion limit.
     ///
@@ -50,19 +59,108 @@ pub struct DecodeContext {
     /// crate with the `no-recursion-limit` feature.
     #[cfg(not(feature = "no-recursion-limit"))]
     recurse_count: u32,
+    /// Where `Arc<str>` fields get their strings, see [`DecodeContext::with_interner`].
+    interner: Option<&'static dyn StringInterner>,
+    /// When the decode gives up, see [`DecodeContext::with_deadline`].
+    deadline: Option<Instant>,
+    /// Token aborting the decode, see [`DecodeContext::with_cancel`].
+    cancel: Option<&'a dyn DecodeCancel>,
+    /// Bound on the memory the decode allocates, see [`DecodeContext::with_budget`].
+    budget: Option<&'a MemoryBudget>,
+}
+
+/// Cooperative cancellation of a decode in progress, set with
+/// [`DecodeContext::with_cancel`].
+///
+/// Polled at every message boundary, so it should be a cheap load.
+pub trait DecodeCancel: Send + Sync {
+    fn is_cancelled(&self) -> bool;
+}
+
+impl DecodeCancel for AtomicBool {
+    #[inline]
+    fn is_cancelled(&self) -> bool {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+/// Bytes a decode may allocate, set with [`DecodeContext::with_budget`].
+///
+/// Frame size limits do not bound decoded memory: a few bytes per element of a repeated message
+/// field become `size_of::<T>()` bytes each once decoded. The budget is charged with the length
+/// of every `string` and `bytes` value and the in-memory size of every collection element and
+/// map entry, and the decode fails with [`DecodeError::budget_exceeded`] once the total passes
+/// the limit.
+///
+/// Charges accumulate across every decode sharing the budget until [`reset`](Self::reset).
+#[derive(Debug)]
+pub struct MemoryBudget {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+impl MemoryBudget {
+    pub const fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    /// Adds `bytes` to the total, failing once it is over the limit.
+    #[inline]
+    pub fn charge(&self, bytes: usize) -> Result<(), DecodeError> {
+        let used = self.used.fetch_add(bytes, Ordering::Relaxed).saturating_add(bytes);
+        if used > self.limit {
+            Err(DecodeError::budget_exceeded(self.limit))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Bytes charged since the budget was created or reset.
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    pub const fn limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn reset(&self) {
+        self.used.store(0, Ordering::Relaxed);
+    }
 }
 
 #[cfg(not(feature = "no-recursion-limit"))]
-impl Default for DecodeContext {
+impl Default for DecodeContext<'_> {
     #[inline]
-    fn default() -> DecodeContext {
+    fn default() -> Self {
         DecodeContext {
             recurse_count: crate::RECURSION_LIMIT,
+            interner: None,
+            deadline: None,
+            cancel: None,
+            budget: None,
         }
     }
 }
 
-impl DecodeContext {
+impl core::fmt::Debug for DecodeContext<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut debug = f.debug_struct("DecodeContext");
+        #[cfg(not(feature = "no-recursion-limit"))]
+        debug.field("recurse_count", &self.recurse_count);
+        debug
+            .field("interner", &self.interner.is_some())
+            .field("deadline", &self.deadline)
+            .field("cancel", &self.cancel.is_some())
+            .field("budget", &self.budget.map(MemoryBudget::limit))
+            .finish()
+    }
+}
+
+impl<'a> DecodeContext<'a> {
     /// Call this function before recursively decoding.
     ///
     /// There is no `exit` function since this function creates a new `DecodeContext`
@@ -71,9 +169,13 @@ impl DecodeContext {
     #[cfg(not(feature = "no-recursion-limit"))]
     #[inline]
     #[must_use]
-    pub const fn enter_recursion(&self) -> DecodeContext {
+    pub const fn enter_recursion(&self) -> DecodeContext<'a> {
         DecodeContext {
             recurse_count: self.recurse_count - 1,
+            interner: self.interner,
+            deadline: self.deadline,
+            cancel: self.cancel,
+            budget: self.budget,
         }
     }
 
@@ -81,12 +183,104 @@ impl DecodeContext {
     #[cfg(feature = "no-recursion-limit")]
     #[inline]
     #[must_use]
-    pub const fn enter_recursion(&self) -> DecodeContext {
-        DecodeContext {}
+    pub const fn enter_recursion(&self) -> DecodeContext<'a> {
+        *self
+    }
+
+    /// Decodes `Arc<str>` fields through `interner`, so equal strings share one allocation.
+    #[inline]
+    #[must_use]
+    pub const fn with_interner(mut self, interner: &'static dyn StringInterner) -> DecodeContext<'a> {
+        self.interner = Some(interner);
+        self
+    }
+
+    /// The interner set with [`DecodeContext::with_interner`].
+    #[inline]
+    pub const fn interner(&self) -> Option<&'static dyn StringInterner> {
+        self.interner
+    }
+
+    /// Aborts the decode with [`DecodeAbort::DeadlineExceeded`] once `deadline` has passed.
+    ///
+    /// The clock is read at message boundaries (every nested message and every element of a
+    /// repeated message field), not inside a single string or packed field.
+    #[inline]
+    #[must_use]
+    pub const fn with_deadline(mut self, deadline: Instant) -> DecodeContext<'a> {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// [`with_deadline`](Self::with_deadline) `timeout` from now.
+    #[inline]
+    #[must_use]
+    pub fn with_timeout(self, timeout: Duration) -> DecodeContext<'a> {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// The deadline set with [`DecodeContext::with_deadline`].
+    #[inline]
+    pub const fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Aborts the decode with [`DecodeAbort::Cancelled`] once `cancel` reports cancellation,
+    /// checked at the same boundaries as the deadline.
+    ///
+    /// The token is borrowed for as long as the context is in use, so one owned by a request or
+    /// connection works as well as a `static`.
+    #[inline]
+    #[must_use]
+    pub const fn with_cancel(mut self, cancel: &'a dyn DecodeCancel) -> DecodeContext<'a> {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Charges the decoded values against `budget`, see [`MemoryBudget`].
+    ///
+    /// Like the cancel token, the budget is borrowed for as long as the context is in use.
+    #[inline]
+    #[must_use]
+    pub const fn with_budget(mut self, budget: &'a MemoryBudget) -> DecodeContext<'a> {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// The budget set with [`DecodeContext::with_budget`].
+    #[inline]
+    pub const fn budget(&self) -> Option<&'a MemoryBudget> {
+        self.budget
+    }
+
+    /// Charges `bytes` to the budget, if one is set.
+    #[inline]
+    pub fn charge(&self, bytes: usize) -> Result<(), DecodeError> {
+        match self.budget {
+            Some(budget) => budget.charge(bytes),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails when the deadline has passed or the cancellation token fired.
+    #[inline]
+    pub fn check_interrupted(&self) -> Result<(), DecodeError> {
+        if let Some(cancel) = self.cancel
+            && cancel.is_cancelled()
+        {
+            return Err(DecodeError::aborted(DecodeAbort::Cancelled));
+        }
+        if let Some(deadline) = self.deadline
+            && Instant::now() >= deadline
+        {
+            return Err(DecodeError::aborted(DecodeAbort::DeadlineExceeded));
+        }
+        Ok(())
     }
 
     /// Checks whether the recursion limit has been reached in the stack of
-    /// decodes described by the `DecodeContext` at `self.ctx`.
+    /// decodes described by the `DecodeContext` at `self.ctx`, and whether the
+    /// decode was interrupted (see [`DecodeContext::check_interrupted`]).
     ///
     /// Returns `Ok<()>` if it is ok to continue recursing.
     /// Returns `Err<DecodeError>` if the recursion limit has been reached.
@@ -96,15 +290,13 @@ impl DecodeContext {
         if self.recurse_count == 0 {
             Err(DecodeError::new("recursion limit reached"))
         } else {
-            Ok(())
+            self.check_interrupted()
         }
     }
-    #[allow(clippy::trivially_copy_pass_by_ref)]
-    #[allow(clippy::unnecessary_wraps)]
     #[cfg(feature = "no-recursion-limit")]
     #[inline]
-    pub const fn limit_reached(&self) -> Result<(), DecodeError> {
-        Ok(())
+    pub fn limit_reached(&self) -> Result<(), DecodeError> {
+        self.check_interrupted()
     }
 }
 
@@ -356,6 +548,15 @@ mod test {
         assert!(s.is_empty());
     }
 
+    #[test]
+    fn string_merge_lossy_replaces_invalid_utf8() {
+        let mut s = String::from("stale");
+        let buf = b"\x04a\x80b\xff";
+
+        string::merge_lossy(WireType::LengthDelimited, &mut s, &mut &buf[..], DecodeContext::default()).expect("lossy merge");
+        assert_eq!(s, "a\u{FFFD}b\u{FFFD}");
+    }
+
     /// This big bowl o' macro soup generates an encoding property test for each combination of map
     /// type, scalar map key, and value type.
     /// TODO: these tests take a long time to compile, can this be improved?
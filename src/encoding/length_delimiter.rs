@@ -9,6 +9,11 @@ pub use crate::error::EncodeError;
 pub use crate::error::UnknownEnumValue;
 pub use crate::name::Name;
 
+/// Largest encoded size of a length delimiter, in bytes.
+///
+/// Reading this many bytes off a stream is always enough to find the length of the next frame.
+pub const MAX_HEADER_LEN: usize = 10;
+
 /// Encodes a length delimiter to the buffer.
 ///
 /// See [Message.encode_length_delimited] for more info.
@@ -52,3 +57,16 @@ pub fn decode_length_delimiter(mut buf: impl Buf) -> Result<usize, DecodeError>
     }
     Ok(length as usize)
 }
+
+/// Reads the length delimiter at the start of `buf` without consuming it.
+///
+/// Returns the message length and the size of the delimiter itself, so a reader knows exactly how
+/// many more bytes make up the frame. `None` means the delimiter is incomplete; if `buf` already
+/// holds [`MAX_HEADER_LEN`] bytes or more, the delimiter is invalid and the stream should be
+/// considered corrupt.
+pub fn peek_len(buf: &[u8]) -> Option<(usize, usize)> {
+    let header = buf.iter().take(MAX_HEADER_LEN).position(|byte| byte & 0x80 == 0)? + 1;
+    let mut delimiter = &buf[..header];
+    let length = decode_varint(&mut delimiter).ok()?;
+    Some((usize::try_from(length).ok()?, header))
+}
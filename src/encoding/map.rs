@@ -148,6 +148,7 @@ macro_rules! map {
             if buf.remaining() != limit {
                 return Err(DecodeError::new("delimited length exceeded"));
             }
+            ctx.charge(size_of::<(K, V)>())?;
             values.insert(key, val);
             Ok(())
         }
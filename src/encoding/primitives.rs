@@ -274,14 +274,14 @@ fixed_width!(i64, 8, WireType::SixtyFourBit, sfixed64, put_i64_le, get_i64_le);
 macro_rules! length_delimited_encode {
     ($ty:ty) => {
         encode_repeated!($ty, by_ref);
-        #[allow(clippy::ptr_arg)]
+        #[allow(clippy::ptr_arg, clippy::missing_const_for_fn)]
         #[inline]
         pub fn encoded_len_tagged(tag: u32, value: &$ty) -> usize {
             let len = value.len();
             key_len(tag) + encoded_len_varint(len as u64) + len
         }
 
-        #[allow(clippy::ptr_arg)]
+        #[allow(clippy::ptr_arg, clippy::missing_const_for_fn)]
         #[inline]
         pub fn encoded_len(value: &$ty) -> usize {
             let len = value.len();
@@ -386,6 +386,18 @@ pub mod string {
         }
     }
 
+    /// Like [`merge`], but invalid UTF-8 sequences are replaced with `U+FFFD` instead of failing.
+    #[inline]
+    pub fn merge_lossy(wire_type: WireType, value: &mut String, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        let mut raw = Vec::new();
+        bytes::merge_one_copy(wire_type, &mut raw, buf, ctx)?;
+        *value = match String::from_utf8(raw) {
+            Ok(valid) => valid,
+            Err(err) => String::from_utf8_lossy(err.as_bytes()).into_owned(),
+        };
+        Ok(())
+    }
+
     length_delimited_encode!(String);
     length_delimited_decode!(String);
 
@@ -450,7 +462,7 @@ pub mod bytes {
         wire_type: WireType,
         value: &mut impl BytesAdapterDecode,
         buf: &mut impl Buf,
-        _ctx: DecodeContext,
+        ctx: DecodeContext,
     ) -> Result<(), DecodeError> {
         check_wire_type(WireType::LengthDelimited, wire_type)?;
         let len = decode_varint(buf)?;
@@ -458,6 +470,7 @@ pub mod bytes {
             return Err(DecodeError::new("buffer underflow"));
         }
         let len = len as usize;
+        ctx.charge(len)?;
 
         // Clear the existing value. This follows from the following rule in the encoding guide[1]:
         //
@@ -479,7 +492,7 @@ pub mod bytes {
         wire_type: WireType,
         value: &mut impl BytesAdapterDecode,
         buf: &mut impl Buf,
-        _ctx: DecodeContext,
+        ctx: DecodeContext,
     ) -> Result<(), DecodeError> {
         check_wire_type(WireType::LengthDelimited, wire_type)?;
         let len = decode_varint(buf)?;
@@ -487,6 +500,7 @@ pub mod bytes {
             return Err(DecodeError::new("buffer underflow"));
         }
         let len = len as usize;
+        ctx.charge(len)?;
 
         // If we must copy, make sure to copy only once.
         value.replace_with(buf.take(len));
@@ -1,6 +1,8 @@
 //! Protobuf encoding and decoding errors.
 
 use alloc::borrow::Cow;
+#[cfg(feature = "tonic")]
+use alloc::string::ToString;
 use alloc::vec::Vec;
 use core::fmt;
 
@@ -17,6 +19,24 @@ pub struct DecodeError {
     /// message type and field where decoding failed. The stack contains an
     /// entry per level of nesting.
     stack: Vec<(&'static str, &'static str)>,
+    /// Enum name and raw value of an unknown enum value not yet passed to the
+    /// [`unknown_enum`](crate::unknown_enum) hooks.
+    unknown_enum: Option<(&'static str, i64)>,
+    /// Offset of the top-level field where decoding failed, when the caller tracked it.
+    offset: Option<usize>,
+    /// Why the decode stopped before the data ran out, if it did.
+    abort: Option<DecodeAbort>,
+}
+
+/// A decode stopped by its [`DecodeContext`](crate::DecodeContext) rather than by bad data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeAbort {
+    /// The deadline set with [`with_deadline`](crate::DecodeContext::with_deadline) passed.
+    DeadlineExceeded,
+    /// The token set with [`with_cancel`](crate::DecodeContext::with_cancel) fired.
+    Cancelled,
+    /// The values decoded so far used up the [`MemoryBudget`](crate::MemoryBudget).
+    BudgetExceeded,
 }
 
 impl DecodeError {
@@ -29,9 +49,97 @@ impl DecodeError {
         DecodeError {
             description: description.into(),
             stack: Vec::new(),
+            unknown_enum: None,
+            offset: None,
+            abort: None,
+        }
+    }
+
+    /// Creates the error for a decode stopped by its context.
+    #[cold]
+    pub fn aborted(abort: DecodeAbort) -> DecodeError {
+        let description = match abort {
+            DecodeAbort::DeadlineExceeded => "decode deadline exceeded",
+            DecodeAbort::Cancelled => "decode cancelled",
+            DecodeAbort::BudgetExceeded => "memory budget exceeded",
+        };
+        DecodeError {
+            abort: Some(abort),
+            ..DecodeError::new(description)
+        }
+    }
+
+    /// Creates the error for a decode that allocated more than the `limit` of its
+    /// [`MemoryBudget`](crate::MemoryBudget).
+    #[cold]
+    pub fn budget_exceeded(limit: usize) -> DecodeError {
+        DecodeError {
+            description: format!("memory budget of {limit} bytes exceeded").into(),
+            ..DecodeError::aborted(DecodeAbort::BudgetExceeded)
         }
     }
 
+    /// Why the decode was stopped, when the data itself was not at fault.
+    pub const fn abort(&self) -> Option<DecodeAbort> {
+        self.abort
+    }
+
+    /// Creates the error for `value`, which `enum_name` has no variant for.
+    ///
+    /// The value is reported to the [`unknown_enum`](crate::unknown_enum) hooks once the error
+    /// reaches the message field holding the enum.
+    #[doc(hidden)]
+    #[cold]
+    pub fn unknown_enum_value(enum_name: &'static str, value: i64) -> DecodeError {
+        DecodeError {
+            unknown_enum: Some((enum_name, value)),
+            ..DecodeError::new("invalid enum value")
+        }
+    }
+
+    /// Reports a pending unknown enum value as decoded into `message.field`.
+    ///
+    /// Meant to be used only by `Message` implementations.
+    #[doc(hidden)]
+    #[cold]
+    #[must_use]
+    pub fn report_unknown_enum(mut self, message: &'static str, field: &'static str) -> DecodeError {
+        self.report_pending_enum(Some(message), Some(field));
+        self
+    }
+
+    /// Reports a pending unknown enum value that no message field claimed.
+    #[cold]
+    pub(crate) fn report_unplaced_unknown_enum(mut self) -> DecodeError {
+        self.report_pending_enum(None, None);
+        self
+    }
+
+    fn report_pending_enum(&mut self, message: Option<&'static str>, field: Option<&'static str>) {
+        if let Some((enum_name, value)) = self.unknown_enum.take() {
+            crate::unknown_enum::report(&crate::unknown_enum::UnknownEnum {
+                enum_name,
+                message,
+                field,
+                value,
+            });
+        }
+    }
+
+    /// Records the byte offset of the top-level field that failed, keeping one already recorded.
+    #[cold]
+    #[must_use]
+    pub(crate) fn at_offset(mut self, offset: usize) -> DecodeError {
+        self.offset.get_or_insert(offset);
+        self
+    }
+
+    /// Byte offset of the top-level field where decoding failed, set by
+    /// [`ProtoDecode::decode_to_eof`](crate::ProtoDecode::decode_to_eof).
+    pub const fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
     /// Pushes a (message, field) name location pair on to the location stack.
     ///
     /// Meant to be used only by `Message` implementations.
@@ -43,13 +151,22 @@ impl DecodeError {
 
 impl fmt::Debug for DecodeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("DecodeError").field("description", &self.description).field("stack", &self.stack).finish()
+        f.debug_struct("DecodeError")
+            .field("description", &self.description)
+            .field("stack", &self.stack)
+            .field("offset", &self.offset)
+            .field("abort", &self.abort)
+            .finish_non_exhaustive()
     }
 }
 
 impl fmt::Display for DecodeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str("failed to decode Protobuf message: ")?;
+        f.write_str("failed to decode Protobuf message")?;
+        if let Some(offset) = self.offset {
+            write!(f, " at byte {offset}")?;
+        }
+        f.write_str(": ")?;
         for &(message, field) in &self.stack {
             write!(f, "{message}.{field}: ")?;
         }
@@ -111,6 +228,91 @@ impl From<EncodeError> for std::io::Error {
     }
 }
 
+/// Codec and validation failures, with one gRPC status code per kind.
+///
+/// Converting into `tonic::Status` keeps codes consistent across the codec, generated servers
+/// and custom transports; [`ProtoError::is_retryable`] tells callers whether sending the same
+/// message again can succeed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProtoError {
+    /// The bytes are not a valid message (`DATA_LOSS`), or the decode was aborted
+    /// (`DEADLINE_EXCEEDED`, `CANCELLED` or `RESOURCE_EXHAUSTED`).
+    Decode(DecodeError),
+    /// The output buffer was too small for the message (`RESOURCE_EXHAUSTED`).
+    Encode(EncodeError),
+    /// The message decoded but a validator rejected it (`INVALID_ARGUMENT`).
+    Validation(DecodeError),
+}
+
+impl ProtoError {
+    /// gRPC status code reported for this error.
+    #[cfg(feature = "tonic")]
+    pub const fn code(&self) -> tonic::Code {
+        match self {
+            ProtoError::Decode(error) => match error.abort {
+                Some(DecodeAbort::DeadlineExceeded) => tonic::Code::DeadlineExceeded,
+                Some(DecodeAbort::Cancelled) => tonic::Code::Cancelled,
+                Some(DecodeAbort::BudgetExceeded) => tonic::Code::ResourceExhausted,
+                None => tonic::Code::DataLoss,
+            },
+            ProtoError::Encode(_) => tonic::Code::ResourceExhausted,
+            ProtoError::Validation(_) => tonic::Code::InvalidArgument,
+        }
+    }
+
+    /// Whether retrying the same message can succeed.
+    ///
+    /// Malformed or invalid messages fail the same way every time; an encode only fails for lack
+    /// of buffer capacity, which a retry with a fresh buffer can resolve, and a decode aborted by
+    /// its deadline or token can complete with more time. A message over the memory budget
+    /// exceeds it again.
+    pub const fn is_retryable(&self) -> bool {
+        match self {
+            ProtoError::Encode(_) => true,
+            ProtoError::Decode(error) => matches!(error.abort, Some(DecodeAbort::DeadlineExceeded | DecodeAbort::Cancelled)),
+            ProtoError::Validation(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for ProtoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtoError::Decode(error) => fmt::Display::fmt(error, f),
+            ProtoError::Encode(error) => fmt::Display::fmt(error, f),
+            ProtoError::Validation(error) => write!(f, "validation failed: {error}"),
+        }
+    }
+}
+
+impl core::error::Error for ProtoError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            ProtoError::Decode(error) | ProtoError::Validation(error) => Some(error),
+            ProtoError::Encode(error) => Some(error),
+        }
+    }
+}
+
+impl From<DecodeError> for ProtoError {
+    fn from(error: DecodeError) -> ProtoError {
+        ProtoError::Decode(error)
+    }
+}
+
+impl From<EncodeError> for ProtoError {
+    fn from(error: EncodeError) -> ProtoError {
+        ProtoError::Encode(error)
+    }
+}
+
+#[cfg(feature = "tonic")]
+impl From<ProtoError> for tonic::Status {
+    fn from(error: ProtoError) -> tonic::Status {
+        tonic::Status::new(error.code(), error.to_string())
+    }
+}
+
 /// An error indicating that an unknown enumeration value was encountered.
 ///
 /// The Protobuf spec mandates that enumeration value sets are ‘open’, so this
@@ -151,4 +353,29 @@ mod test {
         assert_eq!(std_io_error.kind(), std::io::ErrorKind::InvalidData);
         assert_eq!(std_io_error.to_string(), "failed to decode Protobuf message: something failed");
     }
+
+    #[test]
+    fn test_proto_error_retryable() {
+        assert!(!ProtoError::Decode(DecodeError::new("bad")).is_retryable());
+        assert!(!ProtoError::Validation(DecodeError::new("bad")).is_retryable());
+        assert!(ProtoError::Encode(EncodeError::new(8, 4)).is_retryable());
+    }
+
+    #[cfg(feature = "tonic")]
+    #[test]
+    fn test_proto_error_into_status() {
+        let status = tonic::Status::from(ProtoError::Decode(DecodeError::new("truncated")));
+        assert_eq!(status.code(), tonic::Code::DataLoss);
+        assert_eq!(status.message(), "failed to decode Protobuf message: truncated");
+
+        let status = tonic::Status::from(ProtoError::Validation(DecodeError::new("id must be positive")));
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(
+            status.message(),
+            "validation failed: failed to decode Protobuf message: id must be positive"
+        );
+
+        let status = tonic::Status::from(ProtoError::from(EncodeError::new(8, 4)));
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+    }
 }
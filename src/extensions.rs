@@ -0,0 +1,294 @@
+//! Extension ranges and extension fields.
+//!
+//! A message reserves field numbers for other crates with a field of type [`ExtensionSet`]:
+//!
+//! ```rust,ignore
+//! #[proto_message(proto_path = "protos/orders.proto")]
+//! pub struct Order {
+//!     pub id: u64,
+//!     #[proto(extensions = "100..200")]
+//!     pub extensions: ExtensionSet,
+//! }
+//! ```
+//!
+//! Fields with numbers in the range are kept on decode and written back on encode as they came,
+//! whether or not anything knows them, and the schema gets `extensions 100 to 199;`. Another crate
+//! declares a typed field in the range and reads or writes it through the message:
+//!
+//! ```rust,ignore
+//! pub static PRIORITY: Extension<Order, u32, 100> = Extension::new("shipping.priority");
+//!
+//! PRIORITY.set(&mut order, &3);
+//! assert_eq!(PRIORITY.get(&order)?, Some(3));
+//! ```
+//!
+//! Registering the field with [`register`] lets tools list the extensions known for a message.
+
+use alloc::collections::BTreeMap;
+use core::any::TypeId;
+use core::fmt;
+use core::marker::PhantomData;
+use std::sync::RwLock;
+
+use bytes::Buf;
+
+use crate::ArchivedProtoField;
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::ProtoArchive;
+use crate::ProtoDefault;
+use crate::ProtoEncode;
+use crate::ProtoExt;
+use crate::ProtoFieldMerge;
+use crate::ProtoShadowEncode;
+use crate::RevVec;
+use crate::RevWriter;
+use crate::alloc::vec::Vec;
+use crate::encoding::WireType;
+use crate::encoding::decode_key;
+use crate::encoding::decode_varint;
+use crate::encoding::encode_key;
+use crate::encoding::encode_varint;
+
+/// A message with extension ranges, implemented by `#[proto_message]` for structs with an
+/// `#[proto(extensions = "...")]` field.
+pub trait Extendable {
+    /// Inclusive ranges of the field numbers reserved for extensions.
+    const EXTENSION_RANGES: &'static [(u32, u32)];
+
+    fn extensions(&self) -> &ExtensionSet;
+    fn extensions_mut(&mut self) -> &mut ExtensionSet;
+}
+
+/// Fields of a message in its extension ranges, kept as encoded.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ExtensionSet {
+    // Every occurrence of a field number, keys included, in the order they were read or set.
+    fields: BTreeMap<u32, Vec<u8>>,
+}
+
+impl ExtensionSet {
+    pub const fn new() -> Self {
+        Self { fields: BTreeMap::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Field numbers present, in ascending order.
+    pub fn tags(&self) -> impl Iterator<Item = u32> + '_ {
+        self.fields.keys().copied()
+    }
+
+    pub fn contains(&self, tag: u32) -> bool {
+        self.fields.contains_key(&tag)
+    }
+
+    /// Encoded occurrences of field `tag`, keys included.
+    pub fn raw(&self, tag: u32) -> Option<&[u8]> {
+        self.fields.get(&tag).map(Vec::as_slice)
+    }
+
+    pub fn remove(&mut self, tag: u32) {
+        self.fields.remove(&tag);
+    }
+
+    pub fn clear(&mut self) {
+        self.fields.clear();
+    }
+
+    /// Bytes the set adds to the message encoding.
+    pub fn encoded_len(&self) -> usize {
+        self.fields.values().map(Vec::len).sum()
+    }
+
+    /// Keeps one occurrence of field `tag`, whose key was just read. Used by the decode of
+    /// messages with extension ranges.
+    pub fn merge_field(&mut self, tag: u32, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        ctx.limit_reached()?;
+        let mut record = Vec::new();
+        encode_key(tag, wire_type, &mut record);
+        match wire_type {
+            WireType::Varint => encode_varint(decode_varint(buf)?, &mut record),
+            WireType::ThirtyTwoBit => copy_payload(4, buf, &mut record)?,
+            WireType::SixtyFourBit => copy_payload(8, buf, &mut record)?,
+            WireType::LengthDelimited => {
+                let len = decode_varint(buf)?;
+                encode_varint(len, &mut record);
+                copy_payload(len, buf, &mut record)?;
+            }
+            WireType::StartGroup | WireType::EndGroup => {
+                return Err(DecodeError::new("groups are not supported in extension ranges"));
+            }
+        }
+        ctx.charge(record.len())?;
+        self.fields.entry(tag).or_default().extend_from_slice(&record);
+        Ok(())
+    }
+
+    /// Writes the fields in ascending order behind whatever the writer holds.
+    pub fn archive(&self, w: &mut impl RevWriter) {
+        for record in self.fields.values().rev() {
+            w.put_slice(record);
+        }
+    }
+}
+
+fn copy_payload(len: u64, buf: &mut impl Buf, record: &mut Vec<u8>) -> Result<(), DecodeError> {
+    if len > buf.remaining() as u64 {
+        return Err(DecodeError::new("buffer underflow"));
+    }
+    let mut left = len as usize;
+    while left > 0 {
+        let chunk = buf.chunk();
+        let take = chunk.len().min(left);
+        record.extend_from_slice(&chunk[..take]);
+        buf.advance(take);
+        left -= take;
+    }
+    Ok(())
+}
+
+/// A field of type `T` numbered `TAG` in the extension ranges of `M`.
+pub struct Extension<M, T, const TAG: u32> {
+    name: &'static str,
+    _types: PhantomData<fn() -> (M, T)>,
+}
+
+impl<M: Extendable, T, const TAG: u32> Extension<M, T, TAG> {
+    /// Declares the field; `name` is its full proto name such as `"shipping.priority"`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, at compile time when used in a `static`, when `TAG` is outside the extension
+    /// ranges of `M`.
+    pub const fn new(name: &'static str) -> Self {
+        let mut idx = 0;
+        let mut in_range = false;
+        while idx < M::EXTENSION_RANGES.len() {
+            let (start, end) = M::EXTENSION_RANGES[idx];
+            in_range |= start <= TAG && TAG <= end;
+            idx += 1;
+        }
+        assert!(in_range, "extension tag is outside the extension ranges of the message");
+        Self { name, _types: PhantomData }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub const fn tag(&self) -> u32 {
+        TAG
+    }
+
+    pub fn has(&self, message: &M) -> bool {
+        message.extensions().contains(TAG)
+    }
+
+    pub fn clear(&self, message: &mut M) {
+        message.extensions_mut().remove(TAG);
+    }
+}
+
+impl<M: Extendable, T: ProtoFieldMerge + ProtoDefault, const TAG: u32> Extension<M, T, TAG> {
+    /// The field decoded from `message`, or `None` when it is not set. Several occurrences merge
+    /// like they do in a message.
+    pub fn get(&self, message: &M) -> Result<Option<T>, DecodeError> {
+        let Some(mut record) = message.extensions().raw(TAG) else {
+            return Ok(None);
+        };
+        let ctx = DecodeContext::default();
+        let mut value = T::proto_default();
+        while record.has_remaining() {
+            let (_, wire_type) = decode_key(&mut record)?;
+            value.merge_value(wire_type, &mut record, ctx)?;
+        }
+        Ok(Some(value))
+    }
+}
+
+impl<M: Extendable, T: ProtoEncode + ProtoExt, const TAG: u32> Extension<M, T, TAG> {
+    /// Sets the field on `message`, replacing any value it had. Default values are written too,
+    /// so [`has`](Self::has) tells them apart from an unset field.
+    pub fn set(&self, message: &mut M, value: &T) {
+        let shadow = <T::Shadow<'_> as ProtoShadowEncode<'_, T>>::from_sun(value);
+        let mut w = RevVec::with_capacity(ProtoArchive::size_hint::<TAG>(&shadow).unwrap_or(16));
+        ArchivedProtoField::<TAG, T::Shadow<'_>>::new_always(&shadow, &mut w);
+        message.extensions_mut().fields.insert(TAG, w.finish_tight());
+    }
+}
+
+/// A registered extension field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtensionInfo {
+    /// Rust name of the extended message.
+    pub extendee: &'static str,
+    /// Full proto name of the field.
+    pub name: &'static str,
+    pub tag: u32,
+    /// Rust name of the field type.
+    pub value_type: &'static str,
+}
+
+/// Two extensions registered with one number on the same message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtensionConflict {
+    pub registered: ExtensionInfo,
+    pub rejected: ExtensionInfo,
+}
+
+impl fmt::Display for ExtensionConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "extension {} of {} uses number {}, already taken by {}",
+            self.rejected.name, self.rejected.extendee, self.rejected.tag, self.registered.name
+        )
+    }
+}
+
+impl core::error::Error for ExtensionConflict {}
+
+static REGISTRY: RwLock<BTreeMap<(TypeId, u32), ExtensionInfo>> = RwLock::new(BTreeMap::new());
+
+/// Records `extension` for [`lookup`] and [`registered`]. Registering the same field again is a
+/// no-op.
+///
+/// # Errors
+///
+/// Fails when another field already took the number on the same message.
+pub fn register<M: Extendable + 'static, T: 'static, const TAG: u32>(extension: &Extension<M, T, TAG>) -> Result<(), ExtensionConflict> {
+    let info = ExtensionInfo {
+        extendee: core::any::type_name::<M>(),
+        name: extension.name,
+        tag: TAG,
+        value_type: core::any::type_name::<T>(),
+    };
+    let mut registry = REGISTRY.write().unwrap_or_else(std::sync::PoisonError::into_inner);
+    match registry.get(&(TypeId::of::<M>(), TAG)) {
+        Some(registered) if *registered == info => Ok(()),
+        Some(registered) => Err(ExtensionConflict {
+            registered: *registered,
+            rejected: info,
+        }),
+        None => {
+            registry.insert((TypeId::of::<M>(), TAG), info);
+            Ok(())
+        }
+    }
+}
+
+/// The extension registered for number `tag` of `M`.
+pub fn lookup<M: 'static>(tag: u32) -> Option<ExtensionInfo> {
+    let registry = REGISTRY.read().unwrap_or_else(std::sync::PoisonError::into_inner);
+    registry.get(&(TypeId::of::<M>(), tag)).copied()
+}
+
+/// Every extension registered for `M`, by number.
+pub fn registered<M: 'static>() -> Vec<ExtensionInfo> {
+    let registry = REGISTRY.read().unwrap_or_else(std::sync::PoisonError::into_inner);
+    let id = TypeId::of::<M>();
+    registry.range((id, 0)..=(id, u32::MAX)).map(|(_, info)| *info).collect()
+}
@@ -0,0 +1,155 @@
+//! Random access into large repeated message fields.
+//!
+//! A `Vec` field marked `#[proto(offset_index = N)]` is written with a side field `N`, a packed
+//! `fixed64` list holding where each element starts. The side field is written first, so a reader
+//! finds it without walking the message and reads one element in constant time:
+//!
+//! ```rust,ignore
+//! #[proto_message]
+//! pub struct Archive {
+//!     pub id: u64,
+//!     #[proto(offset_index = 15)]
+//!     pub events: Vec<Event>,
+//! }
+//!
+//! let count = Archive::events_len(&bytes)?;
+//! let event: Option<Event> = Archive::events_at(&bytes, count - 1)?;
+//! ```
+//!
+//! Offsets count back from the end of the message payload, which the reverse writer knows before
+//! anything in front of the elements is written. Decoders unaware of the index skip it as an
+//! unknown field, and encodings without one are still read, by scanning the field.
+
+use bytes::Buf;
+
+use crate::ArchivedProtoField;
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::ProtoArchive;
+use crate::ProtoDecode;
+use crate::ProtoExt;
+use crate::RevWriter;
+use crate::alloc::vec::Vec;
+use crate::encoding::WireType;
+use crate::encoding::decode_key;
+use crate::encoding::decode_varint;
+use crate::encoding::encoded_len_varint;
+use crate::encoding::key_len;
+use crate::encoding::skip_field;
+
+/// Writes `elements` under `TAG` like a repeated message field and returns, last element first,
+/// the distance of each element's key from the end of the payload started at `mark`.
+#[doc(hidden)]
+pub fn archive_indexed<const TAG: u32, T: ProtoArchive + ProtoExt, W: RevWriter>(elements: &[T], mark: W::Mark, w: &mut W) -> Vec<u64> {
+    let mut distances = Vec::with_capacity(elements.len());
+    for element in elements.iter().rev() {
+        ArchivedProtoField::<TAG, T>::new_always(element, w);
+        distances.push(w.written_since(mark) as u64);
+    }
+    distances
+}
+
+/// Writes the offsets index of [`archive_indexed`]'s `distances` under `index_tag`.
+#[doc(hidden)]
+pub fn archive_offsets(index_tag: u32, distances: &[u64], w: &mut impl RevWriter) {
+    if distances.is_empty() {
+        return;
+    }
+    for distance in distances {
+        w.put_fixed64(*distance);
+    }
+    w.put_varint((distances.len() * 8) as u64);
+    w.put_varint(u64::from(index_tag << 3 | WireType::LengthDelimited as u32));
+}
+
+/// Upper bound of the bytes [`archive_offsets`] writes for `count` elements.
+#[doc(hidden)]
+pub const fn offsets_size_hint(index_tag: u32, count: usize) -> usize {
+    if count == 0 {
+        0
+    } else {
+        key_len(index_tag) + encoded_len_varint((count * 8) as u64) + count * 8
+    }
+}
+
+/// Element `idx` of the repeated message field `tag` in the encoded message `buf`, or `None` past
+/// the last element.
+///
+/// With an offsets index under `index_tag` only the element itself is decoded; without one the
+/// elements before it are skipped over by length.
+pub fn get_repeated_element<T: ProtoDecode>(buf: &[u8], tag: u32, index_tag: u32, idx: usize) -> Result<Option<T>, DecodeError> {
+    let ctx = DecodeContext::default();
+    let start = match find_index(buf, index_tag)? {
+        Some(index) => {
+            let Some(distance) = idx.checked_mul(8).and_then(|at| index.get(at..at + 8)) else {
+                return Ok(None);
+            };
+            let distance = u64::from_le_bytes(distance.try_into().expect("8 bytes"));
+            let distance = usize::try_from(distance).unwrap_or(usize::MAX);
+            let start = buf.len().checked_sub(distance).ok_or_else(stale_index)?;
+            Some(start)
+        }
+        None => scan_elements(buf, tag)?.get(idx).copied(),
+    };
+    let Some(start) = start else {
+        return Ok(None);
+    };
+
+    let mut element = &buf[start..];
+    let (element_tag, wire_type) = decode_key(&mut element)?;
+    if element_tag != tag || wire_type != WireType::LengthDelimited {
+        return Err(stale_index());
+    }
+    let len = usize::try_from(decode_varint(&mut element)?).unwrap_or(usize::MAX);
+    let payload = element.get(..len).ok_or_else(|| DecodeError::new("buffer underflow"))?;
+    T::decode(payload, ctx).map(Some)
+}
+
+/// Number of elements of the repeated field `tag` in the encoded message `buf`, read from the
+/// offsets index under `index_tag` when there is one.
+pub fn repeated_len(buf: &[u8], tag: u32, index_tag: u32) -> Result<usize, DecodeError> {
+    match find_index(buf, index_tag)? {
+        Some(index) => Ok(index.len() / 8),
+        None => Ok(scan_elements(buf, tag)?.len()),
+    }
+}
+
+/// Payload of the offsets index. The encoder writes it first, so the scan normally stops at the
+/// first field.
+fn find_index(buf: &[u8], index_tag: u32) -> Result<Option<&[u8]>, DecodeError> {
+    let ctx = DecodeContext::default();
+    let mut rest = buf;
+    while rest.has_remaining() {
+        let (tag, wire_type) = decode_key(&mut rest)?;
+        if tag == index_tag && wire_type == WireType::LengthDelimited {
+            let len = usize::try_from(decode_varint(&mut rest)?).unwrap_or(usize::MAX);
+            let index = rest.get(..len).ok_or_else(|| DecodeError::new("buffer underflow"))?;
+            if len % 8 != 0 {
+                return Err(DecodeError::new("offsets index is not a list of fixed64 values"));
+            }
+            return Ok(Some(index));
+        }
+        skip_field(wire_type, tag, &mut rest, ctx)?;
+    }
+    Ok(None)
+}
+
+/// Start of every occurrence of field `tag`, for encodings without an index.
+fn scan_elements(buf: &[u8], tag: u32) -> Result<Vec<usize>, DecodeError> {
+    let ctx = DecodeContext::default();
+    let mut starts = Vec::new();
+    let mut rest = buf;
+    while rest.has_remaining() {
+        let start = buf.len() - rest.len();
+        let (field_tag, wire_type) = decode_key(&mut rest)?;
+        if field_tag == tag {
+            starts.push(start);
+        }
+        skip_field(wire_type, field_tag, &mut rest, ctx)?;
+    }
+    Ok(starts)
+}
+
+fn stale_index() -> DecodeError {
+    DecodeError::new("offsets index does not match the message")
+}
@@ -0,0 +1,205 @@
+//! Shared `Arc<str>` strings for decoded `string` fields.
+//!
+//! A field declared as `Arc<str>` decodes like a `String`. With an interner set on the
+//! [`DecodeContext`](crate::DecodeContext), every occurrence of the same text resolves to one
+//! allocation, which pays off for feeds repeating a small set of symbols or tickers:
+//!
+//! ```rust,ignore
+//! static SYMBOLS: StringPool = StringPool::new();
+//!
+//! let ctx = DecodeContext::default().with_interner(&SYMBOLS);
+//! let trade = Trade::decode(bytes, ctx)?;
+//! ```
+
+use alloc::collections::BTreeSet;
+use alloc::sync::Arc;
+use alloc::vec;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use bytes::Buf;
+
+use crate::DecodeError;
+use crate::encoding::DecodeContext;
+use crate::encoding::WireType;
+use crate::encoding::check_wire_type;
+use crate::encoding::decode_varint;
+use crate::encoding::skip_field;
+use crate::traits::ArchivedProtoField;
+use crate::traits::ProtoArchive;
+use crate::traits::ProtoDecode;
+use crate::traits::ProtoDecoder;
+use crate::traits::ProtoDefault;
+use crate::traits::ProtoEncode;
+use crate::traits::ProtoExt;
+use crate::traits::ProtoKind;
+use crate::traits::ProtoShadowDecode;
+use crate::traits::ProtoShadowEncode;
+use crate::traits::buffer::RevWriter;
+
+/// Maps decoded text to a shared `Arc<str>`, set with
+/// [`DecodeContext::with_interner`](crate::DecodeContext::with_interner).
+pub trait StringInterner: Send + Sync {
+    fn intern(&self, value: &str) -> Arc<str>;
+}
+
+/// Interner keeping every distinct string it has seen until [`clear`](Self::clear) is called.
+///
+/// Meant for a bounded vocabulary; a field carrying free-form text would grow the pool without
+/// limit.
+#[derive(Debug, Default)]
+pub struct StringPool {
+    strings: Mutex<BTreeSet<Arc<str>>>,
+}
+
+impl StringPool {
+    pub const fn new() -> Self {
+        Self {
+            strings: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    /// Number of distinct strings held.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Drops the pool's references; strings still held by decoded messages stay alive.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeSet<Arc<str>>> {
+        self.strings.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl StringInterner for StringPool {
+    fn intern(&self, value: &str) -> Arc<str> {
+        let mut strings = self.lock();
+        if let Some(shared) = strings.get(value) {
+            return Arc::clone(shared);
+        }
+        let shared: Arc<str> = Arc::from(value);
+        strings.insert(Arc::clone(&shared));
+        shared
+    }
+}
+
+fn to_shared(raw: &[u8], ctx: DecodeContext) -> Result<Arc<str>, DecodeError> {
+    let value = core::str::from_utf8(raw).map_err(|_| DecodeError::new("invalid string value: data is not UTF-8 encoded"))?;
+    Ok(match ctx.interner() {
+        Some(interner) => interner.intern(value),
+        None => Arc::from(value),
+    })
+}
+
+impl ProtoExt for Arc<str> {
+    const KIND: ProtoKind = ProtoKind::String;
+}
+
+impl ProtoDecoder for Arc<str> {
+    #[inline]
+    fn merge_field(value: &mut Self, tag: u32, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        if tag == 1 {
+            value.merge(wire_type, buf, ctx)
+        } else {
+            skip_field(wire_type, tag, buf, ctx)
+        }
+    }
+
+    #[inline]
+    fn merge(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        check_wire_type(WireType::LengthDelimited, wire_type)?;
+        let len = decode_varint(buf)?;
+        if len > buf.remaining() as u64 {
+            return Err(DecodeError::new("buffer underflow"));
+        }
+        let len = len as usize;
+        // Interned strings share the pool's allocation, so only fresh ones count against the budget
+        if ctx.interner().is_none() {
+            ctx.charge(len)?;
+        }
+        // Interned strings usually exist already, so read them in place when the buffer allows it.
+        if buf.chunk().len() >= len {
+            *self = to_shared(&buf.chunk()[..len], ctx)?;
+            buf.advance(len);
+        } else {
+            let mut raw = vec![0; len];
+            buf.copy_to_slice(&mut raw);
+            *self = to_shared(&raw, ctx)?;
+        }
+        Ok(())
+    }
+}
+
+impl ProtoDefault for Arc<str> {
+    #[inline]
+    fn proto_default() -> Self {
+        Arc::from("")
+    }
+}
+
+impl ProtoShadowDecode<Arc<str>> for Arc<str> {
+    #[inline]
+    fn to_sun(self) -> Result<Arc<str>, DecodeError> {
+        Ok(self)
+    }
+}
+
+impl ProtoDecode for Arc<str> {
+    type ShadowDecoded = Self;
+}
+
+impl<'a> ProtoShadowEncode<'a, Arc<str>> for &'a Arc<str> {
+    #[inline]
+    fn from_sun(value: &'a Arc<str>) -> Self {
+        value
+    }
+}
+
+impl ProtoArchive for &Arc<str> {
+    #[inline]
+    fn is_default(&self) -> bool {
+        self.is_empty()
+    }
+
+    #[inline]
+    fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
+        w.put_slice(self.as_bytes());
+        if TAG != 0 {
+            w.put_varint(self.len() as u64);
+            ArchivedProtoField::<TAG, Self>::put_key(w);
+        }
+    }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        Some(ArchivedProtoField::<TAG, Self>::hint_delimited(self.len()))
+    }
+}
+
+impl ProtoArchive for Arc<str> {
+    #[inline]
+    fn is_default(&self) -> bool {
+        <&Arc<str> as ProtoArchive>::is_default(&self)
+    }
+
+    #[inline]
+    fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
+        (&self).archive::<TAG>(w);
+    }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        <&Arc<str> as ProtoArchive>::size_hint::<TAG>(&self)
+    }
+}
+
+impl ProtoEncode for Arc<str> {
+    type Shadow<'a> = &'a Arc<str>;
+}
@@ -0,0 +1,146 @@
+//! JSON schemas of registered messages for `schemars`.
+//!
+//! With the `schemars` feature every non-generic `#[proto_message]` type implements
+//! `schemars::JsonSchema`, built from the field metadata of the schema registry. The schema
+//! describes the proto3 JSON that [`Transcoder`](crate::transcode::Transcoder) reads and writes, so
+//! an HTTP gateway can publish it next to the transcoded routes:
+//!
+//! ```rust,ignore
+//! let schema = schemars::schema_for!(Order);
+//! ```
+//!
+//! Fields are named in lowerCamelCase and none are required, 64-bit integers are strings, `bytes`
+//! are base64 strings, enums are their variant names and complex enums are objects with at most
+//! one property. Nested messages and enums are definitions named by their full proto name.
+
+use std::borrow::Cow;
+
+use schemars::Schema;
+use schemars::SchemaGenerator;
+use schemars::json_schema;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::schemas::Field;
+use crate::schemas::ProtoEntry;
+use crate::schemas::ProtoIdent;
+use crate::schemas::ProtoLabel;
+use crate::schemas::ProtoType;
+use crate::schemas::Variant;
+use crate::schemas::schema_for;
+use crate::transcode::Kind;
+use crate::transcode::field_name;
+use crate::transcode::json_name;
+use crate::transcode::kind;
+
+/// Definition name of the type `ident`: its full proto name.
+pub fn schema_name(ident: &ProtoIdent) -> Cow<'static, str> {
+    Cow::Owned(ident.full_name())
+}
+
+/// Schema of the registered message or enum `ident`. Definitions of the types it refers to are
+/// added to `generator`; a type missing from the registry accepts any value.
+pub fn message_schema(ident: &ProtoIdent, generator: &mut SchemaGenerator) -> Schema {
+    match schema_for(ident).map(|schema| schema.content) {
+        Some(ProtoEntry::Struct { fields }) => fields_schema(fields, generator),
+        Some(ProtoEntry::ComplexEnum { variants }) => oneof_schema(variants, generator),
+        Some(ProtoEntry::SimpleEnum { variants }) => enum_schema(variants),
+        _ => Schema::default(),
+    }
+}
+
+fn fields_schema(fields: &[&Field], generator: &mut SchemaGenerator) -> Schema {
+    let mut properties = Map::new();
+    for (idx, field) in fields.iter().enumerate() {
+        properties.insert(json_name(&field_name(field, idx)), field_schema(field, generator).into());
+    }
+    json_schema!({
+        "type": "object",
+        "properties": properties,
+    })
+}
+
+fn oneof_schema(variants: &[&Variant], generator: &mut SchemaGenerator) -> Schema {
+    let mut properties = Map::new();
+    for variant in variants {
+        let schema = match variant.fields {
+            [] => json_schema!({ "type": "object" }),
+            [field] if field.name.is_none() => ident_schema(&field.proto_ident, generator),
+            fields => fields_schema(fields, generator),
+        };
+        properties.insert(json_name(&variant.oneof_field_name()), schema.into());
+    }
+    json_schema!({
+        "type": "object",
+        "properties": properties,
+        "maxProperties": 1,
+    })
+}
+
+fn enum_schema(variants: &[&Variant]) -> Schema {
+    let names: Vec<&str> = variants.iter().map(|variant| variant.name).collect();
+    json_schema!({
+        "type": "string",
+        "enum": names,
+    })
+}
+
+fn field_schema(field: &Field, generator: &mut SchemaGenerator) -> Schema {
+    if field.is_bytes() {
+        return scalar_schema(ProtoType::Bytes);
+    }
+    if let ProtoType::Map { key: _, value } = field.proto_ident.proto_type {
+        let value = field.map_idents().1.map_or_else(|| scalar_schema(*value), |ident| ident_schema(&ident, generator));
+        return json_schema!({
+            "type": "object",
+            "additionalProperties": value,
+        });
+    }
+    if field.proto_label == ProtoLabel::Repeated {
+        let items = ident_schema(&field.array_elem.unwrap_or(field.proto_ident), generator);
+        return json_schema!({
+            "type": "array",
+            "items": items,
+        });
+    }
+    ident_schema(&field.proto_ident, generator)
+}
+
+/// Schema of one value of type `ident`; messages and enums are referenced by definition.
+fn ident_schema(ident: &ProtoIdent, generator: &mut SchemaGenerator) -> Schema {
+    match kind(ident) {
+        Ok(Kind::Scalar(ty)) => scalar_schema(ty),
+        Ok(Kind::Enum(_) | Kind::Message(_)) => definition_ref(ident, generator),
+        Err(_) => Schema::default(),
+    }
+}
+
+fn definition_ref(ident: &ProtoIdent, generator: &mut SchemaGenerator) -> Schema {
+    let name = ident.full_name();
+    if !generator.definitions().contains_key(&name) {
+        // Placeholder first, so recursive messages refer to the definition being built.
+        generator.definitions_mut().insert(name.clone(), Value::Bool(true));
+        let schema = message_schema(ident, generator);
+        generator.definitions_mut().insert(name.clone(), schema.into());
+    }
+    let path = generator.settings().definitions_path.trim_start_matches('#').trim_end_matches('/');
+    Schema::new_ref(format!("#{path}/{}", name.replace('~', "~0").replace('/', "~1")))
+}
+
+fn scalar_schema(ty: ProtoType) -> Schema {
+    match ty {
+        ProtoType::Bool => json_schema!({ "type": "boolean" }),
+        ProtoType::Int32 | ProtoType::Sint32 | ProtoType::Sfixed32 | ProtoType::Enum => {
+            json_schema!({ "type": "integer", "format": "int32" })
+        }
+        ProtoType::Uint32 | ProtoType::Fixed32 => json_schema!({ "type": "integer", "format": "uint32", "minimum": 0 }),
+        ProtoType::Int64 | ProtoType::Sint64 | ProtoType::Sfixed64 => json_schema!({ "type": "string", "format": "int64" }),
+        ProtoType::Uint64 | ProtoType::Fixed64 => json_schema!({ "type": "string", "format": "uint64" }),
+        ProtoType::Float => json_schema!({ "type": "number", "format": "float" }),
+        ProtoType::Double => json_schema!({ "type": "number", "format": "double" }),
+        ProtoType::String => json_schema!({ "type": "string" }),
+        ProtoType::Bytes => json_schema!({ "type": "string", "contentEncoding": "base64" }),
+        ProtoType::Optional(inner) | ProtoType::Repeated(inner) => scalar_schema(*inner),
+        ProtoType::Message(_) | ProtoType::Map { .. } | ProtoType::None => Schema::default(),
+    }
+}
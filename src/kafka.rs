@@ -0,0 +1,226 @@
+//! Kafka record payloads for registered messages, in the shapes `rdkafka` produces and consumes.
+//!
+//! [`ProtoSerializer`] turns a message into the `Vec<u8>` a `FutureRecord` or `BaseRecord`
+//! takes as its payload, and [`ProtoDeserializer`] reads a consumed `rdkafka::Message`, or the
+//! `Option<&[u8]>` returned by `Message::payload`, where `None` is a tombstone.
+//!
+//! A framed payload names its type, so topics carrying several message types can be routed
+//! through a [`TypeRegistry`](crate::schemas::TypeRegistry):
+//!
+//! ```text
+//! [MAGIC_BYTE] [varint length] [type URL] [protobuf message]
+//! ```
+//!
+//! No protobuf message starts with [`MAGIC_BYTE`], since it would be the key of field number 0,
+//! so the deserializer reads framed and plain payloads alike. [`Payload`] implements rdkafka's
+//! `FromBytes`, so `Message::payload_view::<Payload>()` exposes the frame without decoding the
+//! message.
+//!
+//! ```rust,ignore
+//! let serializer = ProtoSerializer::<Order>::framed();
+//! let payload = serializer.serialize(&order);
+//! producer.send(FutureRecord::to("orders").key(&order.id).payload(&payload), timeout).await?;
+//!
+//! let order: Option<Order> = ProtoDeserializer::<Order>::new().deserialize_message(&message)?;
+//! ```
+
+use core::marker::PhantomData;
+
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::ProtoDecode;
+use crate::ProtoEncode;
+use crate::encoding::decode_varint;
+use crate::encoding::encode_varint;
+use crate::schemas::ProtoIdentifiable;
+use crate::schemas::type_url_name;
+use crate::traits::ProtoExt;
+
+/// Confluent Schema Registry framing and client
+/// Only available when "confluent" feature is enabled
+#[cfg(feature = "confluent")]
+pub mod confluent;
+
+/// First byte of a framed payload.
+pub const MAGIC_BYTE: u8 = 0x01;
+
+/// Encodes messages of type `T` as record payloads.
+#[derive(Debug)]
+pub struct ProtoSerializer<T> {
+    framed: bool,
+    _message: PhantomData<fn(&T)>,
+}
+
+impl<T> Clone for ProtoSerializer<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ProtoSerializer<T> {}
+
+impl<T> Default for ProtoSerializer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ProtoSerializer<T> {
+    /// Serializer writing the bare protobuf encoding.
+    pub const fn new() -> Self {
+        Self {
+            framed: false,
+            _message: PhantomData,
+        }
+    }
+
+    /// Serializer prefixing every payload with [`MAGIC_BYTE`] and the type URL of `T`.
+    pub const fn framed() -> Self {
+        Self {
+            framed: true,
+            _message: PhantomData,
+        }
+    }
+}
+
+impl<T: ProtoEncode + ProtoExt + ProtoIdentifiable> ProtoSerializer<T> {
+    pub fn serialize(&self, value: &T) -> Vec<u8> {
+        let payload = value.encode_to_vec();
+        if !self.framed {
+            return payload;
+        }
+        let type_url = T::PROTO_IDENT.type_url();
+        let mut buf = Vec::with_capacity(type_url.len() + payload.len() + 3);
+        buf.push(MAGIC_BYTE);
+        encode_varint(type_url.len() as u64, &mut buf);
+        buf.extend_from_slice(type_url.as_bytes());
+        buf.extend_from_slice(&payload);
+        buf
+    }
+}
+
+/// Decodes record payloads holding messages of type `T`, framed or not.
+#[derive(Debug)]
+pub struct ProtoDeserializer<T> {
+    require_frame: bool,
+    _message: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for ProtoDeserializer<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ProtoDeserializer<T> {}
+
+impl<T> Default for ProtoDeserializer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ProtoDeserializer<T> {
+    pub const fn new() -> Self {
+        Self {
+            require_frame: false,
+            _message: PhantomData,
+        }
+    }
+
+    /// Rejects payloads without a frame, for topics where every producer frames.
+    #[must_use]
+    pub const fn require_frame(mut self) -> Self {
+        self.require_frame = true;
+        self
+    }
+}
+
+impl<T: ProtoDecode + ProtoIdentifiable> ProtoDeserializer<T> {
+    /// Message in `payload`; `None` for a tombstone.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError`] if the frame names another type or is missing while required, or
+    /// if the message does not decode.
+    pub fn deserialize(&self, payload: Option<&[u8]>) -> Result<Option<T>, DecodeError> {
+        let Some(payload) = payload else {
+            return Ok(None);
+        };
+        let message = match split_frame(payload)? {
+            Some((type_url, message)) => {
+                let expected = T::PROTO_IDENT.full_name();
+                if type_url_name(type_url) != expected {
+                    return Err(DecodeError::new(format!("payload holds {type_url}, expected {expected}")));
+                }
+                message
+            }
+            None if self.require_frame => return Err(DecodeError::new("payload is not framed")),
+            None => payload,
+        };
+        T::decode(message, DecodeContext::default()).map(Some)
+    }
+
+    /// Message in the payload of a consumed record; `None` for a tombstone.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`deserialize`](Self::deserialize).
+    pub fn deserialize_message<M: rdkafka::Message>(&self, message: &M) -> Result<Option<T>, DecodeError> {
+        self.deserialize(message.payload())
+    }
+}
+
+/// Record payload viewed in place, framed or plain, from `Message::payload_view::<Payload>()`.
+#[derive(Debug, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Payload([u8]);
+
+impl Payload {
+    /// Type URL of a framed payload.
+    pub fn type_url(&self) -> Option<&str> {
+        split_frame(&self.0).ok().flatten().map(|(type_url, _)| type_url)
+    }
+
+    /// Encoded message, without the frame.
+    pub fn message(&self) -> &[u8] {
+        match split_frame(&self.0) {
+            Ok(Some((_, message))) => message,
+            _ => &self.0,
+        }
+    }
+}
+
+impl rdkafka::message::FromBytes for Payload {
+    type Error = DecodeError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<&Self, DecodeError> {
+        split_frame(bytes)?;
+        // SAFETY: `Payload` is a `repr(transparent)` wrapper around `[u8]`.
+        Ok(unsafe { &*(core::ptr::from_ref::<[u8]>(bytes) as *const Self) })
+    }
+}
+
+impl rdkafka::message::ToBytes for Payload {
+    fn to_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Type URL and message of a framed payload, `None` for a plain one.
+///
+/// # Errors
+///
+/// Returns [`DecodeError`] if the frame is cut short or its type URL is not UTF-8.
+pub fn split_frame(payload: &[u8]) -> Result<Option<(&str, &[u8])>, DecodeError> {
+    let Some((&MAGIC_BYTE, mut rest)) = payload.split_first() else {
+        return Ok(None);
+    };
+    let len = usize::try_from(decode_varint(&mut rest)?).map_err(|_| DecodeError::new("buffer underflow"))?;
+    if len > rest.len() {
+        return Err(DecodeError::new("buffer underflow"));
+    }
+    let (type_url, message) = rest.split_at(len);
+    let type_url = core::str::from_utf8(type_url).map_err(|_| DecodeError::new("invalid string value: data is not UTF-8 encoded"))?;
+    Ok(Some((type_url, message)))
+}
@@ -0,0 +1,496 @@
+//! Confluent Schema Registry framing and client, for Kafka platforms that require
+//! registry-framed protobuf.
+//!
+//! A framed payload is [`MAGIC_BYTE`], the big-endian `u32` schema id, the message indexes
+//! locating the message type inside its `.proto` file and the protobuf message. The indexes are
+//! a zigzag varint count followed by zigzag varint indexes; the common `[0]`, the first message
+//! of the file, is the single byte `0`.
+//!
+//! [`SchemaRegistry::register_emitted`] registers the `.proto` file [`write_all`] emitted for a
+//! type, with the files it imports registered first as references under their import paths.
+//!
+//! ```rust,ignore
+//! proto_rs::schemas::write_all("protos", &RustClientCtx::disabled())?;
+//! let registry = SchemaRegistry::new("http://registry:8081");
+//! let schema = registry.register_emitted::<OrderPlaced>("protos", "orders-value").await?;
+//!
+//! let payload = ConfluentSerializer::new(&schema).serialize(&event);
+//! let event: Option<OrderPlaced> = ConfluentDeserializer::new().deserialize(message.payload())?;
+//! ```
+//!
+//! [`write_all`]: crate::schemas::write_all
+
+use core::fmt::Write as _;
+use core::marker::PhantomData;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::ProtoDecode;
+use crate::ProtoEncode;
+use crate::encoding::decode_varint;
+use crate::encoding::encode_varint;
+use crate::schemas::ProtoIdentifiable;
+use crate::traits::ProtoExt;
+
+/// First byte of a registry-framed payload.
+pub const MAGIC_BYTE: u8 = 0;
+
+const CONTENT_TYPE: &str = "application/vnd.schemaregistry.v1+json";
+
+/// Failure to talk to the schema registry or to find the schema to register.
+#[derive(Debug)]
+pub enum RegistryError {
+    /// The request did not reach the registry or its answer could not be read.
+    Http(reqwest::Error),
+    /// The registry answered with an error status.
+    Registry { status: u16, message: String },
+    /// Reading an emitted `.proto` file failed.
+    Io(io::Error),
+    /// The emitted files do not hold the type or an import.
+    MissingSchema(String),
+}
+
+impl core::fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RegistryError::Http(error) => core::fmt::Display::fmt(error, f),
+            RegistryError::Registry { status, message } => write!(f, "schema registry returned {status}: {message}"),
+            RegistryError::Io(error) => core::fmt::Display::fmt(error, f),
+            RegistryError::MissingSchema(reason) => write!(f, "missing schema: {reason}"),
+        }
+    }
+}
+
+impl core::error::Error for RegistryError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            RegistryError::Http(error) => Some(error),
+            RegistryError::Io(error) => Some(error),
+            RegistryError::Registry { .. } | RegistryError::MissingSchema(_) => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for RegistryError {
+    fn from(error: reqwest::Error) -> RegistryError {
+        RegistryError::Http(error)
+    }
+}
+
+impl From<io::Error> for RegistryError {
+    fn from(error: io::Error) -> RegistryError {
+        RegistryError::Io(error)
+    }
+}
+
+/// Schema imported by a registered schema: the import path, and the subject and version it is
+/// registered under.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaReference {
+    pub name: String,
+    pub subject: String,
+    pub version: i32,
+}
+
+/// Where a message type is registered: the schema id and the message indexes of the type inside
+/// the schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisteredSchema {
+    pub id: u32,
+    pub message_indexes: Vec<i32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SchemaRequest<'a> {
+    schema_type: &'static str,
+    schema: &'a str,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    references: &'a [SchemaReference],
+}
+
+#[derive(Deserialize)]
+struct IdResponse {
+    id: u32,
+}
+
+#[derive(Deserialize)]
+struct VersionResponse {
+    version: i32,
+}
+
+#[derive(Deserialize)]
+struct SchemaResponse {
+    schema: String,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    message: String,
+}
+
+/// Client of a Confluent-compatible schema registry.
+#[derive(Debug, Clone)]
+pub struct SchemaRegistry {
+    base_url: String,
+    client: reqwest::Client,
+    basic_auth: Option<(String, String)>,
+}
+
+impl SchemaRegistry {
+    /// Client of the registry at `base_url`, e.g. `http://registry:8081`.
+    pub fn new(base_url: &str) -> Self {
+        Self::with_client(base_url, reqwest::Client::new())
+    }
+
+    /// Client sending its requests through `client`, e.g. one configured with TLS settings.
+    pub fn with_client(base_url: &str, client: reqwest::Client) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client,
+            basic_auth: None,
+        }
+    }
+
+    /// Authenticates every request with HTTP basic auth, e.g. a Confluent Cloud API key.
+    #[must_use]
+    pub fn basic_auth(mut self, username: &str, password: &str) -> Self {
+        self.basic_auth = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    /// Registers `schema` under `subject` and returns its id. Registering a schema the subject
+    /// already holds returns the existing id.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegistryError::Registry`] if the registry rejects the schema, e.g. as
+    /// incompatible with the previous version.
+    pub async fn register(&self, subject: &str, schema: &str, references: &[SchemaReference]) -> Result<u32, RegistryError> {
+        let url = format!("{}/subjects/{}/versions", self.base_url, encode_path(subject));
+        let response: IdResponse = self.send(self.client.post(url).json(&protobuf_schema(schema, references))).await?;
+        Ok(response.id)
+    }
+
+    /// Version of `schema` in `subject`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegistryError::Registry`] if the subject does not hold the schema.
+    pub async fn version(&self, subject: &str, schema: &str, references: &[SchemaReference]) -> Result<i32, RegistryError> {
+        let url = format!("{}/subjects/{}", self.base_url, encode_path(subject));
+        let response: VersionResponse = self.send(self.client.post(url).json(&protobuf_schema(schema, references))).await?;
+        Ok(response.version)
+    }
+
+    /// Schema text registered with `id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegistryError::Registry`] if no schema has this id.
+    pub async fn schema(&self, id: u32) -> Result<String, RegistryError> {
+        let url = format!("{}/schemas/ids/{id}", self.base_url);
+        let response: SchemaResponse = self.send(self.client.get(url)).await?;
+        Ok(response.schema)
+    }
+
+    /// Registers the `.proto` file of `T` found under `output_dir`, the directory given to
+    /// [`write_all`](crate::schemas::write_all), under `subject`. Imported files are registered
+    /// first, each under its import path; `google/protobuf` imports are left to the registry.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegistryError::MissingSchema`] if the file of `T` or an import is not under
+    /// `output_dir`, or `T` is not a top-level message of its file, and the errors of
+    /// [`register`](Self::register).
+    pub async fn register_emitted<T: ProtoIdentifiable>(
+        &self,
+        output_dir: impl AsRef<Path>,
+        subject: &str,
+    ) -> Result<RegisteredSchema, RegistryError> {
+        let output_dir = output_dir.as_ref();
+        let ident = T::PROTO_IDENT;
+        let schema = std::fs::read_to_string(output_dir.join(ident.proto_file_path))?;
+        let index = top_level_index(&schema, ident.name)
+            .ok_or_else(|| RegistryError::MissingSchema(format!("{} is not declared in {}", ident.name, ident.proto_file_path)))?;
+
+        let mut files = Vec::new();
+        collect_imports(output_dir, &schema, &mut files)?;
+        let mut versions = BTreeMap::new();
+        for (name, text) in &files {
+            let references = references(text, &versions);
+            self.register(name, text, &references).await?;
+            let version = self.version(name, text, &references).await?;
+            versions.insert(name.clone(), version);
+        }
+
+        let id = self.register(subject, &schema, &references(&schema, &versions)).await?;
+        Ok(RegisteredSchema {
+            id,
+            message_indexes: vec![index],
+        })
+    }
+
+    async fn send<R: serde::de::DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<R, RegistryError> {
+        let request = match &self.basic_auth {
+            Some((username, password)) => request.basic_auth(username, Some(password)),
+            None => request,
+        };
+        let response = request.header(reqwest::header::ACCEPT, CONTENT_TYPE).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let message = serde_json::from_str::<ErrorResponse>(&body).map_or(body, |error| error.message);
+            return Err(RegistryError::Registry {
+                status: status.as_u16(),
+                message,
+            });
+        }
+        Ok(response.json().await?)
+    }
+}
+
+const fn protobuf_schema<'a>(schema: &'a str, references: &'a [SchemaReference]) -> SchemaRequest<'a> {
+    SchemaRequest {
+        schema_type: "PROTOBUF",
+        schema,
+        references,
+    }
+}
+
+/// Percent-encodes a subject for a URL path segment.
+fn encode_path(segment: &str) -> String {
+    segment.bytes().fold(String::with_capacity(segment.len()), |mut out, byte| {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            out.push(char::from(byte));
+        } else {
+            write!(out, "%{byte:02X}").unwrap();
+        }
+        out
+    })
+}
+
+/// Import paths of a `.proto` file, without `google/protobuf` ones.
+fn imports(schema: &str) -> impl Iterator<Item = &str> {
+    schema
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("import "))
+        .filter_map(|import| import.split('"').nth(1))
+        .filter(|import| !import.starts_with("google/protobuf/"))
+}
+
+fn references(schema: &str, versions: &BTreeMap<String, i32>) -> Vec<SchemaReference> {
+    imports(schema)
+        .filter_map(|import| {
+            versions.get(import).map(|version| SchemaReference {
+                name: import.to_string(),
+                subject: import.to_string(),
+                version: *version,
+            })
+        })
+        .collect()
+}
+
+/// Files imported by `schema`, transitively, each after its own imports.
+fn collect_imports(output_dir: &Path, schema: &str, files: &mut Vec<(String, String)>) -> Result<(), RegistryError> {
+    for import in imports(schema) {
+        if files.iter().any(|(name, _)| name == import) {
+            continue;
+        }
+        let text = std::fs::read_to_string(resolve_import(output_dir, import)?)?;
+        collect_imports(output_dir, &text, files)?;
+        files.push((import.to_string(), text));
+    }
+    Ok(())
+}
+
+/// Emitted file for `import`: the path itself under `output_dir`, or the registered file with the
+/// same file name, as imports between registry files name only the file.
+fn resolve_import(output_dir: &Path, import: &str) -> Result<PathBuf, RegistryError> {
+    let direct = output_dir.join(import);
+    if direct.is_file() {
+        return Ok(direct);
+    }
+    let file_name = Path::new(import).file_name();
+    crate::schemas::file_names()
+        .into_iter()
+        .find(|registered| Path::new(registered).file_name() == file_name)
+        .map(|registered| output_dir.join(registered))
+        .filter(|path| path.is_file())
+        .ok_or_else(|| RegistryError::MissingSchema(format!("import {import} is not under {}", output_dir.display())))
+}
+
+/// Position of message `name` among the top-level messages of `schema`.
+fn top_level_index(schema: &str, name: &str) -> Option<i32> {
+    let mut depth = 0_usize;
+    let mut index = 0;
+    for line in schema.lines() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        if depth == 0
+            && let Some(declared) = line.strip_prefix("message ")
+        {
+            if declared.split(|ch: char| ch == '{' || ch.is_whitespace()).next() == Some(name) {
+                return Some(index);
+            }
+            index += 1;
+        }
+        depth = (depth + line.matches('{').count()).saturating_sub(line.matches('}').count());
+    }
+    None
+}
+
+/// Parts of a registry-framed payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame<'a> {
+    pub schema_id: u32,
+    pub message_indexes: Vec<i32>,
+    pub message: &'a [u8],
+}
+
+/// Appends the frame header for `schema_id` and `message_indexes` to `buf`.
+#[allow(clippy::cast_sign_loss)]
+pub fn write_header(schema_id: u32, message_indexes: &[i32], buf: &mut Vec<u8>) {
+    buf.push(MAGIC_BYTE);
+    buf.extend_from_slice(&schema_id.to_be_bytes());
+    if message_indexes == [0] {
+        buf.push(0);
+        return;
+    }
+    let zigzag = |value: i32| u64::from(((value << 1) ^ (value >> 31)) as u32);
+    encode_varint(zigzag(i32::try_from(message_indexes.len()).unwrap_or(i32::MAX)), buf);
+    for index in message_indexes {
+        encode_varint(zigzag(*index), buf);
+    }
+}
+
+/// Splits a registry-framed payload.
+///
+/// # Errors
+///
+/// Returns [`DecodeError`] if `payload` does not start with [`MAGIC_BYTE`] or its header is cut
+/// short.
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+pub fn split_frame(payload: &[u8]) -> Result<Frame<'_>, DecodeError> {
+    let Some((&MAGIC_BYTE, rest)) = payload.split_first() else {
+        return Err(DecodeError::new("payload is not registry-framed"));
+    };
+    let Some((schema_id, mut rest)) = rest.split_first_chunk::<4>() else {
+        return Err(DecodeError::new("buffer underflow"));
+    };
+    let zigzag = |buf: &mut &[u8]| decode_varint(buf).map(|value| ((value >> 1) as i32) ^ -((value & 1) as i32));
+    let count = zigzag(&mut rest)?;
+    let message_indexes = if count == 0 {
+        vec![0]
+    } else {
+        let count = usize::try_from(count).map_err(|_| DecodeError::new("invalid message index count"))?;
+        if count > rest.len() {
+            return Err(DecodeError::new("buffer underflow"));
+        }
+        (0..count).map(|_| zigzag(&mut rest)).collect::<Result<_, _>>()?
+    };
+    Ok(Frame {
+        schema_id: u32::from_be_bytes(*schema_id),
+        message_indexes,
+        message: rest,
+    })
+}
+
+/// Encodes messages of type `T` as payloads framed with a registered schema.
+#[derive(Debug, Clone)]
+pub struct ConfluentSerializer<T> {
+    header: Vec<u8>,
+    _message: PhantomData<fn(&T)>,
+}
+
+impl<T> ConfluentSerializer<T> {
+    /// Serializer framing payloads with the id and message indexes of `schema`.
+    pub fn new(schema: &RegisteredSchema) -> Self {
+        let mut header = Vec::with_capacity(6);
+        write_header(schema.id, &schema.message_indexes, &mut header);
+        Self {
+            header,
+            _message: PhantomData,
+        }
+    }
+}
+
+impl<T: ProtoEncode + ProtoExt> ConfluentSerializer<T> {
+    pub fn serialize(&self, value: &T) -> Vec<u8> {
+        let payload = value.encode_to_vec();
+        let mut buf = Vec::with_capacity(self.header.len() + payload.len());
+        buf.extend_from_slice(&self.header);
+        buf.extend_from_slice(&payload);
+        buf
+    }
+}
+
+/// Decodes registry-framed payloads holding messages of type `T`, whatever schema version they
+/// were written with.
+#[derive(Debug, Clone)]
+pub struct ConfluentDeserializer<T> {
+    message_indexes: Option<Vec<i32>>,
+    _message: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for ConfluentDeserializer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ConfluentDeserializer<T> {
+    pub const fn new() -> Self {
+        Self {
+            message_indexes: None,
+            _message: PhantomData,
+        }
+    }
+
+    /// Rejects payloads whose message indexes differ from those of `schema`, for subjects whose
+    /// file declares several messages.
+    #[must_use]
+    pub fn expect_indexes(mut self, schema: &RegisteredSchema) -> Self {
+        self.message_indexes = Some(schema.message_indexes.clone());
+        self
+    }
+}
+
+impl<T: ProtoDecode> ConfluentDeserializer<T> {
+    /// Message in `payload`; `None` for a tombstone.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError`] if the payload is not framed, names other message indexes than
+    /// expected, or the message does not decode.
+    pub fn deserialize(&self, payload: Option<&[u8]>) -> Result<Option<T>, DecodeError> {
+        let Some(payload) = payload else {
+            return Ok(None);
+        };
+        let frame = split_frame(payload)?;
+        if let Some(expected) = &self.message_indexes
+            && *expected != frame.message_indexes
+        {
+            return Err(DecodeError::new(format!(
+                "payload holds message {:?}, expected {expected:?}",
+                frame.message_indexes
+            )));
+        }
+        T::decode(frame.message, DecodeContext::default()).map(Some)
+    }
+
+    /// Message in the payload of a consumed record; `None` for a tombstone.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`deserialize`](Self::deserialize).
+    pub fn deserialize_message<M: rdkafka::Message>(&self, message: &M) -> Result<Option<T>, DecodeError> {
+        self.deserialize(message.payload())
+    }
+}
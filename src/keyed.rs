@@ -0,0 +1,462 @@
+//! Field-number keyed value trees of registered messages, shared by the CBOR and MessagePack
+//! codecs.
+//!
+//! A message becomes a map from field number to value, walked through the same registry
+//! metadata as [`transcode`](crate::transcode): absent fields are left out, repeated fields are
+//! arrays, map fields are maps with their native keys, enums are their numbers and complex enums
+//! are maps holding the single set variant under its oneof tag.
+
+use std::collections::BTreeMap;
+
+use bytes::Buf;
+
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::ProtoDecode;
+use crate::ProtoEncode;
+use crate::encoding::WireType;
+use crate::encoding::decode_key;
+use crate::encoding::decode_varint;
+use crate::encoding::encode_key;
+use crate::encoding::encode_varint;
+use crate::encoding::skip_field;
+use crate::schemas::Field;
+use crate::schemas::ProtoEntry;
+use crate::schemas::ProtoIdent;
+use crate::schemas::ProtoIdentifiable;
+use crate::schemas::ProtoLabel;
+use crate::schemas::ProtoType;
+use crate::schemas::Variant;
+use crate::schemas::schema_for;
+use crate::traits::ProtoExt;
+use crate::transcode::Kind;
+use crate::transcode::TranscodeError;
+use crate::transcode::kind;
+use crate::transcode::length_delimited;
+use crate::transcode::wire_type_of;
+use crate::transcode::write_len_delimited;
+use crate::transcode::zigzag;
+
+/// Format-independent value of a message, a field or a map key.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Keyed {
+    Null,
+    Bool(bool),
+    Int(i128),
+    F32(f32),
+    F64(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+    Array(Vec<Keyed>),
+    Map(Vec<(Keyed, Keyed)>),
+}
+
+/// Keyed tree of `value`.
+pub(crate) fn to_keyed<T: ProtoEncode + ProtoExt + ProtoIdentifiable>(value: &T) -> Result<Keyed, TranscodeError> {
+    let ident = message_ident::<T>()?;
+    decode_message(&ident, &value.encode_to_vec(), DecodeContext::default())
+}
+
+/// Message of type `T` described by `keyed`. Unknown field numbers are skipped.
+pub(crate) fn from_keyed<T: ProtoDecode + ProtoIdentifiable>(keyed: &Keyed) -> Result<T, TranscodeError> {
+    let ident = message_ident::<T>()?;
+    let mut buf = Vec::new();
+    encode_message(&ident, keyed, &mut buf)?;
+    Ok(T::decode(buf.as_slice(), DecodeContext::default())?)
+}
+
+pub(crate) fn message_ident<T: ProtoIdentifiable>() -> Result<ProtoIdent, TranscodeError> {
+    let ident = T::PROTO_IDENT;
+    match schema_for(&ident).map(|schema| schema.content) {
+        Some(ProtoEntry::Struct { .. } | ProtoEntry::ComplexEnum { .. }) => Ok(ident),
+        _ => Err(TranscodeError::UnknownMessage(ident.name.to_string())),
+    }
+}
+
+fn decode_message(ident: &ProtoIdent, mut buf: &[u8], ctx: DecodeContext) -> Result<Keyed, TranscodeError> {
+    ctx.limit_reached()?;
+    match schema_for(ident).map(|schema| schema.content) {
+        Some(ProtoEntry::Struct { fields }) => decode_fields(fields, buf, ctx),
+        Some(ProtoEntry::ComplexEnum { variants }) => {
+            let mut set = None;
+            while buf.has_remaining() {
+                let (tag, wire_type) = decode_key(&mut buf)?;
+                let Some(variant) = (tag as usize).checked_sub(1).and_then(|idx| variants.get(idx)) else {
+                    skip_field(wire_type, tag, &mut buf, ctx)?;
+                    continue;
+                };
+                let value = match variant.fields {
+                    [field] if field.name.is_none() => decode_value(&kind(&field.proto_ident)?, wire_type, &mut buf, ctx)?,
+                    fields => {
+                        let nested = length_delimited(wire_type, &mut buf)?;
+                        decode_fields(fields, nested, ctx.enter_recursion())?
+                    }
+                };
+                // Only the last variant on the wire is set.
+                set = Some((tag, value));
+            }
+            Ok(Keyed::Map(
+                set.map(|(tag, value)| (Keyed::Int(tag.into()), value)).into_iter().collect(),
+            ))
+        }
+        _ => Err(TranscodeError::UnknownMessage(ident.name.to_string())),
+    }
+}
+
+fn decode_fields(fields: &[&Field], mut buf: &[u8], ctx: DecodeContext) -> Result<Keyed, TranscodeError> {
+    let mut out: BTreeMap<u32, Keyed> = BTreeMap::new();
+    while buf.has_remaining() {
+        let (tag, wire_type) = decode_key(&mut buf)?;
+        let Some(field) = fields.iter().find(|field| field.tag == tag) else {
+            skip_field(wire_type, tag, &mut buf, ctx)?;
+            continue;
+        };
+        if field.is_bytes() {
+            out.insert(tag, decode_value(&Kind::Scalar(ProtoType::Bytes), wire_type, &mut buf, ctx)?);
+        } else if let ProtoType::Map { key, value } = field.proto_ident.proto_type {
+            let (key_ident, value_ident) = field.map_idents();
+            let key_kind = key_ident.map_or(Ok(Kind::Scalar(*key)), |ident| kind(&ident))?;
+            let value_kind = value_ident.map_or(Ok(Kind::Scalar(*value)), |ident| kind(&ident))?;
+            let entry = length_delimited(wire_type, &mut buf)?;
+            let (map_key, map_value) = decode_entry(&key_kind, &value_kind, entry, ctx)?;
+            let Keyed::Map(entries) = out.entry(tag).or_insert_with(|| Keyed::Map(Vec::new())) else {
+                continue;
+            };
+            match entries.iter_mut().find(|(existing, _)| *existing == map_key) {
+                Some(entry) => entry.1 = map_value,
+                None => entries.push((map_key, map_value)),
+            }
+        } else if field.proto_label == ProtoLabel::Repeated {
+            let elem = kind(&field.array_elem.unwrap_or(field.proto_ident))?;
+            let Keyed::Array(items) = out.entry(tag).or_insert_with(|| Keyed::Array(Vec::new())) else {
+                continue;
+            };
+            let packed = match elem {
+                Kind::Scalar(ty) => ty.is_packable(),
+                Kind::Enum(_) => true,
+                Kind::Message(_) => false,
+            };
+            if packed && wire_type == WireType::LengthDelimited {
+                let mut packed = length_delimited(wire_type, &mut buf)?;
+                let item_wire_type = match elem {
+                    Kind::Scalar(ty) => wire_type_of(ty),
+                    _ => WireType::Varint,
+                };
+                while packed.has_remaining() {
+                    items.push(decode_value(&elem, item_wire_type, &mut packed, ctx)?);
+                }
+            } else {
+                items.push(decode_value(&elem, wire_type, &mut buf, ctx)?);
+            }
+        } else {
+            out.insert(tag, decode_value(&kind(&field.proto_ident)?, wire_type, &mut buf, ctx)?);
+        }
+    }
+    Ok(Keyed::Map(
+        out.into_iter().map(|(tag, value)| (Keyed::Int(tag.into()), value)).collect(),
+    ))
+}
+
+fn decode_entry(key: &Kind, value: &Kind, mut buf: &[u8], ctx: DecodeContext) -> Result<(Keyed, Keyed), TranscodeError> {
+    let mut entry = (None, None);
+    while buf.has_remaining() {
+        let (tag, wire_type) = decode_key(&mut buf)?;
+        match tag {
+            1 => entry.0 = Some(decode_value(key, wire_type, &mut buf, ctx)?),
+            2 => entry.1 = Some(decode_value(value, wire_type, &mut buf, ctx)?),
+            _ => skip_field(wire_type, tag, &mut buf, ctx)?,
+        }
+    }
+    Ok((
+        entry.0.unwrap_or_else(|| default_keyed(key)),
+        entry.1.unwrap_or_else(|| default_keyed(value)),
+    ))
+}
+
+/// Value of a map key or value missing from its entry.
+pub(crate) const fn default_keyed(kind: &Kind) -> Keyed {
+    match kind {
+        Kind::Scalar(ProtoType::Bool) => Keyed::Bool(false),
+        Kind::Scalar(ProtoType::String) => Keyed::Text(String::new()),
+        Kind::Scalar(ProtoType::Bytes) => Keyed::Bytes(Vec::new()),
+        Kind::Scalar(ProtoType::Float) => Keyed::F32(0.0),
+        Kind::Scalar(ProtoType::Double) => Keyed::F64(0.0),
+        Kind::Scalar(_) | Kind::Enum(_) => Keyed::Int(0),
+        Kind::Message(_) => Keyed::Map(Vec::new()),
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn decode_value(kind: &Kind, wire_type: WireType, buf: &mut &[u8], ctx: DecodeContext) -> Result<Keyed, TranscodeError> {
+    let ty = match kind {
+        Kind::Message(ident) => {
+            let nested = length_delimited(wire_type, buf)?;
+            return decode_message(ident, nested, ctx.enter_recursion());
+        }
+        Kind::Enum(_) => {
+            if wire_type != WireType::Varint {
+                return Err(DecodeError::new("invalid wire type").into());
+            }
+            return Ok(Keyed::Int((decode_varint(buf)? as i32).into()));
+        }
+        Kind::Scalar(ty) => *ty,
+    };
+    if wire_type != wire_type_of(ty) {
+        return Err(DecodeError::new("invalid wire type").into());
+    }
+    let fixed = |buf: &mut &[u8], len: usize| {
+        if buf.remaining() < len {
+            Err(DecodeError::new("buffer underflow"))
+        } else {
+            Ok(())
+        }
+    };
+    let value = match ty {
+        ProtoType::Bool => Keyed::Bool(decode_varint(buf)? != 0),
+        ProtoType::Int32 | ProtoType::Enum => Keyed::Int((decode_varint(buf)? as i32).into()),
+        ProtoType::Uint32 => Keyed::Int((decode_varint(buf)? as u32).into()),
+        ProtoType::Int64 => Keyed::Int((decode_varint(buf)? as i64).into()),
+        ProtoType::Uint64 => Keyed::Int(decode_varint(buf)?.into()),
+        ProtoType::Sint32 => Keyed::Int((zigzag(decode_varint(buf)?) as i32).into()),
+        ProtoType::Sint64 => Keyed::Int(zigzag(decode_varint(buf)?).into()),
+        ProtoType::Fixed32 => {
+            fixed(buf, 4)?;
+            Keyed::Int(buf.get_u32_le().into())
+        }
+        ProtoType::Sfixed32 => {
+            fixed(buf, 4)?;
+            Keyed::Int(buf.get_i32_le().into())
+        }
+        ProtoType::Fixed64 => {
+            fixed(buf, 8)?;
+            Keyed::Int(buf.get_u64_le().into())
+        }
+        ProtoType::Sfixed64 => {
+            fixed(buf, 8)?;
+            Keyed::Int(buf.get_i64_le().into())
+        }
+        ProtoType::Float => {
+            fixed(buf, 4)?;
+            Keyed::F32(buf.get_f32_le())
+        }
+        ProtoType::Double => {
+            fixed(buf, 8)?;
+            Keyed::F64(buf.get_f64_le())
+        }
+        ProtoType::String => {
+            let payload = length_delimited(wire_type, buf)?;
+            let text = core::str::from_utf8(payload).map_err(|_| DecodeError::new("invalid string value: data is not UTF-8 encoded"))?;
+            Keyed::Text(text.to_string())
+        }
+        ProtoType::Bytes => Keyed::Bytes(length_delimited(wire_type, buf)?.to_vec()),
+        _ => return Err(TranscodeError::invalid("unsupported field type")),
+    };
+    Ok(value)
+}
+
+fn encode_message(ident: &ProtoIdent, value: &Keyed, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+    let Keyed::Map(entries) = value else {
+        return Err(TranscodeError::invalid(format!("expected a map for {}", ident.name)));
+    };
+    match schema_for(ident).map(|schema| schema.content) {
+        Some(ProtoEntry::Struct { fields }) => encode_fields(fields, entries, buf),
+        Some(ProtoEntry::ComplexEnum { variants }) => encode_oneof(variants, entries, buf),
+        _ => Err(TranscodeError::UnknownMessage(ident.name.to_string())),
+    }
+}
+
+pub(crate) fn tag_of(key: &Keyed) -> Result<u32, TranscodeError> {
+    match key {
+        Keyed::Int(tag) => {
+            u32::try_from(*tag).ok().filter(|tag| *tag > 0).ok_or_else(|| TranscodeError::invalid(format!("invalid field number {tag}")))
+        }
+        _ => Err(TranscodeError::invalid("expected an integer field number")),
+    }
+}
+
+fn encode_fields(fields: &[&Field], entries: &[(Keyed, Keyed)], buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+    for (key, value) in entries {
+        let tag = tag_of(key)?;
+        let Some(field) = fields.iter().find(|field| field.tag == tag) else {
+            continue;
+        };
+        if *value != Keyed::Null {
+            encode_field(field, value, buf).map_err(|error| error.at(&tag.to_string()))?;
+        }
+    }
+    Ok(())
+}
+
+fn encode_oneof(variants: &[&Variant], entries: &[(Keyed, Keyed)], buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+    let mut set = None;
+    for (key, value) in entries.iter().filter(|(_, value)| *value != Keyed::Null) {
+        let tag = tag_of(key)?;
+        let Some(variant) = (tag as usize).checked_sub(1).and_then(|idx| variants.get(idx)) else {
+            continue;
+        };
+        if set.replace((tag, variant, value)).is_some() {
+            return Err(TranscodeError::invalid("more than one variant is set"));
+        }
+    }
+    let Some((tag, variant, value)) = set else {
+        return Ok(());
+    };
+    let encoded = match variant.fields {
+        [field] if field.name.is_none() => encode_value(tag, &field.proto_ident, value, buf),
+        fields => {
+            let Keyed::Map(entries) = value else {
+                return Err(TranscodeError::invalid("expected a map").at(&tag.to_string()));
+            };
+            let mut nested = Vec::new();
+            encode_fields(fields, entries, &mut nested).map(|()| write_len_delimited(tag, &nested, buf))
+        }
+    };
+    encoded.map_err(|error| error.at(&tag.to_string()))
+}
+
+fn encode_field(field: &Field, value: &Keyed, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+    if field.is_bytes() {
+        encode_key(field.tag, WireType::LengthDelimited, buf);
+        return encode_scalar(ProtoType::Bytes, value, buf);
+    }
+    if let ProtoType::Map { key, value: value_type } = field.proto_ident.proto_type {
+        return encode_map(field, *key, *value_type, value, buf);
+    }
+    if field.proto_label != ProtoLabel::Repeated {
+        return encode_value(field.tag, &field.proto_ident, value, buf);
+    }
+    let Keyed::Array(items) = value else {
+        return Err(TranscodeError::invalid("expected an array"));
+    };
+    let elem = field.array_elem.unwrap_or(field.proto_ident);
+    match kind(&elem)? {
+        Kind::Scalar(ty) if ty.is_packable() => {
+            let mut packed = Vec::new();
+            for (idx, item) in items.iter().enumerate() {
+                encode_scalar(ty, item, &mut packed).map_err(|error| error.at(&format!("[{idx}]")))?;
+            }
+            write_len_delimited(field.tag, &packed, buf);
+        }
+        Kind::Enum(_) => {
+            let mut packed = Vec::new();
+            for (idx, item) in items.iter().enumerate() {
+                encode_scalar(ProtoType::Enum, item, &mut packed).map_err(|error| error.at(&format!("[{idx}]")))?;
+            }
+            write_len_delimited(field.tag, &packed, buf);
+        }
+        _ => {
+            for (idx, item) in items.iter().enumerate() {
+                encode_value(field.tag, &elem, item, buf).map_err(|error| error.at(&format!("[{idx}]")))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn encode_map(field: &Field, key: ProtoType, value: ProtoType, keyed: &Keyed, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+    let Keyed::Map(entries) = keyed else {
+        return Err(TranscodeError::invalid("expected a map"));
+    };
+    let (key_ident, value_ident) = field.map_idents();
+    let key_type = key_ident.map_or(key, |ident| ident.proto_type);
+    for (idx, (map_key, map_value)) in entries.iter().enumerate() {
+        let mut entry = Vec::new();
+        let encoded = tagged_scalar(1, key_type, map_key, &mut entry).and_then(|()| match value_ident {
+            Some(ident) => encode_value(2, &ident, map_value, &mut entry),
+            None => tagged_scalar(2, value, map_value, &mut entry),
+        });
+        encoded.map_err(|error| error.at(&format!("[{idx}]")))?;
+        write_len_delimited(field.tag, &entry, buf);
+    }
+    Ok(())
+}
+
+/// One occurrence of a field of type `ident` under `tag`.
+fn encode_value(tag: u32, ident: &ProtoIdent, value: &Keyed, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+    match kind(ident)? {
+        Kind::Scalar(ty) => tagged_scalar(tag, ty, value, buf),
+        Kind::Enum(_) => tagged_scalar(tag, ProtoType::Enum, value, buf),
+        Kind::Message(ident) => {
+            let mut nested = Vec::new();
+            encode_message(&ident, value, &mut nested)?;
+            write_len_delimited(tag, &nested, buf);
+            Ok(())
+        }
+    }
+}
+
+fn tagged_scalar(tag: u32, ty: ProtoType, value: &Keyed, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+    if matches!(ty, ProtoType::Message(_) | ProtoType::Map { .. } | ProtoType::None) {
+        return Err(TranscodeError::invalid("unsupported field type"));
+    }
+    encode_key(tag, wire_type_of(ty), buf);
+    encode_scalar(ty, value, buf)
+}
+
+/// Scalar payload without its key.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn encode_scalar(ty: ProtoType, value: &Keyed, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+    match ty {
+        ProtoType::Bool => {
+            let Keyed::Bool(value) = value else {
+                return Err(TranscodeError::invalid("expected a boolean"));
+            };
+            buf.push(u8::from(*value));
+        }
+        ProtoType::Int32 | ProtoType::Enum => encode_varint(integer(value, i32::MIN.into(), i32::MAX.into())? as u64, buf),
+        ProtoType::Int64 => encode_varint(integer(value, i64::MIN.into(), i64::MAX.into())? as u64, buf),
+        ProtoType::Uint32 => encode_varint(integer(value, 0, u32::MAX.into())? as u64, buf),
+        ProtoType::Uint64 => encode_varint(integer(value, 0, u64::MAX.into())? as u64, buf),
+        ProtoType::Sint32 | ProtoType::Sint64 => {
+            let (min, max) = if matches!(ty, ProtoType::Sint32) {
+                (i32::MIN.into(), i32::MAX.into())
+            } else {
+                (i64::MIN.into(), i64::MAX.into())
+            };
+            let value = integer(value, min, max)? as i64;
+            encode_varint(((value << 1) ^ (value >> 63)) as u64, buf);
+        }
+        ProtoType::Fixed32 => buf.extend_from_slice(&(integer(value, 0, u32::MAX.into())? as u32).to_le_bytes()),
+        ProtoType::Sfixed32 => buf.extend_from_slice(&(integer(value, i32::MIN.into(), i32::MAX.into())? as i32).to_le_bytes()),
+        ProtoType::Fixed64 => buf.extend_from_slice(&(integer(value, 0, u64::MAX.into())? as u64).to_le_bytes()),
+        ProtoType::Sfixed64 => buf.extend_from_slice(&(integer(value, i64::MIN.into(), i64::MAX.into())? as i64).to_le_bytes()),
+        ProtoType::Float => buf.extend_from_slice(&(float(value)? as f32).to_le_bytes()),
+        ProtoType::Double => buf.extend_from_slice(&float(value)?.to_le_bytes()),
+        ProtoType::String => {
+            let Keyed::Text(text) = value else {
+                return Err(TranscodeError::invalid("expected a text string"));
+            };
+            encode_varint(text.len() as u64, buf);
+            buf.extend_from_slice(text.as_bytes());
+        }
+        ProtoType::Bytes => {
+            let Keyed::Bytes(bytes) = value else {
+                return Err(TranscodeError::invalid("expected a byte string"));
+            };
+            encode_varint(bytes.len() as u64, buf);
+            buf.extend_from_slice(bytes);
+        }
+        ProtoType::Optional(inner) | ProtoType::Repeated(inner) => return encode_scalar(*inner, value, buf),
+        ProtoType::Message(_) | ProtoType::Map { .. } | ProtoType::None => return Err(TranscodeError::invalid("unsupported field type")),
+    }
+    Ok(())
+}
+
+/// Integer checked against `min..=max`.
+fn integer(value: &Keyed, min: i128, max: i128) -> Result<i128, TranscodeError> {
+    match value {
+        Keyed::Int(number) if (min..=max).contains(number) => Ok(*number),
+        Keyed::Int(number) => Err(TranscodeError::invalid(format!("{number} is out of range"))),
+        _ => Err(TranscodeError::invalid("expected an integer")),
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn float(value: &Keyed) -> Result<f64, TranscodeError> {
+    match value {
+        Keyed::F32(number) => Ok(f64::from(*number)),
+        Keyed::F64(number) => Ok(*number),
+        Keyed::Int(number) => Ok(*number as f64),
+        _ => Err(TranscodeError::invalid("expected a number")),
+    }
+}
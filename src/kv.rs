@@ -0,0 +1,321 @@
+//! Order-preserving key encodings for key-value stores.
+//!
+//! Stores such as RocksDB or sled compare keys as byte strings. [`encode_key`] writes a [`KvKey`]
+//! so that comparing the bytes gives the same order as comparing the values, and values keep the
+//! normal protobuf encoding through [`encode_value`]:
+//!
+//! - unsigned integers are big-endian and fixed width, signed integers the same with the sign bit
+//!   flipped, floats in their IEEE total order;
+//! - strings and byte strings are escaped (`00` becomes `00 FF`) and end with `00 01`, so a shorter
+//!   string sorts before every string it is a prefix of;
+//! - `Option` writes `00` for `None` and `01` before the value;
+//! - tuples, arrays and `#[proto_message(kv_key)]` structs concatenate their parts in order, so
+//!   they sort by the first part, then the second, and so on. Simple enums sort by their
+//!   discriminant.
+//!
+//! ```rust,ignore
+//! #[proto_message(kv_key)]
+//! pub struct OrderKey {
+//!     pub customer: String,
+//!     pub placed_at: i64,
+//! }
+//!
+//! db.put(kv::encode_key(&key), kv::encode_value(&order))?;
+//! let key: OrderKey = kv::decode_key(&raw)?;
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::ProtoDecode;
+use crate::ProtoEncode;
+use crate::ProtoExt;
+
+/// A type with an order-preserving byte encoding.
+///
+/// Implemented for integers, floats, `bool`, `char`, strings, byte strings, `Option`, tuples and
+/// arrays, and derived with `#[proto_message(kv_key)]`.
+pub trait KvKey: Sized {
+    /// Appends the encoding of `self` to `out`.
+    fn write_key(&self, out: &mut Vec<u8>);
+
+    /// Reads one value from the front of `buf`, advancing it past the value.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DecodeError`] if `buf` does not start with a valid encoding.
+    fn read_key(buf: &mut &[u8]) -> Result<Self, DecodeError>;
+}
+
+/// Order-preserving encoding of `key`.
+pub fn encode_key<K: KvKey>(key: &K) -> Vec<u8> {
+    let mut out = Vec::new();
+    key.write_key(&mut out);
+    out
+}
+
+/// Decodes a key written by [`encode_key`].
+///
+/// # Errors
+///
+/// Returns a [`DecodeError`] if `bytes` is not exactly one encoded `K`.
+pub fn decode_key<K: KvKey>(mut bytes: &[u8]) -> Result<K, DecodeError> {
+    let key = K::read_key(&mut bytes)?;
+    if !bytes.is_empty() {
+        return Err(DecodeError::new("trailing bytes after key"));
+    }
+    Ok(key)
+}
+
+/// Protobuf encoding of a stored value.
+pub fn encode_value<T: ProtoEncode + ProtoExt>(value: &T) -> Vec<u8> {
+    value.encode_to_vec()
+}
+
+/// Decodes a value written by [`encode_value`].
+///
+/// # Errors
+///
+/// Returns a [`DecodeError`] if `bytes` is not a valid `T`.
+pub fn decode_value<T: ProtoDecode>(bytes: &[u8]) -> Result<T, DecodeError> {
+    <T as ProtoDecode>::decode(bytes, DecodeContext::default())
+}
+
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8], DecodeError> {
+    if buf.len() < len {
+        return Err(DecodeError::new("key ends early"));
+    }
+    let (head, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(head)
+}
+
+fn take_array<const N: usize>(buf: &mut &[u8]) -> Result<[u8; N], DecodeError> {
+    let mut array = [0u8; N];
+    array.copy_from_slice(take(buf, N)?);
+    Ok(array)
+}
+
+macro_rules! impl_unsigned {
+    ($($ty:ty),*) => {$(
+        impl KvKey for $ty {
+            #[inline]
+            fn write_key(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_be_bytes());
+            }
+
+            #[inline]
+            fn read_key(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+                Ok(<$ty>::from_be_bytes(take_array(buf)?))
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_signed {
+    ($($ty:ty => $unsigned:ty),*) => {$(
+        impl KvKey for $ty {
+            #[inline]
+            fn write_key(&self, out: &mut Vec<u8>) {
+                ((*self as $unsigned) ^ (1 << (<$unsigned>::BITS - 1))).write_key(out);
+            }
+
+            #[inline]
+            fn read_key(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+                Ok((<$unsigned>::read_key(buf)? ^ (1 << (<$unsigned>::BITS - 1))) as $ty)
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_float {
+    ($($ty:ty => $bits:ty),*) => {$(
+        impl KvKey for $ty {
+            #[inline]
+            fn write_key(&self, out: &mut Vec<u8>) {
+                let bits = self.to_bits();
+                let sign = 1 << (<$bits>::BITS - 1);
+                let ordered = if bits & sign == 0 { bits ^ sign } else { !bits };
+                ordered.write_key(out);
+            }
+
+            #[inline]
+            fn read_key(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+                let ordered = <$bits>::read_key(buf)?;
+                let sign = 1 << (<$bits>::BITS - 1);
+                let bits = if ordered & sign == 0 { !ordered } else { ordered ^ sign };
+                Ok(<$ty>::from_bits(bits))
+            }
+        }
+    )*};
+}
+
+impl_unsigned!(u8, u16, u32, u64, u128);
+impl_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128);
+impl_float!(f32 => u32, f64 => u64);
+
+impl KvKey for bool {
+    #[inline]
+    fn write_key(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(*self));
+    }
+
+    #[inline]
+    fn read_key(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        match take(buf, 1)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(DecodeError::new("invalid bool in key")),
+        }
+    }
+}
+
+impl KvKey for char {
+    #[inline]
+    fn write_key(&self, out: &mut Vec<u8>) {
+        u32::from(*self).write_key(out);
+    }
+
+    #[inline]
+    fn read_key(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        char::from_u32(u32::read_key(buf)?).ok_or_else(|| DecodeError::new("invalid char in key"))
+    }
+}
+
+const ESCAPE: u8 = 0x00;
+const ESCAPED_ZERO: u8 = 0xFF;
+const TERMINATOR: u8 = 0x01;
+
+fn write_escaped(bytes: &[u8], out: &mut Vec<u8>) {
+    for chunk in bytes.split_inclusive(|byte| *byte == ESCAPE) {
+        out.extend_from_slice(chunk);
+        if chunk.last() == Some(&ESCAPE) {
+            out.push(ESCAPED_ZERO);
+        }
+    }
+    out.extend_from_slice(&[ESCAPE, TERMINATOR]);
+}
+
+fn read_escaped(buf: &mut &[u8]) -> Result<Vec<u8>, DecodeError> {
+    let mut bytes = Vec::new();
+    loop {
+        let Some(zero) = buf.iter().position(|byte| *byte == ESCAPE) else {
+            return Err(DecodeError::new("unterminated string in key"));
+        };
+        bytes.extend_from_slice(&buf[..zero]);
+        match buf.get(zero + 1) {
+            Some(&TERMINATOR) => {
+                *buf = &buf[zero + 2..];
+                return Ok(bytes);
+            }
+            Some(&ESCAPED_ZERO) => {
+                bytes.push(ESCAPE);
+                *buf = &buf[zero + 2..];
+            }
+            _ => return Err(DecodeError::new("invalid escape in key")),
+        }
+    }
+}
+
+impl KvKey for String {
+    #[inline]
+    fn write_key(&self, out: &mut Vec<u8>) {
+        write_escaped(self.as_bytes(), out);
+    }
+
+    #[inline]
+    fn read_key(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        String::from_utf8(read_escaped(buf)?).map_err(|_| DecodeError::new("invalid UTF-8 in key"))
+    }
+}
+
+impl KvKey for Vec<u8> {
+    #[inline]
+    fn write_key(&self, out: &mut Vec<u8>) {
+        write_escaped(self, out);
+    }
+
+    #[inline]
+    fn read_key(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        read_escaped(buf)
+    }
+}
+
+impl<T: KvKey> KvKey for Option<T> {
+    #[inline]
+    fn write_key(&self, out: &mut Vec<u8>) {
+        match self {
+            None => out.push(0),
+            Some(value) => {
+                out.push(1);
+                value.write_key(out);
+            }
+        }
+    }
+
+    #[inline]
+    fn read_key(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        match take(buf, 1)?[0] {
+            0 => Ok(None),
+            1 => T::read_key(buf).map(Some),
+            _ => Err(DecodeError::new("invalid option in key")),
+        }
+    }
+}
+
+impl<T: KvKey, const N: usize> KvKey for [T; N] {
+    #[inline]
+    fn write_key(&self, out: &mut Vec<u8>) {
+        for item in self {
+            item.write_key(out);
+        }
+    }
+
+    #[inline]
+    fn read_key(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let mut items = Vec::with_capacity(N);
+        for _ in 0..N {
+            items.push(T::read_key(buf)?);
+        }
+        items.try_into().map_err(|_| DecodeError::new("array length mismatch in key"))
+    }
+}
+
+impl KvKey for () {
+    #[inline]
+    fn write_key(&self, _out: &mut Vec<u8>) {}
+
+    #[inline]
+    fn read_key(_buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok(())
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: KvKey),+> KvKey for ($($name,)+) {
+            #[inline]
+            #[allow(non_snake_case)]
+            fn write_key(&self, out: &mut Vec<u8>) {
+                let ($($name,)+) = self;
+                $($name.write_key(out);)+
+            }
+
+            #[inline]
+            fn read_key(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+                Ok(($($name::read_key(buf)?,)+))
+            }
+        }
+    };
+}
+
+impl_tuple!(A);
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+impl_tuple!(A, B, C, D, E);
+impl_tuple!(A, B, C, D, E, F);
+impl_tuple!(A, B, C, D, E, F, G);
+impl_tuple!(A, B, C, D, E, F, G, H);
@@ -1,4 +1,5 @@
-#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type, maybe_uninit_array_assume_init))]
+#![cfg_attr(not(feature = "stable"), feature(maybe_uninit_array_assume_init))]
+#![cfg_attr(feature = "allocator", feature(allocator_api))]
 #![allow(clippy::must_use_candidate)]
 #![allow(clippy::doc_markdown)]
 #![allow(clippy::cast_possible_truncation)]
@@ -15,20 +16,41 @@
 extern crate self as proto_rs;
 
 pub use prosto_derive::impl_proto_ident;
+pub use prosto_derive::import_proto;
 pub use prosto_derive::inject_proto_import;
 pub use prosto_derive::proto_dump;
 pub use prosto_derive::proto_message;
 pub use prosto_derive::proto_rpc;
+pub use prosto_derive::register_treat_as;
 pub use traits::ArchivedProtoField;
 pub use traits::ArchivedProtoMessage;
 pub use traits::ArchivedProtoMessageWriter;
 pub use traits::DecodeIrBuilder;
+pub use traits::ProtoArrayMerge;
+pub use traits::ProtoCollectMerge;
+pub use traits::ProtoLossyMerge;
+pub use traits::ProtoNarrowMerge;
 pub use traits::ProtoShadowDecode;
 pub use traits::ProtoShadowEncode;
+pub use traits::ProtoUniqueMerge;
 pub use traits::ZeroCopy;
+#[doc(hidden)]
+pub use traits::add_field_max_len;
+pub use traits::buffer::BufferStrategy;
+pub use traits::buffer::RevArray;
 pub use traits::buffer::RevVec;
 pub use traits::buffer::RevWriter;
 pub use traits::const_test_validate_with_ext;
+pub use wrappers::CollectedMap;
+pub use wrappers::MapView;
+#[cfg(feature = "papaya")]
+pub use wrappers::PapayaMapSnapshot;
+#[cfg(feature = "papaya")]
+pub use wrappers::PapayaSetSnapshot;
+#[cfg(feature = "allocator")]
+pub use wrappers::ProtoAllocator;
+pub use wrappers::ProtoView;
+pub use wrappers::SetView;
 
 #[cfg(not(feature = "no-recursion-limit"))]
 const RECURSION_LIMIT: u32 = 100;
@@ -40,8 +62,21 @@ pub extern crate alloc;
 pub extern crate std;
 
 // Re-export the bytes crate for use within derived code.
+// Re-export async-graphql for the derives `#[proto_message(graphql)]` adds.
+#[cfg(feature = "graphql")]
+pub use async_graphql;
 pub use bytes;
+// Re-export schemars for the `JsonSchema` impls of derived messages.
+#[cfg(feature = "schemars")]
+pub use schemars;
+// Re-export serde for the derives `#[proto_message(serde)]` adds.
+#[cfg(feature = "serde")]
+pub use serde;
 
+/// Conversions between repeated numeric fields and Arrow arrays
+/// Only available when "arrow" feature is enabled
+#[cfg(feature = "arrow")]
+pub mod arrow;
 mod coders;
 pub mod custom_rpc;
 pub mod custom_types;
@@ -50,40 +85,170 @@ mod tonic;
 mod types;
 mod wrappers;
 
+/// Field-level diff and patch of messages
+/// Only available when "diff" feature is enabled
+#[cfg(feature = "diff")]
+pub mod diff;
 #[doc(hidden)]
 pub mod encoding;
 mod error;
+/// Extension ranges of messages and extension fields declared by other crates
+pub mod extensions;
+/// Offsets indexes for random access into repeated message fields
+pub mod indexed;
+/// Shared `Arc<str>` strings for decoded `string` fields
+pub mod intern;
+/// Order-preserving key encodings for key-value stores
+pub mod kv;
+/// Wire-level rewriting of stored messages across schema changes
+/// Only available when "migrate" feature is enabled
+#[cfg(feature = "migrate")]
+pub mod migrate;
 mod name;
+/// AIP-158 page tokens and auto-pagination of list calls
+pub mod pagination;
+/// Consistent encodes of state shared through `ArcSwap`
+/// Only available when "arc_swap" feature is enabled
+#[cfg(feature = "arc_swap")]
+pub mod snapshot;
+/// Struct-of-arrays containers encoded as repeated message fields
+pub mod soa;
+/// Push-based decoding of length-delimited messages from partial buffers
+pub mod stream;
+#[cfg(feature = "wire-trace")]
+mod trace;
 mod traits;
+/// Hooks that see enum values the local definition does not know
+pub mod unknown_enum;
 
 /// Build-time proto schema registry
 /// Only available when "build-schemas" feature is enabled
 #[cfg(feature = "build-schemas")]
 pub mod schemas;
 
+/// Random valid messages for load tests and fuzz corpora
+/// Only available when "testkit" feature is enabled
+#[cfg(feature = "testkit")]
+pub mod testkit;
+
+/// Kafka record payloads for registered messages
+/// Only available when "kafka" feature is enabled
+#[cfg(feature = "kafka")]
+pub mod kafka;
+
+/// Length-delimited, checksummed record files for message logs
+/// Only available when "records" feature is enabled
+#[cfg(feature = "records")]
+pub mod records;
+
+/// JSON schemas of registered messages for `schemars`
+/// Only available when "schemars" feature is enabled
+#[cfg(feature = "schemars")]
+pub mod json_schema;
+
+/// Proto3 JSON <-> binary transcoding for gateways
+/// Only available when "transcode" feature is enabled
+#[cfg(feature = "transcode")]
+pub mod transcode;
+
+/// CBOR encoding of registered messages keyed by field number
+/// Only available when "cbor" feature is enabled
+#[cfg(feature = "cbor")]
+pub mod cbor;
+
+/// MessagePack encoding of registered messages keyed by field number
+/// Only available when "msgpack" feature is enabled
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+
+/// Messages in external caches, and the response cache of generated servers
+pub mod cache;
+
+/// Compact positional encoding of registered messages for local caches
+/// Only available when "compact" feature is enabled
+#[cfg(feature = "compact")]
+pub mod compact;
+
+/// Table rows of flat registered messages
+/// Only available when "sql" feature is enabled
+#[cfg(feature = "sql")]
+pub mod sql;
+
+#[cfg(any(feature = "cbor", feature = "compact", feature = "msgpack", feature = "sql"))]
+mod keyed;
+
 pub use crate::coders::BytesMode;
 pub use crate::coders::ProtoCodec;
 pub use crate::coders::ProtoEncoder;
 pub use crate::coders::SunByRef;
 pub use crate::coders::SunByVal;
+pub use crate::custom_types::AttributeMap;
+pub use crate::custom_types::AttributeValue;
+pub use crate::custom_types::FromAttribute;
+pub use crate::custom_types::MaybeUtf8;
+pub use crate::custom_types::ProtoResult;
+pub use crate::encoding::DecodeCancel;
 pub use crate::encoding::DecodeContext;
+pub use crate::encoding::MemoryBudget;
+pub use crate::encoding::length_delimiter::MAX_HEADER_LEN;
 pub use crate::encoding::length_delimiter::decode_length_delimiter;
 pub use crate::encoding::length_delimiter::encode_length_delimiter;
 pub use crate::encoding::length_delimiter::length_delimiter_len;
+pub use crate::encoding::length_delimiter::peek_len;
+pub use crate::error::DecodeAbort;
 pub use crate::error::DecodeError;
 pub use crate::error::EncodeError;
+pub use crate::error::ProtoError;
 pub use crate::error::UnknownEnumValue;
+pub use crate::extensions::Extendable;
+pub use crate::extensions::Extension;
+pub use crate::extensions::ExtensionSet;
 pub use crate::name::Name;
+pub use crate::soa::SoaColumns;
 #[cfg(feature = "tonic")]
 pub use crate::tonic::EncoderExt;
 #[cfg(feature = "tonic")]
+pub use crate::tonic::MethodDescriptor;
+#[cfg(feature = "tonic")]
 pub use crate::tonic::ProtoRequest;
 #[cfg(feature = "tonic")]
 pub use crate::tonic::ProtoResponse;
 #[cfg(feature = "tonic")]
+pub use crate::tonic::STACK_ENCODE_THRESHOLD;
+#[cfg(feature = "tonic")]
+pub use crate::tonic::auth;
+#[cfg(feature = "tonic")]
+pub use crate::tonic::bus;
+#[cfg(feature = "capture")]
+pub use crate::tonic::capture;
+#[cfg(feature = "tonic")]
+pub use crate::tonic::dedup;
+#[cfg(feature = "tonic")]
+pub use crate::tonic::dispatch;
+#[cfg(all(feature = "tonic", feature = "build-schemas", not(target_arch = "wasm32")))]
+pub use crate::tonic::forward;
+#[cfg(feature = "grpc-web")]
+pub use crate::tonic::grpc_web;
+#[cfg(feature = "http3")]
+pub use crate::tonic::http3;
+#[cfg(feature = "tonic")]
+pub use crate::tonic::limit;
+#[cfg(feature = "tonic")]
 pub use crate::tonic::map_proto_response;
 #[cfg(feature = "tonic")]
 pub use crate::tonic::map_proto_stream_result;
+#[cfg(all(feature = "tonic", not(target_arch = "wasm32")))]
+pub use crate::tonic::registry;
+#[cfg(feature = "tonic")]
+pub use crate::tonic::response_stream;
+#[cfg(feature = "tonic")]
+pub use crate::tonic::shutdown;
+#[cfg(feature = "tonic")]
+pub use crate::tonic::sizes;
+#[cfg(all(feature = "tonic", not(target_arch = "wasm32")))]
+pub use crate::tonic::transport;
+#[cfg(feature = "websocket")]
+pub use crate::tonic::websocket;
 pub use crate::traits::ProtoArchive;
 pub use crate::traits::ProtoDecode;
 pub use crate::traits::ProtoDecoder;
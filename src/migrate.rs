@@ -0,0 +1,316 @@
+//! Rewriting stored messages after a schema refactor, without decoding them into Rust types.
+//!
+//! A [`MigrationPlan`] lists what happened to the fields of the old message, keyed by their old
+//! tag: renumbered, widened to a 64-bit type, moved into a nested message, dropped, or left in place
+//! with a plan for the message they hold. [`rewrite`] walks the old encoding one field at a time and
+//! writes the new one; fields the plan does not mention are copied byte for byte, so unknown and
+//! unchanged fields survive the migration untouched.
+//!
+//! ```rust,ignore
+//! let plan = MigrationPlan::new()
+//!     .renumber(2, 7)
+//!     .widen(3, Widen::Int32ToInt64)
+//!     .move_into(4, 9, 1)
+//!     .nested(5, MigrationPlan::new().drop_field(1));
+//! let upgraded = migrate::rewrite(&stored, &plan)?;
+//! ```
+
+use std::collections::BTreeMap;
+
+use bytes::Buf;
+
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::encoding::MAX_TAG;
+use crate::encoding::MIN_TAG;
+use crate::encoding::WireType;
+use crate::encoding::decode_key;
+use crate::encoding::decode_varint;
+use crate::encoding::encode_key;
+use crate::encoding::encode_varint;
+use crate::encoding::skip_field;
+
+/// A 32-bit field type changed to its 64-bit counterpart.
+///
+/// Each value is read the way a decoder of the old type reads it and written as the same number
+/// in the new type. Packed repeated fields are converted element by element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Widen {
+    /// `int32` to `int64`
+    Int32ToInt64,
+    /// `uint32` to `uint64`
+    UInt32ToUInt64,
+    /// `sint32` to `sint64`
+    SInt32ToSInt64,
+    /// `fixed32` to `fixed64`
+    Fixed32ToFixed64,
+    /// `sfixed32` to `sfixed64`
+    SFixed32ToSFixed64,
+    /// `float` to `double`
+    FloatToDouble,
+}
+
+impl Widen {
+    const fn is_varint(self) -> bool {
+        matches!(self, Widen::Int32ToInt64 | Widen::UInt32ToUInt64 | Widen::SInt32ToSInt64)
+    }
+
+    fn varint(self, value: u64) -> u64 {
+        match self {
+            Widen::Int32ToInt64 => i64::from(value as i32) as u64,
+            Widen::SInt32ToSInt64 => {
+                let value = value as u32;
+                let value = i64::from((value >> 1) as i32 ^ -((value & 1) as i32));
+                ((value << 1) ^ (value >> 63)) as u64
+            }
+            _ => u64::from(value as u32),
+        }
+    }
+
+    fn fixed(self, value: [u8; 4]) -> [u8; 8] {
+        match self {
+            Widen::SFixed32ToSFixed64 => i64::from(i32::from_le_bytes(value)).to_le_bytes(),
+            Widen::FloatToDouble => f64::from(f32::from_le_bytes(value)).to_le_bytes(),
+            _ => u64::from(u32::from_le_bytes(value)).to_le_bytes(),
+        }
+    }
+}
+
+/// Where a field of the old message ends up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Tag(u32),
+    Nested { parent: u32, tag: u32 },
+    Drop,
+}
+
+#[derive(Debug, Clone)]
+struct FieldRule {
+    target: Target,
+    widen: Option<Widen>,
+    nested: Option<MigrationPlan>,
+}
+
+/// How each field of an old message maps onto the new one, keyed by old tag.
+///
+/// Every builder method applies to one old tag and can be combined with the others for the same
+/// tag, e.g. a field can be widened and moved. Tags the plan does not mention are copied as they
+/// are.
+#[derive(Debug, Clone, Default)]
+pub struct MigrationPlan {
+    fields: BTreeMap<u32, FieldRule>,
+}
+
+impl MigrationPlan {
+    /// A plan that changes nothing.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn rule(&mut self, tag: u32) -> &mut FieldRule {
+        assert_tag(tag);
+        self.fields.entry(tag).or_insert(FieldRule {
+            target: Target::Tag(tag),
+            widen: None,
+            nested: None,
+        })
+    }
+
+    /// Writes field `from` under tag `to`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either tag is not a valid field number.
+    #[must_use]
+    pub fn renumber(mut self, from: u32, to: u32) -> Self {
+        assert_tag(to);
+        self.rule(from).target = Target::Tag(to);
+        self
+    }
+
+    /// Converts field `tag` to the wider type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tag` is not a valid field number.
+    #[must_use]
+    pub fn widen(mut self, tag: u32, widen: Widen) -> Self {
+        self.rule(tag).widen = Some(widen);
+        self
+    }
+
+    /// Moves field `tag` into the message field `parent` of the new message, where it is written
+    /// under `new_tag`.
+    ///
+    /// All fields moved into the same parent are written as one message after the remaining
+    /// fields. Decoders merge it with any `parent` value already in the message, so `parent` must
+    /// be a singular message field of the new schema.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any tag is not a valid field number.
+    #[must_use]
+    pub fn move_into(mut self, tag: u32, parent: u32, new_tag: u32) -> Self {
+        assert_tag(parent);
+        assert_tag(new_tag);
+        self.rule(tag).target = Target::Nested { parent, tag: new_tag };
+        self
+    }
+
+    /// Removes field `tag` from the message.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tag` is not a valid field number.
+    #[must_use]
+    pub fn drop_field(mut self, tag: u32) -> Self {
+        self.rule(tag).target = Target::Drop;
+        self
+    }
+
+    /// Rewrites the messages held by field `tag` with `plan`. Works for singular, repeated and map
+    /// value fields alike, since each occurrence is one message.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tag` is not a valid field number.
+    #[must_use]
+    pub fn nested(mut self, tag: u32, plan: MigrationPlan) -> Self {
+        self.rule(tag).nested = Some(plan);
+        self
+    }
+}
+
+fn assert_tag(tag: u32) {
+    assert!((MIN_TAG..=MAX_TAG).contains(&tag), "invalid field number {tag}");
+}
+
+/// Rewrites one encoded message of the old schema into the new one.
+///
+/// # Errors
+///
+/// Returns a [`DecodeError`] if `bytes` is not a valid message, if a widened field has a wire type
+/// its old type cannot have, or if a field the plan changes is a group.
+pub fn rewrite(bytes: &[u8], plan: &MigrationPlan) -> Result<Vec<u8>, DecodeError> {
+    let mut out = Vec::with_capacity(bytes.len());
+    rewrite_message(bytes, plan, DecodeContext::default(), &mut out)?;
+    Ok(out)
+}
+
+fn rewrite_message(mut buf: &[u8], plan: &MigrationPlan, ctx: DecodeContext, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+    ctx.limit_reached()?;
+    // Parent tag -> fields moved into it, in the order the parents are first seen
+    let mut moved: Vec<(u32, Vec<u8>)> = Vec::new();
+
+    while buf.has_remaining() {
+        let field = buf;
+        let (tag, wire_type) = decode_key(&mut buf)?;
+        let value = buf;
+        skip_field(wire_type, tag, &mut buf, ctx)?;
+        let value = &value[..value.len() - buf.len()];
+
+        let Some(rule) = plan.fields.get(&tag) else {
+            out.extend_from_slice(&field[..field.len() - buf.len()]);
+            continue;
+        };
+        if wire_type == WireType::StartGroup {
+            return Err(DecodeError::new(format!("field {tag}: groups cannot be migrated")));
+        }
+        match rule.target {
+            Target::Drop => {}
+            Target::Tag(new_tag) => rewrite_field(rule, tag, new_tag, wire_type, value, ctx, out)?,
+            Target::Nested { parent, tag: new_tag } => {
+                let idx = if let Some(idx) = moved.iter().position(|(existing, _)| *existing == parent) {
+                    idx
+                } else {
+                    moved.push((parent, Vec::new()));
+                    moved.len() - 1
+                };
+                rewrite_field(rule, tag, new_tag, wire_type, value, ctx, &mut moved[idx].1)?;
+            }
+        }
+    }
+
+    for (parent, fields) in moved {
+        write_len_delimited(parent, &fields, out);
+    }
+    Ok(())
+}
+
+/// Writes the value of old field `tag` under `new_tag`, converted as `rule` says.
+fn rewrite_field(
+    rule: &FieldRule,
+    tag: u32,
+    new_tag: u32,
+    wire_type: WireType,
+    mut value: &[u8],
+    ctx: DecodeContext,
+    out: &mut Vec<u8>,
+) -> Result<(), DecodeError> {
+    if let Some(plan) = &rule.nested {
+        if wire_type != WireType::LengthDelimited {
+            return Err(DecodeError::new(format!("field {tag}: expected a message, found {wire_type:?}")));
+        }
+        let payload = length_delimited(value)?;
+        let mut nested = Vec::with_capacity(payload.len());
+        rewrite_message(payload, plan, ctx.enter_recursion(), &mut nested)?;
+        write_len_delimited(new_tag, &nested, out);
+        return Ok(());
+    }
+
+    let Some(widen) = rule.widen else {
+        encode_key(new_tag, wire_type, out);
+        out.extend_from_slice(value);
+        return Ok(());
+    };
+
+    match (wire_type, widen.is_varint()) {
+        (WireType::Varint, true) => {
+            encode_key(new_tag, WireType::Varint, out);
+            encode_varint(widen.varint(decode_varint(&mut value)?), out);
+        }
+        (WireType::ThirtyTwoBit, false) => {
+            encode_key(new_tag, WireType::SixtyFourBit, out);
+            out.extend_from_slice(&widen.fixed(value.try_into().map_err(|_| DecodeError::new("buffer underflow"))?));
+        }
+        (WireType::LengthDelimited, true) => {
+            let mut payload = length_delimited(value)?;
+            let mut packed = Vec::with_capacity(payload.len());
+            while payload.has_remaining() {
+                encode_varint(widen.varint(decode_varint(&mut payload)?), &mut packed);
+            }
+            write_len_delimited(new_tag, &packed, out);
+        }
+        (WireType::LengthDelimited, false) => {
+            let payload = length_delimited(value)?;
+            let chunks = payload.chunks_exact(4);
+            if !chunks.remainder().is_empty() {
+                return Err(DecodeError::new(format!("field {tag}: packed length is not a multiple of 4")));
+            }
+            let mut packed = Vec::with_capacity(payload.len() * 2);
+            for chunk in chunks {
+                packed.extend_from_slice(&widen.fixed([chunk[0], chunk[1], chunk[2], chunk[3]]));
+            }
+            write_len_delimited(new_tag, &packed, out);
+        }
+        _ => {
+            return Err(DecodeError::new(format!(
+                "field {tag}: cannot apply {widen:?} to a {wire_type:?} field"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Payload of a length-delimited value whose bounds `skip_field` already checked.
+fn length_delimited(mut value: &[u8]) -> Result<&[u8], DecodeError> {
+    decode_varint(&mut value)?;
+    Ok(value)
+}
+
+fn write_len_delimited(tag: u32, payload: &[u8], out: &mut Vec<u8>) {
+    encode_key(tag, WireType::LengthDelimited, out);
+    encode_varint(payload.len() as u64, out);
+    out.extend_from_slice(payload);
+}
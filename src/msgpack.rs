@@ -0,0 +1,81 @@
+//! MessagePack encoding of registered messages, for peers that share the message definitions
+//! but do not speak protobuf.
+//!
+//! The layout is the one of [`cbor`](crate::cbor): a message is a map from field number to
+//! value, so `Order { id: 7, .. }` starts with `81 01 07`. `bytes` fields are `bin` values and
+//! `float` fields stay 32-bit. Decoding skips field numbers the message does not know.
+//!
+//! ```rust,ignore
+//! let bytes = proto_rs::msgpack::to_msgpack(&order)?;
+//! let order: Order = proto_rs::msgpack::from_msgpack(&bytes)?;
+//! ```
+
+use rmpv::Value;
+
+use crate::ProtoDecode;
+use crate::ProtoEncode;
+use crate::keyed::Keyed;
+use crate::keyed::from_keyed;
+use crate::keyed::to_keyed;
+use crate::schemas::ProtoIdentifiable;
+use crate::traits::ProtoExt;
+use crate::transcode::TranscodeError;
+
+/// MessagePack encoding of `value`.
+///
+/// # Errors
+///
+/// Returns [`TranscodeError::UnknownMessage`] if `T` has no registered message schema.
+pub fn to_msgpack<T: ProtoEncode + ProtoExt + ProtoIdentifiable>(value: &T) -> Result<Vec<u8>, TranscodeError> {
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &to_value(to_keyed(value)?)).map_err(|error| TranscodeError::Malformed(error.to_string()))?;
+    Ok(buf)
+}
+
+/// Message of type `T` from its MessagePack encoding.
+///
+/// # Errors
+///
+/// Returns [`TranscodeError::Malformed`] if `bytes` are not one MessagePack value and
+/// [`TranscodeError::Invalid`] if the value does not match the schema.
+pub fn from_msgpack<T: ProtoDecode + ProtoIdentifiable>(mut bytes: &[u8]) -> Result<T, TranscodeError> {
+    let value = rmpv::decode::read_value(&mut bytes).map_err(|error| TranscodeError::Malformed(error.to_string()))?;
+    if !bytes.is_empty() {
+        return Err(TranscodeError::Malformed("trailing bytes after the value".to_string()));
+    }
+    from_keyed(&from_value(value)?)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn to_value(keyed: Keyed) -> Value {
+    match keyed {
+        Keyed::Null => Value::Nil,
+        Keyed::Bool(value) => Value::Boolean(value),
+        // Field values fit in 64 bits
+        Keyed::Int(value) if value < 0 => Value::from(value as i64),
+        Keyed::Int(value) => Value::from(value as u64),
+        Keyed::F32(value) => Value::F32(value),
+        Keyed::F64(value) => Value::F64(value),
+        Keyed::Text(value) => Value::from(value),
+        Keyed::Bytes(value) => Value::Binary(value),
+        Keyed::Array(items) => Value::Array(items.into_iter().map(to_value).collect()),
+        Keyed::Map(entries) => Value::Map(entries.into_iter().map(|(key, value)| (to_value(key), to_value(value))).collect()),
+    }
+}
+
+fn from_value(value: Value) -> Result<Keyed, TranscodeError> {
+    Ok(match value {
+        Value::Nil => Keyed::Null,
+        Value::Boolean(value) => Keyed::Bool(value),
+        Value::Integer(value) => Keyed::Int(value.as_i64().map(i128::from).or_else(|| value.as_u64().map(i128::from)).unwrap_or_default()),
+        Value::F32(value) => Keyed::F32(value),
+        Value::F64(value) => Keyed::F64(value),
+        Value::String(value) => Keyed::Text(value.into_str().ok_or_else(|| TranscodeError::Malformed("string is not UTF-8".to_string()))?),
+        Value::Binary(value) => Keyed::Bytes(value),
+        Value::Array(items) => Keyed::Array(items.into_iter().map(from_value).collect::<Result<_, _>>()?),
+        Value::Map(entries) => Keyed::Map(
+            entries.into_iter().map(|(key, value)| Ok((from_value(key)?, from_value(value)?))).collect::<Result<_, TranscodeError>>()?,
+        ),
+        Value::Ext(..) => return Err(TranscodeError::Malformed("unsupported MessagePack extension value".to_string())),
+    })
+}
@@ -0,0 +1,137 @@
+//! AIP-158 pagination.
+//!
+//! `#[proto_message]` recognizes the standard list shapes and implements the traits below for
+//! them: a `page_token: String` field makes a [`PageRequest`], and a `next_page_token: String`
+//! field next to exactly one repeated field makes a [`PageResponse`]. For every unary rpc whose
+//! messages form such a pair, the generated client's `<method>_auto_paginate` streams the items of
+//! all pages:
+//!
+//! ```rust,ignore
+//! let mut books = client.list_books_auto_paginate(ListBooksRequest { page_size: 50, ..Default::default() });
+//! while let Some(book) = books.next().await {
+//!     index(book?);
+//! }
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Request of a paginated list call.
+pub trait PageRequest {
+    /// Sets the token of the page to fetch; empty for the first page.
+    fn set_page_token(&mut self, token: String);
+}
+
+/// Response of a paginated list call.
+pub trait PageResponse {
+    type Item;
+
+    /// Token of the next page; empty on the last page.
+    fn next_page_token(&self) -> &str;
+
+    fn into_items(self) -> Vec<Self::Item>;
+}
+
+#[cfg(feature = "tonic")]
+pub use self::client::Paginate;
+#[cfg(feature = "tonic")]
+pub use self::client::auto_paginate;
+
+#[cfg(feature = "tonic")]
+mod client {
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
+    use alloc::vec::IntoIter;
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::Context;
+    use core::task::Poll;
+
+    use tonic::Response;
+    use tonic::Status;
+    use tonic::codegen::tokio_stream::Stream;
+
+    use super::PageRequest;
+    use super::PageResponse;
+
+    /// Calls `call` with `request`, then with a copy carrying each `next_page_token` until one is
+    /// empty, yielding the items of every page in order. The first error ends the stream.
+    pub fn auto_paginate<Req, Resp, F, Fut>(request: Req, call: F) -> Paginate<Req, Resp, F, Fut>
+    where
+        Req: PageRequest + Clone,
+        Resp: PageResponse,
+        F: FnMut(Req) -> Fut,
+        Fut: Future<Output = Result<Response<Resp>, Status>>,
+    {
+        Paginate {
+            template: request.clone(),
+            next: Some(request),
+            call,
+            pending: None,
+            items: Vec::new().into_iter(),
+        }
+    }
+
+    /// Stream returned by [`auto_paginate`].
+    pub struct Paginate<Req, Resp: PageResponse, F, Fut> {
+        template: Req,
+        next: Option<Req>,
+        call: F,
+        pending: Option<Pin<Box<Fut>>>,
+        items: IntoIter<Resp::Item>,
+    }
+
+    // Nothing is pinned structurally: the page call in flight is boxed.
+    impl<Req, Resp: PageResponse, F, Fut> Unpin for Paginate<Req, Resp, F, Fut> {}
+
+    impl<Req, Resp: PageResponse, F, Fut> core::fmt::Debug for Paginate<Req, Resp, F, Fut> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.debug_struct("Paginate")
+                .field("buffered", &self.items.len())
+                .field("fetching", &self.pending.is_some())
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl<Req, Resp, F, Fut> Stream for Paginate<Req, Resp, F, Fut>
+    where
+        Req: PageRequest + Clone,
+        Resp: PageResponse,
+        F: FnMut(Req) -> Fut,
+        Fut: Future<Output = Result<Response<Resp>, Status>>,
+    {
+        type Item = Result<Resp::Item, Status>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.get_mut();
+            loop {
+                if let Some(item) = this.items.next() {
+                    return Poll::Ready(Some(Ok(item)));
+                }
+                if let Some(pending) = &mut this.pending {
+                    let page = match pending.as_mut().poll(cx) {
+                        Poll::Pending => return Poll::Pending,
+                        Poll::Ready(page) => page,
+                    };
+                    this.pending = None;
+                    let page = match page {
+                        Ok(page) => page.into_inner(),
+                        Err(status) => return Poll::Ready(Some(Err(status))),
+                    };
+                    let token = page.next_page_token();
+                    if !token.is_empty() {
+                        let mut next = this.template.clone();
+                        next.set_page_token(token.to_string());
+                        this.next = Some(next);
+                    }
+                    this.items = page.into_items().into_iter();
+                    continue;
+                }
+                match this.next.take() {
+                    Some(request) => this.pending = Some(Box::pin((this.call)(request))),
+                    None => return Poll::Ready(None),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,369 @@
+//! A framed file format for durable message logs.
+//!
+//! A record file starts with an 8-byte magic and a version byte, followed by blocks. Each block is
+//! a 9-byte header — a compression byte, the stored length and the CRC-32 of the stored bytes,
+//! both little-endian `u32` — and the stored bytes. Uncompressed, those are the block's records,
+//! each a varint length followed by one encoded message; with `records-zstd` a block can be stored
+//! as one zstd frame of the same bytes.
+//!
+//! [`Writer`] collects records into a block until it reaches the block size, so a crash loses at
+//! most the records written since the last full block or [`Writer::flush`]. [`Reader`] checks
+//! every block against its CRC and reports a cut-off last block as [`RecordError::Truncated`].
+//!
+//! ```rust,ignore
+//! let mut writer = records::Writer::new(File::create("orders.log")?)?;
+//! writer.write(&order)?;
+//! writer.finish()?;
+//!
+//! let reader = records::Reader::new(File::open("orders.log")?)?;
+//! for order in reader.messages::<Order>() {
+//!     replay(order?);
+//! }
+//! ```
+
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::marker::PhantomData;
+
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::ProtoDecode;
+use crate::ProtoEncode;
+use crate::ProtoExt;
+use crate::encoding::decode_varint;
+use crate::encoding::encode_varint;
+
+/// First bytes of every record file.
+pub const MAGIC: [u8; 8] = *b"PROTOREC";
+/// Version of the block layout written after [`MAGIC`].
+pub const VERSION: u8 = 1;
+/// Block size used unless [`Writer::block_size`] sets another one.
+pub const DEFAULT_BLOCK_SIZE: usize = 64 * 1024;
+
+const BLOCK_HEADER_LEN: usize = 9;
+const STORED_RAW: u8 = 0;
+const STORED_ZSTD: u8 = 1;
+
+/// Failure to read or write a record file.
+#[derive(Debug)]
+pub enum RecordError {
+    /// Reading or writing the underlying stream failed.
+    Io(io::Error),
+    /// The file ends inside a block, e.g. after a crash while it was written.
+    Truncated,
+    /// The file is not a record file, or a block does not match its checksum.
+    Corrupt(String),
+    /// A record is not a valid message of the requested type.
+    Decode(DecodeError),
+}
+
+impl core::fmt::Display for RecordError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RecordError::Io(error) => core::fmt::Display::fmt(error, f),
+            RecordError::Truncated => f.write_str("record file ends inside a block"),
+            RecordError::Corrupt(reason) => write!(f, "corrupt record file: {reason}"),
+            RecordError::Decode(error) => core::fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+impl core::error::Error for RecordError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            RecordError::Io(error) => Some(error),
+            RecordError::Decode(error) => Some(error),
+            RecordError::Truncated | RecordError::Corrupt(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for RecordError {
+    fn from(error: io::Error) -> RecordError {
+        RecordError::Io(error)
+    }
+}
+
+impl From<DecodeError> for RecordError {
+    fn from(error: DecodeError) -> RecordError {
+        RecordError::Decode(error)
+    }
+}
+
+/// Appends messages to a record file.
+///
+/// Records are buffered until the current block is full; call [`flush`](Self::flush) to make the
+/// records written so far durable and [`finish`](Self::finish) when done. Records still buffered
+/// when the writer is dropped are lost.
+#[derive(Debug)]
+pub struct Writer<W: Write> {
+    inner: W,
+    block: Vec<u8>,
+    block_size: usize,
+    #[cfg(feature = "records-zstd")]
+    zstd_level: Option<i32>,
+}
+
+impl<W: Write> Writer<W> {
+    /// Starts a record file on `inner` by writing its header.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of writing the header.
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        inner.write_all(&MAGIC)?;
+        inner.write_all(&[VERSION])?;
+        Ok(Self {
+            inner,
+            block: Vec::new(),
+            block_size: DEFAULT_BLOCK_SIZE,
+            #[cfg(feature = "records-zstd")]
+            zstd_level: None,
+        })
+    }
+
+    /// Writes a block once its records reach `size` bytes. `0` writes every record as its own
+    /// block.
+    #[must_use]
+    pub const fn block_size(mut self, size: usize) -> Self {
+        self.block_size = size;
+        self
+    }
+
+    /// Compresses each block with zstd at `level`.
+    #[cfg(feature = "records-zstd")]
+    #[must_use]
+    pub const fn zstd(mut self, level: i32) -> Self {
+        self.zstd_level = Some(level);
+        self
+    }
+
+    /// Appends one message.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of writing a full block.
+    pub fn write<T: ProtoEncode + ProtoExt>(&mut self, message: &T) -> io::Result<()> {
+        self.write_encoded(&message.encode_to_vec())
+    }
+
+    /// Appends one already encoded message.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of writing a full block.
+    pub fn write_encoded(&mut self, message: &[u8]) -> io::Result<()> {
+        encode_varint(message.len() as u64, &mut self.block);
+        self.block.extend_from_slice(message);
+        if self.block.len() >= self.block_size {
+            self.write_block()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the buffered records as a block and flushes the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of writing or flushing.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.write_block()?;
+        self.inner.flush()
+    }
+
+    /// Flushes the buffered records and returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of writing or flushing.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_block(&mut self) -> io::Result<()> {
+        if self.block.is_empty() {
+            return Ok(());
+        }
+        #[cfg(feature = "records-zstd")]
+        let compressed = self.zstd_level.map(|level| zstd::bulk::compress(&self.block, level)).transpose()?;
+        #[cfg(feature = "records-zstd")]
+        let (stored_as, stored) = match &compressed {
+            Some(compressed) => (STORED_ZSTD, compressed.as_slice()),
+            None => (STORED_RAW, self.block.as_slice()),
+        };
+        #[cfg(not(feature = "records-zstd"))]
+        let (stored_as, stored) = (STORED_RAW, self.block.as_slice());
+        let len = u32::try_from(stored.len()).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "block exceeds 4 GiB"))?;
+
+        let mut header = [0u8; BLOCK_HEADER_LEN];
+        header[0] = stored_as;
+        header[1..5].copy_from_slice(&len.to_le_bytes());
+        header[5..9].copy_from_slice(&crc32fast::hash(stored).to_le_bytes());
+        self.inner.write_all(&header)?;
+        self.inner.write_all(stored)?;
+        self.block.clear();
+        Ok(())
+    }
+}
+
+/// Reads the records of a record file in the order they were written.
+#[derive(Debug)]
+pub struct Reader<R: Read> {
+    inner: R,
+    block: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: Read> Reader<R> {
+    /// Opens a record file by checking its header.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecordError::Corrupt`] if `inner` does not start with a record file header of a
+    /// known version.
+    pub fn new(mut inner: R) -> Result<Self, RecordError> {
+        let mut header = [0u8; MAGIC.len() + 1];
+        if !read_full(&mut inner, &mut header)? {
+            return Err(RecordError::Corrupt("missing file header".into()));
+        }
+        if header[..MAGIC.len()] != MAGIC {
+            return Err(RecordError::Corrupt("not a record file".into()));
+        }
+        if header[MAGIC.len()] != VERSION {
+            return Err(RecordError::Corrupt(format!("unsupported version {}", header[MAGIC.len()])));
+        }
+        Ok(Self {
+            inner,
+            block: Vec::new(),
+            pos: 0,
+        })
+    }
+
+    /// The next encoded message, or `None` at the end of the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecordError::Truncated`] if the file ends inside a block and
+    /// [`RecordError::Corrupt`] if a block does not match its checksum.
+    pub fn next_encoded(&mut self) -> Result<Option<&[u8]>, RecordError> {
+        while self.pos == self.block.len() {
+            if !self.read_block()? {
+                return Ok(None);
+            }
+        }
+        let mut rest = &self.block[self.pos..];
+        let len = decode_varint(&mut rest).map_err(|_| RecordError::Corrupt("bad record length".into()))?;
+        let start = self.block.len() - rest.len();
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| start.checked_add(len))
+            .filter(|end| *end <= self.block.len())
+            .ok_or_else(|| RecordError::Corrupt("record overruns its block".into()))?;
+        self.pos = end;
+        Ok(Some(&self.block[start..end]))
+    }
+
+    /// The next message, or `None` at the end of the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`next_encoded`](Self::next_encoded), and [`RecordError::Decode`] if
+    /// the record is not a valid `T`.
+    pub fn read<T: ProtoDecode>(&mut self) -> Result<Option<T>, RecordError> {
+        match self.next_encoded()? {
+            Some(bytes) => Ok(Some(<T as ProtoDecode>::decode(bytes, DecodeContext::default())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Iterator over the remaining messages.
+    pub fn messages<T: ProtoDecode>(self) -> Messages<R, T> {
+        Messages {
+            reader: self,
+            failed: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Loads the next block, returning `false` at a clean end of file.
+    fn read_block(&mut self) -> Result<bool, RecordError> {
+        let mut header = [0u8; BLOCK_HEADER_LEN];
+        if !read_full(&mut self.inner, &mut header)? {
+            return Ok(false);
+        }
+        let len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]);
+        let crc = u32::from_le_bytes([header[5], header[6], header[7], header[8]]);
+
+        let mut stored = Vec::new();
+        (&mut self.inner).take(u64::from(len)).read_to_end(&mut stored)?;
+        if stored.len() != len as usize {
+            return Err(RecordError::Truncated);
+        }
+        if crc32fast::hash(&stored) != crc {
+            return Err(RecordError::Corrupt("block checksum mismatch".into()));
+        }
+
+        self.block = match header[0] {
+            STORED_RAW => stored,
+            STORED_ZSTD => decompress(&stored)?,
+            other => return Err(RecordError::Corrupt(format!("unknown block compression {other}"))),
+        };
+        self.pos = 0;
+        Ok(true)
+    }
+}
+
+#[cfg(feature = "records-zstd")]
+fn decompress(stored: &[u8]) -> Result<Vec<u8>, RecordError> {
+    zstd::stream::decode_all(stored).map_err(|error| RecordError::Corrupt(format!("bad zstd block: {error}")))
+}
+
+#[cfg(not(feature = "records-zstd"))]
+fn decompress(_stored: &[u8]) -> Result<Vec<u8>, RecordError> {
+    Err(RecordError::Corrupt(
+        "zstd block; enable the records-zstd feature to read it".into(),
+    ))
+}
+
+/// Fills `buf`, returning `false` if the stream ended before its first byte.
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> Result<bool, RecordError> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(RecordError::Truncated),
+            Ok(read) => filled += read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error.into()),
+        }
+    }
+    Ok(true)
+}
+
+/// Iterator over the messages of a [`Reader`]; stops after the first error.
+#[derive(Debug)]
+pub struct Messages<R: Read, T> {
+    reader: Reader<R>,
+    failed: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<R: Read, T: ProtoDecode> Iterator for Messages<R, T> {
+    type Item = Result<T, RecordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let next = self.reader.read().transpose();
+        self.failed = matches!(next, Some(Err(_)));
+        next
+    }
+}
@@ -6,9 +6,35 @@ use std::io;
 use std::path::Path;
 use std::sync::LazyLock;
 
+mod buf;
+mod conflicts;
+pub mod descriptor;
+mod envelope;
+mod jvm;
+mod options;
+mod preview;
 mod proto_output;
+pub mod python;
 mod rust_client;
+mod scrub;
+mod type_registry;
 mod utils;
+mod verify;
+
+pub use buf::BufLayout;
+pub use conflicts::ConflictingDefinition;
+pub use conflicts::SchemaConflict;
+pub use conflicts::conflicts;
+pub use envelope::SelfDescribing;
+pub use jvm::JvmLayout;
+pub use preview::Preview;
+pub use preview::preview;
+pub use scrub::ScrubPolicy;
+pub use scrub::scrub;
+pub use type_registry::TypeRegistry;
+pub use verify::MismatchKind;
+pub use verify::SchemaMismatch;
+pub use verify::verify_against;
 
 /// Represents a proto schema collected at compile time
 #[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
@@ -17,9 +43,24 @@ pub struct ProtoSchema {
     pub generics: &'static [Generic],
     pub lifetimes: &'static [Lifetime],
     pub top_level_attributes: &'static [Attribute],
+    /// `#[proto(option("pkg.name") = value)]` on the type.
+    pub options: &'static [ProtoOption],
+    /// Inclusive field number ranges from `#[proto(extensions = "...")]`, written as
+    /// `extensions 100 to 199;`.
+    pub extension_ranges: &'static [(u32, u32)],
+    /// Field numbers written as `reserved 15;`, the offsets indexes of
+    /// `#[proto(offset_index = N)]` fields.
+    pub reserved_tags: &'static [u32],
     pub content: ProtoEntry,
 }
 
+impl ProtoSchema {
+    /// Proto literal of the `#[proto(option("<name>") = ...)]` custom option on the type.
+    pub fn option(&self, name: &str) -> Option<&'static str> {
+        find_option(self.options, name)
+    }
+}
+
 pub struct RustClientCtx<'a> {
     pub output_path: Option<&'a str>,
     pub imports: &'a [&'a str],
@@ -31,6 +72,9 @@ pub struct RustClientCtx<'a> {
     pub type_replacements: BTreeMap<ProtoIdent, Vec<TypeReplace>>,
     pub split_modules: BTreeMap<String, String>,
     pub only_these_modules: Option<BTreeMap<String, String>>,
+    pub import_remaps: BTreeMap<String, String>,
+    pub buf_layout: Option<BufLayout>,
+    pub jvm_layout: Option<JvmLayout>,
 }
 
 impl<'a> RustClientCtx<'a> {
@@ -46,6 +90,9 @@ impl<'a> RustClientCtx<'a> {
             type_replacements: BTreeMap::new(),
             split_modules: BTreeMap::new(),
             only_these_modules: None,
+            import_remaps: BTreeMap::new(),
+            buf_layout: None,
+            jvm_layout: None,
         }
     }
 
@@ -61,6 +108,9 @@ impl<'a> RustClientCtx<'a> {
             type_replacements: BTreeMap::new(),
             split_modules: BTreeMap::new(),
             only_these_modules: None,
+            import_remaps: BTreeMap::new(),
+            buf_layout: None,
+            jvm_layout: None,
         }
     }
     #[must_use]
@@ -146,6 +196,32 @@ impl<'a> RustClientCtx<'a> {
         self
     }
 
+    /// Writes `import "to";` wherever the generated `.proto` files would import `from`, e.g. to
+    /// point well-known types at a vendored copy. Both paths include the `.proto` suffix. Entries
+    /// from the `PROTO_IMPORT_REMAP` environment variable apply too; these take precedence.
+    #[must_use]
+    pub fn remap_import(mut self, from: &str, to: &str) -> Self {
+        self.import_remaps.insert(from.to_string(), to.to_string());
+        self
+    }
+
+    /// Lays out `write_all` output as a buf module: files go to their package directories under
+    /// lower_snake_case names, and `buf.yaml` (plus `buf.gen.yaml` with plugins) is written at the
+    /// root. `write_only_these` keeps its explicit paths.
+    #[must_use]
+    pub fn with_buf_layout(mut self, layout: BufLayout) -> Self {
+        self.buf_layout = Some(layout);
+        self
+    }
+
+    /// Adds `java_package`, `java_multiple_files` and `java_outer_classname` options to every
+    /// written file, derived from the Rust modules declaring its types.
+    #[must_use]
+    pub fn with_jvm_layout(mut self, layout: JvmLayout) -> Self {
+        self.jvm_layout = Some(layout);
+        self
+    }
+
     pub fn only_these_modules(modules: &[(&str, &str)]) -> Self {
         let map = modules.iter().map(|(name, path)| (name.to_string(), path.to_string())).collect();
         Self {
@@ -159,6 +235,9 @@ impl<'a> RustClientCtx<'a> {
             type_replacements: BTreeMap::new(),
             split_modules: BTreeMap::new(),
             only_these_modules: Some(map),
+            import_remaps: BTreeMap::new(),
+            buf_layout: None,
+            jvm_layout: None,
         }
     }
 }
@@ -178,6 +257,49 @@ pub struct ProtoIdent {
     pub generics: &'static [ProtoIdent],
 }
 
+/// Prefix of the type URLs built by [`ProtoIdent::type_url`], as used by `google.protobuf.Any`.
+pub const TYPE_URL_PREFIX: &str = "type.googleapis.com/";
+
+impl ProtoIdent {
+    /// Fully-qualified proto name, `package.Name`, or the bare name without a package.
+    pub fn full_name(&self) -> String {
+        if self.proto_package_name.is_empty() {
+            self.name.to_string()
+        } else {
+            format!("{}.{}", self.proto_package_name, self.name)
+        }
+    }
+
+    /// `type.googleapis.com/package.Name`, the type URL of this type inside `google.protobuf.Any`.
+    pub fn type_url(&self) -> String {
+        format!("{TYPE_URL_PREFIX}{}", self.full_name())
+    }
+
+    /// 64-bit FNV-1a hash of [`full_name`](Self::full_name), for tagging payloads where a type URL
+    /// is too long. It only changes when the package or the name does.
+    pub const fn stable_id(&self) -> u64 {
+        let mut hash = FNV_OFFSET;
+        if !self.proto_package_name.is_empty() {
+            hash = fnv1a(hash, self.proto_package_name.as_bytes());
+            hash = fnv1a(hash, b".");
+        }
+        fnv1a(hash, self.name.as_bytes())
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// 64-bit FNV-1a over `bytes`, continuing from `hash`.
+const fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+        i += 1;
+    }
+    hash
+}
+
 #[derive(Clone, Debug, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum GenericArg {
     Type(ProtoIdent),
@@ -212,6 +334,20 @@ pub enum ProtoType {
     None,
 }
 impl ProtoType {
+    /// Whether repeated fields of this type use the packed encoding.
+    pub const fn is_packable(&self) -> bool {
+        !matches!(
+            self,
+            ProtoType::String
+                | ProtoType::Bytes
+                | ProtoType::Message(_)
+                | ProtoType::Map { .. }
+                | ProtoType::None
+                | ProtoType::Optional(_)
+                | ProtoType::Repeated(_)
+        )
+    }
+
     const fn is_allowed_as_key(&self) -> bool {
         matches!(
             self,
@@ -384,6 +520,7 @@ impl_proto_ident_primitive!(f32, ProtoType::Float);
 impl_proto_ident_primitive!(f64, ProtoType::Double);
 impl_proto_ident_primitive!(crate::bytes::Bytes, ProtoType::Bytes);
 impl_proto_ident_primitive!(::std::string::String, ProtoType::String);
+impl_proto_ident_primitive!(crate::MaybeUtf8, ProtoType::String);
 impl_proto_ident_primitive!(::core::sync::atomic::AtomicBool, ProtoType::Bool);
 impl_proto_ident_primitive!(::core::sync::atomic::AtomicU8, ProtoType::Uint32);
 impl_proto_ident_primitive!(::core::sync::atomic::AtomicU16, ProtoType::Uint32);
@@ -406,6 +543,22 @@ impl_proto_ident_primitive!(::core::num::NonZeroI32, ProtoType::Int32);
 impl_proto_ident_primitive!(::core::num::NonZeroI64, ProtoType::Int64);
 impl_proto_ident_primitive!(::core::num::NonZeroIsize, ProtoType::Int64);
 
+/// Identity of `google.protobuf.Empty`, used for `()` and `#[proto_message(empty = "wkt")]` unit structs.
+pub const EMPTY_PROTO_IDENT: ProtoIdent = ProtoIdent {
+    module_path: "",
+    name: "Empty",
+    proto_package_name: "google.protobuf",
+    proto_file_path: "google/protobuf/empty.proto",
+    proto_type: ProtoType::Message("Empty"),
+    generics: &[],
+};
+
+#[cfg(feature = "build-schemas")]
+impl ProtoIdentifiable for () {
+    const PROTO_IDENT: ProtoIdent = EMPTY_PROTO_IDENT;
+    const PROTO_TYPE: ProtoType = EMPTY_PROTO_IDENT.proto_type;
+}
+
 #[cfg(feature = "build-schemas")]
 impl<T: ProtoIdentifiable, const N: usize> ProtoIdentifiable for [T; N] {
     const PROTO_IDENT: ProtoIdent = T::PROTO_IDENT;
@@ -451,6 +604,39 @@ impl<T: ProtoIdentifiable> ProtoIdentifiable for ::std::sync::Arc<T> {
     const PROTO_TYPE: ProtoType = T::PROTO_TYPE;
 }
 
+#[cfg(feature = "build-schemas")]
+impl ProtoIdentifiable for ::std::sync::Arc<str> {
+    const PROTO_IDENT: ProtoIdent = ProtoIdent {
+        module_path: module_path!(),
+        name: "Arc",
+        proto_package_name: "",
+        proto_file_path: "",
+        proto_type: Self::PROTO_TYPE,
+        generics: &[ProtoIdent {
+            module_path: module_path!(),
+            name: "str",
+            proto_package_name: "",
+            proto_file_path: "",
+            proto_type: ProtoType::String,
+            generics: &[],
+        }],
+    };
+    const PROTO_TYPE: ProtoType = ProtoType::String;
+}
+
+#[cfg(feature = "build-schemas")]
+impl<T: ProtoIdentifiable> ProtoIdentifiable for ::std::sync::Weak<T> {
+    const PROTO_IDENT: ProtoIdent = ProtoIdent {
+        module_path: module_path!(),
+        name: "Weak",
+        proto_package_name: "",
+        proto_file_path: "",
+        proto_type: Self::PROTO_TYPE,
+        generics: &[T::PROTO_IDENT],
+    };
+    const PROTO_TYPE: ProtoType = T::PROTO_TYPE;
+}
+
 #[cfg(feature = "build-schemas")]
 impl<T: ProtoIdentifiable> ProtoIdentifiable for ::std::sync::Mutex<T> {
     const PROTO_IDENT: ProtoIdent = ProtoIdent {
@@ -490,6 +676,32 @@ impl<T: ProtoIdentifiable> ProtoIdentifiable for ::std::collections::VecDeque<T>
     const PROTO_TYPE: ProtoType = ProtoType::Repeated(&T::PROTO_TYPE);
 }
 
+#[cfg(feature = "build-schemas")]
+impl<T: ProtoIdentifiable> ProtoIdentifiable for ::std::collections::BinaryHeap<T> {
+    const PROTO_IDENT: ProtoIdent = ProtoIdent {
+        module_path: module_path!(),
+        name: "BinaryHeap",
+        proto_package_name: "",
+        proto_file_path: "",
+        proto_type: Self::PROTO_TYPE,
+        generics: &[T::PROTO_IDENT],
+    };
+    const PROTO_TYPE: ProtoType = ProtoType::Repeated(&T::PROTO_TYPE);
+}
+
+#[cfg(feature = "build-schemas")]
+impl<T: ProtoIdentifiable> ProtoIdentifiable for ::std::collections::LinkedList<T> {
+    const PROTO_IDENT: ProtoIdent = ProtoIdent {
+        module_path: module_path!(),
+        name: "LinkedList",
+        proto_package_name: "",
+        proto_file_path: "",
+        proto_type: Self::PROTO_TYPE,
+        generics: &[T::PROTO_IDENT],
+    };
+    const PROTO_TYPE: ProtoType = ProtoType::Repeated(&T::PROTO_TYPE);
+}
+
 #[cfg(feature = "build-schemas")]
 impl<K: ProtoIdentifiable, V: ProtoIdentifiable, S> ProtoIdentifiable for ::std::collections::HashMap<K, V, S> {
     const PROTO_IDENT: ProtoIdent = ProtoIdent {
@@ -548,30 +760,32 @@ impl<T: ProtoIdentifiable> ProtoIdentifiable for ::std::collections::BTreeSet<T>
     const PROTO_TYPE: ProtoType = ProtoType::Repeated(&T::PROTO_TYPE);
 }
 
+/// `ArcSwapAny` shows up as `ArcSwap` or `ArcSwapOption` depending on the stored pointer, with the
+/// pointee as its only generic.
 #[cfg(all(feature = "build-schemas", feature = "arc_swap"))]
-impl<T: ProtoIdentifiable> ProtoIdentifiable for arc_swap::ArcSwap<T> {
-    const PROTO_IDENT: ProtoIdent = ProtoIdent {
-        module_path: module_path!(),
-        name: "ArcSwap",
-        proto_package_name: "",
-        proto_file_path: "",
-        proto_type: Self::PROTO_TYPE,
-        generics: &[T::PROTO_IDENT],
-    };
-    const PROTO_TYPE: ProtoType = T::PROTO_TYPE;
-}
-
-#[cfg(all(feature = "build-schemas", feature = "arc_swap"))]
-impl<T: ProtoIdentifiable> ProtoIdentifiable for arc_swap::ArcSwapOption<T> {
-    const PROTO_IDENT: ProtoIdent = ProtoIdent {
-        module_path: module_path!(),
-        name: "ArcSwapOption",
-        proto_package_name: "",
-        proto_file_path: "",
-        proto_type: Self::PROTO_TYPE,
-        generics: &[T::PROTO_IDENT],
+impl<R, S> ProtoIdentifiable for arc_swap::ArcSwapAny<R, S>
+where
+    R: ProtoIdentifiable + arc_swap::RefCnt,
+    S: arc_swap::strategy::Strategy<R>,
+{
+    const PROTO_IDENT: ProtoIdent = {
+        let pointer = R::PROTO_IDENT;
+        let is_option = matches!(pointer.name.as_bytes(), b"Option");
+        let pointer = if is_option && !pointer.generics.is_empty() {
+            pointer.generics[0]
+        } else {
+            pointer
+        };
+        ProtoIdent {
+            module_path: module_path!(),
+            name: if is_option { "ArcSwapOption" } else { "ArcSwap" },
+            proto_package_name: "",
+            proto_file_path: "",
+            proto_type: Self::PROTO_TYPE,
+            generics: pointer.generics,
+        }
     };
-    const PROTO_TYPE: ProtoType = ProtoType::Optional(&T::PROTO_TYPE);
+    const PROTO_TYPE: ProtoType = R::PROTO_TYPE;
 }
 
 #[cfg(all(feature = "build-schemas", feature = "cache_padded"))]
@@ -635,6 +849,31 @@ pub struct Attribute {
     pub tokens: &'static str,
 }
 
+/// A `key = "value"` pair from `#[proto(annotate(...))]`.
+#[derive(Clone, Debug, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Annotation {
+    pub key: &'static str,
+    pub value: &'static str,
+}
+
+/// A custom option from `#[proto(option("acme.priority") = 3)]`.
+///
+/// [`write_all`] writes it as `(acme.priority) = 3` and declares the extension in the support
+/// file of its package, `acme_options.proto`.
+#[derive(Clone, Debug, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ProtoOption {
+    /// Full name of the option, e.g. `acme.priority`.
+    pub name: &'static str,
+    /// The value as a proto literal, e.g. `3`, `true` or `"gold"`.
+    pub value: &'static str,
+    /// Type of the extension: `int64`, `double`, `bool` or `string`.
+    pub value_type: &'static str,
+}
+
+fn find_option(options: &[ProtoOption], name: &str) -> Option<&'static str> {
+    options.iter().find(|option| option.name == name).map(|option| option.value)
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct UserAttr {
     pub level: AttrLevel,
@@ -731,6 +970,13 @@ pub struct Variant {
     pub discriminant: Option<i32>,
 }
 
+impl Variant {
+    /// Name of the oneof field carrying this variant of a complex enum.
+    pub fn oneof_field_name(&self) -> String {
+        utils::to_snake_case(self.name)
+    }
+}
+
 #[derive(Clone, Debug, Copy, Eq, PartialEq, Hash)]
 pub struct Field {
     pub name: Option<&'static str>,
@@ -744,11 +990,57 @@ pub struct Field {
     pub array_len: Option<&'static str>,
     pub array_is_bytes: bool,
     pub array_elem: Option<ProtoIdent>,
+    /// `#[proto(label = "...")]` values; `#[proto(sensitive)]` adds `"sensitive"`.
+    pub labels: &'static [&'static str],
+    pub annotations: &'static [Annotation],
+    pub options: &'static [ProtoOption],
+}
+
+impl Field {
+    /// Key and value idents of a map field, preferring the Rust types from the wrapper.
+    pub fn map_idents(&self) -> (Option<ProtoIdent>, Option<ProtoIdent>) {
+        if let Some(wrapper) = self.wrapper
+            && let [key, value] = wrapper.generics
+        {
+            return (Some(*key), Some(*value));
+        }
+        match self.generic_args {
+            [GenericArg::Type(key), GenericArg::Type(value)] => (Some(*key), Some(*value)),
+            _ => (None, None),
+        }
+    }
+
+    /// Whether the field is marked `#[proto(sensitive)]`.
+    pub fn is_sensitive(&self) -> bool {
+        self.has_label("sensitive")
+    }
+
+    /// Whether the field carries `#[proto(label = "<label>")]`.
+    pub fn has_label(&self, label: &str) -> bool {
+        self.labels.contains(&label)
+    }
+
+    /// Value of the `#[proto(annotate(<key> = "..."))]` annotation.
+    pub fn annotation(&self, key: &str) -> Option<&'static str> {
+        self.annotations.iter().find(|annotation| annotation.key == key).map(|annotation| annotation.value)
+    }
+
+    /// Proto literal of the `#[proto(option("<name>") = ...)]` custom option.
+    pub fn option(&self, name: &str) -> Option<&'static str> {
+        find_option(self.options, name)
+    }
+
+    /// Whether the field is a single `bytes` blob rather than a repeated field.
+    pub fn is_bytes(&self) -> bool {
+        self.array_is_bytes || (self.array_len.is_none() && proto_output::is_bytes_proto_field(self))
+    }
 }
 
 #[derive(Clone, Debug, Copy, Eq, PartialEq, Hash)]
 pub struct ServiceMethod {
     pub name: &'static str,
+    /// Full gRPC path when it differs from `/package.Service/name`.
+    pub route: Option<&'static str>,
     pub request: ProtoIdent,
     pub request_generic_args: &'static [GenericArg],
     pub request_wrapper: Option<ProtoIdent>,
@@ -757,6 +1049,14 @@ pub struct ServiceMethod {
     pub response_wrapper: Option<ProtoIdent>,
     pub client_streaming: bool,
     pub server_streaming: bool,
+    pub options: &'static [ProtoOption],
+}
+
+impl ServiceMethod {
+    /// Proto literal of the `#[proto(option("<name>") = ...)]` custom option.
+    pub fn option(&self, name: &str) -> Option<&'static str> {
+        find_option(self.options, name)
+    }
 }
 
 #[derive(Clone, Debug, Copy, Eq, PartialEq, Hash)]
@@ -770,6 +1070,10 @@ pub enum ProtoLabel {
 inventory::collect!(ProtoSchema);
 
 static REGISTRY: LazyLock<BTreeMap<String, Vec<&'static ProtoSchema>>> = LazyLock::new(|| build_registry().0);
+static IDENT_INDEX: LazyLock<BTreeMap<ProtoIdent, &'static ProtoSchema>> = LazyLock::new(|| build_registry().1);
+static NAME_INDEX: LazyLock<BTreeMap<String, &'static ProtoSchema>> = LazyLock::new(build_name_index);
+static SCHEMA_HASHES: LazyLock<BTreeMap<ProtoIdent, u64>> = LazyLock::new(build_schema_hashes);
+static PACKAGE_INDEX: LazyLock<BTreeMap<&'static str, Vec<&'static ProtoSchema>>> = LazyLock::new(build_package_index);
 
 /// Get an iterator over all registered proto schemas
 ///
@@ -815,47 +1119,67 @@ pub fn write_all(output_dir: &str, rust_client_output: &RustClientCtx<'_>) -> io
     let (registry, ident_index) = build_registry();
     let all_entries: Vec<&ProtoSchema> = registry.values().flat_map(|entries| entries.iter().copied()).collect();
     let specializations = proto_output::collect_generic_specializations(&all_entries, &ident_index);
+    let import_remaps = import_remaps(rust_client_output);
+    let packages: BTreeMap<&str, String> =
+        registry.iter().map(|(file_name, entries)| (file_name.as_str(), utils::package_name_for_entries(file_name, entries))).collect();
+    let buf_layout = rust_client_output.buf_layout.as_ref();
+    let buf_path = |file_name: &str| buf_layout.and_then(|_| Some(buf::module_path(file_name, packages.get(file_name)?)));
+    let option_extensions = options::collect(&all_entries)?;
+    let options_path = |package: &str| {
+        let file_name = options::file_name(package);
+        if buf_layout.is_some() {
+            buf::module_path(&file_name, package)
+        } else {
+            file_name
+        }
+    };
 
     for (file_name, entries) in &registry {
-        let output_path = format!("{output_dir}/{file_name}");
+        let output_path = format!("{output_dir}/{}", buf_path(file_name).unwrap_or_else(|| file_name.clone()));
 
         if let Some(parent) = Path::new(&output_path).parent() {
             fs::create_dir_all(parent)?;
         }
 
-        let path = Path::new(file_name.as_str());
-        let file_name_last = path.file_name().unwrap().to_str().unwrap();
-        let package_name = entries
-            .first()
-            .map(|schema| schema.id.proto_package_name)
-            .filter(|name| !name.is_empty())
-            .map_or(utils::derive_package_name(file_name_last), ToString::to_string);
+        let package_name = utils::package_name_for_entries(file_name, entries);
         let mut output = String::new();
 
         output.push_str("//CODEGEN BELOW - DO NOT TOUCH ME\n");
-        output.push_str("syntax = \"proto3\";\n");
-        writeln!(output, "package {package_name};").unwrap();
+        output.push_str(&proto_output::file_header(entries, &package_name));
+        if let Some(layout) = &rust_client_output.jvm_layout {
+            output.push_str(&layout.file_options(file_name, entries, &package_name));
+        }
 
         output.push('\n');
 
         let imports = proto_output::collect_imports(entries.as_slice(), &ident_index, file_name, &package_name)?;
-        if !imports.is_empty() {
-            let mut import_stems = BTreeSet::new();
+        let option_packages = options::packages_used(entries);
+        if !imports.is_empty() || !option_packages.is_empty() {
+            let mut import_paths = BTreeSet::new();
             for import in &imports {
-                let import_path = Path::new(import);
-                let import_file = import_path.file_name().and_then(|name| name.to_str()).unwrap_or(import);
-                let import_stem = import_file.strip_suffix(".proto").unwrap_or(import_file);
-                import_stems.insert(import_stem.to_string());
+                let stem = import_stem(import);
+                let registered = packages.keys().find(|file_name| import_stem(file_name) == stem);
+                let path = registered.and_then(|file_name| buf_path(file_name)).unwrap_or_else(|| format!("{stem}.proto"));
+                import_paths.insert(remapped_import(&path, &import_remaps));
+            }
+            for package in option_packages {
+                import_paths.insert(remapped_import(&options_path(package), &import_remaps));
             }
-            for import_stem in import_stems {
-                writeln!(output, "import \"{import_stem}.proto\";").unwrap();
+            for import_path in import_paths {
+                writeln!(output, "import \"{import_path}\";").unwrap();
             }
             output.push('\n');
         }
 
-        let definitions = proto_output::render_entries(entries, &package_name, &ident_index, &specializations);
+        let editions = proto_output::uses_editions(entries);
+        let (entries, specializations) = proto_output::with_unplaced_generics(entries, &ident_index, &specializations);
+        let definitions = proto_output::render_entries(&entries, &package_name, &ident_index, &specializations);
         for definition in definitions {
-            output.push_str(&definition);
+            if editions {
+                output.push_str(&proto_output::with_explicit_presence(&definition));
+            } else {
+                output.push_str(&definition);
+            }
             output.push('\n');
         }
 
@@ -863,6 +1187,21 @@ pub fn write_all(output_dir: &str, rust_client_output: &RustClientCtx<'_>) -> io
         count += 1;
     }
 
+    for (package, extensions) in &option_extensions {
+        let output_path = format!("{output_dir}/{}", options_path(package));
+        if let Some(parent) = Path::new(&output_path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&output_path, options::render(package, extensions))?;
+        count += 1;
+    }
+
+    if let Some(layout) = buf_layout {
+        let mut packages: BTreeSet<String> = packages.into_values().collect();
+        packages.extend(option_extensions.keys().map(ToString::to_string));
+        layout.write_config(output_dir, &packages)?;
+    }
+
     if rust_client_output.output_path.is_some() || rust_client_output.only_these_modules.is_some() {
         rust_client::write_rust_client_module(
             rust_client_output.output_path,
@@ -883,6 +1222,33 @@ pub fn write_all(output_dir: &str, rust_client_output: &RustClientCtx<'_>) -> io
     Ok(count)
 }
 
+/// `PROTO_IMPORT_REMAP` entries (`from=to`, comma separated) overridden by the context's own.
+fn import_remaps(rust_client_output: &RustClientCtx<'_>) -> BTreeMap<String, String> {
+    let mut remaps = BTreeMap::new();
+    if let Ok(table) = std::env::var("PROTO_IMPORT_REMAP") {
+        for (from, to) in table.split(',').filter_map(|entry| entry.split_once('=')) {
+            remaps.insert(from.trim().to_string(), to.trim().to_string());
+        }
+    }
+    remaps.extend(rust_client_output.import_remaps.iter().map(|(from, to)| (from.clone(), to.clone())));
+    remaps
+}
+
+fn remapped_import(import: &str, remaps: &BTreeMap<String, String>) -> String {
+    remaps.get(import).cloned().unwrap_or_else(|| import.to_string())
+}
+
+/// Import path without the `.proto` suffix. Well-known types keep their `google/protobuf/` path,
+/// everything else is imported by file stem.
+fn import_stem(import: &str) -> &str {
+    let import = if import.starts_with("google/protobuf/") {
+        import
+    } else {
+        Path::new(import).file_name().and_then(|name| name.to_str()).unwrap_or(import)
+    };
+    import.strip_suffix(".proto").unwrap_or(import)
+}
+
 /// Write only specified proto files to their respective output paths.
 ///
 /// Each entry is `("full/proto/file.proto", "output/path.proto")`.
@@ -897,6 +1263,9 @@ pub fn write_only_these(protos: &[(&str, &str)], rust_client_output: &RustClient
     let (registry, ident_index) = build_registry();
     let all_entries: Vec<&ProtoSchema> = registry.values().flat_map(|entries| entries.iter().copied()).collect();
     let specializations = proto_output::collect_generic_specializations(&all_entries, &ident_index);
+    let import_remaps = import_remaps(rust_client_output);
+    let option_extensions = options::collect(&all_entries)?;
+    let mut written_options = BTreeSet::new();
 
     for (file_name, entries) in &registry {
         let Some(&output_path) = filter.get(file_name.as_str()) else {
@@ -907,39 +1276,48 @@ pub fn write_only_these(protos: &[(&str, &str)], rust_client_output: &RustClient
             fs::create_dir_all(parent)?;
         }
 
-        let path = Path::new(file_name.as_str());
-        let file_name_last = path.file_name().unwrap().to_str().unwrap();
-        let package_name = entries
-            .first()
-            .map(|schema| schema.id.proto_package_name)
-            .filter(|name| !name.is_empty())
-            .map_or(utils::derive_package_name(file_name_last), ToString::to_string);
+        let package_name = utils::package_name_for_entries(file_name, entries);
         let mut output = String::new();
 
         output.push_str("//CODEGEN BELOW - DO NOT TOUCH ME\n");
-        output.push_str("syntax = \"proto3\";\n");
-        writeln!(output, "package {package_name};").unwrap();
+        output.push_str(&proto_output::file_header(entries, &package_name));
+        if let Some(layout) = &rust_client_output.jvm_layout {
+            output.push_str(&layout.file_options(file_name, entries, &package_name));
+        }
 
         output.push('\n');
 
         let imports = proto_output::collect_imports(entries.as_slice(), &ident_index, file_name, &package_name)?;
-        if !imports.is_empty() {
-            let mut import_stems = BTreeSet::new();
+        let option_packages = options::packages_used(entries);
+        if !imports.is_empty() || !option_packages.is_empty() {
+            let mut import_paths = BTreeSet::new();
             for import in &imports {
-                let import_path = Path::new(import);
-                let import_file = import_path.file_name().and_then(|name| name.to_str()).unwrap_or(import);
-                let import_stem = import_file.strip_suffix(".proto").unwrap_or(import_file);
-                import_stems.insert(import_stem.to_string());
+                import_paths.insert(remapped_import(&format!("{}.proto", import_stem(import)), &import_remaps));
             }
-            for import_stem in import_stems {
-                writeln!(output, "import \"{import_stem}.proto\";").unwrap();
+            // Support files of the options used are written next to the file
+            for package in option_packages {
+                let options_file = options::file_name(package);
+                let options_path = Path::new(output_path).with_file_name(&options_file);
+                if written_options.insert(options_path.clone()) {
+                    fs::write(&options_path, options::render(package, &option_extensions[package]))?;
+                }
+                import_paths.insert(remapped_import(&options_file, &import_remaps));
+            }
+            for import_path in import_paths {
+                writeln!(output, "import \"{import_path}\";").unwrap();
             }
             output.push('\n');
         }
 
-        let definitions = proto_output::render_entries(entries, &package_name, &ident_index, &specializations);
+        let editions = proto_output::uses_editions(entries);
+        let (entries, specializations) = proto_output::with_unplaced_generics(entries, &ident_index, &specializations);
+        let definitions = proto_output::render_entries(&entries, &package_name, &ident_index, &specializations);
         for definition in definitions {
-            output.push_str(&definition);
+            if editions {
+                output.push_str(&proto_output::with_explicit_presence(&definition));
+            } else {
+                output.push_str(&definition);
+            }
             output.push('\n');
         }
 
@@ -967,6 +1345,36 @@ pub fn write_only_these(protos: &[(&str, &str)], rust_client_output: &RustClient
     Ok(count)
 }
 
+/// Schema registered for `ident`, if any
+pub fn schema_for(ident: &ProtoIdent) -> Option<&'static ProtoSchema> {
+    IDENT_INDEX.get(ident).copied()
+}
+
+/// Registered message with full proto name `name` (`package.Message`), or the bare message name
+/// for messages without a package
+pub fn message_by_name(name: &str) -> Option<&'static ProtoSchema> {
+    let (package, message) = name.rsplit_once('.').unwrap_or(("", name));
+    all().find(|schema| {
+        schema.generics.is_empty()
+            && schema.id.name == message
+            && schema.id.proto_package_name == package
+            && matches!(schema.content, ProtoEntry::Struct { .. } | ProtoEntry::ComplexEnum { .. })
+    })
+}
+
+/// Every field of a registered message or complex enum variant that carries `label`, with the
+/// schema it belongs to
+pub fn labeled_fields(label: &str) -> impl Iterator<Item = (&'static ProtoSchema, &'static Field)> + '_ {
+    all().flat_map(move |schema| {
+        let fields: Vec<&'static Field> = match schema.content {
+            ProtoEntry::Struct { fields } => fields.to_vec(),
+            ProtoEntry::ComplexEnum { variants } => variants.iter().flat_map(|variant| variant.fields.iter().copied()).collect(),
+            _ => Vec::new(),
+        };
+        fields.into_iter().filter(move |field| field.has_label(label)).map(move |field| (schema, field))
+    })
+}
+
 /// Get the total number of registered files
 pub fn count() -> usize {
     REGISTRY.len()
@@ -977,6 +1385,108 @@ pub fn file_names() -> Vec<String> {
     REGISTRY.keys().cloned().collect()
 }
 
+/// Full gRPC names (`package.Service`) of every registered `#[proto_rpc]` service
+pub fn service_names() -> Vec<String> {
+    all().filter_map(full_service_name).collect::<BTreeSet<_>>().into_iter().collect()
+}
+
+/// Registered message, enum or service with full proto name `type_name` (`package.Name`), or
+/// the bare name for types without a package
+///
+/// Services are found under their gRPC name, as listed by [`service_names`]. Generic types are
+/// registered per instantiation and are not indexed by name.
+pub fn lookup(type_name: &str) -> Option<&'static ProtoSchema> {
+    NAME_INDEX.get(type_name).copied()
+}
+
+/// FNV-1a hash of the `.proto` definition registered for `ident`
+///
+/// Two builds agree on the hash exactly when they would emit the same definition, so a consumer
+/// can tell whether a payload was written against the schema it knows.
+pub fn schema_hash(ident: &ProtoIdent) -> Option<u64> {
+    SCHEMA_HASHES.get(ident).copied()
+}
+
+fn build_schema_hashes() -> BTreeMap<ProtoIdent, u64> {
+    IDENT_INDEX
+        .values()
+        .filter(|schema| schema.generics.is_empty() && !matches!(schema.content, ProtoEntry::Import { .. }))
+        .map(|schema| {
+            let definition = proto_output::render_entries(&[schema], schema.id.proto_package_name, &IDENT_INDEX, &BTreeMap::new()).concat();
+            (schema.id, fnv1a(FNV_OFFSET, definition.as_bytes()))
+        })
+        .collect()
+}
+
+/// Registered message or enum behind a type URL (`type.googleapis.com/package.Name`)
+///
+/// Only the part after the last `/` is used, so URLs with other hosts resolve as well.
+pub fn lookup_type_url(type_url: &str) -> Option<&'static ProtoSchema> {
+    lookup(type_url_name(type_url)).filter(|schema| !matches!(schema.content, ProtoEntry::Service { .. }))
+}
+
+/// Full proto name in a type URL: everything after the last `/`
+pub(crate) fn type_url_name(type_url: &str) -> &str {
+    type_url.rsplit_once('/').map_or(type_url, |(_, name)| name)
+}
+
+/// Messages, enums and services declared in proto package `package`, in registration order
+pub fn by_package(package: &str) -> impl Iterator<Item = &'static ProtoSchema> {
+    PACKAGE_INDEX.get(package).into_iter().flatten().copied()
+}
+
+/// Every registered `#[proto_rpc]` service, once each
+pub fn services() -> impl Iterator<Item = &'static ProtoSchema> {
+    IDENT_INDEX.values().copied().filter(|schema| matches!(schema.content, ProtoEntry::Service { .. }))
+}
+
+/// gRPC name of a service schema, taken from its routes when they were renamed
+fn full_service_name(schema: &ProtoSchema) -> Option<String> {
+    let ProtoEntry::Service { methods, rpc_package_name } = schema.content else {
+        return None;
+    };
+    let routed = methods.iter().find_map(|method| method.route).and_then(|route| route.strip_prefix('/')?.split_once('/'));
+    Some(match routed {
+        Some((service, _)) => service.to_string(),
+        None => format!("{rpc_package_name}.{}", schema.id.name),
+    })
+}
+
+/// Full proto name of a schema that can be looked up by name
+fn full_type_name(schema: &ProtoSchema) -> Option<String> {
+    if !schema.generics.is_empty() {
+        return None;
+    }
+    match schema.content {
+        ProtoEntry::Import { .. } => None,
+        ProtoEntry::Service { .. } => full_service_name(schema),
+        _ if schema.id.proto_package_name.is_empty() => Some(schema.id.name.to_string()),
+        _ => Some(format!("{}.{}", schema.id.proto_package_name, schema.id.name)),
+    }
+}
+
+fn build_name_index() -> BTreeMap<String, &'static ProtoSchema> {
+    let mut index = BTreeMap::new();
+    for schema in all() {
+        if let Some(name) = full_type_name(schema) {
+            index.entry(name).or_insert(schema);
+        }
+    }
+    index
+}
+
+fn build_package_index() -> BTreeMap<&'static str, Vec<&'static ProtoSchema>> {
+    let mut index: BTreeMap<&'static str, Vec<&'static ProtoSchema>> = BTreeMap::new();
+    let mut seen = BTreeSet::new();
+    for schema in all() {
+        if matches!(schema.content, ProtoEntry::Import { .. }) || !seen.insert(schema.id) {
+            continue;
+        }
+        index.entry(schema.id.proto_package_name).or_default().push(schema);
+    }
+    index
+}
+
 fn build_registry() -> (
     BTreeMap<String, Vec<&'static ProtoSchema>>,
     BTreeMap<ProtoIdent, &'static ProtoSchema>,
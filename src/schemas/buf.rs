@@ -0,0 +1,132 @@
+//! `buf` workspace layout for [`write_all`](super::write_all).
+//!
+//! With a [`BufLayout`] set on the [`RustClientCtx`](super::RustClientCtx), every file is written
+//! to the directory of its package (`acme.billing.v1` goes to `acme/billing/v1/`) under a
+//! lower_snake_case name, imports point at those paths, and `buf.yaml` (plus `buf.gen.yaml` when
+//! plugins are configured) lands next to them. The output directory is then a buf module that
+//! passes `PACKAGE_DIRECTORY_MATCH`, `PACKAGE_SAME_DIRECTORY` and `FILE_LOWER_SNAKE_CASE`.
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// STANDARD lint rules about names taken from the Rust types, which the layout cannot change.
+const NAMING_RULES: &[&str] = &[
+    "ENUM_VALUE_PREFIX",
+    "ENUM_ZERO_VALUE_SUFFIX",
+    "RPC_REQUEST_RESPONSE_UNIQUE",
+    "RPC_REQUEST_STANDARD_NAME",
+    "RPC_RESPONSE_STANDARD_NAME",
+    "SERVICE_SUFFIX",
+];
+
+/// Settings for `buf.yaml` and `buf.gen.yaml`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BufLayout {
+    plugins: Vec<(String, String)>,
+    lint_except: Vec<String>,
+    strict_naming: bool,
+}
+
+impl BufLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a `buf.gen.yaml` plugin: `remote` (e.g. `buf.build/protocolbuffers/go`) writing to `out`.
+    #[must_use]
+    pub fn plugin(mut self, remote: &str, out: &str) -> Self {
+        self.plugins.push((remote.to_string(), out.to_string()));
+        self
+    }
+
+    /// Adds a lint rule to the `except` list of `buf.yaml`.
+    #[must_use]
+    pub fn lint_except(mut self, rule: &str) -> Self {
+        self.lint_except.push(rule.to_string());
+        self
+    }
+
+    /// Keeps the naming rules (`ENUM_VALUE_PREFIX`, `SERVICE_SUFFIX`, ...) enabled.
+    ///
+    /// They are excepted by default, since the names come from the Rust types.
+    #[must_use]
+    pub const fn strict_naming(mut self) -> Self {
+        self.strict_naming = true;
+        self
+    }
+
+    /// `buf.yaml` for a module whose files declare `packages`.
+    pub fn buf_yaml(&self, packages: &BTreeSet<String>) -> String {
+        let mut except: BTreeSet<&str> = self.lint_except.iter().map(String::as_str).collect();
+        if !self.strict_naming {
+            except.extend(NAMING_RULES);
+        }
+        if packages.iter().any(|package| !has_version_suffix(package)) {
+            except.insert("PACKAGE_VERSION_SUFFIX");
+        }
+
+        let mut yaml =
+            String::from("# CODEGEN BELOW - DO NOT TOUCH ME\nversion: v2\nmodules:\n  - path: .\nlint:\n  use:\n    - STANDARD\n");
+        if !except.is_empty() {
+            yaml.push_str("  except:\n");
+            for rule in except {
+                writeln!(yaml, "    - {rule}").unwrap();
+            }
+        }
+        yaml.push_str("breaking:\n  use:\n    - FILE\n");
+        yaml
+    }
+
+    /// `buf.gen.yaml` running the configured plugins, or `None` without plugins.
+    pub fn buf_gen_yaml(&self) -> Option<String> {
+        if self.plugins.is_empty() {
+            return None;
+        }
+        let mut yaml = String::from("# CODEGEN BELOW - DO NOT TOUCH ME\nversion: v2\nplugins:\n");
+        for (remote, out) in &self.plugins {
+            writeln!(yaml, "  - remote: {remote}\n    out: {out}").unwrap();
+        }
+        Some(yaml)
+    }
+
+    pub(super) fn write_config(&self, output_dir: &str, packages: &BTreeSet<String>) -> io::Result<()> {
+        fs::write(Path::new(output_dir).join("buf.yaml"), self.buf_yaml(packages))?;
+        if let Some(gen_yaml) = self.buf_gen_yaml() {
+            fs::write(Path::new(output_dir).join("buf.gen.yaml"), gen_yaml)?;
+        }
+        Ok(())
+    }
+}
+
+/// Path of registry file `file_name` inside the module: the package directory and the
+/// lower_snake_case file name.
+pub(super) fn module_path(file_name: &str, package: &str) -> String {
+    let stem = Path::new(file_name).file_stem().and_then(|stem| stem.to_str()).unwrap_or(file_name);
+    let stem = super::utils::to_snake_case(&stem.replace(['-', '.', ' '], "_")).replace("__", "_");
+    if package.is_empty() {
+        format!("{stem}.proto")
+    } else {
+        format!("{}/{stem}.proto", package.replace('.', "/"))
+    }
+}
+
+/// Whether the last package segment is a version (`v1`, `v2beta1`, `v1alpha`, `v1test2`).
+fn has_version_suffix(package: &str) -> bool {
+    let last = package.rsplit('.').next().unwrap_or(package);
+    let Some(rest) = last.strip_prefix('v') else {
+        return false;
+    };
+    let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+    if digits == 0 {
+        return false;
+    }
+    let rest = &rest[digits..];
+    ["alpha", "beta", "test"]
+        .iter()
+        .filter_map(|stability| rest.strip_prefix(stability))
+        .any(|tail| tail.bytes().all(|byte| byte.is_ascii_digit()))
+        || rest.is_empty()
+}
@@ -0,0 +1,98 @@
+//! Detection of types registered more than once with different definitions.
+//!
+//! Every crate linked into the binary registers its schemas through `inventory`. When two of them
+//! export a type under the same proto name into the same package, `write_all` keeps whichever
+//! registration it sees first. [`conflicts`] lists those collisions so a build script can report
+//! them or refuse to write.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::IDENT_INDEX;
+use super::ProtoEntry;
+use super::ProtoSchema;
+use super::proto_output;
+use super::utils::proto_ident_base_type_name;
+
+/// A proto type that several registrations define differently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaConflict {
+    /// Package shared by the registrations.
+    pub package: &'static str,
+    /// Proto name of the type within the package.
+    pub name: String,
+    /// Every registration of the type, in registration order.
+    pub definitions: Vec<ConflictingDefinition>,
+}
+
+/// One registration of a conflicting type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictingDefinition {
+    /// Rust module that declared the type.
+    pub module_path: &'static str,
+    /// `.proto` file the type is written to.
+    pub proto_file_path: &'static str,
+    /// The definition as it would be emitted.
+    pub definition: String,
+}
+
+impl SchemaConflict {
+    /// Full proto name (`package.Type`).
+    pub fn full_name(&self) -> String {
+        format!("{}.{}", self.package, self.name)
+    }
+}
+
+impl fmt::Display for SchemaConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "`{}` has {} different definitions:", self.full_name(), self.definitions.len())?;
+        for definition in &self.definitions {
+            writeln!(f, "  {} ({}):", definition.module_path, definition.proto_file_path)?;
+            for line in definition.definition.trim_end().lines() {
+                writeln!(f, "    {line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Types registered under the same package and name with differing definitions.
+///
+/// Registrations that render to the same definition are not conflicts, so a type reachable
+/// through several crates is only reported when its definitions actually disagree.
+pub fn conflicts() -> Vec<SchemaConflict> {
+    let mut groups: BTreeMap<(&'static str, String), Vec<&'static ProtoSchema>> = BTreeMap::new();
+    for schema in inventory::iter::<ProtoSchema>() {
+        if schema.id.proto_file_path.is_empty() || !schema.generics.is_empty() || matches!(schema.content, ProtoEntry::Import { .. }) {
+            continue;
+        }
+        let group = groups.entry((schema.id.proto_package_name, proto_ident_base_type_name(schema.id))).or_default();
+        if !group.iter().any(|registered| registered.id == schema.id) {
+            group.push(schema);
+        }
+    }
+
+    let mut conflicts = Vec::new();
+    for ((package, name), schemas) in groups {
+        if schemas.len() < 2 {
+            continue;
+        }
+        let definitions: Vec<ConflictingDefinition> = schemas
+            .iter()
+            .map(|schema| ConflictingDefinition {
+                module_path: schema.id.module_path,
+                proto_file_path: schema.id.proto_file_path,
+                definition: proto_output::render_entries(&[schema], package, &IDENT_INDEX, &BTreeMap::new()).concat(),
+            })
+            .collect();
+        if definitions.iter().all(|definition| definition.definition == definitions[0].definition) {
+            continue;
+        }
+        conflicts.push(SchemaConflict {
+            package,
+            name,
+            definitions,
+        });
+    }
+    conflicts
+}
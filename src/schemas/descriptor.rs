@@ -0,0 +1,359 @@
+//! Reader for `google.protobuf.FileDescriptorSet`, as written by `protoc --descriptor_set_out` or
+//! `buf build -o`.
+//!
+//! Only the parts needed to compare schemas and route calls are kept: packages, messages with
+//! their fields, enums with their values and services with their methods. Options other than
+//! `map_entry` are skipped.
+
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::encoding::WireType;
+use crate::encoding::decode_key;
+use crate::encoding::decode_varint;
+use crate::encoding::skip_field;
+
+/// A decoded `FileDescriptorSet`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DescriptorSet {
+    pub files: Vec<FileDescriptor>,
+}
+
+/// One `.proto` file of a descriptor set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileDescriptor {
+    /// Path of the file, e.g. `shop/v1/order.proto`.
+    pub name: String,
+    pub package: String,
+    pub messages: Vec<MessageDescriptor>,
+    pub enums: Vec<EnumDescriptor>,
+    pub services: Vec<ServiceDescriptor>,
+}
+
+/// A message, with the messages and enums declared inside it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageDescriptor {
+    pub name: String,
+    pub fields: Vec<FieldDescriptor>,
+    pub nested: Vec<MessageDescriptor>,
+    pub enums: Vec<EnumDescriptor>,
+    /// Whether this is the synthesized entry message of a `map<K, V>` field.
+    pub map_entry: bool,
+}
+
+/// A message field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldDescriptor {
+    pub name: String,
+    pub number: u32,
+    /// `FieldDescriptorProto.Label`: 1 optional, 2 required, 3 repeated.
+    pub label: i32,
+    /// `FieldDescriptorProto.Type`, e.g. 9 for `string` or 11 for messages.
+    pub kind: i32,
+    /// Fully-qualified name of message and enum types, with a leading `.`.
+    pub type_name: String,
+    /// Index of the oneof holding the field, including the synthetic oneof of proto3 `optional`.
+    pub oneof_index: Option<i32>,
+    /// Whether the field was declared `optional` in a proto3 file.
+    pub proto3_optional: bool,
+}
+
+/// An enum and its values, in declaration order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnumDescriptor {
+    pub name: String,
+    pub values: Vec<(String, i32)>,
+}
+
+/// A service and its methods.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServiceDescriptor {
+    pub name: String,
+    pub methods: Vec<RpcDescriptor>,
+}
+
+/// A method of a service.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RpcDescriptor {
+    pub name: String,
+    /// Fully-qualified request type, with a leading `.`.
+    pub input_type: String,
+    /// Fully-qualified response type, with a leading `.`.
+    pub output_type: String,
+    pub client_streaming: bool,
+    pub server_streaming: bool,
+}
+
+impl DescriptorSet {
+    /// Decodes a serialized `FileDescriptorSet`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when `bytes` is not a valid descriptor set.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut set = DescriptorSet::default();
+        for_each_field(bytes, |tag, value| {
+            if tag == 1 {
+                set.files.push(FileDescriptor::decode(value.bytes()?)?);
+            }
+            Ok(())
+        })?;
+        Ok(set)
+    }
+
+    /// Every message of the set, nested ones included, under its full name (`package.Outer.Inner`).
+    pub fn messages(&self) -> Vec<(String, &MessageDescriptor)> {
+        let mut messages = Vec::new();
+        for file in &self.files {
+            for message in &file.messages {
+                collect_messages(&qualify(&file.package, &message.name), message, &mut messages);
+            }
+        }
+        messages
+    }
+
+    /// Every enum of the set, nested ones included, under its full name.
+    pub fn enums(&self) -> Vec<(String, &EnumDescriptor)> {
+        let mut enums = Vec::new();
+        for file in &self.files {
+            enums.extend(file.enums.iter().map(|descriptor| (qualify(&file.package, &descriptor.name), descriptor)));
+        }
+        for (name, message) in self.messages() {
+            enums.extend(message.enums.iter().map(|descriptor| (format!("{name}.{}", descriptor.name), descriptor)));
+        }
+        enums
+    }
+
+    /// Every service of the set under its gRPC name (`package.Service`).
+    pub fn services(&self) -> Vec<(String, &ServiceDescriptor)> {
+        self.files.iter().flat_map(|file| file.services.iter().map(|service| (qualify(&file.package, &service.name), service))).collect()
+    }
+
+    /// Message with full name `name`, without the leading `.`.
+    pub fn message(&self, name: &str) -> Option<&MessageDescriptor> {
+        self.messages().into_iter().find(|(full_name, _)| full_name == name).map(|(_, message)| message)
+    }
+}
+
+impl FileDescriptor {
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut file = FileDescriptor::default();
+        for_each_field(bytes, |tag, value| {
+            match tag {
+                1 => file.name = value.string()?,
+                2 => file.package = value.string()?,
+                4 => file.messages.push(MessageDescriptor::decode(value.bytes()?)?),
+                5 => file.enums.push(EnumDescriptor::decode(value.bytes()?)?),
+                6 => file.services.push(ServiceDescriptor::decode(value.bytes()?)?),
+                _ => {}
+            }
+            Ok(())
+        })?;
+        Ok(file)
+    }
+}
+
+impl MessageDescriptor {
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut message = MessageDescriptor::default();
+        for_each_field(bytes, |tag, value| {
+            match tag {
+                1 => message.name = value.string()?,
+                2 => message.fields.push(FieldDescriptor::decode(value.bytes()?)?),
+                3 => message.nested.push(MessageDescriptor::decode(value.bytes()?)?),
+                4 => message.enums.push(EnumDescriptor::decode(value.bytes()?)?),
+                // MessageOptions.map_entry
+                7 => for_each_field(value.bytes()?, |tag, value| {
+                    if tag == 7 {
+                        message.map_entry = value.varint()? != 0;
+                    }
+                    Ok(())
+                })?,
+                _ => {}
+            }
+            Ok(())
+        })?;
+        Ok(message)
+    }
+
+    /// The field with number `number`.
+    pub fn field(&self, number: u32) -> Option<&FieldDescriptor> {
+        self.fields.iter().find(|field| field.number == number)
+    }
+}
+
+impl FieldDescriptor {
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut field = FieldDescriptor::default();
+        for_each_field(bytes, |tag, value| {
+            match tag {
+                1 => field.name = value.string()?,
+                3 => field.number = value.varint()? as u32,
+                4 => field.label = value.varint()? as i32,
+                5 => field.kind = value.varint()? as i32,
+                6 => field.type_name = value.string()?,
+                9 => field.oneof_index = Some(value.varint()? as i32),
+                17 => field.proto3_optional = value.varint()? != 0,
+                _ => {}
+            }
+            Ok(())
+        })?;
+        Ok(field)
+    }
+
+    /// Whether the field is `repeated`.
+    pub const fn is_repeated(&self) -> bool {
+        self.label == 3
+    }
+
+    /// Proto name of a scalar field type (`uint64`, `string`, ...), or `None` for messages and enums.
+    pub const fn scalar_name(&self) -> Option<&'static str> {
+        Some(match self.kind {
+            1 => "double",
+            2 => "float",
+            3 => "int64",
+            4 => "uint64",
+            5 => "int32",
+            6 => "fixed64",
+            7 => "fixed32",
+            8 => "bool",
+            9 => "string",
+            12 => "bytes",
+            13 => "uint32",
+            15 => "sfixed32",
+            16 => "sfixed64",
+            17 => "sint32",
+            18 => "sint64",
+            _ => return None,
+        })
+    }
+}
+
+impl EnumDescriptor {
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut descriptor = EnumDescriptor::default();
+        for_each_field(bytes, |tag, value| {
+            match tag {
+                1 => descriptor.name = value.string()?,
+                2 => {
+                    let mut name = String::new();
+                    let mut number = 0;
+                    for_each_field(value.bytes()?, |tag, value| {
+                        match tag {
+                            1 => name = value.string()?,
+                            2 => number = value.varint()? as i32,
+                            _ => {}
+                        }
+                        Ok(())
+                    })?;
+                    descriptor.values.push((name, number));
+                }
+                _ => {}
+            }
+            Ok(())
+        })?;
+        Ok(descriptor)
+    }
+}
+
+impl ServiceDescriptor {
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut service = ServiceDescriptor::default();
+        for_each_field(bytes, |tag, value| {
+            match tag {
+                1 => service.name = value.string()?,
+                2 => service.methods.push(RpcDescriptor::decode(value.bytes()?)?),
+                _ => {}
+            }
+            Ok(())
+        })?;
+        Ok(service)
+    }
+}
+
+impl RpcDescriptor {
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut method = RpcDescriptor::default();
+        for_each_field(bytes, |tag, value| {
+            match tag {
+                1 => method.name = value.string()?,
+                2 => method.input_type = value.string()?,
+                3 => method.output_type = value.string()?,
+                5 => method.client_streaming = value.varint()? != 0,
+                6 => method.server_streaming = value.varint()? != 0,
+                _ => {}
+            }
+            Ok(())
+        })?;
+        Ok(method)
+    }
+}
+
+fn collect_messages<'a>(name: &str, message: &'a MessageDescriptor, out: &mut Vec<(String, &'a MessageDescriptor)>) {
+    out.push((name.to_string(), message));
+    for nested in &message.nested {
+        collect_messages(&format!("{name}.{}", nested.name), nested, out);
+    }
+}
+
+fn qualify(package: &str, name: &str) -> String {
+    if package.is_empty() {
+        name.to_string()
+    } else {
+        format!("{package}.{name}")
+    }
+}
+
+/// Value of one field of a descriptor message.
+enum FieldValue<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Other,
+}
+
+impl<'a> FieldValue<'a> {
+    fn varint(&self) -> Result<u64, DecodeError> {
+        match self {
+            FieldValue::Varint(value) => Ok(*value),
+            _ => Err(DecodeError::new("invalid wire type in descriptor")),
+        }
+    }
+
+    fn bytes(&self) -> Result<&'a [u8], DecodeError> {
+        match self {
+            FieldValue::Bytes(bytes) => Ok(bytes),
+            _ => Err(DecodeError::new("invalid wire type in descriptor")),
+        }
+    }
+
+    fn string(&self) -> Result<String, DecodeError> {
+        core::str::from_utf8(self.bytes()?)
+            .map(ToString::to_string)
+            .map_err(|_| DecodeError::new("invalid string value: data is not UTF-8 encoded"))
+    }
+}
+
+/// Calls `visit` with every field of the message in `buf`, in wire order.
+fn for_each_field<'a>(mut buf: &'a [u8], mut visit: impl FnMut(u32, FieldValue<'a>) -> Result<(), DecodeError>) -> Result<(), DecodeError> {
+    let ctx = DecodeContext::default();
+    while !buf.is_empty() {
+        let (tag, wire_type) = decode_key(&mut buf)?;
+        let value = match wire_type {
+            WireType::Varint => FieldValue::Varint(decode_varint(&mut buf)?),
+            WireType::LengthDelimited => {
+                let len = decode_varint(&mut buf)? as usize;
+                if len > buf.len() {
+                    return Err(DecodeError::new("buffer underflow"));
+                }
+                let (field, rest) = buf.split_at(len);
+                buf = rest;
+                FieldValue::Bytes(field)
+            }
+            _ => {
+                skip_field(wire_type, tag, &mut buf, ctx)?;
+                FieldValue::Other
+            }
+        };
+        visit(tag, value)?;
+    }
+    Ok(())
+}
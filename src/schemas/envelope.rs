@@ -0,0 +1,134 @@
+//! Messages that carry the type URL and schema hash of their payload.
+
+use super::ProtoIdentifiable;
+use super::schema_hash;
+use super::type_url_name;
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::ProtoDecode;
+use crate::ProtoEncode;
+use crate::encoding::WireType;
+use crate::encoding::bytes;
+use crate::encoding::decode_key;
+use crate::encoding::decode_varint;
+use crate::encoding::fixed64;
+use crate::encoding::skip_field;
+use crate::encoding::string;
+use crate::traits::ProtoExt;
+
+/// A value wrapped with the type URL and [`schema_hash`] of its type, so consumers can route it
+/// and check its schema without knowing the type up front.
+///
+/// On the wire it is an ordinary message that any protobuf library can read:
+///
+/// ```proto
+/// message SelfDescribing {
+///   string type_url = 1;
+///   fixed64 schema_hash = 2;
+///   bytes payload = 3;
+/// }
+/// ```
+///
+/// [`TypeRegistry::decode_any_registered`](super::TypeRegistry::decode_any_registered) decodes
+/// an envelope of any registered type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfDescribing<T> {
+    /// `type.googleapis.com/package.Name` of the payload type.
+    pub type_url: String,
+    /// Hash of the payload's `.proto` definition in the writer's build, `0` if it was not registered.
+    pub schema_hash: u64,
+    pub value: T,
+}
+
+impl<T: ProtoIdentifiable> SelfDescribing<T> {
+    pub fn new(value: T) -> Self {
+        let ident = T::PROTO_IDENT;
+        Self {
+            type_url: ident.type_url(),
+            schema_hash: schema_hash(&ident).unwrap_or_default(),
+            value,
+        }
+    }
+
+    /// Whether the payload was written against the definition of `T` in this build.
+    pub fn is_current(&self) -> bool {
+        schema_hash(&T::PROTO_IDENT) == Some(self.schema_hash)
+    }
+}
+
+impl<T: ProtoEncode + ProtoExt> SelfDescribing<T> {
+    pub fn encode_to_vec(&self) -> Vec<u8> {
+        let payload = self.value.encode_to_vec();
+        let mut buf = Vec::with_capacity(self.type_url.len() + payload.len() + 24);
+        string::encode_tagged(1, &self.type_url, &mut buf);
+        fixed64::encode_tagged(2, self.schema_hash, &mut buf);
+        bytes::encode_tagged(3, &payload, &mut buf);
+        buf
+    }
+}
+
+impl<T: ProtoDecode + ProtoIdentifiable> SelfDescribing<T> {
+    /// Decodes an envelope holding a `T`, failing when it names another type.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::decode_with(bytes, DecodeContext::default())
+    }
+
+    pub fn decode_with(bytes: &[u8], ctx: DecodeContext) -> Result<Self, DecodeError> {
+        let header = Header::parse(bytes)?;
+        let expected = T::PROTO_IDENT.full_name();
+        if type_url_name(header.type_url) != expected {
+            return Err(DecodeError::new(format!("envelope holds {}, expected {expected}", header.type_url)));
+        }
+        Ok(Self {
+            type_url: header.type_url.to_string(),
+            schema_hash: header.schema_hash,
+            value: T::decode(header.payload, ctx)?,
+        })
+    }
+}
+
+/// Envelope fields, with the payload still encoded.
+pub(super) struct Header<'a> {
+    pub type_url: &'a str,
+    pub schema_hash: u64,
+    pub payload: &'a [u8],
+}
+
+impl<'a> Header<'a> {
+    pub fn parse(mut buf: &'a [u8]) -> Result<Self, DecodeError> {
+        let ctx = DecodeContext::default();
+        let mut header = Header {
+            type_url: "",
+            schema_hash: 0,
+            payload: &[],
+        };
+        while !buf.is_empty() {
+            let (tag, wire_type) = decode_key(&mut buf)?;
+            match tag {
+                1 | 3 => {
+                    if wire_type != WireType::LengthDelimited {
+                        return Err(DecodeError::new(format!("invalid wire type for envelope field {tag}")));
+                    }
+                    let len = decode_varint(&mut buf)? as usize;
+                    if len > buf.len() {
+                        return Err(DecodeError::new("buffer underflow"));
+                    }
+                    let (field, rest) = buf.split_at(len);
+                    buf = rest;
+                    if tag == 1 {
+                        header.type_url =
+                            core::str::from_utf8(field).map_err(|_| DecodeError::new("invalid string value: data is not UTF-8 encoded"))?;
+                    } else {
+                        header.payload = field;
+                    }
+                }
+                2 => fixed64::merge(wire_type, &mut header.schema_hash, &mut buf, ctx)?,
+                _ => skip_field(wire_type, tag, &mut buf, ctx)?,
+            }
+        }
+        if header.type_url.is_empty() {
+            return Err(DecodeError::new("envelope has no type URL"));
+        }
+        Ok(header)
+    }
+}
@@ -0,0 +1,192 @@
+//! Java and Kotlin file options for [`write_all`](super::write_all).
+//!
+//! With a [`JvmLayout`] set on the [`RustClientCtx`](super::RustClientCtx), every file gets
+//! `java_package`, `java_multiple_files` and `java_outer_classname` options derived from the Rust
+//! modules declaring its types, so the classes `protoc` generates for JVM consumers keep their
+//! names as long as the Rust types stay where they are. The parent modules become the Java
+//! package and the innermost module the outer class; a file declaring a single service uses
+//! `{Service}Proto` instead. `protoc --kotlin_out` reads the same options.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::ProtoEntry;
+use super::ProtoSchema;
+
+/// Java identifiers that cannot be package segments; they get a `_` suffix.
+const JAVA_KEYWORDS: &[&str] = &[
+    "abstract",
+    "assert",
+    "boolean",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extends",
+    "false",
+    "final",
+    "finally",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "implements",
+    "import",
+    "instanceof",
+    "int",
+    "interface",
+    "long",
+    "native",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "short",
+    "static",
+    "strictfp",
+    "super",
+    "switch",
+    "synchronized",
+    "this",
+    "throw",
+    "throws",
+    "transient",
+    "true",
+    "try",
+    "void",
+    "volatile",
+    "while",
+];
+
+/// Settings for the Java file options.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JvmLayout {
+    base_package: String,
+    nested: bool,
+    packages: BTreeMap<String, String>,
+    outer_classnames: BTreeMap<String, String>,
+}
+
+impl JvmLayout {
+    /// Layout whose Java packages start with `base_package`, e.g. `com.acme`.
+    pub fn new(base_package: &str) -> Self {
+        Self {
+            base_package: base_package.trim_matches('.').to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Writes `java_multiple_files = false`, so the classes of a file are nested in its outer
+    /// class, mirroring the Rust module they come from. By default every type gets its own file.
+    #[must_use]
+    pub const fn nested(mut self) -> Self {
+        self.nested = true;
+        self
+    }
+
+    /// Pins the Java package of the files in proto package `proto_package`, e.g. to keep it after
+    /// the Rust types move to another module.
+    #[must_use]
+    pub fn java_package(mut self, proto_package: &str, java_package: &str) -> Self {
+        self.packages.insert(proto_package.to_string(), java_package.to_string());
+        self
+    }
+
+    /// Pins the outer class of registry file `file_name` (e.g. `protos/billing.proto`).
+    #[must_use]
+    pub fn outer_classname(mut self, file_name: &str, classname: &str) -> Self {
+        self.outer_classnames.insert(file_name.to_string(), classname.to_string());
+        self
+    }
+
+    /// `option java_*` lines for registry file `file_name` in package `package_name`.
+    pub(super) fn file_options(&self, file_name: &str, entries: &[&ProtoSchema], package_name: &str) -> String {
+        let modules = common_module(entries);
+        let (parents, innermost) = match modules.split_last() {
+            Some((innermost, parents)) => (parents, Some(*innermost)),
+            None => (&[][..], None),
+        };
+
+        let java_package = self.packages.get(package_name).cloned().unwrap_or_else(|| {
+            let mut segments: Vec<String> = self.base_package.split('.').filter(|segment| !segment.is_empty()).map(String::from).collect();
+            segments.extend(parents.iter().map(|segment| java_segment(segment)));
+            segments.join(".")
+        });
+        let outer_classname =
+            self.outer_classnames.get(file_name).cloned().unwrap_or_else(|| outer_classname(file_name, entries, innermost));
+
+        let mut options = String::new();
+        if !java_package.is_empty() {
+            writeln!(options, "option java_package = \"{java_package}\";").unwrap();
+        }
+        writeln!(options, "option java_multiple_files = {};", !self.nested).unwrap();
+        writeln!(options, "option java_outer_classname = \"{outer_classname}\";").unwrap();
+        options
+    }
+}
+
+/// Module path shared by the types of a file, without the crate name.
+fn common_module(entries: &[&ProtoSchema]) -> Vec<&'static str> {
+    let mut paths = entries
+        .iter()
+        .filter(|entry| !matches!(entry.content, ProtoEntry::Import { .. }) && !entry.id.module_path.is_empty())
+        .map(|entry| entry.id.module_path.split("::").skip(1).collect::<Vec<_>>());
+    let Some(mut common) = paths.next() else {
+        return Vec::new();
+    };
+    for path in paths {
+        let shared = common.iter().zip(&path).take_while(|(left, right)| left == right).count();
+        common.truncate(shared);
+    }
+    common
+}
+
+/// `{Service}Proto` for a file with one service, otherwise the innermost module (or the file
+/// stem) in PascalCase followed by `Proto`. `OuterClass` is appended when that names one of the
+/// file's types, as `protoc` does.
+fn outer_classname(file_name: &str, entries: &[&ProtoSchema], innermost: Option<&str>) -> String {
+    let mut services = entries.iter().filter(|entry| matches!(entry.content, ProtoEntry::Service { .. }));
+    let base = if let (Some(service), None) = (services.next(), services.next()) {
+        service.id.name.to_string()
+    } else {
+        let stem = std::path::Path::new(file_name).file_stem().and_then(|stem| stem.to_str()).unwrap_or(file_name);
+        pascal_case(innermost.unwrap_or(stem))
+    };
+    let classname = format!("{base}Proto");
+    if entries.iter().any(|entry| entry.id.name == classname) {
+        format!("{classname}OuterClass")
+    } else {
+        classname
+    }
+}
+
+fn java_segment(segment: &str) -> String {
+    let segment = segment.trim_start_matches("r#");
+    if JAVA_KEYWORDS.contains(&segment) {
+        format!("{segment}_")
+    } else {
+        segment.to_string()
+    }
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split(|ch: char| !ch.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map(|first| first.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+        })
+        .collect()
+}
@@ -0,0 +1,163 @@
+//! Custom options from `#[proto(option("acme.priority") = 3)]`.
+//!
+//! Every option is an extension of one of the `google.protobuf.*Options` messages. The extensions
+//! of package `acme` are declared in `acme_options.proto`, written by [`write_all`](super::write_all)
+//! and imported by every file using them, so `protoc` and tools reading the descriptors see the
+//! options as typed values.
+//!
+//! Extension numbers are derived from the option name and fall in 50000-99999, the range left to
+//! in-house options, so they stay the same across builds as long as the name does.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::io;
+
+use super::Field;
+use super::ProtoEntry;
+use super::ProtoOption;
+use super::ProtoSchema;
+use super::fnv1a;
+
+const FIRST_NUMBER: u64 = 50_000;
+const NUMBERS: u64 = 50_000;
+
+/// An option as declared in the support file of its package.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct Extension {
+    pub(super) name: &'static str,
+    pub(super) extendee: &'static str,
+    pub(super) value_type: &'static str,
+    pub(super) number: u32,
+}
+
+/// Extensions declared by the options used in `entries`, by package.
+///
+/// Fails when an option is used on two kinds of items, with values of two types, or when two
+/// options of one kind hash to the same number.
+pub(super) fn collect(entries: &[&ProtoSchema]) -> io::Result<BTreeMap<&'static str, Vec<Extension>>> {
+    let mut declared: BTreeMap<&'static str, (&'static str, &'static str)> = BTreeMap::new();
+    for (extendee, option) in uses(entries) {
+        let Some((declared_extendee, declared_type)) = declared.get_mut(option.name) else {
+            declared.insert(option.name, (extendee, option.value_type));
+            continue;
+        };
+        if *declared_extendee != extendee {
+            return Err(invalid(format!(
+                "option {} is used on both {declared_extendee} and {extendee}",
+                option.name
+            )));
+        }
+        *declared_type = match (*declared_type, option.value_type) {
+            (declared, used) if declared == used => declared,
+            ("int64", "double") | ("double", "int64") => "double",
+            (declared, used) => return Err(invalid(format!("option {} has both {declared} and {used} values", option.name))),
+        };
+    }
+
+    let mut numbers: BTreeMap<(&str, u32), &str> = BTreeMap::new();
+    let mut packages: BTreeMap<&'static str, Vec<Extension>> = BTreeMap::new();
+    for (full_name, (extendee, value_type)) in declared {
+        let number = extension_number(full_name);
+        if let Some(other) = numbers.insert((extendee, number), full_name) {
+            return Err(invalid(format!(
+                "options {other} and {full_name} both get extension number {number}, rename one of them"
+            )));
+        }
+        let (package, name) = split_name(full_name);
+        packages.entry(package).or_default().push(Extension {
+            name,
+            extendee,
+            value_type,
+            number,
+        });
+    }
+    Ok(packages)
+}
+
+/// Packages of the options used in `entries`, whose support files they import.
+pub(super) fn packages_used(entries: &[&ProtoSchema]) -> BTreeSet<&'static str> {
+    uses(entries).map(|(_, option)| split_name(option.name).0).collect()
+}
+
+/// Name of the support file declaring the options of `package`.
+pub(super) fn file_name(package: &str) -> String {
+    format!("{}_options.proto", package.replace('.', "_"))
+}
+
+/// The support file declaring `extensions` of `package`.
+pub(super) fn render(package: &str, extensions: &[Extension]) -> String {
+    let mut output = String::new();
+    output.push_str("//CODEGEN BELOW - DO NOT TOUCH ME\n");
+    output.push_str("syntax = \"proto3\";\n");
+    writeln!(output, "package {package};").unwrap();
+    output.push_str("\nimport \"google/protobuf/descriptor.proto\";\n");
+
+    let mut by_extendee: BTreeMap<&str, Vec<&Extension>> = BTreeMap::new();
+    for extension in extensions {
+        by_extendee.entry(extension.extendee).or_default().push(extension);
+    }
+    for (extendee, extensions) in by_extendee {
+        writeln!(output, "\nextend google.protobuf.{extendee} {{").unwrap();
+        for extension in extensions {
+            writeln!(
+                output,
+                "  optional {} {} = {};",
+                extension.value_type, extension.name, extension.number
+            )
+            .unwrap();
+        }
+        output.push_str("}\n");
+    }
+    output
+}
+
+/// ` [(acme.priority) = 3]` after a field, or nothing without options.
+pub(super) fn field_suffix(options: &[ProtoOption]) -> String {
+    if options.is_empty() {
+        return String::new();
+    }
+    let options: Vec<String> = options.iter().map(|option| format!("({}) = {}", option.name, option.value)).collect();
+    format!(" [{}]", options.join(", "))
+}
+
+/// `option (acme.priority) = 3;` lines inside a message, enum, service or rpc body.
+pub(super) fn statements(options: &[ProtoOption], indent: &str) -> Vec<String> {
+    options.iter().map(|option| format!("{indent}option ({}) = {};", option.name, option.value)).collect()
+}
+
+fn extension_number(full_name: &str) -> u32 {
+    let hash = fnv1a(super::FNV_OFFSET, full_name.as_bytes());
+    u32::try_from(FIRST_NUMBER + hash % NUMBERS).expect("extension numbers fit in u32")
+}
+
+fn split_name(full_name: &'static str) -> (&'static str, &'static str) {
+    full_name.rsplit_once('.').expect("option names are checked to have a package")
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Every option use in `entries` with the options message it extends.
+fn uses<'a>(entries: &'a [&'a ProtoSchema]) -> impl Iterator<Item = (&'static str, &'static ProtoOption)> + 'a {
+    entries.iter().flat_map(|entry| {
+        let item_extendee = match entry.content {
+            ProtoEntry::Struct { .. } | ProtoEntry::ComplexEnum { .. } | ProtoEntry::Import { .. } => "MessageOptions",
+            ProtoEntry::SimpleEnum { .. } => "EnumOptions",
+            ProtoEntry::Service { .. } => "ServiceOptions",
+        };
+        let fields: Vec<&'static Field> = match entry.content {
+            ProtoEntry::Struct { fields } => fields.to_vec(),
+            ProtoEntry::ComplexEnum { variants } => variants.iter().flat_map(|variant| variant.fields.iter().copied()).collect(),
+            _ => Vec::new(),
+        };
+        let methods = match entry.content {
+            ProtoEntry::Service { methods, .. } => methods,
+            _ => &[],
+        };
+        let field_uses = fields.into_iter().flat_map(|field| field.options.iter().map(|option| ("FieldOptions", option)));
+        let method_uses = methods.iter().flat_map(|method| method.options.iter().map(|option| ("MethodOptions", option)));
+        entry.options.iter().map(move |option| (item_extendee, option)).chain(field_uses).chain(method_uses)
+    })
+}
@@ -0,0 +1,519 @@
+//! Size-bounded debug text of encoded messages, for logging.
+//!
+//! [`preview`] walks a message with its registered schema and renders it in the style of `Debug`,
+//! but stops adding detail once the text reaches a byte budget: long strings are cut, bytes
+//! fields show their length and a short hex prefix, and lists, maps and messages close early with
+//! a count of what was left out. Fields marked `#[proto(sensitive)]` print as `<redacted>`. The
+//! result always has balanced delimiters, so a multi-megabyte message logs as one short line:
+//!
+//! ```text
+//! Upload { name: "report.csv", data: <4194304 bytes: 89504e47..>, tags: ["q3", .. 998 more] }
+//! ```
+
+use std::fmt::Write;
+
+use bytes::Buf;
+
+use super::Field;
+use super::ProtoEntry;
+use super::ProtoIdent;
+use super::ProtoIdentifiable;
+use super::ProtoLabel;
+use super::ProtoType;
+use super::Variant;
+use super::message_by_name;
+use super::schema_for;
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::ProtoEncode;
+use crate::ProtoExt;
+use crate::encoding::WireType;
+use crate::encoding::decode_key;
+use crate::encoding::decode_varint;
+use crate::encoding::skip_field;
+
+/// Bytes of a `bytes` field shown in hex at most.
+const HEX_PREVIEW: usize = 16;
+
+/// Size-bounded debug text of a message, see [`preview`].
+pub trait Preview {
+    /// Debug text of `self` that stops growing once it reaches `max_bytes`.
+    ///
+    /// Falls back to the type name and encoded length when the type has no registered schema.
+    fn preview(&self, max_bytes: usize) -> String;
+}
+
+impl<T> Preview for T
+where
+    T: ProtoEncode + ProtoExt + ProtoIdentifiable,
+{
+    fn preview(&self, max_bytes: usize) -> String {
+        let bytes = self.encode_to_vec();
+        preview_message(&T::PROTO_IDENT, &bytes, max_bytes).unwrap_or_else(|_| format!("{} <{} bytes>", T::PROTO_IDENT.name, bytes.len()))
+    }
+}
+
+/// Renders `bytes`, a `type_name` message, as debug text of about `max_bytes`.
+///
+/// Once the text reaches `max_bytes`, every open message, list and map is closed with an elision
+/// marker, so the result may exceed the budget by those closing characters.
+///
+/// `type_name` is the full proto name (`package.Message`), or the bare message name for messages
+/// without a package.
+///
+/// # Errors
+///
+/// Returns an error if no message is registered under `type_name` or `bytes` is not a valid
+/// encoding of it.
+pub fn preview(type_name: &str, bytes: &[u8], max_bytes: usize) -> Result<String, DecodeError> {
+    let schema = message_by_name(type_name).ok_or_else(|| DecodeError::new(format!("no registered message named {type_name}")))?;
+    preview_message(&schema.id, bytes, max_bytes)
+}
+
+fn preview_message(ident: &ProtoIdent, bytes: &[u8], max_bytes: usize) -> Result<String, DecodeError> {
+    let decoder = Decoder { cap: max_bytes };
+    let node = decoder.message(ident, bytes, DecodeContext::default())?;
+    let mut renderer = Renderer {
+        out: String::new(),
+        max: max_bytes,
+    };
+    renderer.node(&node);
+    Ok(renderer.out)
+}
+
+/// Decoded value, keeping strings and bytes borrowed from the payload.
+enum Node<'a> {
+    Scalar(String),
+    Text(&'a str),
+    Bytes(&'a [u8]),
+    Redacted,
+    Message {
+        name: &'static str,
+        fields: Vec<(u32, String, Node<'a>)>,
+    },
+    Tuple {
+        name: &'static str,
+        value: Box<Node<'a>>,
+    },
+    List {
+        items: Vec<Node<'a>>,
+        more: usize,
+    },
+    Map {
+        entries: Vec<(Node<'a>, Node<'a>)>,
+        more: usize,
+    },
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Scalar(ProtoType),
+    Enum(&'static [&'static Variant]),
+    Message(ProtoIdent),
+}
+
+fn kind(ident: &ProtoIdent) -> Result<Kind, DecodeError> {
+    match ident.proto_type {
+        ProtoType::Message(_) => match schema_for(ident).map(|schema| schema.content) {
+            Some(ProtoEntry::SimpleEnum { variants }) => Ok(Kind::Enum(variants)),
+            Some(ProtoEntry::Struct { .. } | ProtoEntry::ComplexEnum { .. }) => Ok(Kind::Message(*ident)),
+            _ => Err(DecodeError::new(format!("no registered message named {}", ident.name))),
+        },
+        ProtoType::Optional(inner) | ProtoType::Repeated(inner) => Ok(Kind::Scalar(*inner)),
+        ty => Ok(Kind::Scalar(ty)),
+    }
+}
+
+struct Decoder {
+    /// Items kept per list or map; the rest are only counted, as the budget could not show them.
+    cap: usize,
+}
+
+impl Decoder {
+    fn message<'a>(&self, ident: &ProtoIdent, bytes: &'a [u8], ctx: DecodeContext) -> Result<Node<'a>, DecodeError> {
+        ctx.limit_reached()?;
+        match schema_for(ident).map(|schema| schema.content) {
+            Some(ProtoEntry::Struct { fields }) => self.fields(ident.name, fields, bytes, ctx),
+            Some(ProtoEntry::ComplexEnum { variants }) => {
+                let mut buf = bytes;
+                let mut node = Node::Message {
+                    name: ident.name,
+                    fields: Vec::new(),
+                };
+                while buf.has_remaining() {
+                    let (tag, wire_type) = decode_key(&mut buf)?;
+                    let Some(variant) = (tag as usize).checked_sub(1).and_then(|idx| variants.get(idx)) else {
+                        skip_field(wire_type, tag, &mut buf, ctx)?;
+                        continue;
+                    };
+                    // Only the last variant on the wire is set.
+                    node = match variant.fields {
+                        [] => {
+                            skip_field(wire_type, tag, &mut buf, ctx)?;
+                            Node::Scalar(variant.name.to_string())
+                        }
+                        [field] if field.name.is_none() => {
+                            let value = if field.is_sensitive() {
+                                skip_field(wire_type, tag, &mut buf, ctx)?;
+                                Node::Redacted
+                            } else {
+                                self.value(kind(&field.proto_ident)?, wire_type, &mut buf, ctx)?
+                            };
+                            Node::Tuple {
+                                name: variant.name,
+                                value: Box::new(value),
+                            }
+                        }
+                        fields => {
+                            let nested = length_delimited(wire_type, &mut buf)?;
+                            self.fields(variant.name, fields, nested, ctx.enter_recursion())?
+                        }
+                    };
+                }
+                Ok(node)
+            }
+            _ => Err(DecodeError::new(format!("no registered message named {}", ident.name))),
+        }
+    }
+
+    fn fields<'a>(&self, name: &'static str, fields: &[&Field], bytes: &'a [u8], ctx: DecodeContext) -> Result<Node<'a>, DecodeError> {
+        let mut buf = bytes;
+        let mut out: Vec<(u32, String, Node<'a>)> = Vec::new();
+        while buf.has_remaining() {
+            let (tag, wire_type) = decode_key(&mut buf)?;
+            let Some((idx, field)) = fields.iter().enumerate().find(|(_, field)| field.tag == tag) else {
+                skip_field(wire_type, tag, &mut buf, ctx)?;
+                continue;
+            };
+            let repeated = field.proto_label == ProtoLabel::Repeated && !field.is_bytes();
+            let map = matches!(field.proto_ident.proto_type, ProtoType::Map { .. });
+            if !(repeated || map) || field.is_sensitive() {
+                let value = if field.is_sensitive() {
+                    skip_field(wire_type, tag, &mut buf, ctx)?;
+                    Node::Redacted
+                } else if field.is_bytes() {
+                    Node::Bytes(length_delimited(wire_type, &mut buf)?)
+                } else {
+                    self.value(kind(&field.proto_ident)?, wire_type, &mut buf, ctx)?
+                };
+                out.retain(|(existing, _, _)| *existing != tag);
+                out.push((tag, field.name.map_or_else(|| idx.to_string(), ToString::to_string), value));
+                continue;
+            }
+            let position = out.iter().position(|(existing, _, _)| *existing == tag).unwrap_or_else(|| {
+                let empty = if map {
+                    Node::Map {
+                        entries: Vec::new(),
+                        more: 0,
+                    }
+                } else {
+                    Node::List {
+                        items: Vec::new(),
+                        more: 0,
+                    }
+                };
+                out.push((tag, field.name.map_or_else(|| idx.to_string(), ToString::to_string), empty));
+                out.len() - 1
+            });
+            let (_, _, node) = &mut out[position];
+            match node {
+                Node::Map { entries, more } => {
+                    let entry = length_delimited(wire_type, &mut buf)?;
+                    if entries.len() < self.cap {
+                        entries.push(self.entry(field, entry, ctx)?);
+                    } else {
+                        *more += 1;
+                    }
+                }
+                Node::List { items, more } => self.items(field, wire_type, &mut buf, items, more, ctx)?,
+                _ => {}
+            }
+        }
+        Ok(Node::Message { name, fields: out })
+    }
+
+    /// Adds one occurrence of a repeated field, packed or not, to `items`.
+    fn items<'a>(
+        &self,
+        field: &Field,
+        wire_type: WireType,
+        buf: &mut &'a [u8],
+        items: &mut Vec<Node<'a>>,
+        more: &mut usize,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        let elem = kind(&field.array_elem.unwrap_or(field.proto_ident))?;
+        let packed = match elem {
+            Kind::Scalar(ty) => ty.is_packable(),
+            Kind::Enum(_) => true,
+            Kind::Message(_) => false,
+        };
+        if packed && wire_type == WireType::LengthDelimited {
+            let mut packed = length_delimited(wire_type, buf)?;
+            let item_wire_type = match elem {
+                Kind::Scalar(ty) => wire_type_of(ty),
+                _ => WireType::Varint,
+            };
+            while packed.has_remaining() {
+                let item = self.value(elem, item_wire_type, &mut packed, ctx)?;
+                if items.len() < self.cap {
+                    items.push(item);
+                } else {
+                    *more += 1;
+                }
+            }
+        } else if items.len() < self.cap {
+            items.push(self.value(elem, wire_type, buf, ctx)?);
+        } else {
+            skip_field(wire_type, field.tag, buf, ctx)?;
+            *more += 1;
+        }
+        Ok(())
+    }
+
+    fn entry<'a>(&self, field: &Field, bytes: &'a [u8], ctx: DecodeContext) -> Result<(Node<'a>, Node<'a>), DecodeError> {
+        let ProtoType::Map { key, value } = field.proto_ident.proto_type else {
+            return Err(DecodeError::new("invalid map field"));
+        };
+        let (key_ident, value_ident) = field.map_idents();
+        let key = key_ident.map_or(Ok(Kind::Scalar(*key)), |ident| kind(&ident))?;
+        let value = value_ident.map_or(Ok(Kind::Scalar(*value)), |ident| kind(&ident))?;
+        let mut buf = bytes;
+        let mut entry = (None, None);
+        while buf.has_remaining() {
+            let (tag, wire_type) = decode_key(&mut buf)?;
+            match tag {
+                1 => entry.0 = Some(self.value(key, wire_type, &mut buf, ctx)?),
+                2 => entry.1 = Some(self.value(value, wire_type, &mut buf, ctx)?),
+                _ => skip_field(wire_type, tag, &mut buf, ctx)?,
+            }
+        }
+        Ok((
+            entry.0.unwrap_or_else(|| default_node(key)),
+            entry.1.unwrap_or_else(|| default_node(value)),
+        ))
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    fn value<'a>(&self, kind: Kind, wire_type: WireType, buf: &mut &'a [u8], ctx: DecodeContext) -> Result<Node<'a>, DecodeError> {
+        let ty = match kind {
+            Kind::Message(ident) => {
+                let nested = length_delimited(wire_type, buf)?;
+                return self.message(&ident, nested, ctx.enter_recursion());
+            }
+            Kind::Enum(variants) => {
+                if wire_type != WireType::Varint {
+                    return Err(DecodeError::new("invalid wire type"));
+                }
+                return Ok(enum_node(variants, decode_varint(buf)? as i32));
+            }
+            Kind::Scalar(ty) => ty,
+        };
+        if wire_type != wire_type_of(ty) {
+            return Err(DecodeError::new("invalid wire type"));
+        }
+        let fixed = |buf: &mut &[u8], len: usize| {
+            if buf.remaining() < len {
+                Err(DecodeError::new("buffer underflow"))
+            } else {
+                Ok(())
+            }
+        };
+        let value = match ty {
+            ProtoType::Bool => (decode_varint(buf)? != 0).to_string(),
+            ProtoType::Int32 | ProtoType::Enum => (decode_varint(buf)? as i32).to_string(),
+            ProtoType::Uint32 => (decode_varint(buf)? as u32).to_string(),
+            ProtoType::Int64 => (decode_varint(buf)? as i64).to_string(),
+            ProtoType::Uint64 => decode_varint(buf)?.to_string(),
+            ProtoType::Sint32 => (zigzag(decode_varint(buf)?) as i32).to_string(),
+            ProtoType::Sint64 => zigzag(decode_varint(buf)?).to_string(),
+            ProtoType::Fixed32 => {
+                fixed(buf, 4)?;
+                buf.get_u32_le().to_string()
+            }
+            ProtoType::Sfixed32 => {
+                fixed(buf, 4)?;
+                buf.get_i32_le().to_string()
+            }
+            ProtoType::Fixed64 => {
+                fixed(buf, 8)?;
+                buf.get_u64_le().to_string()
+            }
+            ProtoType::Sfixed64 => {
+                fixed(buf, 8)?;
+                buf.get_i64_le().to_string()
+            }
+            ProtoType::Float => {
+                fixed(buf, 4)?;
+                format!("{:?}", buf.get_f32_le())
+            }
+            ProtoType::Double => {
+                fixed(buf, 8)?;
+                format!("{:?}", buf.get_f64_le())
+            }
+            ProtoType::String => {
+                let payload = length_delimited(wire_type, buf)?;
+                let text =
+                    core::str::from_utf8(payload).map_err(|_| DecodeError::new("invalid string value: data is not UTF-8 encoded"))?;
+                return Ok(Node::Text(text));
+            }
+            ProtoType::Bytes => return Ok(Node::Bytes(length_delimited(wire_type, buf)?)),
+            _ => return Err(DecodeError::new("unsupported field type")),
+        };
+        Ok(Node::Scalar(value))
+    }
+}
+
+/// Map key or value missing from its entry.
+fn default_node(kind: Kind) -> Node<'static> {
+    match kind {
+        Kind::Scalar(ProtoType::Bool) => Node::Scalar("false".to_string()),
+        Kind::Scalar(ProtoType::String) => Node::Text(""),
+        Kind::Scalar(ProtoType::Bytes) => Node::Bytes(&[]),
+        Kind::Scalar(ProtoType::Float | ProtoType::Double) => Node::Scalar("0.0".to_string()),
+        Kind::Scalar(_) => Node::Scalar("0".to_string()),
+        Kind::Enum(variants) => enum_node(variants, 0),
+        Kind::Message(ident) => Node::Message {
+            name: ident.name,
+            fields: Vec::new(),
+        },
+    }
+}
+
+fn enum_node(variants: &[&Variant], number: i32) -> Node<'static> {
+    let name = variants.iter().find(|variant| variant.discriminant.unwrap_or_default() == number).map(|variant| variant.name);
+    Node::Scalar(name.map_or_else(|| number.to_string(), ToString::to_string))
+}
+
+struct Renderer {
+    out: String,
+    max: usize,
+}
+
+impl Renderer {
+    const fn room(&self) -> usize {
+        self.max.saturating_sub(self.out.len())
+    }
+
+    fn node(&mut self, node: &Node<'_>) {
+        match node {
+            Node::Scalar(value) => self.out.push_str(value),
+            Node::Text(text) => self.text(text),
+            Node::Bytes(bytes) => self.bytes(bytes),
+            Node::Redacted => self.out.push_str("<redacted>"),
+            Node::Message { name, fields } => {
+                self.out.push_str(name);
+                if fields.is_empty() {
+                    self.out.push_str(" {}");
+                    return;
+                }
+                self.out.push_str(" { ");
+                for (idx, (_, field, value)) in fields.iter().enumerate() {
+                    if idx > 0 {
+                        self.out.push_str(", ");
+                    }
+                    if self.room() == 0 {
+                        self.out.push_str("..");
+                        break;
+                    }
+                    self.out.push_str(field);
+                    self.out.push_str(": ");
+                    self.node(value);
+                }
+                self.out.push_str(" }");
+            }
+            Node::Tuple { name, value } => {
+                self.out.push_str(name);
+                self.out.push('(');
+                self.node(value);
+                self.out.push(')');
+            }
+            Node::List { items, more } => {
+                self.out.push('[');
+                self.sequence(items.len(), *more, |this, idx| this.node(&items[idx]));
+                self.out.push(']');
+            }
+            Node::Map { entries, more } => {
+                self.out.push('{');
+                self.sequence(entries.len(), *more, |this, idx| {
+                    let (key, value) = &entries[idx];
+                    this.node(key);
+                    this.out.push_str(": ");
+                    this.node(value);
+                });
+                self.out.push('}');
+            }
+        }
+    }
+
+    /// Writes `len` comma-separated items while there is room, then the number left out.
+    fn sequence(&mut self, len: usize, more: usize, mut item: impl FnMut(&mut Self, usize)) {
+        for idx in 0..len {
+            if idx > 0 {
+                self.out.push_str(", ");
+            }
+            if self.room() == 0 {
+                write!(self.out, ".. {} more", len - idx + more).unwrap();
+                return;
+            }
+            item(self, idx);
+        }
+        if more > 0 {
+            write!(self.out, ", .. {more} more").unwrap();
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        // Quotes count against the budget.
+        let room = self.room().saturating_sub(2);
+        if text.len() <= room {
+            write!(self.out, "{text:?}").unwrap();
+            return;
+        }
+        let mut cut = room;
+        while !text.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        write!(self.out, "{:?}.. ({} bytes)", &text[..cut], text.len()).unwrap();
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        let shown = bytes.len().min(HEX_PREVIEW).min(self.room() / 2);
+        write!(self.out, "<{} bytes", bytes.len()).unwrap();
+        if shown > 0 {
+            self.out.push_str(": ");
+            for byte in &bytes[..shown] {
+                write!(self.out, "{byte:02x}").unwrap();
+            }
+            if shown < bytes.len() {
+                self.out.push_str("..");
+            }
+        }
+        self.out.push('>');
+    }
+}
+
+const fn wire_type_of(ty: ProtoType) -> WireType {
+    match ty {
+        ProtoType::Fixed32 | ProtoType::Sfixed32 | ProtoType::Float => WireType::ThirtyTwoBit,
+        ProtoType::Fixed64 | ProtoType::Sfixed64 | ProtoType::Double => WireType::SixtyFourBit,
+        ProtoType::String | ProtoType::Bytes | ProtoType::Message(_) | ProtoType::Map { .. } => WireType::LengthDelimited,
+        _ => WireType::Varint,
+    }
+}
+
+#[allow(clippy::cast_possible_wrap)]
+const fn zigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn length_delimited<'a>(wire_type: WireType, buf: &mut &'a [u8]) -> Result<&'a [u8], DecodeError> {
+    if wire_type != WireType::LengthDelimited {
+        return Err(DecodeError::new("invalid wire type"));
+    }
+    let len = decode_varint(buf)?;
+    let len = usize::try_from(len).ok().filter(|&len| len <= buf.len()).ok_or_else(|| DecodeError::new("buffer underflow"))?;
+    let (payload, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(payload)
+}
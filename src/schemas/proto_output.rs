@@ -6,13 +6,20 @@ use super::GenericArg;
 use super::ProtoEntry;
 use super::ProtoIdent;
 use super::ProtoLabel;
+use super::ProtoOption;
 use super::ProtoSchema;
 use super::ProtoType;
 use super::ServiceMethod;
 use super::Variant;
+use super::options;
+use super::utils::Newtype;
 use super::utils::WrapperKind;
 use super::utils::entry_sort_key;
+use super::utils::field_newtype;
+use super::utils::is_soa_schema;
 use super::utils::is_wrapper_schema;
+use super::utils::nest_parent;
+use super::utils::nested_type_path;
 use super::utils::proto_ident_base_type_name;
 use super::utils::proto_map_types;
 use super::utils::proto_scalar_type;
@@ -239,6 +246,81 @@ pub(crate) fn collect_generic_specializations(
     ordered
 }
 
+/// Generic messages without a `proto_path` (such as `ProtoResult`) have no file of their own.
+/// Adds them to a file's `entries` along with the specializations that file refers to.
+pub(crate) fn with_unplaced_generics<'a>(
+    entries: &[&'a ProtoSchema],
+    ident_index: &BTreeMap<ProtoIdent, &'static ProtoSchema>,
+    specializations: &BTreeMap<ProtoIdent, Vec<GenericSpecialization>>,
+) -> (Vec<&'a ProtoSchema>, BTreeMap<ProtoIdent, Vec<GenericSpecialization>>) {
+    let mut entries = entries.to_vec();
+    let mut specializations = specializations.clone();
+    let unplaced: Vec<&ProtoSchema> = ident_index
+        .values()
+        .copied()
+        .filter(|schema| schema.id.proto_file_path.is_empty())
+        .filter(|schema| schema.generics.iter().any(|generic| matches!(generic.kind, super::GenericKind::Type)))
+        .collect();
+    if unplaced.is_empty() {
+        return (entries, specializations);
+    }
+
+    let candidates: Vec<&ProtoSchema> = entries.iter().copied().chain(unplaced.iter().copied()).collect();
+    let local = collect_generic_specializations(&candidates, ident_index);
+    for schema in unplaced {
+        if let Some(specs) = local.get(&schema.id).filter(|specs| !specs.is_empty()) {
+            entries.push(schema);
+            specializations.insert(schema.id, specs.clone());
+        }
+    }
+    (entries, specializations)
+}
+
+/// `syntax`/`edition` and `package` lines opening the file holding `entries`.
+///
+/// Proto3 has no extension ranges, so a file with an extendable message is written as edition
+/// 2023 with proto3's implicit presence as the file default; see [`with_explicit_presence`].
+pub(crate) fn file_header(entries: &[&ProtoSchema], package_name: &str) -> String {
+    if uses_editions(entries) {
+        format!("edition = \"2023\";\npackage {package_name};\noption features.field_presence = IMPLICIT;\n")
+    } else {
+        format!("syntax = \"proto3\";\npackage {package_name};\n")
+    }
+}
+
+pub(crate) fn uses_editions(entries: &[&ProtoSchema]) -> bool {
+    entries.iter().any(|entry| !entry.extension_ranges.is_empty())
+}
+
+/// Rewrites the proto3 `optional` fields of a rendered definition for an edition 2023 file, where
+/// explicit presence is a field feature instead of a label.
+pub(crate) fn with_explicit_presence(definition: &str) -> String {
+    const EXPLICIT: &str = "features.field_presence = EXPLICIT";
+    let mut output = String::with_capacity(definition.len());
+    for line in definition.split_inclusive('\n') {
+        let body = line.trim_end_matches('\n');
+        let indent = &body[..body.len() - body.trim_start().len()];
+        let Some(field) = body.trim_start().strip_prefix("optional ") else {
+            output.push_str(line);
+            continue;
+        };
+        output.push_str(indent);
+        if let Some(field) = field.strip_suffix("];") {
+            output.push_str(field);
+            output.push_str(", ");
+            output.push_str(EXPLICIT);
+            output.push_str("];");
+        } else {
+            output.push_str(field.strip_suffix(';').unwrap_or(field));
+            output.push_str(" [");
+            output.push_str(EXPLICIT);
+            output.push_str("];");
+        }
+        output.push_str(&line[body.len()..]);
+    }
+    output
+}
+
 pub(crate) fn render_entries(
     entries: &[&ProtoSchema],
     package_name: &str,
@@ -265,6 +347,7 @@ pub(crate) fn render_entries(
     let mut rendered = Vec::new();
     rendered.extend(wrapper_definitions);
     let mut seen_proto_types = std::collections::BTreeSet::new();
+    let mut definitions = Vec::new();
 
     for entry in ordered_entries {
         if matches!(entry.content, ProtoEntry::Import { .. }) {
@@ -291,7 +374,43 @@ pub(crate) fn render_entries(
         }
 
         let specs = specializations.get(&entry.id);
-        rendered.extend(render_entry(entry, package_name, ident_index, specs));
+        definitions.push((entry, render_entry(entry, package_name, ident_index, specs)));
+    }
+    rendered.extend(nest_definitions(&definitions, ident_index));
+    rendered
+}
+
+/// Moves `nest_in` messages into the definitions of their parents.
+fn nest_definitions(definitions: &[(&ProtoSchema, Vec<String>)], ident_index: &BTreeMap<ProtoIdent, &'static ProtoSchema>) -> Vec<String> {
+    let parent_of = |entry: &ProtoSchema| {
+        if !nested_type_path(entry.id, ident_index).contains('.') {
+            return None;
+        }
+        let parent = nest_parent(entry)?;
+        definitions.iter().position(|(candidate, _)| proto_ident_base_type_name(candidate.id) == parent)
+    };
+    let parents: Vec<Option<usize>> = definitions.iter().map(|(entry, _)| parent_of(entry)).collect();
+
+    (0..definitions.len()).filter(|idx| parents[*idx].is_none()).flat_map(|idx| assemble_nested(idx, definitions, &parents)).collect()
+}
+
+fn assemble_nested(idx: usize, definitions: &[(&ProtoSchema, Vec<String>)], parents: &[Option<usize>]) -> Vec<String> {
+    let mut rendered = definitions[idx].1.clone();
+    let children: Vec<String> = (0..definitions.len())
+        .filter(|child| parents[*child] == Some(idx))
+        .flat_map(|child| assemble_nested(child, definitions, parents))
+        .collect();
+    if let Some(last) = rendered.last_mut().filter(|_| !children.is_empty()) {
+        let body = last.trim_end();
+        let mut output = body.strip_suffix('}').unwrap_or(body).trim_end().to_string();
+        for child in children {
+            output.push_str(if output.ends_with('{') { "\n" } else { "\n\n" });
+            let indented: Vec<String> =
+                child.trim().lines().map(|line| if line.is_empty() { String::new() } else { format!("  {line}") }).collect();
+            output.push_str(&indented.join("\n"));
+        }
+        output.push_str("\n}\n");
+        *last = output;
     }
     rendered
 }
@@ -523,12 +642,15 @@ const fn wrapper_prefix_for_kind(kind: WrapperKind) -> &'static str {
         WrapperKind::Option => "Option",
         WrapperKind::Vec => "Vec",
         WrapperKind::VecDeque => "VecDeque",
+        WrapperKind::BinaryHeap => "BinaryHeap",
+        WrapperKind::LinkedList => "LinkedList",
         WrapperKind::HashMap => "HashMap",
         WrapperKind::BTreeMap => "BTreeMap",
         WrapperKind::HashSet => "HashSet",
         WrapperKind::BTreeSet => "BTreeSet",
         WrapperKind::Box => "Box",
         WrapperKind::Arc => "Arc",
+        WrapperKind::Weak => "Weak",
         WrapperKind::Mutex => "Mutex",
         WrapperKind::ArcSwap => "ArcSwap",
         WrapperKind::ArcSwapOption => "ArcSwapOption",
@@ -549,7 +671,12 @@ fn render_wrapper_message(
             let field_type = proto_ident_type_name(ident, package_name, ident_index);
             let label = match kind {
                 WrapperKind::Option | WrapperKind::ArcSwapOption => "optional ",
-                WrapperKind::Vec | WrapperKind::VecDeque | WrapperKind::HashSet | WrapperKind::BTreeSet => "repeated ",
+                WrapperKind::Vec
+                | WrapperKind::VecDeque
+                | WrapperKind::BinaryHeap
+                | WrapperKind::LinkedList
+                | WrapperKind::HashSet
+                | WrapperKind::BTreeSet => "repeated ",
                 _ => "",
             };
             (label, field_type)
@@ -583,13 +710,17 @@ fn render_entry(
         for spec in specs {
             let substitution = build_substitution(&type_generics, &spec.args);
             let definition = match entry.content {
-                ProtoEntry::Struct { fields } => render_struct(&spec.name, fields, package_name, ident_index, Some(&substitution)),
-                ProtoEntry::SimpleEnum { variants } => render_simple_enum(&spec.name, variants),
+                ProtoEntry::Struct { fields } => {
+                    render_struct(&spec.name, entry, fields, package_name, ident_index, Some(&substitution))
+                }
+                ProtoEntry::SimpleEnum { variants } => render_simple_enum(&spec.name, entry.options, variants),
                 ProtoEntry::ComplexEnum { variants } => {
-                    render_complex_enum(&spec.name, variants, package_name, ident_index, Some(&substitution))
+                    render_complex_enum(&spec.name, entry.options, variants, package_name, ident_index, Some(&substitution))
                 }
                 ProtoEntry::Import { .. } => continue,
-                ProtoEntry::Service { methods, .. } => render_service(&spec.name, methods, package_name, ident_index, Some(&substitution)),
+                ProtoEntry::Service { methods, .. } => {
+                    render_service(&spec.name, entry.options, methods, package_name, ident_index, Some(&substitution))
+                }
             };
             rendered.push(definition);
         }
@@ -598,11 +729,13 @@ fn render_entry(
 
     let entry_name = wrapper_schema_message_name(entry).unwrap_or_else(|| proto_ident_base_type_name(entry.id));
     let definition = match entry.content {
-        ProtoEntry::Struct { fields } => render_struct(&entry_name, fields, package_name, ident_index, None),
-        ProtoEntry::SimpleEnum { variants } => render_simple_enum(&entry_name, variants),
-        ProtoEntry::ComplexEnum { variants } => render_complex_enum(&entry_name, variants, package_name, ident_index, None),
+        ProtoEntry::Struct { fields } => render_struct(&entry_name, entry, fields, package_name, ident_index, None),
+        ProtoEntry::SimpleEnum { variants } => render_simple_enum(&entry_name, entry.options, variants),
+        ProtoEntry::ComplexEnum { variants } => {
+            render_complex_enum(&entry_name, entry.options, variants, package_name, ident_index, None)
+        }
         ProtoEntry::Import { .. } => return Vec::new(),
-        ProtoEntry::Service { methods, .. } => render_service(&entry_name, methods, package_name, ident_index, None),
+        ProtoEntry::Service { methods, .. } => render_service(&entry_name, entry.options, methods, package_name, ident_index, None),
     };
 
     vec![definition]
@@ -627,25 +760,37 @@ fn build_substitution<'a>(type_generics: &'a [&'a str], args: &'a [GenericArg])
 
 fn render_struct(
     name: &str,
+    entry: &ProtoSchema,
     fields: &[&Field],
     package_name: &str,
     ident_index: &BTreeMap<ProtoIdent, &'static ProtoSchema>,
     substitution: Option<&BTreeMap<&str, ProtoIdent>>,
 ) -> String {
-    if fields.is_empty() {
+    if fields.is_empty() && entry.options.is_empty() && entry.extension_ranges.is_empty() && entry.reserved_tags.is_empty() {
         return format!("message {name} {{}}\n");
     }
 
-    let mut lines = Vec::new();
+    let mut lines = options::statements(entry.options, "  ");
     for (idx, field) in fields.iter().enumerate() {
         lines.push(render_field(field, idx, package_name, ident_index, substitution));
     }
+    if !entry.reserved_tags.is_empty() {
+        let tags: Vec<String> = entry.reserved_tags.iter().map(u32::to_string).collect();
+        lines.push(format!("  reserved {};", tags.join(", ")));
+    }
+    for &(start, end) in entry.extension_ranges {
+        if start == end {
+            lines.push(format!("  extensions {start};"));
+        } else {
+            lines.push(format!("  extensions {start} to {end};"));
+        }
+    }
 
     format!("message {name} {{\n{}\n}}\n", lines.join("\n"))
 }
 
-fn render_simple_enum(name: &str, variants: &[&Variant]) -> String {
-    let mut lines = Vec::new();
+fn render_simple_enum(name: &str, options: &[ProtoOption], variants: &[&Variant]) -> String {
+    let mut lines = options::statements(options, "  ");
     for variant in variants {
         let value = variant.discriminant.unwrap_or_default();
         lines.push(format!("  {} = {};", variant.name, value));
@@ -655,6 +800,7 @@ fn render_simple_enum(name: &str, variants: &[&Variant]) -> String {
 
 fn render_complex_enum(
     name: &str,
+    options: &[ProtoOption],
     variants: &[&Variant],
     package_name: &str,
     ident_index: &BTreeMap<ProtoIdent, &'static ProtoSchema>,
@@ -677,7 +823,8 @@ fn render_complex_enum(
 
         if variant.fields.len() == 1 && variant.fields[0].name.is_none() {
             let proto_type = field_type_name(variant.fields[0], package_name, ident_index, substitution);
-            oneof_fields.push(format!("    {proto_type} {field_name} = {tag};"));
+            let field_options = options::field_suffix(variant.fields[0].options);
+            oneof_fields.push(format!("    {proto_type} {field_name} = {tag}{field_options};"));
             continue;
         }
 
@@ -687,10 +834,12 @@ fn render_complex_enum(
         oneof_fields.push(format!("    {msg_name} {field_name} = {tag};"));
     }
 
+    let option_lines: String = options::statements(options, "  ").into_iter().map(|line| line + "\n").collect();
     format!(
-        "{}\nmessage {} {{\n  oneof value {{\n{}\n  }}\n}}\n",
+        "{}\nmessage {} {{\n{}  oneof value {{\n{}\n  }}\n}}\n",
         nested_messages.join("\n\n"),
         name,
+        option_lines,
         oneof_fields.join("\n")
     )
 }
@@ -714,7 +863,7 @@ fn render_named_fields(
 ///  - Fields already detected as `Bytes` by the derive macro (direct `Vec<u8>`, `Vec<AtomicU8>`, etc.)
 ///  - Fields whose wrapper is a collection (Vec, VecDeque, HashSet, BTreeSet) with a byte-like
 ///    inner element (`u8` or `AtomicU8`), including type-alias wrappers like `CustomVec<u8>`.
-fn is_bytes_proto_field(field: &Field) -> bool {
+pub(super) fn is_bytes_proto_field(field: &Field) -> bool {
     // The derive macro already identified this as bytes.
     if matches!(field.proto_ident.proto_type, ProtoType::Bytes) {
         return true;
@@ -747,36 +896,130 @@ fn render_field(
     substitution: Option<&BTreeMap<&str, ProtoIdent>>,
 ) -> String {
     let name = field.name.map_or_else(|| format!("field_{idx}"), ToString::to_string);
+    let field_options = options::field_suffix(field.options);
+
+    // `[T; N]` other than `[u8; N]`, including `[[u8; N]; M]` whose elements are bytes
+    if let Some(len) = field.array_len
+        && !field.array_is_bytes
+    {
+        let proto_type = field_type_name(field, package_name, ident_index, substitution);
+        return format!("  repeated {proto_type} {name} = {}{field_options}; // at most {len} elements", field.tag);
+    }
 
     if is_bytes_proto_field(field) {
         // Bytes fields are never "repeated" — the bytes scalar already represents a blob.
         // Preserve "optional" when the field is wrapped in Option.
         let label = if matches!(field.proto_label, ProtoLabel::Optional) { "optional " } else { "" };
-        return format!("  {label}bytes {name} = {};", field.tag);
+        return format!("  {label}bytes {name} = {}{field_options};", field.tag);
     }
 
-    let label = match proto_label_for_field(field) {
+    let label = match proto_label_for_field(field, ident_index) {
         ProtoLabel::None => "",
         ProtoLabel::Optional => "optional ",
         ProtoLabel::Repeated => "repeated ",
     };
     let proto_type = field_type_name(field, package_name, ident_index, substitution);
-    format!("  {label}{proto_type} {name} = {};", field.tag)
+    let comment = field_newtype(field, ident_index).map_or_else(String::new, |newtype| newtype_comment(&newtype));
+    format!("  {label}{proto_type} {name} = {}{field_options};{comment}", field.tag)
+}
+
+/// A field as [`render_field`] writes it: name, label and type name.
+pub(super) struct FieldSignature {
+    pub(super) name: String,
+    pub(super) tag: u32,
+    pub(super) label: ProtoLabel,
+    pub(super) type_name: String,
+}
+
+/// Signature of the fields a struct message is written with.
+pub(super) fn struct_signatures(
+    fields: &[&Field],
+    package_name: &str,
+    ident_index: &BTreeMap<ProtoIdent, &'static ProtoSchema>,
+) -> Vec<FieldSignature> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let name = field.name.map_or_else(|| format!("field_{idx}"), ToString::to_string);
+            let (label, type_name) = if field.array_len.is_some() && !field.array_is_bytes {
+                (ProtoLabel::Repeated, field_type_name(field, package_name, ident_index, None))
+            } else if is_bytes_proto_field(field) {
+                let label = if matches!(field.proto_label, ProtoLabel::Optional) { ProtoLabel::Optional } else { ProtoLabel::None };
+                (label, "bytes".to_string())
+            } else {
+                (proto_label_for_field(field, ident_index), field_type_name(field, package_name, ident_index, None))
+            };
+            FieldSignature {
+                name,
+                tag: field.tag,
+                label,
+                type_name,
+            }
+        })
+        .collect()
+}
+
+/// Signature of the oneof fields a complex enum is written with, as by [`render_complex_enum`].
+pub(super) fn complex_enum_signatures(
+    name: &str,
+    variants: &[&Variant],
+    package_name: &str,
+    ident_index: &BTreeMap<ProtoIdent, &'static ProtoSchema>,
+) -> Vec<FieldSignature> {
+    variants
+        .iter()
+        .enumerate()
+        .map(|(idx, variant)| {
+            let type_name = if variant.fields.len() == 1 && variant.fields[0].name.is_none() {
+                field_type_name(variant.fields[0], package_name, ident_index, None)
+            } else {
+                format!("{name}{}", variant.name)
+            };
+            FieldSignature {
+                name: to_snake_case(variant.name),
+                tag: idx as u32 + 1,
+                label: ProtoLabel::None,
+                type_name,
+            }
+        })
+        .collect()
+}
+
+/// Trailing comment with the unit and valid range of a newtype field.
+fn newtype_comment(newtype: &Newtype) -> String {
+    let mut parts = Vec::new();
+    if let Some(unit) = newtype.unit {
+        parts.push(format!("unit: {unit}"));
+    }
+    if let Some(range) = newtype.range {
+        parts.push(format!("range: {range}"));
+    }
+    format!(" // {}", parts.join(", "))
 }
 
-const fn proto_label_for_field(field: &Field) -> ProtoLabel {
+fn proto_label_for_field(field: &Field, ident_index: &BTreeMap<ProtoIdent, &'static ProtoSchema>) -> ProtoLabel {
+    // A soa container holds the rows of a repeated field
+    if matches!(field.proto_label, ProtoLabel::None) && ident_index.get(&field.proto_ident).is_some_and(|schema| is_soa_schema(schema)) {
+        return ProtoLabel::Repeated;
+    }
     field.proto_label
 }
 
 fn render_service(
     name: &str,
+    options: &[ProtoOption],
     methods: &[&ServiceMethod],
     package_name: &str,
     ident_index: &BTreeMap<ProtoIdent, &'static ProtoSchema>,
     substitution: Option<&BTreeMap<&str, ProtoIdent>>,
 ) -> String {
+    // Renamed routes share one `/package.Service/` prefix; the derive checks the package
+    let routed = methods.iter().find_map(|method| method.route).and_then(|route| route.strip_prefix('/')?.split_once('/'));
+    let name = routed.map_or(name, |(service, _)| service.rsplit('.').next().unwrap_or(service));
     let mut lines = Vec::new();
     lines.push(format!("service {name} {{"));
+    lines.extend(options::statements(options, "  "));
 
     for method in methods {
         let request_type = method_type_name(
@@ -800,7 +1043,14 @@ fn render_service(
         } else {
             response_type
         };
-        lines.push(format!("  rpc {}({}) returns ({});", method.name, request_type, response_type));
+        let signature = format!("  rpc {}({}) returns ({})", method.name, request_type, response_type);
+        if method.options.is_empty() {
+            lines.push(format!("{signature};"));
+        } else {
+            lines.push(format!("{signature} {{"));
+            lines.extend(options::statements(method.options, "    "));
+            lines.push("  }".to_string());
+        }
     }
 
     lines.push("}".to_string());
@@ -927,8 +1177,11 @@ fn wrapper_schema_message_name(schema: &ProtoSchema) -> Option<String> {
         WrapperKind::Option
         | WrapperKind::Vec
         | WrapperKind::VecDeque
+        | WrapperKind::BinaryHeap
+        | WrapperKind::LinkedList
         | WrapperKind::Box
         | WrapperKind::Arc
+        | WrapperKind::Weak
         | WrapperKind::Mutex
         | WrapperKind::ArcSwap
         | WrapperKind::ArcSwapOption
@@ -942,11 +1195,21 @@ fn wrapper_schema_message_name(schema: &ProtoSchema) -> Option<String> {
 const fn wrapper_kind_inline_for_field(field: &Field, kind: WrapperKind) -> bool {
     match kind {
         WrapperKind::Option | WrapperKind::ArcSwapOption => matches!(field.proto_label, ProtoLabel::Optional),
-        WrapperKind::Vec | WrapperKind::VecDeque | WrapperKind::HashSet | WrapperKind::BTreeSet => {
+        WrapperKind::Vec
+        | WrapperKind::VecDeque
+        | WrapperKind::BinaryHeap
+        | WrapperKind::LinkedList
+        | WrapperKind::HashSet
+        | WrapperKind::BTreeSet => {
             matches!(field.proto_label, ProtoLabel::Repeated)
         }
         WrapperKind::HashMap | WrapperKind::BTreeMap => proto_map_types(&field.proto_ident.proto_type).is_some(),
-        WrapperKind::Box | WrapperKind::Arc | WrapperKind::Mutex | WrapperKind::ArcSwap | WrapperKind::CachePadded => true,
+        WrapperKind::Box
+        | WrapperKind::Arc
+        | WrapperKind::Weak
+        | WrapperKind::Mutex
+        | WrapperKind::ArcSwap
+        | WrapperKind::CachePadded => true,
     }
 }
 
@@ -1024,9 +1287,9 @@ fn proto_const_segment(value: &str) -> String {
 fn proto_ident_type_name(ident: ProtoIdent, package_name: &str, ident_index: &BTreeMap<ProtoIdent, &'static ProtoSchema>) -> String {
     let ident = resolve_transparent_ident(ident, ident_index);
     if ident.proto_package_name.is_empty() || ident.proto_package_name == package_name {
-        proto_ident_base_type_name(ident)
+        nested_type_path(ident, ident_index)
     } else {
-        format!("{}.{}", ident.proto_package_name, proto_ident_base_type_name(ident))
+        format!("{}.{}", ident.proto_package_name, nested_type_path(ident, ident_index))
     }
 }
 
@@ -0,0 +1,430 @@
+//! Python dataclasses and gRPC clients for the registered schemas.
+//!
+//! [`write_all`] turns the output directory into a Python package with one module per proto
+//! package. Messages become dataclasses, simple enums `IntEnum`s and complex enums dataclasses
+//! with one optional attribute per variant, of which at most one is set. Every service gets a
+//! `{Service}Client` over a `grpc.Channel` (grpcio). The encoding is done by `_proto_rs.py`,
+//! written next to the modules, so no protoc step or `protobuf` package is involved:
+//!
+//! ```python
+//! from generated import orders
+//!
+//! client = orders.OrdersClient(grpc.insecure_channel("localhost:50051"))
+//! order = client.place(orders.Order(id=1, lines=[orders.Line(sku="tea")]))
+//! ```
+//!
+//! Generic messages and fields of types without a Python mapping are left out.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::Field;
+use super::ProtoEntry;
+use super::ProtoIdent;
+use super::ProtoLabel;
+use super::ProtoSchema;
+use super::ProtoType;
+use super::ServiceMethod;
+use super::Variant;
+use super::utils::is_wrapper_schema;
+use super::utils::resolve_transparent_ident;
+use super::utils::to_snake_case;
+
+const RUNTIME: &str = include_str!("python_runtime.py");
+
+const KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif", "else", "except",
+    "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try",
+    "while", "with", "yield",
+];
+
+/// Writes the Python package for every registered schema to `output_dir`, replacing what was
+/// there, and returns the number of files written.
+///
+/// # Errors
+///
+/// Will return `Err` if fs throws error
+pub fn write_all(output_dir: &str) -> io::Result<usize> {
+    match fs::remove_dir_all(output_dir) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+    fs::create_dir_all(output_dir)?;
+
+    let (registry, ident_index) = super::build_registry();
+    let mut modules: BTreeMap<String, Vec<&'static ProtoSchema>> = BTreeMap::new();
+    for schema in registry.values().flatten() {
+        if schema.generics.is_empty() && !matches!(schema.content, ProtoEntry::Import { .. }) && !is_wrapper_schema(schema) {
+            modules.entry(module_name(schema.id.proto_package_name)).or_default().push(schema);
+        }
+    }
+
+    let dir = Path::new(output_dir);
+    fs::write(dir.join("__init__.py"), "\"\"\"Generated by proto_rs. Do not edit.\"\"\"\n")?;
+    fs::write(dir.join("_proto_rs.py"), RUNTIME)?;
+    for (module, schemas) in &modules {
+        let mut writer = ModuleWriter {
+            module,
+            ident_index: &ident_index,
+            imports: BTreeSet::new(),
+            body: String::new(),
+        };
+        for schema in schemas {
+            writer.entry(schema);
+        }
+        fs::write(dir.join(format!("{module}.py")), writer.finish(schemas))?;
+    }
+    Ok(modules.len() + 2)
+}
+
+/// Python module of a proto package: `acme.billing.v1` is `acme_billing_v1`.
+fn module_name(package: &str) -> String {
+    if package.is_empty() {
+        return "messages".to_string();
+    }
+    package.chars().map(|ch| if ch.is_ascii_alphanumeric() { ch.to_ascii_lowercase() } else { '_' }).collect()
+}
+
+fn attribute_name(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("{name}_")
+    } else {
+        name.to_string()
+    }
+}
+
+fn python_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Value type of a field: a scalar with its codec kind, or a registered enum or message.
+enum Kind {
+    Scalar(&'static str),
+    Enum(&'static ProtoSchema),
+    Message(&'static ProtoSchema),
+}
+
+/// Codec kind of a scalar proto type.
+const fn scalar_kind(ty: ProtoType) -> Option<&'static str> {
+    Some(match ty {
+        ProtoType::Double => "double",
+        ProtoType::Float => "float",
+        ProtoType::Int32 => "int32",
+        ProtoType::Int64 => "int64",
+        ProtoType::Uint32 => "uint32",
+        ProtoType::Uint64 => "uint64",
+        ProtoType::Sint32 => "sint32",
+        ProtoType::Sint64 => "sint64",
+        ProtoType::Fixed32 => "fixed32",
+        ProtoType::Fixed64 => "fixed64",
+        ProtoType::Sfixed32 => "sfixed32",
+        ProtoType::Sfixed64 => "sfixed64",
+        ProtoType::Bool => "bool",
+        ProtoType::Bytes => "bytes",
+        ProtoType::String => "string",
+        ProtoType::Optional(inner) | ProtoType::Repeated(inner) => return scalar_kind(*inner),
+        ProtoType::Message(_) | ProtoType::Enum | ProtoType::Map { .. } | ProtoType::None => return None,
+    })
+}
+
+struct FieldSpec {
+    tag: u32,
+    attribute: String,
+    label: &'static str,
+    kind: String,
+    value_kind: Option<String>,
+    annotation: String,
+    default: String,
+}
+
+struct ModuleWriter<'a> {
+    module: &'a str,
+    ident_index: &'a BTreeMap<ProtoIdent, &'static ProtoSchema>,
+    imports: BTreeSet<String>,
+    body: String,
+}
+
+impl ModuleWriter<'_> {
+    fn kind(&self, ident: ProtoIdent) -> Option<Kind> {
+        let ident = resolve_transparent_ident(ident, self.ident_index);
+        if !matches!(ident.proto_type, ProtoType::Message(_)) {
+            return scalar_kind(ident.proto_type).map(Kind::Scalar);
+        }
+        let schema = *self.ident_index.get(&ident)?;
+        match schema.content {
+            ProtoEntry::SimpleEnum { .. } => Some(Kind::Enum(schema)),
+            ProtoEntry::Struct { .. } | ProtoEntry::ComplexEnum { .. } if schema.generics.is_empty() => Some(Kind::Message(schema)),
+            _ => None,
+        }
+    }
+
+    /// Python name of a generated class, qualified with its module when it lives in another one.
+    fn class_ref(&mut self, schema: &ProtoSchema) -> String {
+        let module = module_name(schema.id.proto_package_name);
+        if module == self.module {
+            schema.id.name.to_string()
+        } else {
+            let class = format!("{module}.{}", schema.id.name);
+            self.imports.insert(module);
+            class
+        }
+    }
+
+    /// Codec kind, annotation and default of one value of `kind`.
+    fn value(&mut self, kind: &Kind) -> (String, String, String) {
+        match kind {
+            Kind::Scalar(scalar) => {
+                let (annotation, default) = match *scalar {
+                    "double" | "float" => ("float", "0.0"),
+                    "bool" => ("bool", "False"),
+                    "string" => ("str", "\"\""),
+                    "bytes" => ("bytes", "b\"\""),
+                    _ => ("int", "0"),
+                };
+                ((*scalar).to_string(), annotation.to_string(), default.to_string())
+            }
+            Kind::Enum(schema) | Kind::Message(schema) => {
+                let prefix = if matches!(kind, Kind::Enum(_)) { "enum" } else { "message" };
+                let full_name = schema.id.full_name();
+                let default = format!("_dc.field(default_factory=_pr.default_of({}))", python_string(&full_name));
+                (format!("{prefix}:{full_name}"), self.class_ref(schema), default)
+            }
+        }
+    }
+
+    fn field(&mut self, field: &Field, idx: usize) -> Option<FieldSpec> {
+        let attribute = attribute_name(&to_snake_case(field.name.unwrap_or(&format!("field_{idx}"))));
+        let tag = field.tag;
+        if field.is_bytes() {
+            return Some(FieldSpec {
+                tag,
+                attribute,
+                label: "single",
+                kind: "bytes".to_string(),
+                value_kind: None,
+                annotation: "bytes".to_string(),
+                default: "b\"\"".to_string(),
+            });
+        }
+        if let ProtoType::Map { key, value } = field.proto_ident.proto_type {
+            let (key_ident, value_ident) = field.map_idents();
+            let key = key_ident.map_or(scalar_kind(*key).map(Kind::Scalar), |ident| self.kind(ident))?;
+            let value = value_ident.map_or(scalar_kind(*value).map(Kind::Scalar), |ident| self.kind(ident))?;
+            let (key_kind, key_annotation, _) = self.value(&key);
+            let (value_kind, value_annotation, _) = self.value(&value);
+            return Some(FieldSpec {
+                tag,
+                attribute,
+                label: "map",
+                kind: key_kind,
+                value_kind: Some(value_kind),
+                annotation: format!("dict[{key_annotation}, {value_annotation}]"),
+                default: "_dc.field(default_factory=dict)".to_string(),
+            });
+        }
+        if field.proto_label == ProtoLabel::Repeated {
+            let elem = self.kind(field.array_elem.unwrap_or(field.proto_ident))?;
+            let (kind, annotation, _) = self.value(&elem);
+            return Some(FieldSpec {
+                tag,
+                attribute,
+                label: "repeated",
+                kind,
+                value_kind: None,
+                annotation: format!("list[{annotation}]"),
+                default: "_dc.field(default_factory=list)".to_string(),
+            });
+        }
+        let (kind, annotation, default) = self.value(&self.kind(field.proto_ident)?);
+        Some(if field.proto_label == ProtoLabel::Optional {
+            FieldSpec {
+                tag,
+                attribute,
+                label: "optional",
+                kind,
+                value_kind: None,
+                annotation: format!("Optional[{annotation}]"),
+                default: "None".to_string(),
+            }
+        } else {
+            FieldSpec {
+                tag,
+                attribute,
+                label: "single",
+                kind,
+                value_kind: None,
+                annotation,
+                default,
+            }
+        })
+    }
+
+    fn entry(&mut self, schema: &ProtoSchema) {
+        let name = schema.id.name;
+        let full_name = schema.id.full_name();
+        match schema.content {
+            ProtoEntry::SimpleEnum { variants } => {
+                writeln!(
+                    self.body,
+                    "\n@_pr.enumeration({})\nclass {name}(_enum.IntEnum):",
+                    python_string(&full_name)
+                )
+                .unwrap();
+                for variant in variants {
+                    writeln!(
+                        self.body,
+                        "    {} = {}",
+                        attribute_name(variant.name),
+                        variant.discriminant.unwrap_or_default()
+                    )
+                    .unwrap();
+                }
+            }
+            ProtoEntry::Struct { fields } => {
+                let specs: Vec<FieldSpec> = fields.iter().enumerate().filter_map(|(idx, field)| self.field(field, idx)).collect();
+                self.dataclass(name, &full_name, &specs, false);
+            }
+            ProtoEntry::ComplexEnum { variants } => self.oneof(name, &full_name, variants),
+            ProtoEntry::Service { methods, rpc_package_name } => self.client(name, rpc_package_name, methods),
+            ProtoEntry::Import { .. } => {}
+        }
+    }
+
+    /// A complex enum: one optional attribute per variant, unit and struct variants as classes
+    /// of their own.
+    fn oneof(&mut self, name: &str, full_name: &str, variants: &[&Variant]) {
+        let package = full_name.rsplit_once('.').map(|(package, _)| format!("{package}."));
+        let package = package.as_deref().unwrap_or_default();
+        let mut specs = Vec::new();
+        for (idx, variant) in variants.iter().enumerate() {
+            let attribute = attribute_name(&to_snake_case(variant.name));
+            let (kind, annotation) = match variant.fields {
+                [field] if field.name.is_none() => {
+                    let Some(kind) = self.kind(field.proto_ident) else { continue };
+                    let (kind, annotation, _) = self.value(&kind);
+                    (kind, annotation)
+                }
+                fields => {
+                    let class = format!("{name}{}", variant.name);
+                    let class_full_name = format!("{package}{class}");
+                    let field_specs: Vec<FieldSpec> = fields.iter().enumerate().filter_map(|(idx, field)| self.field(field, idx)).collect();
+                    self.dataclass(&class, &class_full_name, &field_specs, false);
+                    (format!("message:{class_full_name}"), class)
+                }
+            };
+            specs.push(FieldSpec {
+                tag: u32::try_from(idx + 1).unwrap_or(u32::MAX),
+                attribute,
+                label: "optional",
+                kind,
+                value_kind: None,
+                annotation: format!("Optional[{annotation}]"),
+                default: "None".to_string(),
+            });
+        }
+        self.dataclass(name, full_name, &specs, true);
+    }
+
+    fn dataclass(&mut self, name: &str, full_name: &str, specs: &[FieldSpec], oneof: bool) {
+        writeln!(self.body, "\n@_pr.message(\n    {},\n    [", python_string(full_name)).unwrap();
+        for spec in specs {
+            let value_kind = spec.value_kind.as_deref().map(|kind| format!(", {}", python_string(kind))).unwrap_or_default();
+            writeln!(
+                self.body,
+                "        ({}, {}, {}, {}{value_kind}),",
+                spec.tag,
+                python_string(&spec.attribute),
+                python_string(spec.label),
+                python_string(&spec.kind)
+            )
+            .unwrap();
+        }
+        let oneof = if oneof { ",\n    oneof=True" } else { "" };
+        writeln!(self.body, "    ]{oneof},\n)\n@_dc.dataclass\nclass {name}:").unwrap();
+        if specs.is_empty() {
+            self.body.push_str("    pass\n");
+        }
+        for spec in specs {
+            writeln!(self.body, "    {}: {} = {}", spec.attribute, spec.annotation, spec.default).unwrap();
+        }
+    }
+
+    fn client(&mut self, name: &str, rpc_package_name: &str, methods: &[&ServiceMethod]) {
+        let service = if rpc_package_name.is_empty() {
+            name.to_string()
+        } else {
+            format!("{rpc_package_name}.{name}")
+        };
+        writeln!(self.body, "\nclass {name}Client:\n    \"\"\"Client of `{service}`.\"\"\"\n").unwrap();
+        self.body.push_str("    def __init__(self, channel: grpc.Channel) -> None:\n");
+        let mut calls = Vec::new();
+        for method in methods {
+            let (Some(Kind::Message(request)), Some(Kind::Message(response))) = (self.kind(method.request), self.kind(method.response))
+            else {
+                continue;
+            };
+            let request = self.class_ref(request);
+            let response = self.class_ref(response);
+            let attribute = attribute_name(&to_snake_case(method.name));
+            let route = method.route.map_or_else(|| format!("/{service}/{}", method.name), ToString::to_string);
+            let shape = match (method.client_streaming, method.server_streaming) {
+                (false, false) => "unary_unary",
+                (false, true) => "unary_stream",
+                (true, false) => "stream_unary",
+                (true, true) => "stream_stream",
+            };
+            writeln!(
+                self.body,
+                "        self._{attribute} = channel.{shape}(\n            {},\n            request_serializer=_pr.encode,\n            response_deserializer=_pr.decoder({response}),\n        )",
+                python_string(&route)
+            )
+            .unwrap();
+            let request = if method.client_streaming {
+                format!("Iterator[{request}]")
+            } else {
+                request
+            };
+            let response = if method.server_streaming {
+                format!("Iterator[{response}]")
+            } else {
+                response
+            };
+            calls.push(format!(
+                "\n    def {attribute}(self, request: {request}, **kwargs: Any) -> {response}:\n        return self._{attribute}(request, **kwargs)\n"
+            ));
+        }
+        if calls.is_empty() {
+            self.body.push_str("        self._channel = channel\n");
+        }
+        for call in calls {
+            self.body.push_str(&call);
+        }
+    }
+
+    fn finish(self, schemas: &[&ProtoSchema]) -> String {
+        let package = schemas.first().map_or("", |schema| schema.id.proto_package_name);
+        let mut output = String::new();
+        writeln!(
+            output,
+            "\"\"\"Types and clients of proto package `{package}`, generated by proto_rs. Do not edit.\"\"\"\n"
+        )
+        .unwrap();
+        output.push_str("from __future__ import annotations\n\nimport dataclasses as _dc\nimport enum as _enum\nfrom typing import Any, Iterator, Optional\n\n");
+        if schemas.iter().any(|schema| matches!(schema.content, ProtoEntry::Service { .. })) {
+            // Only annotations name grpc, so the types import without grpcio installed.
+            output.push_str("from typing import TYPE_CHECKING\n\nif TYPE_CHECKING:\n    import grpc\n\n");
+        }
+        output.push_str("from . import _proto_rs as _pr\n");
+        for module in &self.imports {
+            writeln!(output, "from . import {module}").unwrap();
+        }
+        output.push('\n');
+        output.push_str(&self.body);
+        output
+    }
+}
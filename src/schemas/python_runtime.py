@@ -0,0 +1,301 @@
+"""Protobuf wire codec for the dataclasses generated by proto_rs. Do not edit."""
+
+from __future__ import annotations
+
+import enum
+import struct
+from typing import Any, Callable, Dict, Tuple
+
+_TYPES: Dict[str, type] = {}
+
+_VARINT = {"int32", "int64", "uint32", "uint64", "sint32", "sint64", "bool"}
+_FIXED32 = {"fixed32": "<I", "sfixed32": "<i", "float": "<f"}
+_FIXED64 = {"fixed64": "<Q", "sfixed64": "<q", "double": "<d"}
+_MASK64 = (1 << 64) - 1
+
+
+class Field:
+    """One field of a message: tag, attribute, label (single, optional, repeated or map) and kind."""
+
+    __slots__ = ("tag", "name", "label", "kind", "value_kind")
+
+    def __init__(self, tag: int, name: str, label: str, kind: str, value_kind: str = "") -> None:
+        self.tag = tag
+        self.name = name
+        self.label = label
+        self.kind = kind
+        self.value_kind = value_kind
+
+
+def message(full_name: str, fields: list, oneof: bool = False) -> Callable[[type], type]:
+    """Registers a dataclass as the message `full_name`; a oneof holds at most one set field."""
+
+    def register(cls: type) -> type:
+        cls.__proto_name__ = full_name
+        cls.__proto_fields__ = tuple(Field(*spec) for spec in fields)
+        cls.__proto_oneof__ = oneof
+        _TYPES[full_name] = cls
+        return cls
+
+    return register
+
+
+def enumeration(full_name: str) -> Callable[[type], type]:
+    """Registers an `IntEnum` as the enum `full_name`."""
+
+    def register(cls: type) -> type:
+        cls.__proto_name__ = full_name
+        _TYPES[full_name] = cls
+        return cls
+
+    return register
+
+
+def default_of(full_name: str) -> Callable[[], Any]:
+    """Default value factory of a message or enum, resolved when first called."""
+
+    def make() -> Any:
+        cls = _TYPES[full_name]
+        if issubclass(cls, enum.Enum):
+            return next((member for member in cls if member.value == 0), next(iter(cls)))
+        return cls()
+
+    return make
+
+
+def encode(message: Any) -> bytes:
+    """Protobuf encoding of a generated message."""
+    out = bytearray()
+    cls = type(message)
+    for field in cls.__proto_fields__:
+        value = getattr(message, field.name)
+        if cls.__proto_oneof__:
+            if value is not None:
+                _put_value(out, field.tag, field.kind, value)
+        elif field.label == "map":
+            for key, item in value.items():
+                entry = bytearray()
+                _put_value(entry, 1, field.kind, key)
+                _put_value(entry, 2, field.value_kind, item)
+                _put_key(out, field.tag, 2)
+                _put_bytes(out, entry)
+        elif field.label == "repeated":
+            if not value:
+                continue
+            if _wire_type(field.kind) != 2:
+                packed = bytearray()
+                for item in value:
+                    _put_scalar(packed, field.kind, item)
+                _put_key(out, field.tag, 2)
+                _put_bytes(out, packed)
+            else:
+                for item in value:
+                    _put_value(out, field.tag, field.kind, item)
+        elif field.label == "optional":
+            if value is not None:
+                _put_value(out, field.tag, field.kind, value)
+        elif not _is_default(field.kind, value):
+            _put_value(out, field.tag, field.kind, value)
+    return bytes(out)
+
+
+def decode(cls: type, data: bytes) -> Any:
+    """Decodes `data` as the generated message class `cls`; unknown fields are skipped."""
+    fields = {field.tag: field for field in cls.__proto_fields__}
+    values: Dict[str, Any] = {}
+    view = memoryview(data)
+    pos = 0
+    while pos < len(view):
+        key, pos = _get_varint(view, pos)
+        tag, wire = key >> 3, key & 7
+        field = fields.get(tag)
+        if field is None:
+            pos = _skip(view, pos, wire)
+            continue
+        if field.label == "map":
+            entry, pos = _get_bytes(view, pos)
+            item_key, item = _decode_entry(field, entry)
+            values.setdefault(field.name, {})[item_key] = item
+        elif field.label == "repeated":
+            items = values.setdefault(field.name, [])
+            item_wire = _wire_type(field.kind)
+            if wire == 2 and item_wire != 2:
+                packed, pos = _get_bytes(view, pos)
+                inner = 0
+                while inner < len(packed):
+                    item, inner = _get_value(packed, inner, item_wire, field.kind)
+                    items.append(item)
+            else:
+                item, pos = _get_value(view, pos, wire, field.kind)
+                items.append(item)
+        else:
+            item, pos = _get_value(view, pos, wire, field.kind)
+            if cls.__proto_oneof__:
+                values.clear()
+            values[field.name] = item
+    return cls(**values)
+
+
+def decoder(cls: type) -> Callable[[bytes], Any]:
+    """`decode` bound to `cls`, as a response deserializer."""
+    return lambda data: decode(cls, data)
+
+
+def _is_default(kind: str, value: Any) -> bool:
+    if kind.startswith("message:"):
+        return not encode(value)
+    return not value
+
+
+def _wire_type(kind: str) -> int:
+    if kind in _VARINT or kind.startswith("enum:"):
+        return 0
+    if kind in _FIXED64:
+        return 1
+    if kind in _FIXED32:
+        return 5
+    return 2
+
+
+def _put_varint(out: bytearray, value: int) -> None:
+    value &= _MASK64
+    while value >= 0x80:
+        out.append((value & 0x7F) | 0x80)
+        value >>= 7
+    out.append(value)
+
+
+def _put_key(out: bytearray, tag: int, wire: int) -> None:
+    _put_varint(out, tag << 3 | wire)
+
+
+def _put_bytes(out: bytearray, data: bytes) -> None:
+    _put_varint(out, len(data))
+    out += data
+
+
+def _put_value(out: bytearray, tag: int, kind: str, value: Any) -> None:
+    _put_key(out, tag, _wire_type(kind))
+    _put_scalar(out, kind, value)
+
+
+def _put_scalar(out: bytearray, kind: str, value: Any) -> None:
+    if kind in ("sint32", "sint64"):
+        _put_varint(out, (value << 1) ^ (value >> 63))
+    elif kind in _VARINT or kind.startswith("enum:"):
+        _put_varint(out, int(value))
+    elif kind in _FIXED32:
+        out += struct.pack(_FIXED32[kind], value)
+    elif kind in _FIXED64:
+        out += struct.pack(_FIXED64[kind], value)
+    elif kind == "string":
+        _put_bytes(out, value.encode("utf-8"))
+    elif kind == "bytes":
+        _put_bytes(out, value)
+    else:
+        _put_bytes(out, encode(value))
+
+
+def _get_varint(data: memoryview, pos: int) -> Tuple[int, int]:
+    value = 0
+    shift = 0
+    while True:
+        byte = data[pos]
+        pos += 1
+        value |= (byte & 0x7F) << shift
+        if byte < 0x80:
+            return value & _MASK64, pos
+        shift += 7
+        if shift >= 70:
+            raise ValueError("varint is too long")
+
+
+def _get_bytes(data: memoryview, pos: int) -> Tuple[memoryview, int]:
+    length, pos = _get_varint(data, pos)
+    if pos + length > len(data):
+        raise ValueError("buffer underflow")
+    return data[pos : pos + length], pos + length
+
+
+def _get_value(data: memoryview, pos: int, wire: int, kind: str) -> Tuple[Any, int]:
+    if wire == 0:
+        raw, pos = _get_varint(data, pos)
+        return _from_varint(kind, raw), pos
+    if wire == 5:
+        return struct.unpack_from(_FIXED32.get(kind, "<I"), data, pos)[0], pos + 4
+    if wire == 1:
+        return struct.unpack_from(_FIXED64.get(kind, "<Q"), data, pos)[0], pos + 8
+    if wire != 2:
+        raise ValueError(f"unexpected wire type {wire}")
+    payload, pos = _get_bytes(data, pos)
+    if kind == "string":
+        return str(payload, "utf-8"), pos
+    if kind == "bytes":
+        return bytes(payload), pos
+    return decode(_TYPES[kind.split(":", 1)[1]], payload), pos
+
+
+def _from_varint(kind: str, raw: int) -> Any:
+    if kind == "bool":
+        return raw != 0
+    if kind in ("sint32", "sint64"):
+        return (raw >> 1) ^ -(raw & 1)
+    if kind == "uint32":
+        return raw & 0xFFFFFFFF
+    if kind == "uint64":
+        return raw
+    if kind == "int64":
+        return raw - (1 << 64) if raw >= 1 << 63 else raw
+    value = raw & 0xFFFFFFFF
+    value = value - (1 << 32) if value >= 1 << 31 else value
+    if kind.startswith("enum:"):
+        try:
+            return _TYPES[kind[5:]](value)
+        except ValueError:
+            return value
+    return value
+
+
+def _decode_entry(field: Field, data: memoryview) -> Tuple[Any, Any]:
+    key: Any = None
+    value: Any = None
+    pos = 0
+    while pos < len(data):
+        entry_key, pos = _get_varint(data, pos)
+        tag, wire = entry_key >> 3, entry_key & 7
+        if tag == 1:
+            key, pos = _get_value(data, pos, wire, field.kind)
+        elif tag == 2:
+            value, pos = _get_value(data, pos, wire, field.value_kind)
+        else:
+            pos = _skip(data, pos, wire)
+    return (
+        key if key is not None else _zero(field.kind),
+        value if value is not None else _zero(field.value_kind),
+    )
+
+
+def _zero(kind: str) -> Any:
+    if kind == "string":
+        return ""
+    if kind == "bytes":
+        return b""
+    if kind == "bool":
+        return False
+    if kind in ("float", "double"):
+        return 0.0
+    if ":" in kind:
+        return default_of(kind.split(":", 1)[1])()
+    return 0
+
+
+def _skip(data: memoryview, pos: int, wire: int) -> int:
+    if wire == 0:
+        return _get_varint(data, pos)[1]
+    if wire == 1:
+        return pos + 8
+    if wire == 5:
+        return pos + 4
+    if wire == 2:
+        return _get_bytes(data, pos)[1]
+    raise ValueError(f"cannot skip wire type {wire}")
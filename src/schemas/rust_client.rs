@@ -19,10 +19,12 @@ use super::TypeReplace;
 use super::UserAttr;
 use super::Variant;
 use super::utils::WrapperKind;
+use super::utils::field_newtype;
 use super::utils::indent_line;
 use super::utils::is_wrapper_schema;
 use super::utils::module_path_for_package;
 use super::utils::module_path_segments;
+use super::utils::package_name_for_entries;
 use super::utils::proto_ident_base_type_name;
 use super::utils::proto_map_types;
 use super::utils::proto_scalar_type;
@@ -713,7 +715,7 @@ fn render_entries(
         };
 
         let user_attrs = build_entry_user_attrs(entry, client_attrs, client_attr_removals, module_type_attrs, ident_index);
-        let entry_type_replacements = build_entry_type_replacements(entry, type_replacements);
+        let entry_type_replacements = build_entry_type_replacements(entry, type_replacements, ident_index);
         if let Some(definition) = render_rust_entry(
             entry,
             package_name,
@@ -846,9 +848,9 @@ fn render_rust_struct(
         });
         render_field_attributes(&mut output, field, idx, &field_attrs, &field_overrides, indent + 4);
         indent_line(&mut output, indent + 4);
-        let name = field.name.unwrap_or("field");
+        let name = field.name.map_or_else(|| format!("field_{idx}"), ToString::to_string);
         output.push_str("pub ");
-        output.push_str(name);
+        output.push_str(&name);
         output.push_str(": ");
         let type_replacement = field.name.and_then(|name| lookup_field_replacement(type_replacements, None, name));
         output.push_str(&render_field_type(
@@ -1095,8 +1097,17 @@ fn render_rust_service(
         };
 
         render_method_attributes(&mut output, user_attrs.method_attrs.get(method.name), indent + 4);
+        let method_name = to_snake_case(method.name);
+        if screaming_to_pascal_case(&method_name) != method.name {
+            indent_line(&mut output, indent + 4);
+            writeln!(output, "#[proto(rpc_name = \"{}\")]", method.name).unwrap();
+        }
+        if let Some(route) = method.route {
+            indent_line(&mut output, indent + 4);
+            writeln!(output, "#[proto(route = \"{route}\")]").unwrap();
+        }
         indent_line(&mut output, indent + 4);
-        writeln!(output, "async fn {}(", to_snake_case(method.name)).unwrap();
+        writeln!(output, "async fn {method_name}(").unwrap();
         indent_line(&mut output, indent + 8);
         writeln!(output, "&self,").unwrap();
         indent_line(&mut output, indent + 8);
@@ -1233,7 +1244,7 @@ fn is_tag_only_attr(tokens: &str, expected_tag: u32) -> bool {
 }
 
 /// Attributes that should only be present in source code and not in generated clients.
-const SOURCE_ONLY_ATTR_KEYS: &[&str] = &["getter", "validator", "treat_as"];
+const SOURCE_ONLY_ATTR_KEYS: &[&str] = &["getter", "validator", "treat_as", "on_duplicate", "name"];
 
 fn has_source_only_attrs(tokens: &str) -> bool {
     let normalized = tokens.replace(' ', "");
@@ -1519,9 +1530,14 @@ const fn find_entry_methods(entry: &ProtoSchema) -> Option<&[&ServiceMethod]> {
     }
 }
 
-fn build_entry_type_replacements(entry: &ProtoSchema, type_replacements: &BTreeMap<ProtoIdent, Vec<TypeReplace>>) -> EntryTypeReplacements {
+fn build_entry_type_replacements(
+    entry: &ProtoSchema,
+    type_replacements: &BTreeMap<ProtoIdent, Vec<TypeReplace>>,
+    ident_index: &BTreeMap<ProtoIdent, &'static ProtoSchema>,
+) -> EntryTypeReplacements {
     let mut entry_replacements = EntryTypeReplacements::default();
     let Some(replacements) = type_replacements.get(&entry.id) else {
+        add_newtype_replacements(entry, ident_index, &mut entry_replacements);
         return entry_replacements;
     };
 
@@ -1569,9 +1585,36 @@ fn build_entry_type_replacements(entry: &ProtoSchema, type_replacements: &BTreeM
         }
     }
 
+    add_newtype_replacements(entry, ident_index, &mut entry_replacements);
     entry_replacements
 }
 
+/// Fields holding a newtype with a `unit` or `range` keep the newtype instead of its inner
+/// scalar, unless a `replace_type` already targets them.
+fn add_newtype_replacements(
+    entry: &ProtoSchema,
+    ident_index: &BTreeMap<ProtoIdent, &'static ProtoSchema>,
+    entry_replacements: &mut EntryTypeReplacements,
+) {
+    let fields: Vec<(Option<&str>, &Field)> = match entry.content {
+        ProtoEntry::Struct { fields } => fields.iter().map(|field| (None, *field)).collect(),
+        ProtoEntry::ComplexEnum { variants } => {
+            variants.iter().flat_map(|variant| variant.fields.iter().map(|field| (Some(variant.name), *field))).collect()
+        }
+        ProtoEntry::SimpleEnum { .. } | ProtoEntry::Import { .. } | ProtoEntry::Service { .. } => Vec::new(),
+    };
+    for (variant, field) in fields {
+        let (Some(name), Some(newtype)) = (field.name, field_newtype(field, ident_index)) else {
+            continue;
+        };
+        let id = newtype.schema.id;
+        entry_replacements
+            .field_types
+            .entry(FieldTargetKey::new(variant, name))
+            .or_insert_with(|| format!("::{}::{}", id.module_path, id.name));
+    }
+}
+
 fn resolve_method_replace_type<'a>(kind: &'a MethodReplace, fallback: &'a str) -> &'a str {
     match kind {
         MethodReplace::Argument(replacement) | MethodReplace::Return(replacement) if !replacement.is_empty() => replacement,
@@ -1743,10 +1786,13 @@ fn render_wrapper_inner_type(
         WrapperKind::Option
         | WrapperKind::Vec
         | WrapperKind::VecDeque
+        | WrapperKind::BinaryHeap
+        | WrapperKind::LinkedList
         | WrapperKind::HashSet
         | WrapperKind::BTreeSet
         | WrapperKind::Box
         | WrapperKind::Arc
+        | WrapperKind::Weak
         | WrapperKind::Mutex
         | WrapperKind::ArcSwap
         | WrapperKind::ArcSwapOption
@@ -1761,7 +1807,12 @@ fn unwrap_optional_inner(mut inner_ident: ProtoIdent) -> ProtoIdent {
             return inner_ident;
         };
         match kind {
-            WrapperKind::Arc | WrapperKind::Box | WrapperKind::Mutex | WrapperKind::ArcSwap | WrapperKind::CachePadded => {
+            WrapperKind::Arc
+            | WrapperKind::Weak
+            | WrapperKind::Box
+            | WrapperKind::Mutex
+            | WrapperKind::ArcSwap
+            | WrapperKind::CachePadded => {
                 if let Some(next) = inner_ident.generics.first().copied() {
                     inner_ident = next;
                 } else {
@@ -2107,12 +2158,15 @@ fn render_method_wrapper_type(
 
     match kind {
         WrapperKind::Option | WrapperKind::ArcSwapOption => Some(format!("::core::option::Option<{inner_type}>")),
-        WrapperKind::Vec | WrapperKind::VecDeque | WrapperKind::HashSet | WrapperKind::BTreeSet => {
-            Some(format!("::proto_rs::alloc::vec::Vec<{inner_type}>"))
-        }
+        WrapperKind::Vec
+        | WrapperKind::VecDeque
+        | WrapperKind::BinaryHeap
+        | WrapperKind::LinkedList
+        | WrapperKind::HashSet
+        | WrapperKind::BTreeSet => Some(format!("::proto_rs::alloc::vec::Vec<{inner_type}>")),
         WrapperKind::Box => Some(format!("::std::boxed::Box<{inner_type}>")),
         WrapperKind::Arc => Some(format!("::std::sync::Arc<{inner_type}>")),
-        WrapperKind::Mutex => Some(inner_type),
+        WrapperKind::Mutex | WrapperKind::Weak => Some(inner_type),
         WrapperKind::ArcSwap => Some(format!("::arc_swap::ArcSwap<{inner_type}>")),
         WrapperKind::CachePadded => Some(format!("::crossbeam_utils::CachePadded<{inner_type}>")),
         WrapperKind::HashMap | WrapperKind::BTreeMap => None,
@@ -2180,9 +2234,12 @@ fn render_wrapper_kind_type(
     let inner_type = render_proto_type(inner_ident, current_package, package_by_ident, proto_type_index, client_imports);
     match kind {
         WrapperKind::Option | WrapperKind::ArcSwapOption => Some(format!("::core::option::Option<{inner_type}>")),
-        WrapperKind::Vec | WrapperKind::VecDeque | WrapperKind::HashSet | WrapperKind::BTreeSet => {
-            Some(format!("::proto_rs::alloc::vec::Vec<{inner_type}>"))
-        }
+        WrapperKind::Vec
+        | WrapperKind::VecDeque
+        | WrapperKind::BinaryHeap
+        | WrapperKind::LinkedList
+        | WrapperKind::HashSet
+        | WrapperKind::BTreeSet => Some(format!("::proto_rs::alloc::vec::Vec<{inner_type}>")),
         WrapperKind::HashMap | WrapperKind::BTreeMap => render_map_type_with_kind(
             kind,
             &inner_ident.proto_type,
@@ -2193,7 +2250,7 @@ fn render_wrapper_kind_type(
         ),
         WrapperKind::Box => Some(format!("::std::boxed::Box<{inner_type}>")),
         WrapperKind::Arc => Some(format!("::std::sync::Arc<{inner_type}>")),
-        WrapperKind::Mutex => Some(inner_type),
+        WrapperKind::Mutex | WrapperKind::Weak => Some(inner_type),
         WrapperKind::ArcSwap => Some(format!("::arc_swap::ArcSwap<{inner_type}>")),
         WrapperKind::CachePadded => Some(format!("::crossbeam_utils::CachePadded<{inner_type}>")),
     }
@@ -2320,16 +2377,6 @@ fn build_proto_type_index(registry: &BTreeMap<String, Vec<&'static ProtoSchema>>
     index
 }
 
-fn package_name_for_entries(file_name: &str, entries: &[&ProtoSchema]) -> String {
-    let path = Path::new(file_name);
-    let file_name_last = path.file_name().and_then(|name| name.to_str()).unwrap_or(file_name);
-    entries
-        .first()
-        .map(|schema| schema.id.proto_package_name)
-        .filter(|name| !name.is_empty())
-        .map_or_else(|| super::utils::derive_package_name(file_name_last), ToString::to_string)
-}
-
 #[cfg(test)]
 mod tests {
     use super::apply_top_level_attr_removals;
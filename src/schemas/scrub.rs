@@ -0,0 +1,222 @@
+//! Removal of sensitive fields from encoded payloads.
+//!
+//! [`scrub`] walks a message with its registered schema and rewrites every field marked
+//! `#[proto(sensitive)]`, at any depth, so request samples can be stored for debugging without
+//! keeping personal data. Fields the schema does not know are copied unchanged.
+
+use bytes::Buf;
+
+use super::Field;
+use super::ProtoEntry;
+use super::ProtoIdent;
+use super::ProtoLabel;
+use super::ProtoType;
+use super::message_by_name;
+use super::schema_for;
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::encoding::WireType;
+use crate::encoding::decode_key;
+use crate::encoding::decode_varint;
+use crate::encoding::encode_key;
+use crate::encoding::encode_varint;
+use crate::encoding::skip_field;
+
+/// What happens to a sensitive field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrubPolicy {
+    /// Drops the field from the payload.
+    Remove,
+    /// Keeps the field but replaces every string or bytes value with the mask and every other
+    /// value with its default. Maps and packed numbers have no such value and are removed.
+    Mask(&'static str),
+}
+
+/// Re-encodes `bytes`, a `type_name` message, with its sensitive fields scrubbed.
+///
+/// `type_name` is the full proto name (`package.Message`), or the bare message name for messages
+/// without a package.
+///
+/// # Errors
+///
+/// Returns an error if no message is registered under `type_name` or `bytes` is not a valid
+/// encoding of it.
+pub fn scrub(type_name: &str, bytes: &[u8], policy: ScrubPolicy) -> Result<Vec<u8>, DecodeError> {
+    let schema = message_by_name(type_name).ok_or_else(|| DecodeError::new(format!("no registered message named {type_name}")))?;
+    let mut out = Vec::with_capacity(bytes.len());
+    scrub_message(&schema.id, bytes, policy, DecodeContext::default(), &mut out)?;
+    Ok(out)
+}
+
+fn scrub_message(ident: &ProtoIdent, bytes: &[u8], policy: ScrubPolicy, ctx: DecodeContext, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+    ctx.limit_reached()?;
+    match schema_for(ident).map(|schema| schema.content) {
+        Some(ProtoEntry::Struct { fields }) => scrub_fields(fields, bytes, policy, ctx, out),
+        Some(ProtoEntry::ComplexEnum { variants }) => {
+            let mut buf = bytes;
+            while buf.has_remaining() {
+                let start = buf;
+                let (tag, wire_type) = decode_key(&mut buf)?;
+                let variant = (tag as usize).checked_sub(1).and_then(|idx| variants.get(idx));
+                match variant.map(|variant| variant.fields) {
+                    Some([field]) if field.name.is_none() => {
+                        if field.is_sensitive() {
+                            skip_field(wire_type, tag, &mut buf, ctx)?;
+                            scrubbed(field, tag, wire_type, policy, out);
+                        } else {
+                            scrub_value(&field.proto_ident, tag, wire_type, &mut buf, start, policy, ctx, out)?;
+                        }
+                    }
+                    Some(fields @ [_, ..]) => {
+                        let nested = length_delimited(wire_type, &mut buf)?;
+                        let mut scrubbed = Vec::with_capacity(nested.len());
+                        scrub_fields(fields, nested, policy, ctx.enter_recursion(), &mut scrubbed)?;
+                        write_len_delimited(tag, &scrubbed, out);
+                    }
+                    _ => {
+                        skip_field(wire_type, tag, &mut buf, ctx)?;
+                        copy_raw(start, buf, out);
+                    }
+                }
+            }
+            Ok(())
+        }
+        _ => Err(DecodeError::new(format!("no registered message named {}", ident.name))),
+    }
+}
+
+fn scrub_fields(fields: &[&Field], bytes: &[u8], policy: ScrubPolicy, ctx: DecodeContext, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+    let mut buf = bytes;
+    while buf.has_remaining() {
+        let start = buf;
+        let (tag, wire_type) = decode_key(&mut buf)?;
+        let Some(field) = fields.iter().find(|field| field.tag == tag) else {
+            skip_field(wire_type, tag, &mut buf, ctx)?;
+            copy_raw(start, buf, out);
+            continue;
+        };
+        if field.is_sensitive() {
+            skip_field(wire_type, tag, &mut buf, ctx)?;
+            scrubbed(field, tag, wire_type, policy, out);
+        } else if field.is_bytes() {
+            skip_field(wire_type, tag, &mut buf, ctx)?;
+            copy_raw(start, buf, out);
+        } else if let ProtoType::Map { .. } = field.proto_ident.proto_type {
+            let (_, value_ident) = field.map_idents();
+            let entry = length_delimited(wire_type, &mut buf)?;
+            match value_ident.filter(is_message) {
+                Some(value_ident) => {
+                    let mut scrubbed = Vec::with_capacity(entry.len());
+                    scrub_entry(&value_ident, entry, policy, ctx, &mut scrubbed)?;
+                    write_len_delimited(tag, &scrubbed, out);
+                }
+                None => copy_raw(start, buf, out),
+            }
+        } else {
+            let ident = match field.proto_label {
+                ProtoLabel::Repeated => field.array_elem.unwrap_or(field.proto_ident),
+                ProtoLabel::None | ProtoLabel::Optional => field.proto_ident,
+            };
+            scrub_value(&ident, tag, wire_type, &mut buf, start, policy, ctx, out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copies one occurrence of a field of type `ident`, scrubbing inside it when it is a message.
+#[allow(clippy::too_many_arguments)]
+fn scrub_value(
+    ident: &ProtoIdent,
+    tag: u32,
+    wire_type: WireType,
+    buf: &mut &[u8],
+    start: &[u8],
+    policy: ScrubPolicy,
+    ctx: DecodeContext,
+    out: &mut Vec<u8>,
+) -> Result<(), DecodeError> {
+    if is_message(ident) && wire_type == WireType::LengthDelimited {
+        let nested = length_delimited(wire_type, buf)?;
+        let mut scrubbed = Vec::with_capacity(nested.len());
+        scrub_message(ident, nested, policy, ctx.enter_recursion(), &mut scrubbed)?;
+        write_len_delimited(tag, &scrubbed, out);
+    } else {
+        skip_field(wire_type, tag, buf, ctx)?;
+        copy_raw(start, buf, out);
+    }
+    Ok(())
+}
+
+fn scrub_entry(value: &ProtoIdent, bytes: &[u8], policy: ScrubPolicy, ctx: DecodeContext, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+    let mut buf = bytes;
+    while buf.has_remaining() {
+        let start = buf;
+        let (tag, wire_type) = decode_key(&mut buf)?;
+        if tag == 2 {
+            scrub_value(value, tag, wire_type, &mut buf, start, policy, ctx, out)?;
+        } else {
+            skip_field(wire_type, tag, &mut buf, ctx)?;
+            copy_raw(start, buf, out);
+        }
+    }
+    Ok(())
+}
+
+/// Replacement for a sensitive field, written under its original key.
+fn scrubbed(field: &Field, tag: u32, wire_type: WireType, policy: ScrubPolicy, out: &mut Vec<u8>) {
+    let ScrubPolicy::Mask(mask) = policy else {
+        return;
+    };
+    let elem = field.array_elem.unwrap_or(field.proto_ident).proto_type;
+    let masked = field.is_bytes() || matches!(elem, ProtoType::String);
+    let payload: &[u8] = match wire_type {
+        WireType::LengthDelimited if masked => mask.as_bytes(),
+        WireType::LengthDelimited if is_message(&field.array_elem.unwrap_or(field.proto_ident)) => &[],
+        WireType::Varint => {
+            encode_key(tag, wire_type, out);
+            out.push(0);
+            return;
+        }
+        WireType::ThirtyTwoBit => {
+            encode_key(tag, wire_type, out);
+            out.extend_from_slice(&[0; 4]);
+            return;
+        }
+        WireType::SixtyFourBit => {
+            encode_key(tag, wire_type, out);
+            out.extend_from_slice(&[0; 8]);
+            return;
+        }
+        WireType::LengthDelimited | WireType::StartGroup | WireType::EndGroup => return,
+    };
+    write_len_delimited(tag, payload, out);
+}
+
+fn is_message(ident: &ProtoIdent) -> bool {
+    matches!(ident.proto_type, ProtoType::Message(_))
+        && matches!(
+            schema_for(ident).map(|schema| schema.content),
+            Some(ProtoEntry::Struct { .. } | ProtoEntry::ComplexEnum { .. })
+        )
+}
+
+fn copy_raw(start: &[u8], rest: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(&start[..start.len() - rest.len()]);
+}
+
+fn length_delimited<'a>(wire_type: WireType, buf: &mut &'a [u8]) -> Result<&'a [u8], DecodeError> {
+    if wire_type != WireType::LengthDelimited {
+        return Err(DecodeError::new("invalid wire type"));
+    }
+    let len = decode_varint(buf)?;
+    let len = usize::try_from(len).ok().filter(|&len| len <= buf.len()).ok_or_else(|| DecodeError::new("buffer underflow"))?;
+    let (payload, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(payload)
+}
+
+fn write_len_delimited(tag: u32, payload: &[u8], out: &mut Vec<u8>) {
+    encode_key(tag, WireType::LengthDelimited, out);
+    encode_varint(payload.len() as u64, out);
+    out.extend_from_slice(payload);
+}
@@ -0,0 +1,115 @@
+//! Decoding of payloads whose type is only known from a type URL at runtime.
+
+use std::any::Any;
+use std::collections::BTreeMap;
+
+use super::ProtoIdent;
+use super::ProtoIdentifiable;
+use super::SelfDescribing;
+use super::envelope;
+use super::type_url_name;
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::ProtoDecode;
+
+type DecodeFn = fn(&[u8], DecodeContext) -> Result<Box<dyn Any + Send>, DecodeError>;
+
+fn decode_boxed<T: ProtoDecode + Send + 'static>(bytes: &[u8], ctx: DecodeContext) -> Result<Box<dyn Any + Send>, DecodeError> {
+    Ok(Box::new(T::decode(bytes, ctx)?))
+}
+
+/// Message types registered by type URL, for decoding `Any` payloads, dead-letter queues and
+/// other envelopes that name their content.
+///
+/// ```rust,ignore
+/// let types = TypeRegistry::new().with::<Order>().with::<Refund>();
+/// let decoded = types.decode("type.googleapis.com/shop.v1.Order", &payload)?;
+/// let order = decoded.downcast::<Order>().unwrap();
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct TypeRegistry {
+    by_name: BTreeMap<String, (ProtoIdent, DecodeFn)>,
+    by_id: BTreeMap<u64, String>,
+}
+
+impl TypeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `T` under [`ProtoIdent::type_url`] and [`ProtoIdent::stable_id`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a type with the same full name, or the same stable id, is already registered.
+    pub fn register<T: ProtoDecode + ProtoIdentifiable + Send + 'static>(&mut self) -> &mut Self {
+        let ident = T::PROTO_IDENT;
+        let name = ident.full_name();
+        assert!(!self.by_name.contains_key(&name), "type `{name}` is registered twice");
+        if let Some(existing) = self.by_id.insert(ident.stable_id(), name.clone()) {
+            panic!("types `{existing}` and `{name}` have the same stable id");
+        }
+        self.by_name.insert(name, (ident, decode_boxed::<T>));
+        self
+    }
+
+    /// Builder-style [`TypeRegistry::register`].
+    #[must_use]
+    pub fn with<T: ProtoDecode + ProtoIdentifiable + Send + 'static>(mut self) -> Self {
+        self.register::<T>();
+        self
+    }
+
+    /// Ident of the type registered for `type_url`; only the part after the last `/` is compared.
+    pub fn resolve(&self, type_url: &str) -> Option<&ProtoIdent> {
+        self.by_name.get(type_url_name(type_url)).map(|(ident, _)| ident)
+    }
+
+    /// Ident of the type registered with `stable_id`.
+    pub fn resolve_id(&self, stable_id: u64) -> Option<&ProtoIdent> {
+        self.by_id.get(&stable_id).and_then(|name| self.by_name.get(name)).map(|(ident, _)| ident)
+    }
+
+    /// Decodes `bytes` as the type registered for `type_url`; downcast the result to the concrete type.
+    pub fn decode(&self, type_url: &str, bytes: &[u8]) -> Result<Box<dyn Any + Send>, DecodeError> {
+        self.decode_with(type_url, bytes, DecodeContext::default())
+    }
+
+    pub fn decode_with(&self, type_url: &str, bytes: &[u8], ctx: DecodeContext) -> Result<Box<dyn Any + Send>, DecodeError> {
+        let (_, decode) =
+            self.by_name.get(type_url_name(type_url)).ok_or_else(|| DecodeError::new(format!("no type registered for {type_url}")))?;
+        decode(bytes, ctx)
+    }
+
+    /// Decodes a [`SelfDescribing`] envelope of any registered type, dispatching on its type URL.
+    ///
+    /// The schema hash is returned as written; compare it with
+    /// [`schema_hash`](super::schema_hash) to detect payloads from another schema version.
+    pub fn decode_any_registered(&self, bytes: &[u8]) -> Result<SelfDescribing<Box<dyn Any + Send>>, DecodeError> {
+        let header = envelope::Header::parse(bytes)?;
+        Ok(SelfDescribing {
+            type_url: header.type_url.to_string(),
+            schema_hash: header.schema_hash,
+            value: self.decode(header.type_url, header.payload)?,
+        })
+    }
+
+    /// Decodes `bytes` as the type registered with `stable_id`.
+    pub fn decode_id(&self, stable_id: u64, bytes: &[u8]) -> Result<Box<dyn Any + Send>, DecodeError> {
+        let name = self.by_id.get(&stable_id).ok_or_else(|| DecodeError::new(format!("no type registered with id {stable_id:#018x}")))?;
+        self.decode(name, bytes)
+    }
+
+    /// Type URLs of the registered types, sorted.
+    pub fn type_urls(&self) -> impl Iterator<Item = String> + '_ {
+        self.by_name.values().map(|(ident, _)| ident.type_url())
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_name.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_name.is_empty()
+    }
+}
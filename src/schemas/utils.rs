@@ -1,5 +1,6 @@
 use std::collections::BTreeMap;
 
+use super::Field;
 use super::ProtoEntry;
 use super::ProtoIdent;
 use super::ProtoLabel;
@@ -10,6 +11,19 @@ pub(crate) fn derive_package_name(file_path: &str) -> String {
     file_path.trim_end_matches(".proto").replace(['/', '\\', '-', '.'], "_").to_lowercase()
 }
 
+/// Package of the `.proto` file `file_name`: the first package set explicitly by one of its
+/// definitions, otherwise the one derived from the file name.
+pub(crate) fn package_name_for_entries(file_name: &str, entries: &[&ProtoSchema]) -> String {
+    let file_name_last = std::path::Path::new(file_name).file_name().and_then(|name| name.to_str()).unwrap_or(file_name);
+    let derived = derive_package_name(file_name_last);
+    entries
+        .iter()
+        .filter(|schema| !matches!(schema.content, ProtoEntry::Import { .. }))
+        .map(|schema| schema.id.proto_package_name)
+        .find(|name| !name.is_empty() && *name != derived)
+        .map_or(derived, ToString::to_string)
+}
+
 pub(crate) fn module_path_segments(package_name: &str) -> Vec<String> {
     package_name.split('.').filter(|segment| !segment.is_empty()).map(sanitize_module_segment).collect()
 }
@@ -117,12 +131,15 @@ pub(crate) enum WrapperKind {
     Option,
     Vec,
     VecDeque,
+    BinaryHeap,
+    LinkedList,
     HashMap,
     BTreeMap,
     HashSet,
     BTreeSet,
     Box,
     Arc,
+    Weak,
     Mutex,
     ArcSwap,
     ArcSwapOption,
@@ -135,12 +152,15 @@ pub(crate) fn wrapper_kind(wrapper: Option<ProtoIdent>) -> Option<WrapperKind> {
         "Option" => WrapperKind::Option,
         "Vec" => WrapperKind::Vec,
         "VecDeque" => WrapperKind::VecDeque,
+        "BinaryHeap" => WrapperKind::BinaryHeap,
+        "LinkedList" => WrapperKind::LinkedList,
         "HashMap" => WrapperKind::HashMap,
         "BTreeMap" => WrapperKind::BTreeMap,
         "HashSet" => WrapperKind::HashSet,
         "BTreeSet" => WrapperKind::BTreeSet,
         "Box" => WrapperKind::Box,
         "Arc" => WrapperKind::Arc,
+        "Weak" => WrapperKind::Weak,
         "Mutex" => WrapperKind::Mutex,
         "ArcSwap" => WrapperKind::ArcSwap,
         "ArcSwapOption" => WrapperKind::ArcSwapOption,
@@ -162,7 +182,14 @@ pub(crate) fn wrapper_kind_for(wrapper: Option<ProtoIdent>, ident: ProtoIdent) -
 pub(crate) fn wrapper_label(wrapper: Option<ProtoIdent>, ident: ProtoIdent, current: ProtoLabel) -> ProtoLabel {
     match wrapper_kind_for(wrapper, ident) {
         Some(WrapperKind::Option | WrapperKind::ArcSwapOption) => ProtoLabel::Optional,
-        Some(WrapperKind::Vec | WrapperKind::VecDeque | WrapperKind::HashSet | WrapperKind::BTreeSet) => ProtoLabel::Repeated,
+        Some(
+            WrapperKind::Vec
+            | WrapperKind::VecDeque
+            | WrapperKind::BinaryHeap
+            | WrapperKind::LinkedList
+            | WrapperKind::HashSet
+            | WrapperKind::BTreeSet,
+        ) => ProtoLabel::Repeated,
         _ => current,
     }
 }
@@ -187,6 +214,8 @@ const WRAPPER_SCHEMA_PREFIXES: &[(&str, WrapperKind)] = &[
     ("CachePadded", WrapperKind::CachePadded),
     ("Option", WrapperKind::Option),
     ("VecDeque", WrapperKind::VecDeque),
+    ("BinaryHeap", WrapperKind::BinaryHeap),
+    ("LinkedList", WrapperKind::LinkedList),
     ("Vec", WrapperKind::Vec),
     ("HashMap", WrapperKind::HashMap),
     ("BTreeMap", WrapperKind::BTreeMap),
@@ -194,6 +223,7 @@ const WRAPPER_SCHEMA_PREFIXES: &[(&str, WrapperKind)] = &[
     ("BTreeSet", WrapperKind::BTreeSet),
     ("Box", WrapperKind::Box),
     ("Arc", WrapperKind::Arc),
+    ("Weak", WrapperKind::Weak),
     ("Mutex", WrapperKind::Mutex),
 ];
 
@@ -281,12 +311,96 @@ fn transparent_inner_ident(ident: &ProtoIdent, ident_index: &BTreeMap<ProtoIdent
 
     match schema.content {
         ProtoEntry::Struct { fields } if fields.len() == 1 => Some(fields[0].proto_ident),
+        ProtoEntry::ComplexEnum { variants: [variant] } if variant.fields.len() == 1 => Some(variant.fields[0].proto_ident),
+        ProtoEntry::SimpleEnum { .. } if is_string_enum_schema(schema) => Some(<String as super::ProtoIdentifiable>::PROTO_IDENT),
+        ProtoEntry::SimpleEnum { .. } => wide_enum_ident(schema),
         _ => None,
     }
 }
 
-fn is_transparent_schema(schema: &ProtoSchema) -> bool {
+pub(crate) fn is_transparent_schema(schema: &ProtoSchema) -> bool {
     schema.top_level_attributes.iter().any(|attr| attr.path == "proto_message" && attr.tokens.contains("transparent"))
+        || is_string_enum_schema(schema)
+        || wide_enum_ident(schema).is_some()
+}
+
+/// Enum declared with `#[repr(i64)]` or `#[repr(u64)]`, written as `int64` or `uint64` in other messages.
+fn wide_enum_ident(schema: &ProtoSchema) -> Option<ProtoIdent> {
+    let repr = schema.top_level_attributes.iter().find(|attr| attr.path == "repr")?;
+    if repr.tokens.contains("i64") {
+        Some(<i64 as super::ProtoIdentifiable>::PROTO_IDENT)
+    } else if repr.tokens.contains("u64") {
+        Some(<u64 as super::ProtoIdentifiable>::PROTO_IDENT)
+    } else {
+        None
+    }
+}
+
+/// Enum declared with `#[proto_message(string_enum)]`, written as `string` in other messages.
+fn is_string_enum_schema(schema: &ProtoSchema) -> bool {
+    schema.top_level_attributes.iter().any(|attr| attr.path == "proto_message" && attr.tokens == "string_enum")
+}
+
+/// Container declared with `#[proto(soa)]`, whose schema is one row; fields holding it are repeated.
+pub(crate) fn is_soa_schema(schema: &ProtoSchema) -> bool {
+    schema
+        .top_level_attributes
+        .iter()
+        .any(|attr| attr.path == "proto" && attr.tokens.split(|ch: char| !ch.is_alphanumeric() && ch != '_').any(|word| word == "soa"))
+}
+
+/// Parent message named by `#[proto_message(nest_in = "Parent")]`.
+pub(crate) fn nest_parent(schema: &ProtoSchema) -> Option<&'static str> {
+    proto_message_setting(schema, "nest_in")
+}
+
+/// Value of a `#[proto_message(key = "value")]` setting recorded in the schema.
+fn proto_message_setting(schema: &ProtoSchema, key: &str) -> Option<&'static str> {
+    schema
+        .top_level_attributes
+        .iter()
+        .filter(|attr| attr.path == "proto_message")
+        .find_map(|attr| attr.tokens.strip_prefix("#[proto_message(")?.strip_prefix(key)?.strip_prefix(" = \"")?.split('"').next())
+}
+
+/// Transparent newtype declared with `unit` and/or `range`, e.g. `Millis(u64)`.
+pub(crate) struct Newtype {
+    pub schema: &'static ProtoSchema,
+    pub unit: Option<&'static str>,
+    pub range: Option<&'static str>,
+}
+
+/// The newtype a field holds, directly or inside an `Option`, list or array.
+pub(crate) fn field_newtype(field: &Field, ident_index: &BTreeMap<ProtoIdent, &'static ProtoSchema>) -> Option<Newtype> {
+    let candidates = [Some(field.rust_proto_ident), Some(field.proto_ident), field.array_elem];
+    candidates.into_iter().flatten().find_map(|ident| {
+        let schema = *ident_index.get(&ident)?;
+        let unit = proto_message_setting(schema, "unit");
+        let range = proto_message_setting(schema, "range");
+        (unit.is_some() || range.is_some()).then_some(Newtype { schema, unit, range })
+    })
+}
+
+/// Proto name of `ident` relative to its package, including the messages it is nested in.
+pub(crate) fn nested_type_path(ident: ProtoIdent, ident_index: &BTreeMap<ProtoIdent, &'static ProtoSchema>) -> String {
+    let name = proto_ident_base_type_name(ident);
+    let mut path = name.clone();
+    let mut current = ident_index.get(&ident).copied();
+    for _ in 0..=ident_index.len() {
+        let Some(parent) = current.and_then(nest_parent) else {
+            return path;
+        };
+        current = ident_index
+            .values()
+            .copied()
+            .find(|schema| schema.id.proto_file_path == ident.proto_file_path && proto_ident_base_type_name(schema.id) == parent);
+        if current.is_none() {
+            return path;
+        }
+        path = format!("{parent}.{path}");
+    }
+    // Nesting cycles leave their members at the top level
+    name
 }
 
 pub(crate) fn to_snake_case(s: &str) -> String {
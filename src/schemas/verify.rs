@@ -0,0 +1,270 @@
+//! Comparison of the registered schemas against an externally compiled descriptor set.
+//!
+//! Rust-first types drift from the canonical `.proto` repository when one side changes and the
+//! other does not. [`verify_against`] reads the `FileDescriptorSet` built from the canonical files
+//! (`protoc --descriptor_set_out` or `buf build -o`) and reports every registered message and
+//! enum whose tags, types or labels differ from it.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt;
+
+use super::IDENT_INDEX;
+use super::ProtoEntry;
+use super::ProtoLabel;
+use super::ProtoSchema;
+use super::descriptor::DescriptorSet;
+use super::descriptor::EnumDescriptor;
+use super::descriptor::FieldDescriptor;
+use super::descriptor::MessageDescriptor;
+use super::proto_output;
+use super::proto_output::FieldSignature;
+use super::utils::is_transparent_schema;
+use super::utils::is_wrapper_schema;
+use super::utils::nested_type_path;
+use super::utils::to_snake_case;
+use super::utils::wrapper_kind_from_schema_name;
+use crate::DecodeError;
+
+/// A registered type that disagrees with the descriptor set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaMismatch {
+    /// Full proto name of the registered type (`package.Type`).
+    pub type_name: String,
+    pub kind: MismatchKind,
+}
+
+/// How a registered type differs from its upstream definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MismatchKind {
+    /// The package is in the descriptor set, the type is not.
+    MissingType,
+    /// The registered field or enum value has no counterpart upstream.
+    MissingField { field: String },
+    /// The upstream field or enum value is not registered.
+    ExtraField { field: String, tag: i64 },
+    /// The field has another number upstream.
+    Tag { field: String, ours: u32, upstream: u32 },
+    /// The field has another type upstream.
+    Type { field: String, ours: String, upstream: String },
+    /// The field is `optional`, `repeated` or neither on one side only.
+    Label {
+        field: String,
+        ours: ProtoLabel,
+        upstream: ProtoLabel,
+    },
+    /// The enum value has another number upstream.
+    EnumValue { value: String, ours: i32, upstream: i32 },
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = &self.type_name;
+        match &self.kind {
+            MismatchKind::MissingType => write!(f, "`{name}` is not in the descriptor set"),
+            MismatchKind::MissingField { field } => write!(f, "`{name}.{field}` is not in the descriptor set"),
+            MismatchKind::ExtraField { field, tag } => write!(f, "`{name}.{field}` = {tag} is only in the descriptor set"),
+            MismatchKind::Tag { field, ours, upstream } => write!(f, "`{name}.{field}` has tag {ours}, upstream {upstream}"),
+            MismatchKind::Type { field, ours, upstream } => write!(f, "`{name}.{field}` has type {ours}, upstream {upstream}"),
+            MismatchKind::Label { field, ours, upstream } => {
+                write!(f, "`{name}.{field}` is {}, upstream {}", label_name(*ours), label_name(*upstream))
+            }
+            MismatchKind::EnumValue { value, ours, upstream } => write!(f, "`{name}.{value}` = {ours}, upstream {upstream}"),
+        }
+    }
+}
+
+const fn label_name(label: ProtoLabel) -> &'static str {
+    match label {
+        ProtoLabel::None => "singular",
+        ProtoLabel::Optional => "optional",
+        ProtoLabel::Repeated => "repeated",
+    }
+}
+
+/// Mismatches between the registered messages and enums and the serialized `FileDescriptorSet`
+/// in `descriptor_set`.
+///
+/// Only types in packages the set declares are checked, so a set built from part of the upstream
+/// repository does not report the rest of the registry. Fields are matched by name. Services and
+/// generic types are not compared.
+///
+/// # Errors
+///
+/// Returns `Err` when `descriptor_set` is not a valid descriptor set.
+pub fn verify_against(descriptor_set: &[u8]) -> Result<Vec<SchemaMismatch>, DecodeError> {
+    let set = DescriptorSet::decode(descriptor_set)?;
+    let packages: BTreeSet<&str> = set.files.iter().map(|file| file.package.as_str()).collect();
+    let messages: BTreeMap<String, &MessageDescriptor> = set.messages().into_iter().collect();
+    let enums: BTreeMap<String, &EnumDescriptor> = set.enums().into_iter().collect();
+
+    let mut mismatches = Vec::new();
+    for schema in IDENT_INDEX.values().copied().filter(|schema| is_comparable(schema)) {
+        let package = schema.id.proto_package_name;
+        if !packages.contains(package) {
+            continue;
+        }
+        let path = nested_type_path(schema.id, &IDENT_INDEX);
+        let type_name = if package.is_empty() {
+            path.clone()
+        } else {
+            format!("{package}.{path}")
+        };
+        let mut report = |kind| {
+            mismatches.push(SchemaMismatch {
+                type_name: type_name.clone(),
+                kind,
+            });
+        };
+
+        match schema.content {
+            ProtoEntry::SimpleEnum { variants } => {
+                let Some(upstream) = enums.get(&type_name) else {
+                    report(MismatchKind::MissingType);
+                    continue;
+                };
+                let ours: Vec<(String, i32)> =
+                    variants.iter().map(|variant| (variant.name.to_string(), variant.discriminant.unwrap_or_default())).collect();
+                let prefix = format!("{}_", to_snake_case(path.rsplit('.').next().unwrap_or(&path)).to_uppercase());
+                compare_enum(&ours, upstream, &prefix, &mut report);
+            }
+            ProtoEntry::Struct { fields } => {
+                let Some(upstream) = messages.get(&type_name) else {
+                    report(MismatchKind::MissingType);
+                    continue;
+                };
+                let ours = proto_output::struct_signatures(fields, package, &IDENT_INDEX);
+                compare_message(&ours, upstream, &messages, &mut report);
+            }
+            ProtoEntry::ComplexEnum { variants } => {
+                let Some(upstream) = messages.get(&type_name) else {
+                    report(MismatchKind::MissingType);
+                    continue;
+                };
+                let name = path.rsplit('.').next().unwrap_or(&path);
+                let ours = proto_output::complex_enum_signatures(name, variants, package, &IDENT_INDEX);
+                compare_message(&ours, upstream, &messages, &mut report);
+            }
+            ProtoEntry::Import { .. } | ProtoEntry::Service { .. } => {}
+        }
+    }
+    Ok(mismatches)
+}
+
+/// Messages and enums written under their own name.
+fn is_comparable(schema: &ProtoSchema) -> bool {
+    schema.generics.is_empty()
+        && !schema.id.proto_file_path.is_empty()
+        && matches!(
+            schema.content,
+            ProtoEntry::Struct { .. } | ProtoEntry::SimpleEnum { .. } | ProtoEntry::ComplexEnum { .. }
+        )
+        && !is_wrapper_schema(schema)
+        && !is_transparent_schema(schema)
+        && wrapper_kind_from_schema_name(schema.id.name).is_none()
+}
+
+/// Values are matched by name, with or without the `ENUM_NAME_` prefix style guides ask for.
+fn compare_enum(ours: &[(String, i32)], upstream: &EnumDescriptor, prefix: &str, report: &mut impl FnMut(MismatchKind)) {
+    let same = |ours: &str, theirs: &str| ours.strip_prefix(prefix).unwrap_or(ours) == theirs.strip_prefix(prefix).unwrap_or(theirs);
+    for (value, number) in ours {
+        match upstream.values.iter().find(|(name, _)| same(value, name)) {
+            None => report(MismatchKind::MissingField { field: value.clone() }),
+            Some((_, upstream)) if upstream != number => report(MismatchKind::EnumValue {
+                value: value.clone(),
+                ours: *number,
+                upstream: *upstream,
+            }),
+            Some(_) => {}
+        }
+    }
+    for (value, number) in &upstream.values {
+        if !ours.iter().any(|(name, _)| same(name, value)) {
+            report(MismatchKind::ExtraField {
+                field: value.clone(),
+                tag: i64::from(*number),
+            });
+        }
+    }
+}
+
+fn compare_message(
+    ours: &[FieldSignature],
+    upstream: &MessageDescriptor,
+    messages: &BTreeMap<String, &MessageDescriptor>,
+    report: &mut impl FnMut(MismatchKind),
+) {
+    for field in ours {
+        let Some(theirs) = upstream.fields.iter().find(|theirs| theirs.name == field.name) else {
+            report(MismatchKind::MissingField { field: field.name.clone() });
+            continue;
+        };
+        if theirs.number != field.tag {
+            report(MismatchKind::Tag {
+                field: field.name.clone(),
+                ours: field.tag,
+                upstream: theirs.number,
+            });
+        }
+        let (label, upstream_type) = upstream_signature(theirs, messages);
+        if !same_type(&field.type_name, &upstream_type) {
+            report(MismatchKind::Type {
+                field: field.name.clone(),
+                ours: field.type_name.clone(),
+                upstream: upstream_type,
+            });
+        }
+        // Message fields have presence either way, so `optional` on them changes nothing
+        let presence_only = theirs.kind == MESSAGE_KIND && label != ProtoLabel::Repeated && field.label != ProtoLabel::Repeated;
+        if label != field.label && !presence_only {
+            report(MismatchKind::Label {
+                field: field.name.clone(),
+                ours: field.label,
+                upstream: label,
+            });
+        }
+    }
+    for theirs in &upstream.fields {
+        if !ours.iter().any(|field| field.name == theirs.name) {
+            report(MismatchKind::ExtraField {
+                field: theirs.name.clone(),
+                tag: i64::from(theirs.number),
+            });
+        }
+    }
+}
+
+/// `FieldDescriptorProto.Type` of message fields.
+const MESSAGE_KIND: i32 = 11;
+
+/// Label and type of an upstream field, with map entries folded back into `map<K, V>`.
+fn upstream_signature(field: &FieldDescriptor, messages: &BTreeMap<String, &MessageDescriptor>) -> (ProtoLabel, String) {
+    let referenced = field.type_name.strip_prefix('.').unwrap_or(&field.type_name);
+    if let Some(entry) = messages.get(referenced).filter(|entry| entry.map_entry && field.is_repeated()) {
+        let part = |number| entry.field(number).map_or_else(String::new, |field| upstream_signature(field, messages).1);
+        return (ProtoLabel::None, format!("map<{}, {}>", part(1), part(2)));
+    }
+    let label = if field.is_repeated() {
+        ProtoLabel::Repeated
+    } else if field.proto3_optional {
+        ProtoLabel::Optional
+    } else {
+        ProtoLabel::None
+    };
+    let name = field.scalar_name().map_or_else(|| referenced.to_string(), ToString::to_string);
+    (label, name)
+}
+
+/// Whether our type name, relative to the registered package, names the fully-qualified `upstream`.
+fn same_type(ours: &str, upstream: &str) -> bool {
+    if let (Some(ours), Some(upstream)) = (map_parts(ours), map_parts(upstream)) {
+        return same_type(ours.0, upstream.0) && same_type(ours.1, upstream.1);
+    }
+    ours == upstream || upstream.strip_suffix(ours).is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+fn map_parts(type_name: &str) -> Option<(&str, &str)> {
+    let inner = type_name.strip_prefix("map<")?.strip_suffix('>')?;
+    let (key, value) = inner.split_once(',')?;
+    Some((key.trim(), value.trim()))
+}
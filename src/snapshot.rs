@@ -0,0 +1,78 @@
+//! Encoding of shared state held in an `ArcSwap`.
+//!
+//! A server keeping a large cached state behind `ArcSwap<T>` encodes it for every reader.
+//! Encoding from the guard returned by `load()` ties up one of arc-swap's few per-thread debt
+//! slots for as long as the encode runs. [`encode_snapshot`] takes one consistent `Arc` with
+//! `load_full()` and encodes that, so the bytes match a single version of the state and writers
+//! keep swapping freely while the encode runs:
+//!
+//! ```rust,ignore
+//! static BOOK: LazyLock<ArcSwap<OrderBook>> = LazyLock::new(|| ArcSwap::from_pointee(OrderBook::default()));
+//!
+//! let bytes = encode_snapshot(&BOOK);
+//! // in async handlers, large books are encoded on the blocking pool
+//! let bytes = encode_snapshot_offloaded(&BOOK, 256 * 1024).await;
+//! ```
+
+use alloc::sync::Arc;
+
+use arc_swap::ArcSwapAny;
+use arc_swap::strategy::Strategy;
+use bytes::Bytes;
+
+use crate::ProtoEncode;
+use crate::ProtoExt;
+
+/// An `Arc` snapshot of the state together with its encoding.
+#[derive(Debug, Clone)]
+pub struct EncodedSnapshot<T> {
+    pub value: Arc<T>,
+    pub bytes: Bytes,
+}
+
+/// Encodes the state current at the time of the call.
+pub fn encode_snapshot<T, S>(state: &ArcSwapAny<Arc<T>, S>) -> Bytes
+where
+    T: ProtoEncode + ProtoExt,
+    S: Strategy<Arc<T>>,
+{
+    snapshot(state).bytes
+}
+
+/// Like [`encode_snapshot`], also returning the `Arc` that was encoded, e.g. to key a cache of
+/// encodings by `Arc::ptr_eq`.
+pub fn snapshot<T, S>(state: &ArcSwapAny<Arc<T>, S>) -> EncodedSnapshot<T>
+where
+    T: ProtoEncode + ProtoExt,
+    S: Strategy<Arc<T>>,
+{
+    let value = state.load_full();
+    let bytes = Bytes::from(value.encode_to_vec());
+    EncodedSnapshot { value, bytes }
+}
+
+/// Encodes the state current at the time of the call, on tokio's blocking pool when its
+/// [`encoded_len_hint`](ProtoEncode::encoded_len_hint) is `blocking_threshold` bytes or more (or
+/// unknown), so a large state does not stall the runtime worker the handler runs on.
+///
+/// Must be called within a tokio runtime. A panic during the encode is resumed in the caller.
+///
+/// # Panics
+///
+/// Panics when the runtime shuts down before the blocking encode ran.
+#[cfg(feature = "tonic")]
+pub async fn encode_snapshot_offloaded<T, S>(state: &ArcSwapAny<Arc<T>, S>, blocking_threshold: usize) -> Bytes
+where
+    T: ProtoEncode + ProtoExt + Send + Sync + 'static,
+    S: Strategy<Arc<T>>,
+{
+    let value = state.load_full();
+    if value.encoded_len_hint().is_some_and(|len| len < blocking_threshold) {
+        return Bytes::from(value.encode_to_vec());
+    }
+    match tokio::task::spawn_blocking(move || Bytes::from(value.encode_to_vec())).await {
+        Ok(bytes) => bytes,
+        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+        Err(err) => panic!("snapshot encode did not run: {err}"),
+    }
+}
@@ -0,0 +1,59 @@
+//! Struct-of-arrays containers encoded as a repeated message field.
+//!
+//! Data held column by column, one `Vec` per field, is written as the rows a repeated message
+//! field carries without building a row struct per element. `#[proto(soa)]` on the container
+//! derives the adapter:
+//!
+//! ```rust,ignore
+//! #[proto_message]
+//! #[proto(soa)]
+//! pub struct Trades {
+//!     pub price: Vec<u64>,
+//!     pub size: Vec<u32>,
+//! }
+//!
+//! #[proto_message]
+//! pub struct Tape {
+//!     pub venue: String,
+//!     pub trades: Trades, // repeated Trades trades = 2;
+//! }
+//! ```
+//!
+//! The schema of the container is the row message, and fields holding a container are written
+//! as `repeated`. Decoding appends one element to every column per row. Encoded on its own, a
+//! container is a message with the rows in field 1.
+
+use crate::ArchivedProtoField;
+use crate::ProtoArchive;
+use crate::ProtoExt;
+use crate::RevWriter;
+
+/// Columns that are encoded row by row.
+///
+/// The row count is the length of the shortest column, as with [`Iterator::zip`].
+pub trait SoaColumns {
+    /// Borrowed view of one row, archived as the row message.
+    type Row<'a>: ProtoArchive + ProtoExt
+    where
+        Self: 'a;
+
+    /// Number of rows.
+    fn rows(&self) -> usize;
+
+    /// Row `idx`, below [`rows`](Self::rows).
+    fn row(&self, idx: usize) -> Self::Row<'_>;
+}
+
+/// Writes every row of `columns` under `TAG`, empty rows included.
+#[inline]
+pub fn archive_rows<const TAG: u32, C: SoaColumns + ?Sized>(columns: &C, w: &mut impl RevWriter) {
+    for idx in (0..columns.rows()).rev() {
+        ArchivedProtoField::<TAG, C::Row<'_>>::new_always(&columns.row(idx), w);
+    }
+}
+
+/// Upper bound of the bytes [`archive_rows`] writes.
+#[inline]
+pub fn rows_size_hint<const TAG: u32, C: SoaColumns + ?Sized>(columns: &C) -> Option<usize> {
+    (0..columns.rows()).try_fold(0usize, |total, idx| Some(total + columns.row(idx).size_hint::<TAG>()?))
+}
@@ -0,0 +1,377 @@
+//! Table rows of flat registered messages, for services that persist request and response
+//! snapshots in SQL tables without a third struct per type.
+//!
+//! A [`RowCodec`] maps every field of a message to a column named after the field, in
+//! declaration order. Only flat messages have a codec: every field has to be a scalar, an enum,
+//! `bytes` or an optional one of those. Columns use Postgres types: `int32`-sized integers and
+//! enums are `INTEGER`, other integers `BIGINT`, `float` is `REAL`, `double` is
+//! `DOUBLE PRECISION`, `string` is `TEXT` and `bytes` is `BYTEA`. Optional fields are the only
+//! nullable columns.
+//!
+//! ```rust,ignore
+//! let codec = proto_rs::sql::RowCodec::<Order>::new()?;
+//! let ddl = codec.create_table("order_snapshots");
+//! let row = codec.to_row(&order)?;
+//! let order: Order = codec.from_row(row)?;
+//! ```
+//!
+//! With the `sql-postgres` feature, [`RowCodec::bind`] binds a message to an `sqlx` query and
+//! [`SqlRow`] reads query results back as messages.
+
+use std::fmt::Write;
+use std::marker::PhantomData;
+
+use crate::ProtoDecode;
+use crate::ProtoEncode;
+use crate::keyed::Keyed;
+use crate::keyed::default_keyed;
+use crate::keyed::from_keyed;
+use crate::keyed::message_ident;
+use crate::keyed::to_keyed;
+use crate::schemas::ProtoEntry;
+use crate::schemas::ProtoIdentifiable;
+use crate::schemas::ProtoLabel;
+use crate::schemas::ProtoType;
+use crate::schemas::schema_for;
+use crate::traits::ProtoExt;
+use crate::transcode::Kind;
+use crate::transcode::TranscodeError;
+use crate::transcode::field_name;
+use crate::transcode::kind;
+
+/// Postgres type of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlType {
+    Boolean,
+    Integer,
+    BigInt,
+    Real,
+    DoublePrecision,
+    Text,
+    Bytea,
+}
+
+impl SqlType {
+    /// Type name as written in DDL.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            SqlType::Boolean => "BOOLEAN",
+            SqlType::Integer => "INTEGER",
+            SqlType::BigInt => "BIGINT",
+            SqlType::Real => "REAL",
+            SqlType::DoublePrecision => "DOUBLE PRECISION",
+            SqlType::Text => "TEXT",
+            SqlType::Bytea => "BYTEA",
+        }
+    }
+}
+
+/// Column holding one field of a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    /// Field name; tuple fields are `field_{idx}`.
+    pub name: String,
+    pub sql_type: SqlType,
+    /// Set for optional fields.
+    pub nullable: bool,
+    pub tag: u32,
+    kind: ProtoType,
+}
+
+/// Value of one column.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    Null,
+    Bool(bool),
+    Int(i32),
+    BigInt(i64),
+    Real(f32),
+    Double(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+/// Row mapping of message `T`.
+#[derive(Debug, Clone)]
+pub struct RowCodec<T> {
+    columns: Vec<Column>,
+    _message: PhantomData<fn() -> T>,
+}
+
+impl<T: ProtoIdentifiable> RowCodec<T> {
+    /// Codec for `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TranscodeError::UnknownMessage`] if `T` has no registered message schema and
+    /// [`TranscodeError::Invalid`] naming the first field that cannot be a column.
+    pub fn new() -> Result<Self, TranscodeError> {
+        let ident = message_ident::<T>()?;
+        let Some(ProtoEntry::Struct { fields }) = schema_for(&ident).map(|schema| schema.content) else {
+            return Err(TranscodeError::invalid(format!("{} is not a struct", ident.name)));
+        };
+        let mut columns = Vec::with_capacity(fields.len());
+        for (idx, field) in fields.iter().enumerate() {
+            let name = field_name(field, idx);
+            let ty = if field.is_bytes() {
+                ProtoType::Bytes
+            } else if field.proto_label == ProtoLabel::Repeated || matches!(field.proto_ident.proto_type, ProtoType::Map { .. }) {
+                return Err(TranscodeError::invalid("repeated and map fields are not columns").at(&name));
+            } else {
+                match kind(&field.proto_ident)? {
+                    Kind::Scalar(ty) => ty,
+                    Kind::Enum(_) => ProtoType::Enum,
+                    Kind::Message(_) => return Err(TranscodeError::invalid("message fields are not columns").at(&name)),
+                }
+            };
+            let sql_type = sql_type(ty).ok_or_else(|| TranscodeError::invalid("unsupported field type").at(&name))?;
+            columns.push(Column {
+                name,
+                sql_type,
+                nullable: field.proto_label == ProtoLabel::Optional,
+                tag: field.tag,
+                kind: ty,
+            });
+        }
+        Ok(Self {
+            columns,
+            _message: PhantomData,
+        })
+    }
+
+    /// Columns in field declaration order.
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// `CREATE TABLE IF NOT EXISTS` statement for a table holding only the message columns.
+    pub fn create_table(&self, table: &str) -> String {
+        let mut sql = format!("CREATE TABLE IF NOT EXISTS {table} (");
+        for (idx, column) in self.columns.iter().enumerate() {
+            let separator = if idx == 0 { "" } else { "," };
+            let null = if column.nullable { "" } else { " NOT NULL" };
+            write!(sql, "{separator}\n    \"{}\" {}{null}", column.name, column.sql_type.as_str()).unwrap();
+        }
+        sql.push_str("\n)");
+        sql
+    }
+
+    /// `INSERT` statement binding the columns as `$1`, `$2`, ... in order.
+    pub fn insert(&self, table: &str) -> String {
+        let placeholders = (1..=self.columns.len()).map(|idx| format!("${idx}")).collect::<Vec<_>>().join(", ");
+        format!("INSERT INTO {table} ({}) VALUES ({placeholders})", self.column_list())
+    }
+
+    /// `SELECT` statement reading the columns in order.
+    pub fn select(&self, table: &str) -> String {
+        format!("SELECT {} FROM {table}", self.column_list())
+    }
+
+    fn column_list(&self) -> String {
+        self.columns.iter().map(|column| format!("\"{}\"", column.name)).collect::<Vec<_>>().join(", ")
+    }
+}
+
+impl<T: ProtoEncode + ProtoExt + ProtoIdentifiable> RowCodec<T> {
+    /// Column values of `value`. Unset optional fields are [`SqlValue::Null`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TranscodeError::Invalid`] if a `uint64` or `fixed64` value does not fit a
+    /// `BIGINT`.
+    pub fn to_row(&self, value: &T) -> Result<Vec<SqlValue>, TranscodeError> {
+        let Keyed::Map(entries) = to_keyed(value)? else {
+            return Err(TranscodeError::invalid("expected a map"));
+        };
+        self.columns
+            .iter()
+            .map(|column| {
+                let key = Keyed::Int(column.tag.into());
+                match entries.iter().find(|(tag, _)| *tag == key) {
+                    Some((_, value)) => to_sql(column.sql_type, value),
+                    None if column.nullable => Ok(SqlValue::Null),
+                    None => to_sql(column.sql_type, &default_keyed(&Kind::Scalar(column.kind))),
+                }
+                .map_err(|error| error.at(&column.name))
+            })
+            .collect()
+    }
+}
+
+impl<T: ProtoDecode + ProtoIdentifiable> RowCodec<T> {
+    /// Message from column values in [`columns`](Self::columns) order. `NULL` leaves a field
+    /// unset.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TranscodeError::Invalid`] if the row has the wrong number of values or a value
+    /// does not fit its field.
+    pub fn from_row(&self, row: impl IntoIterator<Item = SqlValue>) -> Result<T, TranscodeError> {
+        let row: Vec<SqlValue> = row.into_iter().collect();
+        if row.len() != self.columns.len() {
+            return Err(TranscodeError::invalid(format!(
+                "expected {} columns, got {}",
+                self.columns.len(),
+                row.len()
+            )));
+        }
+        let entries = self
+            .columns
+            .iter()
+            .zip(row)
+            .filter_map(|(column, value)| from_sql(value).map(|value| (Keyed::Int(column.tag.into()), value)))
+            .collect();
+        from_keyed(&Keyed::Map(entries))
+    }
+}
+
+const fn sql_type(ty: ProtoType) -> Option<SqlType> {
+    Some(match ty {
+        ProtoType::Bool => SqlType::Boolean,
+        ProtoType::Int32 | ProtoType::Sint32 | ProtoType::Sfixed32 | ProtoType::Enum => SqlType::Integer,
+        ProtoType::Uint32
+        | ProtoType::Fixed32
+        | ProtoType::Int64
+        | ProtoType::Sint64
+        | ProtoType::Sfixed64
+        | ProtoType::Uint64
+        | ProtoType::Fixed64 => SqlType::BigInt,
+        ProtoType::Float => SqlType::Real,
+        ProtoType::Double => SqlType::DoublePrecision,
+        ProtoType::String => SqlType::Text,
+        ProtoType::Bytes => SqlType::Bytea,
+        ProtoType::Optional(inner) => return sql_type(*inner),
+        ProtoType::Repeated(_) | ProtoType::Message(_) | ProtoType::Map { .. } | ProtoType::None => return None,
+    })
+}
+
+fn to_sql(sql_type: SqlType, value: &Keyed) -> Result<SqlValue, TranscodeError> {
+    Ok(match (sql_type, value) {
+        (SqlType::Boolean, Keyed::Bool(value)) => SqlValue::Bool(*value),
+        (SqlType::Integer, Keyed::Int(value)) => {
+            SqlValue::Int(i32::try_from(*value).map_err(|_| TranscodeError::invalid(format!("{value} does not fit INTEGER")))?)
+        }
+        (SqlType::BigInt, Keyed::Int(value)) => {
+            SqlValue::BigInt(i64::try_from(*value).map_err(|_| TranscodeError::invalid(format!("{value} does not fit BIGINT")))?)
+        }
+        (SqlType::Real, Keyed::F32(value)) => SqlValue::Real(*value),
+        (SqlType::DoublePrecision, Keyed::F64(value)) => SqlValue::Double(*value),
+        (SqlType::Text, Keyed::Text(value)) => SqlValue::Text(value.clone()),
+        (SqlType::Bytea, Keyed::Bytes(value)) => SqlValue::Bytes(value.clone()),
+        _ => return Err(TranscodeError::invalid("value does not match the column type")),
+    })
+}
+
+fn from_sql(value: SqlValue) -> Option<Keyed> {
+    Some(match value {
+        SqlValue::Null => return None,
+        SqlValue::Bool(value) => Keyed::Bool(value),
+        SqlValue::Int(value) => Keyed::Int(value.into()),
+        SqlValue::BigInt(value) => Keyed::Int(value.into()),
+        SqlValue::Real(value) => Keyed::F32(value),
+        SqlValue::Double(value) => Keyed::F64(value),
+        SqlValue::Text(value) => Keyed::Text(value),
+        SqlValue::Bytes(value) => Keyed::Bytes(value),
+    })
+}
+
+#[cfg(feature = "sql-postgres")]
+mod postgres {
+    use sqlx::FromRow;
+    use sqlx::Postgres;
+    use sqlx::Row;
+    use sqlx::postgres::PgArguments;
+    use sqlx::postgres::PgRow;
+    use sqlx::query::Query;
+
+    use super::RowCodec;
+    use super::SqlType;
+    use super::SqlValue;
+    use crate::ProtoDecode;
+    use crate::ProtoEncode;
+    use crate::schemas::ProtoIdentifiable;
+    use crate::traits::ProtoExt;
+    use crate::transcode::TranscodeError;
+
+    /// Message read from a query row by column name, for `sqlx::query_as`. Columns that are not
+    /// fields, such as a surrogate key, are ignored.
+    ///
+    /// ```rust,ignore
+    /// let orders: Vec<SqlRow<Order>> = sqlx::query_as("SELECT * FROM order_snapshots").fetch_all(&pool).await?;
+    /// ```
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct SqlRow<T>(pub T);
+
+    impl<'r, T: ProtoDecode + ProtoIdentifiable> FromRow<'r, PgRow> for SqlRow<T> {
+        fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+            let codec = RowCodec::<T>::new().map_err(|error| sqlx::Error::Decode(Box::new(error)))?;
+            codec.decode(row).map(SqlRow).map_err(|error| sqlx::Error::Decode(Box::new(error)))
+        }
+    }
+
+    impl<T: ProtoEncode + ProtoExt + ProtoIdentifiable> RowCodec<T> {
+        /// Binds the columns of `value` to `query` in order, e.g. to the statement from
+        /// [`insert`](Self::insert).
+        ///
+        /// # Errors
+        ///
+        /// Returns the errors of [`to_row`](Self::to_row).
+        pub fn bind<'q>(
+            &self,
+            mut query: Query<'q, Postgres, PgArguments>,
+            value: &T,
+        ) -> Result<Query<'q, Postgres, PgArguments>, TranscodeError> {
+            for (column, value) in self.columns.iter().zip(self.to_row(value)?) {
+                query = match (column.sql_type, value) {
+                    (SqlType::Boolean, SqlValue::Bool(value)) => query.bind(value),
+                    (SqlType::Boolean, _) => query.bind(None::<bool>),
+                    (SqlType::Integer, SqlValue::Int(value)) => query.bind(value),
+                    (SqlType::Integer, _) => query.bind(None::<i32>),
+                    (SqlType::BigInt, SqlValue::BigInt(value)) => query.bind(value),
+                    (SqlType::BigInt, _) => query.bind(None::<i64>),
+                    (SqlType::Real, SqlValue::Real(value)) => query.bind(value),
+                    (SqlType::Real, _) => query.bind(None::<f32>),
+                    (SqlType::DoublePrecision, SqlValue::Double(value)) => query.bind(value),
+                    (SqlType::DoublePrecision, _) => query.bind(None::<f64>),
+                    (SqlType::Text, SqlValue::Text(value)) => query.bind(value),
+                    (SqlType::Text, _) => query.bind(None::<String>),
+                    (SqlType::Bytea, SqlValue::Bytes(value)) => query.bind(value),
+                    (SqlType::Bytea, _) => query.bind(None::<Vec<u8>>),
+                };
+            }
+            Ok(query)
+        }
+    }
+
+    impl<T: ProtoDecode + ProtoIdentifiable> RowCodec<T> {
+        /// Message read from `row` by column name.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`TranscodeError::Invalid`] naming the column that is missing or has another
+        /// type, and the errors of [`from_row`](Self::from_row).
+        pub fn decode(&self, row: &PgRow) -> Result<T, TranscodeError> {
+            let values = self
+                .columns
+                .iter()
+                .map(|column| {
+                    let name = column.name.as_str();
+                    let value = match column.sql_type {
+                        SqlType::Boolean => row.try_get::<Option<bool>, _>(name).map(|value| value.map(SqlValue::Bool)),
+                        SqlType::Integer => row.try_get::<Option<i32>, _>(name).map(|value| value.map(SqlValue::Int)),
+                        SqlType::BigInt => row.try_get::<Option<i64>, _>(name).map(|value| value.map(SqlValue::BigInt)),
+                        SqlType::Real => row.try_get::<Option<f32>, _>(name).map(|value| value.map(SqlValue::Real)),
+                        SqlType::DoublePrecision => row.try_get::<Option<f64>, _>(name).map(|value| value.map(SqlValue::Double)),
+                        SqlType::Text => row.try_get::<Option<String>, _>(name).map(|value| value.map(SqlValue::Text)),
+                        SqlType::Bytea => row.try_get::<Option<Vec<u8>>, _>(name).map(|value| value.map(SqlValue::Bytes)),
+                    };
+                    value.map(|value| value.unwrap_or(SqlValue::Null)).map_err(|error| TranscodeError::invalid(error.to_string()).at(name))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            self.from_row(values)
+        }
+    }
+}
+
+#[cfg(feature = "sql-postgres")]
+pub use postgres::SqlRow;
@@ -0,0 +1,175 @@
+//! Push-based decoding of length-delimited messages from arbitrary byte chunks.
+//!
+//! Each frame is a varint length followed by one encoded message, the layout written by
+//! [`encode_length_delimiter`](crate::encode_length_delimiter) in front of a message. [`Decoder`]
+//! keeps a partial frame between calls, so an event loop can hand it whatever a socket read
+//! returned without tracking frame boundaries itself.
+//!
+//! ```rust,ignore
+//! let mut decoder = stream::Decoder::<Order>::new();
+//! loop {
+//!     let n = socket.read(&mut chunk)?;
+//!     let mut next = decoder.feed(&chunk[..n])?;
+//!     while let Some(order) = next {
+//!         handle(order);
+//!         next = decoder.decode_next()?;
+//!     }
+//! }
+//! ```
+
+use alloc::format;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::ProtoDecode;
+use crate::encoding::MAX_HEADER_LEN;
+use crate::encoding::peek_len;
+
+/// Largest frame accepted unless [`Decoder::max_frame_len`] sets another limit, matching the
+/// default gRPC message size.
+pub const DEFAULT_MAX_FRAME_LEN: usize = 4 * 1024 * 1024;
+
+/// Decodes a stream of length-delimited `T` messages fed in chunks of any size.
+///
+/// A frame whose message fails to decode is dropped and decoding continues with the next frame. A
+/// malformed or oversized length leaves no way to find the next frame, so the decoder discards its
+/// buffered bytes and should be [`reset`](Self::reset) or replaced along with the connection.
+#[derive(Debug)]
+pub struct Decoder<'a, T> {
+    buf: Vec<u8>,
+    /// Start of the unread bytes in `buf`.
+    pos: usize,
+    state: State,
+    max_frame_len: usize,
+    ctx: DecodeContext<'a>,
+    _message: PhantomData<fn() -> T>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Waiting for the complete varint length of the next frame.
+    Length,
+    /// Waiting for a message of `len` bytes behind a length of `header` bytes.
+    Body { header: usize, len: usize },
+}
+
+impl<'a, T: ProtoDecode> Decoder<'a, T> {
+    pub fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            pos: 0,
+            state: State::Length,
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+            ctx: DecodeContext::default(),
+            _message: PhantomData,
+        }
+    }
+
+    /// Rejects frames longer than `len` bytes before buffering them.
+    #[must_use]
+    pub const fn max_frame_len(mut self, len: usize) -> Self {
+        self.max_frame_len = len;
+        self
+    }
+
+    /// Decodes every message with `ctx`.
+    #[must_use]
+    pub const fn with_context(mut self, ctx: DecodeContext<'a>) -> Self {
+        self.ctx = ctx;
+        self
+    }
+
+    /// Buffers `chunk` and returns the first message it completes.
+    ///
+    /// A chunk can complete several frames; the messages after the first are returned by
+    /// [`decode_next`](Self::decode_next).
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of decoding a completed frame, or of a malformed or oversized length.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<Option<T>, DecodeError> {
+        self.compact();
+        self.buf.extend_from_slice(chunk);
+        self.decode_next()
+    }
+
+    /// Returns the next message already buffered, or `None` once more bytes are needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of decoding a completed frame, or of a malformed or oversized length.
+    pub fn decode_next(&mut self) -> Result<Option<T>, DecodeError> {
+        if self.state == State::Length {
+            let Some((header, len)) = self.read_length()? else {
+                return Ok(None);
+            };
+            self.state = State::Body { header, len };
+        }
+        let State::Body { header, len } = self.state else {
+            return Ok(None);
+        };
+        if self.buffered_len() < header + len {
+            return Ok(None);
+        }
+
+        let start = self.pos + header;
+        let message = T::decode(&self.buf[start..start + len], self.ctx);
+        self.pos = start + len;
+        self.state = State::Length;
+        message.map(Some)
+    }
+
+    /// Bytes received but not yet returned as messages, including a partial frame.
+    pub const fn buffered_len(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    /// Whether no bytes of an unfinished frame are buffered.
+    pub fn is_idle(&self) -> bool {
+        self.state == State::Length && self.buffered_len() == 0
+    }
+
+    /// Drops buffered bytes and waits for the start of a new frame.
+    pub fn reset(&mut self) {
+        self.buf.clear();
+        self.pos = 0;
+        self.state = State::Length;
+    }
+
+    /// Reads the size of the next frame's length and message once the whole length is buffered.
+    fn read_length(&mut self) -> Result<Option<(usize, usize)>, DecodeError> {
+        let pending = &self.buf[self.pos..];
+        let Some((len, header)) = peek_len(pending) else {
+            if pending.len() < MAX_HEADER_LEN {
+                return Ok(None);
+            }
+            self.reset();
+            return Err(DecodeError::new("invalid frame length"));
+        };
+        if len > self.max_frame_len {
+            let max = self.max_frame_len;
+            self.reset();
+            return Err(DecodeError::new(format!("frame of {len} bytes exceeds the limit of {max}")));
+        }
+        Ok(Some((header, len)))
+    }
+
+    /// Moves the unread bytes to the front once the consumed prefix outgrows them.
+    fn compact(&mut self) {
+        if self.pos == self.buf.len() {
+            self.buf.clear();
+            self.pos = 0;
+        } else if self.pos > self.buf.len() / 2 {
+            self.buf.drain(..self.pos);
+            self.pos = 0;
+        }
+    }
+}
+
+impl<T: ProtoDecode> Default for Decoder<'_, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
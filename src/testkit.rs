@@ -0,0 +1,317 @@
+//! Schema-driven random messages.
+//!
+//! [`arbitrary`] walks the field metadata collected by `build-schemas`, writes a random but
+//! well-formed wire encoding and decodes it into the requested type, so every generated value
+//! went through the same decode path as production traffic. Use it to seed fuzz corpora
+//! ([`arbitrary_bytes`]) or to drive load tests with varied payloads.
+//!
+//! Integers are kept inside the range of the Rust field type (`u8`, `i16`, `NonZeroU32`, ...),
+//! strings are ASCII, enums only take declared values and fixed-size arrays get their exact
+//! length. Types with custom conversions or validators may still reject a generated value;
+//! that surfaces as the returned `DecodeError`.
+
+pub mod golden;
+/// Only available when "transcode" feature is enabled as well
+#[cfg(feature = "transcode")]
+pub mod vectors;
+
+use rand::Rng;
+use rand::RngExt;
+
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::ProtoDecode;
+use crate::alloc::vec::Vec;
+use crate::encoding::WireType;
+use crate::encoding::encode_key;
+use crate::encoding::encode_varint;
+use crate::schemas::Field;
+use crate::schemas::ProtoEntry;
+use crate::schemas::ProtoIdent;
+use crate::schemas::ProtoIdentifiable;
+use crate::schemas::ProtoLabel;
+use crate::schemas::ProtoType;
+use crate::schemas::Variant;
+use crate::schemas::schema_for;
+
+/// Size and depth controls for generated messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArbitraryConfig {
+    /// Levels of nested messages below the top-level one. Message fields past the budget are
+    /// left unset.
+    pub depth_budget: usize,
+    /// Upper bound for repeated fields, maps, strings and bytes.
+    pub max_len: usize,
+}
+
+impl Default for ArbitraryConfig {
+    fn default() -> Self {
+        Self {
+            depth_budget: 4,
+            max_len: 8,
+        }
+    }
+}
+
+impl ArbitraryConfig {
+    pub const fn new(depth_budget: usize) -> Self {
+        Self { depth_budget, max_len: 8 }
+    }
+
+    #[must_use]
+    pub const fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+}
+
+/// Random instance of `T` with at most `depth_budget` levels of nested messages.
+///
+/// # Errors
+///
+/// Returns the error of `T`'s decoder if it rejects the generated encoding.
+pub fn arbitrary<T, R>(rng: &mut R, depth_budget: usize) -> Result<T, DecodeError>
+where
+    T: ProtoDecode + ProtoIdentifiable,
+    R: Rng + ?Sized,
+{
+    arbitrary_with(rng, &ArbitraryConfig::new(depth_budget))
+}
+
+/// [`arbitrary`] with explicit size controls.
+///
+/// # Errors
+///
+/// Returns the error of `T`'s decoder if it rejects the generated encoding.
+pub fn arbitrary_with<T, R>(rng: &mut R, config: &ArbitraryConfig) -> Result<T, DecodeError>
+where
+    T: ProtoDecode + ProtoIdentifiable,
+    R: Rng + ?Sized,
+{
+    let bytes = arbitrary_bytes::<T, R>(rng, config);
+    T::decode(bytes.as_slice(), DecodeContext::default())
+}
+
+/// Random wire encoding of a `T` message, without decoding it.
+pub fn arbitrary_bytes<T, R>(rng: &mut R, config: &ArbitraryConfig) -> Vec<u8>
+where
+    T: ProtoIdentifiable,
+    R: Rng + ?Sized,
+{
+    let mut buf = Vec::new();
+    Generator { rng, config }.message(&T::PROTO_IDENT, config.depth_budget, &mut buf);
+    buf
+}
+
+struct Generator<'a, R: ?Sized> {
+    rng: &'a mut R,
+    config: &'a ArbitraryConfig,
+}
+
+impl<R: Rng + ?Sized> Generator<'_, R> {
+    fn message(&mut self, ident: &ProtoIdent, depth: usize, buf: &mut Vec<u8>) {
+        match schema_for(ident).map(|schema| schema.content) {
+            Some(ProtoEntry::Struct { fields }) => {
+                for field in fields {
+                    self.field(field, depth, buf);
+                }
+            }
+            Some(ProtoEntry::ComplexEnum { variants }) => self.oneof(variants, depth, buf),
+            _ => {}
+        }
+    }
+
+    fn oneof(&mut self, variants: &[&Variant], depth: usize, buf: &mut Vec<u8>) {
+        // Only unit variants avoid nesting once the budget is spent.
+        let candidates: Vec<usize> = (0..variants.len()).filter(|&idx| depth > 0 || variants[idx].fields.is_empty()).collect();
+        if candidates.is_empty() {
+            return;
+        }
+        let idx = candidates[self.rng.random_range(0..candidates.len())];
+        let variant = variants[idx];
+        let tag = u32::try_from(idx + 1).unwrap_or(u32::MAX);
+        match variant.fields {
+            [] => write_len_delimited(tag, &[], buf),
+            [field] if field.name.is_none() => self.value(tag, &field.proto_ident, depth, buf),
+            fields => {
+                let mut nested = Vec::new();
+                for field in fields {
+                    self.field(field, depth - 1, &mut nested);
+                }
+                write_len_delimited(tag, &nested, buf);
+            }
+        }
+    }
+
+    fn field(&mut self, field: &Field, depth: usize, buf: &mut Vec<u8>) {
+        let array_len = field.array_len.and_then(|len| len.parse::<usize>().ok());
+        if field.is_bytes() {
+            if field.proto_label == ProtoLabel::Optional && self.rng.random_bool(0.5) {
+                return;
+            }
+            let len = array_len.unwrap_or_else(|| self.len());
+            let bytes: Vec<u8> = (0..len).map(|_| self.rng.random()).collect();
+            write_len_delimited(field.tag, &bytes, buf);
+            return;
+        }
+        if let ProtoType::Map { key, value } = field.proto_ident.proto_type {
+            self.map(field, *key, *value, depth, buf);
+            return;
+        }
+        match field.proto_label {
+            ProtoLabel::Optional => {
+                if self.rng.random_bool(0.5) {
+                    self.value(field.tag, &field.proto_ident, depth, buf);
+                }
+            }
+            ProtoLabel::Repeated => {
+                let count = array_len.unwrap_or_else(|| self.len());
+                self.repeated(field, count, depth, buf);
+            }
+            ProtoLabel::None => self.value(field.tag, &field.proto_ident, depth, buf),
+        }
+    }
+
+    fn repeated(&mut self, field: &Field, count: usize, depth: usize, buf: &mut Vec<u8>) {
+        let elem = field.array_elem.unwrap_or(field.proto_ident);
+        if elem.proto_type.is_packable() {
+            let mut packed = Vec::new();
+            for _ in 0..count {
+                // Array elements carry the proto name; the field ident keeps the Rust one.
+                self.scalar(elem.proto_type, field.proto_ident.name, &mut packed);
+            }
+            write_len_delimited(field.tag, &packed, buf);
+        } else {
+            for _ in 0..count {
+                self.value(field.tag, &elem, depth, buf);
+            }
+        }
+    }
+
+    fn map(&mut self, field: &Field, key: ProtoType, value: ProtoType, depth: usize, buf: &mut Vec<u8>) {
+        let (key_ident, value_ident) = field.map_idents();
+        if matches!(value, ProtoType::Message(_)) && depth == 0 {
+            return;
+        }
+        for _ in 0..self.len() {
+            let mut entry = Vec::new();
+            match key_ident {
+                Some(ident) => self.value(1, &ident, depth, &mut entry),
+                None => self.tagged_scalar(1, key, "", &mut entry),
+            }
+            match value_ident {
+                Some(ident) => self.value(2, &ident, depth, &mut entry),
+                None => self.tagged_scalar(2, value, "", &mut entry),
+            }
+            write_len_delimited(field.tag, &entry, buf);
+        }
+    }
+
+    /// One occurrence of a field of type `ident` under `tag`.
+    fn value(&mut self, tag: u32, ident: &ProtoIdent, depth: usize, buf: &mut Vec<u8>) {
+        let ProtoType::Message(_) = ident.proto_type else {
+            self.tagged_scalar(tag, ident.proto_type, ident.name, buf);
+            return;
+        };
+        match schema_for(ident).map(|schema| schema.content) {
+            Some(ProtoEntry::SimpleEnum { variants }) if !variants.is_empty() => {
+                let variant = variants[self.rng.random_range(0..variants.len())];
+                encode_key(tag, WireType::Varint, buf);
+                #[allow(clippy::cast_sign_loss)]
+                encode_varint(i64::from(variant.discriminant.unwrap_or_default()) as u64, buf);
+            }
+            Some(ProtoEntry::Struct { .. } | ProtoEntry::ComplexEnum { .. }) if depth > 0 => {
+                let mut nested = Vec::new();
+                self.message(ident, depth - 1, &mut nested);
+                write_len_delimited(tag, &nested, buf);
+            }
+            _ => {}
+        }
+    }
+
+    fn tagged_scalar(&mut self, tag: u32, ty: ProtoType, rust_name: &str, buf: &mut Vec<u8>) {
+        let wire_type = match ty {
+            ProtoType::Fixed32 | ProtoType::Sfixed32 | ProtoType::Float => WireType::ThirtyTwoBit,
+            ProtoType::Fixed64 | ProtoType::Sfixed64 | ProtoType::Double => WireType::SixtyFourBit,
+            ProtoType::String | ProtoType::Bytes => WireType::LengthDelimited,
+            ProtoType::Optional(inner) | ProtoType::Repeated(inner) => return self.tagged_scalar(tag, *inner, rust_name, buf),
+            ProtoType::Message(_) | ProtoType::Map { .. } | ProtoType::None => return,
+            _ => WireType::Varint,
+        };
+        encode_key(tag, wire_type, buf);
+        self.scalar(ty, rust_name, buf);
+    }
+
+    /// Scalar payload without its key.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn scalar(&mut self, ty: ProtoType, rust_name: &str, buf: &mut Vec<u8>) {
+        match ty {
+            ProtoType::Bool => buf.push(u8::from(self.rng.random_bool(0.5))),
+            ProtoType::Int32 | ProtoType::Int64 | ProtoType::Uint32 | ProtoType::Uint64 | ProtoType::Enum => {
+                let value = self.integer(ty, rust_name);
+                encode_varint(value as i64 as u64, buf);
+            }
+            ProtoType::Sint32 | ProtoType::Sint64 => {
+                let value = self.integer(ty, rust_name) as i64;
+                encode_varint(((value << 1) ^ (value >> 63)) as u64, buf);
+            }
+            ProtoType::Fixed32 | ProtoType::Sfixed32 => {
+                buf.extend_from_slice(&(self.integer(ty, rust_name) as u32).to_le_bytes());
+            }
+            ProtoType::Fixed64 | ProtoType::Sfixed64 => {
+                buf.extend_from_slice(&(self.integer(ty, rust_name) as u64).to_le_bytes());
+            }
+            ProtoType::Float => buf.extend_from_slice(&self.rng.random_range(-1.0e6f32..1.0e6).to_le_bytes()),
+            ProtoType::Double => buf.extend_from_slice(&self.rng.random_range(-1.0e12f64..1.0e12).to_le_bytes()),
+            ProtoType::String => {
+                let len = self.len();
+                let text: Vec<u8> = (0..len).map(|_| self.rng.random_range(b' '..=b'~')).collect();
+                encode_varint(text.len() as u64, buf);
+                buf.extend_from_slice(&text);
+            }
+            ProtoType::Bytes => {
+                let len = self.len();
+                encode_varint(len as u64, buf);
+                buf.extend((0..len).map(|_| self.rng.random::<u8>()));
+            }
+            ProtoType::Optional(inner) | ProtoType::Repeated(inner) => self.scalar(*inner, rust_name, buf),
+            ProtoType::Message(_) | ProtoType::Map { .. } | ProtoType::None => {}
+        }
+    }
+
+    fn integer(&mut self, ty: ProtoType, rust_name: &str) -> i128 {
+        let (min, max) = integer_range(ty, rust_name);
+        self.rng.random_range(min..=max)
+    }
+
+    fn len(&mut self) -> usize {
+        self.rng.random_range(0..=self.config.max_len)
+    }
+}
+
+fn write_len_delimited(tag: u32, payload: &[u8], buf: &mut Vec<u8>) {
+    encode_key(tag, WireType::LengthDelimited, buf);
+    encode_varint(payload.len() as u64, buf);
+    buf.extend_from_slice(payload);
+}
+
+/// Value range of an integer field, narrowed to its Rust type when the schema names one.
+fn integer_range(ty: ProtoType, rust_name: &str) -> (i128, i128) {
+    let name = rust_name.rsplit("::").next().unwrap_or(rust_name);
+    let name = name.strip_prefix("Atomic").unwrap_or(name);
+    let (non_zero, name) = name.strip_prefix("NonZero").map_or((false, name), |rest| (true, rest));
+    let (min, max) = match name.to_ascii_lowercase().as_str() {
+        "u8" => (0, i128::from(u8::MAX)),
+        "u16" => (0, i128::from(u16::MAX)),
+        "i8" => (i128::from(i8::MIN), i128::from(i8::MAX)),
+        "i16" => (i128::from(i16::MIN), i128::from(i16::MAX)),
+        "char" => (0x20, 0x7e),
+        _ => match ty {
+            ProtoType::Int32 | ProtoType::Sint32 | ProtoType::Sfixed32 | ProtoType::Enum => (i128::from(i32::MIN), i128::from(i32::MAX)),
+            ProtoType::Uint32 | ProtoType::Fixed32 => (0, i128::from(u32::MAX)),
+            ProtoType::Uint64 | ProtoType::Fixed64 => (0, i128::from(u64::MAX)),
+            _ => (i128::from(i64::MIN), i128::from(i64::MAX)),
+        },
+    };
+    if non_zero && min == 0 { (1, max) } else { (min, max) }
+}
@@ -0,0 +1,203 @@
+//! Golden-file wire-format checks.
+//!
+//! Each checked type gets one deterministic sample (generated with [`arbitrary_with`] from a seed
+//! derived from the type's proto name) whose encoding is stored as `<package>.<Name>.bin`. The
+//! first run writes the file; later runs re-encode the sample and fail on any byte difference,
+//! which catches tag, wire-type or ordering changes coming from derive updates.
+//!
+//! ```rust,ignore
+//! #[test]
+//! fn wire_format_is_stable() {
+//!     GoldenSet::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden"))
+//!         .check::<Order>()
+//!         .check::<Invoice>()
+//!         .finish();
+//! }
+//! ```
+//!
+//! `HashMap` fields encode in iteration order, so a file that differs only in entry order is
+//! accepted when it decodes back to the same sample.
+//!
+//! Set `PROTO_GOLDEN_UPDATE=1` to rewrite the files after an intended format change.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+use rand::SeedableRng;
+use rand::rngs::Xoshiro256PlusPlus;
+
+use super::ArbitraryConfig;
+use super::arbitrary_with;
+use crate::DecodeContext;
+use crate::ProtoDecode;
+use crate::ProtoEncode;
+use crate::ProtoExt;
+use crate::alloc::format;
+use crate::alloc::string::String;
+use crate::alloc::vec::Vec;
+use crate::schemas::ProtoIdentifiable;
+
+/// Whether golden files are rewritten instead of compared.
+/// Priority: `PROTO_GOLDEN_UPDATE` env var > default (false)
+pub fn should_update() -> bool {
+    matches!(
+        std::env::var("PROTO_GOLDEN_UPDATE").ok().as_deref(),
+        Some("1" | "true" | "True" | "TRUE")
+    )
+}
+
+/// File stem and sample seed for `T`, e.g. `shop.Order`.
+pub fn golden_name<T: ProtoIdentifiable>() -> String {
+    let ident = T::PROTO_IDENT;
+    if ident.proto_package_name.is_empty() {
+        String::from(ident.name)
+    } else {
+        format!("{}.{}", ident.proto_package_name, ident.name)
+    }
+}
+
+/// Deterministic sample encoding for `T`.
+///
+/// The sample depends only on the type's schema and proto name, so it is identical across runs,
+/// platforms and `rand` releases.
+pub fn golden_bytes<T>() -> Result<Vec<u8>, String>
+where
+    T: ProtoDecode + ProtoEncode + ProtoExt + ProtoIdentifiable,
+{
+    sample::<T>().map(|sample| sample.encode_to_vec())
+}
+
+pub(super) fn sample<T>() -> Result<T, String>
+where
+    T: ProtoDecode + ProtoIdentifiable,
+{
+    let name = golden_name::<T>();
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(fnv1a(name.as_bytes()));
+    arbitrary_with(&mut rng, &ArbitraryConfig::default()).map_err(|err| format!("{name}: sample does not decode: {err}"))
+}
+
+/// Collects golden checks for several types and reports every drift at once.
+#[derive(Debug)]
+pub struct GoldenSet {
+    dir: PathBuf,
+    update: bool,
+    written: Vec<String>,
+    failures: Vec<String>,
+}
+
+impl GoldenSet {
+    /// Golden files live in `dir`, which is created on first write.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            update: should_update(),
+            written: Vec::new(),
+            failures: Vec::new(),
+        }
+    }
+
+    /// Overrides `PROTO_GOLDEN_UPDATE`.
+    #[must_use]
+    pub const fn updating(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    /// Compares the sample encoding of `T` with its golden file, writing the file if it is missing.
+    #[must_use]
+    pub fn check<T>(mut self) -> Self
+    where
+        T: ProtoDecode + ProtoEncode + ProtoExt + ProtoIdentifiable + PartialEq,
+    {
+        let name = golden_name::<T>();
+        let sample = match sample::<T>() {
+            Ok(sample) => sample,
+            Err(failure) => {
+                self.failures.push(failure);
+                return self;
+            }
+        };
+        let bytes = sample.encode_to_vec();
+        let path = self.dir.join(format!("{name}.bin"));
+        match fs::read(&path) {
+            Ok(expected) if !self.update => {
+                let reordered = || {
+                    expected.len() == bytes.len()
+                        && T::decode(expected.as_slice(), DecodeContext::default()).is_ok_and(|golden| golden == sample)
+                };
+                if let Some(failure) = compare(&name, &expected, &bytes)
+                    && !reordered()
+                {
+                    self.failures.push(failure);
+                }
+            }
+            _ => match fs::create_dir_all(&self.dir).and_then(|()| fs::write(&path, &bytes)) {
+                Ok(()) => self.written.push(name),
+                Err(err) => self.failures.push(format!("{name}: cannot write {}: {err}", path.display())),
+            },
+        }
+        self
+    }
+
+    /// Types whose golden file was (re)written by this set.
+    pub fn written(&self) -> &[String] {
+        &self.written
+    }
+
+    /// Drift and I/O failures collected so far.
+    pub fn failures(&self) -> &[String] {
+        &self.failures
+    }
+
+    /// Panics with every collected failure.
+    pub fn finish(self) {
+        assert!(
+            self.failures.is_empty(),
+            "golden wire format drift in {} (rerun with PROTO_GOLDEN_UPDATE=1 if intended):\n{}",
+            self.dir.display(),
+            self.failures.join("\n")
+        );
+    }
+}
+
+fn compare(name: &str, expected: &[u8], actual: &[u8]) -> Option<String> {
+    let offset = expected.iter().zip(actual).position(|(a, b)| a != b);
+    let offset = match offset {
+        Some(offset) => offset,
+        None if expected.len() == actual.len() => return None,
+        None => expected.len().min(actual.len()),
+    };
+    let mut failure = format!(
+        "{name}: encoding differs at byte {offset} (golden {} bytes, now {} bytes)",
+        expected.len(),
+        actual.len()
+    );
+    let window = offset.saturating_sub(4)..offset + 12;
+    let _ = write!(failure, "\n  golden: {}", hex(expected, window.clone()));
+    let _ = write!(failure, "\n  now:    {}", hex(actual, window));
+    Some(failure)
+}
+
+fn hex(bytes: &[u8], range: core::ops::Range<usize>) -> String {
+    let end = range.end.min(bytes.len());
+    let start = range.start.min(end);
+    let mut out = String::new();
+    for byte in &bytes[start..end] {
+        let _ = write!(out, "{byte:02x} ");
+    }
+    out.truncate(out.trim_end().len());
+    out
+}
+
+/// FNV-1a, used for seeds because it is fixed forever unlike `std`'s hashers.
+const fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+        i += 1;
+    }
+    hash
+}
@@ -0,0 +1,441 @@
+//! Cross-language test vectors.
+//!
+//! A [`VectorSet`] writes every added message three ways under `<dir>/<package>.<Name>/`: the
+//! proto-rs encoding (`<case>.bin`), its proto3 JSON form (`<case>.json`) and protobuf text
+//! format (`<case>.txt`), plus a `manifest.json` listing every case. Go, Java or C++ suites
+//! compiled from the same `.proto` files parse the JSON or text, encode it with their own
+//! runtime and compare bytes (or decode the `.bin` and compare messages), which pins byte-level
+//! compatibility with proto-rs encoders:
+//!
+//! ```rust,ignore
+//! VectorSet::new("target/vectors")
+//!     .add("two_items", &order)?
+//!     .add("card", &Payment::Card { last4: 4242, network: "visa".into() })?
+//!     .add_sample::<Order>()?
+//!     .finish()?;
+//! ```
+//!
+//! JSON follows [`Transcoder`](crate::transcode::Transcoder). Text files start with the
+//! `# proto-message:` header read by text format tooling; strings and bytes escape everything
+//! outside printable ASCII as octal, which every text format parser accepts.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+use bytes::Buf;
+use serde_json::Value;
+use serde_json::json;
+
+use super::golden::sample;
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::ProtoDecode;
+use crate::ProtoEncode;
+use crate::ProtoExt;
+use crate::alloc::format;
+use crate::alloc::string::String;
+use crate::alloc::string::ToString;
+use crate::alloc::vec::Vec;
+use crate::encoding::WireType;
+use crate::encoding::decode_key;
+use crate::encoding::decode_varint;
+use crate::encoding::skip_field;
+use crate::schemas::Field;
+use crate::schemas::ProtoEntry;
+use crate::schemas::ProtoIdent;
+use crate::schemas::ProtoIdentifiable;
+use crate::schemas::ProtoLabel;
+use crate::schemas::ProtoType;
+use crate::schemas::Variant;
+use crate::schemas::schema_for;
+use crate::transcode::TranscodeError;
+use crate::transcode::Transcoder;
+use crate::transcode::field_name;
+use crate::transcode::length_delimited;
+use crate::transcode::wire_type_of;
+use crate::transcode::zigzag;
+
+/// Case name used by [`VectorSet::add_sample`].
+pub const SAMPLE_CASE: &str = "sample";
+
+/// One written vector, as listed in `manifest.json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vector {
+    /// Full proto name of the message, e.g. `shop.Order`; also the directory of its files.
+    pub type_name: String,
+    pub case: String,
+}
+
+/// Writes `(bin, json, txt)` triples of messages for other protobuf runtimes.
+#[derive(Debug)]
+pub struct VectorSet {
+    dir: PathBuf,
+    vectors: Vec<Vector>,
+}
+
+impl VectorSet {
+    /// Vectors are written under `dir`, which is created on first write.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            vectors: Vec::new(),
+        }
+    }
+
+    /// Writes `value` as case `case` of its message type, replacing an earlier case of that name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TranscodeError::Invalid`] if `case` is not a plain file name (ASCII letters,
+    /// digits, `_`, `-` and `.`), [`TranscodeError::UnknownMessage`] if `T` has no registered
+    /// schema, and any error of rendering or writing the files.
+    pub fn add<T>(mut self, case: &str, value: &T) -> Result<Self, TranscodeError>
+    where
+        T: ProtoEncode + ProtoExt + ProtoIdentifiable,
+    {
+        let valid =
+            !case.is_empty() && !case.starts_with('.') && case.bytes().all(|byte| byte.is_ascii_alphanumeric() || b"_-.".contains(&byte));
+        if !valid {
+            return Err(TranscodeError::Invalid {
+                path: String::new(),
+                reason: format!("`{case}` is not a valid vector case name"),
+            });
+        }
+        let type_name = T::PROTO_IDENT.full_name();
+        let bytes = value.encode_to_vec();
+        let json = Transcoder::of::<T>()?.binary_to_json(&bytes)?;
+        let text = text_format(&T::PROTO_IDENT, &bytes)?;
+
+        let dir = self.dir.join(&type_name);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(format!("{case}.bin")), &bytes)?;
+        fs::write(dir.join(format!("{case}.json")), serde_json::to_string_pretty(&json)? + "\n")?;
+        fs::write(dir.join(format!("{case}.txt")), text)?;
+
+        self.vectors.retain(|vector| vector.type_name != type_name || vector.case != case);
+        self.vectors.push(Vector {
+            type_name,
+            case: case.to_string(),
+        });
+        Ok(self)
+    }
+
+    /// Writes the deterministic golden sample of `T` (see [`golden_bytes`](super::golden::golden_bytes))
+    /// as case [`SAMPLE_CASE`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TranscodeError::Decode`] if the sample does not decode, and the errors of
+    /// [`add`](Self::add).
+    pub fn add_sample<T>(self) -> Result<Self, TranscodeError>
+    where
+        T: ProtoDecode + ProtoEncode + ProtoExt + ProtoIdentifiable,
+    {
+        let value = sample::<T>().map_err(|failure| TranscodeError::Decode(DecodeError::new(failure)))?;
+        self.add(SAMPLE_CASE, &value)
+    }
+
+    /// Vectors written by this set, in the order they were added.
+    pub fn vectors(&self) -> &[Vector] {
+        &self.vectors
+    }
+
+    /// Writes `manifest.json` and returns its path.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of writing the manifest.
+    pub fn finish(self) -> Result<PathBuf, TranscodeError> {
+        let vectors: Vec<Value> = self
+            .vectors
+            .iter()
+            .map(|vector| {
+                let file = |ext: &str| format!("{}/{}.{ext}", vector.type_name, vector.case);
+                json!({
+                    "type": vector.type_name,
+                    "case": vector.case,
+                    "bin": file("bin"),
+                    "json": file("json"),
+                    "txt": file("txt"),
+                })
+            })
+            .collect();
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join("manifest.json");
+        fs::write(&path, serde_json::to_string_pretty(&json!({ "vectors": vectors }))? + "\n")?;
+        Ok(path)
+    }
+}
+
+/// Protobuf text format of `bytes`, an `ident` message, with its `proto-message` header.
+fn text_format(ident: &ProtoIdent, bytes: &[u8]) -> Result<String, TranscodeError> {
+    let mut writer = TextWriter {
+        out: format!("# proto-message: {}\n", ident.full_name()),
+        depth: 0,
+    };
+    writer.message(ident, bytes, DecodeContext::default())?;
+    Ok(writer.out)
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Scalar(ProtoType),
+    Enum(&'static [&'static Variant]),
+    Message(ProtoIdent),
+}
+
+fn kind(ident: &ProtoIdent) -> Result<Kind, TranscodeError> {
+    match ident.proto_type {
+        ProtoType::Message(_) => match schema_for(ident).map(|schema| schema.content) {
+            Some(ProtoEntry::SimpleEnum { variants }) => Ok(Kind::Enum(variants)),
+            Some(ProtoEntry::Struct { .. } | ProtoEntry::ComplexEnum { .. }) => Ok(Kind::Message(*ident)),
+            _ => Err(TranscodeError::UnknownMessage(ident.name.to_string())),
+        },
+        ProtoType::Optional(inner) | ProtoType::Repeated(inner) => Ok(Kind::Scalar(*inner)),
+        ty => Ok(Kind::Scalar(ty)),
+    }
+}
+
+struct TextWriter {
+    out: String,
+    depth: usize,
+}
+
+impl TextWriter {
+    fn message(&mut self, ident: &ProtoIdent, bytes: &[u8], ctx: DecodeContext) -> Result<(), TranscodeError> {
+        ctx.limit_reached()?;
+        match schema_for(ident).map(|schema| schema.content) {
+            Some(ProtoEntry::Struct { fields }) => self.fields(fields, bytes, ctx),
+            Some(ProtoEntry::ComplexEnum { variants }) => {
+                let mut buf = bytes;
+                let start = self.out.len();
+                while buf.has_remaining() {
+                    let (tag, wire_type) = decode_key(&mut buf)?;
+                    let Some(variant) = (tag as usize).checked_sub(1).and_then(|idx| variants.get(idx)) else {
+                        skip_field(wire_type, tag, &mut buf, ctx)?;
+                        continue;
+                    };
+                    // Only the last variant on the wire is set.
+                    self.out.truncate(start);
+                    let name = variant.oneof_field_name();
+                    match variant.fields {
+                        [field] if field.name.is_none() => self.value(&name, kind(&field.proto_ident)?, wire_type, &mut buf, ctx)?,
+                        fields => {
+                            let nested = length_delimited(wire_type, &mut buf)?;
+                            self.open(&name);
+                            self.fields(fields, nested, ctx.enter_recursion())?;
+                            self.close();
+                        }
+                    }
+                }
+                Ok(())
+            }
+            _ => Err(TranscodeError::UnknownMessage(ident.name.to_string())),
+        }
+    }
+
+    fn fields(&mut self, fields: &[&Field], mut buf: &[u8], ctx: DecodeContext) -> Result<(), TranscodeError> {
+        while buf.has_remaining() {
+            let (tag, wire_type) = decode_key(&mut buf)?;
+            let Some((idx, field)) = fields.iter().enumerate().find(|(_, field)| field.tag == tag) else {
+                skip_field(wire_type, tag, &mut buf, ctx)?;
+                continue;
+            };
+            let name = field_name(field, idx);
+            if field.is_bytes() {
+                self.value(&name, Kind::Scalar(ProtoType::Bytes), wire_type, &mut buf, ctx)?;
+            } else if let ProtoType::Map { key, value } = field.proto_ident.proto_type {
+                let (key_ident, value_ident) = field.map_idents();
+                let key_kind = key_ident.map_or(Ok(Kind::Scalar(*key)), |ident| kind(&ident))?;
+                let value_kind = value_ident.map_or(Ok(Kind::Scalar(*value)), |ident| kind(&ident))?;
+                let mut entry = length_delimited(wire_type, &mut buf)?;
+                self.open(&name);
+                while entry.has_remaining() {
+                    let (tag, wire_type) = decode_key(&mut entry)?;
+                    match tag {
+                        1 => self.value("key", key_kind, wire_type, &mut entry, ctx)?,
+                        2 => self.value("value", value_kind, wire_type, &mut entry, ctx)?,
+                        _ => skip_field(wire_type, tag, &mut entry, ctx)?,
+                    }
+                }
+                self.close();
+            } else if field.proto_label == ProtoLabel::Repeated {
+                let elem = kind(&field.array_elem.unwrap_or(field.proto_ident))?;
+                let packed = match elem {
+                    Kind::Scalar(ty) => ty.is_packable(),
+                    Kind::Enum(_) => true,
+                    Kind::Message(_) => false,
+                };
+                if packed && wire_type == WireType::LengthDelimited {
+                    let mut packed = length_delimited(wire_type, &mut buf)?;
+                    let item_wire_type = match elem {
+                        Kind::Scalar(ty) => wire_type_of(ty),
+                        _ => WireType::Varint,
+                    };
+                    while packed.has_remaining() {
+                        self.value(&name, elem, item_wire_type, &mut packed, ctx)?;
+                    }
+                } else {
+                    self.value(&name, elem, wire_type, &mut buf, ctx)?;
+                }
+            } else {
+                self.value(&name, kind(&field.proto_ident)?, wire_type, &mut buf, ctx)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    fn value(&mut self, name: &str, kind: Kind, wire_type: WireType, buf: &mut &[u8], ctx: DecodeContext) -> Result<(), TranscodeError> {
+        let ty = match kind {
+            Kind::Message(ident) => {
+                let nested = length_delimited(wire_type, buf)?;
+                self.open(name);
+                self.message(&ident, nested, ctx.enter_recursion())?;
+                self.close();
+                return Ok(());
+            }
+            Kind::Enum(variants) => {
+                if wire_type != WireType::Varint {
+                    return Err(DecodeError::new("invalid wire type").into());
+                }
+                let number = decode_varint(buf)? as i32;
+                let variant = variants.iter().find(|variant| variant.discriminant.unwrap_or_default() == number);
+                self.line(
+                    name,
+                    &variant.map_or_else(|| number.to_string(), |variant| variant.name.to_string()),
+                );
+                return Ok(());
+            }
+            Kind::Scalar(ty) => ty,
+        };
+        if wire_type != wire_type_of(ty) {
+            return Err(DecodeError::new("invalid wire type").into());
+        }
+        let fixed = |buf: &mut &[u8], len: usize| {
+            if buf.remaining() < len {
+                Err(DecodeError::new("buffer underflow"))
+            } else {
+                Ok(())
+            }
+        };
+        let value = match ty {
+            ProtoType::Bool => (decode_varint(buf)? != 0).to_string(),
+            ProtoType::Int32 | ProtoType::Enum => (decode_varint(buf)? as i32).to_string(),
+            ProtoType::Uint32 => (decode_varint(buf)? as u32).to_string(),
+            ProtoType::Int64 => (decode_varint(buf)? as i64).to_string(),
+            ProtoType::Uint64 => decode_varint(buf)?.to_string(),
+            ProtoType::Sint32 => (zigzag(decode_varint(buf)?) as i32).to_string(),
+            ProtoType::Sint64 => zigzag(decode_varint(buf)?).to_string(),
+            ProtoType::Fixed32 => {
+                fixed(buf, 4)?;
+                buf.get_u32_le().to_string()
+            }
+            ProtoType::Sfixed32 => {
+                fixed(buf, 4)?;
+                buf.get_i32_le().to_string()
+            }
+            ProtoType::Fixed64 => {
+                fixed(buf, 8)?;
+                buf.get_u64_le().to_string()
+            }
+            ProtoType::Sfixed64 => {
+                fixed(buf, 8)?;
+                buf.get_i64_le().to_string()
+            }
+            ProtoType::Float => {
+                fixed(buf, 4)?;
+                let value = buf.get_f32_le();
+                if value.is_finite() {
+                    format!("{value:?}")
+                } else {
+                    non_finite(f64::from(value))
+                }
+            }
+            ProtoType::Double => {
+                fixed(buf, 8)?;
+                let value = buf.get_f64_le();
+                if value.is_finite() {
+                    format!("{value:?}")
+                } else {
+                    non_finite(value)
+                }
+            }
+            ProtoType::String => {
+                let payload = length_delimited(wire_type, buf)?;
+                core::str::from_utf8(payload).map_err(|_| DecodeError::new("invalid string value: data is not UTF-8 encoded"))?;
+                quote(payload)
+            }
+            ProtoType::Bytes => quote(length_delimited(wire_type, buf)?),
+            _ => {
+                return Err(TranscodeError::Invalid {
+                    path: name.to_string(),
+                    reason: "unsupported field type".to_string(),
+                });
+            }
+        };
+        self.line(name, &value);
+        Ok(())
+    }
+
+    fn indent(&mut self) {
+        for _ in 0..self.depth {
+            self.out.push_str("  ");
+        }
+    }
+
+    fn line(&mut self, name: &str, value: &str) {
+        self.indent();
+        let _ = writeln!(self.out, "{name}: {value}");
+    }
+
+    fn open(&mut self, name: &str) {
+        self.indent();
+        let _ = writeln!(self.out, "{name} {{");
+        self.depth += 1;
+    }
+
+    fn close(&mut self) {
+        self.depth -= 1;
+        // Empty messages stay on one line.
+        if self.out.ends_with(" {\n") {
+            self.out.truncate(self.out.len() - 1);
+            self.out.push_str("}\n");
+            return;
+        }
+        self.indent();
+        self.out.push_str("}\n");
+    }
+}
+
+fn non_finite(value: f64) -> String {
+    if value.is_nan() {
+        "nan".to_string()
+    } else if value > 0.0 {
+        "inf".to_string()
+    } else {
+        "-inf".to_string()
+    }
+}
+
+/// Double-quoted text format literal, escaping everything outside printable ASCII.
+fn quote(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() + 2);
+    out.push('"');
+    for &byte in bytes {
+        match byte {
+            b'"' => out.push_str("\\\""),
+            b'\\' => out.push_str("\\\\"),
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            b' '..=b'~' => out.push(char::from(byte)),
+            _ => {
+                let _ = write!(out, "\\{byte:03o}");
+            }
+        }
+    }
+    out.push('"');
+    out
+}
@@ -1,12 +1,40 @@
 use tonic::Status;
+use tonic::codec::BufferSettings;
 use tonic::codec::Codec;
 use tonic::codec::DecodeBuf;
 use tonic::codec::Decoder;
 use tonic::codec::EncodeBuf;
 use tonic::codec::Encoder;
+pub mod auth;
+pub mod bus;
+pub mod cache;
+#[cfg(feature = "capture")]
+pub mod capture;
+pub mod dedup;
+mod descriptor;
+pub mod dispatch;
+#[cfg(all(feature = "build-schemas", not(target_arch = "wasm32")))]
+pub mod forward;
+#[cfg(feature = "grpc-web")]
+pub mod grpc_web;
+#[cfg(feature = "http3")]
+pub mod http3;
+pub mod limit;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod registry;
 mod req;
 mod resp;
+pub mod response_stream;
+pub mod shutdown;
+pub mod sizes;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod transport;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+use bytes::Buf;
 use bytes::BufMut;
+use bytes::Bytes;
+pub use descriptor::MethodDescriptor;
 pub use req::ProtoRequest;
 pub use resp::ProtoResponse;
 pub use resp::map_proto_response;
@@ -14,11 +42,14 @@ pub use resp::map_proto_stream_result;
 
 use crate::ProtoDecode;
 use crate::ProtoEncode;
+use crate::ProtoError;
 use crate::ProtoExt;
 use crate::alloc::boxed::Box;
 use crate::alloc::sync::Arc;
 use crate::coders::AsBytes;
 use crate::coders::BytesMode;
+#[cfg(feature = "capture")]
+use crate::coders::CodecSide;
 use crate::coders::ProtoCodec;
 use crate::coders::ProtoDecoder;
 use crate::coders::ProtoEncoder;
@@ -26,6 +57,27 @@ use crate::coders::SunByRef;
 use crate::coders::SunByRefDeref;
 use crate::coders::SunByVal;
 use crate::encoding::DecodeContext;
+use crate::traits::buffer::BufferStrategy;
+
+/// tonic's default, which `BufferSettings` does not expose.
+const DEFAULT_YIELD_THRESHOLD: usize = 32 * 1024;
+
+/// Messages whose [`encoded_len_hint`](ProtoEncode::encoded_len_hint) is at most this many bytes
+/// are encoded on the stack with [`encode_to_array`](ProtoEncode::encode_to_array) instead of a
+/// heap-allocated writer, which covers acks, pings and other tiny responses.
+pub const STACK_ENCODE_THRESHOLD: usize = 256;
+
+#[inline]
+fn encode_message<T: ProtoEncode + ProtoExt>(item: &T, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+    // A hint that undercounts (a custom `size_hint`) overflows the array; the writer path still fits.
+    if item.encoded_len_hint().is_some_and(|len| len <= STACK_ENCODE_THRESHOLD)
+        && let Ok(bytes) = item.encode_to_array::<STACK_ENCODE_THRESHOLD>()
+    {
+        dst.put_slice(bytes.as_slice());
+        return Ok(());
+    }
+    ProtoEncode::encode(item, dst).map_err(|e| ProtoError::Encode(e).into())
+}
 
 impl<Encode, Decode, Mode> Codec for ProtoCodec<Encode, Decode, Mode>
 where
@@ -40,16 +92,42 @@ where
     type Decoder = ProtoDecoder<Decode>;
 
     fn encoder(&mut self) -> Self::Encoder {
-        ProtoEncoder::default()
+        let encoder = ProtoEncoder::accounted(self.accounting.map(|(route, side)| (route, side.encoded_kind())));
+        #[cfg(feature = "capture")]
+        let encoder = encoder.tapped(self.tap(CodecSide::encoded_kind));
+        encoder
     }
 
     fn decoder(&mut self) -> Self::Decoder {
-        ProtoDecoder::default()
+        let decoder = ProtoDecoder::accounted(self.accounting.map(|(route, side)| (route, side.decoded_kind())));
+        #[cfg(feature = "capture")]
+        let decoder = decoder.tapped(self.tap(CodecSide::decoded_kind));
+        decoder
+    }
+}
+
+#[cfg(feature = "capture")]
+impl<Encode, Decode, Mode> ProtoCodec<Encode, Decode, Mode> {
+    /// Capture target of the next encoder or decoder; the call number is shared by both.
+    fn tap(&mut self, kind: fn(CodecSide) -> sizes::MessageKind) -> Option<capture::Tap> {
+        let (route, side) = self.accounting.filter(|_| capture::is_active())?;
+        if self.call == 0 {
+            self.call = capture::next_call();
+        }
+        Some(capture::Tap {
+            route,
+            side,
+            kind: kind(side),
+            call: self.call,
+        })
     }
 }
 
 pub trait EncoderExt<T, Mode> {
     fn encode_sun(&mut self, item: T, dst: &mut EncodeBuf<'_>) -> Result<(), Status>;
+
+    /// Encodes `item` into a standalone buffer, for transports that bypass tonic's codec.
+    fn encode_bytes(&mut self, item: T) -> Result<Bytes, Status>;
 }
 
 impl<T, Mode> EncoderExt<T, Mode> for ProtoEncoder<T, BytesMode>
@@ -60,6 +138,10 @@ where
         dst.put_slice(item.as_bytes());
         Ok(())
     }
+
+    fn encode_bytes(&mut self, item: T) -> Result<Bytes, Status> {
+        Ok(Bytes::copy_from_slice(item.as_bytes()))
+    }
 }
 
 impl<T> EncoderExt<T, SunByVal> for ProtoEncoder<T, SunByVal>
@@ -67,7 +149,11 @@ where
     T: ProtoEncode + ProtoExt,
 {
     fn encode_sun(&mut self, item: T, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
-        ProtoEncode::encode(&item, dst).map_err(|e| Status::internal(format!("encode failed: {e}")))
+        encode_message(&item, dst)
+    }
+
+    fn encode_bytes(&mut self, item: T) -> Result<Bytes, Status> {
+        Ok(Bytes::from(item.encode_to_vec()))
     }
 }
 
@@ -76,7 +162,11 @@ where
     T: ProtoEncode + ProtoExt,
 {
     fn encode_sun(&mut self, item: T, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
-        ProtoEncode::encode(&item, dst).map_err(|e| Status::internal(format!("encode failed: {e}")))
+        encode_message(&item, dst)
+    }
+
+    fn encode_bytes(&mut self, item: T) -> Result<Bytes, Status> {
+        Ok(Bytes::from(item.encode_to_vec()))
     }
 }
 
@@ -85,7 +175,11 @@ where
     T: ProtoEncode + ProtoExt,
 {
     fn encode_sun(&mut self, item: Arc<T>, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
-        ProtoEncode::encode(item.as_ref(), dst).map_err(|e| Status::internal(format!("encode failed: {e}")))
+        encode_message(item.as_ref(), dst)
+    }
+
+    fn encode_bytes(&mut self, item: Arc<T>) -> Result<Bytes, Status> {
+        Ok(Bytes::from(item.as_ref().encode_to_vec()))
     }
 }
 
@@ -94,7 +188,11 @@ where
     T: ProtoEncode + ProtoExt,
 {
     fn encode_sun(&mut self, item: Box<T>, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
-        ProtoEncode::encode(item.as_ref(), dst).map_err(|e| Status::internal(format!("encode failed: {e}")))
+        encode_message(item.as_ref(), dst)
+    }
+
+    fn encode_bytes(&mut self, item: Box<T>) -> Result<Bytes, Status> {
+        Ok(Bytes::from(item.as_ref().encode_to_vec()))
     }
 }
 
@@ -107,7 +205,34 @@ where
 
     #[inline]
     fn encode(&mut self, item: T, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
-        <Self as EncoderExt<T, Mode>>::encode_sun(self, item, dst)
+        #[cfg(feature = "capture")]
+        if let Some(tap) = self.tap.filter(capture::Tap::is_on) {
+            let bytes = <Self as EncoderExt<T, Mode>>::encode_bytes(self, item)?;
+            tap.record(&bytes);
+            if let Some((route, kind)) = self.accounting.filter(|_| sizes::is_active()) {
+                sizes::record(route, kind, bytes.len());
+            }
+            dst.put_slice(&bytes);
+            return Ok(());
+        }
+        let Some((route, kind)) = self.accounting.filter(|_| sizes::is_active()) else {
+            return <Self as EncoderExt<T, Mode>>::encode_sun(self, item, dst);
+        };
+        let before = dst.remaining_mut();
+        <Self as EncoderExt<T, Mode>>::encode_sun(self, item, dst)?;
+        sizes::record(route, kind, before - dst.remaining_mut());
+        Ok(())
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        BufferStrategy::configured_global().map_or_else(BufferSettings::default, |strategy| {
+            let yield_threshold = if strategy.max_retained == usize::MAX {
+                DEFAULT_YIELD_THRESHOLD
+            } else {
+                strategy.max_retained
+            };
+            BufferSettings::new(strategy.initial_capacity, yield_threshold)
+        })
     }
 }
 
@@ -119,9 +244,23 @@ where
     type Error = Status;
 
     fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        if let Some((route, kind)) = self.accounting
+            && sizes::is_active()
+        {
+            sizes::record(route, kind, src.remaining());
+        }
+        #[cfg(feature = "capture")]
+        if let Some(tap) = self.tap.filter(capture::Tap::is_on) {
+            let bytes = src.copy_to_bytes(src.remaining());
+            tap.record(&bytes);
+            return match T::decode(bytes, DecodeContext::default()) {
+                Ok(msg) => Ok(Some(msg)),
+                Err(err) => Err(ProtoError::Decode(err).into()),
+            };
+        }
         match T::decode(src, DecodeContext::default()) {
             Ok(msg) => Ok(Some(msg)),
-            Err(err) => Err(Status::data_loss(format!("failed to decode message: {err}"))),
+            Err(err) => Err(ProtoError::Decode(err).into()),
         }
     }
 }
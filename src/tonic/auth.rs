@@ -0,0 +1,88 @@
+//! Request authentication used by services declared with `#[proto_rpc(auth = "...")]`.
+//!
+//! With `auth = "bearer"` the service type implements [`BearerAuth`] and the generated server
+//! checks the `authorization: Bearer <token>` header before dispatching. With
+//! `auth = "provider"` the service implements [`AuthProvider`] and inspects the headers itself.
+//! Either way a rejected request is answered with `UNAUTHENTICATED` without reaching the handler.
+//! An accepted request carries the caller's [`Principal`] in its extensions, which keeps cached
+//! and shared responses of one caller away from the others.
+
+use tonic::Status;
+use tonic::codegen::http::HeaderMap;
+use tonic::codegen::http::header::AUTHORIZATION;
+use tonic::metadata::AsciiMetadataValue;
+use tonic::metadata::errors::InvalidMetadataValue;
+
+use crate::alloc::format;
+use crate::alloc::string::String;
+use crate::alloc::string::ToString;
+
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// Identity of an authenticated caller, stored in the request extensions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Principal(pub String);
+
+/// Verifies bearer tokens for a service generated with `auth = "bearer"`.
+pub trait BearerAuth: Send + Sync + 'static {
+    fn verify_token(&self, token: &str) -> bool;
+
+    /// Identity of the caller holding an accepted `token`; the token itself by default.
+    fn principal(&self, token: &str) -> String {
+        token.to_string()
+    }
+}
+
+/// Verifies raw request headers for a service generated with `auth = "provider"`.
+pub trait AuthProvider: Send + Sync + 'static {
+    fn authenticate(&self, headers: &HeaderMap) -> bool;
+
+    /// Identity of the caller of an accepted request; the `authorization` header by default.
+    ///
+    /// Providers that authenticate through other headers should override this, otherwise their
+    /// callers are not told apart and cached or shared responses are not reused.
+    fn principal(&self, headers: &HeaderMap) -> Option<String> {
+        headers.get(AUTHORIZATION)?.to_str().ok().map(ToString::to_string)
+    }
+}
+
+/// Extracts the token from an `authorization: Bearer <token>` header.
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers.get(AUTHORIZATION)?.to_str().ok()?.strip_prefix(BEARER_PREFIX).map(str::trim).filter(|token| !token.is_empty())
+}
+
+/// Builds the `authorization` metadata value carrying `token`.
+///
+/// # Errors
+///
+/// Returns an error if the token contains characters not allowed in a header value.
+pub fn bearer_value(token: &str) -> Result<AsciiMetadataValue, InvalidMetadataValue> {
+    format!("{BEARER_PREFIX}{token}").parse()
+}
+
+/// Server-side check for `auth = "bearer"`.
+///
+/// # Errors
+///
+/// Returns `UNAUTHENTICATED` if the header is missing, malformed, or the token is rejected.
+pub fn check_bearer<A: BearerAuth + ?Sized>(auth: &A, headers: &HeaderMap) -> Result<Option<Principal>, Status> {
+    let token = bearer_token(headers).ok_or_else(|| Status::unauthenticated("missing bearer token"))?;
+    if auth.verify_token(token) {
+        Ok(Some(Principal(auth.principal(token))))
+    } else {
+        Err(Status::unauthenticated("invalid bearer token"))
+    }
+}
+
+/// Server-side check for `auth = "provider"`.
+///
+/// # Errors
+///
+/// Returns `UNAUTHENTICATED` if the provider rejects the request.
+pub fn check_provider<A: AuthProvider + ?Sized>(auth: &A, headers: &HeaderMap) -> Result<Option<Principal>, Status> {
+    if auth.authenticate(headers) {
+        Ok(auth.principal(headers).map(Principal))
+    } else {
+        Err(Status::unauthenticated("request rejected by auth provider"))
+    }
+}
@@ -0,0 +1,218 @@
+//! Message-bus (request/reply) transport for `#[proto_rpc]` services.
+//!
+//! The same generated client and server run over any request/reply bus such as NATS:
+//!
+//! - [`BusChannel`] turns a [`RequestReply`] implementation into a `GrpcService`, so a generated
+//!   client is built with `FooClient::new(BusChannel::new(transport))`.
+//! - [`BusDispatcher`] feeds messages received on a subscription into a generated server and
+//!   returns the reply payload to publish.
+//!
+//! Subjects are derived from routes: `/pkg.Service/Method` becomes `pkg.Service.Method`. The request
+//! payload is the gRPC length-delimited body unchanged; the reply is a small envelope carrying
+//! the gRPC status followed by the length-delimited response messages. Server-streaming replies
+//! are collected into one envelope, so they must be finite.
+
+use core::convert::Infallible;
+use core::future::Future;
+use core::task::Context;
+use core::task::Poll;
+
+use bytes::Buf;
+use bytes::BufMut;
+use bytes::Bytes;
+use bytes::BytesMut;
+use http_body_util::BodyExt;
+use http_body_util::Full;
+use tonic::Code;
+use tonic::Status;
+use tonic::body::Body;
+use tonic::codegen::BoxFuture;
+use tonic::codegen::Service;
+use tonic::codegen::http;
+use tonic::codegen::http::HeaderMap;
+use tonic::codegen::http::HeaderValue;
+use tonic::codegen::http::header::CONTENT_TYPE;
+use tonic::metadata::GRPC_CONTENT_TYPE;
+use tonic::server::NamedService;
+
+use crate::alloc::boxed::Box;
+use crate::alloc::format;
+use crate::alloc::string::String;
+use crate::alloc::string::ToString;
+
+type BoxError = Box<dyn core::error::Error + Send + Sync>;
+
+/// Client side of a request/reply bus, e.g. `async_nats::Client::request`.
+pub trait RequestReply: Send + Sync + 'static {
+    fn request(&self, subject: String, payload: Bytes) -> impl Future<Output = Result<Bytes, Status>> + Send;
+}
+
+/// Converts a gRPC route (`/pkg.Service/Method`) to a bus subject (`pkg.Service.Method`).
+pub fn subject_for_route(route: &str) -> Option<String> {
+    let (service, method) = route.strip_prefix('/')?.split_once('/')?;
+    Some(format!("{service}.{method}"))
+}
+
+/// Converts a bus subject (`pkg.Service.Method`) back to its gRPC route.
+pub fn route_for_subject(subject: &str) -> Option<String> {
+    let (service, method) = subject.rsplit_once('.')?;
+    Some(format!("/{service}/{method}"))
+}
+
+/// Reply envelope: `status code (i32) | message length (u32) | message | length-delimited body`.
+pub(crate) fn encode_reply(status: &Status, body: &[u8]) -> Bytes {
+    let message = status.message().as_bytes();
+    let message_len = u32::try_from(message.len()).unwrap_or(u32::MAX);
+    let mut buf = BytesMut::with_capacity(8 + message.len() + body.len());
+    buf.put_i32(status.code() as i32);
+    buf.put_u32(message_len);
+    buf.put_slice(&message[..message_len as usize]);
+    buf.put_slice(body);
+    buf.freeze()
+}
+
+pub(crate) fn decode_reply(mut reply: Bytes) -> Result<(Status, Bytes), Status> {
+    let malformed = || Status::internal("malformed bus reply");
+    if reply.remaining() < 8 {
+        return Err(malformed());
+    }
+    let code = Code::from_i32(reply.get_i32());
+    let message_len = reply.get_u32() as usize;
+    if reply.remaining() < message_len {
+        return Err(malformed());
+    }
+    let message = reply.split_to(message_len);
+    let message = core::str::from_utf8(&message).map_err(|_| malformed())?.to_string();
+    Ok((Status::new(code, message), reply))
+}
+
+/// Response carrying `body` followed by `status` in the trailers.
+pub(crate) fn grpc_response(status: &Status, body: Bytes) -> http::Response<Body> {
+    let mut trailers = HeaderMap::new();
+    if status.add_header(&mut trailers).is_err() {
+        trailers.insert(Status::GRPC_STATUS, HeaderValue::from(Code::Internal as i32));
+    }
+    let body = Full::new(body).with_trailers(core::future::ready(Some(Ok::<_, Infallible>(trailers))));
+    let mut response = http::Response::new(Body::new(body));
+    response.headers_mut().insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+    response
+}
+
+/// Builds the request a generated server expects for `route` with a length-delimited `payload`.
+pub(crate) fn grpc_request(route: &str, payload: Bytes) -> Result<http::Request<Body>, Status> {
+    http::Request::builder()
+        .method(http::Method::POST)
+        .uri(route)
+        .header(CONTENT_TYPE, GRPC_CONTENT_TYPE)
+        .header("te", "trailers")
+        .body(Body::new(Full::new(payload)))
+        .map_err(|err| Status::invalid_argument(err.to_string()))
+}
+
+/// Runs a response from a generated server to completion: the status and the length-delimited body.
+pub(crate) async fn collect_response(response: http::Response<Body>) -> (Status, Bytes) {
+    let (parts, body) = response.into_parts();
+    // Trailers-only responses carry the status in the headers.
+    if let Some(status) = Status::from_header_map(&parts.headers) {
+        return (status, Bytes::new());
+    }
+    match body.collect().await {
+        Ok(collected) => {
+            let status = collected
+                .trailers()
+                .and_then(Status::from_header_map)
+                .unwrap_or_else(|| Status::internal("response ended without grpc-status"));
+            (status, collected.to_bytes())
+        }
+        Err(status) => (status, Bytes::new()),
+    }
+}
+
+/// `GrpcService` that sends each call as one request on a [`RequestReply`] bus.
+#[derive(Debug, Clone)]
+pub struct BusChannel<C> {
+    transport: C,
+}
+
+impl<C> BusChannel<C> {
+    pub const fn new(transport: C) -> Self {
+        Self { transport }
+    }
+
+    pub const fn transport(&self) -> &C {
+        &self.transport
+    }
+}
+
+impl<C> Service<http::Request<Body>> for BusChannel<C>
+where
+    C: RequestReply + Clone,
+{
+    type Response = http::Response<Body>;
+    type Error = BoxError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let transport = self.transport.clone();
+        let subject = subject_for_route(request.uri().path());
+        Box::pin(async move {
+            let Some(subject) = subject else {
+                return Ok(Status::unimplemented("route has no bus subject").into_http());
+            };
+            let payload = request.into_body().collect().await?.to_bytes();
+            let response = match transport.request(subject, payload).await.and_then(decode_reply) {
+                Ok((status, body)) => grpc_response(&status, body),
+                Err(status) => status.into_http(),
+            };
+            Ok(response)
+        })
+    }
+}
+
+/// Feeds bus messages into a generated server.
+#[derive(Debug, Clone)]
+pub struct BusDispatcher<S> {
+    service: S,
+}
+
+impl<S> BusDispatcher<S>
+where
+    S: Service<http::Request<Body>, Response = http::Response<Body>, Error = Infallible> + NamedService + Clone,
+{
+    pub const fn new(service: S) -> Self {
+        Self { service }
+    }
+
+    /// Wildcard subject covering every method of the service, e.g. `pkg.Service.*`.
+    pub fn subscription_subject(&self) -> String {
+        format!("{}.*", S::NAME)
+    }
+
+    /// Handles one message and returns the reply payload to publish.
+    pub async fn dispatch(&self, subject: &str, payload: Bytes) -> Bytes {
+        let (status, body) = match route_for_subject(subject) {
+            Some(route) => call_route(&self.service, &route, payload).await,
+            None => (Status::unimplemented(format!("unknown subject `{subject}`")), Bytes::new()),
+        };
+        encode_reply(&status, &body)
+    }
+}
+
+/// Runs one call against a generated server and collects its status and length-delimited body.
+pub(crate) async fn call_route<S>(service: &S, route: &str, payload: Bytes) -> (Status, Bytes)
+where
+    S: Service<http::Request<Body>, Response = http::Response<Body>, Error = Infallible> + Clone,
+{
+    let request = match grpc_request(route, payload) {
+        Ok(request) => request,
+        Err(status) => return (status, Bytes::new()),
+    };
+    let mut service = service.clone();
+    let Ok(()) = core::future::poll_fn(|cx| service.poll_ready(cx)).await;
+    let Ok(response) = service.call(request).await;
+    collect_response(response).await
+}
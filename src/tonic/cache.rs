@@ -0,0 +1,253 @@
+//! In-memory response cache for unary methods marked `#[proto(cache(ttl = "..."))]`.
+//!
+//! Entries are keyed by a [`RequestKey`]: the route, the caller's [`Principal`] when the service
+//! authenticates requests, the metadata values the method lists in `vary`, and the full canonical
+//! encoding of the request. A hit therefore needs the same bytes from the same caller; callers
+//! never see each other's responses. Only successful responses are stored, at most
+//! [`DEFAULT_MAX_ENTRIES`] unless changed with [`ResponseCache::set_max_entries`], evicting the
+//! oldest entry first. Hit and miss counters are kept per route and exposed through
+//! [`CacheStats`].
+
+use core::any::Any;
+use core::time::Duration;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::ProtoEncode;
+use crate::ProtoExt;
+use crate::alloc::boxed::Box;
+use crate::alloc::string::String;
+use crate::alloc::sync::Arc;
+use crate::tonic::auth::Principal;
+
+/// Smallest entry count at which expired entries are swept on insert.
+const MIN_SWEEP_LEN: usize = 64;
+
+/// Entry bound of a new [`ResponseCache`].
+pub const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+/// Hit and miss counters for one route.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// What a cached response is stored under: the route, the caller and the full request.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestKey {
+    route: &'static str,
+    principal: Option<String>,
+    metadata: Vec<Option<Vec<u8>>>,
+    request: Vec<u8>,
+}
+
+impl RequestKey {
+    /// Key of the request with canonical encoding `request` on `route`.
+    pub const fn new(route: &'static str, request: Vec<u8>) -> Self {
+        Self {
+            route,
+            principal: None,
+            metadata: Vec::new(),
+            request,
+        }
+    }
+
+    /// Adds the identity of the caller.
+    #[must_use]
+    pub fn with_principal(mut self, principal: impl Into<String>) -> Self {
+        self.principal = Some(principal.into());
+        self
+    }
+
+    /// Adds a metadata value the response depends on; `None` for an absent key.
+    #[must_use]
+    pub fn with_metadata(mut self, value: Option<&[u8]>) -> Self {
+        self.metadata.push(value.map(<[u8]>::to_vec));
+        self
+    }
+
+    pub const fn route(&self) -> &'static str {
+        self.route
+    }
+}
+
+/// Key of `request` on `route`, including the [`Principal`] the auth check stored and the values
+/// of the `vary` metadata keys.
+///
+/// Returns `None` when `authenticated` is set but no principal was stored; such a call must not
+/// be cached or shared.
+pub fn request_key<M: ProtoEncode + ProtoExt>(
+    route: &'static str,
+    request: &tonic::Request<M>,
+    authenticated: bool,
+    vary: &[&str],
+) -> Option<RequestKey> {
+    let mut key = RequestKey::new(route, request.get_ref().encode_to_vec());
+    match request.extensions().get::<Principal>() {
+        Some(principal) => key = key.with_principal(principal.0.clone()),
+        None if authenticated => return None,
+        None => {}
+    }
+    for name in vary {
+        key = key.with_metadata(request.metadata().get(*name).map(tonic::metadata::MetadataValue::as_bytes));
+    }
+    Some(key)
+}
+
+/// Response cache shared by every clone of a generated server.
+#[derive(Clone, Default)]
+pub struct ResponseCache {
+    inner: Arc<Mutex<CacheState>>,
+}
+
+struct CacheState {
+    entries: HashMap<Arc<RequestKey>, Entry>,
+    /// Keys in insertion order with the sequence number of their entry; stale pairs are skipped.
+    order: VecDeque<(u64, Arc<RequestKey>)>,
+    stats: HashMap<&'static str, CacheStats>,
+    next_seq: u64,
+    next_sweep: usize,
+    max_entries: usize,
+}
+
+impl Default for CacheState {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            stats: HashMap::new(),
+            next_seq: 0,
+            next_sweep: 0,
+            max_entries: DEFAULT_MAX_ENTRIES,
+        }
+    }
+}
+
+impl CacheState {
+    fn is_current(&self, seq: u64, key: &RequestKey) -> bool {
+        self.entries.get(key).is_some_and(|entry| entry.seq == seq)
+    }
+
+    fn sweep(&mut self, now: Instant) {
+        self.entries.retain(|_, entry| entry.expires > now);
+        let order = core::mem::take(&mut self.order);
+        self.order = order.into_iter().filter(|(seq, key)| self.is_current(*seq, key)).collect();
+        self.next_sweep = (self.entries.len() * 2).max(MIN_SWEEP_LEN);
+    }
+
+    fn evict_oldest(&mut self) {
+        while let Some((seq, key)) = self.order.pop_front() {
+            if self.is_current(seq, &key) {
+                self.entries.remove(&key);
+                return;
+            }
+        }
+    }
+}
+
+struct Entry {
+    expires: Instant,
+    seq: u64,
+    value: Box<dyn Any + Send + Sync>,
+}
+
+impl core::fmt::Debug for ResponseCache {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let state = self.lock();
+        f.debug_struct("ResponseCache")
+            .field("entries", &state.entries.len())
+            .field("max_entries", &state.max_entries)
+            .field("stats", &state.stats)
+            .finish()
+    }
+}
+
+impl ResponseCache {
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.inner.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Returns a live entry for `key`, counting a hit or a miss for its route.
+    pub fn get<V: Clone + 'static>(&self, key: &RequestKey) -> Option<V> {
+        let mut state = self.lock();
+        let now = Instant::now();
+        let value = match state.entries.get(key) {
+            Some(entry) if entry.expires > now => entry.value.downcast_ref::<V>().cloned(),
+            Some(_) => {
+                state.entries.remove(key);
+                None
+            }
+            None => None,
+        };
+        let counters = state.stats.entry(key.route).or_default();
+        if value.is_some() {
+            counters.hits += 1;
+        } else {
+            counters.misses += 1;
+        }
+        value
+    }
+
+    /// Stores `value` for `key` until `ttl` elapses, evicting the oldest entries beyond the bound.
+    pub fn insert<V: Send + Sync + 'static>(&self, key: RequestKey, value: V, ttl: Duration) {
+        let mut state = self.lock();
+        if state.max_entries == 0 {
+            return;
+        }
+        let now = Instant::now();
+        if state.entries.len() >= state.next_sweep || state.order.len() >= state.next_sweep.max(state.max_entries) * 2 {
+            state.sweep(now);
+        }
+        let key = Arc::new(key);
+        if !state.entries.contains_key(&key) {
+            while state.entries.len() >= state.max_entries {
+                state.evict_oldest();
+            }
+        }
+        let seq = state.next_seq;
+        state.next_seq += 1;
+        state.order.push_back((seq, key.clone()));
+        state.entries.insert(
+            key,
+            Entry {
+                expires: now + ttl,
+                seq,
+                value: Box::new(value),
+            },
+        );
+    }
+
+    /// Bounds the number of stored entries, evicting the oldest ones beyond it. `0` disables
+    /// caching.
+    pub fn set_max_entries(&self, max_entries: usize) {
+        let mut state = self.lock();
+        state.max_entries = max_entries;
+        while state.entries.len() > max_entries {
+            state.evict_oldest();
+        }
+    }
+
+    /// Hit and miss counters for `route`.
+    pub fn stats(&self, route: &str) -> CacheStats {
+        self.lock().stats.get(route).copied().unwrap_or_default()
+    }
+
+    /// Number of stored entries, including ones that expired but were not swept yet.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops every entry; counters are kept.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.order.clear();
+    }
+}
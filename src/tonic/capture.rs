@@ -0,0 +1,324 @@
+//! Wire-level capture of rpc traffic and replay against a service.
+//!
+//! While a [`Capture`] is running, the codecs of generated servers (or clients, with
+//! [`Capture::start_client`]) tee every message they encode or decode into a
+//! [record file](crate::records): the encoded payload with its route, a call number pairing
+//! requests with responses, and a timestamp. [`Corpus`] reads such a file back and replays the
+//! recorded requests through a [`DispatchTable`], so real traffic becomes a regression test:
+//!
+//! ```rust,ignore
+//! let capture = Capture::start(File::create("orders.capture")?)?;
+//! // serve traffic
+//! capture.stop()?;
+//!
+//! let corpus = Corpus::read(File::open("orders.capture")?)?;
+//! let table = DispatchTable::new().with(OrdersServer::new(candidate));
+//! for replayed in corpus.replay(&table).await {
+//!     assert!(replayed.matches(), "{} diverged", replayed.call.route);
+//! }
+//! ```
+//!
+//! Only payloads are captured: metadata and the final status are not, so a call that failed is
+//! recorded with its requests and no responses. Calls started before the capture are not
+//! recorded.
+
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering;
+use std::collections::BTreeMap;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use prosto_derive::proto_message;
+use tonic::Request;
+use tonic::Status;
+use tonic::codegen::tokio_stream::StreamExt;
+
+use crate::alloc::boxed::Box;
+use crate::alloc::string::String;
+use crate::alloc::string::ToString;
+use crate::alloc::vec::Vec;
+use crate::coders::CodecSide;
+use crate::records;
+use crate::records::RecordError;
+use crate::tonic::dispatch::DispatchReply;
+use crate::tonic::dispatch::DispatchTable;
+use crate::tonic::sizes::MessageKind;
+
+/// One captured message, as stored in the record file.
+#[proto_message(proto_path = "protos/capture.proto")]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CapturedMessage {
+    /// Number of the call on the capturing process; shared by its requests and responses.
+    pub call: u64,
+    /// gRPC route of the method, e.g. `/pkg.Service/Method`.
+    pub route: String,
+    pub response: bool,
+    /// Microseconds since the Unix epoch.
+    pub timestamp_micros: u64,
+    pub payload: Vec<u8>,
+}
+
+struct Sink {
+    writer: records::Writer<Box<dyn Write + Send>>,
+    side: CodecSide,
+    error: Option<io::Error>,
+}
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static NEXT_CALL: AtomicU64 = AtomicU64::new(1);
+static SINK: Mutex<Option<Sink>> = Mutex::new(None);
+
+fn sink() -> std::sync::MutexGuard<'static, Option<Sink>> {
+    SINK.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+#[inline]
+pub(crate) fn is_active() -> bool {
+    ACTIVE.load(Ordering::Acquire)
+}
+
+pub(crate) fn next_call() -> u64 {
+    NEXT_CALL.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Capture target of one codec: the call its messages belong to.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Tap {
+    pub(crate) route: &'static str,
+    pub(crate) side: CodecSide,
+    pub(crate) kind: MessageKind,
+    pub(crate) call: u64,
+}
+
+impl Tap {
+    /// Whether the running capture records this codec's side.
+    pub(crate) fn is_on(&self) -> bool {
+        is_active() && sink().as_ref().is_some_and(|sink| sink.side == self.side)
+    }
+
+    pub(crate) fn record(&self, payload: &[u8]) {
+        let mut sink = sink();
+        let Some(sink) = sink.as_mut().filter(|sink| sink.side == self.side && sink.error.is_none()) else {
+            return;
+        };
+        let timestamp_micros = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |elapsed| u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX));
+        let message = CapturedMessage {
+            call: self.call,
+            route: self.route.to_string(),
+            response: self.kind == MessageKind::Response,
+            timestamp_micros,
+            payload: payload.to_vec(),
+        };
+        if let Err(error) = sink.writer.write(&message) {
+            sink.error = Some(error);
+        }
+    }
+}
+
+/// A running capture. Stopping it, or dropping it, finishes the record file.
+#[derive(Debug)]
+#[must_use = "the capture stops when dropped"]
+pub struct Capture {
+    _private: (),
+}
+
+impl Capture {
+    /// Starts recording the messages of generated servers into a record file on `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of writing the record file header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a capture is already running.
+    pub fn start<W: Write + Send + 'static>(writer: W) -> io::Result<Self> {
+        Self::start_side(writer, CodecSide::Server)
+    }
+
+    /// Starts recording the messages of generated clients into a record file on `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of writing the record file header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a capture is already running.
+    pub fn start_client<W: Write + Send + 'static>(writer: W) -> io::Result<Self> {
+        Self::start_side(writer, CodecSide::Client)
+    }
+
+    fn start_side<W: Write + Send + 'static>(writer: W, side: CodecSide) -> io::Result<Self> {
+        let mut sink = sink();
+        assert!(sink.is_none(), "a capture is already running");
+        let writer: Box<dyn Write + Send> = Box::new(writer);
+        *sink = Some(Sink {
+            writer: records::Writer::new(writer)?,
+            side,
+            error: None,
+        });
+        ACTIVE.store(true, Ordering::Release);
+        Ok(Self { _private: () })
+    }
+
+    /// Writes the messages recorded so far to the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of writing the record file.
+    pub fn flush(&self) -> io::Result<()> {
+        let mut sink = sink();
+        let Some(sink) = sink.as_mut() else {
+            return Ok(());
+        };
+        if let Some(error) = sink.error.take() {
+            return Err(error);
+        }
+        sink.writer.flush()
+    }
+
+    /// Stops recording and finishes the record file.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of writing the record file.
+    pub fn stop(self) -> io::Result<()> {
+        core::mem::forget(self);
+        Self::finish()
+    }
+
+    fn finish() -> io::Result<()> {
+        ACTIVE.store(false, Ordering::Release);
+        let Some(sink) = sink().take() else {
+            return Ok(());
+        };
+        if let Some(error) = sink.error {
+            return Err(error);
+        }
+        sink.writer.finish()?.flush()
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        let _ = Self::finish();
+    }
+}
+
+/// One call read back from a capture, with its messages in wire order.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CapturedCall {
+    pub route: String,
+    /// Time of the call's first message, in microseconds since the Unix epoch.
+    pub timestamp_micros: u64,
+    pub requests: Vec<Bytes>,
+    pub responses: Vec<Bytes>,
+}
+
+/// The calls of a capture file, in the order they started.
+#[derive(Debug, Clone, Default)]
+pub struct Corpus {
+    calls: Vec<CapturedCall>,
+}
+
+impl Corpus {
+    /// Reads a record file written by a [`Capture`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of reading or decoding the record file.
+    pub fn read<R: Read>(reader: R) -> Result<Self, RecordError> {
+        let mut calls: Vec<CapturedCall> = Vec::new();
+        let mut by_number = BTreeMap::new();
+        for message in records::Reader::new(reader)?.messages::<CapturedMessage>() {
+            let message = message?;
+            let index = *by_number.entry(message.call).or_insert_with(|| {
+                calls.push(CapturedCall {
+                    route: message.route.clone(),
+                    timestamp_micros: message.timestamp_micros,
+                    ..CapturedCall::default()
+                });
+                calls.len() - 1
+            });
+            let call = &mut calls[index];
+            let payload = Bytes::from(message.payload);
+            if message.response {
+                call.responses.push(payload);
+            } else {
+                call.requests.push(payload);
+            }
+        }
+        Ok(Self { calls })
+    }
+
+    pub fn calls(&self) -> &[CapturedCall] {
+        &self.calls
+    }
+
+    pub const fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Replays every call, one at a time and in capture order, through `table`.
+    ///
+    /// Calls with other than one request (client and bidirectional streams) fail with
+    /// `Unimplemented`, as [`DispatchTable`] does not serve them.
+    pub async fn replay<'a>(&'a self, table: &DispatchTable) -> Vec<Replayed<'a>> {
+        let mut replayed = Vec::with_capacity(self.calls.len());
+        for call in &self.calls {
+            let result = match call.requests.as_slice() {
+                [request] => replay_call(table, &call.route, request.clone()).await,
+                _ => Err(Status::unimplemented(format!(
+                    "`{}` is not a unary or server-streaming call",
+                    call.route
+                ))),
+            };
+            replayed.push(Replayed { call, result });
+        }
+        replayed
+    }
+}
+
+async fn replay_call(table: &DispatchTable, route: &str, request: Bytes) -> Result<Vec<Bytes>, Status> {
+    match table.dispatch(route, Request::new(request)).await?.into_inner() {
+        DispatchReply::Unary(response) => Ok(vec![response]),
+        DispatchReply::Stream(mut stream) => {
+            let mut responses = Vec::new();
+            while let Some(response) = stream.next().await {
+                responses.push(response?);
+            }
+            Ok(responses)
+        }
+    }
+}
+
+/// Outcome of replaying one captured call.
+#[derive(Debug)]
+pub struct Replayed<'a> {
+    pub call: &'a CapturedCall,
+    /// Encoded responses of the replayed call, or its error status.
+    pub result: Result<Vec<Bytes>, Status>,
+}
+
+impl Replayed<'_> {
+    /// Whether the replay produced the captured responses, byte for byte. As statuses are not
+    /// captured, a failed replay matches a call captured without responses.
+    pub fn matches(&self) -> bool {
+        match &self.result {
+            Ok(responses) => *responses == self.call.responses,
+            Err(_) => self.call.responses.is_empty(),
+        }
+    }
+}
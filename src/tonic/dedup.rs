@@ -0,0 +1,127 @@
+//! Sharing of concurrent identical calls for unary methods marked `#[proto(idempotent)]`.
+//!
+//! Calls are keyed by a [`RequestKey`], as in the [`cache`](crate::cache): the route, the caller's
+//! principal on authenticated services, and the full canonical encoding of the request. While one
+//! call for a key is running, later identical requests from the same caller wait for it and
+//! receive a clone of its result, errors included, instead of running the handler again. Nothing
+//! is kept once the call completes. If the running call is cancelled (its client went away), one
+//! of the waiting requests runs the handler in its place.
+
+use core::any::Any;
+use core::future::Future;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use tokio::sync::watch;
+
+use crate::alloc::sync::Arc;
+use crate::cache::RequestKey;
+
+type Shared = Option<Arc<dyn Any + Send + Sync>>;
+
+/// Executed and shared call counters for one route.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DedupStats {
+    /// Calls that ran the handler.
+    pub executed: u64,
+    /// Calls answered with the result of an identical call already in flight.
+    pub shared: u64,
+}
+
+/// In-flight calls shared by every clone of a generated server.
+#[derive(Clone, Default)]
+pub struct InFlight {
+    inner: Arc<Mutex<InFlightState>>,
+}
+
+#[derive(Default)]
+struct InFlightState {
+    calls: HashMap<RequestKey, watch::Sender<Shared>>,
+    stats: HashMap<&'static str, DedupStats>,
+}
+
+impl core::fmt::Debug for InFlight {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let state = self.lock();
+        f.debug_struct("InFlight").field("calls", &state.calls.len()).field("stats", &state.stats).finish()
+    }
+}
+
+/// Removes the entry of a call that ends without publishing, waking its waiters to retry.
+struct Running<'a> {
+    in_flight: &'a InFlight,
+    key: RequestKey,
+    finished: bool,
+}
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.in_flight.lock().calls.remove(&self.key);
+        }
+    }
+}
+
+impl InFlight {
+    fn lock(&self) -> std::sync::MutexGuard<'_, InFlightState> {
+        self.inner.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Runs `call` unless an identical call for `key` is in flight, in which case its result is
+    /// awaited and cloned. Without a key the call always runs on its own.
+    pub async fn run<V, F, Fut>(&self, key: Option<RequestKey>, call: F) -> V
+    where
+        V: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        let Some(key) = key else {
+            return call().await;
+        };
+        let route = key.route();
+        loop {
+            let mut receiver = {
+                let mut state = self.lock();
+                if let Some(sender) = state.calls.get(&key) {
+                    sender.subscribe()
+                } else {
+                    state.calls.insert(key.clone(), watch::channel(None).0);
+                    state.stats.entry(route).or_default().executed += 1;
+                    break;
+                }
+            };
+            let shared = receiver.wait_for(Option::is_some).await.ok().and_then(|shared| shared.clone());
+            if let Some(value) = shared.as_deref().and_then(|value| value.downcast_ref::<V>()) {
+                self.lock().stats.entry(route).or_default().shared += 1;
+                return value.clone();
+            }
+            // The running call was cancelled before finishing; take over.
+        }
+
+        let mut running = Running {
+            in_flight: self,
+            key,
+            finished: false,
+        };
+        let value = call().await;
+        running.finished = true;
+        if let Some(sender) = self.lock().calls.remove(&running.key) {
+            sender.send_replace(Some(Arc::new(value.clone())));
+        }
+        value
+    }
+
+    /// Executed and shared counters for `route`.
+    pub fn stats(&self, route: &str) -> DedupStats {
+        self.lock().stats.get(route).copied().unwrap_or_default()
+    }
+
+    /// Number of calls currently running.
+    pub fn len(&self) -> usize {
+        self.lock().calls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
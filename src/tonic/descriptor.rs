@@ -0,0 +1,30 @@
+/// Static description of one rpc of a generated service.
+///
+/// Every generated server module exposes a `METHODS` slice of these (also available as
+/// `XServer::METHODS`), so middleware and routers can look up routes and streaming shapes
+/// without parsing `.proto` files at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MethodDescriptor {
+    /// Rpc name as it appears in the `.proto` service definition.
+    pub name: &'static str,
+    /// Full gRPC path, e.g. `/package.Service/Method`.
+    pub route: &'static str,
+    pub client_streaming: bool,
+    pub server_streaming: bool,
+    /// Proto name of the request message, qualified with its package when it has one.
+    pub request_type: &'static str,
+    /// Proto name of the response message (the stream item for server-streaming rpcs).
+    pub response_type: &'static str,
+}
+
+impl MethodDescriptor {
+    /// Whether neither side of the call streams.
+    pub const fn is_unary(&self) -> bool {
+        !self.client_streaming && !self.server_streaming
+    }
+
+    /// Finds the descriptor served under `route` in `methods`.
+    pub fn by_route<'a>(methods: &'a [MethodDescriptor], route: &str) -> Option<&'a MethodDescriptor> {
+        methods.iter().find(|method| method.route == route)
+    }
+}
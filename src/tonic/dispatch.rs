@@ -0,0 +1,156 @@
+//! Transport-agnostic dispatch into `#[proto_rpc]` services.
+//!
+//! Every generated server implements [`ServiceDispatcher`]: given an rpc name and the encoded
+//! request message, it decodes the request, calls the service and encodes the reply, without any
+//! HTTP/2 framing. Alternate transports (NATS, WebSocket, an in-process bus) only move bytes and
+//! metadata around and reuse this generated dispatch instead of emitting their own glue:
+//!
+//! ```rust,ignore
+//! let table = DispatchTable::new().with(OrdersServer::new(desk));
+//! let reply = table.dispatch("/shop.Orders/Place", Request::new(payload)).await?;
+//! ```
+//!
+//! Payloads are bare messages, not the length-delimited gRPC body. Authentication, concurrency
+//! limits and drain tracking apply as on the tonic path; the response cache and
+//! `idempotent` call sharing do not.
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+
+use bytes::Bytes;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::BoxFuture;
+use tonic::codegen::BoxStream;
+use tonic::codegen::tokio_stream::Stream;
+use tonic::codegen::tokio_stream::StreamExt;
+
+use crate::ProtoDecode;
+use crate::ProtoError;
+use crate::alloc::boxed::Box;
+use crate::alloc::format;
+use crate::coders::ProtoEncoder;
+use crate::encoding::DecodeContext;
+use crate::tonic::EncoderExt;
+use crate::tonic::MethodDescriptor;
+
+/// Encoded reply of one dispatched call.
+pub enum DispatchReply {
+    Unary(Bytes),
+    /// Encoded items of a server-streaming rpc.
+    Stream(BoxStream<Bytes>),
+}
+
+impl core::fmt::Debug for DispatchReply {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Unary(bytes) => f.debug_tuple("Unary").field(bytes).finish(),
+            Self::Stream(_) => f.write_str("Stream(..)"),
+        }
+    }
+}
+
+/// Method-name dispatch of a generated server: decode, call, encode.
+pub trait ServiceDispatcher: Send + Sync + 'static {
+    /// gRPC service name (`package.Service`).
+    fn service_name(&self) -> &'static str;
+
+    /// Rpcs accepted by [`dispatch`](Self::dispatch), in declaration order.
+    fn methods(&self) -> &'static [MethodDescriptor];
+
+    /// Calls the rpc named `method` (as in the `.proto` service, e.g. `Place`) with an encoded
+    /// request message. Unknown methods fail with `Unimplemented`.
+    fn dispatch(&self, method: &str, request: Request<Bytes>) -> BoxFuture<Response<DispatchReply>, Status>;
+}
+
+/// Dispatchers keyed by service name, routing `/package.Service/Method` paths.
+#[derive(Default, Clone)]
+pub struct DispatchTable {
+    services: BTreeMap<&'static str, Arc<dyn ServiceDispatcher>>,
+}
+
+impl core::fmt::Debug for DispatchTable {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_set().entries(self.services.keys()).finish()
+    }
+}
+
+impl DispatchTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a generated server (e.g. `OrdersServer::new(svc)`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if a service with the same gRPC name is already registered.
+    pub fn register(&mut self, service: impl ServiceDispatcher) -> &mut Self {
+        let name = service.service_name();
+        assert!(!self.services.contains_key(name), "service `{name}` is registered twice");
+        self.services.insert(name, Arc::new(service));
+        self
+    }
+
+    /// Builder-style [`DispatchTable::register`].
+    #[must_use]
+    pub fn with(mut self, service: impl ServiceDispatcher) -> Self {
+        self.register(service);
+        self
+    }
+
+    pub fn service(&self, name: &str) -> Option<&Arc<dyn ServiceDispatcher>> {
+        self.services.get(name)
+    }
+
+    /// Names of the registered services, sorted.
+    pub fn service_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.services.keys().copied()
+    }
+
+    /// Dispatches a call addressed by its gRPC route.
+    pub fn dispatch(&self, route: &str, request: Request<Bytes>) -> BoxFuture<Response<DispatchReply>, Status> {
+        let target = route
+            .strip_prefix('/')
+            .and_then(|path| path.split_once('/'))
+            .and_then(|(service, method)| Some((self.services.get(service)?, method)));
+        if let Some((service, method)) = target {
+            service.dispatch(method, request)
+        } else {
+            let status = Status::unimplemented(format!("no service serves `{route}`"));
+            Box::pin(core::future::ready(Err(status)))
+        }
+    }
+}
+
+/// Decodes the request message of a dispatched call, keeping its metadata and extensions.
+pub fn decode_request<T: ProtoDecode>(request: Request<Bytes>) -> Result<Request<T>, Status> {
+    let (metadata, extensions, payload) = request.into_parts();
+    let message = T::decode(payload, DecodeContext::default()).map_err(ProtoError::Decode)?;
+    Ok(Request::from_parts(metadata, extensions, message))
+}
+
+/// Encodes a unary response the way the generated codec would.
+pub fn unary_reply<T, Mode>(response: Response<T>) -> Result<Response<DispatchReply>, Status>
+where
+    ProtoEncoder<T, Mode>: EncoderExt<T, Mode>,
+{
+    let (metadata, message, extensions) = response.into_parts();
+    let bytes = ProtoEncoder::<T, Mode>::default().encode_bytes(message)?;
+    Ok(Response::from_parts(metadata, DispatchReply::Unary(bytes), extensions))
+}
+
+/// Encodes every item of a server-streaming response the way the generated codec would.
+pub fn stream_reply<T, Mode, S>(response: Response<S>) -> Response<DispatchReply>
+where
+    T: Send + 'static,
+    Mode: Send + 'static,
+    S: Stream<Item = Result<T, Status>> + Send + 'static,
+    ProtoEncoder<T, Mode>: EncoderExt<T, Mode>,
+{
+    let (metadata, stream, extensions) = response.into_parts();
+    let mut encoder = ProtoEncoder::<T, Mode>::default();
+    let stream = stream.map(move |item| encoder.encode_bytes(item?));
+    Response::from_parts(metadata, DispatchReply::Stream(Box::pin(stream)), extensions)
+}
@@ -0,0 +1,205 @@
+//! Schema-agnostic forwarding of gRPC calls.
+//!
+//! [`ForwardingService`] accepts every rpc declared in a serialized `FileDescriptorSet` and
+//! forwards the call to a backend `GrpcService` (a `Channel`, [`BusChannel`](super::bus::BusChannel),
+//! ...) without decoding it: headers, the length-delimited body and the trailers pass through
+//! unchanged, so no Rust type has to exist for the forwarded messages. Routes missing from the set
+//! fail with `Unimplemented` before reaching the backend.
+//!
+//! The route table is shared by every clone of the service and replaced atomically by
+//! [`reload`](ForwardingService::reload), so a gateway picks up a new descriptor set without
+//! restarting its listener:
+//!
+//! ```rust,ignore
+//! let gateway = ForwardingService::from_descriptor_set(channel, &std::fs::read("api.binpb")?)?;
+//! let router = Routes::default().into_axum_router().fallback_service(gateway.clone());
+//! // later, after `buf build -o api.binpb`
+//! gateway.reload(&std::fs::read("api.binpb")?)?;
+//! ```
+
+use core::convert::Infallible;
+use core::task::Context;
+use core::task::Poll;
+use std::collections::BTreeMap;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+
+use bytes::Bytes;
+use tonic::Status;
+use tonic::body::Body;
+use tonic::client::GrpcService;
+use tonic::codegen::BoxFuture;
+use tonic::codegen::Service;
+use tonic::codegen::http;
+
+use crate::DecodeError;
+use crate::alloc::boxed::Box;
+use crate::alloc::format;
+use crate::alloc::string::String;
+use crate::alloc::string::ToString;
+use crate::alloc::sync::Arc;
+use crate::alloc::vec::Vec;
+use crate::schemas::descriptor::DescriptorSet;
+
+type BoxError = Box<dyn core::error::Error + Send + Sync>;
+
+/// One rpc accepted by a [`ForwardingService`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForwardedMethod {
+    /// gRPC route, `/package.Service/Method`.
+    pub route: String,
+    /// Full name of the request message, without the leading dot.
+    pub input_type: String,
+    /// Full name of the response message, without the leading dot.
+    pub output_type: String,
+    pub client_streaming: bool,
+    pub server_streaming: bool,
+}
+
+/// Routes accepted by a [`ForwardingService`], keyed by route.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForwardTable {
+    methods: BTreeMap<String, ForwardedMethod>,
+}
+
+impl ForwardTable {
+    /// Every rpc of every service in the serialized `FileDescriptorSet`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when `descriptor_set` is not a valid descriptor set.
+    pub fn from_descriptor_set(descriptor_set: &[u8]) -> Result<Self, DecodeError> {
+        let set = DescriptorSet::decode(descriptor_set)?;
+        let mut methods = BTreeMap::new();
+        for (service, descriptor) in set.services() {
+            for rpc in &descriptor.methods {
+                let route = format!("/{service}/{}", rpc.name);
+                let method = ForwardedMethod {
+                    route: route.clone(),
+                    input_type: rpc.input_type.trim_start_matches('.').to_string(),
+                    output_type: rpc.output_type.trim_start_matches('.').to_string(),
+                    client_streaming: rpc.client_streaming,
+                    server_streaming: rpc.server_streaming,
+                };
+                methods.insert(route, method);
+            }
+        }
+        Ok(Self { methods })
+    }
+
+    pub fn get(&self, route: &str) -> Option<&ForwardedMethod> {
+        self.methods.get(route)
+    }
+
+    /// Accepted rpcs, ordered by route.
+    pub fn methods(&self) -> impl Iterator<Item = &ForwardedMethod> {
+        self.methods.values()
+    }
+
+    /// Names of the services with at least one accepted rpc.
+    pub fn service_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.methods.keys().filter_map(|route| route[1..].split_once('/').map(|(service, _)| service)).collect();
+        names.dedup();
+        names
+    }
+
+    pub fn len(&self) -> usize {
+        self.methods.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.methods.is_empty()
+    }
+}
+
+/// `tower::Service` forwarding the rpcs of a reloadable [`ForwardTable`] to `backend`.
+#[derive(Debug, Clone)]
+pub struct ForwardingService<T> {
+    backend: T,
+    table: Arc<RwLock<Arc<ForwardTable>>>,
+}
+
+impl<T> ForwardingService<T> {
+    /// Service accepting the rpcs of `table`.
+    pub fn new(backend: T, table: ForwardTable) -> Self {
+        Self {
+            backend,
+            table: Arc::new(RwLock::new(Arc::new(table))),
+        }
+    }
+
+    /// Service accepting every rpc of the serialized `FileDescriptorSet`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when `descriptor_set` is not a valid descriptor set.
+    pub fn from_descriptor_set(backend: T, descriptor_set: &[u8]) -> Result<Self, DecodeError> {
+        Ok(Self::new(backend, ForwardTable::from_descriptor_set(descriptor_set)?))
+    }
+
+    /// Current route table. Calls already forwarded are not affected by later reloads.
+    pub fn table(&self) -> Arc<ForwardTable> {
+        Arc::clone(&self.table.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Replaces the route table of this service and all its clones.
+    pub fn replace(&self, table: ForwardTable) {
+        *self.table.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(table);
+    }
+
+    /// Replaces the route table with the rpcs of a new descriptor set and returns how many are
+    /// accepted. On error the previous table stays in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` when `descriptor_set` is not a valid descriptor set.
+    pub fn reload(&self, descriptor_set: &[u8]) -> Result<usize, DecodeError> {
+        let table = ForwardTable::from_descriptor_set(descriptor_set)?;
+        let len = table.len();
+        self.replace(table);
+        Ok(len)
+    }
+
+    pub const fn backend(&self) -> &T {
+        &self.backend
+    }
+}
+
+impl<T> Service<http::Request<Body>> for ForwardingService<T>
+where
+    T: GrpcService<Body> + Clone + Send + 'static,
+    T::Future: Send,
+    T::ResponseBody: tonic::codegen::Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as tonic::codegen::Body>::Error: Into<BoxError>,
+{
+    type Response = http::Response<Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let route = request.uri().path();
+        if self.table().get(route).is_none() {
+            let status = Status::unimplemented(format!("no forwarded rpc for `{route}`"));
+            return Box::pin(core::future::ready(Ok(status.into_http())));
+        }
+        let mut backend = self.backend.clone();
+        Box::pin(async move {
+            if let Err(err) = core::future::poll_fn(|cx| backend.poll_ready(cx)).await {
+                return Ok(backend_unavailable(&err.into()));
+            }
+            let response = match backend.call(request).await {
+                Ok(response) => response.map(Body::new),
+                Err(err) => backend_unavailable(&err.into()),
+            };
+            Ok(response)
+        })
+    }
+}
+
+fn backend_unavailable(err: &BoxError) -> http::Response<Body> {
+    Status::unavailable(format!("forwarding backend failed: {err}")).into_http()
+}
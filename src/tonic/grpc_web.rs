@@ -0,0 +1,224 @@
+//! gRPC-web support for generated clients (`grpc-web` feature).
+//!
+//! Browsers cannot speak HTTP/2 gRPC directly, so a wasm32 frontend talks to a gRPC-web endpoint
+//! (an Envoy filter or `tonic-web` in front of the backend) through `fetch`:
+//!
+//! - [`FetchChannel`] (wasm32 only) is a `GrpcService` built on the browser `fetch` API, so the
+//!   generated client is used as `FooClient::new(FetchChannel::new("https://api.example.com"))`.
+//! - [`decode_response`] and [`encode_response`] convert between gRPC-web and gRPC responses on
+//!   any target; they back `FetchChannel` and are usable from custom transports or test doubles.
+//!
+//! Request bodies are the gRPC length-delimited messages unchanged. In the response, the trailers
+//! travel as a final frame flagged `0x80` holding `name: value\r\n` lines. `fetch` buffers the whole
+//! response, so server streams are delivered once the call completes.
+
+use core::convert::Infallible;
+
+use bytes::Buf;
+use bytes::BufMut;
+use bytes::Bytes;
+use bytes::BytesMut;
+use http_body_util::BodyExt;
+use http_body_util::Full;
+use tonic::Status;
+use tonic::body::Body;
+use tonic::codegen::http;
+use tonic::codegen::http::HeaderMap;
+use tonic::codegen::http::HeaderName;
+use tonic::codegen::http::HeaderValue;
+use tonic::codegen::http::header::CONTENT_TYPE;
+use tonic::metadata::GRPC_CONTENT_TYPE;
+
+use crate::alloc::vec::Vec;
+
+/// Content type of binary gRPC-web requests and responses.
+pub const GRPC_WEB_CONTENT_TYPE: &str = "application/grpc-web+proto";
+
+/// Frame flag marking the trailers frame.
+const TRAILERS_FLAG: u8 = 0x80;
+
+/// Runs a gRPC response to completion and re-frames it as a gRPC-web response body, with the
+/// trailers appended as the final frame.
+pub async fn encode_response(response: http::Response<Body>) -> http::Response<Bytes> {
+    let (mut parts, body) = response.into_parts();
+    parts.headers.insert(CONTENT_TYPE, HeaderValue::from_static(GRPC_WEB_CONTENT_TYPE));
+    let (data, trailers) = match body.collect().await {
+        Ok(collected) => {
+            let trailers = collected.trailers().cloned();
+            (collected.to_bytes(), trailers)
+        }
+        Err(status) => {
+            let mut trailers = HeaderMap::new();
+            let _ = status.add_header(&mut trailers);
+            (Bytes::new(), Some(trailers))
+        }
+    };
+    let Some(trailers) = trailers else {
+        // Trailers-only responses already carry the status in the headers.
+        return http::Response::from_parts(parts, data);
+    };
+
+    let mut block = Vec::new();
+    for (name, value) in &trailers {
+        block.extend_from_slice(name.as_str().as_bytes());
+        block.extend_from_slice(b": ");
+        block.extend_from_slice(value.as_bytes());
+        block.extend_from_slice(b"\r\n");
+    }
+    let block_len = u32::try_from(block.len()).unwrap_or(u32::MAX);
+    let mut buf = BytesMut::with_capacity(data.len() + 5 + block.len());
+    buf.put_slice(&data);
+    buf.put_u8(TRAILERS_FLAG);
+    buf.put_u32(block_len);
+    buf.put_slice(&block);
+    http::Response::from_parts(parts, buf.freeze())
+}
+
+/// Splits a gRPC-web response body into message frames and trailers, producing the response a
+/// generated client expects.
+pub fn decode_response(response: http::Response<Bytes>) -> Result<http::Response<Body>, Status> {
+    let (mut parts, mut body) = response.into_parts();
+    let malformed = || Status::internal("malformed grpc-web response");
+    let mut data = BytesMut::with_capacity(body.len());
+    let mut trailers = None;
+    while body.has_remaining() {
+        if body.remaining() < 5 {
+            return Err(malformed());
+        }
+        let flag = body[0];
+        let len = u32::from_be_bytes([body[1], body[2], body[3], body[4]]) as usize;
+        if body.remaining() < 5 + len {
+            return Err(malformed());
+        }
+        let frame = body.split_to(5 + len);
+        if flag & TRAILERS_FLAG == 0 {
+            data.put_slice(&frame);
+        } else {
+            trailers = Some(parse_trailers(&frame[5..]).ok_or_else(malformed)?);
+        }
+    }
+
+    parts.headers.insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+    let body = Full::new(data.freeze());
+    let body = match trailers {
+        Some(trailers) => Body::new(body.with_trailers(core::future::ready(Some(Ok::<_, Infallible>(trailers))))),
+        None => Body::new(body),
+    };
+    Ok(http::Response::from_parts(parts, body))
+}
+
+fn parse_trailers(block: &[u8]) -> Option<HeaderMap> {
+    let mut trailers = HeaderMap::new();
+    for line in block.split(|&byte| byte == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        let colon = line.iter().position(|&byte| byte == b':')?;
+        let name = HeaderName::from_bytes(line[..colon].trim_ascii()).ok()?;
+        let value = HeaderValue::from_bytes(line[colon + 1..].trim_ascii()).ok()?;
+        trailers.append(name, value);
+    }
+    Some(trailers)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub use fetch::FetchChannel;
+
+#[cfg(target_arch = "wasm32")]
+mod fetch {
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::Context;
+    use core::task::Poll;
+
+    use bytes::Bytes;
+    use http_body_util::BodyExt;
+    use js_sys::Uint8Array;
+    use tonic::Status;
+    use tonic::body::Body;
+    use tonic::codegen::Service;
+    use tonic::codegen::http;
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen::JsValue;
+    use wasm_bindgen_futures::JsFuture;
+
+    use super::GRPC_WEB_CONTENT_TYPE;
+    use super::decode_response;
+    use crate::alloc::boxed::Box;
+    use crate::alloc::format;
+    use crate::alloc::string::String;
+
+    type BoxError = Box<dyn core::error::Error + Send + Sync>;
+
+    fn js_status(err: &JsValue) -> Status {
+        Status::unavailable(format!("fetch failed: {err:?}"))
+    }
+
+    /// `GrpcService` that sends each call with the browser `fetch` API.
+    #[derive(Debug, Clone)]
+    pub struct FetchChannel {
+        base_url: String,
+    }
+
+    impl FetchChannel {
+        /// `base_url` is the gRPC-web endpoint without a trailing slash, e.g. `https://api.example.com`.
+        pub fn new(base_url: impl Into<String>) -> Self {
+            Self { base_url: base_url.into() }
+        }
+    }
+
+    impl Service<http::Request<Body>> for FetchChannel {
+        type Response = http::Response<Body>;
+        type Error = BoxError;
+        type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+            let url = format!("{}{}", self.base_url, request.uri().path());
+            Box::pin(async move {
+                let (parts, body) = request.into_parts();
+                let payload = body.collect().await?.to_bytes();
+                Ok(fetch(&url, &parts.headers, &payload).await?)
+            })
+        }
+    }
+
+    async fn fetch(url: &str, headers: &http::HeaderMap, payload: &[u8]) -> Result<http::Response<Body>, Status> {
+        let init = web_sys::RequestInit::new();
+        init.set_method("POST");
+        let request_headers = web_sys::Headers::new().map_err(|err| js_status(&err))?;
+        for (name, value) in headers {
+            // Browsers own these; everything else (metadata, auth) is forwarded.
+            if matches!(name.as_str(), "content-type" | "te" | "user-agent") {
+                continue;
+            }
+            if let Ok(value) = value.to_str() {
+                request_headers.append(name.as_str(), value).map_err(|err| js_status(&err))?;
+            }
+        }
+        request_headers.set("content-type", GRPC_WEB_CONTENT_TYPE).map_err(|err| js_status(&err))?;
+        request_headers.set("x-grpc-web", "1").map_err(|err| js_status(&err))?;
+        init.set_headers(&request_headers);
+        init.set_body(&Uint8Array::from(payload));
+
+        let request = web_sys::Request::new_with_str_and_init(url, &init).map_err(|err| js_status(&err))?;
+        let window = web_sys::window().ok_or_else(|| Status::unavailable("fetch needs a browser window"))?;
+        let response = JsFuture::from(window.fetch_with_request(&request)).await.map_err(|err| js_status(&err))?;
+        let response: web_sys::Response = response.dyn_into().map_err(|err| js_status(&err))?;
+
+        let mut builder = http::Response::builder().status(response.status());
+        for name in ["grpc-status", "grpc-message", "content-type"] {
+            if let Ok(Some(value)) = response.headers().get(name) {
+                builder = builder.header(name, value);
+            }
+        }
+        let buffer = JsFuture::from(response.array_buffer().map_err(|err| js_status(&err))?).await.map_err(|err| js_status(&err))?;
+        let body = Bytes::from(Uint8Array::new(&buffer).to_vec());
+        let response = builder.body(body).map_err(|err| Status::internal(format!("{err}")))?;
+        decode_response(response)
+    }
+}
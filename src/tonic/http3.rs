@@ -0,0 +1,211 @@
+//! Experimental HTTP/3 (QUIC) transport for `#[proto_rpc]` services (`http3` feature).
+//!
+//! gRPC framing is unchanged: requests and responses keep the length-delimited `ProtoCodec`
+//! bodies and the status trailers, only carried over an `h3` stream on a `quinn` connection.
+//!
+//! - [`H3Channel`] is a `GrpcService` over one QUIC connection, so a generated client is built
+//!   with `FooClient::new(H3Channel::connect(&endpoint, addr, "example.com").await?)`.
+//! - [`serve_http3`] accepts connections on a `quinn::Endpoint` and dispatches every request
+//!   through a generated server.
+//!
+//! TLS is configured on the `quinn` endpoints. Bodies are forwarded frame by frame, so streaming
+//! responses are delivered as they are produced.
+
+use core::convert::Infallible;
+use core::fmt;
+use core::net::SocketAddr;
+use core::task::Context;
+use core::task::Poll;
+
+use bytes::Buf;
+use bytes::Bytes;
+use h3::client::SendRequest;
+use h3::quic::RecvStream;
+use h3::quic::SendStream;
+use http_body_util::BodyExt;
+use http_body_util::channel::Channel;
+use http_body_util::channel::Sender;
+use tonic::Status;
+use tonic::body::Body;
+use tonic::codegen::BoxFuture;
+use tonic::codegen::Service;
+use tonic::codegen::http;
+
+use crate::alloc::boxed::Box;
+use crate::alloc::format;
+use crate::alloc::string::ToString;
+
+type BoxError = Box<dyn core::error::Error + Send + Sync>;
+
+/// Frames buffered between an h3 stream and the http body it feeds.
+const BODY_BUFFER: usize = 16;
+
+/// `GrpcService` that sends each call as one HTTP/3 request stream.
+#[derive(Clone)]
+pub struct H3Channel {
+    sender: SendRequest<h3_quinn::OpenStreams, Bytes>,
+    authority: http::uri::Authority,
+}
+
+impl fmt::Debug for H3Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("H3Channel").field("authority", &self.authority).finish_non_exhaustive()
+    }
+}
+
+impl H3Channel {
+    /// Connects to `addr` through `endpoint`, verifying the server certificate for `server_name`.
+    pub async fn connect(endpoint: &quinn::Endpoint, addr: SocketAddr, server_name: &str) -> Result<Self, BoxError> {
+        let connection = endpoint.connect(addr, server_name)?.await?;
+        let (mut driver, sender) = h3::client::new(h3_quinn::Connection::new(connection)).await?;
+        tokio::spawn(async move { core::future::poll_fn(|cx| driver.poll_close(cx)).await });
+        let authority = format!("{server_name}:{}", addr.port()).parse()?;
+        Ok(Self { sender, authority })
+    }
+}
+
+impl Service<http::Request<Body>> for H3Channel {
+    type Response = http::Response<Body>;
+    type Error = BoxError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let mut sender = self.sender.clone();
+        let authority = self.authority.clone();
+        Box::pin(async move {
+            let (mut parts, body) = request.into_parts();
+            let path = parts.uri.path_and_query().cloned().ok_or("request has no path")?;
+            parts.uri = http::Uri::builder().scheme("https").authority(authority).path_and_query(path).build()?;
+
+            let stream = sender.send_request(http::Request::from_parts(parts, ())).await?;
+            let (mut send, mut recv) = stream.split();
+            tokio::spawn(async move {
+                if pump_request(&mut send, body).await.is_err() {
+                    send.stop_stream(h3::error::Code::H3_REQUEST_CANCELLED);
+                }
+            });
+
+            let response = recv.recv_response().await?;
+            let (mut tx, body) = Channel::new(BODY_BUFFER);
+            tokio::spawn(async move {
+                if let Err(status) = pump_response(&mut recv, &mut tx).await {
+                    tx.abort(status);
+                }
+            });
+            Ok(response.map(|()| Body::new(body)))
+        })
+    }
+}
+
+/// Sends a client request body, then finishes the stream.
+async fn pump_request<S: SendStream<Bytes>>(stream: &mut h3::client::RequestStream<S, Bytes>, mut body: Body) -> Result<(), BoxError> {
+    while let Some(frame) = body.frame().await {
+        if let Ok(data) = frame?.into_data() {
+            stream.send_data(data).await?;
+        }
+    }
+    stream.finish().await?;
+    Ok(())
+}
+
+/// Forwards response data and trailers from the h3 stream into `tx`.
+async fn pump_response<S: RecvStream>(
+    stream: &mut h3::client::RequestStream<S, Bytes>,
+    tx: &mut Sender<Bytes, Status>,
+) -> Result<(), Status> {
+    let failed = |err: h3::error::StreamError| Status::unavailable(err.to_string());
+    while let Some(mut data) = stream.recv_data().await.map_err(failed)? {
+        if tx.send_data(data.copy_to_bytes(data.remaining())).await.is_err() {
+            return Ok(());
+        }
+    }
+    if let Some(trailers) = stream.recv_trailers().await.map_err(failed)? {
+        let _ = tx.send_trailers(trailers).await;
+    }
+    Ok(())
+}
+
+/// Accepts QUIC connections on `endpoint` and serves every request with `service` until the
+/// endpoint is closed.
+pub async fn serve_http3<S>(service: S, endpoint: quinn::Endpoint)
+where
+    S: Service<http::Request<Body>, Response = http::Response<Body>, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    while let Some(incoming) = endpoint.accept().await {
+        let service = service.clone();
+        tokio::spawn(async move {
+            if let Ok(connection) = incoming.await {
+                let _ = serve_connection(service, connection).await;
+            }
+        });
+    }
+}
+
+async fn serve_connection<S>(service: S, connection: quinn::Connection) -> Result<(), BoxError>
+where
+    S: Service<http::Request<Body>, Response = http::Response<Body>, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    let mut connection = h3::server::Connection::<_, Bytes>::new(h3_quinn::Connection::new(connection)).await?;
+    while let Some(resolver) = connection.accept().await? {
+        let mut service = service.clone();
+        tokio::spawn(async move {
+            let Ok((head, stream)) = resolver.resolve_request().await else {
+                return;
+            };
+            let (mut send, mut recv) = stream.split();
+            let (mut tx, body) = Channel::new(BODY_BUFFER);
+            tokio::spawn(async move {
+                if let Err(status) = pump_server_request(&mut recv, &mut tx).await {
+                    tx.abort(status);
+                }
+            });
+
+            let request = head.map(|()| Body::new(body));
+            let Ok(()) = core::future::poll_fn(|cx| service.poll_ready(cx)).await;
+            let Ok(response) = service.call(request).await;
+            if send_server_response(&mut send, response).await.is_err() {
+                send.stop_stream(h3::error::Code::H3_INTERNAL_ERROR);
+            }
+        });
+    }
+    Ok(())
+}
+
+async fn pump_server_request<S: RecvStream>(
+    stream: &mut h3::server::RequestStream<S, Bytes>,
+    tx: &mut Sender<Bytes, Status>,
+) -> Result<(), Status> {
+    let failed = |err: h3::error::StreamError| Status::cancelled(err.to_string());
+    while let Some(mut data) = stream.recv_data().await.map_err(failed)? {
+        if tx.send_data(data.copy_to_bytes(data.remaining())).await.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+async fn send_server_response<S: SendStream<Bytes>>(
+    stream: &mut h3::server::RequestStream<S, Bytes>,
+    response: http::Response<Body>,
+) -> Result<(), BoxError> {
+    let (parts, mut body) = response.into_parts();
+    stream.send_response(http::Response::from_parts(parts, ())).await?;
+    while let Some(frame) = body.frame().await {
+        match frame?.into_data() {
+            Ok(data) => stream.send_data(data).await?,
+            Err(frame) => {
+                if let Ok(trailers) = frame.into_trailers() {
+                    stream.send_trailers(trailers).await?;
+                }
+            }
+        }
+    }
+    stream.finish().await?;
+    Ok(())
+}
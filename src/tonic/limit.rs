@@ -0,0 +1,96 @@
+//! Capacity controls for servers declared with `concurrency_limit` / `rate_limit`.
+//!
+//! These mirror tower's `ConcurrencyLimit` and `RateLimit` layers, but keep their state behind an
+//! `Arc` so every clone of the generated server (tonic clones one per connection) draws from the
+//! same budget.
+
+use core::time::Duration;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+
+use crate::alloc::sync::Arc;
+
+/// Shared limits checked by the generated server before each call is dispatched.
+#[derive(Debug, Clone, Default)]
+pub struct ServiceLimits {
+    concurrency: Option<Arc<Semaphore>>,
+    rate: Option<Arc<RateLimiter>>,
+}
+
+/// Held for the lifetime of a call; releases the concurrency slot on drop.
+#[derive(Debug)]
+pub struct LimitPermit {
+    _concurrency: Option<OwnedSemaphorePermit>,
+}
+
+impl ServiceLimits {
+    /// `concurrency` caps in-flight calls; `rate` allows `num` calls per `per` window.
+    pub fn new(concurrency: Option<usize>, rate: Option<(u64, Duration)>) -> Self {
+        Self {
+            concurrency: concurrency.map(|limit| Arc::new(Semaphore::new(limit))),
+            rate: rate.map(|(num, per)| Arc::new(RateLimiter::new(num, per))),
+        }
+    }
+
+    /// Waits until both the rate window and the concurrency limit admit another call.
+    pub async fn acquire(&self) -> LimitPermit {
+        if let Some(rate) = &self.rate {
+            rate.acquire().await;
+        }
+        let concurrency = match &self.concurrency {
+            // The semaphore is never closed, so acquiring cannot fail.
+            Some(semaphore) => semaphore.clone().acquire_owned().await.ok(),
+            None => None,
+        };
+        LimitPermit { _concurrency: concurrency }
+    }
+}
+
+/// Fixed-window limiter: `num` calls per `per`, matching tower's `RateLimit`.
+#[derive(Debug)]
+struct RateLimiter {
+    num: u64,
+    per: Duration,
+    window: Mutex<Window>,
+}
+
+#[derive(Debug)]
+struct Window {
+    until: Instant,
+    remaining: u64,
+}
+
+impl RateLimiter {
+    fn new(num: u64, per: Duration) -> Self {
+        Self {
+            num,
+            per,
+            window: Mutex::new(Window {
+                until: Instant::now() + per,
+                remaining: num,
+            }),
+        }
+    }
+
+    async fn acquire(&self) {
+        loop {
+            let wait_until = {
+                let mut window = self.window.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+                let now = Instant::now();
+                if now >= window.until {
+                    window.until = now + self.per;
+                    window.remaining = self.num;
+                }
+                if window.remaining > 0 {
+                    window.remaining -= 1;
+                    return;
+                }
+                window.until
+            };
+            tokio::time::sleep_until(wait_until.into()).await;
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Assemble many generated servers into one `tonic` router.
+//!
+//! Large binaries register every constructed service once in a [`ServiceRegistry`] and hand it to
+//! the server builder in a single call instead of maintaining an `add_service` chain. With the
+//! `build-schemas` feature the registry can be checked against the services discovered through
+//! the schema inventory, so a forgotten registration shows up at startup.
+
+use core::convert::Infallible;
+
+use tonic::body::Body;
+use tonic::codegen::Service;
+use tonic::codegen::http;
+use tonic::server::NamedService;
+use tonic::service::Routes;
+use tonic::service::RoutesBuilder;
+use tonic::transport::Server;
+use tonic::transport::server::Router;
+
+use crate::alloc::vec::Vec;
+
+/// Type-erased set of constructed gRPC services.
+#[derive(Debug, Default)]
+pub struct ServiceRegistry {
+    routes: RoutesBuilder,
+    names: Vec<&'static str>,
+}
+
+impl ServiceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a constructed service (e.g. `GreeterServer::new(svc)`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if a service with the same gRPC name is already registered.
+    pub fn register<S>(&mut self, service: S) -> &mut Self
+    where
+        S: Service<http::Request<Body>, Response = http::Response<Body>, Error = Infallible> + NamedService + Clone + Send + Sync + 'static,
+        S::Future: Send + 'static,
+    {
+        assert!(!self.names.contains(&S::NAME), "service `{}` is registered twice", S::NAME);
+        self.names.push(S::NAME);
+        self.routes.add_service(service);
+        self
+    }
+
+    /// Builder-style [`ServiceRegistry::register`].
+    #[must_use]
+    pub fn with<S>(mut self, service: S) -> Self
+    where
+        S: Service<http::Request<Body>, Response = http::Response<Body>, Error = Infallible> + NamedService + Clone + Send + Sync + 'static,
+        S::Future: Send + 'static,
+    {
+        self.register(service);
+        self
+    }
+
+    /// gRPC names (`package.Service`) of the registered services, in registration order.
+    pub fn names(&self) -> &[&'static str] {
+        &self.names
+    }
+
+    pub const fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn into_routes(self) -> Routes {
+        self.routes.routes()
+    }
+
+    /// Adds every registered service to `builder`.
+    pub fn serve_all<L: Clone>(self, builder: &mut Server<L>) -> Router<L> {
+        builder.add_routes(self.into_routes())
+    }
+
+    /// Services found in the schema inventory that were not registered.
+    #[cfg(feature = "build-schemas")]
+    pub fn missing_services(&self) -> Vec<crate::alloc::string::String> {
+        crate::schemas::service_names().into_iter().filter(|name| !self.names.contains(&name.as_str())).collect()
+    }
+}
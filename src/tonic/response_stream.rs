@@ -0,0 +1,219 @@
+//! Consumption helpers for the response streams of generated clients.
+//!
+//! Server-streaming client methods return `Response<impl Stream<Item = Result<T, Status>>>`.
+//! [`ResponseStreamExt`] covers the usual ways of reading one without hand-built combinator
+//! stacks, and every generated client module re-exports it:
+//!
+//! ```rust,ignore
+//! use orders_client::ResponseStreamExt;
+//!
+//! let fills = client.fills(request).await?.into_inner().collect_all(1_000).await?;
+//! let ticks = client.ticks(request).await?.into_inner().into_timeout_stream(Duration::from_secs(5));
+//! ```
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::Context;
+use core::task::Poll;
+use core::time::Duration;
+
+use tokio::time::Instant;
+use tokio::time::Sleep;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::BoxStream;
+use tonic::codegen::tokio_stream::Stream;
+use tonic::codegen::tokio_stream::StreamExt;
+
+use crate::alloc::boxed::Box;
+use crate::alloc::format;
+use crate::alloc::vec::Vec;
+
+type Reopening<T> = Pin<Box<dyn Future<Output = Result<BoxStream<T>, Status>> + Send>>;
+type ReopenFn<T, K> = Box<dyn FnMut(Option<&K>, &Status) -> Option<Reopening<T>> + Send>;
+
+/// Adapters for `Stream<Item = Result<T, Status>>`, implemented for every such stream.
+pub trait ResponseStreamExt<T>: Stream<Item = Result<T, Status>> + Send + Sized + 'static {
+    /// Reads the whole stream into a `Vec`.
+    ///
+    /// Fails with the first error status, or with `ResourceExhausted` once more than `limit`
+    /// messages arrive.
+    fn collect_all(self, limit: usize) -> impl Future<Output = Result<Vec<T>, Status>> + Send
+    where
+        T: Send,
+    {
+        async move {
+            let mut stream = Box::pin(self);
+            let mut items = Vec::new();
+            while let Some(item) = stream.next().await {
+                if items.len() == limit {
+                    return Err(Status::resource_exhausted(format!("stream yielded more than {limit} messages")));
+                }
+                items.push(item?);
+            }
+            Ok(items)
+        }
+    }
+
+    /// Ends the stream with `DeadlineExceeded` when no message arrives within `idle` of the
+    /// previous one (or of the call, for the first message).
+    fn into_timeout_stream(self, idle: Duration) -> TimeoutStream<Self> {
+        TimeoutStream {
+            stream: Box::pin(self),
+            sleep: Box::pin(tokio::time::sleep(idle)),
+            idle,
+            done: false,
+        }
+    }
+
+    /// Reopens the stream after an error, resuming from the last message received.
+    ///
+    /// `token` extracts a resume token (a cursor, sequence number or offset) from every message.
+    /// When the stream fails, `reopen` receives the token of the last message, if any, and the
+    /// error; it returns the call that continues the stream, or `None` to pass the error through
+    /// and end. A failed reopen is handed to `reopen` again, so it should count its attempts.
+    fn resume_with<K, Tok, F, Fut, S>(self, token: Tok, mut reopen: F) -> Resumable<T, K, Tok>
+    where
+        T: 'static,
+        Tok: FnMut(&T) -> K,
+        F: FnMut(Option<&K>, &Status) -> Option<Fut> + Send + 'static,
+        Fut: Future<Output = Result<Response<S>, Status>> + Send + 'static,
+        S: Stream<Item = Result<T, Status>> + Send + 'static,
+    {
+        Resumable {
+            stream: Some(Box::pin(self)),
+            reopening: None,
+            last: None,
+            token,
+            reopen: Box::new(move |last: Option<&K>, status: &Status| {
+                reopen(last, status).map(|call| -> Reopening<T> {
+                    Box::pin(async move { call.await.map(|response| -> BoxStream<T> { Box::pin(response.into_inner()) }) })
+                })
+            }),
+        }
+    }
+}
+
+impl<T, S> ResponseStreamExt<T> for S where S: Stream<Item = Result<T, Status>> + Send + 'static {}
+
+/// Stream returned by [`ResponseStreamExt::into_timeout_stream`].
+pub struct TimeoutStream<S> {
+    stream: Pin<Box<S>>,
+    sleep: Pin<Box<Sleep>>,
+    idle: Duration,
+    done: bool,
+}
+
+impl<S> core::fmt::Debug for TimeoutStream<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TimeoutStream").field("idle", &self.idle).field("done", &self.done).finish_non_exhaustive()
+    }
+}
+
+impl<T, S> Stream for TimeoutStream<S>
+where
+    S: Stream<Item = Result<T, Status>>,
+{
+    type Item = Result<T, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        match self.stream.as_mut().poll_next(cx) {
+            Poll::Ready(Some(item)) => {
+                let deadline = Instant::now() + self.idle;
+                self.sleep.as_mut().reset(deadline);
+                Poll::Ready(Some(item))
+            }
+            Poll::Ready(None) => {
+                self.done = true;
+                Poll::Ready(None)
+            }
+            Poll::Pending => {
+                if self.sleep.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                self.done = true;
+                Poll::Ready(Some(Err(Status::deadline_exceeded(format!("no message within {:?}", self.idle)))))
+            }
+        }
+    }
+}
+
+/// Stream returned by [`ResponseStreamExt::resume_with`].
+pub struct Resumable<T, K, Tok> {
+    stream: Option<BoxStream<T>>,
+    reopening: Option<Reopening<T>>,
+    last: Option<K>,
+    token: Tok,
+    reopen: ReopenFn<T, K>,
+}
+
+// Nothing is pinned structurally: the stream and the reopen future are boxed.
+impl<T, K, Tok> Unpin for Resumable<T, K, Tok> {}
+
+impl<T, K, Tok> core::fmt::Debug for Resumable<T, K, Tok> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Resumable")
+            .field("reopening", &self.reopening.is_some())
+            .field("done", &(self.stream.is_none() && self.reopening.is_none()))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, K, Tok> Resumable<T, K, Tok> {
+    /// Starts a reopen for `status`, or hands the error back when `reopen` gives up.
+    fn recover(&mut self, status: Status) -> Option<Status> {
+        self.stream = None;
+        self.reopening = (self.reopen)(self.last.as_ref(), &status);
+        if self.reopening.is_some() { None } else { Some(status) }
+    }
+}
+
+impl<T, K, Tok> Stream for Resumable<T, K, Tok>
+where
+    Tok: FnMut(&T) -> K,
+{
+    type Item = Result<T, Status>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(reopening) = &mut this.reopening {
+                match reopening.as_mut().poll(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Ok(stream)) => {
+                        this.reopening = None;
+                        this.stream = Some(stream);
+                    }
+                    Poll::Ready(Err(status)) => {
+                        if let Some(status) = this.recover(status) {
+                            return Poll::Ready(Some(Err(status)));
+                        }
+                        continue;
+                    }
+                }
+            }
+            let Some(stream) = &mut this.stream else {
+                return Poll::Ready(None);
+            };
+            match stream.as_mut().poll_next(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(None) => {
+                    this.stream = None;
+                    return Poll::Ready(None);
+                }
+                Poll::Ready(Some(Ok(item))) => {
+                    this.last = Some((this.token)(&item));
+                    return Poll::Ready(Some(Ok(item)));
+                }
+                Poll::Ready(Some(Err(status))) => {
+                    if let Some(status) = this.recover(status) {
+                        return Poll::Ready(Some(Err(status)));
+                    }
+                }
+            }
+        }
+    }
+}
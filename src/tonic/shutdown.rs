@@ -0,0 +1,124 @@
+//! Graceful shutdown support for generated servers.
+//!
+//! Every generated server carries a [`Drain`]. Each call holds an [`InFlight`] guard until its
+//! response body has been sent, streams included, and the drain is inserted into the request
+//! extensions so handlers (in particular long-lived streams) can watch [`Drain::cancelled`] and
+//! wind down. Calling [`Drain::drain`] flips the signal, after which new calls are rejected with
+//! `UNAVAILABLE`, and waits for in-flight calls to finish.
+
+use core::pin::pin;
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::Ordering;
+
+use http_body_util::BodyExt;
+use tokio::sync::Notify;
+use tonic::body::Body;
+use tonic::codegen::tokio_stream::StreamExt;
+
+use crate::alloc::boxed::Box;
+use crate::alloc::sync::Arc;
+use crate::dispatch::DispatchReply;
+
+/// Shared shutdown state of a generated server and all its clones.
+#[derive(Debug, Clone, Default)]
+pub struct Drain {
+    inner: Arc<DrainState>,
+}
+
+#[derive(Debug, Default)]
+struct DrainState {
+    in_flight: AtomicUsize,
+    draining: AtomicBool,
+    notify: Notify,
+}
+
+/// Marks one call as in flight until dropped.
+#[derive(Debug)]
+pub struct InFlight {
+    drain: Drain,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if self.drain.inner.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.drain.inner.notify.notify_waiters();
+        }
+    }
+}
+
+impl Drain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a call; the drain waits for the returned guard to drop.
+    pub fn guard(&self) -> InFlight {
+        self.inner.in_flight.fetch_add(1, Ordering::SeqCst);
+        InFlight { drain: self.clone() }
+    }
+
+    /// Registers a call unless the drain has started.
+    pub fn try_guard(&self) -> Option<InFlight> {
+        // Counted before the check, so a drain that starts concurrently either sees the call or
+        // the call sees the drain.
+        let guard = self.guard();
+        (!self.is_draining()).then_some(guard)
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.inner.in_flight.load(Ordering::SeqCst)
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.inner.draining.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once [`Drain::drain`] has been called.
+    pub async fn cancelled(&self) {
+        self.wait_until(Self::is_draining).await;
+    }
+
+    /// Signals cancellation without waiting: handlers see [`Drain::cancelled`] resolve and new
+    /// calls are rejected.
+    pub fn cancel(&self) {
+        self.inner.draining.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    /// Signals cancellation and waits until no call is in flight.
+    pub async fn drain(&self) {
+        self.cancel();
+        self.wait_until(|drain| drain.in_flight() == 0).await;
+    }
+
+    async fn wait_until(&self, done: impl Fn(&Self) -> bool) {
+        loop {
+            let mut notified = pin!(self.inner.notify.notified());
+            notified.as_mut().enable();
+            if done(self) {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Keeps `guard` until `body` is dropped, so a response stays in flight until it is fully sent.
+pub fn guard_body(body: Body, guard: InFlight) -> Body {
+    Body::new(body.map_frame(move |frame| {
+        let _in_flight = &guard;
+        frame
+    }))
+}
+
+/// Keeps `guard` until a streamed reply ends.
+pub fn guard_reply(reply: DispatchReply, guard: InFlight) -> DispatchReply {
+    match reply {
+        DispatchReply::Unary(bytes) => DispatchReply::Unary(bytes),
+        DispatchReply::Stream(stream) => DispatchReply::Stream(Box::pin(stream.map(move |item| {
+            let _in_flight = &guard;
+            item
+        }))),
+    }
+}
@@ -0,0 +1,149 @@
+//! Per-method message size accounting.
+//!
+//! Codecs built by generated clients and servers know their route, and report the size of every
+//! message they encode or decode to the hooks registered here. Sizes are the protobuf payload of
+//! one message, before gRPC framing and compression. With no hook registered the codec skips the
+//! accounting entirely.
+//!
+//! ```rust,ignore
+//! proto_rs::sizes::add_size_hook(|size| {
+//!     metrics::histogram!("grpc_message_bytes", "route" => size.route, "kind" => size.kind.as_str())
+//!         .record(size.bytes as f64);
+//! });
+//! ```
+//!
+//! [`SizeTotals`] is a ready-made hook that keeps per-route counters.
+
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::RwLock;
+
+use crate::alloc::boxed::Box;
+use crate::alloc::sync::Arc;
+use crate::alloc::vec::Vec;
+
+/// Which half of a call a message belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageKind {
+    Request,
+    Response,
+}
+
+impl MessageKind {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            MessageKind::Request => "request",
+            MessageKind::Response => "response",
+        }
+    }
+}
+
+/// One encoded or decoded message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageSize {
+    /// gRPC route of the method, e.g. `/pkg.Service/Method`.
+    pub route: &'static str,
+    pub kind: MessageKind,
+    pub bytes: usize,
+}
+
+type SizeHook = Box<dyn Fn(&MessageSize) + Send + Sync>;
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static HOOKS: RwLock<Vec<SizeHook>> = RwLock::new(Vec::new());
+
+/// Registers a callback invoked for every message encoded or decoded by a generated client or
+/// server. Hooks run on the codec's task, so they should be cheap.
+pub fn add_size_hook(hook: impl Fn(&MessageSize) + Send + Sync + 'static) {
+    let mut hooks = HOOKS.write().unwrap_or_else(std::sync::PoisonError::into_inner);
+    hooks.push(Box::new(hook));
+    ACTIVE.store(true, Ordering::Release);
+}
+
+/// Removes every registered hook.
+pub fn clear_size_hooks() {
+    let mut hooks = HOOKS.write().unwrap_or_else(std::sync::PoisonError::into_inner);
+    hooks.clear();
+    ACTIVE.store(false, Ordering::Release);
+}
+
+#[inline]
+pub(crate) fn is_active() -> bool {
+    ACTIVE.load(Ordering::Acquire)
+}
+
+pub(crate) fn record(route: &'static str, kind: MessageKind, bytes: usize) {
+    let size = MessageSize { route, kind, bytes };
+    let hooks = HOOKS.read().unwrap_or_else(std::sync::PoisonError::into_inner);
+    for hook in hooks.iter() {
+        hook(&size);
+    }
+}
+
+/// Message count and total bytes in one direction.
+#[derive(Debug, Default)]
+pub struct SizeCounter {
+    messages: AtomicU64,
+    bytes: AtomicU64,
+}
+
+impl SizeCounter {
+    fn add(&self, bytes: usize) {
+        self.messages.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn messages(&self) -> u64 {
+        self.messages.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+}
+
+/// Request and response counters of one route.
+#[derive(Debug, Default)]
+pub struct RouteSizes {
+    pub requests: SizeCounter,
+    pub responses: SizeCounter,
+}
+
+/// Per-route totals, fed by a size hook.
+#[derive(Debug, Default)]
+pub struct SizeTotals {
+    routes: Mutex<BTreeMap<&'static str, Arc<RouteSizes>>>,
+}
+
+impl SizeTotals {
+    /// Creates the totals and registers the hook that feeds them.
+    pub fn install() -> Arc<Self> {
+        let totals = Arc::new(Self::default());
+        let hook_totals = Arc::clone(&totals);
+        add_size_hook(move |size| hook_totals.record(size));
+        totals
+    }
+
+    pub fn record(&self, size: &MessageSize) {
+        let route = self.route(size.route);
+        match size.kind {
+            MessageKind::Request => route.requests.add(size.bytes),
+            MessageKind::Response => route.responses.add(size.bytes),
+        }
+    }
+
+    /// Counters for `route`, created on first use.
+    pub fn route(&self, route: &'static str) -> Arc<RouteSizes> {
+        let mut routes = self.routes.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        Arc::clone(routes.entry(route).or_default())
+    }
+
+    /// Routes seen so far with their counters.
+    pub fn routes(&self) -> Vec<(&'static str, Arc<RouteSizes>)> {
+        let routes = self.routes.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        routes.iter().map(|(route, sizes)| (*route, Arc::clone(sizes))).collect()
+    }
+}
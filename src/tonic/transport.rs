@@ -0,0 +1,108 @@
+//! Connection helpers used by generated clients.
+//!
+//! [`Connector`] accepts any tower service that turns a [`Uri`] into an IO stream, the
+//! same shape [`Endpoint::connect_with_connector`] expects. On unix targets
+//! [`UdsConnector`] dials a Unix domain socket instead of TCP.
+
+use core::future::Future;
+
+pub use hyper_util::rt::TokioIo;
+use tonic::codegen::Service;
+use tonic::codegen::http::Uri;
+use tonic::transport::Channel;
+use tonic::transport::Endpoint;
+use tonic::transport::Error;
+
+use crate::alloc::boxed::Box;
+
+type BoxError = Box<dyn core::error::Error + Send + Sync>;
+
+/// A custom transport for [`Endpoint`]s.
+///
+/// Implemented for every `Service<Uri>` whose response is a hyper IO stream. Tokio
+/// streams can be wrapped in [`TokioIo`].
+pub trait Connector: Send + 'static {
+    fn connect(self, endpoint: Endpoint) -> impl Future<Output = Result<Channel, Error>> + Send;
+}
+
+impl<C> Connector for C
+where
+    C: Service<Uri> + Send + 'static,
+    C::Response: hyper::rt::Read + hyper::rt::Write + Send + Unpin,
+    C::Future: Send,
+    BoxError: From<C::Error> + Send,
+{
+    async fn connect(self, endpoint: Endpoint) -> Result<Channel, Error> {
+        endpoint.connect_with_connector(self).await
+    }
+}
+
+#[cfg(unix)]
+pub use uds::UdsConnector;
+#[cfg(unix)]
+pub use uds::connect_uds;
+
+#[cfg(unix)]
+mod uds {
+    use core::pin::Pin;
+    use core::task::Context;
+    use core::task::Poll;
+    use std::io;
+    use std::path::Path;
+    use std::path::PathBuf;
+
+    use hyper_util::rt::TokioIo;
+    use tokio::net::UnixStream;
+    use tonic::codegen::Service;
+    use tonic::codegen::http::Uri;
+    use tonic::transport::Channel;
+    use tonic::transport::Endpoint;
+    use tonic::transport::Error;
+
+    use crate::alloc::boxed::Box;
+
+    /// Authority used for channels that never resolve their uri.
+    const UDS_PLACEHOLDER_URI: &str = "http://[::]:50051";
+
+    /// Connects every request to the same Unix domain socket, ignoring the uri.
+    #[derive(Debug, Clone)]
+    pub struct UdsConnector {
+        path: PathBuf,
+    }
+
+    impl UdsConnector {
+        pub fn new(path: impl AsRef<Path>) -> Self {
+            Self {
+                path: path.as_ref().to_path_buf(),
+            }
+        }
+
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+    }
+
+    impl Service<Uri> for UdsConnector {
+        type Response = TokioIo<UnixStream>;
+        type Error = io::Error;
+        type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _uri: Uri) -> Self::Future {
+            let path = self.path.clone();
+            Box::pin(async move { Ok(TokioIo::new(UnixStream::connect(path).await?)) })
+        }
+    }
+
+    /// Opens a [`Channel`] over the Unix domain socket at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the transport error if the socket cannot be reached.
+    pub async fn connect_uds(path: impl AsRef<Path>) -> Result<Channel, Error> {
+        Endpoint::from_static(UDS_PLACEHOLDER_URI).connect_with_connector(UdsConnector::new(path)).await
+    }
+}
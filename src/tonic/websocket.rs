@@ -0,0 +1,238 @@
+//! WebSocket transport for `#[proto_rpc]` services (`websocket` feature).
+//!
+//! For networks where HTTP/2 gRPC is not an option (browsers behind proxies, some load
+//! balancers), generated clients and servers can exchange calls as binary WebSocket messages:
+//!
+//! - [`WsChannel`] is a `GrpcService` over one WebSocket connection, so a generated client is
+//!   built with `FooClient::new(WsChannel::connect("ws://host/rpc").await?)`.
+//! - [`serve_websocket`] (or [`accept`] for a raw TCP stream) drives a connection against a
+//!   generated server, using its route table to dispatch.
+//!
+//! Each request message is `call id (u32) | route length (u16) | route | length-delimited body`, and
+//! each reply is `call id (u32)` followed by the same envelope as the [`bus`](crate::bus)
+//! transport. Calls are multiplexed by id. As with the bus, server-streaming replies are collected
+//! into one message, so those streams must be finite.
+
+use core::convert::Infallible;
+use core::task::Context;
+use core::task::Poll;
+use std::collections::HashMap;
+
+use bytes::Buf;
+use bytes::BufMut;
+use bytes::Bytes;
+use bytes::BytesMut;
+use futures_util::SinkExt;
+use futures_util::StreamExt;
+use http_body_util::BodyExt;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::Error as WsError;
+use tokio_tungstenite::tungstenite::Message;
+use tonic::Status;
+use tonic::body::Body;
+use tonic::codegen::BoxFuture;
+use tonic::codegen::Service;
+use tonic::codegen::http;
+
+use crate::alloc::boxed::Box;
+use crate::alloc::string::String;
+use crate::alloc::string::ToString;
+use crate::tonic::bus::call_route;
+use crate::tonic::bus::decode_reply;
+use crate::tonic::bus::encode_reply;
+use crate::tonic::bus::grpc_response;
+
+type BoxError = Box<dyn core::error::Error + Send + Sync>;
+
+fn encode_request(id: u32, route: &str, body: &[u8]) -> Result<Bytes, Status> {
+    let route_len = u16::try_from(route.len()).map_err(|_| Status::invalid_argument("route is too long"))?;
+    let mut buf = BytesMut::with_capacity(6 + route.len() + body.len());
+    buf.put_u32(id);
+    buf.put_u16(route_len);
+    buf.put_slice(route.as_bytes());
+    buf.put_slice(body);
+    Ok(buf.freeze())
+}
+
+fn decode_request(mut frame: Bytes) -> Option<(u32, String, Bytes)> {
+    if frame.remaining() < 6 {
+        return None;
+    }
+    let id = frame.get_u32();
+    let route_len = frame.get_u16() as usize;
+    if frame.remaining() < route_len {
+        return None;
+    }
+    let route = frame.split_to(route_len);
+    let route = core::str::from_utf8(&route).ok()?.to_string();
+    Some((id, route, frame))
+}
+
+fn encode_frame(id: u32, reply: &[u8]) -> Bytes {
+    let mut buf = BytesMut::with_capacity(4 + reply.len());
+    buf.put_u32(id);
+    buf.put_slice(reply);
+    buf.freeze()
+}
+
+struct PendingCall {
+    route: String,
+    payload: Bytes,
+    reply: oneshot::Sender<Result<Bytes, Status>>,
+}
+
+/// `GrpcService` that multiplexes calls over one WebSocket connection.
+///
+/// The connection is driven by a background task; clones share it. Once the socket closes every
+/// pending and future call fails with `Unavailable`.
+#[derive(Debug, Clone)]
+pub struct WsChannel {
+    calls: mpsc::UnboundedSender<PendingCall>,
+}
+
+impl WsChannel {
+    /// Spawns the task driving an established WebSocket connection.
+    pub fn new<T>(socket: WebSocketStream<T>) -> Self
+    where
+        T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (calls, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(drive_client(socket, receiver));
+        Self { calls }
+    }
+
+    /// Opens a `ws://` connection to `url`.
+    pub async fn connect(url: &str) -> Result<Self, WsError> {
+        let (socket, _) = tokio_tungstenite::connect_async(url).await?;
+        Ok(Self::new(socket))
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.calls.is_closed()
+    }
+}
+
+impl Service<http::Request<Body>> for WsChannel {
+    type Response = http::Response<Body>;
+    type Error = BoxError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let calls = self.calls.clone();
+        Box::pin(async move {
+            let route = request.uri().path().to_string();
+            let payload = request.into_body().collect().await?.to_bytes();
+            let (reply, receiver) = oneshot::channel();
+            let closed = || Status::unavailable("websocket connection closed");
+            let reply = match calls.send(PendingCall { route, payload, reply }) {
+                Ok(()) => receiver.await.unwrap_or_else(|_| Err(closed())),
+                Err(_) => Err(closed()),
+            };
+            let response = match reply.and_then(decode_reply) {
+                Ok((status, body)) => grpc_response(&status, body),
+                Err(status) => status.into_http(),
+            };
+            Ok(response)
+        })
+    }
+}
+
+async fn drive_client<T>(mut socket: WebSocketStream<T>, mut calls: mpsc::UnboundedReceiver<PendingCall>)
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut pending: HashMap<u32, oneshot::Sender<Result<Bytes, Status>>> = HashMap::new();
+    let mut next_id = 0u32;
+    loop {
+        tokio::select! {
+            call = calls.recv() => {
+                let Some(call) = call else {
+                    let _ = socket.close(None).await;
+                    break;
+                };
+                let id = next_id;
+                next_id = next_id.wrapping_add(1);
+                let frame = match encode_request(id, &call.route, &call.payload) {
+                    Ok(frame) => frame,
+                    Err(status) => {
+                        let _ = call.reply.send(Err(status));
+                        continue;
+                    }
+                };
+                if let Err(err) = socket.send(Message::Binary(frame)).await {
+                    let _ = call.reply.send(Err(Status::unavailable(err.to_string())));
+                    break;
+                }
+                pending.insert(id, call.reply);
+            }
+            message = socket.next() => match message {
+                Some(Ok(Message::Binary(mut frame))) if frame.len() >= 4 => {
+                    let id = frame.get_u32();
+                    if let Some(reply) = pending.remove(&id) {
+                        let _ = reply.send(Ok(frame));
+                    }
+                }
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    calls.close();
+    for (_, reply) in pending {
+        let _ = reply.send(Err(Status::unavailable("websocket connection closed")));
+    }
+}
+
+/// Serves calls arriving on an established WebSocket connection until the peer closes it.
+///
+/// Calls run concurrently; replies are written as they complete.
+pub async fn serve_websocket<S, T>(service: S, socket: WebSocketStream<T>) -> Result<(), WsError>
+where
+    S: Service<http::Request<Body>, Response = http::Response<Body>, Error = Infallible> + Clone + Send + Sync + 'static,
+    S::Future: Send + 'static,
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let (mut sink, mut stream) = socket.split();
+    let (replies, mut completed) = mpsc::unbounded_channel::<Bytes>();
+    loop {
+        tokio::select! {
+            message = stream.next() => match message {
+                Some(Ok(Message::Binary(frame))) => {
+                    let Some((id, route, payload)) = decode_request(frame) else {
+                        continue;
+                    };
+                    let service = service.clone();
+                    let replies = replies.clone();
+                    tokio::spawn(async move {
+                        let (status, body) = call_route(&service, &route, payload).await;
+                        let _ = replies.send(encode_frame(id, &encode_reply(&status, &body)));
+                    });
+                }
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(err)) => return Err(err),
+            },
+            Some(frame) = completed.recv() => sink.send(Message::Binary(frame)).await?,
+        }
+    }
+    Ok(())
+}
+
+/// Performs the WebSocket handshake on `stream` and serves it with [`serve_websocket`].
+pub async fn accept<S, T>(service: S, stream: T) -> Result<(), WsError>
+where
+    S: Service<http::Request<Body>, Response = http::Response<Body>, Error = Infallible> + Clone + Send + Sync + 'static,
+    S::Future: Send + 'static,
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let socket = tokio_tungstenite::accept_async(stream).await?;
+    serve_websocket(service, socket).await
+}
@@ -0,0 +1,115 @@
+//! `tracing` instrumentation of the wire codec (`wire-trace` feature).
+//!
+//! Events use the `proto_rs::wire` target:
+//!
+//! - every decoded field emits a `TRACE` event with the decoder type, tag, wire type, the field's
+//!   byte range within the top-level message and the time spent in it;
+//! - a field that fails to decode emits a `DEBUG` event with the same data and the error, once per
+//!   enclosing message, so the log shows the path down to the broken field;
+//! - every encoded message emits a `TRACE` event with its type, size and encode time.
+//!
+//! When neither level is enabled for the target the codec takes the untraced path.
+
+use core::any::type_name;
+use core::cell::Cell;
+
+use bytes::Buf;
+use tracing::Level;
+
+use crate::encoding::DecodeContext;
+use crate::encoding::decode_key;
+use crate::error::DecodeError;
+use crate::traits::ProtoDecoder;
+
+std::thread_local! {
+    /// `Buf::remaining` at the start of the top-level message being decoded.
+    static ORIGIN: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+pub(crate) fn enabled() -> bool {
+    tracing::enabled!(target: "proto_rs::wire", Level::DEBUG)
+}
+
+/// Marks the start of a top-level decode; offsets of its fields are relative to it.
+pub(crate) struct Origin {
+    prev: Option<usize>,
+}
+
+impl Origin {
+    pub(crate) fn enter(remaining: usize) -> Option<Self> {
+        enabled().then(|| Self {
+            prev: ORIGIN.replace(Some(remaining)),
+        })
+    }
+}
+
+impl Drop for Origin {
+    fn drop(&mut self) {
+        ORIGIN.set(self.prev);
+    }
+}
+
+fn offset(remaining: usize) -> usize {
+    ORIGIN.get().map_or(0, |origin| origin.saturating_sub(remaining))
+}
+
+/// `Instant` panics on wasm32-unknown-unknown, so durations are reported as zero there.
+struct Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,
+}
+
+impl Stopwatch {
+    fn start() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    fn elapsed_ns(&self) -> u64 {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            u64::try_from(self.start.elapsed().as_nanos()).unwrap_or(u64::MAX)
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            0
+        }
+    }
+}
+
+/// Traced counterpart of `ProtoDecoder::decode_one_field`.
+pub(crate) fn decode_field<T: ProtoDecoder>(value: &mut T, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+    let start = offset(buf.remaining());
+    let stopwatch = Stopwatch::start();
+    let mut key = None;
+    let result = decode_key(buf).and_then(|(tag, wire_type)| {
+        key = Some((tag, wire_type));
+        if tag == 0 {
+            return Err(DecodeError::new("invalid tag 0"));
+        }
+        T::merge_field(value, tag, wire_type, buf, ctx)
+    });
+    let end = offset(buf.remaining());
+    let elapsed_ns = stopwatch.elapsed_ns();
+    let ty = type_name::<T>();
+    let (tag, wire_type) = key.map_or((None, None), |(tag, wire_type)| (Some(tag), Some(wire_type)));
+    match &result {
+        Ok(()) => tracing::trace!(target: "proto_rs::wire", ty, tag, ?wire_type, start, end, elapsed_ns, "decoded field"),
+        Err(error) => tracing::debug!(target: "proto_rs::wire", ty, tag, ?wire_type, start, end, elapsed_ns, %error, "field decode failed"),
+    }
+    result
+}
+
+/// Runs an encode and reports the produced size.
+pub(crate) fn encode_message<T: ?Sized, R>(encode: impl FnOnce() -> R, len: impl FnOnce(&R) -> usize) -> R {
+    if !tracing::enabled!(target: "proto_rs::wire", Level::TRACE) {
+        return encode();
+    }
+    let stopwatch = Stopwatch::start();
+    let encoded = encode();
+    let elapsed_ns = stopwatch.elapsed_ns();
+    tracing::trace!(target: "proto_rs::wire", ty = type_name::<T>(), len = len(&encoded), elapsed_ns, "encoded message");
+    encoded
+}
@@ -2,11 +2,16 @@
 #![allow(clippy::wrong_self_convention)]
 
 pub use decode::DecodeIrBuilder;
+pub use decode::ProtoArrayMerge;
+pub use decode::ProtoCollectMerge;
 pub use decode::ProtoDecode;
 pub use decode::ProtoDecoder;
 pub use decode::ProtoDefault;
 pub use decode::ProtoFieldMerge;
+pub use decode::ProtoLossyMerge;
+pub use decode::ProtoNarrowMerge;
 pub use decode::ProtoShadowDecode;
+pub use decode::ProtoUniqueMerge;
 pub use encode::ArchivedProtoField;
 pub use encode::ArchivedProtoMessage;
 pub use encode::ArchivedProtoMessageWriter;
@@ -16,6 +21,7 @@ pub use encode::ProtoShadowEncode;
 pub use encode::ZeroCopy;
 pub use utils::PrimitiveKind;
 pub use utils::ProtoKind;
+pub use utils::add_field_max_len;
 pub use utils::const_test_validate_with_ext;
 pub use utils::const_unreachable;
 
@@ -30,6 +36,10 @@ mod utils;
 pub trait ProtoExt: Sized {
     const KIND: ProtoKind;
     const WIRE_TYPE: WireType = Self::KIND.wire_type();
+    /// Upper bound of the payload of any value, without field key or length prefix; `None` when
+    /// values can grow without limit. Derived messages sum their fields, see
+    /// [`add_field_max_len`].
+    const MAX_PAYLOAD_LEN: Option<usize> = Self::KIND.max_payload_len();
     const _REPEATED_SUPPORT: Option<&'static str> = None;
 
     const _TEST_REPEATED: () = {
@@ -42,4 +52,5 @@ pub trait ProtoExt: Sized {
 }
 impl<T: ProtoExt> ProtoExt for &T {
     const KIND: ProtoKind = T::KIND;
+    const MAX_PAYLOAD_LEN: Option<usize> = T::MAX_PAYLOAD_LEN;
 }
@@ -1,3 +1,9 @@
+mod strategy;
+
+pub use strategy::BufferStrategy;
+
+use crate::error::EncodeError;
+
 /// Reverse writer trait (keeps your existing API shape).
 pub trait RevWriter {
     type RawBuf;
@@ -7,6 +13,15 @@ pub trait RevWriter {
     fn with_capacity(cap: usize) -> Self;
     fn empty() -> Self;
 
+    /// A writer of `cap` bytes that grows as `strategy` says.
+    #[inline]
+    fn with_strategy(cap: usize, _strategy: &BufferStrategy) -> Self
+    where
+        Self: Sized,
+    {
+        Self::with_capacity(cap)
+    }
+
     fn mark(&self) -> Self::Mark;
     fn written_since(&self, mark: Self::Mark) -> usize;
     fn as_written_slice(&self) -> &[u8];
@@ -34,6 +49,7 @@ pub trait RevWriter {
 pub struct RevVec {
     buf: Vec<u8>,
     pos: usize, // valid bytes are in [pos..cap)
+    strategy: BufferStrategy,
 }
 
 impl RevVec {
@@ -53,10 +69,11 @@ impl RevVec {
         let old_cap = self.cap();
         let used = old_cap - self.pos;
 
-        let mut new_cap = (old_cap * 2).next_power_of_two().max(Self::MIN_GROW);
-        while new_cap < used + need {
-            new_cap *= 2;
-        }
+        let new_cap = if old_cap == 0 {
+            Self::MIN_GROW.max(need)
+        } else {
+            self.strategy.grow(old_cap, used + need)
+        };
 
         let mut new_buf: Vec<u8> = Vec::with_capacity(new_cap);
         let new_cap = new_buf.capacity();
@@ -81,12 +98,27 @@ impl RevWriter for RevVec {
         let mut buf = Vec::with_capacity(cap);
         let cap = buf.capacity();
         unsafe { buf.set_len(cap) }; // invariant: len == cap
-        Self { buf, pos: cap }
+        Self {
+            buf,
+            pos: cap,
+            strategy: BufferStrategy::DEFAULT,
+        }
     }
 
     #[inline]
     fn empty() -> Self {
-        Self { buf: Vec::new(), pos: 0 }
+        Self {
+            buf: Vec::new(),
+            pos: 0,
+            strategy: BufferStrategy::DEFAULT,
+        }
+    }
+
+    #[inline]
+    fn with_strategy(cap: usize, strategy: &BufferStrategy) -> Self {
+        let mut w = Self::with_capacity(cap);
+        w.strategy = *strategy;
+        w
     }
 
     #[inline]
@@ -177,6 +209,126 @@ impl RevWriter for RevVec {
             }
         }
         unsafe { self.buf.set_len(len) };
+        self.strategy.trim(&mut self.buf);
         self.buf
     }
 }
+
+/// Reverse writer over a `[u8; N]` held inline, for encoding without allocating.
+///
+/// Bytes past the first `N` are counted but not stored, so a buffer that turns out too small is
+/// reported by [`ProtoEncode::encode_to_array`](crate::ProtoEncode::encode_to_array) instead of
+/// panicking mid-write.
+#[derive(Debug)]
+pub struct RevArray<const N: usize> {
+    buf: [u8; N],
+    /// Bytes written so far, including any that did not fit.
+    len: usize,
+}
+
+impl<const N: usize> RevArray<N> {
+    /// The encoded bytes.
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf[N - self.len.min(N)..]
+    }
+
+    /// Fails with the length that was needed when the writes did not fit.
+    #[inline]
+    pub(crate) const fn checked(self) -> Result<Self, EncodeError> {
+        if self.len > N {
+            return Err(EncodeError::new(self.len, N));
+        }
+        Ok(self)
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for RevArray<N> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> RevWriter for RevArray<N> {
+    type TightBuf = Self;
+    type RawBuf = Self;
+    type Mark = usize;
+
+    #[inline]
+    fn with_capacity(_cap: usize) -> Self {
+        Self::empty()
+    }
+
+    #[inline]
+    fn empty() -> Self {
+        Self { buf: [0; N], len: 0 }
+    }
+
+    #[inline]
+    fn mark(&self) -> Self::Mark {
+        self.len
+    }
+
+    #[inline]
+    fn written_since(&self, mark: Self::Mark) -> usize {
+        self.len - mark
+    }
+
+    #[inline]
+    fn as_written_slice(&self) -> &[u8] {
+        self.as_slice()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    fn put_u8(&mut self, b: u8) {
+        self.put_slice(&[b]);
+    }
+
+    #[inline]
+    fn put_slice(&mut self, s: &[u8]) {
+        let end = N.saturating_sub(self.len);
+        self.len += s.len();
+        if self.len <= N {
+            self.buf[end - s.len()..end].copy_from_slice(s);
+        }
+    }
+
+    #[inline]
+    fn put_varint(&mut self, mut v: u64) {
+        let mut tmp = [0u8; 10];
+        let mut i = 0usize;
+        loop {
+            let byte = (v as u8) & 0x7f;
+            v >>= 7;
+            if v == 0 {
+                tmp[i] = byte;
+                i += 1;
+                break;
+            }
+            tmp[i] = byte | 0x80;
+            i += 1;
+        }
+        self.put_slice(&tmp[..i]);
+    }
+
+    #[inline]
+    fn finish_raw(self) -> Self::RawBuf {
+        self
+    }
+
+    #[inline]
+    fn finish_tight(self) -> Self::TightBuf {
+        self
+    }
+}
@@ -0,0 +1,137 @@
+//! Sizing policy of encode buffers.
+//!
+//! Messages with a size hint are archived into a buffer of exactly that size; the strategy covers
+//! the rest: the first allocation of types without a hint, how fast a buffer grows once it runs out
+//! of space, and how much spare capacity an encoded `Vec` may keep.
+//!
+//! ```rust,ignore
+//! // Acks are tiny, snapshots are huge.
+//! BufferStrategy::new(16).register::<Ack>();
+//! BufferStrategy::new(1 << 20).with_growth_factor(4).register::<Snapshot>();
+//! BufferStrategy::new(256).with_max_retained(64 * 1024).set_global();
+//! ```
+
+use core::any::type_name;
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::Ordering;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+/// Initial capacity, growth factor and retention limit of an encode buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferStrategy {
+    /// Capacity of a fresh buffer when the message gives no size hint.
+    pub initial_capacity: usize,
+    /// Factor the capacity is multiplied by whenever the buffer is full; values below 2 act as 2.
+    pub growth_factor: usize,
+    /// Spare capacity above this is released before `encode_to_vec` returns. With the tonic
+    /// encoder, a batch of streamed messages is flushed once it reaches this size.
+    pub max_retained: usize,
+}
+
+static GLOBAL_SET: AtomicBool = AtomicBool::new(false);
+static GLOBAL: RwLock<BufferStrategy> = RwLock::new(BufferStrategy::DEFAULT);
+static HAS_TYPES: AtomicBool = AtomicBool::new(false);
+static TYPES: RwLock<BTreeMap<&'static str, BufferStrategy>> = RwLock::new(BTreeMap::new());
+
+impl BufferStrategy {
+    pub const DEFAULT: Self = Self {
+        initial_capacity: 64,
+        growth_factor: 2,
+        max_retained: usize::MAX,
+    };
+
+    pub const fn new(initial_capacity: usize) -> Self {
+        Self {
+            initial_capacity,
+            ..Self::DEFAULT
+        }
+    }
+
+    #[must_use]
+    pub const fn with_growth_factor(mut self, growth_factor: usize) -> Self {
+        self.growth_factor = growth_factor;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_max_retained(mut self, max_retained: usize) -> Self {
+        self.max_retained = max_retained;
+        self
+    }
+
+    /// Makes this the strategy of every type without its own.
+    pub fn set_global(self) {
+        *GLOBAL.write().unwrap_or_else(std::sync::PoisonError::into_inner) = self;
+        GLOBAL_SET.store(true, Ordering::Release);
+    }
+
+    /// The global strategy, [`DEFAULT`](Self::DEFAULT) unless replaced.
+    pub fn global() -> Self {
+        if !GLOBAL_SET.load(Ordering::Acquire) {
+            return Self::DEFAULT;
+        }
+        *GLOBAL.read().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Uses this strategy for messages of type `T`.
+    ///
+    /// Types are told apart by [`type_name`], so lifetimes are ignored.
+    pub fn register<T: ?Sized>(self) {
+        let mut types = TYPES.write().unwrap_or_else(std::sync::PoisonError::into_inner);
+        types.insert(type_name::<T>(), self);
+        HAS_TYPES.store(true, Ordering::Release);
+    }
+
+    /// Removes the strategy registered for `T`.
+    pub fn unregister<T: ?Sized>() {
+        let mut types = TYPES.write().unwrap_or_else(std::sync::PoisonError::into_inner);
+        types.remove(type_name::<T>());
+        HAS_TYPES.store(!types.is_empty(), Ordering::Release);
+    }
+
+    /// Drops every registered strategy and restores the default global one.
+    pub fn reset() {
+        TYPES.write().unwrap_or_else(std::sync::PoisonError::into_inner).clear();
+        HAS_TYPES.store(false, Ordering::Release);
+        *GLOBAL.write().unwrap_or_else(std::sync::PoisonError::into_inner) = Self::DEFAULT;
+        GLOBAL_SET.store(false, Ordering::Release);
+    }
+
+    /// Strategy used for `T`: its own when registered, the global one otherwise.
+    pub fn for_type<T: ?Sized>() -> Self {
+        if HAS_TYPES.load(Ordering::Acquire) {
+            let types = TYPES.read().unwrap_or_else(std::sync::PoisonError::into_inner);
+            if let Some(strategy) = types.get(type_name::<T>()) {
+                return *strategy;
+            }
+        }
+        Self::global()
+    }
+
+    /// The global strategy, if one was set.
+    #[cfg(feature = "tonic")]
+    pub(crate) fn configured_global() -> Option<Self> {
+        GLOBAL_SET.load(Ordering::Acquire).then(Self::global)
+    }
+
+    /// Capacity after growing a full buffer of `capacity` bytes that needs `needed` in total.
+    #[inline]
+    pub(crate) fn grow(&self, capacity: usize, needed: usize) -> usize {
+        capacity.saturating_mul(self.growth_factor.max(2)).max(needed)
+    }
+
+    /// Releases spare capacity of an encoded buffer above [`max_retained`](Self::max_retained).
+    #[inline]
+    pub(crate) fn trim(&self, buf: &mut Vec<u8>) {
+        if buf.capacity() > self.max_retained {
+            buf.shrink_to(self.max_retained);
+        }
+    }
+}
+
+impl Default for BufferStrategy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
@@ -46,6 +46,17 @@ pub trait ProtoDecoder: ProtoExt {
         Ok(())
     }
 
+    /// Merge a field occurrence under `TAG`, together with the occurrences of the same field that
+    /// directly follow it.
+    ///
+    /// The derive macro calls this for map and set fields. Collections with a per-call cost, like
+    /// the guard of a `papaya` map, override it to decode the whole run at once; the default
+    /// merges the single occurrence at hand.
+    #[inline]
+    fn merge_run<const TAG: u32>(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        Self::merge(self, wire_type, buf, ctx)
+    }
+
     ///top level decode entrypoint
     /// Decode a whole message from a buffer (top-level, not length-delimited wrapper).
     #[inline]
@@ -55,6 +66,8 @@ pub trait ProtoDecoder: ProtoExt {
     {
         // Check recursion limit at top-level entry
         ctx.limit_reached()?;
+        #[cfg(feature = "wire-trace")]
+        let _origin = crate::trace::Origin::enter(buf.remaining());
         let mut sh = <Self as ProtoDefault>::proto_default();
         Self::decode_into(&mut sh, &mut buf, ctx)?;
         Ok(sh)
@@ -72,6 +85,10 @@ pub trait ProtoDecoder: ProtoExt {
     /// must be checked before the first call to this function (it's checked in `merge` before recursion).
     #[inline]
     fn decode_one_field(value: &mut Self, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        #[cfg(feature = "wire-trace")]
+        if crate::trace::enabled() {
+            return crate::trace::decode_field(value, buf, ctx);
+        }
         let (tag, wire) = decode_key(buf)?;
         if tag == 0 {
             return Err(DecodeError::new("invalid tag 0"));
@@ -84,8 +101,33 @@ pub trait ProtoDecode: Sized {
     type ShadowDecoded: ProtoDecoder + ProtoExt + ProtoShadowDecode<Self> + ProtoDefault;
     #[inline]
     fn decode(mut buf: impl Buf, ctx: DecodeContext) -> Result<Self, DecodeError> {
+        #[cfg(feature = "wire-trace")]
+        let _origin = crate::trace::Origin::enter(buf.remaining());
+        ctx.check_interrupted()?;
         let mut sh = <Self::ShadowDecoded as ProtoDefault>::proto_default();
-        Self::ShadowDecoded::decode_into(&mut sh, &mut buf, ctx)?;
+        Self::ShadowDecoded::decode_into(&mut sh, &mut buf, ctx).map_err(DecodeError::report_unplaced_unknown_enum)?;
+        Self::post_decode(sh)
+    }
+
+    /// Decode a message that fills all of `bytes` with no length prefix, such as a config file.
+    ///
+    /// Decodes like [`ProtoDecode::decode`], but a failure records the byte offset of the top-level
+    /// field it happened in, see [`DecodeError::offset`], and bytes that do not start a field, like
+    /// zero padding, are reported as trailing data instead of a bad key.
+    fn decode_to_eof(bytes: &[u8], ctx: DecodeContext) -> Result<Self, DecodeError> {
+        #[cfg(feature = "wire-trace")]
+        let _origin = crate::trace::Origin::enter(bytes.len());
+        ctx.check_interrupted()?;
+        let mut sh = <Self::ShadowDecoded as ProtoDefault>::proto_default();
+        let mut buf = bytes;
+        while !buf.is_empty() {
+            let offset = bytes.len() - buf.len();
+            if decode_key(&mut &buf[..]).is_err() {
+                return Err(DecodeError::new("trailing data that does not start a field").at_offset(offset));
+            }
+            Self::ShadowDecoded::decode_one_field(&mut sh, &mut buf, ctx)
+                .map_err(|err| err.report_unplaced_unknown_enum().at_offset(offset))?;
+        }
         Self::post_decode(sh)
     }
 
@@ -101,11 +143,66 @@ pub trait ProtoDecode: Sized {
     fn validate_with_ext(_value: &mut Self, _ext: &tonic::Extensions) -> Result<(), DecodeError> {
         Ok(())
     }
+
+    const VALIDATE_RESPONSE_WITH_EXT: bool = false;
+
+    /// Client-side counterpart of [`ProtoDecode::validate_with_ext`], run by generated clients on
+    /// every response message (or stream item) with the response extensions.
+    #[cfg(feature = "tonic")]
+    #[inline]
+    fn validate_response_with_ext(_value: &mut Self, _ext: &tonic::Extensions) -> Result<(), DecodeError> {
+        Ok(())
+    }
 }
 
 pub trait ProtoFieldMerge: ProtoExt {
     /// Merge a single *field occurrence* into `self` given the field wire type.
     fn merge_value(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError>;
+
+    /// Merge a field occurrence under `TAG` and the occurrences of the same field that directly
+    /// follow it, see [`ProtoDecoder::merge_run`].
+    #[inline]
+    fn merge_run<const TAG: u32>(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        self.merge_value(wire_type, buf, ctx)
+    }
+}
+
+/// Field merge that rejects repeated map keys or set elements.
+///
+/// Selected by `#[proto(on_duplicate = "error")]`; the default `ProtoFieldMerge` keeps the last value.
+pub trait ProtoUniqueMerge: ProtoFieldMerge {
+    fn merge_unique(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError>;
+}
+
+/// Field merge for maps with `Vec<V>` values that appends every value seen for a key.
+///
+/// Selected by `#[proto(on_duplicate = "collect")]`; on the wire the field is a plain `map<K, V>`.
+pub trait ProtoCollectMerge {
+    fn merge_collect(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError>;
+}
+
+/// Field merge for `string` fields that replaces invalid UTF-8 instead of failing.
+///
+/// Selected by `#[proto(utf8 = "lossy")]`; the default `ProtoFieldMerge` rejects invalid UTF-8.
+pub trait ProtoLossyMerge {
+    fn merge_lossy(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError>;
+}
+
+/// Out-of-range handling for integers narrower than their wire type.
+///
+/// Selected by `#[proto(on_overflow = "saturate" | "wrap")]`; the default `ProtoFieldMerge` fails
+/// with an overflow error.
+pub trait ProtoNarrowMerge {
+    fn merge_saturating(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError>;
+    fn merge_wrapping(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError>;
+}
+
+/// Field merge for fixed arrays `[T; N]` whose elements each take a field occurrence.
+///
+/// One call reads the whole run of occurrences that follows under `TAG`, filling the array from
+/// the start; the derive macro uses it for every array field that is not `[u8; N]`.
+pub trait ProtoArrayMerge {
+    fn merge_array<const TAG: u32>(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError>;
 }
 
 pub trait ProtoDefault: Sized {
@@ -122,6 +219,11 @@ where
     fn merge_value(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
         <T as ProtoDecoder>::merge(self, wire_type, buf, ctx)
     }
+
+    #[inline]
+    fn merge_run<const TAG: u32>(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        <T as ProtoDecoder>::merge_run::<TAG>(self, wire_type, buf, ctx)
+    }
 }
 
 pub trait DecodeIrBuilder<T> {
@@ -3,9 +3,12 @@ use core::marker::PhantomData;
 use bytes::BufMut;
 
 use crate::coders::AsBytes;
+use crate::encoding::encoded_len_varint;
 use crate::error::EncodeError;
 use crate::traits::ProtoExt;
 use crate::traits::ProtoKind;
+use crate::traits::buffer::BufferStrategy;
+use crate::traits::buffer::RevArray;
 use crate::traits::buffer::RevVec;
 use crate::traits::buffer::RevWriter;
 use crate::traits::utils::VarintConst;
@@ -23,6 +26,15 @@ pub trait ProtoArchive {
     /// - TAG == 0 => top-level payload (no field key/len wrapper)
     /// - TAG != 0 => field encoding (payload, then len/key as required by wire type)
     fn archive<const TAG: u32>(&self, w: &mut impl RevWriter);
+
+    /// Upper bound of the bytes [`archive`](Self::archive) writes for the same `TAG`.
+    ///
+    /// Computed from lengths and per-field worst cases without encoding anything; `None` when the
+    /// type cannot bound itself cheaply, in which case writers fall back to growing on demand.
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        None
+    }
 }
 
 pub type ArchivedProtoMessageWriter<T> = ArchivedProtoMessage<T, RevVec>;
@@ -57,6 +69,40 @@ pub trait ProtoEncode {
         value.to_vec_tight()
     }
 
+    /// Encodes into `N` bytes held inline, without allocating. `N` is typically the derived
+    /// `MAX_ENCODED_LEN` of a message whose fields are all bounded.
+    ///
+    /// # Errors
+    ///
+    /// Returns the required length when the encoding does not fit in `N` bytes.
+    #[inline]
+    fn encode_to_array<const N: usize>(&self) -> Result<RevArray<N>, EncodeError>
+    where
+        Self: ProtoExt,
+    {
+        match ArchivedProtoMessage::<Self, RevArray<N>>::new(self) {
+            Some(value) => value.inner.checked(),
+            None => Ok(RevArray::empty()),
+        }
+    }
+
+    /// Upper bound of [`encode_to_vec`](Self::encode_to_vec)'s length, see [`ProtoArchive::size_hint`].
+    #[inline]
+    fn encoded_len_hint(&self) -> Option<usize>
+    where
+        Self: ProtoExt,
+    {
+        let s = Self::Shadow::from_sun(self);
+        if <Self::Shadow<'_> as ProtoArchive>::is_default(&s) {
+            return Some(0);
+        }
+        if matches!(Self::KIND, ProtoKind::SimpleEnum) {
+            s.size_hint::<1>()
+        } else {
+            s.size_hint::<0>()
+        }
+    }
+
     #[inline]
     fn to_zero_copy(&self) -> ZeroCopy<Self>
     where
@@ -81,16 +127,30 @@ where
     T: ProtoEncode + ProtoExt,
     for<'s> <T as ProtoEncode>::Shadow<'s>: ProtoArchive,
 {
-    const INIT_CAP: usize = 64;
     #[inline]
     pub fn new(input: &T) -> Option<Self> {
+        #[cfg(feature = "wire-trace")]
+        return crate::trace::encode_message::<T, _>(
+            || Self::archive_new(input),
+            |archived| archived.as_ref().map_or(0, |m| m.inner.len()),
+        );
+        #[cfg(not(feature = "wire-trace"))]
+        Self::archive_new(input)
+    }
+
+    #[inline]
+    fn archive_new(input: &T) -> Option<Self> {
         let s = T::Shadow::from_sun(input);
         if <<T as ProtoEncode>::Shadow<'_> as ProtoArchive>::is_default(&s) {
             return None;
         }
-        let mut w = W::with_capacity(Self::INIT_CAP);
+        let simple_enum = matches!(T::KIND, ProtoKind::SimpleEnum);
+        let strategy = BufferStrategy::for_type::<T>();
+        // A buffer sized to the upper bound is filled without reallocating.
+        let hint = if simple_enum { s.size_hint::<1>() } else { s.size_hint::<0>() };
+        let mut w = W::with_strategy(hint.unwrap_or(strategy.initial_capacity), &strategy);
 
-        if matches!(T::KIND, ProtoKind::SimpleEnum) {
+        if simple_enum {
             s.archive::<1>(&mut w);
         } else {
             s.archive::<0>(&mut w);
@@ -219,4 +279,29 @@ impl<const TAG: u32, T: ProtoArchive + ProtoExt> ArchivedProtoField<TAG, T> {
     pub fn put_key(w: &mut impl RevWriter) {
         w.put_slice(&Self::_TAG_VARINT.bytes[..Self::TAG_LEN]);
     }
+
+    /// [`ProtoArchive::size_hint`] of a field written with [`archive`](Self::archive).
+    #[inline]
+    pub fn size_hint(input: &T) -> Option<usize> {
+        if <T as ProtoArchive>::is_default(input) {
+            return Some(0);
+        }
+        input.size_hint::<{ TAG }>()
+    }
+
+    /// Key (when `TAG != 0`) plus `payload` bytes.
+    #[inline]
+    pub const fn hint_key(payload: usize) -> usize {
+        if TAG == 0 { payload } else { Self::TAG_LEN + payload }
+    }
+
+    /// Key and length prefix (when `TAG != 0`) plus `payload` bytes.
+    #[inline]
+    pub const fn hint_delimited(payload: usize) -> usize {
+        if TAG == 0 {
+            payload
+        } else {
+            Self::TAG_LEN + encoded_len_varint(payload as u64) + payload
+        }
+    }
 }
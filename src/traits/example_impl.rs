@@ -29,6 +29,7 @@ pub struct ID<'b, K, V> {
 }
 
 /// ---------- Shadow (borrows Sun for encoding) ----------
+#[expect(dead_code)]
 pub struct IDShadow<'a, K: ProtoEncode, V: ProtoEncode> {
     pub id: u64,
     pub k: <K as ProtoEncode>::Shadow<'a>,
@@ -37,6 +38,7 @@ pub struct IDShadow<'a, K: ProtoEncode, V: ProtoEncode> {
 }
 
 /// ---------- decoded-shadow (owned, used for decoding) ----------
+#[expect(dead_code)]
 pub struct IDDecoded<Kd, Vd> {
     pub id: u64,
     pub k: Kd,
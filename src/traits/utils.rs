@@ -1,4 +1,5 @@
 use crate::encoding::WireType;
+use crate::encoding::encoded_len_varint;
 use crate::traits::ProtoExt;
 
 pub struct VarintConst<const N: usize> {
@@ -68,6 +69,16 @@ impl ProtoKind {
     pub const fn is_bytes_kind(&self) -> bool {
         matches!(self, ProtoKind::Primitive(PrimitiveKind::U8))
     }
+    /// Largest payload of a value of this kind, when every value has one.
+    pub const fn max_payload_len(&self) -> Option<usize> {
+        match self {
+            ProtoKind::Primitive(p) => Some(p.max_len()),
+            // both i32 and the 64-bit reprs take up to 10 bytes for negative or wide values
+            ProtoKind::SimpleEnum => Some(10),
+            ProtoKind::Message | ProtoKind::Bytes | ProtoKind::String | ProtoKind::Repeated(_) => None,
+        }
+    }
+
     #[inline]
     pub const fn wire_type(&self) -> WireType {
         match self {
@@ -95,6 +106,42 @@ impl ProtoKind {
     }
 }
 
+impl PrimitiveKind {
+    /// Largest encoding of one value, narrow integers being widened to 32 bits and signed ones
+    /// sign-extended to 64.
+    pub const fn max_len(&self) -> usize {
+        match self {
+            PrimitiveKind::Bool => 1,
+            PrimitiveKind::AtomicU8 | PrimitiveKind::U8 => 2,
+            PrimitiveKind::U16 => 3,
+            PrimitiveKind::U32 | PrimitiveKind::SInt32 => 5,
+            PrimitiveKind::Fixed32 | PrimitiveKind::SFixed32 | PrimitiveKind::F32 => 4,
+            PrimitiveKind::Fixed64 | PrimitiveKind::SFixed64 | PrimitiveKind::F64 => 8,
+            PrimitiveKind::I8
+            | PrimitiveKind::I16
+            | PrimitiveKind::I32
+            | PrimitiveKind::I64
+            | PrimitiveKind::U64
+            | PrimitiveKind::SInt64 => 10,
+        }
+    }
+}
+
+/// Adds the largest encoding of a `T` field with `tag`, key and length prefix included, to
+/// `total`; `None` once either is unbounded.
+pub const fn add_field_max_len<T: ProtoExt>(total: Option<usize>, tag: u32) -> Option<usize> {
+    let (Some(total), Some(payload)) = (total, T::MAX_PAYLOAD_LEN) else {
+        return None;
+    };
+    let key = encoded_len_varint((tag as u64) << 3);
+    let field = if matches!(T::WIRE_TYPE, WireType::LengthDelimited) {
+        key + encoded_len_varint(payload as u64) + payload
+    } else {
+        key + payload
+    };
+    Some(total + field)
+}
+
 #[track_caller]
 #[allow(clippy::extra_unused_type_parameters)]
 pub const fn const_unreachable<T: ProtoExt>(structure_name: &'static str) -> ! {
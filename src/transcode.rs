@@ -0,0 +1,796 @@
+//! Proto3 JSON to binary transcoding and back, driven by the schema registry.
+//!
+//! A [`Transcoder`] walks the field metadata collected by `build-schemas` instead of a typed
+//! struct, so a gateway can turn JSON requests into protobuf frames (and replies back into JSON)
+//! for any registered message without decoding it into Rust values first.
+//!
+//! JSON follows the proto3 mapping: fields are written in lowerCamelCase and read under either
+//! that or their proto name, 64-bit integers are strings, `bytes` are base64, enums are variant
+//! names and complex enums are objects holding the single set variant. Streams are sequences of
+//! JSON values on one side and varint length-prefixed messages on the other.
+//!
+//! ```rust,ignore
+//! let transcoder = Transcoder::for_name("orders.Order")?;
+//! transcoder.json_stream_to_binary(request_body, &mut upstream)?;
+//! transcoder.binary_stream_to_json(&mut upstream, response_body)?;
+//! ```
+
+use std::io;
+use std::io::Read;
+use std::io::Write;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use bytes::Buf;
+use serde_json::Map;
+use serde_json::Number;
+use serde_json::Value;
+
+use crate::DecodeContext;
+use crate::DecodeError;
+use crate::encoding::WireType;
+use crate::encoding::decode_key;
+use crate::encoding::decode_varint;
+use crate::encoding::encode_key;
+use crate::encoding::encode_varint;
+use crate::encoding::skip_field;
+use crate::schemas::Field;
+use crate::schemas::ProtoEntry;
+use crate::schemas::ProtoIdent;
+use crate::schemas::ProtoIdentifiable;
+use crate::schemas::ProtoLabel;
+use crate::schemas::ProtoType;
+use crate::schemas::Variant;
+use crate::schemas::schema_for;
+
+/// Failure to transcode a message.
+#[derive(Debug)]
+pub enum TranscodeError {
+    /// No registered message has this name or ident.
+    UnknownMessage(String),
+    /// The JSON value does not fit the schema; `path` points at the offending field.
+    Invalid { path: String, reason: String },
+    /// The input stream is not valid JSON.
+    Json(serde_json::Error),
+    /// The input is not well-formed CBOR, MessagePack or compact encoding.
+    Malformed(String),
+    /// Compact bytes were written against other message definitions; `expected` is the layout
+    /// hash of the current ones.
+    SchemaChanged { expected: u64, found: u64 },
+    /// The binary input is not a valid message.
+    Decode(DecodeError),
+    /// Reading or writing a stream failed.
+    Io(io::Error),
+}
+
+impl TranscodeError {
+    pub(crate) fn invalid(reason: impl Into<String>) -> Self {
+        TranscodeError::Invalid {
+            path: String::new(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Prefixes the path of an [`Invalid`](Self::Invalid) error with the enclosing field.
+    pub(crate) fn at(self, segment: &str) -> Self {
+        match self {
+            TranscodeError::Invalid { path, reason } => {
+                let path = if path.is_empty() || path.starts_with('[') {
+                    format!("{segment}{path}")
+                } else {
+                    format!("{segment}.{path}")
+                };
+                TranscodeError::Invalid { path, reason }
+            }
+            other => other,
+        }
+    }
+}
+
+impl core::fmt::Display for TranscodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TranscodeError::UnknownMessage(name) => write!(f, "no registered message named {name}"),
+            TranscodeError::Invalid { path, reason } if path.is_empty() => f.write_str(reason),
+            TranscodeError::Invalid { path, reason } => write!(f, "{path}: {reason}"),
+            TranscodeError::Json(error) => write!(f, "invalid JSON: {error}"),
+            TranscodeError::Malformed(reason) => write!(f, "malformed input: {reason}"),
+            TranscodeError::SchemaChanged { expected, found } => {
+                write!(f, "written against layout {found:016x}, the current layout is {expected:016x}")
+            }
+            TranscodeError::Decode(error) => core::fmt::Display::fmt(error, f),
+            TranscodeError::Io(error) => core::fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+impl core::error::Error for TranscodeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            TranscodeError::Json(error) => Some(error),
+            TranscodeError::Decode(error) => Some(error),
+            TranscodeError::Io(error) => Some(error),
+            TranscodeError::UnknownMessage(_)
+            | TranscodeError::Invalid { .. }
+            | TranscodeError::Malformed(_)
+            | TranscodeError::SchemaChanged { .. } => None,
+        }
+    }
+}
+
+impl From<DecodeError> for TranscodeError {
+    fn from(error: DecodeError) -> TranscodeError {
+        TranscodeError::Decode(error)
+    }
+}
+
+impl From<serde_json::Error> for TranscodeError {
+    fn from(error: serde_json::Error) -> TranscodeError {
+        TranscodeError::Json(error)
+    }
+}
+
+impl From<io::Error> for TranscodeError {
+    fn from(error: io::Error) -> TranscodeError {
+        TranscodeError::Io(error)
+    }
+}
+
+/// Converts one registered message type between proto3 JSON and its binary encoding.
+#[derive(Debug, Clone, Copy)]
+pub struct Transcoder {
+    ident: ProtoIdent,
+    ignore_unknown_fields: bool,
+}
+
+impl Transcoder {
+    /// Transcoder for messages of type `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TranscodeError::UnknownMessage`] if `T` has no registered message schema.
+    pub fn of<T: ProtoIdentifiable>() -> Result<Self, TranscodeError> {
+        Self::for_ident(T::PROTO_IDENT)
+    }
+
+    /// Transcoder for the registered message with full proto name `name` (`package.Message`),
+    /// or the bare message name for messages without a package.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TranscodeError::UnknownMessage`] if no registered message has that name.
+    pub fn for_name(name: &str) -> Result<Self, TranscodeError> {
+        crate::schemas::message_by_name(name)
+            .map(|schema| Self {
+                ident: schema.id,
+                ignore_unknown_fields: false,
+            })
+            .ok_or_else(|| TranscodeError::UnknownMessage(name.to_string()))
+    }
+
+    fn for_ident(ident: ProtoIdent) -> Result<Self, TranscodeError> {
+        match schema_for(&ident).map(|schema| schema.content) {
+            Some(ProtoEntry::Struct { .. } | ProtoEntry::ComplexEnum { .. }) => Ok(Self {
+                ident,
+                ignore_unknown_fields: false,
+            }),
+            _ => Err(TranscodeError::UnknownMessage(ident.name.to_string())),
+        }
+    }
+
+    /// Skips JSON fields the schema does not know instead of rejecting the message.
+    #[must_use]
+    pub const fn ignoring_unknown_fields(mut self, ignore: bool) -> Self {
+        self.ignore_unknown_fields = ignore;
+        self
+    }
+
+    /// Binary encoding of one JSON message.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TranscodeError::Invalid`] if the value does not match the schema.
+    pub fn json_to_binary(&self, json: &Value) -> Result<Vec<u8>, TranscodeError> {
+        let mut buf = Vec::new();
+        self.encode_message(&self.ident, json, &mut buf)?;
+        Ok(buf)
+    }
+
+    /// JSON form of one binary message. Unknown fields are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TranscodeError::Decode`] if the bytes are not a valid message.
+    pub fn binary_to_json(&self, bytes: &[u8]) -> Result<Value, TranscodeError> {
+        decode_message(&self.ident, bytes, DecodeContext::default())
+    }
+
+    /// Reads whitespace-separated JSON messages and writes each as a length-prefixed binary
+    /// message, returning how many were written.
+    ///
+    /// # Errors
+    ///
+    /// Stops at the first invalid message or I/O failure; earlier messages stay written.
+    pub fn json_stream_to_binary<R: Read, W: Write>(&self, reader: R, mut writer: W) -> Result<usize, TranscodeError> {
+        let mut count = 0;
+        let mut frame = Vec::new();
+        for json in serde_json::Deserializer::from_reader(reader).into_iter::<Value>() {
+            let message = self.json_to_binary(&json?)?;
+            frame.clear();
+            encode_varint(message.len() as u64, &mut frame);
+            frame.extend_from_slice(&message);
+            writer.write_all(&frame)?;
+            count += 1;
+        }
+        writer.flush()?;
+        Ok(count)
+    }
+
+    /// Reads length-prefixed binary messages and writes each as one line of JSON, returning how
+    /// many were written.
+    ///
+    /// # Errors
+    ///
+    /// Stops at the first invalid or truncated message or I/O failure; earlier messages stay
+    /// written.
+    pub fn binary_stream_to_json<R: Read, W: Write>(&self, mut reader: R, mut writer: W) -> Result<usize, TranscodeError> {
+        let mut count = 0;
+        let mut message = Vec::new();
+        while let Some(len) = read_length_prefix(&mut reader)? {
+            message.resize(len, 0);
+            reader.read_exact(&mut message)?;
+            serde_json::to_writer(&mut writer, &self.binary_to_json(&message)?)?;
+            writer.write_all(b"\n")?;
+            count += 1;
+        }
+        writer.flush()?;
+        Ok(count)
+    }
+
+    fn encode_message(&self, ident: &ProtoIdent, json: &Value, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+        let Value::Object(object) = json else {
+            return Err(TranscodeError::invalid(format!("expected an object for {}", ident.name)));
+        };
+        match schema_for(ident).map(|schema| schema.content) {
+            Some(ProtoEntry::Struct { fields }) => self.encode_fields(fields, object, buf),
+            Some(ProtoEntry::ComplexEnum { variants }) => self.encode_oneof(variants, object, buf),
+            _ => Err(TranscodeError::UnknownMessage(ident.name.to_string())),
+        }
+    }
+
+    fn encode_fields(&self, fields: &[&Field], object: &Map<String, Value>, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+        for (key, value) in object {
+            let Some(field) = fields.iter().enumerate().find(|(idx, field)| name_matches(&field_name(field, *idx), key)) else {
+                if self.ignore_unknown_fields {
+                    continue;
+                }
+                return Err(TranscodeError::invalid("unknown field").at(key));
+            };
+            if !value.is_null() {
+                self.encode_field(field.1, value, buf).map_err(|error| error.at(key))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_oneof(&self, variants: &[&Variant], object: &Map<String, Value>, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+        let mut set = None;
+        for (key, value) in object.iter().filter(|(_, value)| !value.is_null()) {
+            let Some(idx) = variants.iter().position(|variant| name_matches(&variant.oneof_field_name(), key)) else {
+                if self.ignore_unknown_fields {
+                    continue;
+                }
+                return Err(TranscodeError::invalid("unknown variant").at(key));
+            };
+            if set.replace((idx, key, value)).is_some() {
+                return Err(TranscodeError::invalid("more than one variant is set"));
+            }
+        }
+        let Some((idx, key, value)) = set else {
+            return Ok(());
+        };
+        let tag = u32::try_from(idx + 1).unwrap_or(u32::MAX);
+        let encoded = match variants[idx].fields {
+            [] => match value {
+                Value::Object(_) => {
+                    write_len_delimited(tag, &[], buf);
+                    Ok(())
+                }
+                _ => Err(TranscodeError::invalid("expected an empty object")),
+            },
+            [field] if field.name.is_none() => self.encode_value(tag, &field.proto_ident, value, buf),
+            fields => {
+                let Value::Object(object) = value else {
+                    return Err(TranscodeError::invalid("expected an object").at(key));
+                };
+                let mut nested = Vec::new();
+                self.encode_fields(fields, object, &mut nested).map(|()| write_len_delimited(tag, &nested, buf))
+            }
+        };
+        encoded.map_err(|error| error.at(key))
+    }
+
+    fn encode_field(&self, field: &Field, value: &Value, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+        if field.is_bytes() {
+            encode_key(field.tag, WireType::LengthDelimited, buf);
+            return encode_scalar(ProtoType::Bytes, value, buf);
+        }
+        if let ProtoType::Map { key, value: value_type } = field.proto_ident.proto_type {
+            return self.encode_map(field, *key, *value_type, value, buf);
+        }
+        if field.proto_label != ProtoLabel::Repeated {
+            return self.encode_value(field.tag, &field.proto_ident, value, buf);
+        }
+        let Value::Array(items) = value else {
+            return Err(TranscodeError::invalid("expected an array"));
+        };
+        let elem = field.array_elem.unwrap_or(field.proto_ident);
+        match kind(&elem)? {
+            Kind::Scalar(ty) if ty.is_packable() => {
+                let mut packed = Vec::new();
+                for (idx, item) in items.iter().enumerate() {
+                    encode_scalar(ty, item, &mut packed).map_err(|error| error.at(&format!("[{idx}]")))?;
+                }
+                write_len_delimited(field.tag, &packed, buf);
+            }
+            Kind::Enum(variants) => {
+                let mut packed = Vec::new();
+                for (idx, item) in items.iter().enumerate() {
+                    encode_enum(variants, item, &mut packed).map_err(|error| error.at(&format!("[{idx}]")))?;
+                }
+                write_len_delimited(field.tag, &packed, buf);
+            }
+            _ => {
+                for (idx, item) in items.iter().enumerate() {
+                    self.encode_value(field.tag, &elem, item, buf).map_err(|error| error.at(&format!("[{idx}]")))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_map(&self, field: &Field, key: ProtoType, value: ProtoType, json: &Value, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+        let Value::Object(entries) = json else {
+            return Err(TranscodeError::invalid("expected an object"));
+        };
+        let (key_ident, value_ident) = field.map_idents();
+        let key_type = key_ident.map_or(key, |ident| ident.proto_type);
+        for (map_key, map_value) in entries {
+            let key_json = match key_type {
+                ProtoType::Bool => Value::Bool(map_key == "true"),
+                _ => Value::String(map_key.clone()),
+            };
+            let mut entry = Vec::new();
+            let encoded = tagged_scalar(1, key_type, &key_json, &mut entry).and_then(|()| match value_ident {
+                Some(ident) => self.encode_value(2, &ident, map_value, &mut entry),
+                None => tagged_scalar(2, value, map_value, &mut entry),
+            });
+            encoded.map_err(|error| error.at(&format!("[{map_key:?}]")))?;
+            write_len_delimited(field.tag, &entry, buf);
+        }
+        Ok(())
+    }
+
+    /// One occurrence of a field of type `ident` under `tag`.
+    fn encode_value(&self, tag: u32, ident: &ProtoIdent, value: &Value, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+        match kind(ident)? {
+            Kind::Scalar(ty) => tagged_scalar(tag, ty, value, buf),
+            Kind::Enum(variants) => {
+                encode_key(tag, WireType::Varint, buf);
+                encode_enum(variants, value, buf)
+            }
+            Kind::Message(ident) => {
+                let mut nested = Vec::new();
+                self.encode_message(&ident, value, &mut nested)?;
+                write_len_delimited(tag, &nested, buf);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// What a field of some ident holds once wrappers and registry lookups are resolved.
+pub(crate) enum Kind {
+    Scalar(ProtoType),
+    Enum(&'static [&'static Variant]),
+    Message(ProtoIdent),
+}
+
+pub(crate) fn kind(ident: &ProtoIdent) -> Result<Kind, TranscodeError> {
+    match ident.proto_type {
+        ProtoType::Message(_) => match schema_for(ident).map(|schema| schema.content) {
+            Some(ProtoEntry::SimpleEnum { variants }) => Ok(Kind::Enum(variants)),
+            Some(ProtoEntry::Struct { .. } | ProtoEntry::ComplexEnum { .. }) => Ok(Kind::Message(*ident)),
+            _ => Err(TranscodeError::UnknownMessage(ident.name.to_string())),
+        },
+        ProtoType::Optional(inner) | ProtoType::Repeated(inner) => Ok(Kind::Scalar(*inner)),
+        ty => Ok(Kind::Scalar(ty)),
+    }
+}
+
+pub(crate) fn field_name(field: &Field, idx: usize) -> String {
+    field.name.map_or_else(|| format!("field_{idx}"), ToString::to_string)
+}
+
+/// lowerCamelCase JSON name of a proto field name.
+pub(crate) fn json_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for ch in name.chars() {
+        if ch == '_' {
+            upper = true;
+        } else if upper {
+            out.push(ch.to_ascii_uppercase());
+            upper = false;
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+fn name_matches(name: &str, key: &str) -> bool {
+    name == key || json_name(name) == key
+}
+
+pub(crate) const fn wire_type_of(ty: ProtoType) -> WireType {
+    match ty {
+        ProtoType::Fixed32 | ProtoType::Sfixed32 | ProtoType::Float => WireType::ThirtyTwoBit,
+        ProtoType::Fixed64 | ProtoType::Sfixed64 | ProtoType::Double => WireType::SixtyFourBit,
+        ProtoType::String | ProtoType::Bytes | ProtoType::Message(_) | ProtoType::Map { .. } => WireType::LengthDelimited,
+        _ => WireType::Varint,
+    }
+}
+
+pub(crate) fn write_len_delimited(tag: u32, payload: &[u8], buf: &mut Vec<u8>) {
+    encode_key(tag, WireType::LengthDelimited, buf);
+    encode_varint(payload.len() as u64, buf);
+    buf.extend_from_slice(payload);
+}
+
+fn tagged_scalar(tag: u32, ty: ProtoType, value: &Value, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+    if matches!(ty, ProtoType::Message(_) | ProtoType::Map { .. } | ProtoType::None) {
+        return Err(TranscodeError::invalid("unsupported field type"));
+    }
+    encode_key(tag, wire_type_of(ty), buf);
+    encode_scalar(ty, value, buf)
+}
+
+/// Scalar payload without its key.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn encode_scalar(ty: ProtoType, value: &Value, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+    match ty {
+        ProtoType::Bool => {
+            let value = value.as_bool().ok_or_else(|| TranscodeError::invalid("expected a boolean"))?;
+            buf.push(u8::from(value));
+        }
+        ProtoType::Int32 | ProtoType::Enum => encode_varint(integer(value, i32::MIN.into(), i32::MAX.into())? as u64, buf),
+        ProtoType::Int64 => encode_varint(integer(value, i64::MIN.into(), i64::MAX.into())? as u64, buf),
+        ProtoType::Uint32 => encode_varint(integer(value, 0, u32::MAX.into())? as u64, buf),
+        ProtoType::Uint64 => encode_varint(integer(value, 0, u64::MAX.into())? as u64, buf),
+        ProtoType::Sint32 | ProtoType::Sint64 => {
+            let (min, max) = if matches!(ty, ProtoType::Sint32) {
+                (i32::MIN.into(), i32::MAX.into())
+            } else {
+                (i64::MIN.into(), i64::MAX.into())
+            };
+            let value = integer(value, min, max)? as i64;
+            encode_varint(((value << 1) ^ (value >> 63)) as u64, buf);
+        }
+        ProtoType::Fixed32 => buf.extend_from_slice(&(integer(value, 0, u32::MAX.into())? as u32).to_le_bytes()),
+        ProtoType::Sfixed32 => buf.extend_from_slice(&(integer(value, i32::MIN.into(), i32::MAX.into())? as i32).to_le_bytes()),
+        ProtoType::Fixed64 => buf.extend_from_slice(&(integer(value, 0, u64::MAX.into())? as u64).to_le_bytes()),
+        ProtoType::Sfixed64 => buf.extend_from_slice(&(integer(value, i64::MIN.into(), i64::MAX.into())? as i64).to_le_bytes()),
+        ProtoType::Float => buf.extend_from_slice(&(float(value)? as f32).to_le_bytes()),
+        ProtoType::Double => buf.extend_from_slice(&float(value)?.to_le_bytes()),
+        ProtoType::String => {
+            let text = value.as_str().ok_or_else(|| TranscodeError::invalid("expected a string"))?;
+            encode_varint(text.len() as u64, buf);
+            buf.extend_from_slice(text.as_bytes());
+        }
+        ProtoType::Bytes => {
+            let text = value.as_str().ok_or_else(|| TranscodeError::invalid("expected a base64 string"))?;
+            let bytes = decode_base64(text)?;
+            encode_varint(bytes.len() as u64, buf);
+            buf.extend_from_slice(&bytes);
+        }
+        ProtoType::Optional(inner) | ProtoType::Repeated(inner) => return encode_scalar(*inner, value, buf),
+        ProtoType::Message(_) | ProtoType::Map { .. } | ProtoType::None => return Err(TranscodeError::invalid("unsupported field type")),
+    }
+    Ok(())
+}
+
+#[allow(clippy::cast_sign_loss)]
+fn encode_enum(variants: &[&Variant], value: &Value, buf: &mut Vec<u8>) -> Result<(), TranscodeError> {
+    let number = match value {
+        Value::String(name) => variants
+            .iter()
+            .find(|variant| variant.name == name)
+            .map(|variant| variant.discriminant.unwrap_or_default())
+            .ok_or_else(|| TranscodeError::invalid(format!("unknown enum value {name}")))?,
+        _ => i32::try_from(integer(value, i32::MIN.into(), i32::MAX.into())?).unwrap_or_default(),
+    };
+    encode_varint(i64::from(number) as u64, buf);
+    Ok(())
+}
+
+/// Integer from a JSON number or numeric string, checked against `min..=max`.
+#[allow(clippy::cast_possible_truncation)]
+fn integer(value: &Value, min: i128, max: i128) -> Result<i128, TranscodeError> {
+    let parsed = match value {
+        Value::Number(number) => number
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| number.as_u64().map(i128::from))
+            .or_else(|| number.as_f64().filter(|float| float.fract() == 0.0 && float.abs() < 1.0e38).map(|float| float as i128)),
+        Value::String(text) => text.trim().parse::<i128>().ok(),
+        _ => None,
+    };
+    match parsed {
+        Some(number) if (min..=max).contains(&number) => Ok(number),
+        Some(number) => Err(TranscodeError::invalid(format!("{number} is out of range"))),
+        None => Err(TranscodeError::invalid("expected an integer")),
+    }
+}
+
+fn float(value: &Value) -> Result<f64, TranscodeError> {
+    match value {
+        Value::Number(number) => number.as_f64().ok_or_else(|| TranscodeError::invalid("expected a number")),
+        Value::String(text) => match text.as_str() {
+            "NaN" => Ok(f64::NAN),
+            "Infinity" => Ok(f64::INFINITY),
+            "-Infinity" => Ok(f64::NEG_INFINITY),
+            text => text.parse().map_err(|_| TranscodeError::invalid("expected a number")),
+        },
+        _ => Err(TranscodeError::invalid("expected a number")),
+    }
+}
+
+/// Accepts standard and URL-safe base64, with or without padding.
+fn decode_base64(text: &str) -> Result<Vec<u8>, TranscodeError> {
+    let unpadded = text.trim_end_matches('=');
+    if unpadded.contains(['-', '_']) {
+        URL_SAFE_NO_PAD.decode(unpadded)
+    } else {
+        STANDARD_NO_PAD.decode(unpadded)
+    }
+    .map_err(|error| TranscodeError::invalid(format!("invalid base64: {error}")))
+}
+
+fn decode_message(ident: &ProtoIdent, mut buf: &[u8], ctx: DecodeContext) -> Result<Value, TranscodeError> {
+    ctx.limit_reached()?;
+    match schema_for(ident).map(|schema| schema.content) {
+        Some(ProtoEntry::Struct { fields }) => decode_fields(fields, buf, ctx).map(Value::Object),
+        Some(ProtoEntry::ComplexEnum { variants }) => {
+            let mut out = Map::new();
+            while buf.has_remaining() {
+                let (tag, wire_type) = decode_key(&mut buf)?;
+                let Some(variant) = (tag as usize).checked_sub(1).and_then(|idx| variants.get(idx)) else {
+                    skip_field(wire_type, tag, &mut buf, ctx)?;
+                    continue;
+                };
+                let value = match variant.fields {
+                    [field] if field.name.is_none() => decode_value(&kind(&field.proto_ident)?, wire_type, &mut buf, ctx)?,
+                    fields => {
+                        let nested = length_delimited(wire_type, &mut buf)?;
+                        Value::Object(decode_fields(fields, nested, ctx.enter_recursion())?)
+                    }
+                };
+                // Only the last variant on the wire is set.
+                out.clear();
+                out.insert(json_name(&variant.oneof_field_name()), value);
+            }
+            Ok(Value::Object(out))
+        }
+        _ => Err(TranscodeError::UnknownMessage(ident.name.to_string())),
+    }
+}
+
+fn decode_fields(fields: &[&Field], mut buf: &[u8], ctx: DecodeContext) -> Result<Map<String, Value>, TranscodeError> {
+    let mut out = Map::new();
+    while buf.has_remaining() {
+        let (tag, wire_type) = decode_key(&mut buf)?;
+        let Some((idx, field)) = fields.iter().enumerate().find(|(_, field)| field.tag == tag) else {
+            skip_field(wire_type, tag, &mut buf, ctx)?;
+            continue;
+        };
+        let name = json_name(&field_name(field, idx));
+        if field.is_bytes() {
+            let value = decode_value(&Kind::Scalar(ProtoType::Bytes), wire_type, &mut buf, ctx)?;
+            out.insert(name, value);
+        } else if let ProtoType::Map { key, value } = field.proto_ident.proto_type {
+            let (key_ident, value_ident) = field.map_idents();
+            let key_kind = key_ident.map_or(Ok(Kind::Scalar(*key)), |ident| kind(&ident))?;
+            let value_kind = value_ident.map_or(Ok(Kind::Scalar(*value)), |ident| kind(&ident))?;
+            let entry = length_delimited(wire_type, &mut buf)?;
+            let (map_key, map_value) = decode_entry(&key_kind, &value_kind, entry, ctx)?;
+            let map_key = match map_key {
+                Value::String(text) => text,
+                other => other.to_string(),
+            };
+            if let Value::Object(entries) = out.entry(name).or_insert_with(|| Value::Object(Map::new())) {
+                entries.insert(map_key, map_value);
+            }
+        } else if field.proto_label == ProtoLabel::Repeated {
+            let elem = kind(&field.array_elem.unwrap_or(field.proto_ident))?;
+            let Value::Array(items) = out.entry(name).or_insert_with(|| Value::Array(Vec::new())) else {
+                continue;
+            };
+            let packed = match elem {
+                Kind::Scalar(ty) => ty.is_packable(),
+                Kind::Enum(_) => true,
+                Kind::Message(_) => false,
+            };
+            if packed && wire_type == WireType::LengthDelimited {
+                let mut packed = length_delimited(wire_type, &mut buf)?;
+                let item_wire_type = match elem {
+                    Kind::Scalar(ty) => wire_type_of(ty),
+                    _ => WireType::Varint,
+                };
+                while packed.has_remaining() {
+                    items.push(decode_value(&elem, item_wire_type, &mut packed, ctx)?);
+                }
+            } else {
+                items.push(decode_value(&elem, wire_type, &mut buf, ctx)?);
+            }
+        } else {
+            let value = decode_value(&kind(&field.proto_ident)?, wire_type, &mut buf, ctx)?;
+            out.insert(name, value);
+        }
+    }
+    Ok(out)
+}
+
+fn decode_entry(key: &Kind, value: &Kind, mut buf: &[u8], ctx: DecodeContext) -> Result<(Value, Value), TranscodeError> {
+    let mut entry = (None, None);
+    while buf.has_remaining() {
+        let (tag, wire_type) = decode_key(&mut buf)?;
+        match tag {
+            1 => entry.0 = Some(decode_value(key, wire_type, &mut buf, ctx)?),
+            2 => entry.1 = Some(decode_value(value, wire_type, &mut buf, ctx)?),
+            _ => skip_field(wire_type, tag, &mut buf, ctx)?,
+        }
+    }
+    Ok((
+        entry.0.unwrap_or_else(|| default_json(key)),
+        entry.1.unwrap_or_else(|| default_json(value)),
+    ))
+}
+
+/// JSON value of a map key or value missing from its entry.
+fn default_json(kind: &Kind) -> Value {
+    match kind {
+        Kind::Scalar(ProtoType::Bool) => Value::Bool(false),
+        Kind::Scalar(ProtoType::String | ProtoType::Bytes) => Value::String(String::new()),
+        Kind::Scalar(ProtoType::Int64 | ProtoType::Uint64 | ProtoType::Sint64 | ProtoType::Fixed64 | ProtoType::Sfixed64) => {
+            Value::String("0".to_string())
+        }
+        Kind::Scalar(_) => Value::from(0),
+        Kind::Enum(variants) => enum_json(variants, 0),
+        Kind::Message(_) => Value::Object(Map::new()),
+    }
+}
+
+fn enum_json(variants: &[&Variant], number: i32) -> Value {
+    variants
+        .iter()
+        .find(|variant| variant.discriminant.unwrap_or_default() == number)
+        .map_or_else(|| Value::from(number), |variant| Value::String(variant.name.to_string()))
+}
+
+pub(crate) fn length_delimited<'a>(wire_type: WireType, buf: &mut &'a [u8]) -> Result<&'a [u8], DecodeError> {
+    if wire_type != WireType::LengthDelimited {
+        return Err(DecodeError::new("invalid wire type"));
+    }
+    let len = decode_varint(buf)?;
+    let len = usize::try_from(len).ok().filter(|&len| len <= buf.len()).ok_or_else(|| DecodeError::new("buffer underflow"))?;
+    let (payload, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(payload)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn decode_value(kind: &Kind, wire_type: WireType, buf: &mut &[u8], ctx: DecodeContext) -> Result<Value, TranscodeError> {
+    let ty = match kind {
+        Kind::Message(ident) => {
+            let nested = length_delimited(wire_type, buf)?;
+            return decode_message(ident, nested, ctx.enter_recursion());
+        }
+        Kind::Enum(variants) => {
+            if wire_type != WireType::Varint {
+                return Err(DecodeError::new("invalid wire type").into());
+            }
+            return Ok(enum_json(variants, decode_varint(buf)? as i32));
+        }
+        Kind::Scalar(ty) => *ty,
+    };
+    if wire_type != wire_type_of(ty) {
+        return Err(DecodeError::new("invalid wire type").into());
+    }
+    let fixed = |buf: &mut &[u8], len: usize| {
+        if buf.remaining() < len {
+            Err(DecodeError::new("buffer underflow"))
+        } else {
+            Ok(())
+        }
+    };
+    let value = match ty {
+        ProtoType::Bool => Value::Bool(decode_varint(buf)? != 0),
+        ProtoType::Int32 | ProtoType::Enum => Value::from(decode_varint(buf)? as i32),
+        ProtoType::Uint32 => Value::from(decode_varint(buf)? as u32),
+        ProtoType::Int64 => Value::String((decode_varint(buf)? as i64).to_string()),
+        ProtoType::Uint64 => Value::String(decode_varint(buf)?.to_string()),
+        ProtoType::Sint32 => Value::from(zigzag(decode_varint(buf)?) as i32),
+        ProtoType::Sint64 => Value::String(zigzag(decode_varint(buf)?).to_string()),
+        ProtoType::Fixed32 => {
+            fixed(buf, 4)?;
+            Value::from(buf.get_u32_le())
+        }
+        ProtoType::Sfixed32 => {
+            fixed(buf, 4)?;
+            Value::from(buf.get_i32_le())
+        }
+        ProtoType::Fixed64 => {
+            fixed(buf, 8)?;
+            Value::String(buf.get_u64_le().to_string())
+        }
+        ProtoType::Sfixed64 => {
+            fixed(buf, 8)?;
+            Value::String(buf.get_i64_le().to_string())
+        }
+        ProtoType::Float => {
+            fixed(buf, 4)?;
+            let value = buf.get_f32_le();
+            // Shortest decimal form of the f32, not of its f64 widening.
+            float_json(value.to_string().parse().unwrap_or_else(|_| f64::from(value)))
+        }
+        ProtoType::Double => {
+            fixed(buf, 8)?;
+            float_json(buf.get_f64_le())
+        }
+        ProtoType::String => {
+            let payload = length_delimited(wire_type, buf)?;
+            let text = core::str::from_utf8(payload).map_err(|_| DecodeError::new("invalid string value: data is not UTF-8 encoded"))?;
+            Value::String(text.to_string())
+        }
+        ProtoType::Bytes => Value::String(STANDARD.encode(length_delimited(wire_type, buf)?)),
+        _ => return Err(TranscodeError::invalid("unsupported field type")),
+    };
+    Ok(value)
+}
+
+#[allow(clippy::cast_possible_wrap)]
+pub(crate) const fn zigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn float_json(value: f64) -> Value {
+    if value.is_nan() {
+        Value::String("NaN".to_string())
+    } else if value.is_infinite() {
+        Value::String(if value > 0.0 { "Infinity" } else { "-Infinity" }.to_string())
+    } else {
+        Number::from_f64(value).map_or(Value::Null, Value::Number)
+    }
+}
+
+/// Varint length of the next message, or `None` at a clean end of stream.
+fn read_length_prefix(reader: &mut impl Read) -> Result<Option<usize>, TranscodeError> {
+    let mut value = 0u64;
+    for shift in 0..10 {
+        let mut byte = [0u8];
+        if reader.read(&mut byte)? == 0 {
+            if shift == 0 {
+                return Ok(None);
+            }
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        value |= u64::from(byte[0] & 0x7f) << (shift * 7);
+        if byte[0] & 0x80 == 0 {
+            return usize::try_from(value).map(Some).map_err(|_| DecodeError::new("invalid length prefix").into());
+        }
+    }
+    Err(DecodeError::new("invalid length prefix").into())
+}
@@ -5,6 +5,7 @@
 
 use alloc::format;
 use alloc::string::String;
+use alloc::vec::Vec;
 use core::num::NonZeroI8;
 use core::num::NonZeroI16;
 use core::num::NonZeroI32;
@@ -39,6 +40,7 @@ use crate::ProtoEncode;
 use crate::encoding::DecodeContext;
 use crate::encoding::WireType;
 use crate::encoding::check_wire_type;
+use crate::encoding::encoded_len_varint;
 use crate::encoding::skip_field;
 use crate::traits::ArchivedProtoField;
 use crate::traits::PrimitiveKind;
@@ -46,6 +48,8 @@ use crate::traits::ProtoArchive;
 use crate::traits::ProtoDecode;
 use crate::traits::ProtoExt;
 use crate::traits::ProtoKind;
+use crate::traits::ProtoLossyMerge;
+use crate::traits::ProtoNarrowMerge;
 use crate::traits::ProtoShadowDecode;
 use crate::traits::ProtoShadowEncode;
 use crate::traits::buffer::RevWriter;
@@ -121,6 +125,13 @@ macro_rules! impl_proto_primitive_varint_by_value {
                     ArchivedProtoField::<TAG, Self>::put_key(w);
                 }
             }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                let $v: $ty = *self;
+                let value = $to_u64;
+                Some(ArchivedProtoField::<TAG, Self>::hint_key(encoded_len_varint(value)))
+            }
         }
 
         impl ProtoEncode for $ty {
@@ -205,6 +216,11 @@ macro_rules! impl_proto_primitive_fixed_by_value {
                     ArchivedProtoField::<TAG, Self>::put_key(w);
                 }
             }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                Some(ArchivedProtoField::<TAG, Self>::hint_key(core::mem::size_of::<$ty>()))
+            }
         }
 
         impl ProtoEncode for $ty {
@@ -293,6 +309,12 @@ macro_rules! impl_proto_primitive_by_ref {
                     ArchivedProtoField::<TAG, Self>::put_key(w);
                 }
             }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                let bytes: &[u8] = self.as_ref();
+                Some(ArchivedProtoField::<TAG, Self>::hint_delimited(bytes.len()))
+            }
         }
 
         impl ProtoEncode for $ty {
@@ -396,6 +418,35 @@ impl ProtoArchive for String {
     fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
         (&self).archive::<TAG>(w);
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        <&String as ProtoArchive>::size_hint::<TAG>(&self)
+    }
+}
+
+impl ProtoLossyMerge for String {
+    #[inline]
+    fn merge_lossy(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        crate::encoding::string::merge_lossy(wire_type, self, buf, ctx)
+    }
+}
+
+impl ProtoLossyMerge for Option<String> {
+    #[inline]
+    fn merge_lossy(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        self.get_or_insert_with(String::new).merge_lossy(wire_type, buf, ctx)
+    }
+}
+
+impl ProtoLossyMerge for Vec<String> {
+    #[inline]
+    fn merge_lossy(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        let mut value = String::new();
+        value.merge_lossy(wire_type, buf, ctx)?;
+        self.push(value);
+        Ok(())
+    }
 }
 
 impl ProtoArchive for Bytes {
@@ -408,14 +459,19 @@ impl ProtoArchive for Bytes {
     fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
         (&self).archive::<TAG>(w);
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        <&Bytes as ProtoArchive>::size_hint::<TAG>(&self)
+    }
 }
 
 // ============================================================================
-// Narrow primitives (u8, u16, i8, i16)
+// Narrow primitives (u8, u16, i8, i16, usize, isize)
 // ============================================================================
 
 macro_rules! impl_narrow_varint {
-    ($ty:ty, $wide_ty:ty, $prim_kind:ident, $err:literal) => {
+    ($ty:ty, $wide_ty:ty, $full_ty:ty, $prim_kind:ident, $err:literal) => {
         impl ProtoExt for $ty {
             const KIND: ProtoKind = ProtoKind::Primitive(PrimitiveKind::$prim_kind);
         }
@@ -459,6 +515,23 @@ macro_rules! impl_narrow_varint {
             }
         }
 
+        impl ProtoNarrowMerge for $ty {
+            #[inline]
+            fn merge_saturating(&mut self, wire_type: WireType, buf: &mut impl Buf, _ctx: DecodeContext) -> Result<(), DecodeError> {
+                check_wire_type(WireType::Varint, wire_type)?;
+                let widened: $full_ty = crate::encoding::decode_varint(buf)? as $full_ty;
+                *self = <$ty>::try_from(widened).unwrap_or(if widened > 0 { <$ty>::MAX } else { <$ty>::MIN });
+                Ok(())
+            }
+
+            #[inline]
+            fn merge_wrapping(&mut self, wire_type: WireType, buf: &mut impl Buf, _ctx: DecodeContext) -> Result<(), DecodeError> {
+                check_wire_type(WireType::Varint, wire_type)?;
+                *self = crate::encoding::decode_varint(buf)? as $ty;
+                Ok(())
+            }
+        }
+
         impl ProtoDefault for $ty {
             #[inline]
             fn proto_default() -> Self {
@@ -484,6 +557,12 @@ macro_rules! impl_narrow_varint {
                     ArchivedProtoField::<TAG, Self>::put_key(w);
                 }
             }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                let widened: $wide_ty = *self as $wide_ty;
+                Some(ArchivedProtoField::<TAG, Self>::hint_key(encoded_len_varint(widened as u64)))
+            }
         }
 
         impl ProtoEncode for $ty {
@@ -492,10 +571,24 @@ macro_rules! impl_narrow_varint {
     };
 }
 
-impl_narrow_varint!(u8, u32, U8, "u8 overflow");
-impl_narrow_varint!(u16, u32, U16, "u16 overflow");
-impl_narrow_varint!(i8, i32, I8, "i8 overflow");
-impl_narrow_varint!(i16, i32, I16, "i16 overflow");
+impl_narrow_varint!(u8, u32, u64, U8, "u8 overflow");
+impl_narrow_varint!(u16, u32, u64, U16, "u16 overflow");
+impl_narrow_varint!(i8, i32, i64, I8, "i8 overflow");
+impl_narrow_varint!(i16, i32, i64, I16, "i16 overflow");
+impl_narrow_varint!(usize, u64, u64, U64, "usize overflow");
+impl_narrow_varint!(isize, i64, i64, I64, "isize overflow");
+
+impl<T: ProtoNarrowMerge + ProtoDefault> ProtoNarrowMerge for Option<T> {
+    #[inline]
+    fn merge_saturating(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        self.get_or_insert_with(T::proto_default).merge_saturating(wire_type, buf, ctx)
+    }
+
+    #[inline]
+    fn merge_wrapping(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        self.get_or_insert_with(T::proto_default).merge_wrapping(wire_type, buf, ctx)
+    }
+}
 
 // ============================================================================
 // Atomic primitives
@@ -571,6 +664,12 @@ macro_rules! impl_atomic_primitive {
                 let value: $base = ($load)(self);
                 value.archive::<TAG>(w);
             }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                let value: $base = ($load)(self);
+                value.size_hint::<TAG>()
+            }
         }
 
         impl ProtoEncode for $ty {
@@ -588,6 +687,12 @@ macro_rules! impl_atomic_primitive {
                 let value: $base = ($load)(*self);
                 value.archive::<TAG>(w);
             }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                let value: $base = ($load)(*self);
+                value.size_hint::<TAG>()
+            }
         }
     };
 }
@@ -673,6 +778,12 @@ macro_rules! impl_atomic_narrow_primitive {
                     ArchivedProtoField::<TAG, Self>::put_key(w);
                 }
             }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                let value: $wide = ($load)(self) as $wide;
+                Some(ArchivedProtoField::<TAG, Self>::hint_key(encoded_len_varint(value as u64)))
+            }
         }
 
         impl ProtoEncode for $ty {
@@ -693,6 +804,12 @@ macro_rules! impl_atomic_narrow_primitive {
                     ArchivedProtoField::<TAG, Self>::put_key(w);
                 }
             }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                let value: $wide = ($load)(*self) as $wide;
+                Some(ArchivedProtoField::<TAG, Self>::hint_key(encoded_len_varint(value as u64)))
+            }
         }
     };
 }
@@ -882,6 +999,13 @@ macro_rules! impl_nonzero_wide_varint {
                     ArchivedProtoField::<TAG, Self>::put_key(w);
                 }
             }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                Some(ArchivedProtoField::<TAG, Self>::hint_key(encoded_len_varint(
+                    self.get() as u64
+                )))
+            }
         }
 
         impl ProtoEncode for $ty {
@@ -961,6 +1085,12 @@ macro_rules! impl_nonzero_narrow_varint {
                     ArchivedProtoField::<TAG, Self>::put_key(w);
                 }
             }
+
+            #[inline]
+            fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+                let widened: $wide = self.get() as $wide;
+                Some(ArchivedProtoField::<TAG, Self>::hint_key(encoded_len_varint(widened as u64)))
+            }
         }
 
         impl ProtoEncode for $ty {
@@ -1021,6 +1151,11 @@ impl ProtoArchive for () {
 
     #[inline]
     fn archive<const TAG: u32>(&self, _w: &mut impl RevWriter) {}
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        Some(0)
+    }
 }
 
 impl ProtoEncode for () {
@@ -0,0 +1,62 @@
+//! Reporting of enum values the local definition does not know.
+//!
+//! Decoding an unknown value into a simple enum fails with a [`DecodeError`](crate::DecodeError).
+//! Before that error is returned, every hook registered here sees the enum, the message field it
+//! was decoded into and the raw value, so schema drift between deployed versions can be logged
+//! or alerted on instead of only surfacing as failed decodes.
+//!
+//! ```rust,ignore
+//! proto_rs::unknown_enum::add_unknown_enum_hook(|event| {
+//!     tracing::warn!(enum_name = event.enum_name, field = ?event.field, value = event.value, "unknown enum value");
+//! });
+//! ```
+
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::Ordering;
+use std::sync::RwLock;
+
+use crate::alloc::boxed::Box;
+use crate::alloc::vec::Vec;
+
+/// One unknown enum value met while decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownEnum {
+    /// Rust name of the enum.
+    pub enum_name: &'static str,
+    /// Innermost message holding the enum, or `None` when the enum was decoded on its own.
+    pub message: Option<&'static str>,
+    /// Field of `message` holding the enum.
+    pub field: Option<&'static str>,
+    /// Raw value from the wire; values of `#[repr(u64)]` enums above `i64::MAX` wrap around.
+    pub value: i64,
+}
+
+type UnknownEnumHook = Box<dyn Fn(&UnknownEnum) + Send + Sync>;
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static HOOKS: RwLock<Vec<UnknownEnumHook>> = RwLock::new(Vec::new());
+
+/// Registers a callback invoked for every unknown enum value, before the decode error is returned.
+/// Hooks run on the decoding thread, so they should be cheap.
+pub fn add_unknown_enum_hook(hook: impl Fn(&UnknownEnum) + Send + Sync + 'static) {
+    let mut hooks = HOOKS.write().unwrap_or_else(std::sync::PoisonError::into_inner);
+    hooks.push(Box::new(hook));
+    ACTIVE.store(true, Ordering::Release);
+}
+
+/// Removes every registered hook.
+pub fn clear_unknown_enum_hooks() {
+    let mut hooks = HOOKS.write().unwrap_or_else(std::sync::PoisonError::into_inner);
+    hooks.clear();
+    ACTIVE.store(false, Ordering::Release);
+}
+
+pub(crate) fn report(event: &UnknownEnum) {
+    if !ACTIVE.load(Ordering::Acquire) {
+        return;
+    }
+    let hooks = HOOKS.read().unwrap_or_else(std::sync::PoisonError::into_inner);
+    for hook in hooks.iter() {
+        hook(event);
+    }
+}
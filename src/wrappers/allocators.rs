@@ -0,0 +1,295 @@
+//! `Vec<T, A>` and `Box<T, A>` with a custom allocator.
+//!
+//! Decoding allocates through `A::default()`, so the allocator type is a handle to an arena or
+//! region that can be created anywhere, typically a zero-sized type or a thread-local lookup.
+//! Allocators opt in by implementing [`ProtoAllocator`]; the global allocator keeps the plain
+//! `Vec<T>` and `Box<T>` impls.
+
+use alloc::alloc::Allocator;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ptr;
+
+use bytes::Buf;
+
+use crate::DecodeError;
+use crate::encoding::DecodeContext;
+use crate::encoding::WireType;
+use crate::encoding::check_wire_type;
+use crate::encoding::decode_varint;
+use crate::encoding::skip_field;
+#[cfg(feature = "build-schemas")]
+use crate::schemas::ProtoIdent;
+#[cfg(feature = "build-schemas")]
+use crate::schemas::ProtoIdentifiable;
+#[cfg(feature = "build-schemas")]
+use crate::schemas::ProtoType;
+use crate::traits::PrimitiveKind;
+use crate::traits::ProtoArchive;
+use crate::traits::ProtoDecode;
+use crate::traits::ProtoDecoder;
+use crate::traits::ProtoDefault;
+use crate::traits::ProtoEncode;
+use crate::traits::ProtoExt;
+use crate::traits::ProtoFieldMerge;
+use crate::traits::ProtoKind;
+use crate::traits::ProtoShadowDecode;
+use crate::traits::ProtoShadowEncode;
+use crate::traits::buffer::RevWriter;
+
+/// An allocator that decoded `Vec<T, A>` and `Box<T, A>` fields are allocated in.
+///
+/// ```rust,ignore
+/// #[derive(Clone, Copy, Default)]
+/// struct Arena;
+///
+/// unsafe impl Allocator for Arena { /* ... */ }
+/// impl proto_rs::ProtoAllocator for Arena {}
+///
+/// #[proto_message]
+/// struct Row {
+///     cells: Vec<u64, Arena>,
+///     payload: Vec<u8, Arena>,
+/// }
+/// ```
+pub trait ProtoAllocator: Allocator + Clone + Default {}
+
+impl<T: ProtoExt, A: ProtoAllocator> ProtoExt for Vec<T, A> {
+    const KIND: ProtoKind = match T::KIND {
+        ProtoKind::Primitive(PrimitiveKind::U8) => ProtoKind::Bytes,
+        _ => ProtoKind::Repeated(&T::KIND),
+    };
+    const _REPEATED_SUPPORT: Option<&'static str> = match T::KIND {
+        ProtoKind::Primitive(PrimitiveKind::U8) => None,
+        _ => Some("Vec"),
+    };
+}
+
+impl<T: ProtoFieldMerge + ProtoDefault, A: ProtoAllocator> ProtoDecoder for Vec<T, A> {
+    #[inline]
+    fn merge_field(value: &mut Self, tag: u32, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        if tag == 1 {
+            Self::merge(value, wire_type, buf, ctx)
+        } else {
+            skip_field(wire_type, tag, buf, ctx)
+        }
+    }
+
+    #[inline]
+    fn merge(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        if T::KIND.is_bytes_kind() {
+            // SAFETY: only executed for Vec<u8, A>
+            let bytes = unsafe { &mut *(ptr::from_mut(self).cast::<Vec<u8, A>>()) };
+            return merge_bytes(wire_type, bytes, buf);
+        }
+        match T::KIND {
+            ProtoKind::Primitive(_) | ProtoKind::SimpleEnum if wire_type == WireType::LengthDelimited => {
+                let len = decode_varint(buf)? as usize;
+                let remaining = buf.remaining();
+                if len > remaining {
+                    return Err(DecodeError::new("buffer underflow"));
+                }
+                let limit = remaining - len;
+                while buf.remaining() > limit {
+                    let mut v = <T as ProtoDefault>::proto_default();
+                    T::merge_value(&mut v, T::WIRE_TYPE, buf, ctx)?;
+                    ctx.charge(size_of::<T>())?;
+                    self.push(v);
+                }
+                Ok(())
+            }
+            ProtoKind::Repeated(_) => unreachable!(),
+            _ => {
+                let mut v = <T as ProtoDefault>::proto_default();
+                T::merge_value(&mut v, wire_type, buf, ctx)?;
+                ctx.charge(size_of::<T>())?;
+                self.push(v);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Replaces `value` with the next length-delimited bytes, copied into its own allocator.
+fn merge_bytes<A: Allocator>(wire_type: WireType, value: &mut Vec<u8, A>, buf: &mut impl Buf) -> Result<(), DecodeError> {
+    check_wire_type(WireType::LengthDelimited, wire_type)?;
+    let len = decode_varint(buf)? as usize;
+    if len > buf.remaining() {
+        return Err(DecodeError::new("buffer underflow"));
+    }
+    value.clear();
+    value.reserve(len);
+    let mut left = len;
+    while left > 0 {
+        let chunk = buf.chunk();
+        let take = chunk.len().min(left);
+        value.extend_from_slice(&chunk[..take]);
+        buf.advance(take);
+        left -= take;
+    }
+    Ok(())
+}
+
+impl<T, A: ProtoAllocator> ProtoDefault for Vec<T, A> {
+    #[inline]
+    fn proto_default() -> Self {
+        Vec::new_in(A::default())
+    }
+}
+
+impl<T: ProtoDecode, A: ProtoAllocator> ProtoDecode for Vec<T, A>
+where
+    T::ShadowDecoded: ProtoDecoder + ProtoExt,
+    Vec<T::ShadowDecoded, A>: ProtoDecoder + ProtoExt,
+{
+    type ShadowDecoded = Vec<T::ShadowDecoded, A>;
+}
+
+impl<T, U, A: ProtoAllocator> ProtoShadowDecode<Vec<U, A>> for Vec<T, A>
+where
+    T: ProtoShadowDecode<U>,
+{
+    #[inline]
+    fn to_sun(self) -> Result<Vec<U, A>, DecodeError> {
+        let mut out = Vec::with_capacity_in(self.len(), self.allocator().clone());
+        for item in self {
+            out.push(item.to_sun()?);
+        }
+        Ok(out)
+    }
+}
+
+impl<T, A: ProtoAllocator> ProtoArchive for Vec<T, A>
+where
+    T: ProtoArchive + ProtoExt,
+{
+    #[inline]
+    fn is_default(&self) -> bool {
+        self.is_empty()
+    }
+
+    #[inline]
+    fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
+        <&[T] as ProtoArchive>::archive::<TAG>(&self.as_slice(), w);
+    }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        <&[T] as ProtoArchive>::size_hint::<TAG>(&self.as_slice())
+    }
+}
+
+impl<T: ProtoEncode, A: ProtoAllocator> ProtoEncode for Vec<T, A>
+where
+    for<'a> T: 'a + ProtoExt,
+    for<'a> A: 'a,
+    for<'a> T::Shadow<'a>: ProtoArchive + ProtoExt,
+    for<'a> &'a [T]: ProtoArchive + ProtoExt,
+{
+    type Shadow<'a> = &'a [T];
+}
+
+impl<'a, T, A: ProtoAllocator> ProtoShadowEncode<'a, Vec<T, A>> for &'a [T]
+where
+    T: ProtoEncode,
+{
+    #[inline]
+    fn from_sun(value: &'a Vec<T, A>) -> Self {
+        value.as_slice()
+    }
+}
+
+impl<T: ProtoExt, A: ProtoAllocator> ProtoExt for Box<T, A> {
+    const KIND: ProtoKind = T::KIND;
+}
+
+impl<T: ProtoFieldMerge + ProtoDefault, A: ProtoAllocator> ProtoDecoder for Box<T, A> {
+    #[inline]
+    fn merge_field(value: &mut Self, tag: u32, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        if tag == 1 {
+            T::merge_value(value.as_mut(), wire_type, buf, ctx)
+        } else {
+            skip_field(wire_type, tag, buf, ctx)
+        }
+    }
+
+    #[inline]
+    fn merge(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        T::merge_value(self.as_mut(), wire_type, buf, ctx)
+    }
+}
+
+impl<T: ProtoDefault, A: ProtoAllocator> ProtoDefault for Box<T, A> {
+    #[inline]
+    fn proto_default() -> Self {
+        Box::new_in(<T as ProtoDefault>::proto_default(), A::default())
+    }
+}
+
+impl<T: ProtoDecode, A: ProtoAllocator> ProtoDecode for Box<T, A>
+where
+    T::ShadowDecoded: ProtoDecoder + ProtoExt,
+{
+    type ShadowDecoded = Box<T::ShadowDecoded, A>;
+}
+
+impl<T, U, A: ProtoAllocator> ProtoShadowDecode<Box<U, A>> for Box<T, A>
+where
+    T: ProtoShadowDecode<U>,
+{
+    #[inline]
+    fn to_sun(self) -> Result<Box<U, A>, DecodeError> {
+        let alloc = Box::allocator(&self).clone();
+        Ok(Box::new_in((*self).to_sun()?, alloc))
+    }
+}
+
+impl<T, A: ProtoAllocator> ProtoArchive for Box<T, A>
+where
+    T: ProtoArchive,
+{
+    #[inline]
+    fn is_default(&self) -> bool {
+        T::is_default(self.as_ref())
+    }
+
+    #[inline]
+    fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
+        <T as ProtoArchive>::archive::<TAG>(self.as_ref(), w);
+    }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        <T as ProtoArchive>::size_hint::<TAG>(self.as_ref())
+    }
+}
+
+impl<T: ProtoEncode, A: ProtoAllocator> ProtoEncode for Box<T, A>
+where
+    for<'a> A: 'a,
+    for<'a> T::Shadow<'a>: ProtoArchive + ProtoExt,
+{
+    type Shadow<'a> = T::Shadow<'a>;
+}
+
+impl<'a, T, A: ProtoAllocator, S> ProtoShadowEncode<'a, Box<T, A>> for S
+where
+    S: ProtoShadowEncode<'a, T>,
+{
+    #[inline]
+    fn from_sun(value: &'a Box<T, A>) -> Self {
+        S::from_sun(value.as_ref())
+    }
+}
+
+#[cfg(feature = "build-schemas")]
+impl<T: ProtoIdentifiable, A: ProtoAllocator> ProtoIdentifiable for Vec<T, A> {
+    const PROTO_IDENT: ProtoIdent = <Vec<T> as ProtoIdentifiable>::PROTO_IDENT;
+    const PROTO_TYPE: ProtoType = <Vec<T> as ProtoIdentifiable>::PROTO_TYPE;
+}
+
+#[cfg(feature = "build-schemas")]
+impl<T: ProtoIdentifiable, A: ProtoAllocator> ProtoIdentifiable for Box<T, A> {
+    const PROTO_IDENT: ProtoIdent = <Box<T> as ProtoIdentifiable>::PROTO_IDENT;
+    const PROTO_TYPE: ProtoType = <Box<T> as ProtoIdentifiable>::PROTO_TYPE;
+}
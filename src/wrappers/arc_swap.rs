@@ -1,7 +1,6 @@
-use std::sync::Arc;
-
-use arc_swap::ArcSwap;
-use arc_swap::ArcSwapOption;
+use arc_swap::ArcSwapAny;
+use arc_swap::RefCnt;
+use arc_swap::strategy::Strategy;
 use bytes::Buf;
 
 use crate::DecodeError;
@@ -21,23 +20,25 @@ use crate::traits::ProtoShadowDecode;
 use crate::traits::ProtoShadowEncode;
 use crate::traits::buffer::RevWriter;
 
-pub struct ArcSwapShadow<T> {
-    bytes: Vec<u8>,
-    is_default: bool,
-    _marker: core::marker::PhantomData<T>,
-}
+// Everything here is implemented for `ArcSwapAny<R, S>`, so `ArcSwap<T>` (`R = Arc<T>`),
+// `ArcSwapOption<T>` (`R = Option<Arc<T>>`), custom strategies and custom ref-count types all go
+// through the impls of the stored pointer `R`.
 
-pub struct ArcSwapOptionShadow<T> {
+pub struct ArcSwapShadow<R> {
     bytes: Vec<u8>,
     is_default: bool,
-    _marker: core::marker::PhantomData<T>,
+    _marker: core::marker::PhantomData<R>,
 }
 
-impl<T: ProtoExt> ProtoExt for ArcSwap<T> {
-    const KIND: ProtoKind = T::KIND;
+impl<R: RefCnt + ProtoExt, S: Strategy<R>> ProtoExt for ArcSwapAny<R, S> {
+    const KIND: ProtoKind = R::KIND;
 }
 
-impl<T: ProtoFieldMerge + ProtoDefault> ProtoDecoder for ArcSwap<T> {
+impl<R, S> ProtoDecoder for ArcSwapAny<R, S>
+where
+    R: RefCnt + ProtoFieldMerge + ProtoDefault,
+    S: Strategy<R>,
+{
     #[inline]
     fn merge_field(value: &mut Self, tag: u32, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
         if tag == 1 {
@@ -49,49 +50,48 @@ impl<T: ProtoFieldMerge + ProtoDefault> ProtoDecoder for ArcSwap<T> {
 
     #[inline]
     fn merge(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
-        let mut inner = <T as ProtoDefault>::proto_default();
-        T::merge_value(&mut inner, wire_type, buf, ctx)?;
-        self.store(Arc::new(inner));
+        let mut inner = <R as ProtoDefault>::proto_default();
+        R::merge_value(&mut inner, wire_type, buf, ctx)?;
+        self.store(inner);
         Ok(())
     }
 }
 
-impl<T: ProtoDefault> ProtoDefault for ArcSwap<T> {
+impl<R: RefCnt + ProtoDefault, S: Strategy<R> + Default> ProtoDefault for ArcSwapAny<R, S> {
     #[inline]
     fn proto_default() -> Self {
-        ArcSwap::from_pointee(<T as ProtoDefault>::proto_default())
+        ArcSwapAny::with_strategy(<R as ProtoDefault>::proto_default(), S::default())
     }
 }
 
-impl<T: ProtoDecode> ProtoDecode for ArcSwap<T>
+impl<R, S> ProtoDecode for ArcSwapAny<R, S>
 where
-    T::ShadowDecoded: ProtoDecoder + ProtoExt,
-    ArcSwap<T::ShadowDecoded>: ProtoDecoder + ProtoExt,
+    R: RefCnt + ProtoDecode,
+    S: Strategy<R> + Default,
 {
-    type ShadowDecoded = ArcSwap<T::ShadowDecoded>;
+    type ShadowDecoded = R::ShadowDecoded;
 }
 
-impl<T, U> ProtoShadowDecode<ArcSwap<U>> for ArcSwap<T>
+impl<D, R, S> ProtoShadowDecode<ArcSwapAny<R, S>> for D
 where
-    T: ProtoShadowDecode<U>,
+    D: ProtoShadowDecode<R>,
+    R: RefCnt,
+    S: Strategy<R> + Default,
 {
     #[inline]
-    fn to_sun(self) -> Result<ArcSwap<U>, DecodeError> {
-        let arc = ArcSwap::into_inner(self);
-        let inner = Arc::try_unwrap(arc).map_err(|_| DecodeError::new("ArcSwap shadow has extra references"))?;
-        let value = inner.to_sun()?;
-        Ok(ArcSwap::from_pointee(value))
+    fn to_sun(self) -> Result<ArcSwapAny<R, S>, DecodeError> {
+        Ok(ArcSwapAny::with_strategy(self.to_sun()?, S::default()))
     }
 }
 
-impl<T> ProtoExt for ArcSwapShadow<T>
+impl<R> ProtoExt for ArcSwapShadow<R>
 where
-    T: ProtoExt,
+    R: ProtoExt,
 {
-    const KIND: ProtoKind = T::KIND;
+    const KIND: ProtoKind = R::KIND;
 }
 
-impl<T: ProtoExt> ProtoArchive for ArcSwapShadow<T> {
+impl<R: ProtoExt> ProtoArchive for ArcSwapShadow<R> {
     #[inline]
     fn is_default(&self) -> bool {
         self.is_default
@@ -107,24 +107,34 @@ impl<T: ProtoExt> ProtoArchive for ArcSwapShadow<T> {
             ArchivedProtoField::<TAG, Self>::put_key(w);
         }
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        Some(ArchivedProtoField::<TAG, Self>::hint_delimited(self.bytes.len()))
+    }
 }
 
-impl<T: ProtoEncode + ProtoArchive + ProtoExt> ProtoEncode for ArcSwap<T>
+impl<R, S> ProtoEncode for ArcSwapAny<R, S>
 where
-    for<'a> T::Shadow<'a>: ProtoArchive + ProtoExt + ProtoShadowEncode<'a, T>,
+    R: RefCnt + ProtoEncode + ProtoExt,
+    for<'a> R::Shadow<'a>: ProtoArchive + ProtoExt + ProtoShadowEncode<'a, R>,
+    S: Strategy<R>,
 {
-    type Shadow<'a> = ArcSwapShadow<T>;
+    type Shadow<'a> = ArcSwapShadow<R>;
 }
 
-impl<'a, T> ProtoShadowEncode<'a, ArcSwap<T>> for ArcSwapShadow<T>
+impl<'a, R, S> ProtoShadowEncode<'a, ArcSwapAny<R, S>> for ArcSwapShadow<R>
 where
-    T: ProtoEncode + ProtoArchive + ProtoExt,
+    R: RefCnt + ProtoEncode + ProtoExt,
+    for<'b> R::Shadow<'b>: ProtoArchive + ProtoExt + ProtoShadowEncode<'b, R>,
+    S: Strategy<R>,
 {
     #[inline]
-    fn from_sun(value: &'a ArcSwap<T>) -> Self {
-        let guard = value.load_full();
-        let is_default = T::is_default(guard.as_ref());
-        let bytes = if is_default { Vec::new() } else { guard.encode_to_vec() };
+    fn from_sun(value: &'a ArcSwapAny<R, S>) -> Self {
+        // The loaded pointer only lives for this call, so the value is encoded right away
+        let current = value.load_full();
+        let is_default = R::Shadow::from_sun(&current).is_default();
+        let bytes = if is_default { Vec::new() } else { current.encode_to_vec() };
         Self {
             bytes,
             is_default,
@@ -132,117 +142,3 @@ where
         }
     }
 }
-
-impl<T: ProtoExt> ProtoExt for ArcSwapOption<T> {
-    const KIND: ProtoKind = T::KIND;
-}
-
-impl<T: ProtoFieldMerge + ProtoDefault> ProtoDecoder for ArcSwapOption<T> {
-    #[inline]
-    fn merge_field(value: &mut Self, tag: u32, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
-        if tag == 1 {
-            value.merge(wire_type, buf, ctx)
-        } else {
-            skip_field(wire_type, tag, buf, ctx)
-        }
-    }
-
-    #[inline]
-    fn merge(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
-        let mut inner = <T as ProtoDefault>::proto_default();
-        T::merge_value(&mut inner, wire_type, buf, ctx)?;
-        self.store(Some(Arc::new(inner)));
-        Ok(())
-    }
-}
-
-impl<T> ProtoDefault for ArcSwapOption<T> {
-    #[inline]
-    fn proto_default() -> Self {
-        ArcSwapOption::from_pointee(None)
-    }
-}
-
-impl<T: ProtoDecode> ProtoDecode for ArcSwapOption<T>
-where
-    T::ShadowDecoded: ProtoDecoder + ProtoExt,
-    ArcSwapOption<T::ShadowDecoded>: ProtoDecoder + ProtoExt,
-{
-    type ShadowDecoded = ArcSwapOption<T::ShadowDecoded>;
-}
-
-impl<T, U> ProtoShadowDecode<ArcSwapOption<U>> for ArcSwapOption<T>
-where
-    T: ProtoShadowDecode<U>,
-{
-    #[inline]
-    fn to_sun(self) -> Result<ArcSwapOption<U>, DecodeError> {
-        let inner = ArcSwapOption::into_inner(self);
-        let value = match inner {
-            Some(arc) => {
-                let inner = Arc::try_unwrap(arc).map_err(|_| DecodeError::new("ArcSwapOption shadow has extra references"))?;
-                Some(inner.to_sun()?)
-            }
-            None => None,
-        };
-        Ok(ArcSwapOption::from_pointee(value))
-    }
-}
-
-impl<T> ProtoExt for ArcSwapOptionShadow<T>
-where
-    T: ProtoExt,
-{
-    const KIND: ProtoKind = T::KIND;
-}
-
-impl<T: ProtoExt> ProtoArchive for ArcSwapOptionShadow<T> {
-    #[inline]
-    fn is_default(&self) -> bool {
-        self.is_default
-    }
-
-    #[inline]
-    fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
-        w.put_slice(self.bytes.as_slice());
-        if TAG != 0 {
-            if Self::WIRE_TYPE.is_length_delimited() {
-                w.put_varint(self.bytes.len() as u64);
-            }
-            ArchivedProtoField::<TAG, Self>::put_key(w);
-        }
-    }
-}
-
-impl<T: ProtoEncode + ProtoArchive + ProtoExt> ProtoEncode for ArcSwapOption<T>
-where
-    for<'a> T::Shadow<'a>: ProtoArchive + ProtoExt + ProtoShadowEncode<'a, T>,
-{
-    type Shadow<'a> = ArcSwapOptionShadow<T>;
-}
-
-impl<'a, T> ProtoShadowEncode<'a, ArcSwapOption<T>> for ArcSwapOptionShadow<T>
-where
-    T: ProtoEncode + ProtoArchive + ProtoExt,
-{
-    #[inline]
-    fn from_sun(value: &'a ArcSwapOption<T>) -> Self {
-        let guard = value.load_full();
-        match guard.as_ref() {
-            Some(inner) => {
-                let is_default = T::is_default(inner.as_ref());
-                let bytes = if is_default { Vec::new() } else { inner.encode_to_vec() };
-                Self {
-                    bytes,
-                    is_default,
-                    _marker: core::marker::PhantomData,
-                }
-            }
-            None => Self {
-                bytes: Vec::new(),
-                is_default: true,
-                _marker: core::marker::PhantomData,
-            },
-        }
-    }
-}
@@ -19,6 +19,7 @@ use crate::traits::buffer::RevWriter;
 
 impl<T: ProtoExt> ProtoExt for Arc<T> {
     const KIND: ProtoKind = T::KIND;
+    const MAX_PAYLOAD_LEN: Option<usize> = T::MAX_PAYLOAD_LEN;
 }
 
 impl<T: ProtoDecode> ProtoDecode for Arc<T>
@@ -96,6 +97,11 @@ where
     fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
         <T as ProtoArchive>::archive::<TAG>(self.as_ref(), w);
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        <T as ProtoArchive>::size_hint::<TAG>(self.as_ref())
+    }
 }
 
 impl<T: ProtoEncode> ProtoEncode for Arc<T>
@@ -128,4 +134,9 @@ where
     fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
         <T as ProtoArchive>::archive::<TAG>(self.as_ref(), w);
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        <T as ProtoArchive>::size_hint::<TAG>(self.as_ref())
+    }
 }
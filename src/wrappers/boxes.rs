@@ -20,6 +20,7 @@ use crate::traits::buffer::RevWriter;
 
 impl<T: ProtoExt> ProtoExt for Box<T> {
     const KIND: ProtoKind = T::KIND;
+    const MAX_PAYLOAD_LEN: Option<usize> = T::MAX_PAYLOAD_LEN;
 }
 
 impl<T: ProtoFieldMerge + ProtoDefault> ProtoDecoder for Box<T> {
@@ -75,6 +76,11 @@ where
     fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
         <T as ProtoArchive>::archive::<TAG>(self.as_ref(), w);
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        <T as ProtoArchive>::size_hint::<TAG>(self.as_ref())
+    }
 }
 
 impl<T: ProtoEncode> ProtoEncode for Box<T>
@@ -107,4 +113,9 @@ where
     fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
         <T as ProtoArchive>::archive::<TAG>(self.as_ref(), w);
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        <T as ProtoArchive>::size_hint::<TAG>(self.as_ref())
+    }
 }
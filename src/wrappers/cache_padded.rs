@@ -76,6 +76,11 @@ where
     fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
         <T as ProtoArchive>::archive::<TAG>(self, w);
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        <T as ProtoArchive>::size_hint::<TAG>(self)
+    }
 }
 
 impl<T: ProtoEncode> ProtoEncode for CachePadded<T>
@@ -108,6 +113,11 @@ where
     fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
         <T as ProtoArchive>::archive::<TAG>(self, w);
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        <T as ProtoArchive>::size_hint::<TAG>(self)
+    }
 }
 
 #[cfg(test)]
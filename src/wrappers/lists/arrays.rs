@@ -12,6 +12,7 @@ use crate::encoding::skip_field;
 use crate::traits::ArchivedProtoField;
 use crate::traits::PrimitiveKind;
 use crate::traits::ProtoArchive;
+use crate::traits::ProtoArrayMerge;
 use crate::traits::ProtoDecode;
 use crate::traits::ProtoDecoder;
 use crate::traits::ProtoDefault;
@@ -22,6 +23,8 @@ use crate::traits::ProtoKind;
 use crate::traits::ProtoShadowDecode;
 use crate::traits::ProtoShadowEncode;
 use crate::traits::buffer::RevWriter;
+use crate::wrappers::RunKey;
+use crate::wrappers::lists::repeated_size_hint;
 
 #[cfg(feature = "stable")]
 #[inline]
@@ -41,8 +44,16 @@ unsafe fn assume_init_array<T, const N: usize>(arr: [MaybeUninit<T>; N]) -> [T;
 impl<T: ProtoExt, const N: usize> ProtoExt for [T; N] {
     const KIND: ProtoKind = match T::KIND {
         ProtoKind::Primitive(PrimitiveKind::U8) => ProtoKind::Bytes,
+        // protobuf has no repeated-of-repeated; `[[u8; N]; M]` works because `[u8; N]` is bytes
+        ProtoKind::Repeated(_) => panic!("nested arrays are only supported as arrays of byte arrays"),
         _ => ProtoKind::Repeated(&T::KIND),
     };
+    // the bytes themselves or a packed run; elements with keys of their own are not bounded here
+    const MAX_PAYLOAD_LEN: Option<usize> = match (&T::KIND, T::MAX_PAYLOAD_LEN) {
+        (ProtoKind::Primitive(PrimitiveKind::U8), _) => Some(N),
+        (ProtoKind::Primitive(_) | ProtoKind::SimpleEnum, Some(len)) => Some(N * len),
+        _ => None,
+    };
     const _REPEATED_SUPPORT: Option<&'static str> = match T::KIND {
         ProtoKind::Primitive(PrimitiveKind::U8) => None,
         _ => Some("Array"),
@@ -53,7 +64,7 @@ impl<T: ProtoFieldMerge + ProtoDefault, const N: usize> ProtoDecoder for [T; N]
     #[inline]
     fn merge_field(value: &mut Self, tag: u32, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
         if tag == 1 {
-            Self::merge(value, wire_type, buf, ctx)
+            value.merge_array::<1>(wire_type, buf, ctx)
         } else {
             skip_field(wire_type, tag, buf, ctx)
         }
@@ -77,36 +88,68 @@ impl<T: ProtoFieldMerge + ProtoDefault, const N: usize> ProtoDecoder for [T; N]
             buf.copy_to_slice(bytes);
             return Ok(());
         }
-        match T::KIND {
-            ProtoKind::Primitive(_) | ProtoKind::SimpleEnum => {
-                if wire_type == WireType::LengthDelimited {
-                    let len = decode_varint(buf)? as usize;
-                    let mut slice = buf.take(len);
-                    for v in self.iter_mut() {
-                        if !slice.has_remaining() {
-                            break;
-                        }
-                        T::merge_value(v, T::WIRE_TYPE, &mut slice, ctx)?;
-                    }
-                    debug_assert!(!slice.has_remaining());
-                } else {
-                    for v in self.iter_mut() {
-                        T::merge_value(v, wire_type, buf, ctx)?;
-                    }
+        if is_packed::<T>(wire_type) {
+            let len = decode_varint(buf)? as usize;
+            let mut slice = buf.take(len);
+            for v in self.iter_mut() {
+                if !slice.has_remaining() {
+                    break;
                 }
-                Ok(())
+                T::merge_value(v, T::WIRE_TYPE, &mut slice, ctx)?;
             }
-            ProtoKind::String | ProtoKind::Bytes | ProtoKind::Message => {
-                for v in self.iter_mut() {
-                    T::merge_value(v, wire_type, buf, ctx)?;
-                }
-                Ok(())
+            if slice.has_remaining() {
+                return Err(DecodeError::new(format!("too many elements for fixed array of {N}")));
             }
-            ProtoKind::Repeated(_) => unreachable!(),
+            return Ok(());
+        }
+        // Without the field tag the rest of a run cannot be told apart from the next field, so
+        // only single-element arrays decode here; fields go through `merge_array`.
+        if N > 1 {
+            return Err(DecodeError::new(format!(
+                "fixed array of {N} elements must be decoded with its field tag"
+            )));
+        }
+        for v in self.iter_mut() {
+            *v = T::proto_default();
+            T::merge_value(v, wire_type, buf, ctx)?;
         }
+        Ok(())
     }
 }
 
+impl<T: ProtoFieldMerge + ProtoDefault, const N: usize> ProtoArrayMerge for [T; N] {
+    #[inline]
+    fn merge_array<const TAG: u32>(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        if T::KIND.is_bytes_kind() || is_packed::<T>(wire_type) {
+            return self.merge(wire_type, buf, ctx);
+        }
+        let key = RunKey::new(TAG, wire_type);
+        let mut filled = 0;
+        loop {
+            let Some(slot) = self.get_mut(filled) else {
+                return Err(DecodeError::new(format!("too many elements for fixed array of {N}")));
+            };
+            *slot = T::proto_default();
+            T::merge_value(slot, wire_type, buf, ctx)?;
+            filled += 1;
+            if !key.take(buf) {
+                break;
+            }
+        }
+        // A later run of the field replaces the array, as a later occurrence of a singular field would
+        for slot in &mut self[filled..] {
+            *slot = T::proto_default();
+        }
+        Ok(())
+    }
+}
+
+/// Whether `wire_type` carries a packed run of scalar elements.
+#[inline]
+fn is_packed<T: ProtoExt>(wire_type: WireType) -> bool {
+    wire_type == WireType::LengthDelimited && matches!(T::KIND, ProtoKind::Primitive(_) | ProtoKind::SimpleEnum)
+}
+
 impl<T: ProtoDefault, const N: usize> ProtoDefault for [T; N] {
     #[inline]
     fn proto_default() -> Self {
@@ -186,6 +229,14 @@ where
             ProtoKind::Repeated(_) => unreachable!(),
         }
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        if T::KIND.is_bytes_kind() {
+            return Some(ArchivedProtoField::<TAG, Self>::hint_delimited(N));
+        }
+        repeated_size_hint::<TAG, T>(N)
+    }
 }
 
 /// Wrapper type for array shadows that preserves array default semantics.
@@ -198,6 +249,7 @@ pub struct ArrayShadow<'a, T: ProtoArchive + ProtoExt, const N: usize> {
 
 impl<T: ProtoArchive + ProtoExt, const N: usize> ProtoExt for ArrayShadow<'_, T, N> {
     const KIND: ProtoKind = <[T; N] as ProtoExt>::KIND;
+    const MAX_PAYLOAD_LEN: Option<usize> = <[T; N] as ProtoExt>::MAX_PAYLOAD_LEN;
     const _REPEATED_SUPPORT: Option<&'static str> = <[T; N] as ProtoExt>::_REPEATED_SUPPORT;
 }
 
@@ -212,6 +264,11 @@ impl<T: ProtoArchive + ProtoExt, const N: usize> ProtoArchive for ArrayShadow<'_
     fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
         self.slice.archive::<TAG>(w);
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        self.slice.size_hint::<TAG>()
+    }
 }
 
 impl<T: ProtoEncode, const N: usize> ProtoEncode for [T; N]
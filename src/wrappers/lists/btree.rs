@@ -18,6 +18,7 @@ use crate::traits::ProtoFieldMerge;
 use crate::traits::ProtoKind;
 use crate::traits::ProtoShadowDecode;
 use crate::traits::ProtoShadowEncode;
+use crate::traits::ProtoUniqueMerge;
 
 impl<T: ProtoExt + Ord> ProtoExt for BTreeSet<T> {
     const KIND: ProtoKind = ProtoKind::Repeated(&T::KIND);
@@ -34,6 +35,7 @@ impl<T: ProtoFieldMerge + ProtoDefault + Ord> ProtoDecoder for BTreeSet<T> {
         }
     }
 
+    /// An element equal to one already in the set replaces it, so the last occurrence wins.
     #[inline]
     fn merge(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
         match T::KIND {
@@ -44,20 +46,23 @@ impl<T: ProtoFieldMerge + ProtoDefault + Ord> ProtoDecoder for BTreeSet<T> {
                     while slice.has_remaining() {
                         let mut v = <T as ProtoDefault>::proto_default();
                         T::merge_value(&mut v, T::WIRE_TYPE, &mut slice, ctx)?;
-                        self.insert(v);
+                        ctx.charge(size_of::<T>())?;
+                        self.replace(v);
                     }
                     debug_assert!(!slice.has_remaining());
                 } else {
                     let mut v = <T as ProtoDefault>::proto_default();
                     T::merge_value(&mut v, wire_type, buf, ctx)?;
-                    self.insert(v);
+                    ctx.charge(size_of::<T>())?;
+                    self.replace(v);
                 }
                 Ok(())
             }
             ProtoKind::String | ProtoKind::Bytes | ProtoKind::Message => {
                 let mut v = <T as ProtoDefault>::proto_default();
                 T::merge_value(&mut v, wire_type, buf, ctx)?;
-                self.insert(v);
+                ctx.charge(size_of::<T>())?;
+                self.replace(v);
                 Ok(())
             }
             ProtoKind::Repeated(_) => unreachable!(),
@@ -65,6 +70,21 @@ impl<T: ProtoFieldMerge + ProtoDefault + Ord> ProtoDecoder for BTreeSet<T> {
     }
 }
 
+impl<T: ProtoFieldMerge + ProtoDefault + Ord> ProtoUniqueMerge for BTreeSet<T> {
+    #[inline]
+    fn merge_unique(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        // A packed occurrence may carry several elements, so collect them before checking
+        let mut items = Vec::new();
+        <Vec<T> as ProtoFieldMerge>::merge_value(&mut items, wire_type, buf, ctx)?;
+        for item in items {
+            if !self.insert(item) {
+                return Err(DecodeError::new("duplicate set element"));
+            }
+        }
+        Ok(())
+    }
+}
+
 impl<T> ProtoDefault for BTreeSet<T> {
     #[inline]
     fn proto_default() -> Self {
@@ -86,7 +106,11 @@ where
 {
     #[inline]
     fn to_sun(self) -> Result<BTreeSet<U>, DecodeError> {
-        self.into_iter().map(T::to_sun).collect()
+        let mut set = BTreeSet::new();
+        for item in self {
+            set.replace(item.to_sun()?);
+        }
+        Ok(set)
     }
 }
 
@@ -1,9 +1,13 @@
 use alloc::vec::Vec;
 use core::hash::BuildHasher;
 use core::hash::Hash;
+use core::ptr;
 
 use bytes::Buf;
+use papaya::Guard;
 use papaya::HashSet;
+use papaya::HashSetRef;
+use papaya::LocalGuard;
 
 use crate::DecodeError;
 use crate::ProtoArchive;
@@ -23,6 +27,8 @@ use crate::traits::ProtoKind;
 use crate::traits::ProtoShadowDecode;
 use crate::traits::ProtoShadowEncode;
 use crate::traits::buffer::RevWriter;
+use crate::wrappers::RunKey;
+use crate::wrappers::lists::repeated_size_hint;
 
 impl<T: ProtoExt + Eq + Hash, S> ProtoExt for HashSet<T, S> {
     const KIND: ProtoKind = match T::KIND {
@@ -60,33 +66,64 @@ where
 
     #[inline]
     fn merge(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        merge_into(&self.pin(), wire_type, buf, ctx)
+    }
+
+    /// Inserts the whole run of elements under one guard.
+    #[inline]
+    fn merge_run<const TAG: u32>(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        let run = RunKey::new(TAG, wire_type);
         let guard = self.pin();
-        match T::KIND {
-            ProtoKind::Primitive(_) | ProtoKind::SimpleEnum => {
-                if wire_type == WireType::LengthDelimited {
-                    let len = decode_varint(buf)? as usize;
-                    let mut slice = buf.take(len);
-                    while slice.has_remaining() {
-                        let mut v = <T as ProtoDefault>::proto_default();
-                        T::merge_value(&mut v, T::WIRE_TYPE, &mut slice, ctx)?;
-                        guard.insert(v);
-                    }
-                    debug_assert!(!slice.has_remaining());
-                } else {
+        loop {
+            merge_into(&guard, wire_type, buf, ctx)?;
+            if !run.take(buf) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Merges one field occurrence, a single element or a packed run of them, through `guard`.
+#[inline]
+fn merge_into<T, S, G>(
+    guard: &HashSetRef<'_, T, S, G>,
+    wire_type: WireType,
+    buf: &mut impl Buf,
+    ctx: DecodeContext,
+) -> Result<(), DecodeError>
+where
+    T: ProtoFieldMerge + ProtoDefault + Eq + Hash,
+    S: BuildHasher,
+    G: Guard,
+{
+    match T::KIND {
+        ProtoKind::Primitive(_) | ProtoKind::SimpleEnum => {
+            if wire_type == WireType::LengthDelimited {
+                let len = decode_varint(buf)? as usize;
+                let mut slice = buf.take(len);
+                while slice.has_remaining() {
                     let mut v = <T as ProtoDefault>::proto_default();
-                    T::merge_value(&mut v, wire_type, buf, ctx)?;
+                    T::merge_value(&mut v, T::WIRE_TYPE, &mut slice, ctx)?;
+                    ctx.charge(size_of::<T>())?;
                     guard.insert(v);
                 }
-                Ok(())
-            }
-            ProtoKind::String | ProtoKind::Bytes | ProtoKind::Message => {
+                debug_assert!(!slice.has_remaining());
+            } else {
                 let mut v = <T as ProtoDefault>::proto_default();
                 T::merge_value(&mut v, wire_type, buf, ctx)?;
+                ctx.charge(size_of::<T>())?;
                 guard.insert(v);
-                Ok(())
             }
-            ProtoKind::Repeated(_) => unreachable!(),
+            Ok(())
+        }
+        ProtoKind::String | ProtoKind::Bytes | ProtoKind::Message => {
+            let mut v = <T as ProtoDefault>::proto_default();
+            T::merge_value(&mut v, wire_type, buf, ctx)?;
+            ctx.charge(size_of::<T>())?;
+            guard.insert(v);
+            Ok(())
         }
+        ProtoKind::Repeated(_) => unreachable!(),
     }
 }
 
@@ -120,42 +157,62 @@ where
 
 impl<T: ProtoEncode + Eq + Hash, S> ProtoEncode for HashSet<T, S>
 where
-    for<'a> T: 'a + ProtoExt,
-    for<'a> T::Shadow<'a>: ProtoArchive + ProtoExt,
-    for<'a> &'a HashSet<T, S>: ProtoArchive + ProtoExt,
-    for<'a> S: 'a,
+    for<'a> T: 'a + ProtoArchive + ProtoExt,
+    for<'a> S: 'a + BuildHasher,
 {
-    type Shadow<'a> = &'a HashSet<T, S>;
+    type Shadow<'a> = PapayaSetSnapshot<'a, T>;
+}
+
+/// Encode shadow of a `papaya::HashSet`: its elements, read under a single guard.
+///
+/// The size hint and the archive of one encode walk the same elements, even while other threads
+/// insert into or remove from the set.
+pub struct PapayaSetSnapshot<'a, T> {
+    items: Vec<&'a T>,
+    // Keeps the elements alive; dropped after them
+    _guard: LocalGuard<'a>,
+}
+
+impl<T: ProtoExt> ProtoExt for PapayaSetSnapshot<'_, T> {
+    const KIND: ProtoKind = match T::KIND {
+        ProtoKind::Primitive(PrimitiveKind::U8) => ProtoKind::Bytes,
+        _ => ProtoKind::Repeated(&T::KIND),
+    };
 }
 
-impl<'a, T, S> ProtoShadowEncode<'a, HashSet<T, S>> for &'a HashSet<T, S>
+impl<'a, T, S> ProtoShadowEncode<'a, HashSet<T, S>> for PapayaSetSnapshot<'a, T>
 where
     T: Eq + Hash,
+    S: BuildHasher,
 {
     #[inline]
     fn from_sun(value: &'a HashSet<T, S>) -> Self {
-        value
+        let guard = value.guard();
+        let items = value
+            .iter(&guard)
+            // SAFETY: papaya reclaims an element only once every guard that could have observed it
+            // is dropped, and `guard` lives in the snapshot for as long as the references do.
+            .map(|item| unsafe { &*ptr::from_ref(item) })
+            .collect();
+        Self { items, _guard: guard }
     }
 }
 
-impl<T, S> ProtoArchive for &HashSet<T, S>
+impl<T> ProtoArchive for PapayaSetSnapshot<'_, T>
 where
-    T: ProtoArchive + ProtoExt + Eq + Hash,
-    S: BuildHasher,
+    T: ProtoArchive + ProtoExt,
 {
     #[inline]
     fn is_default(&self) -> bool {
-        self.is_empty()
+        self.items.is_empty()
     }
 
     #[inline]
     fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
-        let guard = self.pin();
         match T::KIND {
             ProtoKind::Primitive(_) | ProtoKind::SimpleEnum => {
-                let items: Vec<&T> = guard.iter().collect();
                 let mark = w.mark();
-                for item in items.into_iter().rev() {
+                for item in self.items.iter().rev() {
                     item.archive::<0>(w);
                 }
                 if TAG != 0 {
@@ -165,12 +222,16 @@ where
                 }
             }
             ProtoKind::String | ProtoKind::Bytes | ProtoKind::Message => {
-                let items: Vec<&T> = guard.iter().collect();
-                for item in items.into_iter().rev() {
-                    ArchivedProtoField::<TAG, T>::new_always(item, w);
+                for item in self.items.iter().rev() {
+                    ArchivedProtoField::<TAG, T>::new_always(*item, w);
                 }
             }
             ProtoKind::Repeated(_) => unreachable!(),
         }
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        repeated_size_hint::<TAG, T>(self.items.len())
+    }
 }
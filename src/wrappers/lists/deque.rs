@@ -22,6 +22,7 @@ use crate::traits::ProtoKind;
 use crate::traits::ProtoShadowDecode;
 use crate::traits::ProtoShadowEncode;
 use crate::traits::buffer::RevWriter;
+use crate::wrappers::lists::repeated_size_hint;
 
 impl<T: ProtoExt> ProtoExt for VecDeque<T> {
     const KIND: ProtoKind = match T::KIND {
@@ -59,12 +60,14 @@ impl<T: ProtoFieldMerge + ProtoDefault> ProtoDecoder for VecDeque<T> {
                     while slice.has_remaining() {
                         let mut v = <T as ProtoDefault>::proto_default();
                         T::merge_value(&mut v, T::WIRE_TYPE, &mut slice, ctx)?;
+                        ctx.charge(size_of::<T>())?;
                         self.push_back(v);
                     }
                     debug_assert!(!slice.has_remaining());
                 } else {
                     let mut v = <T as ProtoDefault>::proto_default();
                     T::merge_value(&mut v, wire_type, buf, ctx)?;
+                    ctx.charge(size_of::<T>())?;
                     self.push_back(v);
                 }
                 Ok(())
@@ -72,6 +75,7 @@ impl<T: ProtoFieldMerge + ProtoDefault> ProtoDecoder for VecDeque<T> {
             ProtoKind::String | ProtoKind::Bytes | ProtoKind::Message => {
                 let mut v = <T as ProtoDefault>::proto_default();
                 T::merge_value(&mut v, wire_type, buf, ctx)?;
+                ctx.charge(size_of::<T>())?;
                 self.push_back(v);
                 Ok(())
             }
@@ -150,6 +154,14 @@ where
             ProtoKind::Repeated(_) => unreachable!(),
         }
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        if T::KIND.is_bytes_kind() {
+            return Some(ArchivedProtoField::<TAG, Self>::hint_delimited(self.len()));
+        }
+        repeated_size_hint::<TAG, T>(self.len())
+    }
 }
 
 impl<T: ProtoEncode> ProtoEncode for VecDeque<T>
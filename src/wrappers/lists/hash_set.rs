@@ -19,7 +19,9 @@ use crate::traits::ProtoFieldMerge;
 use crate::traits::ProtoKind;
 use crate::traits::ProtoShadowDecode;
 use crate::traits::ProtoShadowEncode;
+use crate::traits::ProtoUniqueMerge;
 use crate::traits::buffer::RevWriter;
+use crate::wrappers::lists::repeated_size_hint;
 
 impl<T: ProtoExt + Eq + core::hash::Hash, S> ProtoExt for HashSet<T, S> {
     const KIND: ProtoKind = match T::KIND {
@@ -54,6 +56,7 @@ where
         }
     }
 
+    /// An element equal to one already in the set replaces it, so the last occurrence wins.
     #[inline]
     fn merge(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
         match T::KIND {
@@ -64,20 +67,23 @@ where
                     while slice.has_remaining() {
                         let mut v = <T as ProtoDefault>::proto_default();
                         T::merge_value(&mut v, T::WIRE_TYPE, &mut slice, ctx)?;
-                        self.insert(v);
+                        ctx.charge(size_of::<T>())?;
+                        self.replace(v);
                     }
                     debug_assert!(!slice.has_remaining());
                 } else {
                     let mut v = <T as ProtoDefault>::proto_default();
                     T::merge_value(&mut v, wire_type, buf, ctx)?;
-                    self.insert(v);
+                    ctx.charge(size_of::<T>())?;
+                    self.replace(v);
                 }
                 Ok(())
             }
             ProtoKind::String | ProtoKind::Bytes | ProtoKind::Message => {
                 let mut v = <T as ProtoDefault>::proto_default();
                 T::merge_value(&mut v, wire_type, buf, ctx)?;
-                self.insert(v);
+                ctx.charge(size_of::<T>())?;
+                self.replace(v);
                 Ok(())
             }
             ProtoKind::Repeated(_) => unreachable!(),
@@ -85,6 +91,25 @@ where
     }
 }
 
+impl<T, S> ProtoUniqueMerge for HashSet<T, S>
+where
+    T: ProtoFieldMerge + ProtoDefault + Eq + core::hash::Hash,
+    S: Default + core::hash::BuildHasher,
+{
+    #[inline]
+    fn merge_unique(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        // A packed occurrence may carry several elements, so collect them before checking
+        let mut items = Vec::new();
+        <Vec<T> as ProtoFieldMerge>::merge_value(&mut items, wire_type, buf, ctx)?;
+        for item in items {
+            if !self.insert(item) {
+                return Err(DecodeError::new("duplicate set element"));
+            }
+        }
+        Ok(())
+    }
+}
+
 impl<T, S> ProtoDefault for HashSet<T, S>
 where
     S: Default + core::hash::BuildHasher,
@@ -102,7 +127,11 @@ where
 {
     #[inline]
     fn to_sun(self) -> Result<HashSet<U>, DecodeError> {
-        self.into_iter().map(T::to_sun).collect()
+        let mut set = HashSet::with_capacity(self.len());
+        for item in self {
+            set.replace(item.to_sun()?);
+        }
+        Ok(set)
     }
 }
 
@@ -159,4 +188,9 @@ where
             ProtoKind::Repeated(_) => unreachable!(),
         }
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        repeated_size_hint::<TAG, T>(self.len())
+    }
 }
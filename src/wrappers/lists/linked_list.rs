@@ -0,0 +1,153 @@
+use alloc::collections::LinkedList;
+
+use bytes::Buf;
+
+use crate::DecodeError;
+use crate::encoding::DecodeContext;
+use crate::encoding::WireType;
+use crate::encoding::decode_varint;
+use crate::encoding::skip_field;
+use crate::traits::ArchivedProtoField;
+use crate::traits::ProtoArchive;
+use crate::traits::ProtoDecode;
+use crate::traits::ProtoDecoder;
+use crate::traits::ProtoDefault;
+use crate::traits::ProtoEncode;
+use crate::traits::ProtoExt;
+use crate::traits::ProtoFieldMerge;
+use crate::traits::ProtoKind;
+use crate::traits::ProtoShadowDecode;
+use crate::traits::ProtoShadowEncode;
+use crate::traits::buffer::RevWriter;
+use crate::wrappers::lists::repeated_size_hint;
+
+impl<T: ProtoExt> ProtoExt for LinkedList<T> {
+    const KIND: ProtoKind = ProtoKind::Repeated(&T::KIND);
+    const _REPEATED_SUPPORT: Option<&'static str> = Some("LinkedList");
+}
+
+impl<T: ProtoFieldMerge + ProtoDefault> ProtoDecoder for LinkedList<T> {
+    #[inline]
+    fn merge_field(value: &mut Self, tag: u32, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        if tag == 1 {
+            Self::merge(value, wire_type, buf, ctx)
+        } else {
+            skip_field(wire_type, tag, buf, ctx)
+        }
+    }
+
+    #[inline]
+    fn merge(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        match T::KIND {
+            ProtoKind::Primitive(_) | ProtoKind::SimpleEnum => {
+                if wire_type == WireType::LengthDelimited {
+                    let len = decode_varint(buf)? as usize;
+                    let mut slice = buf.take(len);
+                    while slice.has_remaining() {
+                        let mut v = <T as ProtoDefault>::proto_default();
+                        T::merge_value(&mut v, T::WIRE_TYPE, &mut slice, ctx)?;
+                        ctx.charge(size_of::<T>())?;
+                        self.push_back(v);
+                    }
+                    debug_assert!(!slice.has_remaining());
+                } else {
+                    let mut v = <T as ProtoDefault>::proto_default();
+                    T::merge_value(&mut v, wire_type, buf, ctx)?;
+                    ctx.charge(size_of::<T>())?;
+                    self.push_back(v);
+                }
+                Ok(())
+            }
+            ProtoKind::String | ProtoKind::Bytes | ProtoKind::Message => {
+                let mut v = <T as ProtoDefault>::proto_default();
+                T::merge_value(&mut v, wire_type, buf, ctx)?;
+                ctx.charge(size_of::<T>())?;
+                self.push_back(v);
+                Ok(())
+            }
+            ProtoKind::Repeated(_) => unreachable!(),
+        }
+    }
+}
+
+impl<T> ProtoDefault for LinkedList<T> {
+    #[inline]
+    fn proto_default() -> Self {
+        LinkedList::new()
+    }
+}
+
+impl<T: ProtoDecode> ProtoDecode for LinkedList<T>
+where
+    T::ShadowDecoded: ProtoDecoder + ProtoExt,
+    LinkedList<T::ShadowDecoded>: ProtoDecoder + ProtoExt,
+{
+    type ShadowDecoded = LinkedList<T::ShadowDecoded>;
+}
+
+impl<T, U> ProtoShadowDecode<LinkedList<U>> for LinkedList<T>
+where
+    T: ProtoShadowDecode<U>,
+{
+    #[inline]
+    fn to_sun(self) -> Result<LinkedList<U>, DecodeError> {
+        self.into_iter().map(T::to_sun).collect()
+    }
+}
+
+impl<T> ProtoArchive for LinkedList<T>
+where
+    T: ProtoArchive + ProtoExt,
+{
+    #[inline]
+    fn is_default(&self) -> bool {
+        self.is_empty()
+    }
+
+    #[inline]
+    fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
+        match T::KIND {
+            ProtoKind::Primitive(_) | ProtoKind::SimpleEnum => {
+                let mark = w.mark();
+                for item in self.iter().rev() {
+                    item.archive::<0>(w);
+                }
+                if TAG != 0 {
+                    let payload_len = w.written_since(mark);
+                    w.put_varint(payload_len as u64);
+                    ArchivedProtoField::<TAG, Self>::put_key(w);
+                }
+            }
+            ProtoKind::String | ProtoKind::Bytes | ProtoKind::Message => {
+                for item in self.iter().rev() {
+                    ArchivedProtoField::<TAG, T>::new_always(item, w);
+                }
+            }
+            ProtoKind::Repeated(_) => unreachable!(),
+        }
+    }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        repeated_size_hint::<TAG, T>(self.len())
+    }
+}
+
+impl<T: ProtoEncode> ProtoEncode for LinkedList<T>
+where
+    for<'a> T::Shadow<'a>: ProtoArchive + ProtoExt,
+    for<'a> LinkedList<T::Shadow<'a>>: ProtoArchive + ProtoExt,
+{
+    type Shadow<'a> = LinkedList<T::Shadow<'a>>;
+}
+
+impl<'a, T, S> ProtoShadowEncode<'a, LinkedList<T>> for LinkedList<S>
+where
+    S: ProtoShadowEncode<'a, T>,
+    T: ProtoEncode,
+{
+    #[inline]
+    fn from_sun(value: &'a LinkedList<T>) -> Self {
+        value.iter().map(S::from_sun).collect()
+    }
+}
@@ -6,13 +6,18 @@ use crate::traits::PrimitiveKind;
 use crate::traits::buffer::RevWriter;
 
 mod arrays;
+mod binary_heap;
 mod btree;
 #[cfg(feature = "papaya")]
 mod conc_set;
 mod deque;
 mod hash_set;
+mod linked_list;
 mod vec;
 
+#[cfg(feature = "papaya")]
+pub use conc_set::PapayaSetSnapshot;
+
 impl<T: ProtoExt> ProtoExt for &[T] {
     const KIND: ProtoKind = match T::KIND {
         ProtoKind::Primitive(PrimitiveKind::U8) => ProtoKind::Bytes,
@@ -62,4 +67,33 @@ where
             ProtoKind::Repeated(_) => unreachable!(),
         }
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        if T::KIND.is_bytes_kind() {
+            return Some(ArchivedProtoField::<TAG, Self>::hint_delimited(self.len()));
+        }
+        repeated_size_hint::<TAG, T>(self.len())
+    }
+}
+
+/// [`ProtoArchive::size_hint`] of a non-bytes repeated field of `len` elements, from the per-element
+/// worst case so the elements are not visited: one packed run for scalars, one keyed entry per
+/// element otherwise. `None` when elements are unbounded (strings, bytes, collections).
+#[inline]
+pub(crate) const fn repeated_size_hint<const TAG: u32, T>(len: usize) -> Option<usize>
+where
+    T: ProtoArchive + ProtoExt,
+{
+    let Some(max) = T::MAX_PAYLOAD_LEN else {
+        return None;
+    };
+    match T::KIND {
+        ProtoKind::Primitive(_) | ProtoKind::SimpleEnum => match len.checked_mul(max) {
+            Some(payload) => Some(ArchivedProtoField::<TAG, T>::hint_delimited(payload)),
+            None => None,
+        },
+        ProtoKind::String | ProtoKind::Bytes | ProtoKind::Message => len.checked_mul(ArchivedProtoField::<TAG, T>::hint_delimited(max)),
+        ProtoKind::Repeated(_) => None,
+    }
 }
@@ -22,6 +22,7 @@ use crate::traits::ProtoKind;
 use crate::traits::ProtoShadowDecode;
 use crate::traits::ProtoShadowEncode;
 use crate::traits::buffer::RevWriter;
+use crate::wrappers::lists::repeated_size_hint;
 
 impl<T: ProtoExt> ProtoExt for Vec<T> {
     const KIND: ProtoKind = match T::KIND {
@@ -64,11 +65,13 @@ impl<T: ProtoFieldMerge + ProtoDefault> ProtoDecoder for Vec<T> {
                     while buf.remaining() > limit {
                         let mut v = <T as ProtoDefault>::proto_default();
                         T::merge_value(&mut v, T::WIRE_TYPE, buf, ctx)?;
+                        ctx.charge(size_of::<T>())?;
                         self.push(v);
                     }
                 } else {
                     let mut v = <T as ProtoDefault>::proto_default();
                     T::merge_value(&mut v, wire_type, buf, ctx)?;
+                    ctx.charge(size_of::<T>())?;
                     self.push(v);
                 }
                 Ok(())
@@ -76,6 +79,7 @@ impl<T: ProtoFieldMerge + ProtoDefault> ProtoDecoder for Vec<T> {
             ProtoKind::String | ProtoKind::Bytes | ProtoKind::Message => {
                 let mut v = <T as ProtoDefault>::proto_default();
                 T::merge_value(&mut v, wire_type, buf, ctx)?;
+                ctx.charge(size_of::<T>())?;
                 self.push(v);
                 Ok(())
             }
@@ -151,6 +155,14 @@ where
             ProtoKind::Repeated(_) => unreachable!(),
         }
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        if T::KIND.is_bytes_kind() {
+            return Some(ArchivedProtoField::<TAG, Self>::hint_delimited(self.len()));
+        }
+        repeated_size_hint::<TAG, T>(self.len())
+    }
 }
 
 impl<T: ProtoEncode> ProtoEncode for Vec<T>
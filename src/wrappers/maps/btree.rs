@@ -1,4 +1,5 @@
 use alloc::collections::BTreeMap;
+use alloc::collections::btree_map::Entry;
 use alloc::vec::Vec;
 
 use bytes::Buf;
@@ -6,20 +7,25 @@ use bytes::Buf;
 use crate::DecodeError;
 use crate::encoding::DecodeContext;
 use crate::encoding::WireType;
-use crate::encoding::decode_varint;
 use crate::encoding::skip_field;
 use crate::traits::ArchivedProtoField;
 use crate::traits::ProtoArchive;
+use crate::traits::ProtoCollectMerge;
 use crate::traits::ProtoDecode;
 use crate::traits::ProtoDecoder;
 use crate::traits::ProtoDefault;
 use crate::traits::ProtoEncode;
 use crate::traits::ProtoExt;
+use crate::traits::ProtoFieldMerge;
 use crate::traits::ProtoKind;
 use crate::traits::ProtoShadowDecode;
 use crate::traits::ProtoShadowEncode;
+use crate::traits::ProtoUniqueMerge;
 use crate::traits::buffer::RevWriter;
+use crate::wrappers::maps::CollectedMap;
 use crate::wrappers::maps::MapEntryDecoded;
+use crate::wrappers::maps::decode_map_entry;
+use crate::wrappers::maps::map_size_hint;
 
 impl<'a, K, V> ProtoShadowEncode<'a, BTreeMap<K, V>> for &'a BTreeMap<K, V>
 where
@@ -60,6 +66,11 @@ where
             }
         }
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        map_size_hint::<TAG, K, V>(self.len())
+    }
 }
 
 impl<K, V> ProtoExt for BTreeMap<K, V> {
@@ -78,7 +89,7 @@ where
     #[inline]
     fn merge_field(value: &mut Self, tag: u32, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
         if tag == 1 {
-            Self::merge(value, wire_type, buf, ctx)
+            <Self as ProtoDecoder>::merge(value, wire_type, buf, ctx)
         } else {
             skip_field(wire_type, tag, buf, ctx)
         }
@@ -86,24 +97,8 @@ where
 
     #[inline]
     fn merge(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
-        if wire_type != WireType::LengthDelimited {
-            return Err(DecodeError::new("map entry must be length-delimited"));
-        }
-        let len = decode_varint(buf)? as usize;
-        let remaining = buf.remaining();
-        if len > remaining {
-            return Err(DecodeError::new("buffer underflow"));
-        }
         // Each merge call handles exactly one map entry
-        let mut entry = <MapEntryDecoded<K::ShadowDecoded, V::ShadowDecoded> as ProtoDefault>::proto_default();
-        if len > 0 {
-            // Use limit-based decoding to avoid Take wrapper overhead
-            let limit = remaining - len;
-            while buf.remaining() > limit {
-                MapEntryDecoded::<K::ShadowDecoded, V::ShadowDecoded>::decode_one_field(&mut entry, buf, ctx)?;
-            }
-        }
-        let (key, value) = entry.to_sun()?;
+        let (key, value) = decode_map_entry::<K, V>(wire_type, buf, ctx)?;
         self.insert(key, value);
         Ok(())
     }
@@ -151,3 +146,75 @@ where
 {
     type Shadow<'a> = &'a BTreeMap<K, V>;
 }
+
+impl<K, V> ProtoUniqueMerge for BTreeMap<K, V>
+where
+    Self: ProtoFieldMerge,
+    K: ProtoDecode + Ord,
+    V: ProtoDecode,
+    MapEntryDecoded<K::ShadowDecoded, V::ShadowDecoded>: ProtoDecoder + ProtoExt,
+{
+    #[inline]
+    fn merge_unique(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        let (key, value) = decode_map_entry::<K, V>(wire_type, buf, ctx)?;
+        match self.entry(key) {
+            Entry::Vacant(slot) => {
+                slot.insert(value);
+                Ok(())
+            }
+            Entry::Occupied(_) => Err(DecodeError::new("duplicate map key")),
+        }
+    }
+}
+
+impl<K, V> ProtoCollectMerge for BTreeMap<K, Vec<V>>
+where
+    K: ProtoDecode + Ord,
+    V: ProtoDecode,
+    MapEntryDecoded<K::ShadowDecoded, V::ShadowDecoded>: ProtoDecoder + ProtoExt,
+{
+    #[inline]
+    fn merge_collect(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        let (key, value) = decode_map_entry::<K, V>(wire_type, buf, ctx)?;
+        self.entry(key).or_default().push(value);
+        Ok(())
+    }
+}
+
+impl<'a, K, V> ProtoShadowEncode<'a, BTreeMap<K, Vec<V>>> for CollectedMap<'a, BTreeMap<K, Vec<V>>> {
+    #[inline]
+    fn from_sun(value: &'a BTreeMap<K, Vec<V>>) -> Self {
+        CollectedMap(value)
+    }
+}
+
+impl<K, V> ProtoArchive for CollectedMap<'_, BTreeMap<K, Vec<V>>>
+where
+    K: ProtoEncode + Ord,
+    V: ProtoEncode + ProtoExt,
+    for<'b> <K as ProtoEncode>::Shadow<'b>: ProtoArchive + ProtoExt,
+    for<'b> <V as ProtoEncode>::Shadow<'b>: ProtoArchive + ProtoExt,
+{
+    #[inline]
+    fn is_default(&self) -> bool {
+        self.0.values().all(Vec::is_empty)
+    }
+
+    #[inline]
+    fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
+        for (key_value, values) in self.0.iter().rev() {
+            for value_value in values.iter().rev() {
+                let key = <K as ProtoEncode>::Shadow::from_sun(key_value);
+                let value = <V as ProtoEncode>::Shadow::from_sun(value_value);
+                let mark = w.mark();
+                ArchivedProtoField::<2, <V as ProtoEncode>::Shadow<'_>>::archive(&value, w);
+                ArchivedProtoField::<1, <K as ProtoEncode>::Shadow<'_>>::archive(&key, w);
+                if TAG != 0 {
+                    let payload_len = w.written_since(mark);
+                    w.put_varint(payload_len as u64);
+                    ArchivedProtoField::<TAG, Self>::put_key(w);
+                }
+            }
+        }
+    }
+}
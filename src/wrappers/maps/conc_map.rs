@@ -1,14 +1,15 @@
 use alloc::vec::Vec;
 use core::hash::BuildHasher;
 use core::hash::Hash;
+use core::ptr;
 
 use bytes::Buf;
 use papaya::HashMap;
+use papaya::LocalGuard;
 
 use crate::DecodeError;
 use crate::encoding::DecodeContext;
 use crate::encoding::WireType;
-use crate::encoding::decode_varint;
 use crate::encoding::skip_field;
 use crate::traits::ArchivedProtoField;
 use crate::traits::ProtoArchive;
@@ -21,37 +22,59 @@ use crate::traits::ProtoKind;
 use crate::traits::ProtoShadowDecode;
 use crate::traits::ProtoShadowEncode;
 use crate::traits::buffer::RevWriter;
+use crate::wrappers::RunKey;
 use crate::wrappers::maps::MapEntryDecoded;
+use crate::wrappers::maps::decode_map_entry;
+use crate::wrappers::maps::map_size_hint;
 
-impl<'a, K, V, S> ProtoShadowEncode<'a, HashMap<K, V, S>> for &'a HashMap<K, V, S>
+/// Encode shadow of a `papaya::HashMap`: its entries, read under a single guard.
+///
+/// The size hint and the archive of one encode walk the same entries, even while other threads
+/// insert into or remove from the map.
+pub struct PapayaMapSnapshot<'a, K, V> {
+    entries: Vec<(&'a K, &'a V)>,
+    // Keeps the entries alive; dropped after them
+    _guard: LocalGuard<'a>,
+}
+
+impl<K, V> ProtoExt for PapayaMapSnapshot<'_, K, V> {
+    const KIND: ProtoKind = ProtoKind::Repeated(&crate::wrappers::maps::MAP_ENTRY_KIND);
+}
+
+impl<'a, K, V, S> ProtoShadowEncode<'a, HashMap<K, V, S>> for PapayaMapSnapshot<'a, K, V>
 where
     K: ProtoEncode + Eq + Hash,
     V: ProtoEncode,
+    S: BuildHasher,
 {
     #[inline]
     fn from_sun(value: &'a HashMap<K, V, S>) -> Self {
-        value
+        let guard = value.guard();
+        let entries = value
+            .iter(&guard)
+            // SAFETY: papaya reclaims an entry only once every guard that could have observed it is
+            // dropped, and `guard` lives in the snapshot for as long as the references do.
+            .map(|(key, value)| unsafe { (&*ptr::from_ref(key), &*ptr::from_ref(value)) })
+            .collect();
+        Self { entries, _guard: guard }
     }
 }
 
-impl<K, V, S> ProtoArchive for &HashMap<K, V, S>
+impl<K, V> ProtoArchive for PapayaMapSnapshot<'_, K, V>
 where
-    K: ProtoEncode + Eq + Hash,
+    K: ProtoEncode,
     V: ProtoEncode + ProtoExt,
-    S: BuildHasher,
     for<'b> <K as ProtoEncode>::Shadow<'b>: ProtoArchive + ProtoExt,
     for<'b> <V as ProtoEncode>::Shadow<'b>: ProtoArchive + ProtoExt,
 {
     #[inline]
     fn is_default(&self) -> bool {
-        self.is_empty()
+        self.entries.is_empty()
     }
 
     #[inline]
     fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
-        let guard = self.pin();
-        let entries: Vec<(&K, &V)> = guard.iter().collect();
-        for (key_value, value_value) in entries.into_iter().rev() {
+        for &(key_value, value_value) in self.entries.iter().rev() {
             let key = <K as ProtoEncode>::Shadow::from_sun(key_value);
             let value = <V as ProtoEncode>::Shadow::from_sun(value_value);
             let mark = w.mark();
@@ -64,6 +87,11 @@ where
             }
         }
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        map_size_hint::<TAG, K, V>(self.entries.len())
+    }
 }
 
 impl<K, V, S> ProtoExt for HashMap<K, V, S> {
@@ -91,27 +119,23 @@ where
 
     #[inline]
     fn merge(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
-        if wire_type != WireType::LengthDelimited {
-            return Err(DecodeError::new("map entry must be length-delimited"));
-        }
-        let len = decode_varint(buf)? as usize;
-        let remaining = buf.remaining();
-        if len > remaining {
-            return Err(DecodeError::new("buffer underflow"));
-        }
+        let (key, value) = decode_map_entry::<K, V>(wire_type, buf, ctx)?;
+        self.pin().insert(key, value);
+        Ok(())
+    }
+
+    /// Inserts the whole run of entries under one guard.
+    #[inline]
+    fn merge_run<const TAG: u32>(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        let run = RunKey::new(TAG, wire_type);
         let guard = self.pin();
-        // Each merge call handles exactly one map entry
-        let mut entry = <MapEntryDecoded<K::ShadowDecoded, V::ShadowDecoded> as ProtoDefault>::proto_default();
-        if len > 0 {
-            // Use limit-based decoding to avoid Take wrapper overhead
-            let limit = remaining - len;
-            while buf.remaining() > limit {
-                MapEntryDecoded::<K::ShadowDecoded, V::ShadowDecoded>::decode_one_field(&mut entry, buf, ctx)?;
+        loop {
+            let (key, value) = decode_map_entry::<K, V>(wire_type, buf, ctx)?;
+            guard.insert(key, value);
+            if !run.take(buf) {
+                return Ok(());
             }
         }
-        let (key, value) = entry.to_sun()?;
-        guard.insert(key, value);
-        Ok(())
     }
 }
 
@@ -163,6 +187,8 @@ where
     for<'b> K: 'b + ProtoEncode + Eq + Hash,
     for<'b> V: 'b + ProtoEncode + ProtoExt,
     for<'b> S: 'b + BuildHasher,
+    for<'b> <K as ProtoEncode>::Shadow<'b>: ProtoArchive + ProtoExt,
+    for<'b> <V as ProtoEncode>::Shadow<'b>: ProtoArchive + ProtoExt,
 {
-    type Shadow<'a> = &'a HashMap<K, V, S>;
+    type Shadow<'a> = PapayaMapSnapshot<'a, K, V>;
 }
@@ -1,26 +1,33 @@
 use alloc::vec::Vec;
+use core::hash::BuildHasher;
 use core::hash::Hash;
 use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 
 use bytes::Buf;
 
 use crate::DecodeError;
 use crate::encoding::DecodeContext;
 use crate::encoding::WireType;
-use crate::encoding::decode_varint;
 use crate::encoding::skip_field;
 use crate::traits::ArchivedProtoField;
 use crate::traits::ProtoArchive;
+use crate::traits::ProtoCollectMerge;
 use crate::traits::ProtoDecode;
 use crate::traits::ProtoDecoder;
 use crate::traits::ProtoDefault;
 use crate::traits::ProtoEncode;
 use crate::traits::ProtoExt;
+use crate::traits::ProtoFieldMerge;
 use crate::traits::ProtoKind;
 use crate::traits::ProtoShadowDecode;
 use crate::traits::ProtoShadowEncode;
+use crate::traits::ProtoUniqueMerge;
 use crate::traits::buffer::RevWriter;
+use crate::wrappers::maps::CollectedMap;
 use crate::wrappers::maps::MapEntryDecoded;
+use crate::wrappers::maps::decode_map_entry;
+use crate::wrappers::maps::map_size_hint;
 
 impl<'a, K, V, S> ProtoShadowEncode<'a, HashMap<K, V, S>> for &'a HashMap<K, V, S>
 where
@@ -61,6 +68,11 @@ where
             }
         }
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        map_size_hint::<TAG, K, V>(self.len())
+    }
 }
 
 impl<K, V, S> ProtoExt for HashMap<K, V, S> {
@@ -87,24 +99,8 @@ where
 
     #[inline]
     fn merge(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
-        if wire_type != WireType::LengthDelimited {
-            return Err(DecodeError::new("map entry must be length-delimited"));
-        }
-        let len = decode_varint(buf)? as usize;
-        let remaining = buf.remaining();
-        if len > remaining {
-            return Err(DecodeError::new("buffer underflow"));
-        }
         // Each merge call handles exactly one map entry
-        let mut entry = <MapEntryDecoded<K::ShadowDecoded, V::ShadowDecoded> as ProtoDefault>::proto_default();
-        if len > 0 {
-            // Use limit-based decoding to avoid Take wrapper overhead
-            let limit = remaining - len;
-            while buf.remaining() > limit {
-                MapEntryDecoded::<K::ShadowDecoded, V::ShadowDecoded>::decode_one_field(&mut entry, buf, ctx)?;
-            }
-        }
-        let (key, value) = entry.to_sun()?;
+        let (key, value) = decode_map_entry::<K, V>(wire_type, buf, ctx)?;
         self.insert(key, value);
         Ok(())
     }
@@ -160,3 +156,76 @@ where
     // for<'b> <K as ProtoEncode>::Shadow<'a>: ProtoArchive + ProtoExt,
     // for<'b> <V as ProtoEncode>::Shadow<'a>: ProtoArchive + ProtoExt;
 }
+
+impl<K, V, S> ProtoUniqueMerge for HashMap<K, V, S>
+where
+    Self: ProtoFieldMerge,
+    K: ProtoDecode + Eq + Hash,
+    V: ProtoDecode,
+    S: BuildHasher,
+    MapEntryDecoded<K::ShadowDecoded, V::ShadowDecoded>: ProtoDecoder + ProtoExt,
+{
+    #[inline]
+    fn merge_unique(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        let (key, value) = decode_map_entry::<K, V>(wire_type, buf, ctx)?;
+        match self.entry(key) {
+            Entry::Vacant(slot) => {
+                slot.insert(value);
+                Ok(())
+            }
+            Entry::Occupied(_) => Err(DecodeError::new("duplicate map key")),
+        }
+    }
+}
+
+impl<K, V, S> ProtoCollectMerge for HashMap<K, Vec<V>, S>
+where
+    K: ProtoDecode + Eq + Hash,
+    V: ProtoDecode,
+    S: BuildHasher,
+    MapEntryDecoded<K::ShadowDecoded, V::ShadowDecoded>: ProtoDecoder + ProtoExt,
+{
+    #[inline]
+    fn merge_collect(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        let (key, value) = decode_map_entry::<K, V>(wire_type, buf, ctx)?;
+        self.entry(key).or_default().push(value);
+        Ok(())
+    }
+}
+
+impl<'a, K, V, S> ProtoShadowEncode<'a, HashMap<K, Vec<V>, S>> for CollectedMap<'a, HashMap<K, Vec<V>, S>> {
+    #[inline]
+    fn from_sun(value: &'a HashMap<K, Vec<V>, S>) -> Self {
+        CollectedMap(value)
+    }
+}
+
+impl<K, V, S> ProtoArchive for CollectedMap<'_, HashMap<K, Vec<V>, S>>
+where
+    K: ProtoEncode + Eq + Hash,
+    V: ProtoEncode + ProtoExt,
+    for<'b> <K as ProtoEncode>::Shadow<'b>: ProtoArchive + ProtoExt,
+    for<'b> <V as ProtoEncode>::Shadow<'b>: ProtoArchive + ProtoExt,
+{
+    #[inline]
+    fn is_default(&self) -> bool {
+        self.0.values().all(Vec::is_empty)
+    }
+
+    #[inline]
+    fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
+        let entries: Vec<(&K, &V)> = self.0.iter().flat_map(|(key, values)| values.iter().map(move |value| (key, value))).collect();
+        for (key_value, value_value) in entries.into_iter().rev() {
+            let key = <K as ProtoEncode>::Shadow::from_sun(key_value);
+            let value = <V as ProtoEncode>::Shadow::from_sun(value_value);
+            let mark = w.mark();
+            ArchivedProtoField::<2, <V as ProtoEncode>::Shadow<'_>>::archive(&value, w);
+            ArchivedProtoField::<1, <K as ProtoEncode>::Shadow<'_>>::archive(&key, w);
+            if TAG != 0 {
+                let payload_len = w.written_since(mark);
+                w.put_varint(payload_len as u64);
+                ArchivedProtoField::<TAG, Self>::put_key(w);
+            }
+        }
+    }
+}
@@ -1,24 +1,85 @@
 use bytes::Buf;
 
 use crate::DecodeError;
+use crate::ProtoArchive;
 use crate::ProtoDecode;
 use crate::ProtoDecoder;
 use crate::ProtoDefault;
-use crate::ProtoFieldMerge;
+use crate::ProtoEncode;
 use crate::ProtoExt;
+use crate::ProtoFieldMerge;
 use crate::ProtoKind;
 use crate::encoding::DecodeContext;
 use crate::encoding::WireType;
+use crate::encoding::decode_varint;
 use crate::encoding::skip_field;
+use crate::traits::ArchivedProtoField;
 use crate::traits::ProtoShadowDecode;
+use crate::traits::add_field_max_len;
 
 mod btree;
 #[cfg(feature = "papaya")]
 mod conc_map;
 mod hash_map;
 
+#[cfg(feature = "papaya")]
+pub use conc_map::PapayaMapSnapshot;
+
 pub(crate) const MAP_ENTRY_KIND: ProtoKind = ProtoKind::Message;
 
+/// Encode shadow for `#[proto(on_duplicate = "collect")]` maps.
+///
+/// Every value of a `Vec<V>` slot is written as its own `map<K, V>` entry under the same key,
+/// so the decoder can gather them back without changing the wire schema.
+pub struct CollectedMap<'a, M>(pub(crate) &'a M);
+
+impl<M> ProtoExt for CollectedMap<'_, M> {
+    const KIND: ProtoKind = ProtoKind::Repeated(&MAP_ENTRY_KIND);
+}
+
+/// [`ProtoArchive::size_hint`] of `len` `map<K, V>` entries written under `TAG`, from the worst
+/// case of one entry so the entries are not visited. `None` when keys or values are unbounded.
+#[inline]
+pub(crate) fn map_size_hint<'a, const TAG: u32, K, V>(len: usize) -> Option<usize>
+where
+    K: ProtoEncode + 'a,
+    V: ProtoEncode + 'a,
+    <K as ProtoEncode>::Shadow<'a>: ProtoArchive + ProtoExt,
+    <V as ProtoEncode>::Shadow<'a>: ProtoArchive + ProtoExt,
+{
+    let payload = add_field_max_len::<<V as ProtoEncode>::Shadow<'a>>(Some(0), 2);
+    let payload = add_field_max_len::<<K as ProtoEncode>::Shadow<'a>>(payload, 1)?;
+    len.checked_mul(ArchivedProtoField::<TAG, <K as ProtoEncode>::Shadow<'a>>::hint_delimited(payload))
+}
+
+/// Decode one length-delimited map entry into its owned key and value.
+#[inline]
+pub(crate) fn decode_map_entry<K, V>(wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(K, V), DecodeError>
+where
+    K: ProtoDecode,
+    V: ProtoDecode,
+    MapEntryDecoded<K::ShadowDecoded, V::ShadowDecoded>: ProtoDecoder + ProtoExt,
+{
+    if wire_type != WireType::LengthDelimited {
+        return Err(DecodeError::new("map entry must be length-delimited"));
+    }
+    let len = decode_varint(buf)? as usize;
+    let remaining = buf.remaining();
+    if len > remaining {
+        return Err(DecodeError::new("buffer underflow"));
+    }
+    ctx.charge(size_of::<(K, V)>())?;
+    let mut entry = <MapEntryDecoded<K::ShadowDecoded, V::ShadowDecoded> as ProtoDefault>::proto_default();
+    if len > 0 {
+        // Use limit-based decoding to avoid Take wrapper overhead
+        let limit = remaining - len;
+        while buf.remaining() > limit {
+            MapEntryDecoded::<K::ShadowDecoded, V::ShadowDecoded>::decode_one_field(&mut entry, buf, ctx)?;
+        }
+    }
+    entry.to_sun()
+}
+
 pub struct MapEntryDecoded<K, V> {
     key: K,
     value: V,
@@ -1,12 +1,61 @@
+use bytes::Buf;
+
+use crate::encoding::WireType;
+use crate::encoding::encode_key;
+
 mod arcs;
 mod boxes;
 mod lists;
 mod maps;
 mod mutexes;
 mod options;
+mod views;
+mod weak;
+
+#[cfg(feature = "allocator")]
+pub use allocators::ProtoAllocator;
+#[cfg(feature = "papaya")]
+pub use lists::PapayaSetSnapshot;
+pub use maps::CollectedMap;
+#[cfg(feature = "papaya")]
+pub use maps::PapayaMapSnapshot;
+pub use views::MapView;
+pub use views::ProtoView;
+pub use views::SetView;
+
+#[cfg(feature = "allocator")]
+mod allocators;
 
 #[cfg(feature = "cache_padded")]
 mod cache_padded;
 
 #[cfg(feature = "arc_swap")]
 mod arc_swap;
+
+/// Key of the occurrences of one field, for reading a run of them in a single merge call.
+pub(crate) struct RunKey {
+    bytes: [u8; 5],
+    len: usize,
+}
+
+impl RunKey {
+    #[inline]
+    pub(crate) fn new(tag: u32, wire_type: WireType) -> Self {
+        let mut bytes = [0u8; 5];
+        let mut out = &mut bytes[..];
+        encode_key(tag, wire_type, &mut out);
+        let len = 5 - out.len();
+        Self { bytes, len }
+    }
+
+    /// Consumes the next field key when it is this one.
+    #[inline]
+    pub(crate) fn take(&self, buf: &mut impl Buf) -> bool {
+        let key = &self.bytes[..self.len];
+        let matches = buf.chunk().starts_with(key);
+        if matches {
+            buf.advance(key.len());
+        }
+        matches
+    }
+}
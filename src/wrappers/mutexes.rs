@@ -118,6 +118,11 @@ impl<T: ProtoExt> ProtoArchive for MutexShadow<T> {
             ArchivedProtoField::<TAG, Self>::put_key(w);
         }
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        Some(ArchivedProtoField::<TAG, Self>::hint_delimited(self.bytes.len()))
+    }
 }
 
 #[cfg(feature = "parking_lot")]
@@ -18,6 +18,7 @@ use crate::traits::buffer::RevWriter;
 
 impl<T: ProtoExt> ProtoExt for Option<T> {
     const KIND: ProtoKind = T::KIND;
+    const MAX_PAYLOAD_LEN: Option<usize> = T::MAX_PAYLOAD_LEN;
 }
 
 impl<T: ProtoFieldMerge + ProtoDefault> ProtoDecoder for Option<T> {
@@ -80,6 +81,11 @@ where
             inner.archive::<TAG>(w);
         }
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        self.as_ref().map_or(Some(0), T::size_hint::<TAG>)
+    }
 }
 
 impl<T: ProtoEncode> ProtoEncode for Option<T>
@@ -114,4 +120,9 @@ where
             inner.archive::<TAG>(w);
         }
     }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        self.as_ref().map_or(Some(0), T::size_hint::<TAG>)
+    }
 }
@@ -0,0 +1,207 @@
+//! Borrowed stand-ins for map and set fields, used by `sun_ir` encoding.
+//!
+//! A `#[proto(view)]` field of a message with a `sun_ir` type is read from the IR as a
+//! [`MapView`] or [`SetView`] instead of the declared collection, so data held in slices, columns
+//! or arenas is encoded without first building a `BTreeMap` or `HashSet` per message.
+
+use alloc::collections::BTreeMap;
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use core::hash::BuildHasher;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::traits::ArchivedProtoField;
+use crate::traits::PrimitiveKind;
+use crate::traits::ProtoArchive;
+use crate::traits::ProtoEncode;
+use crate::traits::ProtoExt;
+use crate::traits::ProtoKind;
+use crate::traits::ProtoShadowEncode;
+use crate::traits::buffer::RevWriter;
+use crate::wrappers::lists::repeated_size_hint;
+use crate::wrappers::maps::map_size_hint;
+
+/// An encode-only value with the wire shape of a field of type `T`.
+///
+/// Generated `sun_ir` encoders accept a view wherever a `#[proto(view)]` field of type `T` is read.
+pub trait ProtoView<T: ?Sized>: ProtoArchive + ProtoExt {
+    #[doc(hidden)]
+    #[inline]
+    fn as_view(&self) -> &Self {
+        self
+    }
+}
+
+/// Borrowed entries of a `map<K, V>` field, encoded in the order they were given.
+///
+/// ```rust,ignore
+/// let view = MapView::new(&pairs);
+/// let view: MapView<'_, String, u32> = names.iter().zip(&counts).collect();
+/// ```
+pub struct MapView<'a, K, V> {
+    entries: Vec<(&'a K, &'a V)>,
+}
+
+impl<'a, K, V> MapView<'a, K, V> {
+    /// View of a slice of key-value pairs.
+    pub fn new(entries: &'a [(K, V)]) -> Self {
+        entries.iter().map(|(key, value)| (key, value)).collect()
+    }
+
+    pub const fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<'a, K, V> From<&'a [(K, V)]> for MapView<'a, K, V> {
+    #[inline]
+    fn from(entries: &'a [(K, V)]) -> Self {
+        Self::new(entries)
+    }
+}
+
+impl<'a, K, V> FromIterator<(&'a K, &'a V)> for MapView<'a, K, V> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (&'a K, &'a V)>>(iter: I) -> Self {
+        Self {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<K, V> ProtoExt for MapView<'_, K, V> {
+    const KIND: ProtoKind = ProtoKind::Repeated(&crate::wrappers::maps::MAP_ENTRY_KIND);
+}
+
+impl<K, V> ProtoArchive for MapView<'_, K, V>
+where
+    K: ProtoEncode,
+    V: ProtoEncode + ProtoExt,
+    for<'b> <K as ProtoEncode>::Shadow<'b>: ProtoArchive + ProtoExt,
+    for<'b> <V as ProtoEncode>::Shadow<'b>: ProtoArchive + ProtoExt,
+{
+    #[inline]
+    fn is_default(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline]
+    fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
+        for &(key_value, value_value) in self.entries.iter().rev() {
+            let key = <K as ProtoEncode>::Shadow::from_sun(key_value);
+            let value = <V as ProtoEncode>::Shadow::from_sun(value_value);
+            let mark = w.mark();
+            ArchivedProtoField::<2, <V as ProtoEncode>::Shadow<'_>>::archive(&value, w);
+            ArchivedProtoField::<1, <K as ProtoEncode>::Shadow<'_>>::archive(&key, w);
+            if TAG != 0 {
+                let payload_len = w.written_since(mark);
+                w.put_varint(payload_len as u64);
+                ArchivedProtoField::<TAG, Self>::put_key(w);
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        map_size_hint::<TAG, K, V>(self.entries.len())
+    }
+}
+
+impl<K, V> ProtoView<BTreeMap<K, V>> for MapView<'_, K, V> where Self: ProtoArchive {}
+
+impl<K, V, S: BuildHasher> ProtoView<HashMap<K, V, S>> for MapView<'_, K, V> where Self: ProtoArchive {}
+
+/// Borrowed elements of a set field, encoded in the order they were given.
+///
+/// ```rust,ignore
+/// let view = SetView::new(&tags);
+/// let view: SetView<'_, u64> = rows.iter().map(|row| &row.id).collect();
+/// ```
+pub struct SetView<'a, T> {
+    items: Vec<&'a T>,
+}
+
+impl<'a, T> SetView<'a, T> {
+    /// View of a slice of elements.
+    pub fn new(items: &'a [T]) -> Self {
+        items.iter().collect()
+    }
+
+    pub const fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl<'a, T> From<&'a [T]> for SetView<'a, T> {
+    #[inline]
+    fn from(items: &'a [T]) -> Self {
+        Self::new(items)
+    }
+}
+
+impl<'a, T> FromIterator<&'a T> for SetView<'a, T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = &'a T>>(iter: I) -> Self {
+        Self {
+            items: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T: ProtoExt> ProtoExt for SetView<'_, T> {
+    const KIND: ProtoKind = match T::KIND {
+        ProtoKind::Primitive(PrimitiveKind::U8) => ProtoKind::Bytes,
+        _ => ProtoKind::Repeated(&T::KIND),
+    };
+}
+
+impl<T> ProtoArchive for SetView<'_, T>
+where
+    T: ProtoArchive + ProtoExt,
+{
+    #[inline]
+    fn is_default(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    #[inline]
+    fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
+        match T::KIND {
+            ProtoKind::Primitive(_) | ProtoKind::SimpleEnum => {
+                let mark = w.mark();
+                for item in self.items.iter().rev() {
+                    item.archive::<0>(w);
+                }
+                if TAG != 0 {
+                    let payload_len = w.written_since(mark);
+                    w.put_varint(payload_len as u64);
+                    ArchivedProtoField::<TAG, Self>::put_key(w);
+                }
+            }
+            ProtoKind::String | ProtoKind::Bytes | ProtoKind::Message => {
+                for item in self.items.iter().rev() {
+                    ArchivedProtoField::<TAG, T>::new_always(*item, w);
+                }
+            }
+            ProtoKind::Repeated(_) => unreachable!(),
+        }
+    }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        repeated_size_hint::<TAG, T>(self.items.len())
+    }
+}
+
+impl<T: ProtoArchive + ProtoExt> ProtoView<BTreeSet<T>> for SetView<'_, T> {}
+
+impl<T: ProtoArchive + ProtoExt, S: BuildHasher> ProtoView<HashSet<T, S>> for SetView<'_, T> {}
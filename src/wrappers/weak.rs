@@ -0,0 +1,107 @@
+use alloc::sync::Arc;
+use alloc::sync::Weak;
+
+use bytes::Buf;
+
+use crate::DecodeError;
+use crate::encoding::DecodeContext;
+use crate::encoding::WireType;
+use crate::encoding::skip_field;
+use crate::traits::ProtoArchive;
+use crate::traits::ProtoDecode;
+use crate::traits::ProtoDecoder;
+use crate::traits::ProtoDefault;
+use crate::traits::ProtoEncode;
+use crate::traits::ProtoExt;
+use crate::traits::ProtoKind;
+use crate::traits::ProtoShadowDecode;
+use crate::traits::ProtoShadowEncode;
+use crate::traits::buffer::RevWriter;
+
+// A `Weak<T>` field is a back reference into a graph owned elsewhere:
+// - encoding upgrades the pointer and writes the value it points to, or the default of `T` when
+//   the value is gone;
+// - decoding skips the payload and leaves `Weak::new()`, since there is no owner to point into.
+//   Whoever owns the graph relinks the field after decoding.
+
+/// Encode shadow of a `Weak<T>` field: the upgraded pointer, held for as long as the message is
+/// being encoded.
+pub struct WeakShadow<T> {
+    strong: Option<Arc<T>>,
+}
+
+impl<T: ProtoExt> ProtoExt for Weak<T> {
+    const KIND: ProtoKind = T::KIND;
+}
+
+impl<T: ProtoExt> ProtoDecoder for Weak<T> {
+    #[inline]
+    fn merge_field(_value: &mut Self, tag: u32, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        skip_field(wire_type, tag, buf, ctx)
+    }
+
+    #[inline]
+    fn merge(&mut self, wire_type: WireType, buf: &mut impl Buf, ctx: DecodeContext) -> Result<(), DecodeError> {
+        skip_field(wire_type, 0, buf, ctx)
+    }
+}
+
+impl<T> ProtoDefault for Weak<T> {
+    #[inline]
+    fn proto_default() -> Self {
+        Weak::new()
+    }
+}
+
+impl<T: ProtoExt> ProtoDecode for Weak<T> {
+    type ShadowDecoded = Self;
+}
+
+impl<T> ProtoShadowDecode<Weak<T>> for Weak<T> {
+    #[inline]
+    fn to_sun(self) -> Result<Weak<T>, DecodeError> {
+        Ok(self)
+    }
+}
+
+impl<T: ProtoExt> ProtoExt for WeakShadow<T> {
+    const KIND: ProtoKind = T::KIND;
+}
+
+impl<T> ProtoArchive for WeakShadow<T>
+where
+    T: ProtoEncode,
+    for<'a> T::Shadow<'a>: ProtoArchive + ProtoShadowEncode<'a, T>,
+{
+    #[inline]
+    fn is_default(&self) -> bool {
+        self.strong.as_ref().is_none_or(|strong| T::Shadow::from_sun(strong.as_ref()).is_default())
+    }
+
+    #[inline]
+    fn archive<const TAG: u32>(&self, w: &mut impl RevWriter) {
+        if let Some(strong) = self.strong.as_ref() {
+            T::Shadow::from_sun(strong.as_ref()).archive::<TAG>(w);
+        }
+    }
+
+    #[inline]
+    fn size_hint<const TAG: u32>(&self) -> Option<usize> {
+        self.strong.as_ref().map_or(Some(0), |strong| T::Shadow::from_sun(strong.as_ref()).size_hint::<TAG>())
+    }
+}
+
+impl<T> ProtoEncode for Weak<T>
+where
+    T: ProtoEncode + ProtoExt,
+    for<'a> T::Shadow<'a>: ProtoArchive + ProtoExt + ProtoShadowEncode<'a, T>,
+{
+    type Shadow<'a> = WeakShadow<T>;
+}
+
+impl<'a, T> ProtoShadowEncode<'a, Weak<T>> for WeakShadow<T> {
+    #[inline]
+    fn from_sun(value: &'a Weak<T>) -> Self {
+        Self { strong: value.upgrade() }
+    }
+}
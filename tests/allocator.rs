@@ -0,0 +1,117 @@
+#![cfg(feature = "allocator")]
+#![feature(allocator_api)]
+
+use std::alloc::AllocError;
+use std::alloc::Allocator;
+use std::alloc::Global;
+use std::alloc::Layout;
+use std::ptr::NonNull;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoAllocator;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+static ARENA_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Global allocator with a byte counter, standing in for an arena handle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Arena;
+
+unsafe impl Allocator for Arena {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        ARENA_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { Global.deallocate(ptr, layout) }
+    }
+}
+
+impl ProtoAllocator for Arena {}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Cell {
+    pub column: u32,
+    pub value: String,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub key: Vec<u8, Arena>,
+    pub offsets: Vec<u64, Arena>,
+    pub cells: Vec<Cell, Arena>,
+    pub header: Box<Cell, Arena>,
+}
+
+/// The same message with global-allocator collections.
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PlainRow {
+    pub key: Vec<u8>,
+    pub offsets: Vec<u64>,
+    pub cells: Vec<Cell>,
+    pub header: Box<Cell>,
+}
+
+fn collect<T>(items: impl IntoIterator<Item = T>) -> Vec<T, Arena> {
+    let mut out = Vec::new_in(Arena);
+    out.extend(items);
+    out
+}
+
+fn row() -> Row {
+    Row {
+        key: collect(*b"user:42"),
+        offsets: collect([0, 17, 1 << 40]),
+        cells: collect([
+            Cell {
+                column: 1,
+                value: "ada".into(),
+            },
+            Cell {
+                column: 4,
+                value: String::new(),
+            },
+        ]),
+        header: Box::new_in(
+            Cell {
+                column: 9,
+                value: "v2".into(),
+            },
+            Arena,
+        ),
+    }
+}
+
+#[test]
+fn allocator_fields_encode_like_global_ones() {
+    let row = row();
+    let plain = PlainRow {
+        key: row.key.to_vec(),
+        offsets: row.offsets.to_vec(),
+        cells: row.cells.to_vec(),
+        header: Box::new((*row.header).clone()),
+    };
+    assert_eq!(row.encode_to_vec(), plain.encode_to_vec());
+}
+
+#[test]
+fn decode_allocates_in_the_field_allocator() {
+    let bytes = row().encode_to_vec();
+
+    let before = ARENA_BYTES.load(Ordering::Relaxed);
+    let decoded = <Row as ProtoDecode>::decode(bytes.as_slice(), DecodeContext::default()).expect("row decodes");
+    assert!(ARENA_BYTES.load(Ordering::Relaxed) > before);
+    assert_eq!(decoded, row());
+
+    let plain = <PlainRow as ProtoDecode>::decode(bytes.as_slice(), DecodeContext::default()).expect("plain row decodes");
+    assert_eq!(plain.key, b"user:42");
+    assert_eq!(plain.offsets, [0, 17, 1 << 40]);
+}
@@ -3,12 +3,17 @@
 use std::sync::Arc;
 
 use arc_swap::ArcSwap;
+use arc_swap::ArcSwapAny;
 use arc_swap::ArcSwapOption;
+use arc_swap::RefCnt;
 use proto_rs::ProtoDecode;
 use proto_rs::ProtoEncode;
 use proto_rs::encoding::DecodeContext;
 use proto_rs::proto_message;
 
+#[cfg(feature = "build-schemas")]
+mod common;
+
 #[proto_message(proto_path = "protos/tests/arc_swap.proto")]
 #[derive(Clone, Debug, PartialEq, Eq, Default)]
 pub struct SwapInner {
@@ -87,6 +92,51 @@ impl Default for ArcSwapOptionBytesHolder {
     }
 }
 
+/// Handle to a shared [`SwapInner`], standing in for a custom ref-count type
+#[proto_message(transparent)]
+#[derive(Clone, Debug)]
+pub struct SharedInner(Arc<SwapInner>);
+
+// SAFETY: every method forwards to the `Arc` the handle wraps
+unsafe impl RefCnt for SharedInner {
+    type Base = SwapInner;
+
+    fn into_ptr(me: Self) -> *mut SwapInner {
+        <Arc<SwapInner> as RefCnt>::into_ptr(me.0)
+    }
+
+    fn as_ptr(me: &Self) -> *mut SwapInner {
+        <Arc<SwapInner> as RefCnt>::as_ptr(&me.0)
+    }
+
+    unsafe fn from_ptr(ptr: *const SwapInner) -> Self {
+        Self(unsafe { <Arc<SwapInner> as RefCnt>::from_ptr(ptr) })
+    }
+}
+
+#[proto_message(proto_path = "protos/tests/arc_swap.proto")]
+#[derive(Debug)]
+pub struct SwapAnyHolder {
+    #[proto(tag = 1)]
+    pub pointer: ArcSwapAny<Arc<SwapInner>>,
+    #[proto(tag = 2)]
+    pub maybe: ArcSwapAny<Option<Arc<SwapInner>>>,
+    #[proto(tag = 3, rename = "SwapInner")]
+    pub shared: ArcSwapAny<SharedInner>,
+}
+
+/// The same message with plain fields
+#[proto_message(proto_path = "protos/tests/arc_swap.proto")]
+#[derive(Debug, Default)]
+pub struct PlainSwapHolder {
+    #[proto(tag = 1)]
+    pub pointer: SwapInner,
+    #[proto(tag = 2)]
+    pub maybe: Option<SwapInner>,
+    #[proto(tag = 3)]
+    pub shared: SwapInner,
+}
+
 #[test]
 fn arc_swap_roundtrip_preserves_inner_value() {
     let holder = SwapHolder {
@@ -169,3 +219,40 @@ fn arc_swap_option_bytes_roundtrip_handles_presence_and_absence() {
         .expect("decode default arc swap option bytes");
     assert!(decoded_default.maybe_swap_bytes.load().as_ref().is_none());
 }
+
+#[test]
+fn arc_swap_any_fields_encode_like_their_pointee() {
+    let inner = |label: &str, count| SwapInner {
+        label: label.into(),
+        count,
+    };
+    let holder = SwapAnyHolder {
+        pointer: ArcSwapAny::new(Arc::new(inner("pointer", 1))),
+        maybe: ArcSwapAny::new(Some(Arc::new(inner("maybe", 2)))),
+        shared: ArcSwapAny::new(SharedInner(Arc::new(inner("shared", 3)))),
+    };
+    let plain = PlainSwapHolder {
+        pointer: inner("pointer", 1),
+        maybe: Some(inner("maybe", 2)),
+        shared: inner("shared", 3),
+    };
+
+    let encoded = <SwapAnyHolder as ProtoEncode>::encode_to_vec(&holder);
+    assert_eq!(encoded, <PlainSwapHolder as ProtoEncode>::encode_to_vec(&plain));
+
+    let decoded = <SwapAnyHolder as ProtoDecode>::decode(&encoded[..], DecodeContext::default()).expect("decode arc swap any");
+    assert_eq!(*decoded.pointer.load_full(), inner("pointer", 1));
+    assert_eq!(decoded.maybe.load_full().as_deref(), Some(&inner("maybe", 2)));
+    assert_eq!(*decoded.shared.load_full().0, inner("shared", 3));
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn arc_swap_any_schema_matches_the_aliases() {
+    let proto = common::emitted_proto("arc_swap", "protos/tests/arc_swap.proto");
+
+    assert!(
+        proto.contains("message SwapAnyHolder {\n  SwapInner pointer = 1;\n  optional SwapInner maybe = 2;\n  SwapInner shared = 3;\n}"),
+        "{proto}"
+    );
+}
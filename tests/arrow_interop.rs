@@ -0,0 +1,95 @@
+#![cfg(feature = "arrow")]
+
+use arrow_array::Float64Array;
+use arrow_array::UInt64Array;
+use bytes::Bytes;
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::arrow::array_to_vec;
+use proto_rs::arrow::field_to_array;
+use proto_rs::arrow::vec_to_array;
+use proto_rs::arrow::write_packed_array;
+use proto_rs::proto_message;
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Frame {
+    pub symbol: String,
+    pub prices: Vec<f64>,
+    pub sizes: Vec<u64>,
+}
+
+fn frame() -> Frame {
+    Frame {
+        symbol: "AAPL".into(),
+        prices: (0..100).map(|idx| f64::from(idx) * 0.25).collect(),
+        sizes: (0..100).map(|idx| idx * 1000).collect(),
+    }
+}
+
+#[test]
+fn vec_fields_move_into_arrays_and_back() {
+    let frame = frame();
+    let prices = frame.prices.clone();
+    let ptr = prices.as_ptr();
+    let array: Float64Array = vec_to_array(prices);
+    assert_eq!(array.values().as_ptr(), ptr);
+
+    let back = array_to_vec::<f64>(array).unwrap();
+    assert_eq!(back.as_ptr(), ptr);
+    assert_eq!(back, frame.prices);
+
+    // a slice of a shared array is copied
+    let array: UInt64Array = vec_to_array(frame.sizes.clone());
+    let tail = array.slice(90, 10);
+    assert_eq!(array_to_vec::<u64>(tail).unwrap(), frame.sizes[90..]);
+
+    let nulls = UInt64Array::from(vec![Some(1), None]);
+    assert!(array_to_vec::<u64>(nulls).is_err());
+}
+
+/// `encoded` placed in memory so that the bytes at `payload` start `shift` bytes past an 8-byte boundary.
+fn placed(encoded: &[u8], payload: usize, shift: usize) -> Bytes {
+    let mut storage = vec![0u8; encoded.len() + 16];
+    let start = (8 - (storage.as_ptr() as usize + payload) % 8) % 8 + shift;
+    storage[start..start + encoded.len()].copy_from_slice(encoded);
+    Bytes::from(storage).slice(start..start + encoded.len())
+}
+
+#[test]
+fn packed_fields_are_read_from_encoded_messages() {
+    let frame = frame();
+    let encoded = frame.encode_to_vec();
+    // key of field 2 and the length 800
+    let payload = encoded.windows(3).position(|window| window == [0x12, 0xa0, 0x06]).unwrap() + 3;
+
+    let aligned = placed(&encoded, payload, 0);
+    let prices = field_to_array::<f64>(&aligned, 2).unwrap();
+    assert_eq!(prices.values().as_ref(), frame.prices.as_slice());
+    assert_eq!(prices.values().as_ptr().cast::<u8>(), aligned[payload..].as_ptr());
+
+    let misaligned = placed(&encoded, payload, 1);
+    let prices = field_to_array::<f64>(&misaligned, 2).unwrap();
+    assert_eq!(prices.values().as_ref(), frame.prices.as_slice());
+    assert_ne!(prices.values().as_ptr().cast::<u8>(), misaligned[payload..].as_ptr());
+
+    let sizes = field_to_array::<u64>(&aligned, 3).unwrap();
+    assert_eq!(sizes.values().as_ref(), frame.sizes.as_slice());
+    assert!(field_to_array::<u64>(&aligned, 9).unwrap().is_empty());
+}
+
+#[test]
+fn arrays_are_written_as_packed_fields() {
+    let frame = frame();
+    let mut bytes = Frame {
+        symbol: frame.symbol.clone(),
+        ..Frame::default()
+    }
+    .encode_to_vec();
+    write_packed_array::<f64>(2, &vec_to_array(frame.prices.clone()), &mut bytes).unwrap();
+    write_packed_array::<u64>(3, &vec_to_array(frame.sizes.clone()), &mut bytes).unwrap();
+
+    assert_eq!(bytes, frame.encode_to_vec());
+    assert_eq!(Frame::decode(bytes.as_slice(), DecodeContext::default()).unwrap(), frame);
+}
@@ -0,0 +1,98 @@
+use proto_rs::AttributeMap;
+use proto_rs::AttributeValue;
+use proto_rs::DecodeContext;
+use proto_rs::Name;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::custom_types::well_known::Any;
+use proto_rs::proto_message;
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TraceContext {
+    pub trace_id: String,
+    pub sampled: bool,
+}
+
+impl Name for TraceContext {
+    const NAME: &'static str = "TraceContext";
+    const PACKAGE: &'static str = "tracing";
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Job {
+    pub name: String,
+    pub extensions: AttributeMap,
+}
+
+#[test]
+fn typed_accessors_check_the_stored_kind() {
+    let mut attrs = AttributeMap::new();
+    assert_eq!(attrs.set("retries", 3u32), None);
+    attrs.set("offset", -40i64);
+    attrs.set("ratio", 0.5f32);
+    attrs.set("region", "eu-west");
+    attrs.set("blob", &[1u8, 2][..]);
+
+    assert_eq!(attrs.get::<u32>("retries"), Some(3));
+    assert_eq!(attrs.get::<u64>("retries"), Some(3));
+    assert_eq!(attrs.get::<i64>("retries"), None);
+    assert_eq!(attrs.get::<i8>("offset"), Some(-40));
+    assert_eq!(attrs.get::<f64>("ratio"), Some(0.5));
+    assert_eq!(attrs.get::<&str>("region"), Some("eu-west"));
+    assert_eq!(attrs.get::<String>("region").as_deref(), Some("eu-west"));
+    assert_eq!(attrs.get::<&[u8]>("blob"), Some(&[1u8, 2][..]));
+    assert_eq!(attrs.get::<bool>("missing"), None);
+
+    attrs.set("retries", u64::from(u32::MAX) + 1);
+    assert_eq!(attrs.get::<u32>("retries"), None);
+    assert_eq!(attrs.remove("blob"), Some(AttributeValue::Bytes(vec![1, 2])));
+    assert_eq!(attrs.len(), 4);
+}
+
+#[test]
+fn messages_are_packed_as_any() {
+    let trace = TraceContext {
+        trace_id: "abc".into(),
+        sampled: true,
+    };
+    let mut attrs = AttributeMap::new();
+    attrs.set_message("trace", &trace);
+    attrs.set("plain", true);
+
+    assert_eq!(
+        attrs.get::<Any>("trace").map(|any| any.type_url),
+        Some("/tracing.TraceContext".to_string())
+    );
+    assert_eq!(attrs.get_message::<TraceContext>("trace").expect("trace decodes"), Some(trace));
+    assert_eq!(attrs.get_message::<TraceContext>("plain").expect("not a message"), None);
+
+    let other = Any {
+        type_url: "type.googleapis.com/other.Message".into(),
+        value: vec![0xFF],
+    };
+    attrs.set("other", other);
+    assert_eq!(
+        attrs.get_message::<TraceContext>("other").expect("type mismatch is not an error"),
+        None
+    );
+}
+
+#[test]
+fn attribute_map_roundtrips_as_a_field() {
+    let job = Job {
+        name: "reindex".into(),
+        extensions: [
+            ("priority", AttributeValue::from(7u8)),
+            ("dry_run", AttributeValue::from(false)),
+            ("owner", AttributeValue::from("ops")),
+        ]
+        .into_iter()
+        .collect(),
+    };
+    let decoded = <Job as ProtoDecode>::decode(job.encode_to_vec().as_slice(), DecodeContext::default()).expect("job decodes");
+    assert_eq!(decoded, job);
+    assert_eq!(decoded.extensions.get::<u8>("priority"), Some(7));
+    assert_eq!(decoded.extensions.get::<bool>("dry_run"), Some(false));
+}
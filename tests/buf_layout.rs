@@ -0,0 +1,64 @@
+#![cfg(feature = "build-schemas")]
+
+use std::collections::BTreeSet;
+
+use proto_rs::proto_message;
+use proto_rs::schemas::BufLayout;
+use proto_rs::schemas::RustClientCtx;
+
+mod common;
+
+#[proto_message(proto_path = "protos/tests/BufLedger.proto", proto_package = "acme.ledger.v1")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LedgerEntry {
+    pub id: u64,
+}
+
+#[proto_message(proto_path = "protos/tests/buf-report.proto", proto_package = "acme.report.v2beta1")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Report {
+    pub entries: Vec<LedgerEntry>,
+}
+
+#[test]
+fn files_are_written_to_package_directories() {
+    let ctx = RustClientCtx::disabled().with_buf_layout(BufLayout::new().plugin("buf.build/protocolbuffers/go", "gen/go"));
+    let dir = common::write_schemas("buf_layout", &ctx);
+    let ledger = dir.read("acme/ledger/v1/buf_ledger.proto");
+    let report = dir.read("acme/report/v2beta1/buf_report.proto");
+    let buf_yaml = dir.read("buf.yaml");
+    let buf_gen_yaml = dir.read("buf.gen.yaml");
+
+    assert!(!dir.join("protos/tests/BufLedger.proto").exists());
+    assert!(ledger.contains("package acme.ledger.v1;"), "{ledger}");
+    assert!(report.contains("import \"acme/ledger/v1/buf_ledger.proto\";"), "{report}");
+    assert!(report.contains("repeated acme.ledger.v1.LedgerEntry entries = 1;"), "{report}");
+    assert!(buf_yaml.contains("version: v2\nmodules:\n  - path: .\n"), "{buf_yaml}");
+    assert!(buf_yaml.contains("    - STANDARD\n"), "{buf_yaml}");
+    assert!(
+        buf_gen_yaml.contains("  - remote: buf.build/protocolbuffers/go\n    out: gen/go\n"),
+        "{buf_gen_yaml}"
+    );
+}
+
+#[test]
+fn unversioned_packages_except_the_version_rule() {
+    let versioned = BTreeSet::from(["acme.ledger.v1".to_string(), "acme.report.v2beta1".to_string()]);
+    let yaml = BufLayout::new().strict_naming().buf_yaml(&versioned);
+    assert!(!yaml.contains("except"), "{yaml}");
+
+    let unversioned = BTreeSet::from(["billing".to_string()]);
+    let yaml = BufLayout::new().strict_naming().lint_except("FIELD_LOWER_SNAKE_CASE").buf_yaml(&unversioned);
+    assert!(
+        yaml.contains("  except:\n    - FIELD_LOWER_SNAKE_CASE\n    - PACKAGE_VERSION_SUFFIX\n"),
+        "{yaml}"
+    );
+}
+
+#[test]
+fn naming_rules_are_excepted_by_default() {
+    let yaml = BufLayout::new().buf_yaml(&BTreeSet::from(["acme.ledger.v1".to_string()]));
+    assert!(yaml.contains("    - ENUM_VALUE_PREFIX\n"), "{yaml}");
+    assert!(yaml.contains("    - SERVICE_SUFFIX\n"), "{yaml}");
+    assert!(BufLayout::new().buf_gen_yaml().is_none());
+}
@@ -0,0 +1,86 @@
+use proto_rs::ArchivedProtoMessageWriter;
+use proto_rs::BufferStrategy;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Millis(u64);
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+const fn millis_to_u64(value: &Millis) -> u64 {
+    value.0
+}
+
+const fn u64_to_millis(value: u64) -> Millis {
+    Millis(value)
+}
+
+// Variants behind a conversion give no size hint, so the strategy picks the first allocation.
+#[proto_message]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Timed {
+    At(#[proto(into = "u64", into_fn = "millis_to_u64", from_fn = "u64_to_millis")] Millis),
+    Note { text: String },
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stamped {
+    At(#[proto(into = "u64", into_fn = "millis_to_u64", from_fn = "u64_to_millis")] Millis),
+    Note { text: String },
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Logged {
+    At(#[proto(into = "u64", into_fn = "millis_to_u64", from_fn = "u64_to_millis")] Millis),
+    Note { text: String },
+}
+
+fn raw_capacity<T: ProtoEncode + proto_rs::ProtoExt>(value: &T) -> usize {
+    ArchivedProtoMessageWriter::<T>::new(value).expect("non-default value").to_vec_raw().capacity()
+}
+
+#[test]
+fn registered_initial_capacity_is_used_without_hint() {
+    let value = Timed::At(Millis(5));
+    assert_eq!(value.encoded_len_hint(), None);
+    BufferStrategy::new(4096).register::<Timed>();
+    assert_eq!(BufferStrategy::for_type::<Timed>().initial_capacity, 4096);
+    assert_eq!(raw_capacity(&value), 4096);
+    BufferStrategy::unregister::<Timed>();
+    assert_eq!(BufferStrategy::for_type::<Timed>(), BufferStrategy::global());
+}
+
+#[test]
+fn growth_factor_scales_a_full_buffer() {
+    BufferStrategy::new(2).with_growth_factor(8).register::<Stamped>();
+    let value = Stamped::At(Millis(u64::MAX));
+    assert_eq!(value.encoded_len_hint(), None);
+    // The 10 byte varint overflows the 2 byte buffer, which grows to 2 * 8.
+    assert_eq!(raw_capacity(&value), 16);
+    assert!(value.encode_to_vec().len() <= 16);
+}
+
+#[test]
+fn max_retained_trims_encoded_vec() {
+    BufferStrategy::new(4096).with_max_retained(0).register::<Logged>();
+    let bytes = Logged::At(Millis(7)).encode_to_vec();
+    assert_eq!(bytes.capacity(), bytes.len());
+}
+
+#[test]
+fn hinted_types_ignore_initial_capacity() {
+    BufferStrategy::new(1 << 16).register::<String>();
+    let value = String::from("short");
+    assert_eq!(raw_capacity(&value), value.encoded_len_hint().expect("strings are hinted"));
+}
+
+#[test]
+fn default_strategy_matches_previous_behaviour() {
+    let strategy = BufferStrategy::default();
+    assert_eq!(strategy, BufferStrategy::DEFAULT);
+    assert_eq!(strategy.initial_capacity, 64);
+    assert_eq!(strategy.growth_factor, 2);
+    assert_eq!(strategy.max_retained, usize::MAX);
+}
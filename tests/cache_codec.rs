@@ -0,0 +1,46 @@
+use proto_rs::cache::Codec;
+use proto_rs::proto_message;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Session {
+    pub user: String,
+    pub expires_at: u64,
+}
+
+fn session() -> Session {
+    Session {
+        user: "ada".into(),
+        expires_at: 300,
+    }
+}
+
+#[test]
+fn bytes_are_length_delimited() {
+    let bytes = Codec(session()).to_bytes();
+    assert_eq!(bytes[0] as usize, bytes.len() - 1);
+    assert_eq!(Codec::<Session>::from_bytes(&bytes).unwrap().into_inner(), session());
+    assert_eq!(Codec::weigh(&"session:1", &session()) as usize, bytes.len());
+
+    assert_eq!(Codec::<Session>::from_bytes(&[0]).unwrap().0, Session::default());
+    assert!(Codec::<Session>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    let mut padded = bytes.clone();
+    padded.push(0);
+    assert!(Codec::<Session>::from_bytes(&padded).is_err());
+}
+
+#[cfg(feature = "redis")]
+#[test]
+fn redis_values_round_trip() {
+    use redis::FromRedisValue;
+    use redis::ToRedisArgs;
+    use redis::Value;
+
+    let args = Codec(session()).to_redis_args();
+    assert_eq!(args, vec![Codec(session()).to_bytes()]);
+
+    let reply = Value::BulkString(args[0].clone());
+    assert_eq!(Codec::<Session>::from_redis_value(&reply).unwrap().0, session());
+    assert_eq!(Option::<Codec<Session>>::from_redis_value(&Value::Nil).unwrap(), None);
+    assert!(Codec::<Session>::from_redis_value(&Value::Int(1)).is_err());
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "cbor")]
+
+use std::collections::BTreeMap;
+
+use proto_rs::cbor::from_cbor;
+use proto_rs::cbor::to_cbor;
+use proto_rs::proto_message;
+use proto_rs::transcode::TranscodeError;
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Stage {
+    #[default]
+    Open,
+    Shipped,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Reading {
+    pub id: u64,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Payment {
+    Cash,
+    Card(String),
+    Transfer { iban: String, amount: i64 },
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Shipment {
+    pub id: u64,
+    pub stage: Stage,
+    pub note: Option<String>,
+    pub readings: Vec<Reading>,
+    pub offsets: Vec<i32>,
+    pub blob: Vec<u8>,
+    pub ratio: f32,
+    pub counts: BTreeMap<u32, i64>,
+    pub payment: Option<Payment>,
+}
+
+fn shipment() -> Shipment {
+    Shipment {
+        id: u64::MAX,
+        stage: Stage::Shipped,
+        note: Some("fragile".into()),
+        readings: vec![Reading { id: 1 }, Reading::default()],
+        offsets: vec![-2, 0, 300],
+        blob: vec![0, 255],
+        ratio: 0.5,
+        counts: BTreeMap::from([(1, -1), (7, 70)]),
+        payment: Some(Payment::Transfer {
+            iban: "DE00".into(),
+            amount: -3,
+        }),
+    }
+}
+
+#[test]
+fn messages_roundtrip_through_cbor() {
+    let bytes = to_cbor(&shipment()).unwrap();
+    assert_eq!(from_cbor::<Shipment>(&bytes).unwrap(), shipment());
+
+    let unit = Shipment {
+        payment: Some(Payment::Cash),
+        ..Shipment::default()
+    };
+    assert_eq!(from_cbor::<Shipment>(&to_cbor(&unit).unwrap()).unwrap(), unit);
+}
+
+#[test]
+fn fields_are_keyed_by_number() {
+    // {1: 7}, with default fields left out
+    assert_eq!(to_cbor(&Reading { id: 7 }).unwrap(), [0xa1, 0x01, 0x07]);
+    // {2: 1, 3: "x"}
+    let shipment = Shipment {
+        stage: Stage::Shipped,
+        note: Some("x".into()),
+        ..Shipment::default()
+    };
+    assert_eq!(to_cbor(&shipment).unwrap(), [0xa2, 0x02, 0x01, 0x03, 0x61, b'x']);
+
+    // Unknown field numbers are skipped: {1: 7, 99: 0}
+    assert_eq!(
+        from_cbor::<Reading>(&[0xa2, 0x01, 0x07, 0x18, 0x63, 0x00]).unwrap(),
+        Reading { id: 7 }
+    );
+}
+
+#[test]
+fn mismatched_values_are_rejected() {
+    // {1: "seven"}
+    let error = from_cbor::<Reading>(&[0xa1, 0x01, 0x65, b's', b'e', b'v', b'e', b'n']).unwrap_err();
+    assert!(matches!(&error, TranscodeError::Invalid { path, .. } if path == "1"), "{error}");
+    assert!(matches!(from_cbor::<Reading>(&[0xff]), Err(TranscodeError::Malformed(_))));
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "build-schemas")]
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+mod common;
+
+#[proto_message(proto_path = "protos/tests/cfg_fields.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Profile {
+    pub id: u64,
+    #[cfg(not(feature = "build-schemas"))]
+    pub legacy_note: String,
+    #[proto(cfg = "not(feature = \"build-schemas\")")]
+    pub audit: u32,
+    #[proto(cfg = "feature = \"build-schemas\"")]
+    pub region: String,
+    pub name: String,
+}
+
+#[test]
+fn disabled_fields_keep_their_tags() {
+    let profile = Profile {
+        name: "ada".into(),
+        ..Profile::default()
+    };
+    assert_eq!(profile.encode_to_vec(), [0x2A, 0x03, b'a', b'd', b'a']);
+
+    let region = Profile {
+        region: "eu".into(),
+        ..Profile::default()
+    };
+    assert_eq!(region.encode_to_vec(), [0x22, 0x02, b'e', b'u']);
+}
+
+#[test]
+fn tags_of_disabled_fields_are_skipped_on_decode() {
+    // legacy_note = 2 and audit = 3, as written by a build that has them
+    let bytes = [0x08, 0x01, 0x12, 0x01, b'x', 0x18, 0x07, 0x2A, 0x01, b'b'];
+    let decoded = <Profile as ProtoDecode>::decode(bytes.as_slice(), DecodeContext::default()).expect("profile decodes");
+    assert_eq!(
+        decoded,
+        Profile {
+            id: 1,
+            region: String::new(),
+            name: "b".into(),
+        }
+    );
+}
+
+#[test]
+fn schema_lists_conditional_fields_as_optional() {
+    let proto = common::emitted_proto("cfg_fields", "protos/tests/cfg_fields.proto");
+
+    assert!(proto.contains("  uint64 id = 1;\n"), "{proto}");
+    assert!(proto.contains("  optional string legacy_note = 2;\n"), "{proto}");
+    assert!(proto.contains("  optional uint32 audit = 3;\n"), "{proto}");
+    assert!(proto.contains("  optional string region = 4;\n"), "{proto}");
+    assert!(proto.contains("  string name = 5;\n"), "{proto}");
+}
@@ -0,0 +1,64 @@
+//! Helpers shared by the tests that write files.
+// Each test binary uses a subset of the helpers.
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use proto_rs::schemas::RustClientCtx;
+use proto_rs::schemas::write_all;
+
+/// Directory under the system temp dir, unique to the test process, removed on drop.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("proto_rs_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        Self(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.0.join(path)
+    }
+
+    pub fn to_str(&self) -> &str {
+        self.0.to_str().expect("utf-8 temp dir")
+    }
+
+    /// Contents of the file at `path` inside the directory.
+    pub fn read(&self, path: impl AsRef<Path>) -> String {
+        let path = self.0.join(path);
+        std::fs::read_to_string(&path).unwrap_or_else(|error| panic!("{} is not readable: {error}", path.display()))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Every registered schema written with `ctx` into a fresh directory.
+pub fn write_schemas(name: &str, ctx: &RustClientCtx) -> TempDir {
+    let dir = TempDir::new(name);
+    write_all(dir.to_str(), ctx).expect("schemas are written");
+    dir
+}
+
+/// Every registered schema written into a fresh directory, with the rust client in `client.rs`.
+pub fn write_schemas_with_client(name: &str) -> TempDir {
+    let dir = TempDir::new(name);
+    let client = dir.join("client.rs");
+    write_all(dir.to_str(), &RustClientCtx::enabled(client.to_str().expect("utf-8 client path"))).expect("schemas are written");
+    dir
+}
+
+/// The emitted `.proto` file at `path`, with every schema written without a client.
+pub fn emitted_proto(name: &str, path: &str) -> String {
+    write_schemas(name, &RustClientCtx::disabled()).read(path)
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "compact")]
+
+use std::collections::BTreeMap;
+
+use proto_rs::ProtoEncode;
+use proto_rs::compact::from_compact;
+use proto_rs::compact::to_compact;
+use proto_rs::proto_message;
+use proto_rs::transcode::TranscodeError;
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tier {
+    #[default]
+    Free,
+    Paid,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Counter {
+    pub hits: u64,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Unknown,
+    Url(String),
+    File { path: String, line: u32 },
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CacheEntry {
+    pub key: String,
+    pub tier: Tier,
+    pub ttl: Option<u32>,
+    pub counter: Option<Counter>,
+    pub deltas: Vec<i64>,
+    pub body: Vec<u8>,
+    pub score: f32,
+    pub labels: BTreeMap<String, Counter>,
+    pub source: Option<Source>,
+}
+
+fn entry() -> CacheEntry {
+    CacheEntry {
+        key: "user:42".into(),
+        tier: Tier::Paid,
+        ttl: Some(0),
+        counter: Some(Counter { hits: 3 }),
+        deltas: vec![-1, 0, i64::MAX],
+        body: vec![1, 2, 3],
+        score: 0.25,
+        labels: BTreeMap::from([("eu".to_string(), Counter { hits: 1 }), ("us".to_string(), Counter::default())]),
+        source: Some(Source::File {
+            path: "a.rs".into(),
+            line: 9,
+        }),
+    }
+}
+
+#[test]
+fn messages_roundtrip_through_the_compact_form() {
+    let bytes = to_compact(&entry()).unwrap();
+    assert_eq!(from_compact::<CacheEntry>(&bytes).unwrap(), entry());
+    assert!(bytes.len() - 8 < entry().encode_to_vec().len());
+
+    for source in [None, Some(Source::Unknown), Some(Source::Url("https://x".into()))] {
+        let entry = CacheEntry {
+            source,
+            ..CacheEntry::default()
+        };
+        assert_eq!(from_compact::<CacheEntry>(&to_compact(&entry).unwrap()).unwrap(), entry);
+    }
+}
+
+#[test]
+fn fields_are_written_without_keys() {
+    let bytes = to_compact(&Counter { hits: 300 }).unwrap();
+    assert_eq!(bytes[8..], [0xac, 0x02]);
+    assert_eq!(to_compact(&Counter::default()).unwrap()[8..], [0x00]);
+}
+
+#[test]
+fn other_layouts_and_truncated_bytes_are_rejected() {
+    let mut bytes = to_compact(&entry()).unwrap();
+    assert!(matches!(
+        from_compact::<CacheEntry>(&bytes[..bytes.len() - 1]),
+        Err(TranscodeError::Decode(_))
+    ));
+    assert!(matches!(from_compact::<Counter>(&bytes), Err(TranscodeError::SchemaChanged { .. })));
+
+    bytes[0] ^= 1;
+    assert!(matches!(
+        from_compact::<CacheEntry>(&bytes),
+        Err(TranscodeError::SchemaChanged { .. })
+    ));
+}
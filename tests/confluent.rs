@@ -0,0 +1,210 @@
+#![cfg(feature = "confluent")]
+
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use proto_rs::kafka::confluent::ConfluentDeserializer;
+use proto_rs::kafka::confluent::ConfluentSerializer;
+use proto_rs::kafka::confluent::RegisteredSchema;
+use proto_rs::kafka::confluent::RegistryError;
+use proto_rs::kafka::confluent::SchemaRegistry;
+use proto_rs::kafka::confluent::split_frame;
+use proto_rs::kafka::confluent::write_header;
+use proto_rs::proto_message;
+use proto_rs::schemas::RustClientCtx;
+use serde_json::Value;
+use serde_json::json;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+
+mod common;
+
+#[proto_message(proto_path = "protos/tests/confluent_money.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Money {
+    pub cents: i64,
+    pub currency: String,
+}
+
+#[proto_message(proto_path = "protos/tests/confluent_billing.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Invoice {
+    pub id: u64,
+    pub total: Money,
+}
+
+#[proto_message(proto_path = "protos/tests/confluent_billing.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Refund {
+    pub invoice_id: u64,
+}
+
+fn invoice() -> Invoice {
+    Invoice {
+        id: 12,
+        total: Money {
+            cents: 995,
+            currency: "EUR".into(),
+        },
+    }
+}
+
+#[test]
+fn frames_carry_schema_id_and_message_indexes() {
+    let first = RegisteredSchema {
+        id: 7,
+        message_indexes: vec![0],
+    };
+    let payload = ConfluentSerializer::new(&first).serialize(&invoice());
+    assert_eq!(payload[..6], [0, 0, 0, 0, 7, 0]);
+    let frame = split_frame(&payload).unwrap();
+    assert_eq!((frame.schema_id, frame.message_indexes.as_slice()), (7, &[0][..]));
+    assert_eq!(
+        ConfluentDeserializer::<Invoice>::new().deserialize(Some(&payload)).unwrap(),
+        Some(invoice())
+    );
+    assert_eq!(ConfluentDeserializer::<Invoice>::new().deserialize(None).unwrap(), None);
+
+    let mut header = Vec::new();
+    write_header(258, &[1, 0], &mut header);
+    assert_eq!(header, [0, 0, 0, 1, 2, 4, 2, 0]);
+    assert_eq!(split_frame(&header).unwrap().message_indexes, [1, 0]);
+
+    let second = RegisteredSchema {
+        id: 7,
+        message_indexes: vec![1],
+    };
+    let deserializer = ConfluentDeserializer::<Invoice>::new().expect_indexes(&second);
+    assert!(deserializer.deserialize(Some(&payload)).is_err());
+    assert!(ConfluentDeserializer::<Invoice>::new().deserialize(Some(&payload[1..])).is_err());
+    assert!(split_frame(&payload[..3]).is_err());
+}
+
+#[derive(Default)]
+struct MockRegistry {
+    schemas: Vec<(String, Value)>,
+    requests: Vec<(String, String)>,
+}
+
+impl MockRegistry {
+    fn answer(&mut self, method: &str, path: &str, body: &Value) -> (u16, Value) {
+        self.requests.push((method.to_string(), path.to_string()));
+        let found = |subject: &str, schemas: &[(String, Value)]| {
+            schemas.iter().position(|(registered, schema)| registered == subject && schema == body)
+        };
+        match (method, path.strip_prefix("/subjects/"), path.strip_prefix("/schemas/ids/")) {
+            ("POST", Some(rest), _) if rest.ends_with("/versions") => {
+                let subject = rest.trim_end_matches("/versions").to_string();
+                if body["schema"].as_str().unwrap_or_default().contains("Incompatible") {
+                    return (
+                        409,
+                        json!({"error_code": 409, "message": "Schema being registered is incompatible"}),
+                    );
+                }
+                let id = found(&subject, &self.schemas).unwrap_or_else(|| {
+                    self.schemas.push((subject, body.clone()));
+                    self.schemas.len() - 1
+                });
+                (200, json!({"id": id + 1}))
+            }
+            ("POST", Some(subject), _) => match found(subject, &self.schemas) {
+                Some(id) => (
+                    200,
+                    json!({"subject": subject, "id": id + 1, "version": 1, "schema": body["schema"]}),
+                ),
+                None => (404, json!({"error_code": 40403, "message": "Schema not found"})),
+            },
+            ("GET", _, Some(id)) => {
+                let schema = id.parse::<usize>().ok().and_then(|id| self.schemas.get(id - 1));
+                schema.map_or((404, json!({"message": "Schema not found"})), |(_, schema)| {
+                    (200, json!({"schema": schema["schema"]}))
+                })
+            }
+            _ => (404, json!({"message": "not found"})),
+        }
+    }
+}
+
+async fn serve(registry: Arc<Mutex<MockRegistry>>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut chunk = [0; 4096];
+            let (head_len, content_length) = loop {
+                let read = stream.read(&mut chunk).await.unwrap();
+                request.extend_from_slice(&chunk[..read]);
+                if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+                    let head = String::from_utf8_lossy(&request[..end]).to_ascii_lowercase();
+                    let length =
+                        head.lines().find_map(|line| line.strip_prefix("content-length: ")).map_or(0, |len| len.trim().parse().unwrap());
+                    break (end + 4, length);
+                }
+            };
+            while request.len() < head_len + content_length {
+                let read = stream.read(&mut chunk).await.unwrap();
+                request.extend_from_slice(&chunk[..read]);
+            }
+            let head = String::from_utf8_lossy(&request[..head_len]).to_string();
+            let mut request_line = head.split_whitespace();
+            let (method, path) = (request_line.next().unwrap().to_string(), request_line.next().unwrap().to_string());
+            let body = serde_json::from_slice(&request[head_len..]).unwrap_or(Value::Null);
+            let (status, reply) = registry.lock().unwrap().answer(&method, &path, &body);
+            let reply = reply.to_string();
+            let response = format!(
+                "HTTP/1.1 {status} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{reply}",
+                reply.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    url
+}
+
+#[tokio::test]
+async fn emitted_files_are_registered_with_their_imports() {
+    let output_dir = common::write_schemas("confluent", &RustClientCtx::disabled());
+    let mock = Arc::new(Mutex::new(MockRegistry::default()));
+    let registry = SchemaRegistry::new(&serve(mock.clone()).await);
+
+    let invoice_schema = registry.register_emitted::<Invoice>(output_dir.path(), "billing-value").await.unwrap();
+    let refund_schema = registry.register_emitted::<Refund>(output_dir.path(), "billing-value").await.unwrap();
+    assert_eq!(invoice_schema.id, refund_schema.id);
+    let mut indexes = [invoice_schema.message_indexes.clone(), refund_schema.message_indexes.clone()];
+    indexes.sort();
+    assert_eq!(indexes, [vec![0], vec![1]]);
+
+    {
+        let mock = mock.lock().unwrap();
+        let (subject, billing) = &mock.schemas[1];
+        assert_eq!(subject, "billing-value");
+        assert_eq!(billing["schemaType"], "PROTOBUF");
+        assert_eq!(
+            billing["references"],
+            json!([{"name": "confluent_money.proto", "subject": "confluent_money.proto", "version": 1}])
+        );
+        assert_eq!(mock.schemas[0].0, "confluent_money.proto");
+        assert_eq!(
+            mock.requests[0],
+            ("POST".to_string(), "/subjects/confluent_money.proto/versions".to_string())
+        );
+    }
+
+    let schema = registry.schema(invoice_schema.id).await.unwrap();
+    assert!(schema.contains("message Invoice"));
+    let Err(RegistryError::Registry { status, message }) = registry.schema(99).await else {
+        panic!("unknown schema id accepted");
+    };
+    assert_eq!((status, message.as_str()), (404, "Schema not found"));
+    assert!(matches!(
+        registry.register("billing-value", "message Incompatible {}", &[]).await,
+        Err(RegistryError::Registry { status: 409, .. })
+    ));
+
+    let payload = ConfluentSerializer::new(&invoice_schema).serialize(&invoice());
+    let decoded = ConfluentDeserializer::<Invoice>::new().expect_indexes(&invoice_schema).deserialize(Some(&payload)).unwrap();
+    assert_eq!(decoded, Some(invoice()));
+}
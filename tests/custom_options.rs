@@ -0,0 +1,119 @@
+#![cfg(feature = "build-schemas")]
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use proto_rs::schemas;
+use proto_rs::schemas::RustClientCtx;
+use tonic::Response;
+
+mod common;
+
+#[proto_message(proto_path = "protos/tests/custom_options.proto")]
+#[proto(option("acme.priority") = 3, option("acme.owner") = "billing")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Invoice {
+    #[proto(option("acme.weight") = 0.5)]
+    pub amount: u64,
+    #[proto(option("acme.weight") = -2, option("acme.audited") = true)]
+    pub customer: String,
+    pub currency: Currency,
+}
+
+#[proto_message(proto_path = "protos/tests/custom_options.proto")]
+#[proto(option("acme.enum_scope") = "global")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Currency {
+    #[default]
+    Eur,
+    Usd,
+}
+
+#[proto_rpc(
+    rpc_package = "custom_options",
+    proto_path = "protos/tests/custom_options.proto",
+    rpc_server = true,
+    rpc_client = true
+)]
+pub trait Invoices {
+    #[proto(option("acme.sla_ms") = 250)]
+    async fn issue(&self, request: Request<Invoice>) -> Result<Response<Invoice>, Status>;
+    async fn preview(&self, request: Request<Invoice>) -> Result<Response<Invoice>, Status>;
+}
+
+fn written(test: &str) -> (String, String) {
+    let dir = common::write_schemas(&format!("custom_options_{test}"), &RustClientCtx::disabled());
+    (dir.read("protos/tests/custom_options.proto"), dir.read("acme_options.proto"))
+}
+
+#[test]
+fn options_are_written_on_messages_fields_and_methods() {
+    let (proto, _) = written("usage");
+    assert!(proto.contains("import \"acme_options.proto\";"), "{proto}");
+    assert!(
+        proto.contains("message Invoice {\n  option (acme.priority) = 3;\n  option (acme.owner) = \"billing\";\n"),
+        "{proto}"
+    );
+    assert!(proto.contains("  uint64 amount = 1 [(acme.weight) = 0.5];"), "{proto}");
+    assert!(
+        proto.contains("  string customer = 2 [(acme.weight) = -2, (acme.audited) = true];"),
+        "{proto}"
+    );
+    assert!(
+        proto.contains("enum Currency {\n  option (acme.enum_scope) = \"global\";\n"),
+        "{proto}"
+    );
+    assert!(
+        proto.contains("  rpc Issue(Invoice) returns (Invoice) {\n    option (acme.sla_ms) = 250;\n  }"),
+        "{proto}"
+    );
+    assert!(proto.contains("  rpc Preview(Invoice) returns (Invoice);"), "{proto}");
+}
+
+#[test]
+fn extensions_are_declared_in_the_support_file() {
+    let (_, support) = written("support");
+    assert!(
+        support.contains("package acme;\n\nimport \"google/protobuf/descriptor.proto\";\n"),
+        "{support}"
+    );
+    assert!(
+        support.contains("extend google.protobuf.MessageOptions {\n  optional string owner = "),
+        "{support}"
+    );
+    assert!(
+        support.contains("extend google.protobuf.EnumOptions {\n  optional string enum_scope = "),
+        "{support}"
+    );
+    assert!(
+        support.contains("extend google.protobuf.MethodOptions {\n  optional int64 sla_ms = "),
+        "{support}"
+    );
+    // int and float values of one option are declared as double
+    assert!(support.contains("  optional double weight = "), "{support}");
+    assert!(support.contains("  optional bool audited = "), "{support}");
+
+    let numbers: Vec<u32> = support.lines().filter_map(|line| line.strip_suffix(';')?.rsplit_once(" = ")?.1.parse().ok()).collect();
+    assert_eq!(numbers.len(), 6);
+    assert!(numbers.iter().all(|number| (50_000..100_000).contains(number)), "{numbers:?}");
+}
+
+#[test]
+fn options_are_readable_from_the_registry() {
+    let invoice = schemas::message_by_name("custom_options.Invoice").expect("Invoice is registered");
+    assert_eq!(invoice.option("acme.priority"), Some("3"));
+    assert_eq!(invoice.option("acme.owner"), Some("\"billing\""));
+    assert_eq!(invoice.option("acme.missing"), None);
+
+    let schemas::ProtoEntry::Struct { fields } = invoice.content else {
+        panic!("Invoice is a struct");
+    };
+    assert_eq!(fields[1].option("acme.audited"), Some("true"));
+
+    let service = schemas::services().find(|schema| schema.id.name == "Invoices").expect("Invoices is registered");
+    let schemas::ProtoEntry::Service { methods, .. } = service.content else {
+        panic!("Invoices is a service");
+    };
+    assert_eq!(methods[0].option("acme.sla_ms"), Some("250"));
+    assert!(methods[1].options.is_empty());
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use proto_rs::DecodeAbort;
+use proto_rs::DecodeContext;
+use proto_rs::MemoryBudget;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::ProtoError;
+use proto_rs::proto_message;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[proto_message]
+pub struct Leaf {
+    pub id: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[proto_message]
+pub struct Tree {
+    pub name: String,
+    pub payload: Vec<u8>,
+    pub leaves: Vec<Leaf>,
+    pub tags: HashMap<u32, String>,
+}
+
+fn tree(leaves: usize) -> Vec<u8> {
+    Tree {
+        name: "root".into(),
+        payload: vec![7; 100],
+        leaves: vec![Leaf::default(); leaves],
+        tags: HashMap::from([(1, "a".into())]),
+    }
+    .encode_to_vec()
+}
+
+#[test]
+fn decoded_values_are_charged() {
+    let budget = MemoryBudget::new(1 << 20);
+    let decoded = Tree::decode(tree(10).as_slice(), DecodeContext::default().with_budget(&budget)).unwrap();
+    assert_eq!(decoded.leaves.len(), 10);
+    let expected = "root".len() + 100 + 10 * size_of::<Leaf>() + size_of::<(u32, String)>() + "a".len();
+    assert_eq!(budget.used(), expected);
+
+    budget.reset();
+    assert_eq!(budget.used(), 0);
+}
+
+#[test]
+fn repeated_fields_past_the_budget_fail() {
+    // Each element is 2 bytes on the wire and 8 bytes decoded
+    let budget = MemoryBudget::new(4096);
+    let bytes = tree(1000);
+    assert!(bytes.len() < 4096);
+
+    let err = Tree::decode(bytes.as_slice(), DecodeContext::default().with_budget(&budget)).unwrap_err();
+    assert_eq!(err.abort(), Some(DecodeAbort::BudgetExceeded));
+    assert!(err.to_string().ends_with("memory budget of 4096 bytes exceeded"), "{err}");
+
+    let err = ProtoError::Decode(err);
+    assert_eq!(err.code(), tonic::Code::ResourceExhausted);
+    assert!(!err.is_retryable());
+}
+
+#[test]
+fn charges_accumulate_until_reset() {
+    let budget = MemoryBudget::new(150);
+    let ctx = DecodeContext::default().with_budget(&budget);
+    let bytes = tree(0);
+    assert!(Tree::decode(bytes.as_slice(), ctx).is_ok());
+    assert!(Tree::decode(bytes.as_slice(), ctx).is_err());
+
+    budget.reset();
+    assert!(Tree::decode(bytes.as_slice(), ctx).is_ok());
+}
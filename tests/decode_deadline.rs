@@ -0,0 +1,74 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use proto_rs::DecodeAbort;
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::ProtoError;
+use proto_rs::proto_message;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[proto_message]
+pub struct Row {
+    pub id: u64,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[proto_message]
+pub struct Table {
+    pub rows: Vec<Row>,
+}
+
+fn table() -> Vec<u8> {
+    Table {
+        rows: (0..64)
+            .map(|id| Row {
+                id,
+                label: format!("row {id}"),
+            })
+            .collect(),
+    }
+    .encode_to_vec()
+}
+
+#[test]
+fn decodes_before_the_deadline() {
+    let ctx = DecodeContext::default().with_timeout(Duration::from_mins(1));
+    assert!(ctx.deadline().is_some());
+    assert_eq!(Table::decode(table().as_slice(), ctx).unwrap().rows.len(), 64);
+}
+
+#[test]
+fn passed_deadline_aborts_the_decode() {
+    let ctx = DecodeContext::default().with_deadline(Instant::now());
+    let err = Table::decode(table().as_slice(), ctx).unwrap_err();
+    assert_eq!(err.abort(), Some(DecodeAbort::DeadlineExceeded));
+    assert!(err.to_string().ends_with("decode deadline exceeded"), "{err}");
+
+    let err = ProtoError::Decode(err);
+    assert!(err.is_retryable());
+    assert_eq!(err.code(), tonic::Code::DeadlineExceeded);
+}
+
+#[test]
+fn cancel_token_is_checked_at_message_boundaries() {
+    let stop = AtomicBool::new(false);
+    let ctx = DecodeContext::default().with_cancel(&stop);
+    assert!(Table::decode(table().as_slice(), ctx).is_ok());
+
+    stop.store(true, Ordering::Relaxed);
+    let err = Table::decode(table().as_slice(), ctx).unwrap_err();
+    assert_eq!(err.abort(), Some(DecodeAbort::Cancelled));
+    assert_eq!(ProtoError::Decode(err).code(), tonic::Code::Cancelled);
+}
+
+#[test]
+fn malformed_data_is_not_an_abort() {
+    let err = Table::decode([0x0a, 0x05, 0x08].as_slice(), DecodeContext::default()).unwrap_err();
+    assert_eq!(err.abort(), None);
+    assert!(!ProtoError::Decode(err).is_retryable());
+}
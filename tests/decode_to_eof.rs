@@ -0,0 +1,65 @@
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Listener {
+    pub host: String,
+    pub port: u32,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ServerConfig {
+    pub name: String,
+    pub listeners: Vec<Listener>,
+    pub workers: u32,
+}
+
+fn config() -> ServerConfig {
+    ServerConfig {
+        name: "edge".into(),
+        listeners: vec![
+            Listener {
+                host: "0.0.0.0".into(),
+                port: 443,
+            },
+            Listener {
+                host: "::".into(),
+                port: 8443,
+            },
+        ],
+        workers: 4,
+    }
+}
+
+#[test]
+fn whole_buffer_decodes_as_one_message() {
+    let bytes = config().encode_to_vec();
+    let decoded = ServerConfig::decode_to_eof(&bytes, DecodeContext::default()).expect("config decodes");
+    assert_eq!(decoded, config());
+}
+
+#[test]
+fn trailing_padding_is_reported_with_its_offset() {
+    let mut bytes = config().encode_to_vec();
+    let end = bytes.len();
+    bytes.extend([0, 0, 0]);
+    let err = ServerConfig::decode_to_eof(&bytes, DecodeContext::default()).unwrap_err();
+    assert_eq!(err.offset(), Some(end));
+    assert_eq!(
+        err.to_string(),
+        format!("failed to decode Protobuf message at byte {end}: trailing data that does not start a field")
+    );
+}
+
+#[test]
+fn truncated_field_reports_where_it_starts() {
+    let bytes = config().encode_to_vec();
+    // name is 6 bytes, so the first listener starts at byte 6
+    let err = ServerConfig::decode_to_eof(&bytes[..10], DecodeContext::default()).unwrap_err();
+    assert_eq!(err.offset(), Some(6));
+    assert!(ServerConfig::decode(&bytes[..10], DecodeContext::default()).unwrap_err().offset().is_none());
+}
@@ -0,0 +1,270 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::encoding::DecodeContext;
+use proto_rs::proto_message;
+
+#[cfg(feature = "build-schemas")]
+mod common;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct LastWinsMap {
+    #[proto(tag = 1)]
+    pub entries: BTreeMap<u32, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct StrictMaps {
+    #[proto(tag = 1, on_duplicate = "error")]
+    pub tree: BTreeMap<u32, String>,
+    #[proto(tag = 2, on_duplicate = "error")]
+    pub hash: HashMap<u32, u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct StrictSets {
+    #[proto(tag = 1, on_duplicate = "error")]
+    pub tree: BTreeSet<u32>,
+    #[proto(tag = 2, on_duplicate = "error")]
+    pub hash: HashSet<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct CollectingMaps {
+    #[proto(tag = 1, on_duplicate = "collect")]
+    pub tree: BTreeMap<u32, Vec<String>>,
+    #[proto(tag = 2, on_duplicate = "collect")]
+    pub hash: HashMap<u32, Vec<u64>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct PlainMaps {
+    #[proto(tag = 1)]
+    pub tree: BTreeMap<u32, String>,
+    #[proto(tag = 2)]
+    pub hash: HashMap<u32, u64>,
+}
+
+fn concat(parts: &[Vec<u8>]) -> Vec<u8> {
+    parts.iter().flatten().copied().collect()
+}
+
+#[test]
+fn default_policy_keeps_last_value() {
+    let first = LastWinsMap {
+        entries: BTreeMap::from([(1, "first".to_string())]),
+    };
+    let second = LastWinsMap {
+        entries: BTreeMap::from([(1, "second".to_string())]),
+    };
+    let bytes = concat(&[first.encode_to_vec(), second.encode_to_vec()]);
+
+    let decoded = <LastWinsMap as ProtoDecode>::decode(&bytes[..], DecodeContext::default()).expect("decode");
+    assert_eq!(decoded, second);
+}
+
+#[test]
+fn error_policy_rejects_duplicate_map_keys() {
+    let first = StrictMaps {
+        tree: BTreeMap::from([(1, "first".to_string())]),
+        hash: HashMap::from([(7, 70)]),
+    };
+    let decoded = <StrictMaps as ProtoDecode>::decode(&first.encode_to_vec()[..], DecodeContext::default()).expect("decode");
+    assert_eq!(decoded, first);
+
+    let second = StrictMaps {
+        tree: BTreeMap::from([(1, "second".to_string())]),
+        hash: HashMap::new(),
+    };
+    let bytes = concat(&[first.encode_to_vec(), second.encode_to_vec()]);
+    let err = <StrictMaps as ProtoDecode>::decode(&bytes[..], DecodeContext::default()).expect_err("duplicate tree key");
+    assert!(err.to_string().contains("duplicate map key"));
+
+    let third = StrictMaps {
+        tree: BTreeMap::new(),
+        hash: HashMap::from([(7, 71)]),
+    };
+    let bytes = concat(&[first.encode_to_vec(), third.encode_to_vec()]);
+    assert!(<StrictMaps as ProtoDecode>::decode(&bytes[..], DecodeContext::default()).is_err());
+}
+
+#[test]
+fn error_policy_rejects_duplicate_set_elements() {
+    let first = StrictSets {
+        tree: BTreeSet::from([1, 2, 3]),
+        hash: HashSet::from(["a".to_string()]),
+    };
+    let decoded = <StrictSets as ProtoDecode>::decode(&first.encode_to_vec()[..], DecodeContext::default()).expect("decode");
+    assert_eq!(decoded, first);
+
+    let packed_overlap = StrictSets {
+        tree: BTreeSet::from([3, 4]),
+        hash: HashSet::new(),
+    };
+    let bytes = concat(&[first.encode_to_vec(), packed_overlap.encode_to_vec()]);
+    let err = <StrictSets as ProtoDecode>::decode(&bytes[..], DecodeContext::default()).expect_err("duplicate set element");
+    assert!(err.to_string().contains("duplicate set element"));
+
+    let string_overlap = StrictSets {
+        tree: BTreeSet::new(),
+        hash: HashSet::from(["a".to_string()]),
+    };
+    let bytes = concat(&[first.encode_to_vec(), string_overlap.encode_to_vec()]);
+    assert!(<StrictSets as ProtoDecode>::decode(&bytes[..], DecodeContext::default()).is_err());
+}
+
+#[test]
+fn collect_policy_gathers_values_per_key() {
+    let first = PlainMaps {
+        tree: BTreeMap::from([(1, "a".to_string()), (2, "b".to_string())]),
+        hash: HashMap::from([(9, 90)]),
+    };
+    let second = PlainMaps {
+        tree: BTreeMap::from([(1, "c".to_string())]),
+        hash: HashMap::from([(9, 91)]),
+    };
+    let bytes = concat(&[first.encode_to_vec(), second.encode_to_vec()]);
+
+    let decoded = <CollectingMaps as ProtoDecode>::decode(&bytes[..], DecodeContext::default()).expect("decode");
+    assert_eq!(
+        decoded.tree,
+        BTreeMap::from([(1, vec!["a".to_string(), "c".to_string()]), (2, vec!["b".to_string()])])
+    );
+    assert_eq!(decoded.hash, HashMap::from([(9, vec![90, 91])]));
+}
+
+#[test]
+fn collect_policy_encodes_one_entry_per_value() {
+    let message = CollectingMaps {
+        tree: BTreeMap::from([(1, vec!["a".to_string(), "c".to_string()]), (2, Vec::new())]),
+        hash: HashMap::from([(9, vec![90, 91])]),
+    };
+    let bytes = message.encode_to_vec();
+
+    let roundtrip = <CollectingMaps as ProtoDecode>::decode(&bytes[..], DecodeContext::default()).expect("decode");
+    assert_eq!(roundtrip.tree, BTreeMap::from([(1, vec!["a".to_string(), "c".to_string()])]));
+    assert_eq!(roundtrip.hash, message.hash);
+
+    // A plain map reader sees ordinary entries and keeps the last value for each key.
+    let plain = <PlainMaps as ProtoDecode>::decode(&bytes[..], DecodeContext::default()).expect("decode as plain map");
+    assert_eq!(plain.tree, BTreeMap::from([(1, "c".to_string())]));
+    assert_eq!(plain.hash, HashMap::from([(9, 91)]));
+}
+
+/// Sets compare labels by key only, so a repeated key with another value is a duplicate
+#[derive(Debug, Clone, Default)]
+#[proto_message(proto_path = "protos/tests/label_sets.proto")]
+pub struct Label {
+    pub key: String,
+    pub value: u32,
+}
+
+impl PartialEq for Label {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for Label {}
+
+impl PartialOrd for Label {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Label {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl std::hash::Hash for Label {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[proto_message(proto_path = "protos/tests/label_sets.proto")]
+pub struct LabelSets {
+    #[proto(tag = 1)]
+    pub tree: BTreeSet<Label>,
+    #[proto(tag = 2)]
+    pub hash: HashSet<Label>,
+}
+
+#[derive(Debug, Clone, Default)]
+#[proto_message(proto_path = "protos/tests/label_sets.proto")]
+pub struct StrictLabelSets {
+    #[proto(tag = 1, on_duplicate = "error")]
+    pub tree: BTreeSet<Label>,
+    #[proto(tag = 2, on_duplicate = "error")]
+    pub hash: HashSet<Label>,
+}
+
+fn label(key: &str, value: u32) -> Label {
+    Label { key: key.into(), value }
+}
+
+fn values<'a>(labels: impl IntoIterator<Item = &'a Label>) -> Vec<(String, u32)> {
+    let mut values: Vec<_> = labels.into_iter().map(|label| (label.key.clone(), label.value)).collect();
+    values.sort();
+    values
+}
+
+#[test]
+fn message_sets_keep_the_last_equal_element() {
+    let first = LabelSets {
+        tree: BTreeSet::from([label("a", 1), label("b", 2)]),
+        hash: HashSet::from([label("c", 3)]),
+    };
+    let decoded = <LabelSets as ProtoDecode>::decode(&first.encode_to_vec()[..], DecodeContext::default()).expect("decode");
+    assert_eq!(values(&decoded.tree), values(&first.tree));
+    assert_eq!(values(&decoded.hash), values(&first.hash));
+
+    let second = LabelSets {
+        tree: BTreeSet::from([label("a", 10)]),
+        hash: HashSet::from([label("c", 30)]),
+    };
+    let bytes = concat(&[first.encode_to_vec(), second.encode_to_vec()]);
+    let decoded = <LabelSets as ProtoDecode>::decode(&bytes[..], DecodeContext::default()).expect("decode");
+    assert_eq!(values(&decoded.tree), [("a".to_string(), 10), ("b".to_string(), 2)]);
+    assert_eq!(values(&decoded.hash), [("c".to_string(), 30)]);
+}
+
+#[test]
+fn error_policy_rejects_duplicate_messages() {
+    let first = LabelSets {
+        tree: BTreeSet::from([label("a", 1)]),
+        hash: HashSet::from([label("c", 3)]),
+    };
+    let decoded = <StrictLabelSets as ProtoDecode>::decode(&first.encode_to_vec()[..], DecodeContext::default()).expect("decode");
+    assert_eq!(values(&decoded.tree), values(&first.tree));
+
+    let second = LabelSets {
+        tree: BTreeSet::new(),
+        hash: HashSet::from([label("c", 30)]),
+    };
+    let bytes = concat(&[first.encode_to_vec(), second.encode_to_vec()]);
+    let err = <StrictLabelSets as ProtoDecode>::decode(&bytes[..], DecodeContext::default()).expect_err("duplicate label");
+    assert!(err.to_string().contains("duplicate set element"));
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn message_sets_are_repeated_in_the_schema() {
+    let proto = common::emitted_proto("label_sets", "protos/tests/label_sets.proto");
+
+    assert!(proto.contains("  repeated Label tree = 1;\n"), "{proto}");
+    assert!(proto.contains("  repeated Label hash = 2;\n"), "{proto}");
+}
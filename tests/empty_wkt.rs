@@ -0,0 +1,64 @@
+#![cfg(feature = "build-schemas")]
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use proto_rs::schemas::EMPTY_PROTO_IDENT;
+use proto_rs::schemas::ProtoIdentifiable;
+use proto_rs::schemas::RustClientCtx;
+use tonic::Response;
+
+mod common;
+
+#[proto_message(proto_path = "protos/tests/empty_wkt.proto", empty = "wkt")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ack;
+
+#[proto_message(proto_path = "protos/tests/empty_wkt.proto")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Pong;
+
+#[proto_rpc(
+    rpc_package = "empty_wkt",
+    proto_path = "protos/tests/empty_wkt.proto",
+    rpc_server = true,
+    rpc_client = true
+)]
+pub trait PingService {
+    async fn ping(&self, request: Request<()>) -> Result<Response<()>, Status>;
+    async fn ack(&self, request: Request<Ack>) -> Result<Response<Pong>, Status>;
+}
+
+#[test]
+fn unit_types_share_the_empty_ident() {
+    assert_eq!(<() as ProtoIdentifiable>::PROTO_IDENT, EMPTY_PROTO_IDENT);
+    assert_eq!(<Ack as ProtoIdentifiable>::PROTO_IDENT, EMPTY_PROTO_IDENT);
+    assert_ne!(<Pong as ProtoIdentifiable>::PROTO_IDENT, EMPTY_PROTO_IDENT);
+}
+
+#[test]
+fn emitted_proto_uses_google_protobuf_empty() {
+    let proto = common::emitted_proto("empty_wkt", "protos/tests/empty_wkt.proto");
+
+    assert!(proto.contains("import \"google/protobuf/empty.proto\";"), "{proto}");
+    assert!(
+        proto.contains("rpc Ping(google.protobuf.Empty) returns (google.protobuf.Empty);"),
+        "{proto}"
+    );
+    assert!(proto.contains("rpc Ack(google.protobuf.Empty) returns (Pong);"), "{proto}");
+    assert!(proto.contains("message Pong {}"), "{proto}");
+    assert!(!proto.contains("message Ack"), "{proto}");
+}
+
+#[test]
+fn well_known_imports_can_be_remapped() {
+    let ctx = RustClientCtx::disabled().remap_import("google/protobuf/empty.proto", "third_party/google/protobuf/empty.proto");
+    let proto = common::write_schemas("empty_wkt_remap", &ctx).read("protos/tests/empty_wkt.proto");
+
+    assert!(proto.contains("import \"third_party/google/protobuf/empty.proto\";"), "{proto}");
+    assert!(!proto.contains("import \"google/protobuf/empty.proto\";"), "{proto}");
+    assert!(
+        proto.contains("rpc Ping(google.protobuf.Empty) returns (google.protobuf.Empty);"),
+        "{proto}"
+    );
+}
@@ -0,0 +1,54 @@
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+#[proto_message(explicit_tags)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Stage {
+    #[default]
+    Draft = 0,
+    Live = 3,
+}
+
+#[proto_message(explicit_tags)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    #[proto(tag = 4)]
+    Import(String),
+    #[proto(tag = 9)]
+    Manual {
+        #[proto(tag = 2)]
+        author: String,
+    },
+}
+
+#[proto_message(explicit_tags)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Article {
+    #[proto(tag = 5)]
+    pub title: String,
+    #[proto(skip)]
+    pub cached_len: usize,
+    #[proto(tag = 1)]
+    pub stage: Stage,
+    #[proto(tag = 3)]
+    pub source: Source,
+}
+
+#[test]
+fn explicit_tags_are_used_on_the_wire() {
+    let article = Article {
+        title: "t".into(),
+        cached_len: 0,
+        stage: Stage::Live,
+        source: Source::Manual { author: "a".into() },
+    };
+    let bytes = article.encode_to_vec();
+    // stage (1, varint 3), source (3, Manual (9, author (2, "a"))), title (5, "t")
+    assert!(bytes.windows(2).any(|pair| pair == [0x08, 0x03]));
+    assert!(bytes.windows(6).any(|run| run == [0x1A, 0x05, 0x4A, 0x03, 0x12, 0x01]));
+    assert!(bytes.windows(3).any(|run| run == [0x2A, 0x01, b't']));
+    let decoded = <Article as ProtoDecode>::decode(bytes.as_slice(), DecodeContext::default()).expect("article decodes");
+    assert_eq!(decoded, article);
+}
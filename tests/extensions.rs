@@ -0,0 +1,134 @@
+use proto_rs::DecodeContext;
+use proto_rs::Extendable;
+use proto_rs::Extension;
+use proto_rs::ExtensionSet;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::extensions;
+use proto_rs::proto_message;
+
+#[cfg(feature = "build-schemas")]
+mod common;
+
+#[proto_message(proto_path = "protos/tests/extensions.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Order {
+    pub id: u64,
+    pub note: Option<String>,
+    #[proto(extensions = "100..200")]
+    pub extensions: ExtensionSet,
+}
+
+#[proto_message(proto_path = "protos/tests/extensions.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Shipping {
+    pub carrier: String,
+    pub weight: u32,
+}
+
+#[proto_message(proto_path = "protos/tests/extensions.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Batch {
+    pub order: Order,
+}
+
+// What a newer writer that knows field 150 sends.
+#[proto_message(proto_path = "protos/tests/extensions.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OrderV2 {
+    pub id: u64,
+    #[proto(tag = 150)]
+    pub express: bool,
+}
+
+static PRIORITY: Extension<Order, u32, 100> = Extension::new("shipping.priority");
+static SHIPPING: Extension<Order, Shipping, 101> = Extension::new("shipping.details");
+
+fn roundtrip<T: ProtoEncode + ProtoDecode + proto_rs::ProtoExt>(value: &T) -> T {
+    T::decode(value.encode_to_vec().as_slice(), DecodeContext::default()).expect("decodes")
+}
+
+#[test]
+fn extension_fields_roundtrip() {
+    let mut order = Order { id: 7, ..Order::default() };
+    assert_eq!(PRIORITY.get(&order).unwrap(), None);
+
+    PRIORITY.set(&mut order, &0);
+    SHIPPING.set(
+        &mut order,
+        &Shipping {
+            carrier: "ups".into(),
+            weight: 12,
+        },
+    );
+    let decoded = roundtrip(&order);
+    assert_eq!(decoded, order);
+    // default values are kept, so a set field stays set
+    assert!(PRIORITY.has(&decoded));
+    assert_eq!(PRIORITY.get(&decoded).unwrap(), Some(0));
+    assert_eq!(SHIPPING.get(&decoded).unwrap().map(|shipping| shipping.weight), Some(12));
+
+    let mut decoded = decoded;
+    PRIORITY.clear(&mut decoded);
+    assert!(!PRIORITY.has(&decoded));
+    assert_eq!(decoded.extensions().tags().collect::<Vec<_>>(), [101]);
+}
+
+#[test]
+fn messages_holding_only_extensions_are_encoded() {
+    let mut batch = Batch::default();
+    PRIORITY.set(&mut batch.order, &3);
+    let decoded = roundtrip(&batch);
+    assert_eq!(PRIORITY.get(&decoded.order).unwrap(), Some(3));
+    assert_eq!(Order::EXTENSION_RANGES, &[(100, 199)]);
+}
+
+#[test]
+fn unknown_extensions_are_kept_as_encoded() {
+    let newer = OrderV2 { id: 9, express: true };
+    let bytes = newer.encode_to_vec();
+    let order = Order::decode(bytes.as_slice(), DecodeContext::default()).unwrap();
+    assert_eq!(order.id, 9);
+    assert_eq!(order.extensions.tags().collect::<Vec<_>>(), [150]);
+    assert_eq!(order.encode_to_vec(), bytes);
+}
+
+#[test]
+fn registry_rejects_two_fields_with_one_number() {
+    static URGENCY: Extension<Order, bool, 100> = Extension::new("other.urgency");
+
+    extensions::register(&PRIORITY).unwrap();
+    extensions::register(&PRIORITY).unwrap();
+    extensions::register(&SHIPPING).unwrap();
+    let conflict = extensions::register(&URGENCY).unwrap_err();
+    assert_eq!(conflict.registered.name, "shipping.priority");
+    assert_eq!(conflict.rejected.name, "other.urgency");
+
+    let info = extensions::lookup::<Order>(101).expect("registered");
+    assert_eq!(info.name, "shipping.details");
+    assert!(info.value_type.ends_with("Shipping"));
+    let names: Vec<_> = extensions::registered::<Order>().iter().map(|info| info.name).collect();
+    assert_eq!(names, ["shipping.priority", "shipping.details"]);
+    assert!(extensions::registered::<Batch>().is_empty());
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn schema_declares_extension_ranges() {
+    let proto = common::emitted_proto("extensions", "protos/tests/extensions.proto");
+
+    assert!(
+        proto.contains("edition = \"2023\";\npackage extensions;\noption features.field_presence = IMPLICIT;\n"),
+        "{proto}"
+    );
+    assert!(
+        proto.contains(
+            "message Order {\n  uint64 id = 1;\n  string note = 2 [features.field_presence = EXPLICIT];\n  extensions 100 to 199;\n}"
+        ),
+        "{proto}"
+    );
+    assert!(
+        proto.contains("message Shipping {\n  string carrier = 1;\n  uint32 weight = 2;\n}"),
+        "{proto}"
+    );
+}
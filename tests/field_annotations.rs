@@ -0,0 +1,73 @@
+#![cfg(feature = "build-schemas")]
+
+use proto_rs::proto_message;
+use proto_rs::schemas;
+use proto_rs::schemas::ProtoEntry;
+use proto_rs::schemas::ProtoIdentifiable;
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Patient {
+    pub id: u64,
+    #[proto(label = "pii", annotate(owner = "records", retention = "30d"))]
+    pub name: String,
+    #[proto(sensitive, label = "pii", label = "phi")]
+    pub diagnosis: String,
+    #[proto(annotate(doc = "free-form sensitive notes"))]
+    pub notes: String,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Visit {
+    Walkin,
+    Booked {
+        #[proto(label = "pii")]
+        phone: String,
+        slot: u32,
+    },
+}
+
+fn field(schema: &schemas::ProtoSchema, name: &str) -> &'static schemas::Field {
+    let ProtoEntry::Struct { fields } = schema.content else {
+        panic!("{} is not a struct", schema.id.name);
+    };
+    fields.iter().copied().find(|field| field.name == Some(name)).expect("field is registered")
+}
+
+#[test]
+fn labels_and_annotations_are_on_the_schema() {
+    let schema = schemas::schema_for(&Patient::PROTO_IDENT).expect("Patient is registered");
+
+    let name = field(schema, "name");
+    assert!(name.has_label("pii"));
+    assert!(!name.is_sensitive());
+    assert_eq!(name.annotation("owner"), Some("records"));
+    assert_eq!(name.annotation("retention"), Some("30d"));
+    assert_eq!(name.annotation("missing"), None);
+
+    let diagnosis = field(schema, "diagnosis");
+    assert_eq!(diagnosis.labels, ["sensitive", "pii", "phi"]);
+    assert!(diagnosis.is_sensitive());
+
+    let notes = field(schema, "notes");
+    assert!(notes.labels.is_empty());
+    assert!(!notes.is_sensitive(), "annotation values are not markers");
+
+    assert!(field(schema, "id").annotations.is_empty());
+}
+
+#[test]
+fn registry_finds_labeled_fields_across_messages() {
+    let mut pii: Vec<(&str, &str)> = schemas::labeled_fields("pii")
+        .filter(|(schema, _)| schema.id == Patient::PROTO_IDENT || schema.id == Visit::PROTO_IDENT)
+        .map(|(schema, field)| (schema.id.name, field.name.unwrap_or_default()))
+        .collect();
+    pii.sort_unstable();
+    assert_eq!(pii, [("Patient", "diagnosis"), ("Patient", "name"), ("Visit", "phone")]);
+
+    assert_eq!(
+        schemas::labeled_fields("phi").filter(|(schema, _)| schema.id == Patient::PROTO_IDENT).count(),
+        1
+    );
+}
@@ -0,0 +1,94 @@
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Quote {
+    pub venue: String,
+    pub bid: u64,
+    pub ask: u64,
+    pub seq: u64,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HintedQuote {
+    #[proto(cold)]
+    pub venue: String,
+    pub bid: u64,
+    pub ask: u64,
+    #[proto(hot)]
+    pub seq: u64,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Update {
+    Quote {
+        #[proto(cold)]
+        venue: String,
+        #[proto(hot)]
+        seq: u64,
+    },
+    Halt(u64),
+}
+
+fn decode<T: ProtoDecode>(bytes: &[u8]) -> T {
+    T::decode(bytes, DecodeContext::default()).expect("message decodes")
+}
+
+#[test]
+fn hot_fields_lead_and_cold_fields_trail_the_message() {
+    let hinted = HintedQuote {
+        venue: "XNAS".to_string(),
+        bid: 1,
+        ask: 2,
+        seq: 3,
+    };
+    // seq (tag 4), bid (2), ask (3), venue (1)
+    assert_eq!(hinted.encode_to_vec(), [0x20, 3, 0x10, 1, 0x18, 2, 0x0a, 4, b'X', b'N', b'A', b'S']);
+
+    let plain = Quote {
+        venue: "XNAS".to_string(),
+        bid: 1,
+        ask: 2,
+        seq: 3,
+    };
+    assert_eq!(plain.encode_to_vec()[..2], [0x0a, 4]);
+}
+
+#[test]
+fn hints_do_not_change_the_schema() {
+    let hinted = HintedQuote {
+        venue: "XNAS".to_string(),
+        bid: 10,
+        ask: 11,
+        seq: 12,
+    };
+    let plain: Quote = decode(&hinted.encode_to_vec());
+    assert_eq!(
+        plain,
+        Quote {
+            venue: "XNAS".to_string(),
+            bid: 10,
+            ask: 11,
+            seq: 12,
+        }
+    );
+    assert_eq!(decode::<HintedQuote>(&plain.encode_to_vec()), hinted);
+}
+
+#[test]
+fn variant_fields_follow_their_hints() {
+    let update = Update::Quote {
+        venue: "XNAS".to_string(),
+        seq: 5,
+    };
+    let bytes = update.encode_to_vec();
+    // variant tag 1, then seq (tag 2) before venue (tag 1)
+    assert_eq!(bytes[2..4], [0x10, 5]);
+    assert_eq!(decode::<Update>(&bytes), update);
+    assert_eq!(decode::<Update>(&Update::Halt(9).encode_to_vec()), Update::Halt(9));
+}
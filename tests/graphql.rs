@@ -0,0 +1,101 @@
+#![cfg(feature = "graphql")]
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::async_graphql::EmptySubscription;
+use proto_rs::async_graphql::Object;
+use proto_rs::async_graphql::Schema;
+use proto_rs::proto_message;
+use serde_json::json;
+
+#[proto_message(graphql)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Status {
+    #[default]
+    Pending,
+    Shipped,
+}
+
+#[proto_message(graphql)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LineItem {
+    pub sku: String,
+    pub quantity: u32,
+}
+
+#[proto_message(graphql)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Order {
+    pub order_id: u64,
+    pub status: Status,
+    pub items: Vec<LineItem>,
+    #[proto(skip)]
+    pub cached_total: u32,
+}
+
+struct Query;
+
+#[Object(crate = "proto_rs::async_graphql")]
+impl Query {
+    async fn order(&self) -> Order {
+        Order {
+            order_id: 7,
+            status: Status::Shipped,
+            items: vec![LineItem {
+                sku: "tea".into(),
+                quantity: 2,
+            }],
+            cached_total: 9,
+        }
+    }
+}
+
+struct Mutation;
+
+#[Object(crate = "proto_rs::async_graphql")]
+impl Mutation {
+    // Answers with the protobuf encoding the input decodes to, so the test sees the same message.
+    async fn place(&self, order: Order) -> Vec<u8> {
+        order.encode_to_vec()
+    }
+}
+
+fn schema() -> Schema<Query, Mutation, EmptySubscription> {
+    Schema::new(Query, Mutation, EmptySubscription)
+}
+
+#[tokio::test]
+async fn messages_are_graphql_objects() {
+    let response = schema().execute("{ order { orderId status items { sku quantity } } }").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        json!({ "order": { "orderId": 7, "status": "SHIPPED", "items": [{ "sku": "tea", "quantity": 2 }] } })
+    );
+
+    let sdl = schema().sdl();
+    assert!(sdl.contains("input OrderInput"), "{sdl}");
+    assert!(!sdl.contains("cachedTotal"), "{sdl}");
+}
+
+#[tokio::test]
+async fn messages_are_graphql_inputs() {
+    let query = r#"mutation { place(order: { orderId: 3, status: PENDING, items: [{ sku: "jam", quantity: 1 }] }) }"#;
+    let response = schema().execute(query).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let bytes: Vec<u8> = serde_json::from_value(response.data.into_json().unwrap()["place"].clone()).unwrap();
+    let order = Order::decode(bytes.as_slice(), DecodeContext::default()).unwrap();
+    assert_eq!(
+        order,
+        Order {
+            order_id: 3,
+            status: Status::Pending,
+            items: vec![LineItem {
+                sku: "jam".into(),
+                quantity: 1
+            }],
+            cached_total: 0,
+        }
+    );
+}
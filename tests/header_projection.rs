@@ -0,0 +1,72 @@
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Kind {
+    #[default]
+    Trade,
+    Quote,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Level {
+    pub price: u64,
+    pub size: u64,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Envelope {
+    #[proto(header)]
+    pub id: u64,
+    pub levels: Vec<Level>,
+    pub payload: Vec<u8>,
+    #[proto(header)]
+    pub kind: Kind,
+    #[proto(header, tag = 20)]
+    pub route: Option<String>,
+}
+
+fn envelope() -> Envelope {
+    Envelope {
+        id: 42,
+        levels: (0..1000).map(|price| Level { price, size: 1 }).collect(),
+        payload: vec![7; 4096],
+        kind: Kind::Quote,
+        route: Some("eu".into()),
+    }
+}
+
+#[test]
+fn header_holds_only_the_marked_fields() {
+    let bytes = envelope().encode_to_vec();
+    let header = Envelope::decode_header(bytes.as_slice(), DecodeContext::default()).unwrap();
+    assert_eq!(
+        header,
+        EnvelopeHeader {
+            id: 42,
+            kind: Kind::Quote,
+            route: Some("eu".into()),
+        }
+    );
+    assert_eq!(EnvelopeHeader::decode(bytes.as_slice(), DecodeContext::default()).unwrap(), header);
+}
+
+#[test]
+fn skipped_fields_are_not_validated() {
+    // A body field whose payload is not a valid nested message still yields a header.
+    let mut bytes = Envelope {
+        id: 5,
+        ..Envelope::default()
+    }
+    .encode_to_vec();
+    bytes.extend_from_slice(&[0x12, 0x02, 0xff, 0xff]);
+    assert!(Envelope::decode(bytes.as_slice(), DecodeContext::default()).is_err());
+    let header = Envelope::decode_header(bytes.as_slice(), DecodeContext::default()).unwrap();
+    assert_eq!(header.id, 5);
+    assert_eq!(header.route, None);
+}
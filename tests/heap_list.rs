@@ -0,0 +1,120 @@
+use std::collections::BinaryHeap;
+use std::collections::LinkedList;
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoArchive;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+#[cfg(feature = "build-schemas")]
+mod common;
+
+#[proto_message(proto_path = "protos/tests/heap_list.proto")]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Job {
+    pub priority: u32,
+    pub name: String,
+}
+
+#[proto_message(proto_path = "protos/tests/heap_list.proto")]
+#[derive(Debug, Clone, Default)]
+pub struct Queue {
+    pub jobs: BinaryHeap<Job>,
+    pub history: LinkedList<String>,
+    pub scores: LinkedList<i64>,
+    pub ids: BinaryHeap<u32>,
+}
+
+/// The same message with every collection as a `Vec`
+#[proto_message(proto_path = "protos/tests/heap_list.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct QueueList {
+    pub jobs: Vec<Job>,
+    pub history: Vec<String>,
+    pub scores: Vec<i64>,
+    pub ids: Vec<u32>,
+}
+
+fn decode<T: ProtoDecode>(bytes: &[u8]) -> Result<T, proto_rs::DecodeError> {
+    <T as ProtoDecode>::decode(bytes, DecodeContext::default())
+}
+
+fn queue() -> Queue {
+    Queue {
+        jobs: BinaryHeap::from([
+            Job {
+                priority: 1,
+                name: "low".into(),
+            },
+            Job {
+                priority: 9,
+                name: "high".into(),
+            },
+        ]),
+        history: LinkedList::from(["a".to_string(), "b".to_string()]),
+        scores: LinkedList::from([-1, 0, 300]),
+        ids: BinaryHeap::from([3, 1, 2]),
+    }
+}
+
+#[test]
+fn heaps_and_lists_round_trip() {
+    let queue = queue();
+    let bytes = queue.encode_to_vec();
+    // `history` repeats strings, which have no per-element bound
+    assert_eq!(ProtoArchive::size_hint::<0>(&queue), None);
+    assert_eq!(ProtoArchive::size_hint::<3>(&queue.scores), Some(1 + 1 + 3 * 10));
+
+    let mut decoded = decode::<Queue>(&bytes).expect("queue decodes");
+    assert_eq!(decoded.history, queue.history);
+    assert_eq!(decoded.scores, queue.scores);
+    assert_eq!(decoded.ids.clone().into_sorted_vec(), [1, 2, 3]);
+    assert_eq!(decoded.jobs.pop().map(|job| job.name), Some("high".into()));
+    assert_eq!(decoded.jobs.pop().map(|job| job.name), Some("low".into()));
+}
+
+#[test]
+fn heaps_and_lists_share_the_vec_wire_format() {
+    let list = QueueList {
+        jobs: vec![Job {
+            priority: 4,
+            name: "only".into(),
+        }],
+        history: vec!["x".into()],
+        scores: vec![5, -6],
+        ids: vec![7],
+    };
+    let bytes = list.encode_to_vec();
+    // numeric elements are packed exactly like the Vec fields
+    assert_eq!(queue_with(&list).encode_to_vec(), bytes);
+
+    let queue = decode::<Queue>(&bytes).expect("queue decodes");
+    assert_eq!(queue.scores, LinkedList::from([5, -6]));
+    assert_eq!(queue.ids.into_vec(), [7]);
+
+    let unpacked = [0x18, 0x05, 0x18, 0x0B, 0x20, 0x07];
+    let queue = decode::<Queue>(&unpacked).expect("unpacked run decodes");
+    assert_eq!(queue.scores, LinkedList::from([5, 11]));
+    assert_eq!(queue.ids.into_vec(), [7]);
+}
+
+fn queue_with(list: &QueueList) -> Queue {
+    Queue {
+        jobs: list.jobs.iter().cloned().collect(),
+        history: list.history.iter().cloned().collect(),
+        scores: list.scores.iter().copied().collect(),
+        ids: list.ids.iter().copied().collect(),
+    }
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn schema_lists_heaps_and_lists_as_repeated() {
+    let proto = common::emitted_proto("heap_list", "protos/tests/heap_list.proto");
+
+    assert!(proto.contains("  repeated Job jobs = 1;\n"), "{proto}");
+    assert!(proto.contains("  repeated string history = 2;\n"), "{proto}");
+    assert!(proto.contains("  repeated int64 scores = 3;\n"), "{proto}");
+    assert!(proto.contains("  repeated uint32 ids = 4;\n"), "{proto}");
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+
+mod shop {
+    proto_rs::import_proto!("tests/import/shop.proto");
+}
+
+use shop::Customer;
+use shop::Line;
+use shop::Order;
+use shop::OrderStatus;
+use shop::Payment;
+
+fn order() -> Order {
+    Order {
+        id: 150,
+        status: OrderStatus::Shipped,
+        lines: vec![Line {
+            sku: "a".into(),
+            quantity: 2,
+        }],
+        note: Some(String::new()),
+        totals: HashMap::from([("x".into(), -1)]),
+        customer: Some(Customer {
+            email: "e".into(),
+            tiers: vec![1, 2],
+        }),
+        signature: vec![0xff],
+        discount: 0.5,
+        type_: "t".into(),
+    }
+}
+
+#[test]
+fn imported_types_use_the_file_tags() {
+    let expected = [
+        vec![0x08, 0x96, 0x01],                                                         // id = 1
+        vec![0x10, 0x03],                                                               // status = 2, ORDER_STATUS_SHIPPED
+        vec![0x22, 0x05, 0x0a, 0x01, b'a', 0x10, 0x02],                                 // lines = 4
+        vec![0x32, 0x00],                                                               // note = 6, present but empty
+        [vec![0x3a, 0x0e, 0x0a, 0x01, b'x', 0x10], vec![0xff; 9], vec![0x01]].concat(), // totals = 7
+        vec![0x42, 0x07, 0x0a, 0x01, b'e', 0x12, 0x02, 0x01, 0x02],                     // customer = 8
+        vec![0x4a, 0x01, 0xff],                                                         // signature = 9
+        [vec![0x51], 0.5f64.to_le_bytes().to_vec()].concat(),                           // discount = 10
+        vec![0x5a, 0x01, b't'],                                                         // type = 11
+    ]
+    .concat();
+    assert_eq!(order().encode_to_vec(), expected);
+    assert_eq!(Order::decode(expected.as_slice(), DecodeContext::default()).unwrap(), order());
+}
+
+#[test]
+fn enums_keep_their_numbers() {
+    assert_eq!(OrderStatus::default(), OrderStatus::Unspecified);
+    assert_eq!(OrderStatus::Paid as i32, 1);
+    assert_eq!(OrderStatus::Shipped as i32, 3);
+}
+
+#[test]
+fn single_oneof_messages_become_enums() {
+    assert_eq!(Payment::Cash(true).encode_to_vec(), [0x38, 0x01]);
+    assert_eq!(Payment::Voucher("v".into()).encode_to_vec(), [0x12, 0x01, b'v']);
+    let account = Payment::Account(Customer {
+        email: "e".into(),
+        tiers: Vec::new(),
+    });
+    let bytes = account.encode_to_vec();
+    assert_eq!(bytes, [0x2a, 0x03, 0x0a, 0x01, b'e']);
+    assert_eq!(Payment::decode(bytes.as_slice(), DecodeContext::default()).unwrap(), account);
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn imported_types_register_under_the_file_package() {
+    let schema = proto_rs::schemas::message_by_name("shop.v1.Order").expect("registered");
+    assert_eq!(schema.id.proto_file_path, "tests/import/shop.proto");
+}
@@ -0,0 +1,107 @@
+#![cfg(feature = "schemars")]
+
+use std::collections::HashMap;
+
+use proto_rs::proto_message;
+use proto_rs::schemars::SchemaGenerator;
+use proto_rs::schemars::schema_for;
+use proto_rs::transcode::Transcoder;
+use serde_json::json;
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Stage {
+    #[default]
+    Draft,
+    Live,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Manual,
+    Import(String),
+    Sync { peer: String, cursor: u64 },
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Section {
+    pub title: String,
+    pub children: Vec<Section>,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Document {
+    pub doc_id: u64,
+    pub stage: Stage,
+    pub summary: Option<String>,
+    pub body: Vec<u8>,
+    pub weights: Vec<f32>,
+    pub counters: HashMap<String, i32>,
+    pub root: Section,
+    pub sources: Vec<Source>,
+}
+
+#[test]
+fn message_schema_follows_the_proto3_json_mapping() {
+    let schema = schema_for!(Document).to_value();
+    assert_eq!(schema["title"], "Document");
+    assert_eq!(
+        schema["properties"],
+        json!({
+            "docId": { "type": "string", "format": "uint64" },
+            "stage": { "$ref": "#/$defs/Stage" },
+            "summary": { "type": "string" },
+            "body": { "type": "string", "contentEncoding": "base64" },
+            "weights": { "type": "array", "items": { "type": "number", "format": "float" } },
+            "counters": { "type": "object", "additionalProperties": { "type": "integer", "format": "int32" } },
+            "root": { "$ref": "#/$defs/Section" },
+            "sources": { "type": "array", "items": { "$ref": "#/$defs/Source" } },
+        })
+    );
+
+    let defs = &schema["$defs"];
+    assert_eq!(defs["Stage"], json!({ "type": "string", "enum": ["DRAFT", "LIVE"] }));
+    assert_eq!(
+        defs["Source"],
+        json!({
+            "type": "object",
+            "properties": {
+                "manual": { "type": "object" },
+                "import": { "type": "string" },
+                "sync": {
+                    "type": "object",
+                    "properties": {
+                        "peer": { "type": "string" },
+                        "cursor": { "type": "string", "format": "uint64" },
+                    },
+                },
+            },
+            "maxProperties": 1,
+        })
+    );
+    // recursive messages refer to their own definition
+    assert_eq!(
+        defs["Section"]["properties"]["children"]["items"],
+        json!({ "$ref": "#/$defs/Section" })
+    );
+}
+
+#[test]
+fn transcoded_json_uses_the_schema_property_names() {
+    let json = json!({
+        "docId": "7",
+        "stage": "LIVE",
+        "root": { "title": "intro", "children": [{ "title": "part" }] },
+        "sources": [{ "sync": { "peer": "a", "cursor": "3" } }],
+    });
+    let transcoder = Transcoder::of::<Document>().unwrap();
+    let roundtrip = transcoder.binary_to_json(&transcoder.json_to_binary(&json).unwrap()).unwrap();
+
+    let schema = SchemaGenerator::default().into_root_schema_for::<Document>().to_value();
+    for key in roundtrip.as_object().unwrap().keys() {
+        assert!(schema["properties"].get(key).is_some(), "{key} is not in the schema");
+    }
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "build-schemas")]
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::schemas::JvmLayout;
+use proto_rs::schemas::RustClientCtx;
+
+mod common;
+
+pub mod billing {
+    pub mod invoices {
+        use proto_rs::proto_message;
+
+        #[proto_message(proto_path = "protos/tests/jvm_invoices.proto", proto_package = "acme.invoices.v1")]
+        #[derive(Debug, Clone, PartialEq, Default)]
+        pub struct Invoice {
+            pub id: u64,
+        }
+
+        #[proto_message(proto_path = "protos/tests/jvm_invoices.proto", proto_package = "acme.invoices.v1")]
+        #[derive(Debug, Clone, PartialEq, Default)]
+        pub struct InvoiceLine {
+            pub sku: String,
+        }
+    }
+
+    pub mod ledger {
+        use proto_rs::proto_message;
+        use proto_rs::proto_rpc;
+        use tonic::Response;
+
+        #[proto_message(proto_path = "protos/tests/jvm_ledger.proto", proto_package = "acme.ledger.v1")]
+        #[derive(Debug, Clone, PartialEq, Default)]
+        pub struct Posting {
+            pub amount: i64,
+        }
+
+        #[proto_rpc(
+            rpc_package = "acme.ledger.v1",
+            proto_path = "protos/tests/jvm_ledger.proto",
+            rpc_server = true,
+            rpc_client = true
+        )]
+        pub trait Ledger {
+            async fn post(&self, request: Request<Posting>) -> Result<Response<Posting>, Status>;
+        }
+    }
+}
+
+/// Writes every registered file with `layout` and returns the invoices and ledger files.
+fn written(test: &str, layout: JvmLayout) -> (String, String) {
+    let dir = common::write_schemas(&format!("jvm_{test}"), &RustClientCtx::disabled().with_jvm_layout(layout));
+    (
+        dir.read("protos/tests/jvm_invoices.proto"),
+        dir.read("protos/tests/jvm_ledger.proto"),
+    )
+}
+
+#[test]
+fn options_follow_the_rust_modules() {
+    let (invoices, ledger) = written("modules", JvmLayout::new("com.acme"));
+
+    assert!(
+        invoices.contains(
+            "package acme.invoices.v1;\noption java_package = \"com.acme.billing\";\noption java_multiple_files = true;\noption java_outer_classname = \"InvoicesProto\";\n"
+        ),
+        "{invoices}"
+    );
+    assert!(ledger.contains("option java_package = \"com.acme.billing\";\n"), "{ledger}");
+    assert!(ledger.contains("option java_outer_classname = \"LedgerProto\";\n"), "{ledger}");
+}
+
+#[test]
+fn packages_and_outer_classes_can_be_pinned() {
+    let layout = JvmLayout::new("com.acme")
+        .nested()
+        .java_package("acme.ledger.v1", "com.acme.legacy.ledger")
+        .outer_classname("protos/tests/jvm_invoices.proto", "Billing");
+    let (invoices, ledger) = written("pinned", layout);
+
+    assert!(invoices.contains("option java_package = \"com.acme.billing\";\n"), "{invoices}");
+    assert!(
+        invoices.contains("option java_multiple_files = false;\noption java_outer_classname = \"Billing\";\n"),
+        "{invoices}"
+    );
+    assert!(ledger.contains("option java_package = \"com.acme.legacy.ledger\";\n"), "{ledger}");
+}
@@ -0,0 +1,99 @@
+#![cfg(feature = "kafka")]
+
+use proto_rs::kafka::MAGIC_BYTE;
+use proto_rs::kafka::Payload;
+use proto_rs::kafka::ProtoDeserializer;
+use proto_rs::kafka::ProtoSerializer;
+use proto_rs::kafka::split_frame;
+use proto_rs::proto_message;
+use proto_rs::schemas::TypeRegistry;
+use rdkafka::Message;
+use rdkafka::Timestamp;
+use rdkafka::message::OwnedMessage;
+use rdkafka::message::ToBytes;
+
+#[proto_message(proto_path = "protos/tests/kafka.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OrderPlaced {
+    pub id: u64,
+    pub sku: String,
+}
+
+#[proto_message(proto_path = "protos/tests/kafka.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OrderCancelled {
+    pub id: u64,
+}
+
+fn placed() -> OrderPlaced {
+    OrderPlaced { id: 7, sku: "A-1".into() }
+}
+
+#[test]
+fn plain_and_framed_payloads_round_trip() {
+    let deserializer = ProtoDeserializer::<OrderPlaced>::new();
+
+    let plain = ProtoSerializer::new().serialize(&placed());
+    assert_eq!(split_frame(&plain).unwrap(), None);
+    assert_eq!(deserializer.deserialize(Some(&plain)).unwrap(), Some(placed()));
+
+    let framed = ProtoSerializer::framed().serialize(&placed());
+    assert_eq!(framed[0], MAGIC_BYTE);
+    let (type_url, message) = split_frame(&framed).unwrap().unwrap();
+    assert_eq!(type_url, "type.googleapis.com/kafka.OrderPlaced");
+    assert_eq!(message, plain.as_slice());
+    assert_eq!(deserializer.deserialize(Some(&framed)).unwrap(), Some(placed()));
+
+    assert_eq!(deserializer.deserialize(None).unwrap(), None);
+}
+
+#[test]
+fn frames_are_checked() {
+    let cancelled = ProtoSerializer::framed().serialize(&OrderCancelled { id: 7 });
+    assert!(ProtoDeserializer::<OrderPlaced>::new().deserialize(Some(&cancelled)).is_err());
+
+    let plain = ProtoSerializer::new().serialize(&placed());
+    assert!(ProtoDeserializer::<OrderPlaced>::new().require_frame().deserialize(Some(&plain)).is_err());
+
+    let framed = ProtoSerializer::framed().serialize(&placed());
+    assert!(split_frame(&framed[..5]).is_err());
+}
+
+#[test]
+fn framed_topics_route_through_a_type_registry() {
+    let types = TypeRegistry::new().with::<OrderPlaced>().with::<OrderCancelled>();
+    let payload = ProtoSerializer::framed().serialize(&OrderCancelled { id: 9 });
+
+    let (type_url, message) = split_frame(&payload).unwrap().unwrap();
+    let decoded = types.decode(type_url, message).unwrap();
+    assert_eq!(*decoded.downcast::<OrderCancelled>().unwrap(), OrderCancelled { id: 9 });
+}
+
+fn record(payload: Option<Vec<u8>>) -> OwnedMessage {
+    OwnedMessage::new(payload, None, "orders".into(), Timestamp::NotAvailable, 0, 0, None)
+}
+
+#[test]
+fn consumed_records_are_read_in_place() {
+    let framed = ProtoSerializer::framed().serialize(&placed());
+    let plain = ProtoSerializer::new().serialize(&placed());
+    assert_eq!(framed.to_bytes(), framed.as_slice());
+
+    let deserializer = ProtoDeserializer::<OrderPlaced>::new();
+    assert_eq!(
+        deserializer.deserialize_message(&record(Some(framed.clone()))).unwrap(),
+        Some(placed())
+    );
+    assert_eq!(deserializer.deserialize_message(&record(None)).unwrap(), None);
+
+    let message = record(Some(framed));
+    let payload = message.payload_view::<Payload>().unwrap().unwrap();
+    assert_eq!(payload.type_url(), Some("type.googleapis.com/kafka.OrderPlaced"));
+    assert_eq!(payload.message(), plain.as_slice());
+
+    let message = record(Some(plain.clone()));
+    let payload = message.payload_view::<Payload>().unwrap().unwrap();
+    assert_eq!((payload.type_url(), payload.to_bytes()), (None, plain.as_slice()));
+
+    assert!(record(Some(vec![MAGIC_BYTE, 9])).payload_view::<Payload>().unwrap().is_err());
+}
@@ -0,0 +1,135 @@
+use proto_rs::kv;
+use proto_rs::kv::KvKey;
+use proto_rs::proto_message;
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Region {
+    #[default]
+    Unknown,
+    Eu,
+    Us,
+}
+
+#[proto_message(kv_key)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Tier {
+    #[default]
+    Free,
+    Pro,
+    Enterprise,
+}
+
+#[proto_message(kv_key)]
+#[derive(Debug, Clone, PartialEq, PartialOrd, Default)]
+pub struct OrderKey {
+    pub customer: String,
+    pub tier: Tier,
+    pub placed_at: i64,
+    pub line: Option<u16>,
+    #[proto(skip)]
+    pub cached: bool,
+}
+
+#[proto_message(kv_key)]
+#[derive(Debug, Clone, PartialEq, PartialOrd, Default)]
+pub struct ShardKey(pub u8, pub Vec<u8>);
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Order {
+    pub key: OrderKey,
+    pub region: Region,
+    pub total: f64,
+}
+
+fn assert_order_preserved<K: KvKey + PartialOrd + core::fmt::Debug>(values: &[K]) {
+    for a in values {
+        for b in values {
+            let (ka, kb) = (kv::encode_key(a), kv::encode_key(b));
+            assert_eq!(ka.partial_cmp(&kb), a.partial_cmp(b), "{a:?} vs {b:?}");
+        }
+    }
+}
+
+#[test]
+fn scalar_keys_sort_like_their_values() {
+    assert_order_preserved(&[i64::MIN, -70_000, -1, 0, 1, 255, 70_000, i64::MAX]);
+    assert_order_preserved(&[0u32, 1, 255, 256, u32::MAX]);
+    assert_order_preserved(&[f64::NEG_INFINITY, -1.5, 0.0, 1e-300, 2.5, f64::INFINITY]);
+    assert_order_preserved(&[false, true]);
+    assert_order_preserved(&[None, Some(-3i8), Some(0), Some(9)]);
+}
+
+#[test]
+fn string_keys_sort_lexicographically() {
+    let strings: Vec<String> = ["", "a", "a\0", "a\0b", "aa", "ab", "b", "ba", "é"].iter().map(ToString::to_string).collect();
+    assert_order_preserved(&strings);
+    assert_order_preserved(&[vec![], vec![0u8], vec![0, 0], vec![0, 1], vec![1], vec![0xFF]]);
+
+    let pairs: Vec<(String, u32)> = vec![("a".into(), 9), ("a".into(), 10), ("a\0".into(), 0), ("ab".into(), 0)];
+    assert_order_preserved(&pairs);
+}
+
+#[test]
+fn derived_keys_sort_field_by_field() {
+    let key = |customer: &str, tier, placed_at, line| OrderKey {
+        customer: customer.into(),
+        tier,
+        placed_at,
+        line,
+        cached: false,
+    };
+    assert_order_preserved(&[
+        key("alice", Tier::Free, 5, None),
+        key("alice", Tier::Free, 5, Some(1)),
+        key("alice", Tier::Pro, -10, None),
+        key("alice", Tier::Enterprise, 0, Some(0)),
+        key("alicia", Tier::Free, i64::MIN, None),
+        key("bob", Tier::Free, 0, None),
+    ]);
+    assert_order_preserved(&[ShardKey(0, vec![9]), ShardKey(1, vec![]), ShardKey(1, vec![0]), ShardKey(2, vec![])]);
+}
+
+#[test]
+fn keys_round_trip_and_reject_garbage() {
+    let key = OrderKey {
+        customer: "carol\0x".into(),
+        tier: Tier::Pro,
+        placed_at: -42,
+        line: Some(7),
+        cached: true,
+    };
+    let decoded: OrderKey = kv::decode_key(&kv::encode_key(&key)).expect("key decodes");
+    assert_eq!(
+        decoded,
+        OrderKey {
+            cached: false,
+            ..key.clone()
+        }
+    );
+
+    let shard = ShardKey(3, vec![0, 0xFF, 0]);
+    assert_eq!(kv::decode_key::<ShardKey>(&kv::encode_key(&shard)).expect("key decodes"), shard);
+
+    let mut bytes = kv::encode_key(&key);
+    bytes.push(0);
+    assert!(kv::decode_key::<OrderKey>(&bytes).is_err());
+    assert!(kv::decode_key::<OrderKey>(&bytes[..4]).is_err());
+    assert!(kv::decode_key::<Tier>(&kv::encode_key(&9i32)).is_err());
+}
+
+#[test]
+fn values_use_the_protobuf_encoding() {
+    let order = Order {
+        key: OrderKey {
+            customer: "dave".into(),
+            ..OrderKey::default()
+        },
+        region: Region::Eu,
+        total: 12.5,
+    };
+    let bytes = kv::encode_value(&order);
+    assert_eq!(bytes, proto_rs::ProtoEncode::encode_to_vec(&order));
+    assert_eq!(kv::decode_value::<Order>(&bytes).expect("value decodes"), order);
+}
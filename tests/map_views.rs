@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+use proto_rs::DecodeError;
+use proto_rs::MapView;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::ProtoShadowDecode;
+use proto_rs::ProtoShadowEncode;
+use proto_rs::SetView;
+use proto_rs::encoding::DecodeContext;
+use proto_rs::proto_message;
+
+/// Columnar storage: no map or set is ever built on the encode side.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Inventory {
+    warehouse: String,
+    skus: Vec<String>,
+    counts: Vec<u32>,
+    bins: Vec<(u32, String)>,
+    flagged: Vec<u64>,
+}
+
+struct InventoryIr<'a> {
+    warehouse: &'a String,
+    inventory: &'a Inventory,
+    bins: MapView<'a, u32, String>,
+    flagged: SetView<'a, u64>,
+}
+
+#[proto_message(sun = [Inventory], sun_ir = InventoryIr<'a>)]
+struct InventoryProto {
+    warehouse: String,
+    #[proto(view, getter = "$.inventory.skus.iter().zip(&$.inventory.counts).collect::<MapView<'_, _, _>>()")]
+    stock: BTreeMap<String, u32>,
+    #[proto(view)]
+    bins: HashMap<u32, String>,
+    #[proto(view)]
+    flagged: BTreeSet<u64>,
+}
+
+/// The same message with owned collections.
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+struct InventoryOwned {
+    warehouse: String,
+    stock: BTreeMap<String, u32>,
+    bins: HashMap<u32, String>,
+    flagged: BTreeSet<u64>,
+}
+
+impl<'a> ProtoShadowEncode<'a, Inventory> for InventoryIr<'a> {
+    fn from_sun(value: &'a Inventory) -> Self {
+        InventoryIr {
+            warehouse: &value.warehouse,
+            inventory: value,
+            bins: MapView::new(&value.bins),
+            flagged: SetView::new(&value.flagged),
+        }
+    }
+}
+
+impl ProtoShadowDecode<Inventory> for InventoryProto {
+    fn to_sun(self) -> Result<Inventory, DecodeError> {
+        let mut bins: Vec<(u32, String)> = self.bins.into_iter().collect();
+        bins.sort_unstable();
+        Ok(Inventory {
+            warehouse: self.warehouse,
+            skus: self.stock.keys().cloned().collect(),
+            counts: self.stock.into_values().collect(),
+            bins,
+            flagged: self.flagged.into_iter().collect(),
+        })
+    }
+}
+
+impl proto_rs::DecodeIrBuilder<InventoryProto> for Inventory {
+    fn build_ir(&self) -> Result<InventoryProto, DecodeError> {
+        Ok(InventoryProto {
+            warehouse: self.warehouse.clone(),
+            stock: self.skus.iter().cloned().zip(self.counts.iter().copied()).collect(),
+            bins: self.bins.iter().cloned().collect(),
+            flagged: self.flagged.iter().copied().collect(),
+        })
+    }
+}
+
+fn inventory() -> Inventory {
+    Inventory {
+        warehouse: "ams-1".into(),
+        skus: vec!["bolt".into(), "nut".into(), "washer".into()],
+        counts: vec![120, 0, 35],
+        bins: vec![(4, "A4".into())],
+        flagged: vec![3, 17, 99],
+    }
+}
+
+#[test]
+fn views_encode_like_owned_collections() {
+    let inventory = inventory();
+    let owned = InventoryOwned {
+        warehouse: inventory.warehouse.clone(),
+        stock: BTreeMap::from([("bolt".into(), 120), ("nut".into(), 0), ("washer".into(), 35)]),
+        bins: HashMap::from([(4, "A4".into())]),
+        flagged: BTreeSet::from([3, 17, 99]),
+    };
+
+    assert_eq!(Inventory::encode_to_vec(&inventory), InventoryOwned::encode_to_vec(&owned));
+}
+
+#[test]
+fn viewed_messages_round_trip() {
+    let inventory = inventory();
+    let bytes = Inventory::encode_to_vec(&inventory);
+
+    let decoded = <Inventory as ProtoDecode>::decode(bytes.as_slice(), DecodeContext::default()).expect("decode inventory");
+    assert_eq!(decoded, inventory);
+
+    let owned = <InventoryOwned as ProtoDecode>::decode(bytes.as_slice(), DecodeContext::default()).expect("decode owned inventory");
+    assert_eq!(owned.stock.get("washer"), Some(&35));
+    assert_eq!(owned.bins.get(&4).map(String::as_str), Some("A4"));
+}
+
+#[test]
+fn empty_views_are_skipped() {
+    let empty = Inventory::default();
+    assert!(Inventory::encode_to_vec(&empty).is_empty());
+
+    let view: MapView<'_, String, u32> = core::iter::empty().collect();
+    assert!(view.is_empty());
+    assert_eq!(SetView::new(&[1u32, 2]).len(), 2);
+}
@@ -0,0 +1,101 @@
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::ProtoExt;
+use proto_rs::proto_message;
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Falling = -1,
+    Flat = 0,
+    Rising = 1,
+}
+
+#[proto_message(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Celsius(pub i32);
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point {
+    pub x: u32,
+    pub y: u32,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reading {
+    pub sensor: u32,
+    pub offset: i64,
+    pub valid: bool,
+    pub value: f64,
+    pub id: [u8; 16],
+    pub samples: [i32; 4],
+    pub trend: Trend,
+    pub origin: Option<Point>,
+    pub temperature: Celsius,
+    pub level: u8,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Labeled {
+    pub point: Point,
+    pub label: String,
+}
+
+const fn worst_case() -> Reading {
+    Reading {
+        sensor: u32::MAX,
+        offset: -1,
+        valid: true,
+        value: 1.5,
+        id: [0xff; 16],
+        samples: [-1; 4],
+        trend: Trend::Falling,
+        origin: Some(Point { x: u32::MAX, y: u32::MAX }),
+        temperature: Celsius(i32::MIN),
+        level: u8::MAX,
+    }
+}
+
+#[test]
+fn bound_covers_the_largest_values() {
+    assert_eq!(Point::MAX_ENCODED_LEN, 12);
+    assert_eq!(Point { x: u32::MAX, y: u32::MAX }.encode_to_vec().len(), Point::MAX_ENCODED_LEN);
+    assert_eq!(worst_case().encode_to_vec().len(), Reading::MAX_ENCODED_LEN);
+}
+
+#[test]
+fn stack_buffers_hold_any_value() {
+    let reading = worst_case();
+    let encoded = reading.encode_to_array::<{ Reading::MAX_ENCODED_LEN }>().expect("bound is large enough");
+    assert_eq!(encoded.as_slice(), reading.encode_to_vec().as_slice());
+    assert_eq!(
+        Reading::decode(encoded.as_slice(), DecodeContext::default()).expect("reading decodes"),
+        reading
+    );
+
+    let mut buf = [0u8; Point::MAX_ENCODED_LEN];
+    let point = Point { x: 7, y: 300 };
+    let bytes = point.encode_to_array::<{ Point::MAX_ENCODED_LEN }>().expect("point fits");
+    buf[..bytes.as_slice().len()].copy_from_slice(bytes.as_slice());
+    assert_eq!(&buf[..5], point.encode_to_vec().as_slice());
+}
+
+#[test]
+fn small_arrays_report_the_required_length() {
+    let err = worst_case().encode_to_array::<8>().expect_err("eight bytes are too few");
+    assert_eq!(err.required_capacity(), Reading::MAX_ENCODED_LEN);
+    assert_eq!(err.remaining(), 8);
+    assert!(Point::default().encode_to_array::<0>().expect("default is empty").as_slice().is_empty());
+}
+
+#[test]
+fn unbounded_fields_have_no_bound() {
+    assert_eq!(<Labeled as ProtoExt>::MAX_PAYLOAD_LEN, None);
+    assert_eq!(<Vec<u32> as ProtoExt>::MAX_PAYLOAD_LEN, None);
+    assert_eq!(<[u8; 32] as ProtoExt>::MAX_PAYLOAD_LEN, Some(32));
+    assert_eq!(<Option<Point> as ProtoExt>::MAX_PAYLOAD_LEN, Some(12));
+}
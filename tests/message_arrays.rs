@@ -0,0 +1,98 @@
+use proto_rs::DecodeContext;
+use proto_rs::ProtoArchive;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+#[cfg(feature = "build-schemas")]
+mod common;
+
+#[proto_message(proto_path = "protos/tests/message_arrays.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[proto_message(proto_path = "protos/tests/message_arrays.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Shape {
+    pub corners: [Point; 3],
+    pub keys: [[u8; 4]; 2],
+    pub labels: [String; 2],
+    pub weights: [u32; 3],
+}
+
+/// The same message with the arrays as plain repeated fields
+#[proto_message(proto_path = "protos/tests/message_arrays.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ShapeList {
+    pub corners: Vec<Point>,
+    pub keys: Vec<Vec<u8>>,
+    pub labels: Vec<String>,
+    pub weights: Vec<u32>,
+}
+
+fn shape() -> Shape {
+    Shape {
+        corners: [Point { x: 1, y: 2 }, Point::default(), Point { x: 5, y: 6 }],
+        keys: [[1, 2, 3, 4], [0; 4]],
+        labels: ["a".into(), "b".into()],
+        weights: [7, 0, 9],
+    }
+}
+
+fn decode<T: ProtoDecode>(bytes: &[u8]) -> Result<T, proto_rs::DecodeError> {
+    <T as ProtoDecode>::decode(bytes, DecodeContext::default())
+}
+
+#[test]
+fn arrays_of_messages_and_byte_arrays_round_trip() {
+    let shape = shape();
+    let bytes = shape.encode_to_vec();
+    // `labels` repeats strings, which have no per-element bound
+    assert_eq!(ProtoArchive::size_hint::<0>(&shape), None);
+    assert_eq!(decode::<Shape>(&bytes).expect("shape decodes"), shape);
+}
+
+#[test]
+fn arrays_share_the_repeated_wire_format() {
+    let list = decode::<ShapeList>(&shape().encode_to_vec()).expect("list decodes");
+    assert_eq!(list.corners, shape().corners);
+    assert_eq!(list.keys, [vec![1, 2, 3, 4], vec![0; 4]]);
+    assert_eq!(list.labels, ["a", "b"]);
+    assert_eq!(list.weights, [7, 0, 9]);
+
+    let short = ShapeList {
+        corners: vec![Point { x: 3, y: 4 }],
+        ..ShapeList::default()
+    };
+    let decoded = decode::<Shape>(&short.encode_to_vec()).expect("short run decodes");
+    assert_eq!(decoded.corners, [Point { x: 3, y: 4 }, Point::default(), Point::default()]);
+}
+
+#[test]
+fn runs_longer_than_the_array_are_rejected() {
+    let long = ShapeList {
+        corners: vec![Point::default(); 4],
+        ..ShapeList::default()
+    };
+    assert!(decode::<Shape>(&long.encode_to_vec()).is_err());
+
+    let long = ShapeList {
+        weights: vec![1, 2, 3, 4],
+        ..ShapeList::default()
+    };
+    assert!(decode::<Shape>(&long.encode_to_vec()).is_err());
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn schema_lists_arrays_as_repeated_with_their_length() {
+    let proto = common::emitted_proto("message_arrays", "protos/tests/message_arrays.proto");
+
+    assert!(proto.contains("  repeated Point corners = 1; // at most 3 elements\n"), "{proto}");
+    assert!(proto.contains("  repeated bytes keys = 2; // at most 2 elements\n"), "{proto}");
+    assert!(proto.contains("  repeated string labels = 3; // at most 2 elements\n"), "{proto}");
+    assert!(proto.contains("  repeated uint32 weights = 4; // at most 3 elements\n"), "{proto}");
+}
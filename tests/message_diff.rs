@@ -0,0 +1,132 @@
+#![cfg(feature = "diff")]
+
+use std::collections::BTreeMap;
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::diff::FieldChange;
+use proto_rs::diff::MessageDiff;
+use proto_rs::diff::ProtoDiff;
+use proto_rs::proto_message;
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Address {
+    pub street: String,
+    pub city: String,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Role {
+    #[default]
+    Viewer,
+    Editor,
+    Admin,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Account {
+    pub id: u64,
+    pub email: String,
+    pub role: Role,
+    pub address: Address,
+    pub tags: Vec<String>,
+    pub limits: BTreeMap<String, u32>,
+    pub note: Option<String>,
+}
+
+fn account() -> Account {
+    Account {
+        id: 7,
+        email: "ada@example.com".into(),
+        role: Role::Editor,
+        address: Address {
+            street: "1 Loop Rd".into(),
+            city: "Cupertino".into(),
+        },
+        tags: vec!["beta".into(), "staff".into()],
+        limits: BTreeMap::from([("api".into(), 100)]),
+        note: Some("vip".into()),
+    }
+}
+
+#[test]
+fn equal_values_have_an_empty_diff() {
+    assert!(account().diff(&account()).is_empty());
+
+    let mut patched = account();
+    patched.apply(&MessageDiff::default()).expect("empty diff applies");
+    assert_eq!(patched, account());
+}
+
+#[test]
+fn diff_lists_only_the_changed_fields() {
+    let before = account();
+    let mut after = account();
+    after.role = Role::Admin;
+    after.address.city = "Palo Alto".into();
+    after.tags.push("ops".into());
+
+    let diff = before.diff(&after);
+    assert_eq!(diff.changed_tags().collect::<Vec<_>>(), [3, 4, 5]);
+    assert!(!diff.change(3).expect("role changed").is_cleared());
+    assert!(diff.change(1).is_none());
+}
+
+#[test]
+fn applying_a_diff_reproduces_the_newer_value() {
+    let before = account();
+    let mut after = account();
+    after.email = "ada@lovelace.dev".into();
+    after.limits.insert("ws".into(), 5);
+    after.limits.remove("api");
+    after.note = None;
+    after.tags.clear();
+
+    let diff = before.diff(&after);
+    assert!(diff.change(7).expect("note changed").is_cleared());
+    assert!(diff.change(5).expect("tags changed").is_cleared());
+
+    let mut patched = before.clone();
+    patched.apply(&diff).expect("diff applies");
+    assert_eq!(patched, after);
+
+    let mut reverted = after.clone();
+    reverted.apply(&after.diff(&before)).expect("reverse diff applies");
+    assert_eq!(reverted, before);
+}
+
+#[test]
+fn diffs_survive_the_wire() {
+    let before = Account::default();
+    let after = account();
+    let diff = before.diff(&after);
+
+    let decoded = <MessageDiff as ProtoDecode>::decode(diff.encode_to_vec().as_slice(), DecodeContext::default()).expect("diff decodes");
+    assert_eq!(decoded, diff);
+
+    let mut patched = before;
+    patched.apply(&decoded).expect("diff applies");
+    assert_eq!(patched, after);
+}
+
+#[test]
+fn changes_that_write_other_fields_are_rejected() {
+    let smuggled = MessageDiff {
+        changes: vec![FieldChange {
+            tag: 2,
+            encoded: Account {
+                id: 99,
+                ..Account::default()
+            }
+            .encode_to_vec(),
+        }],
+    };
+
+    let mut target = account();
+    assert!(target.apply(&smuggled).is_err());
+    assert_eq!(target, account());
+}
@@ -0,0 +1,176 @@
+#![cfg(feature = "migrate")]
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::encoding::WireType;
+use proto_rs::encoding::encode_key;
+use proto_rs::encoding::encode_varint;
+use proto_rs::migrate::MigrationPlan;
+use proto_rs::migrate::Widen;
+use proto_rs::migrate::rewrite;
+use proto_rs::proto_message;
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OldOwner {
+    pub nick: String,
+    pub legacy_flag: bool,
+    pub age: i32,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OldAccount {
+    pub id: u64,
+    pub label: String,
+    pub balance: i32,
+    pub scores: Vec<f32>,
+    pub street: String,
+    pub city: String,
+    pub visits: u32,
+    pub owner: OldOwner,
+    pub obsolete: String,
+}
+
+#[proto_message(explicit_tags)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NewOwner {
+    #[proto(tag = 1)]
+    pub nick: String,
+    #[proto(tag = 3)]
+    pub age: i64,
+}
+
+#[proto_message(explicit_tags)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Address {
+    #[proto(tag = 1)]
+    pub street: String,
+    #[proto(tag = 2)]
+    pub city: String,
+}
+
+#[proto_message(explicit_tags)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NewAccount {
+    #[proto(tag = 1)]
+    pub id: u64,
+    #[proto(tag = 3)]
+    pub balance: i64,
+    #[proto(tag = 4)]
+    pub scores: Vec<f64>,
+    #[proto(tag = 7)]
+    pub visits: u64,
+    #[proto(tag = 8)]
+    pub owner: NewOwner,
+    #[proto(tag = 10)]
+    pub title: String,
+    #[proto(tag = 11)]
+    pub address: Address,
+}
+
+fn plan() -> MigrationPlan {
+    MigrationPlan::new()
+        .renumber(2, 10)
+        .widen(3, Widen::Int32ToInt64)
+        .widen(4, Widen::FloatToDouble)
+        .move_into(5, 11, 1)
+        .move_into(6, 11, 2)
+        .widen(7, Widen::UInt32ToUInt64)
+        .nested(8, MigrationPlan::new().drop_field(2))
+        .drop_field(9)
+}
+
+fn old_account() -> OldAccount {
+    OldAccount {
+        id: 42,
+        label: "savings".into(),
+        balance: -1_500,
+        scores: vec![0.5, -2.25],
+        street: "Main St 1".into(),
+        city: "Springfield".into(),
+        visits: u32::MAX,
+        owner: OldOwner {
+            nick: "homer".into(),
+            legacy_flag: true,
+            age: 39,
+        },
+        obsolete: "gone".into(),
+    }
+}
+
+#[test]
+fn rewritten_payload_decodes_as_the_new_schema() {
+    let migrated = rewrite(&old_account().encode_to_vec(), &plan()).expect("payload migrates");
+    let account = <NewAccount as ProtoDecode>::decode(migrated.as_slice(), DecodeContext::default()).expect("new schema decodes");
+
+    assert_eq!(
+        account,
+        NewAccount {
+            id: 42,
+            balance: -1_500,
+            scores: vec![0.5, -2.25],
+            visits: u64::from(u32::MAX),
+            owner: NewOwner {
+                nick: "homer".into(),
+                age: 39,
+            },
+            title: "savings".into(),
+            address: Address {
+                street: "Main St 1".into(),
+                city: "Springfield".into(),
+            },
+        }
+    );
+}
+
+#[test]
+fn fixed_and_zigzag_fields_are_widened() {
+    let mut old = Vec::new();
+    encode_key(1, WireType::Varint, &mut old);
+    encode_varint(5, &mut old); // sint32 -3
+    encode_key(2, WireType::ThirtyTwoBit, &mut old);
+    old.extend_from_slice(&(-7i32).to_le_bytes());
+    encode_key(3, WireType::LengthDelimited, &mut old);
+    encode_varint(8, &mut old);
+    old.extend_from_slice(&1u32.to_le_bytes());
+    old.extend_from_slice(&u32::MAX.to_le_bytes());
+
+    let plan = MigrationPlan::new().widen(1, Widen::SInt32ToSInt64).widen(2, Widen::SFixed32ToSFixed64).widen(3, Widen::Fixed32ToFixed64);
+    let migrated = rewrite(&old, &plan).expect("payload migrates");
+
+    let mut expected = Vec::new();
+    encode_key(1, WireType::Varint, &mut expected);
+    encode_varint(5, &mut expected); // sint64 -3
+    encode_key(2, WireType::SixtyFourBit, &mut expected);
+    expected.extend_from_slice(&(-7i64).to_le_bytes());
+    encode_key(3, WireType::LengthDelimited, &mut expected);
+    encode_varint(16, &mut expected);
+    expected.extend_from_slice(&1u64.to_le_bytes());
+    expected.extend_from_slice(&u64::from(u32::MAX).to_le_bytes());
+    assert_eq!(migrated, expected);
+}
+
+#[test]
+fn fields_outside_the_plan_are_copied_verbatim() {
+    let old = old_account();
+    let bytes = old.encode_to_vec();
+
+    assert_eq!(rewrite(&bytes, &MigrationPlan::new()).expect("empty plan"), bytes);
+    let renumbered = rewrite(&bytes, &MigrationPlan::new().renumber(2, 2)).expect("identity renumber");
+    assert_eq!(renumbered, bytes);
+}
+
+#[test]
+fn widening_a_field_of_the_wrong_wire_type_fails() {
+    let err = rewrite(
+        &old_account().encode_to_vec(),
+        &MigrationPlan::new().widen(1, Widen::Fixed32ToFixed64),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("field 1"), "{err}");
+
+    let truncated = &old_account().encode_to_vec()[..5];
+    assert!(rewrite(truncated, &plan()).is_err());
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "msgpack")]
+
+use std::collections::HashMap;
+
+use proto_rs::msgpack::from_msgpack;
+use proto_rs::msgpack::to_msgpack;
+use proto_rs::proto_message;
+use proto_rs::transcode::TranscodeError;
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Sample {
+    pub sensor: u32,
+    pub value: f32,
+    pub tags: Vec<String>,
+    pub raw: Vec<u8>,
+    pub limits: HashMap<String, i64>,
+}
+
+#[test]
+fn messages_roundtrip_through_msgpack() {
+    let sample = Sample {
+        sensor: 3,
+        value: -1.25,
+        tags: vec!["north".into(), String::new()],
+        raw: vec![9, 8, 7],
+        limits: HashMap::from([("low".to_string(), i64::MIN), ("high".to_string(), 40)]),
+    };
+    let bytes = to_msgpack(&sample).unwrap();
+    assert_eq!(from_msgpack::<Sample>(&bytes).unwrap(), sample);
+}
+
+#[test]
+fn fields_are_keyed_by_number() {
+    // {1: 3, 2: 0.5f32, 4: bin[1]}
+    let sample = Sample {
+        sensor: 3,
+        value: 0.5,
+        raw: vec![1],
+        ..Sample::default()
+    };
+    assert_eq!(
+        to_msgpack(&sample).unwrap(),
+        [0x83, 0x01, 0x03, 0x02, 0xca, 0x3f, 0x00, 0x00, 0x00, 0x04, 0xc4, 0x01, 0x01]
+    );
+    assert!(matches!(
+        from_msgpack::<Sample>(&[0x81, 0x01, 0x03, 0x00]),
+        Err(TranscodeError::Malformed(_))
+    ));
+}
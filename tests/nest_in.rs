@@ -0,0 +1,81 @@
+#![cfg(feature = "build-schemas")]
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tonic::Response;
+
+mod common;
+
+#[proto_message(proto_path = "protos/tests/nest_in.proto", nest_in = "Line")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Kind {
+    #[default]
+    Goods,
+    Service,
+}
+
+#[proto_message(proto_path = "protos/tests/nest_in.proto", nest_in = "Order")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Line {
+    pub sku: String,
+    pub kind: Kind,
+}
+
+#[proto_message(proto_path = "protos/tests/nest_in.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Order {
+    pub id: u64,
+    pub lines: Vec<Line>,
+}
+
+#[proto_rpc(
+    rpc_package = "nest_in",
+    proto_path = "protos/tests/nest_in.proto",
+    rpc_server = true,
+    rpc_client = true
+)]
+pub trait Orders {
+    async fn first_line(&self, request: Request<Order>) -> Result<Response<Line>, Status>;
+}
+
+fn rendered(test: &str) -> String {
+    common::emitted_proto(&format!("nest_in_{test}"), "protos/tests/nest_in.proto")
+}
+
+#[test]
+fn nested_messages_are_emitted_inside_their_parent() {
+    let proto = rendered("messages");
+    assert!(
+        proto.contains("message Order {\n  uint64 id = 1;\n  repeated Order.Line lines = 2;\n\n  message Line {\n"),
+        "{proto}"
+    );
+    assert!(
+        proto.contains("    Order.Line.Kind kind = 2;\n\n    enum Kind {\n      GOODS = 0;\n"),
+        "{proto}"
+    );
+    assert!(!proto.contains("\nmessage Line"), "{proto}");
+    assert!(!proto.contains("\nenum Kind"), "{proto}");
+}
+
+#[test]
+fn rpc_references_use_the_nested_path() {
+    let proto = rendered("rpc");
+    assert!(proto.contains("rpc FirstLine(Order) returns (Order.Line);"), "{proto}");
+}
+
+#[test]
+fn nested_messages_round_trip_unchanged() {
+    let order = Order {
+        id: 3,
+        lines: vec![Line {
+            sku: "A-1".into(),
+            kind: Kind::Service,
+        }],
+    };
+    let decoded = <Order as ProtoDecode>::decode(order.encode_to_vec().as_slice(), DecodeContext::default()).expect("order decodes");
+    assert_eq!(decoded, order);
+}
@@ -0,0 +1,132 @@
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+#[cfg(feature = "build-schemas")]
+mod common;
+
+#[proto_message(transparent, unit = "ms", range = "1..=86_400_000")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Millis(pub u64);
+
+#[proto_message(transparent, unit = "bp", range = "-10_000..=10_000")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BasisPoints {
+    pub value: i32,
+}
+
+#[proto_message(proto_path = "protos/tests/newtype_units.proto")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Order {
+    pub timeout: Millis,
+    pub fee: BasisPoints,
+    pub retries: Vec<Millis>,
+    pub deadline: Option<Millis>,
+}
+
+/// `Order` as an external schema sees it
+#[proto_message(proto_path = "protos/tests/newtype_units.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PlainOrder {
+    pub timeout: u64,
+    pub fee: i32,
+    pub retries: Vec<u64>,
+    pub deadline: Option<u64>,
+}
+
+fn decode<T: ProtoDecode>(bytes: &[u8]) -> Result<T, proto_rs::DecodeError> {
+    <T as ProtoDecode>::decode(bytes, DecodeContext::default())
+}
+
+fn order() -> Order {
+    Order {
+        timeout: Millis(30_000),
+        fee: BasisPoints { value: -25 },
+        retries: vec![Millis(100), Millis(400)],
+        deadline: Some(Millis(86_400_000)),
+    }
+}
+
+#[test]
+fn newtypes_encode_as_their_inner_value() {
+    let plain = PlainOrder {
+        timeout: 30_000,
+        fee: -25,
+        retries: vec![100, 400],
+        deadline: Some(86_400_000),
+    };
+    assert_eq!(order().encode_to_vec(), plain.encode_to_vec());
+    assert_eq!(decode::<Order>(&plain.encode_to_vec()).expect("order decodes"), order());
+}
+
+#[test]
+fn out_of_range_values_are_rejected() {
+    let in_range = PlainOrder {
+        timeout: 30_000,
+        ..PlainOrder::default()
+    };
+
+    let too_long = PlainOrder {
+        timeout: 86_400_001,
+        ..in_range.clone()
+    };
+    let err = decode::<Order>(&too_long.encode_to_vec()).expect_err("timeout above the range");
+    assert!(err.to_string().contains("Millis value is out of range 1..=86_400_000"), "{err}");
+
+    let negative_fee = PlainOrder {
+        fee: -10_001,
+        ..in_range.clone()
+    };
+    let err = decode::<Order>(&negative_fee.encode_to_vec()).expect_err("fee below the range");
+    assert!(err.to_string().contains("BasisPoints value is out of range"), "{err}");
+
+    let bad_retry = PlainOrder {
+        retries: vec![5, 90_000_000],
+        ..in_range.clone()
+    };
+    assert!(decode::<Order>(&bad_retry.encode_to_vec()).is_err(), "repeated values are checked");
+
+    let bad_deadline = PlainOrder {
+        deadline: Some(0),
+        ..in_range
+    };
+    assert!(
+        decode::<Order>(&bad_deadline.encode_to_vec()).is_err(),
+        "optional values are checked"
+    );
+}
+
+#[test]
+fn top_level_newtypes_are_checked() {
+    assert_eq!(decode::<Millis>(&Millis(5).encode_to_vec()).expect("millis decodes"), Millis(5));
+    assert!(decode::<Millis>(&Millis(86_400_001).encode_to_vec()).is_err());
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn schema_and_client_keep_units() {
+    let dir = common::write_schemas_with_client("newtype_units");
+    let proto = dir.read("protos/tests/newtype_units.proto");
+    let client = dir.read("client.rs");
+
+    assert!(
+        proto.contains(
+            "message Order {\n  uint64 timeout = 1; // unit: ms, range: 1..=86_400_000\n  int32 fee = 2; // unit: bp, range: -10_000..=10_000\n"
+        ),
+        "{proto}"
+    );
+    assert!(proto.contains("  repeated uint64 retries = 3; // unit: ms"), "{proto}");
+    assert!(proto.contains("message PlainOrder {\n  uint64 timeout = 1;\n"), "{proto}");
+
+    assert!(client.contains("pub timeout: ::newtype_units::Millis,"), "{client}");
+    assert!(client.contains("pub fee: ::newtype_units::BasisPoints,"), "{client}");
+    assert!(
+        client.contains("pub retries: ::proto_rs::alloc::vec::Vec<::newtype_units::Millis>,"),
+        "{client}"
+    );
+    assert!(
+        client.contains("pub deadline: ::core::option::Option<::newtype_units::Millis>,"),
+        "{client}"
+    );
+}
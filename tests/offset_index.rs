@@ -0,0 +1,115 @@
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+#[cfg(feature = "build-schemas")]
+mod common;
+
+#[proto_message(proto_path = "protos/tests/offset_index.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Event {
+    pub seq: u64,
+    pub body: String,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Archive {
+    pub id: u64,
+    #[proto(offset_index = 15)]
+    pub events: Vec<Event>,
+    pub note: String,
+}
+
+#[proto_message(proto_path = "protos/tests/offset_index.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Journal {
+    pub id: u64,
+    #[proto(offset_index = 15)]
+    pub events: Vec<Event>,
+}
+
+// The same message from a writer that does not index.
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PlainArchive {
+    pub id: u64,
+    pub events: Vec<Event>,
+    pub note: String,
+}
+
+fn events(count: u64) -> Vec<Event> {
+    (0..count)
+        .map(|seq| Event {
+            seq,
+            body: "x".repeat(usize::try_from(seq % 7).unwrap()),
+        })
+        .collect()
+}
+
+#[test]
+fn elements_are_read_through_the_index() {
+    let archive = Archive {
+        id: 1,
+        events: events(500),
+        note: "tail".into(),
+    };
+    let bytes = archive.encode_to_vec();
+    // the index leads the message: field 15, length delimited
+    assert_eq!(bytes[0], 15 << 3 | 2);
+
+    assert_eq!(Archive::events_len(&bytes).unwrap(), 500);
+    for idx in [0, 1, 250, 499] {
+        assert_eq!(Archive::events_at(&bytes, idx).unwrap().as_ref(), Some(&archive.events[idx]));
+    }
+    assert_eq!(Archive::events_at(&bytes, 500).unwrap(), None);
+    // event bodies are strings, so the events have no per-element bound
+    assert_eq!(archive.encoded_len_hint(), None);
+
+    // decoders skip the index
+    assert_eq!(Archive::decode(bytes.as_slice(), DecodeContext::default()).unwrap(), archive);
+    let plain = PlainArchive::decode(bytes.as_slice(), DecodeContext::default()).unwrap();
+    assert_eq!(plain.events, archive.events);
+}
+
+#[test]
+fn encodings_without_an_index_are_scanned() {
+    let plain = PlainArchive {
+        id: 2,
+        events: events(10),
+        note: String::new(),
+    };
+    let bytes = plain.encode_to_vec();
+    assert_eq!(Archive::events_len(&bytes).unwrap(), 10);
+    assert_eq!(Archive::events_at(&bytes, 9).unwrap().map(|event| event.seq), Some(9));
+    assert_eq!(Archive::events_at(&bytes, 10).unwrap(), None);
+}
+
+#[test]
+fn empty_fields_have_no_index() {
+    let archive = Archive {
+        id: 3,
+        ..Archive::default()
+    };
+    let bytes = archive.encode_to_vec();
+    assert_eq!(
+        bytes,
+        PlainArchive {
+            id: 3,
+            ..PlainArchive::default()
+        }
+        .encode_to_vec()
+    );
+    assert_eq!(Archive::events_len(&bytes).unwrap(), 0);
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn schema_reserves_the_index_tag() {
+    let proto = common::emitted_proto("offset_index", "protos/tests/offset_index.proto");
+    assert!(
+        proto.contains("message Journal {\n  uint64 id = 1;\n  repeated Event events = 2;\n  reserved 15;\n}"),
+        "{proto}"
+    );
+}
@@ -0,0 +1,139 @@
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::encoding::DecodeContext;
+use proto_rs::proto_message;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct WideReading {
+    #[proto(tag = 1)]
+    pub level: u64,
+    #[proto(tag = 2)]
+    pub offset: i64,
+    #[proto(tag = 3)]
+    pub count: u64,
+    #[proto(tag = 4)]
+    pub hint: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct StrictReading {
+    #[proto(tag = 1)]
+    pub level: u8,
+    #[proto(tag = 2)]
+    pub offset: i16,
+    #[proto(tag = 3)]
+    pub count: usize,
+    #[proto(tag = 4)]
+    pub hint: Option<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct SaturatingReading {
+    #[proto(tag = 1, on_overflow = "saturate")]
+    pub level: u8,
+    #[proto(tag = 2, on_overflow = "saturate")]
+    pub offset: i16,
+    #[proto(tag = 3, on_overflow = "saturate")]
+    pub count: usize,
+    #[proto(tag = 4, on_overflow = "saturate")]
+    pub hint: Option<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct WrappingReading {
+    #[proto(tag = 1, on_overflow = "wrap")]
+    pub level: u8,
+    #[proto(tag = 2, on_overflow = "wrap")]
+    pub offset: i16,
+    #[proto(tag = 3, on_overflow = "wrap")]
+    pub count: usize,
+    #[proto(tag = 4, on_overflow = "wrap")]
+    pub hint: Option<u16>,
+}
+
+fn out_of_range() -> Vec<u8> {
+    WideReading {
+        level: 300,
+        offset: -40_000,
+        count: 5,
+        hint: Some(70_000),
+    }
+    .encode_to_vec()
+}
+
+#[test]
+fn default_policy_rejects_out_of_range_values() {
+    let err = <StrictReading as ProtoDecode>::decode(&out_of_range()[..], DecodeContext::default()).expect_err("overflow");
+    assert!(err.to_string().contains("overflow"));
+
+    let in_range = WideReading {
+        level: 200,
+        offset: -2,
+        count: 9,
+        hint: Some(1),
+    }
+    .encode_to_vec();
+    let decoded = <StrictReading as ProtoDecode>::decode(&in_range[..], DecodeContext::default()).expect("decode");
+    assert_eq!(
+        decoded,
+        StrictReading {
+            level: 200,
+            offset: -2,
+            count: 9,
+            hint: Some(1),
+        }
+    );
+
+    // Without on_overflow the varint is narrowed to 32 bits before the range check, as before the policy existed
+    let high_bits = WideReading {
+        level: (1 << 32) + 5,
+        ..WideReading::default()
+    }
+    .encode_to_vec();
+    let decoded = <StrictReading as ProtoDecode>::decode(&high_bits[..], DecodeContext::default()).expect("decode");
+    assert_eq!(decoded.level, 5);
+}
+
+#[test]
+fn saturate_policy_clamps_to_bounds() {
+    let decoded = <SaturatingReading as ProtoDecode>::decode(&out_of_range()[..], DecodeContext::default()).expect("decode");
+    assert_eq!(
+        decoded,
+        SaturatingReading {
+            level: u8::MAX,
+            offset: i16::MIN,
+            count: 5,
+            hint: Some(u16::MAX),
+        }
+    );
+}
+
+#[test]
+fn wrap_policy_truncates_high_bits() {
+    let decoded = <WrappingReading as ProtoDecode>::decode(&out_of_range()[..], DecodeContext::default()).expect("decode");
+    assert_eq!(
+        decoded,
+        WrappingReading {
+            level: 44,      // 300 mod 256
+            offset: 25_536, // -40_000 + 65_536
+            count: 5,
+            hint: Some(4_464), // 70_000 - 65_536
+        }
+    );
+}
+
+#[test]
+fn policies_roundtrip_in_range_values() {
+    let message = SaturatingReading {
+        level: 7,
+        offset: -7,
+        count: usize::MAX,
+        hint: None,
+    };
+    let decoded = <SaturatingReading as ProtoDecode>::decode(&message.encode_to_vec()[..], DecodeContext::default()).expect("decode");
+    assert_eq!(decoded, message);
+}
@@ -0,0 +1,120 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::bus::BusChannel;
+use proto_rs::bus::BusDispatcher;
+use proto_rs::bus::RequestReply;
+use proto_rs::pagination::PageRequest;
+use proto_rs::pagination::PageResponse;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tokio_stream::StreamExt;
+use tonic::Code;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::Bytes;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Book {
+    pub id: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct ListBooksRequest {
+    pub shelf: String,
+    pub page_size: u32,
+    pub page_token: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct ListBooksResponse {
+    pub books: Vec<Book>,
+    pub next_page_token: String,
+}
+
+#[proto_rpc(rpc_package = "library_rpc", rpc_server = true, rpc_client = true)]
+pub trait Library {
+    async fn list_books(&self, request: Request<ListBooksRequest>) -> Result<Response<ListBooksResponse>, Status>;
+
+    async fn get_book(&self, request: Request<Book>) -> Result<Response<Book>, Status>;
+}
+
+/// Ten books on shelf `main`; tokens are the id of the next book.
+struct Shelf;
+
+impl Library for Shelf {
+    async fn list_books(&self, request: Request<ListBooksRequest>) -> Result<Response<ListBooksResponse>, Status> {
+        let request = request.into_inner();
+        if request.shelf != "main" {
+            return Err(Status::not_found("no such shelf"));
+        }
+        let start = if request.page_token.is_empty() {
+            0
+        } else {
+            request.page_token.parse().map_err(|_| Status::invalid_argument("bad page token"))?
+        };
+        let end = (start + request.page_size).min(10);
+        Ok(Response::new(ListBooksResponse {
+            books: (start..end).map(|id| Book { id }).collect(),
+            next_page_token: if end < 10 { end.to_string() } else { String::new() },
+        }))
+    }
+
+    async fn get_book(&self, request: Request<Book>) -> Result<Response<Book>, Status> {
+        Ok(Response::new(request.into_inner()))
+    }
+}
+
+#[derive(Clone)]
+struct LocalBus(BusDispatcher<library_server::LibraryServer<Shelf>>);
+
+impl RequestReply for LocalBus {
+    async fn request(&self, subject: String, payload: Bytes) -> Result<Bytes, Status> {
+        Ok(self.0.dispatch(&subject, payload).await)
+    }
+}
+
+fn client() -> library_client::LibraryClient<BusChannel<LocalBus>> {
+    let dispatcher = BusDispatcher::new(library_server::LibraryServer::new(Shelf));
+    library_client::LibraryClient::new(BusChannel::new(LocalBus(dispatcher)))
+}
+
+#[test]
+fn page_pairs_are_recognized() {
+    let mut request = ListBooksRequest::default();
+    request.set_page_token("4".to_string());
+    assert_eq!(request.page_token, "4");
+
+    let response = ListBooksResponse {
+        books: vec![Book { id: 1 }],
+        next_page_token: "2".to_string(),
+    };
+    assert_eq!(response.next_page_token(), "2");
+    assert_eq!(response.into_items(), vec![Book { id: 1 }]);
+}
+
+#[tokio::test]
+async fn auto_paginate_streams_every_page() {
+    let request = ListBooksRequest {
+        shelf: "main".to_string(),
+        page_size: 3,
+        ..Default::default()
+    };
+    let books = client().list_books_auto_paginate(request).collect::<Result<Vec<_>, _>>().await.unwrap();
+    assert_eq!(books.iter().map(|book| book.id).collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn auto_paginate_ends_with_the_first_error() {
+    let request = ListBooksRequest {
+        shelf: "attic".to_string(),
+        page_size: 3,
+        ..Default::default()
+    };
+    let results = client().list_books_auto_paginate(request).collect::<Vec<_>>().await;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].as_ref().unwrap_err().code(), Code::NotFound);
+}
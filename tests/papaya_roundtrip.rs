@@ -148,3 +148,101 @@ fn papaya_hashset_roundtrip_with_custom_hasher_strings() {
 
     assert_eq!(decoded, message);
 }
+
+#[proto_message(proto_path = "protos/tests/papaya.proto")]
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct PlainCollections {
+    #[proto(tag = 1)]
+    pub label_by_id: std::collections::HashMap<u32, String>,
+    #[proto(tag = 2)]
+    pub metrics: Vec<u64>,
+}
+
+#[test]
+fn papaya_runs_decode_across_interleaved_fields() {
+    // 1 => "a", metric 5 (unpacked), 2 => "b", 3 => "c", metrics [6, 7] (packed), 1 => "z"
+    let bytes = [
+        0x0A, 0x05, 0x08, 0x01, 0x12, 0x01, b'a', //
+        0x10, 0x05, //
+        0x0A, 0x05, 0x08, 0x02, 0x12, 0x01, b'b', //
+        0x0A, 0x05, 0x08, 0x03, 0x12, 0x01, b'c', //
+        0x12, 0x02, 0x06, 0x07, //
+        0x0A, 0x05, 0x08, 0x01, 0x12, 0x01, b'z',
+    ];
+    let decoded = <PapayaCollections as ProtoDecode>::decode(&bytes[..], DecodeContext::default()).expect("decode papaya collections");
+    let plain = <PlainCollections as ProtoDecode>::decode(&bytes[..], DecodeContext::default()).expect("decode plain collections");
+
+    let labels = decoded.label_by_id.pin();
+    assert_eq!(labels.len(), plain.label_by_id.len());
+    for (id, label) in &plain.label_by_id {
+        assert_eq!(labels.get(id), Some(label));
+    }
+    assert_eq!(labels.get(&1).map(String::as_str), Some("z"));
+
+    let metrics = decoded.metrics.pin();
+    assert_eq!(metrics.len(), plain.metrics.len());
+    assert!(plain.metrics.iter().all(|metric| metrics.contains(metric)));
+}
+
+#[test]
+fn papaya_encode_shadow_is_a_snapshot() {
+    use proto_rs::ProtoArchive;
+    use proto_rs::ProtoShadowEncode;
+    use proto_rs::RevVec;
+    use proto_rs::RevWriter;
+
+    let message = PapayaCollections::default();
+    message.label_by_id.pin().insert(1, "alpha".to_string());
+    message.metrics.pin().insert(7);
+
+    let shadow = <PapayaCollections as ProtoEncode>::Shadow::from_sun(&message);
+    message.label_by_id.pin().insert(2, "beta".to_string());
+    message.label_by_id.pin().remove(&1);
+    message.metrics.pin().insert(1 << 40);
+
+    let mut w = RevVec::with_capacity(0);
+    shadow.archive::<0>(&mut w);
+    // string values have no per-entry bound
+    assert_eq!(ProtoArchive::size_hint::<0>(&shadow), None);
+
+    let before = PapayaCollections::default();
+    before.label_by_id.pin().insert(1, "alpha".to_string());
+    before.metrics.pin().insert(7);
+    assert_eq!(w.as_written_slice(), before.encode_to_vec());
+}
+
+#[test]
+fn papaya_encode_under_concurrent_writes_decodes() {
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+
+    let message = Arc::new(PapayaCollections::default());
+    let stop = Arc::new(AtomicBool::new(false));
+    let writer = {
+        let message = Arc::clone(&message);
+        let stop = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            let mut round = 0u32;
+            while !stop.load(Ordering::Relaxed) {
+                let key = round % 64;
+                if round.is_multiple_of(3) {
+                    message.label_by_id.pin().remove(&key);
+                    message.metrics.pin().remove(&u64::from(key));
+                } else {
+                    message.label_by_id.pin().insert(key, "x".repeat(key as usize));
+                    message.metrics.pin().insert(u64::from(key) << 20);
+                }
+                round = round.wrapping_add(1);
+            }
+        })
+    };
+
+    for _ in 0..500 {
+        let encoded = message.encode_to_vec();
+        assert!(<PapayaCollections as ProtoDecode>::decode(&encoded[..], DecodeContext::default()).is_ok());
+    }
+
+    stop.store(true, Ordering::Relaxed);
+    writer.join().expect("writer thread");
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "build-schemas")]
+
+use std::collections::BTreeMap;
+
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+use proto_rs::schemas::Preview;
+use proto_rs::schemas::preview;
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Csv,
+    Parquet,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Owner {
+    pub id: u64,
+    #[proto(sensitive)]
+    pub email: String,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Manual,
+    Import(Owner),
+    Sync { url: String, retries: u32 },
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Upload {
+    pub name: String,
+    pub data: Vec<u8>,
+    pub tags: Vec<String>,
+    pub sizes: Vec<u32>,
+    pub format: Format,
+    pub owner: Option<Owner>,
+    pub labels: BTreeMap<String, i64>,
+    pub ratio: f64,
+    pub source: Option<Source>,
+}
+
+fn upload() -> Upload {
+    Upload {
+        name: "report.csv".into(),
+        data: vec![0xde, 0xad, 0xbe, 0xef],
+        tags: vec!["q3".into(), "finance".into()],
+        sizes: vec![1, 2, 3],
+        format: Format::Parquet,
+        owner: Some(Owner {
+            id: 7,
+            email: "jane@example.com".into(),
+        }),
+        labels: BTreeMap::from([("a".into(), -1), ("b".into(), 2)]),
+        ratio: 0.5,
+        source: Some(Source::Sync {
+            url: "s3://bucket".into(),
+            retries: 3,
+        }),
+    }
+}
+
+#[test]
+fn small_messages_preview_in_full() {
+    assert_eq!(
+        upload().preview(1024),
+        "Upload { name: \"report.csv\", data: <4 bytes: deadbeef>, tags: [\"q3\", \"finance\"], sizes: [1, 2, 3], \
+         format: PARQUET, owner: Owner { id: 7, email: <redacted> }, labels: {\"a\": -1, \"b\": 2}, ratio: 0.5, \
+         source: Sync { url: \"s3://bucket\", retries: 3 } }"
+    );
+    assert_eq!(Upload::default().preview(64), "Upload {}");
+
+    let import = Upload {
+        source: Some(Source::Import(Owner {
+            id: 1,
+            email: String::new(),
+        })),
+        ..Upload::default()
+    };
+    assert_eq!(import.preview(64), "Upload { source: Import(Owner { id: 1 }) }");
+    let manual = Upload {
+        source: Some(Source::Manual),
+        ..Upload::default()
+    };
+    assert_eq!(manual.preview(64), "Upload { source: Manual }");
+}
+
+#[test]
+fn large_messages_are_cut_to_the_budget() {
+    let large = Upload {
+        name: "x".repeat(1000),
+        data: vec![0xab; 1 << 20],
+        tags: (0..10_000).map(|idx| format!("tag{idx}")).collect(),
+        sizes: (0..10_000).collect(),
+        ..Upload::default()
+    };
+    // The name fills the budget, so every later field is elided.
+    assert_eq!(
+        large.preview(80),
+        format!("Upload {{ name: \"{}\".. (1000 bytes), .. }}", "x".repeat(63))
+    );
+
+    let lists = Upload {
+        tags: (0..10_000).map(|idx| format!("tag{idx}")).collect(),
+        ..Upload::default()
+    };
+    assert_eq!(lists.preview(30), "Upload { tags: [\"tag0\", \"tag1\", .. 9998 more] }");
+
+    let blob = Upload {
+        data: (0..=255).collect(),
+        ..Upload::default()
+    };
+    assert_eq!(blob.preview(64), "Upload { data: <256 bytes: 000102030405060708090a0b0c0d0e0f..> }");
+    assert_eq!(blob.preview(20), "Upload { data: <256 bytes: 0001..> }");
+    assert_eq!(blob.preview(16), "Upload { data: <256 bytes> }");
+}
+
+#[test]
+fn previews_encoded_payloads_by_name() {
+    let bytes = upload().encode_to_vec();
+    assert_eq!(preview("Upload", &bytes, 1024).unwrap(), upload().preview(1024));
+    assert!(preview("Missing", &bytes, 1024).is_err());
+    assert!(preview("Upload", &bytes[..bytes.len() - 1], 1024).is_err());
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "build-schemas")]
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use proto_rs::schemas::ProtoIdentifiable;
+use tonic::Response;
+
+mod common;
+
+#[proto_message(proto_path = "protos/tests/billing.proto", proto_package = "acme.billing.v1")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Invoice {
+    pub id: u64,
+    pub total: u64,
+}
+
+#[proto_message(proto_path = "protos/tests/billing_audit.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AuditRecord {
+    pub invoice: Option<Invoice>,
+}
+
+#[proto_rpc(
+    proto_package = "acme.billing.v1",
+    proto_path = "protos/tests/billing.proto",
+    rpc_server = true,
+    rpc_client = true
+)]
+pub trait Billing {
+    async fn get_invoice(&self, request: Request<Invoice>) -> Result<Response<Invoice>, Status>;
+}
+
+#[test]
+fn explicit_package_replaces_the_derived_one() {
+    let ident = <Invoice as ProtoIdentifiable>::PROTO_IDENT;
+    assert_eq!(ident.proto_package_name, "acme.billing.v1");
+    assert_eq!(ident.proto_file_path, "protos/tests/billing.proto");
+    assert_eq!(<AuditRecord as ProtoIdentifiable>::PROTO_IDENT.proto_package_name, "billing_audit");
+    assert_eq!(billing_server::BillingServer::<()>::SERVICE_NAME, "acme.billing.v1.Billing");
+}
+
+#[test]
+fn schema_files_and_client_modules_use_the_package() {
+    let dir = common::write_schemas_with_client("proto_package");
+    let billing = dir.read("protos/tests/billing.proto");
+    let audit = dir.read("protos/tests/billing_audit.proto");
+    let client = dir.read("client.rs");
+
+    assert!(billing.contains("package acme.billing.v1;"), "{billing}");
+    assert!(billing.contains("rpc GetInvoice(Invoice) returns (Invoice);"), "{billing}");
+    assert!(audit.contains("import \"billing.proto\";"), "{audit}");
+    assert!(audit.contains("optional acme.billing.v1.Invoice invoice = 1;"), "{audit}");
+    assert!(client.contains("pub mod acme {"), "{client}");
+    assert!(client.contains("pub mod billing {"), "{client}");
+    assert!(client.contains("pub mod v1 {"), "{client}");
+}
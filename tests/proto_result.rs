@@ -0,0 +1,102 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::ProtoExt;
+use proto_rs::ProtoResult;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tonic::Response;
+
+#[cfg(feature = "build-schemas")]
+mod common;
+
+#[proto_message(proto_path = "protos/tests/checkout.proto")]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Receipt {
+    pub id: u64,
+}
+
+#[proto_message(proto_path = "protos/tests/checkout.proto")]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AppError {
+    pub code: u32,
+    pub message: String,
+}
+
+#[proto_message(proto_path = "protos/tests/checkout.proto")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkout {
+    pub order: String,
+    #[proto(result)]
+    pub outcome: Result<Receipt, AppError>,
+}
+
+#[proto_message(proto_path = "protos/tests/checkout.proto")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reply {
+    pub outcome: ProtoResult<Receipt, AppError>,
+}
+
+#[proto_rpc(
+    rpc_package = "checkout",
+    proto_path = "protos/tests/checkout.proto",
+    rpc_server = true,
+    rpc_client = true
+)]
+pub trait CheckoutService {
+    async fn pay(&self, request: Request<Checkout>) -> Result<Response<ProtoResult<Receipt, AppError>>, Status>;
+}
+
+fn roundtrip<T: ProtoEncode + ProtoDecode + ProtoExt>(value: &T) -> T {
+    T::decode(value.encode_to_vec().as_slice(), DecodeContext::default()).expect("value decodes")
+}
+
+#[test]
+fn proto_result_roundtrips_both_variants() {
+    let ok = Reply {
+        outcome: ProtoResult::Ok(Receipt { id: 7 }),
+    };
+    let err = Reply {
+        outcome: ProtoResult::Err(AppError {
+            code: 409,
+            message: "already paid".into(),
+        }),
+    };
+    assert_eq!(roundtrip(&ok), ok);
+    assert_eq!(roundtrip(&err), err);
+    assert_eq!(roundtrip(&err).outcome.into_result().unwrap_err().code, 409);
+}
+
+#[test]
+fn result_fields_share_the_proto_result_encoding() {
+    let checkout = Checkout {
+        order: "A-1".into(),
+        outcome: Err(AppError {
+            code: 402,
+            message: "card declined".into(),
+        }),
+    };
+    assert_eq!(roundtrip(&checkout), checkout);
+
+    let reply = Reply {
+        outcome: checkout.outcome.clone().into(),
+    };
+    let checkout_bytes = checkout.encode_to_vec();
+    let reply_bytes = reply.encode_to_vec();
+    // Same payload, only the field key differs.
+    assert!(checkout_bytes.ends_with(&reply_bytes[1..]));
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn schema_emits_ok_err_oneof() {
+    let proto = common::emitted_proto("proto_result", "protos/tests/checkout.proto");
+    assert!(proto.contains("ProtoResultReceiptAppError outcome = 2;"), "{proto}");
+    assert!(proto.contains("rpc Pay(Checkout) returns (ProtoResultReceiptAppError);"), "{proto}");
+    assert!(
+        proto.contains("message ProtoResultReceiptAppError {\n  oneof value {\n    Receipt ok = 1;\n    AppError err = 2;\n  }\n}"),
+        "{proto}"
+    );
+}
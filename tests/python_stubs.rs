@@ -0,0 +1,166 @@
+#![cfg(feature = "build-schemas")]
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::process::Command;
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use proto_rs::schemas::python;
+use tonic::Response;
+
+mod common;
+
+#[proto_message(proto_path = "protos/tests/python_stubs.proto")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Stage {
+    #[default]
+    Open,
+    Paid,
+}
+
+#[proto_message(proto_path = "protos/tests/python_stubs.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Line {
+    pub sku: String,
+    pub quantity: u32,
+}
+
+#[proto_message(proto_path = "protos/tests/python_stubs.proto")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Payment {
+    Cash,
+    Card(String),
+    Transfer { iban: String, amount: i64 },
+}
+
+#[proto_message(proto_path = "protos/tests/python_stubs.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Order {
+    pub id: u64,
+    pub stage: Stage,
+    pub note: Option<String>,
+    pub lines: Vec<Line>,
+    pub weights: Vec<i32>,
+    pub attachment: Vec<u8>,
+    pub totals: BTreeMap<String, i64>,
+    pub payment: Option<Payment>,
+}
+
+#[proto_rpc(
+    rpc_package = "python_stubs",
+    proto_path = "protos/tests/python_stubs.proto",
+    rpc_server = true,
+    rpc_client = true
+)]
+pub trait Orders {
+    async fn place(&self, request: Request<Order>) -> Result<Response<Order>, Status>;
+}
+
+fn order() -> Order {
+    Order {
+        id: 42,
+        stage: Stage::Paid,
+        note: Some("door".into()),
+        lines: vec![
+            Line {
+                sku: "tea".into(),
+                quantity: 2,
+            },
+            Line::default(),
+        ],
+        weights: vec![-1, 0, 7],
+        attachment: vec![0, 255],
+        totals: BTreeMap::from([("eur".to_string(), -5), ("usd".to_string(), 9)]),
+        payment: Some(Payment::Transfer {
+            iban: "DE00".into(),
+            amount: -3,
+        }),
+    }
+}
+
+/// Writes the package to a fresh directory and returns the directory holding it.
+fn written(test: &str) -> common::TempDir {
+    let root = common::TempDir::new(&format!("python_{test}"));
+    python::write_all(root.join("generated").to_str().expect("utf-8 temp dir")).expect("modules are written");
+    root
+}
+
+#[test]
+fn dataclasses_and_clients_are_written() {
+    let root = written("stubs");
+    let module = root.read("generated/python_stubs.py");
+    assert!(root.join("generated/_proto_rs.py").exists());
+    assert!(root.join("generated/__init__.py").exists());
+
+    assert!(
+        module.contains("class Stage(_enum.IntEnum):\n    OPEN = 0\n    PAID = 1\n"),
+        "{module}"
+    );
+    assert!(module.contains("@_dc.dataclass\nclass Order:\n    id: int = 0\n"), "{module}");
+    assert!(module.contains("    note: Optional[str] = None\n"), "{module}");
+    assert!(
+        module.contains("    lines: list[Line] = _dc.field(default_factory=list)\n"),
+        "{module}"
+    );
+    assert!(
+        module.contains("    totals: dict[str, int] = _dc.field(default_factory=dict)\n"),
+        "{module}"
+    );
+    assert!(
+        module.contains("        (3, \"transfer\", \"optional\", \"message:python_stubs.PaymentTransfer\"),\n"),
+        "{module}"
+    );
+    assert!(
+        module.contains("    ],\n    oneof=True,\n)\n@_dc.dataclass\nclass Payment:\n"),
+        "{module}"
+    );
+    assert!(module.contains("class OrdersClient:"), "{module}");
+    assert!(
+        module.contains("channel.unary_unary(\n            \"/python_stubs.Orders/Place\","),
+        "{module}"
+    );
+    assert!(
+        module.contains("    def place(self, request: Order, **kwargs: Any) -> Order:\n"),
+        "{module}"
+    );
+}
+
+const ROUNDTRIP: &str = r#"
+import sys
+from generated import _proto_rs, python_stubs as pb
+
+order = _proto_rs.decode(pb.Order, bytes.fromhex(sys.argv[1]))
+assert order.id == 42 and order.stage is pb.Stage.PAID and order.note == "door", order
+assert [(line.sku, line.quantity) for line in order.lines] == [("tea", 2), ("", 0)], order.lines
+assert order.weights == [-1, 0, 7] and order.attachment == b"\x00\xff", order
+assert order.totals == {"eur": -5, "usd": 9}, order.totals
+assert order.payment.transfer == pb.PaymentTransfer(iban="DE00", amount=-3), order.payment
+print(_proto_rs.encode(order).hex())
+"#;
+
+#[test]
+fn python_codec_matches_the_rust_encoding() {
+    let root = written("roundtrip");
+    let bytes = order().encode_to_vec();
+    let hex = bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    });
+    let output = Command::new("python3").arg("-c").arg(ROUNDTRIP).arg(&hex).current_dir(root.path()).output();
+    let Ok(output) = output else {
+        eprintln!("python3 is not available, skipping");
+        return;
+    };
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let encoded = String::from_utf8(output.stdout).expect("hex output");
+    let encoded: Vec<u8> =
+        (0..encoded.trim().len()).step_by(2).map(|idx| u8::from_str_radix(&encoded[idx..idx + 2], 16).expect("hex byte")).collect();
+    assert_eq!(encoded, bytes);
+    assert_eq!(Order::decode(encoded.as_slice(), DecodeContext::default()).unwrap(), order());
+}
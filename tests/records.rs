@@ -0,0 +1,127 @@
+#![cfg(feature = "records")]
+
+use std::io::Cursor;
+
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+use proto_rs::records::MAGIC;
+use proto_rs::records::Reader;
+use proto_rs::records::RecordError;
+use proto_rs::records::Writer;
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Event {
+    pub seq: u64,
+    pub kind: String,
+    pub payload: Vec<u8>,
+}
+
+fn events(count: u64) -> Vec<Event> {
+    (0..count)
+        .map(|seq| Event {
+            seq,
+            kind: format!("kind-{}", seq % 3),
+            payload: vec![0xAB; usize::try_from(seq % 40).unwrap()],
+        })
+        .collect()
+}
+
+fn write_log(events: &[Event], block_size: usize) -> Vec<u8> {
+    let mut writer = Writer::new(Vec::new()).expect("header is written").block_size(block_size);
+    for event in events {
+        writer.write(event).expect("record is written");
+    }
+    writer.finish().expect("log is flushed")
+}
+
+fn read_log(bytes: &[u8]) -> Vec<Result<Event, RecordError>> {
+    Reader::new(Cursor::new(bytes)).expect("header is valid").messages::<Event>().collect()
+}
+
+#[test]
+fn records_replay_in_write_order() {
+    let written = events(200);
+    for block_size in [0, 256, 64 * 1024] {
+        let log = write_log(&written, block_size);
+        let read: Vec<Event> = read_log(&log).into_iter().map(|event| event.expect("record decodes")).collect();
+        assert_eq!(read, written, "block size {block_size}");
+    }
+}
+
+#[test]
+fn empty_and_default_messages_round_trip() {
+    let mut writer = Writer::new(Vec::new()).expect("header is written");
+    writer.write(&Event::default()).expect("record is written");
+    writer.write_encoded(&[]).expect("record is written");
+    writer.write(&events(2)[1]).expect("record is written");
+    let log = writer.finish().expect("log is flushed");
+
+    let mut reader = Reader::new(log.as_slice()).expect("header is valid");
+    assert_eq!(reader.next_encoded().unwrap(), Some(&[][..]));
+    assert_eq!(reader.next_encoded().unwrap(), Some(&[][..]));
+    assert_eq!(reader.read::<Event>().unwrap(), Some(events(2)[1].clone()));
+    assert_eq!(reader.read::<Event>().unwrap(), None);
+    assert!(write_log(&[], 0).starts_with(&MAGIC));
+    assert!(read_log(&write_log(&[], 0)).is_empty());
+}
+
+#[test]
+fn flush_makes_buffered_records_readable() {
+    let mut writer = Writer::new(Vec::new()).expect("header is written");
+    writer.write(&events(1)[0]).expect("record is written");
+    writer.flush().expect("block is written");
+    writer.write(&events(2)[1]).expect("record is written");
+
+    // The second record is still buffered when the process dies
+    let log = writer.finish().expect("log is flushed");
+    let flushed_len = MAGIC.len() + 1 + 9 + 1 + events(1)[0].encode_to_vec().len();
+    let read = read_log(&log[..flushed_len]);
+    assert_eq!(read.len(), 1);
+    assert_eq!(read[0].as_ref().unwrap(), &events(1)[0]);
+}
+
+#[test]
+fn truncated_last_block_is_reported() {
+    let log = write_log(&events(10), 0);
+    let read = read_log(&log[..log.len() - 3]);
+
+    assert_eq!(read.len(), 10);
+    assert!(read[..9].iter().all(Result::is_ok));
+    assert!(matches!(read[9], Err(RecordError::Truncated)), "{:?}", read[9]);
+}
+
+#[test]
+fn corrupted_block_fails_its_checksum() {
+    let mut log = write_log(&events(10), 128);
+    let last = log.len() - 1;
+    log[last] ^= 0xFF;
+
+    let read = read_log(&log);
+    assert!(matches!(read.last(), Some(Err(RecordError::Corrupt(_)))), "{:?}", read.last());
+    assert!(read[..read.len() - 1].iter().all(Result::is_ok));
+}
+
+#[test]
+fn other_files_are_rejected() {
+    assert!(matches!(Reader::new(&b"PROTOBUF\x01"[..]), Err(RecordError::Corrupt(_))));
+    assert!(matches!(Reader::new(&b"PROTOREC\x09"[..]), Err(RecordError::Corrupt(_))));
+    assert!(matches!(Reader::new(&b"PROTO"[..]), Err(RecordError::Truncated)));
+}
+
+#[cfg(feature = "records-zstd")]
+#[test]
+fn zstd_blocks_round_trip_and_shrink_the_log() {
+    let written = events(500);
+    let plain = write_log(&written, 4096);
+
+    let mut writer = Writer::new(Vec::new()).expect("header is written").block_size(4096).zstd(3);
+    for event in &written {
+        writer.write(event).expect("record is written");
+    }
+    let compressed = writer.finish().expect("log is flushed");
+
+    assert!(compressed.len() < plain.len() / 2, "{} vs {}", compressed.len(), plain.len());
+    let read: Vec<Event> = read_log(&compressed).into_iter().map(|event| event.expect("record decodes")).collect();
+    assert_eq!(read, written);
+}
@@ -0,0 +1,103 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::DecodeError;
+use proto_rs::ProtoDecode;
+use proto_rs::bus::BusChannel;
+use proto_rs::bus::BusDispatcher;
+use proto_rs::bus::RequestReply;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tokio_stream::Stream;
+use tokio_stream::StreamExt;
+use tonic::Code;
+use tonic::Extensions;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::BoxStream;
+use tonic::codegen::Bytes;
+
+fn validate_reading(reading: &mut Reading, _ext: &Extensions) -> Result<(), DecodeError> {
+    if reading.celsius < -273 {
+        return Err(DecodeError::new("below absolute zero"));
+    }
+    Ok(())
+}
+
+#[proto_message]
+#[proto(response_validator_with_ext = validate_reading)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Reading {
+    pub celsius: i32,
+}
+
+#[proto_rpc(rpc_package = "response_validation", rpc_server = true, rpc_client = true)]
+pub trait Sensor {
+    type HistoryStream: Stream<Item = Result<Reading, Status>> + Send;
+
+    async fn current(&self, request: Request<Reading>) -> Result<Response<Reading>, Status>;
+
+    async fn history(&self, request: Request<Reading>) -> Result<Response<Self::HistoryStream>, Status>;
+}
+
+/// A misbehaving server that echoes whatever it is sent, valid or not.
+struct EchoSensor;
+
+impl Sensor for EchoSensor {
+    type HistoryStream = BoxStream<Reading>;
+
+    async fn current(&self, request: Request<Reading>) -> Result<Response<Reading>, Status> {
+        Ok(Response::new(request.into_inner()))
+    }
+
+    async fn history(&self, request: Request<Reading>) -> Result<Response<Self::HistoryStream>, Status> {
+        let last = request.into_inner();
+        let readings = vec![Ok(Reading { celsius: 20 }), Ok(last)];
+        Ok(Response::new(Box::pin(tokio_stream::iter(readings))))
+    }
+}
+
+#[derive(Clone)]
+struct LocalBus(BusDispatcher<sensor_server::SensorServer<EchoSensor>>);
+
+impl RequestReply for LocalBus {
+    async fn request(&self, subject: String, payload: Bytes) -> Result<Bytes, Status> {
+        Ok(self.0.dispatch(&subject, payload).await)
+    }
+}
+
+fn client() -> sensor_client::SensorClient<BusChannel<LocalBus>> {
+    let dispatcher = BusDispatcher::new(sensor_server::SensorServer::new(EchoSensor));
+    sensor_client::SensorClient::new(BusChannel::new(LocalBus(dispatcher)))
+}
+
+#[test]
+fn response_validation_flag_is_enabled() {
+    const _: () = {
+        assert!(<Reading as ProtoDecode>::VALIDATE_RESPONSE_WITH_EXT);
+        assert!(!<Reading as ProtoDecode>::VALIDATE_WITH_EXT);
+    };
+}
+
+#[tokio::test]
+async fn client_rejects_invalid_unary_response() {
+    let mut client = client();
+    let ok = client.current(Reading { celsius: 21 }).await.expect("valid reading");
+    assert_eq!(ok.into_inner().celsius, 21);
+
+    let status = client.current(Reading { celsius: -300 }).await.expect_err("invalid reading is rejected");
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert!(status.message().contains("below absolute zero"));
+}
+
+#[tokio::test]
+async fn client_rejects_invalid_stream_items() {
+    let mut client = client();
+    let mut stream = client.history(Reading { celsius: -300 }).await.expect("stream opens").into_inner();
+    assert_eq!(
+        stream.next().await.expect("first item").expect("valid item"),
+        Reading { celsius: 20 }
+    );
+    let status = stream.next().await.expect("second item").expect_err("invalid item is rejected");
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
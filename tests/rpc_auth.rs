@@ -0,0 +1,148 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::auth::AuthProvider;
+use proto_rs::auth::BearerAuth;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tokio_stream::Stream;
+use tokio_stream::StreamExt;
+use tonic::Code;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::BoxStream;
+use tonic::codegen::http::HeaderMap;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Secret {
+    pub value: String,
+}
+
+#[proto_rpc(rpc_package = "auth_rpc", rpc_server = true, rpc_client = true, auth = "bearer")]
+pub trait Vault {
+    type ListStream: Stream<Item = Result<Secret, Status>> + Send;
+
+    async fn read(&self, request: Request<Secret>) -> Result<Response<Secret>, Status>;
+
+    async fn list(&self, request: Request<Secret>) -> Result<Response<Self::ListStream>, Status>;
+}
+
+#[proto_rpc(rpc_package = "auth_rpc", rpc_server = true, rpc_client = true, auth = "provider")]
+pub trait KeyedVault {
+    async fn read(&self, request: Request<Secret>) -> Result<Response<Secret>, Status>;
+}
+
+struct VaultService;
+
+impl BearerAuth for VaultService {
+    fn verify_token(&self, token: &str) -> bool {
+        token == "s3cret"
+    }
+}
+
+impl AuthProvider for VaultService {
+    fn authenticate(&self, headers: &HeaderMap) -> bool {
+        headers.get("authorization").is_some_and(|value| value == "ApiKey k3y")
+    }
+}
+
+impl Vault for VaultService {
+    type ListStream = BoxStream<Secret>;
+
+    async fn read(&self, request: Request<Secret>) -> Result<Response<Secret>, Status> {
+        Ok(Response::new(request.into_inner()))
+    }
+
+    async fn list(&self, request: Request<Secret>) -> Result<Response<Self::ListStream>, Status> {
+        let value = request.into_inner().value;
+        let stream = tokio_stream::iter(vec![Ok(Secret { value: value.clone() }), Ok(Secret { value })]);
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+impl KeyedVault for VaultService {
+    async fn read(&self, request: Request<Secret>) -> Result<Response<Secret>, Status> {
+        Ok(Response::new(request.into_inner()))
+    }
+}
+
+async fn spawn_server() -> (
+    std::net::SocketAddr,
+    tokio::sync::oneshot::Sender<()>,
+    tokio::task::JoinHandle<Result<(), tonic::transport::Error>>,
+) {
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+    let incoming = TcpListenerStream::new(listener);
+
+    let handle = tokio::spawn(async move {
+        Server::builder()
+            .add_service(vault_server::VaultServer::new(VaultService))
+            .add_service(keyed_vault_server::KeyedVaultServer::new(VaultService))
+            .serve_with_incoming_shutdown(incoming, async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+    });
+
+    (addr, shutdown_tx, handle)
+}
+
+fn secret() -> Secret {
+    Secret { value: "gold".to_string() }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bearer_token_is_injected_and_verified() {
+    let (addr, shutdown, handle) = spawn_server().await;
+
+    let mut client = vault_client::VaultClient::connect(format!("http://{addr}")).await.unwrap().with_bearer_token("s3cret").unwrap();
+    assert_eq!(client.read(secret()).await.unwrap().into_inner(), secret());
+    let values: Vec<_> = client.list(secret()).await.unwrap().into_inner().map(|item| item.unwrap()).collect().await;
+    assert_eq!(values, vec![secret(), secret()]);
+
+    shutdown.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn missing_or_wrong_token_is_unauthenticated() {
+    let (addr, shutdown, handle) = spawn_server().await;
+
+    let mut anonymous = vault_client::VaultClient::connect(format!("http://{addr}")).await.unwrap();
+    assert_eq!(anonymous.read(secret()).await.unwrap_err().code(), Code::Unauthenticated);
+    let Err(status) = anonymous.list(secret()).await else {
+        panic!("stream opened without a token");
+    };
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    let mut wrong = vault_client::VaultClient::connect(format!("http://{addr}")).await.unwrap().with_bearer_token("guess").unwrap();
+    assert_eq!(wrong.read(secret()).await.unwrap_err().code(), Code::Unauthenticated);
+
+    shutdown.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn provider_inspects_headers() {
+    let (addr, shutdown, handle) = spawn_server().await;
+
+    let mut client = keyed_vault_client::KeyedVaultClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap()
+        .with_authorization("ApiKey k3y".parse().unwrap());
+    assert_eq!(client.read(secret()).await.unwrap().into_inner(), secret());
+
+    let mut bearer =
+        keyed_vault_client::KeyedVaultClient::connect(format!("http://{addr}")).await.unwrap().with_bearer_token("k3y").unwrap();
+    assert_eq!(bearer.read(secret()).await.unwrap_err().code(), Code::Unauthenticated);
+
+    shutdown.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+}
@@ -0,0 +1,104 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::bus::BusChannel;
+use proto_rs::bus::BusDispatcher;
+use proto_rs::bus::RequestReply;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tokio_stream::Stream;
+use tokio_stream::StreamExt;
+use tonic::Code;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::BoxStream;
+use tonic::codegen::Bytes;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Order {
+    pub id: u64,
+    pub quantity: u32,
+}
+
+#[proto_rpc(rpc_package = "bus_rpc", rpc_server = true, rpc_client = true)]
+pub trait Orders {
+    type SplitStream: Stream<Item = Result<Order, Status>> + Send;
+
+    async fn place(&self, request: Request<Order>) -> Result<Response<Order>, Status>;
+
+    async fn split(&self, request: Request<Order>) -> Result<Response<Self::SplitStream>, Status>;
+}
+
+struct OrderDesk;
+
+impl Orders for OrderDesk {
+    type SplitStream = BoxStream<Order>;
+
+    async fn place(&self, request: Request<Order>) -> Result<Response<Order>, Status> {
+        let order = request.into_inner();
+        if order.quantity == 0 {
+            return Err(Status::invalid_argument("empty order"));
+        }
+        Ok(Response::new(Order {
+            id: order.id + 1000,
+            ..order
+        }))
+    }
+
+    async fn split(&self, request: Request<Order>) -> Result<Response<Self::SplitStream>, Status> {
+        let order = request.into_inner();
+        let parts = (0..order.quantity).map(move |_| Ok(Order { id: order.id, quantity: 1 }));
+        Ok(Response::new(Box::pin(tokio_stream::iter(parts.collect::<Vec<_>>()))))
+    }
+}
+
+/// Stands in for a NATS connection: requests are handed straight to the subscriber's dispatcher.
+#[derive(Clone)]
+struct LocalBus(BusDispatcher<orders_server::OrdersServer<OrderDesk>>);
+
+impl RequestReply for LocalBus {
+    async fn request(&self, subject: String, payload: Bytes) -> Result<Bytes, Status> {
+        Ok(self.0.dispatch(&subject, payload).await)
+    }
+}
+
+fn client() -> orders_client::OrdersClient<BusChannel<LocalBus>> {
+    let dispatcher = BusDispatcher::new(orders_server::OrdersServer::new(OrderDesk));
+    orders_client::OrdersClient::new(BusChannel::new(LocalBus(dispatcher)))
+}
+
+#[test]
+fn subjects_follow_routes() {
+    assert_eq!(orders_server::SUBJECTS, ["bus_rpc.Orders.Place", "bus_rpc.Orders.Split"]);
+    let dispatcher = BusDispatcher::new(orders_server::OrdersServer::new(OrderDesk));
+    assert_eq!(dispatcher.subscription_subject(), "bus_rpc.Orders.*");
+}
+
+#[tokio::test]
+async fn unary_calls_travel_over_the_bus() {
+    let mut client = client();
+    let reply = client.place(Order { id: 1, quantity: 3 }).await.unwrap().into_inner();
+    assert_eq!(reply, Order { id: 1001, quantity: 3 });
+
+    let err = client.place(Order { id: 1, quantity: 0 }).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert_eq!(err.message(), "empty order");
+}
+
+#[tokio::test]
+async fn finite_streams_are_collected_into_one_reply() {
+    let mut client = client();
+    let parts: Vec<_> = client.split(Order { id: 7, quantity: 3 }).await.unwrap().into_inner().map(|part| part.unwrap()).collect().await;
+    assert_eq!(parts, vec![Order { id: 7, quantity: 1 }; 3]);
+}
+
+#[tokio::test]
+async fn unknown_subjects_are_unimplemented() {
+    let dispatcher = BusDispatcher::new(orders_server::OrdersServer::new(OrderDesk));
+    for subject in ["bus_rpc.Orders.Cancel", "no-dots"] {
+        let reply = dispatcher.dispatch(subject, Bytes::new()).await;
+        let code = i32::from_be_bytes(reply[..4].try_into().unwrap());
+        assert_eq!(Code::from_i32(code), Code::Unimplemented, "{subject}");
+    }
+}
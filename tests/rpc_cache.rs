@@ -0,0 +1,217 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use proto_rs::auth::BearerAuth;
+use proto_rs::auth::bearer_value;
+use proto_rs::cache::CacheStats;
+use proto_rs::cache::RequestKey;
+use proto_rs::cache::ResponseCache;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct PriceQuery {
+    pub sku: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct PriceReply {
+    pub cents: u64,
+    pub computed: u64,
+}
+
+#[proto_rpc(rpc_package = "cache_rpc", rpc_server = true, rpc_client = true)]
+pub trait Pricing {
+    #[proto(cache(ttl = "200ms", key = "request"))]
+    async fn price(&self, request: Request<PriceQuery>) -> Result<Response<PriceReply>, Status>;
+
+    async fn uncached(&self, request: Request<PriceQuery>) -> Result<Response<PriceReply>, Status>;
+}
+
+#[proto_rpc(rpc_package = "cache_rpc", rpc_server = true, rpc_client = true, auth = "bearer")]
+pub trait AccountPricing {
+    #[proto(cache(ttl = "10s", vary = "x-region"))]
+    async fn price(&self, request: Request<PriceQuery>) -> Result<Response<PriceReply>, Status>;
+}
+
+#[derive(Default)]
+struct PricingService {
+    calls: AtomicUsize,
+}
+
+impl PricingService {
+    fn compute(&self, sku: &str) -> Result<Response<PriceReply>, Status> {
+        let computed = self.calls.fetch_add(1, Ordering::SeqCst) as u64 + 1;
+        if sku.is_empty() {
+            return Err(Status::invalid_argument("empty sku"));
+        }
+        Ok(Response::new(PriceReply {
+            cents: sku.len() as u64 * 100,
+            computed,
+        }))
+    }
+}
+
+impl Pricing for PricingService {
+    async fn price(&self, request: Request<PriceQuery>) -> Result<Response<PriceReply>, Status> {
+        self.compute(&request.into_inner().sku)
+    }
+
+    async fn uncached(&self, request: Request<PriceQuery>) -> Result<Response<PriceReply>, Status> {
+        self.compute(&request.into_inner().sku)
+    }
+}
+
+impl BearerAuth for PricingService {
+    fn verify_token(&self, token: &str) -> bool {
+        matches!(token, "alice" | "bob")
+    }
+}
+
+impl AccountPricing for PricingService {
+    async fn price(&self, request: Request<PriceQuery>) -> Result<Response<PriceReply>, Status> {
+        self.compute(&request.into_inner().sku)
+    }
+}
+
+const PRICE_ROUTE: &str = "/cache_rpc.Pricing/Price";
+
+fn query(sku: &str) -> PriceQuery {
+    PriceQuery { sku: sku.to_string() }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cached_method_serves_repeated_requests() {
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    let service = Arc::new(PricingService::default());
+    let server = pricing_server::PricingServer::from_arc(service.clone());
+    let cache = server.response_cache().clone();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let incoming = TcpListenerStream::new(listener);
+    let handle = tokio::spawn(async move {
+        Server::builder()
+            .add_service(server)
+            .serve_with_incoming_shutdown(incoming, async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+    });
+
+    let mut client = pricing_client::PricingClient::connect(format!("http://{addr}")).await.unwrap();
+
+    let first = client.price(query("abc")).await.unwrap().into_inner();
+    let second = client.price(query("abc")).await.unwrap().into_inner();
+    assert_eq!(first, PriceReply { cents: 300, computed: 1 });
+    assert_eq!(second, first);
+    assert_eq!(cache.stats(PRICE_ROUTE), CacheStats { hits: 1, misses: 1 });
+
+    // A different request is a different key.
+    assert_eq!(client.price(query("ab")).await.unwrap().into_inner().computed, 2);
+
+    // Errors are not cached.
+    assert!(client.price(query("")).await.is_err());
+    assert!(client.price(query("")).await.is_err());
+    assert_eq!(service.calls.load(Ordering::SeqCst), 4);
+
+    // Methods without the attribute always reach the handler.
+    assert_eq!(client.uncached(query("abc")).await.unwrap().into_inner().computed, 5);
+    assert_eq!(client.uncached(query("abc")).await.unwrap().into_inner().computed, 6);
+
+    // Entries expire after the ttl.
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(client.price(query("abc")).await.unwrap().into_inner().computed, 7);
+    assert_eq!(cache.stats(PRICE_ROUTE), CacheStats { hits: 1, misses: 5 });
+
+    shutdown_tx.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cached_responses_are_kept_per_caller() {
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    let service = Arc::new(PricingService::default());
+    let server = account_pricing_server::AccountPricingServer::from_arc(service.clone());
+    let cache = server.response_cache().clone();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let incoming = TcpListenerStream::new(listener);
+    let handle = tokio::spawn(async move {
+        Server::builder()
+            .add_service(server)
+            .serve_with_incoming_shutdown(incoming, async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+    });
+
+    let mut client = account_pricing_client::AccountPricingClient::connect(format!("http://{addr}")).await.unwrap();
+    let request = |token: &str, region: Option<&str>| {
+        let mut request = Request::new(query("abc"));
+        request.metadata_mut().insert("authorization", bearer_value(token).unwrap());
+        if let Some(region) = region {
+            request.metadata_mut().insert("x-region", region.parse().unwrap());
+        }
+        request
+    };
+
+    assert_eq!(client.price(request("alice", None)).await.unwrap().into_inner().computed, 1);
+    assert_eq!(client.price(request("alice", None)).await.unwrap().into_inner().computed, 1);
+    // Another caller never sees alice's response.
+    assert_eq!(client.price(request("bob", None)).await.unwrap().into_inner().computed, 2);
+    // Metadata listed in `vary` is part of the key.
+    assert_eq!(client.price(request("alice", Some("eu"))).await.unwrap().into_inner().computed, 3);
+    assert_eq!(client.price(request("alice", Some("eu"))).await.unwrap().into_inner().computed, 3);
+    assert_eq!(cache.stats("/cache_rpc.AccountPricing/Price"), CacheStats { hits: 2, misses: 3 });
+    assert_eq!(cache.len(), 3);
+
+    shutdown_tx.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+}
+
+#[test]
+fn cache_compares_full_keys_and_evicts_the_oldest_entry() {
+    let cache = ResponseCache::default();
+    let ttl = Duration::from_secs(10);
+    let key = |request: &[u8]| RequestKey::new(PRICE_ROUTE, request.to_vec());
+
+    cache.insert(key(b"a"), 1_u32, ttl);
+    cache.insert(key(b"a").with_principal("bob"), 2_u32, ttl);
+    assert_eq!(cache.get::<u32>(&key(b"a")), Some(1));
+    assert_eq!(cache.get::<u32>(&key(b"a").with_principal("bob")), Some(2));
+    assert_eq!(cache.get::<u32>(&key(b"a").with_metadata(None)), None);
+
+    cache.set_max_entries(2);
+    cache.insert(key(b"b"), 3_u32, ttl);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get::<u32>(&key(b"a")), None);
+    assert_eq!(cache.get::<u32>(&key(b"b")), Some(3));
+
+    // Replacing an entry does not evict another one.
+    cache.insert(key(b"b"), 4_u32, ttl);
+    assert_eq!(cache.get::<u32>(&key(b"a").with_principal("bob")), Some(2));
+    assert_eq!(cache.get::<u32>(&key(b"b")), Some(4));
+
+    cache.set_max_entries(1);
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.get::<u32>(&key(b"b")), Some(4));
+}
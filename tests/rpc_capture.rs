@@ -0,0 +1,159 @@
+#![cfg(feature = "capture")]
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::capture::Capture;
+use proto_rs::capture::Corpus;
+use proto_rs::capture::Replayed;
+use proto_rs::dispatch::DispatchTable;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tokio_stream::Stream;
+use tokio_stream::StreamExt;
+use tonic::Code;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::BoxStream;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Order {
+    pub id: u64,
+    pub quantity: u32,
+}
+
+#[proto_rpc(rpc_package = "capture_rpc", rpc_server = true, rpc_client = true)]
+pub trait Orders {
+    type SplitStream: Stream<Item = Result<Order, Status>> + Send;
+
+    async fn place(&self, request: Request<Order>) -> Result<Response<Order>, Status>;
+
+    async fn split(&self, request: Request<Order>) -> Result<Response<Self::SplitStream>, Status>;
+}
+
+/// Adds `markup` to the id of every placed order.
+struct OrderDesk {
+    markup: u64,
+}
+
+impl Orders for OrderDesk {
+    type SplitStream = BoxStream<Order>;
+
+    async fn place(&self, request: Request<Order>) -> Result<Response<Order>, Status> {
+        let order = request.into_inner();
+        if order.quantity == 0 {
+            return Err(Status::invalid_argument("empty order"));
+        }
+        Ok(Response::new(Order {
+            id: order.id + self.markup,
+            ..order
+        }))
+    }
+
+    async fn split(&self, request: Request<Order>) -> Result<Response<Self::SplitStream>, Status> {
+        let order = request.into_inner();
+        let parts = (0..order.quantity).map(move |_| Ok(Order { id: order.id, quantity: 1 }));
+        Ok(Response::new(Box::pin(tokio_stream::iter(parts.collect::<Vec<_>>()))))
+    }
+}
+
+#[derive(Clone, Default)]
+struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn decode(bytes: &[u8]) -> Order {
+    Order::decode(bytes, proto_rs::DecodeContext::default()).unwrap()
+}
+
+// Captures are process-wide, so one test covers capture and replay.
+#[tokio::test(flavor = "multi_thread")]
+async fn captured_traffic_replays_against_a_service() {
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let incoming = TcpListenerStream::new(listener);
+    let handle = tokio::spawn(async move {
+        Server::builder()
+            .add_service(orders_server::OrdersServer::new(OrderDesk { markup: 1000 }))
+            .serve_with_incoming_shutdown(incoming, async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+    });
+    let mut client = orders_client::OrdersClient::connect(format!("http://{addr}")).await.unwrap();
+
+    // Traffic before the capture is not recorded.
+    client.place(Order { id: 1, quantity: 1 }).await.unwrap();
+
+    let file = SharedBuf::default();
+    let capture = Capture::start(file.clone()).unwrap();
+    assert_eq!(client.place(Order { id: 2, quantity: 3 }).await.unwrap().into_inner().id, 1002);
+    let parts: Vec<_> = client.split(Order { id: 3, quantity: 2 }).await.unwrap().into_inner().collect().await;
+    assert_eq!(parts.len(), 2);
+    assert_eq!(
+        client.place(Order { id: 4, quantity: 0 }).await.unwrap_err().code(),
+        Code::InvalidArgument
+    );
+    capture.stop().unwrap();
+
+    // Nor is traffic after it.
+    client.place(Order { id: 5, quantity: 1 }).await.unwrap();
+
+    let corpus = Corpus::read(file.0.lock().unwrap().as_slice()).unwrap();
+    let routes: Vec<_> = corpus.calls().iter().map(|call| call.route.as_str()).collect();
+    assert_eq!(
+        routes,
+        [
+            "/capture_rpc.Orders/Place",
+            "/capture_rpc.Orders/Split",
+            "/capture_rpc.Orders/Place"
+        ]
+    );
+    let [place, split, rejected] = corpus.calls() else {
+        panic!("expected three calls");
+    };
+    assert_eq!(place.requests, [Order { id: 2, quantity: 3 }.encode_to_vec()]);
+    assert_eq!(decode(&place.responses[0]), Order { id: 1002, quantity: 3 });
+    assert_eq!(
+        split.responses.iter().map(|bytes| decode(bytes)).collect::<Vec<_>>(),
+        vec![Order { id: 3, quantity: 1 }; 2]
+    );
+    assert_eq!(rejected.requests.len(), 1);
+    assert!(rejected.responses.is_empty());
+    assert!(place.timestamp_micros > 0 && place.timestamp_micros <= rejected.timestamp_micros);
+
+    // The same implementation reproduces every captured call.
+    let table = DispatchTable::new().with(orders_server::OrdersServer::new(OrderDesk { markup: 1000 }));
+    let replayed = corpus.replay(&table).await;
+    assert!(replayed.iter().all(Replayed::matches));
+    assert_eq!(replayed[2].result.as_ref().unwrap_err().code(), Code::InvalidArgument);
+
+    // A changed one diverges where its responses differ.
+    let table = DispatchTable::new().with(orders_server::OrdersServer::new(OrderDesk { markup: 2000 }));
+    let replayed = corpus.replay(&table).await;
+    assert_eq!(replayed.iter().map(Replayed::matches).collect::<Vec<_>>(), [false, true, true]);
+    assert_eq!(decode(&replayed[0].result.as_ref().unwrap()[0]).id, 2002);
+
+    shutdown_tx.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+}
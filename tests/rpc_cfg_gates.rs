@@ -0,0 +1,55 @@
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct PriceRequest {
+    pub sku: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct PriceReply {
+    pub cents: u64,
+}
+
+// `any()` never holds and `all()` always does, standing in for `feature = "server"` / `feature = "client"`.
+#[proto_rpc(
+    rpc_package = "pricing_rpc",
+    rpc_server = true,
+    rpc_client = true,
+    rpc_server_cfg = "any()",
+    rpc_client_cfg = "all()"
+)]
+pub trait PricingService {
+    async fn price(&self, request: Request<PriceRequest>) -> Result<Response<PriceReply>, Status>;
+}
+
+/// Would clash with the generated server module if it had been emitted.
+mod pricing_service_server {
+    pub static ORIGIN: &str = "hand-written";
+}
+
+struct FixedPrice;
+
+impl pricing_service_client::PricingServiceClientApi for FixedPrice {
+    async fn price(&mut self, _request: Request<PriceRequest>) -> Result<Response<PriceReply>, Status> {
+        Ok(Response::new(PriceReply { cents: 499 }))
+    }
+}
+
+#[test]
+fn server_module_is_compiled_out() {
+    assert_eq!(pricing_service_server::ORIGIN, "hand-written");
+}
+
+#[tokio::test]
+async fn client_module_is_kept() {
+    use pricing_service_client::PricingServiceClientApi;
+
+    let reply = FixedPrice.price(Request::new(PriceRequest { sku: "A-1".to_string() })).await.unwrap();
+    assert_eq!(reply.into_inner().cents, 499);
+}
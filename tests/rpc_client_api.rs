@@ -0,0 +1,119 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tokio_stream::Stream;
+use tokio_stream::StreamExt;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::BoxStream;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Ticker {
+    pub symbol: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Quote {
+    pub price: u64,
+}
+
+#[proto_rpc(rpc_package = "quotes_rpc", rpc_server = true, rpc_client = true)]
+pub trait QuoteService {
+    type WatchStream: Stream<Item = Result<Quote, Status>> + Send;
+
+    async fn latest(&self, request: Request<Ticker>) -> Result<Response<Quote>, Status>;
+
+    async fn watch(&self, request: Request<Ticker>) -> Result<Response<Self::WatchStream>, Status>;
+}
+
+use quote_service_client::QuoteServiceClientApi;
+
+/// Application code that only knows about the client trait.
+async fn average_price(api: &mut impl QuoteServiceClientApi, symbol: &str) -> Result<u64, Status> {
+    let latest = api
+        .latest(Request::new(Ticker {
+            symbol: symbol.to_string(),
+        }))
+        .await?
+        .into_inner();
+    let mut stream = api
+        .watch(Request::new(Ticker {
+            symbol: symbol.to_string(),
+        }))
+        .await?
+        .into_inner();
+
+    let mut total = latest.price;
+    let mut count = 1;
+    while let Some(quote) = stream.next().await {
+        total += quote?.price;
+        count += 1;
+    }
+    Ok(total / count)
+}
+
+struct MockQuotes;
+
+impl QuoteServiceClientApi for MockQuotes {
+    async fn latest(&mut self, _request: Request<Ticker>) -> Result<Response<Quote>, Status> {
+        Ok(Response::new(Quote { price: 10 }))
+    }
+
+    async fn watch(&mut self, _request: Request<Ticker>) -> Result<Response<BoxStream<Quote>>, Status> {
+        let stream = tokio_stream::iter(vec![Ok(Quote { price: 20 }), Ok(Quote { price: 30 })]);
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+struct Quotes;
+
+impl QuoteService for Quotes {
+    type WatchStream = BoxStream<Quote>;
+
+    async fn latest(&self, request: Request<Ticker>) -> Result<Response<Quote>, Status> {
+        Ok(Response::new(Quote {
+            price: request.into_inner().symbol.len() as u64,
+        }))
+    }
+
+    async fn watch(&self, _request: Request<Ticker>) -> Result<Response<Self::WatchStream>, Status> {
+        let stream = tokio_stream::iter(vec![Ok(Quote { price: 4 }), Ok(Quote { price: 5 })]);
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[tokio::test]
+async fn client_trait_accepts_mock() {
+    assert_eq!(average_price(&mut MockQuotes, "ABC").await.unwrap(), 20);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn client_trait_accepts_real_client() {
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let incoming = TcpListenerStream::new(listener);
+
+    let handle = tokio::spawn(async move {
+        Server::builder()
+            .add_service(quote_service_server::QuoteServiceServer::new(Quotes))
+            .serve_with_incoming_shutdown(incoming, async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+    });
+
+    let mut client = quote_service_client::QuoteServiceClient::connect(format!("http://{addr}")).await.unwrap();
+    assert_eq!(average_price(&mut client, "ABC").await.unwrap(), 4);
+
+    shutdown_tx.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+}
@@ -0,0 +1,135 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::task::Context;
+use std::task::Poll;
+
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use proto_rs::transport::TokioIo;
+use tokio::net::TcpStream;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::BoxFuture;
+use tonic::codegen::Service;
+use tonic::codegen::http::Uri;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct EchoMessage {
+    pub text: String,
+}
+
+#[proto_rpc(rpc_package = "connectors_rpc", rpc_server = true, rpc_client = true)]
+pub trait Echo {
+    async fn echo(&self, request: Request<EchoMessage>) -> Result<Response<EchoMessage>, Status>;
+}
+
+struct EchoService;
+
+impl Echo for EchoService {
+    async fn echo(&self, request: Request<EchoMessage>) -> Result<Response<EchoMessage>, Status> {
+        Ok(Response::new(request.into_inner()))
+    }
+}
+
+/// Dials a fixed address regardless of the endpoint uri and counts connections.
+#[derive(Clone)]
+struct CountingConnector {
+    addr: std::net::SocketAddr,
+    dials: Arc<AtomicUsize>,
+}
+
+impl Service<Uri> for CountingConnector {
+    type Response = TokioIo<TcpStream>;
+    type Error = std::io::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _uri: Uri) -> Self::Future {
+        self.dials.fetch_add(1, Ordering::SeqCst);
+        let addr = self.addr;
+        Box::pin(async move { TcpStream::connect(addr).await.map(TokioIo::new) })
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread")]
+async fn client_connects_over_unix_socket() {
+    use tokio::net::UnixListener;
+    use tokio_stream::wrappers::UnixListenerStream;
+    use tonic::transport::Server;
+
+    let dir = std::env::temp_dir().join(format!("proto_rs_uds_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("echo.sock");
+    let _ = std::fs::remove_file(&path);
+
+    let listener = UnixListener::bind(&path).unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let incoming = UnixListenerStream::new(listener);
+
+    let handle = tokio::spawn(async move {
+        Server::builder()
+            .add_service(echo_server::EchoServer::new(EchoService))
+            .serve_with_incoming_shutdown(incoming, async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+    });
+
+    let mut client = echo_client::EchoClient::connect_uds(&path).await.unwrap();
+    let reply = client.echo(EchoMessage { text: "uds".to_string() }).await.unwrap().into_inner();
+    assert_eq!(reply.text, "uds");
+
+    shutdown_tx.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn client_uses_custom_connector() {
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let incoming = TcpListenerStream::new(listener);
+
+    let handle = tokio::spawn(async move {
+        Server::builder()
+            .add_service(echo_server::EchoServer::new(EchoService))
+            .serve_with_incoming_shutdown(incoming, async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+    });
+
+    let dials = Arc::new(AtomicUsize::new(0));
+    let connector = CountingConnector {
+        addr,
+        dials: dials.clone(),
+    };
+
+    let mut client = echo_client::EchoClient::connect_with("http://echo.invalid", connector).await.unwrap();
+    let reply = client
+        .echo(EchoMessage {
+            text: "custom".to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(reply.text, "custom");
+    assert_eq!(dials.load(Ordering::SeqCst), 1);
+
+    shutdown_tx.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+}
@@ -0,0 +1,227 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use proto_rs::cache::RequestKey;
+use proto_rs::dedup::DedupStats;
+use proto_rs::dedup::InFlight;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tokio::sync::Semaphore;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct QuoteQuery {
+    pub sku: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct QuoteReply {
+    pub cents: u64,
+    pub computed: u64,
+}
+
+#[proto_rpc(rpc_package = "dedup_rpc", rpc_server = true, rpc_client = true)]
+pub trait Quotes {
+    #[proto(idempotent)]
+    async fn quote(&self, request: Request<QuoteQuery>) -> Result<Response<QuoteReply>, Status>;
+
+    async fn fresh(&self, request: Request<QuoteQuery>) -> Result<Response<QuoteReply>, Status>;
+}
+
+/// Handlers block until the test hands out a permit, so identical calls overlap.
+struct QuoteService {
+    calls: AtomicU64,
+    gate: Semaphore,
+}
+
+impl QuoteService {
+    async fn compute(&self, sku: &str) -> Result<Response<QuoteReply>, Status> {
+        let computed = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        self.gate.acquire().await.unwrap().forget();
+        if sku.is_empty() {
+            return Err(Status::invalid_argument("empty sku"));
+        }
+        Ok(Response::new(QuoteReply {
+            cents: sku.len() as u64 * 100,
+            computed,
+        }))
+    }
+}
+
+impl Quotes for QuoteService {
+    async fn quote(&self, request: Request<QuoteQuery>) -> Result<Response<QuoteReply>, Status> {
+        self.compute(&request.into_inner().sku).await
+    }
+
+    async fn fresh(&self, request: Request<QuoteQuery>) -> Result<Response<QuoteReply>, Status> {
+        self.compute(&request.into_inner().sku).await
+    }
+}
+
+const QUOTE_ROUTE: &str = "/dedup_rpc.Quotes/Quote";
+
+fn query(sku: &str) -> QuoteQuery {
+    QuoteQuery { sku: sku.to_string() }
+}
+
+async fn wait_for_calls(service: &QuoteService, calls: u64) {
+    while service.calls.load(Ordering::SeqCst) < calls {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    // Let the identical requests reach the generated server and start waiting.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn idempotent_method_shares_concurrent_identical_calls() {
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    let service = Arc::new(QuoteService {
+        calls: AtomicU64::new(0),
+        gate: Semaphore::new(0),
+    });
+    let server = quotes_server::QuotesServer::from_arc(service.clone());
+    let in_flight = server.in_flight().clone();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let incoming = TcpListenerStream::new(listener);
+    let handle = tokio::spawn(async move {
+        Server::builder()
+            .add_service(server)
+            .serve_with_incoming_shutdown(incoming, async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+    });
+
+    let client = quotes_client::QuotesClient::connect(format!("http://{addr}")).await.unwrap();
+    let spawn_quote = |sku: &'static str| {
+        let mut client = client.clone();
+        tokio::spawn(async move { client.quote(query(sku)).await.map(Response::into_inner) })
+    };
+
+    // Three identical requests run the handler once.
+    let calls: Vec<_> = (0..3).map(|_| spawn_quote("abc")).collect();
+    wait_for_calls(&service, 1).await;
+    assert_eq!(in_flight.len(), 1);
+    service.gate.add_permits(1);
+    for call in calls {
+        assert_eq!(call.await.unwrap().unwrap(), QuoteReply { cents: 300, computed: 1 });
+    }
+    assert_eq!(in_flight.stats(QUOTE_ROUTE), DedupStats { executed: 1, shared: 2 });
+    assert!(in_flight.is_empty());
+
+    // Nothing is kept once the call completes.
+    service.gate.add_permits(1);
+    assert_eq!(spawn_quote("abc").await.unwrap().unwrap().computed, 2);
+
+    // Different requests are different keys.
+    let calls = [spawn_quote("ab"), spawn_quote("abcd")];
+    wait_for_calls(&service, 4).await;
+    assert_eq!(in_flight.len(), 2);
+    service.gate.add_permits(2);
+    for call in calls {
+        call.await.unwrap().unwrap();
+    }
+
+    // Errors are shared as well.
+    let calls = [spawn_quote(""), spawn_quote("")];
+    wait_for_calls(&service, 5).await;
+    service.gate.add_permits(1);
+    for call in calls {
+        assert_eq!(call.await.unwrap().unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+    assert_eq!(in_flight.stats(QUOTE_ROUTE), DedupStats { executed: 5, shared: 3 });
+
+    // Methods without the attribute always reach the handler.
+    let calls: Vec<_> = (0..2)
+        .map(|_| {
+            let mut client = client.clone();
+            tokio::spawn(async move { client.fresh(query("abc")).await.map(Response::into_inner) })
+        })
+        .collect();
+    wait_for_calls(&service, 7).await;
+    service.gate.add_permits(2);
+    for call in calls {
+        call.await.unwrap().unwrap();
+    }
+    assert_eq!(service.calls.load(Ordering::SeqCst), 7);
+
+    shutdown_tx.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn waiting_call_takes_over_a_cancelled_one() {
+    let in_flight = InFlight::default();
+    let gate = Arc::new(Semaphore::new(0));
+
+    let leader = tokio::spawn({
+        let in_flight = in_flight.clone();
+        let gate = gate.clone();
+        async move {
+            in_flight
+                .run(Some(RequestKey::new("/svc/Method", vec![7])), || async move {
+                    gate.acquire().await.unwrap().forget();
+                    "leader"
+                })
+                .await
+        }
+    });
+    while in_flight.is_empty() {
+        tokio::task::yield_now().await;
+    }
+    let follower = tokio::spawn({
+        let in_flight = in_flight.clone();
+        async move { in_flight.run(Some(RequestKey::new("/svc/Method", vec![7])), || async { "follower" }).await }
+    });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    leader.abort();
+    assert!(leader.await.unwrap_err().is_cancelled());
+    assert_eq!(follower.await.unwrap(), "follower");
+    assert_eq!(in_flight.stats("/svc/Method"), DedupStats { executed: 2, shared: 0 });
+    assert!(in_flight.is_empty());
+}
+
+#[tokio::test]
+async fn calls_of_other_callers_are_not_shared() {
+    let in_flight = InFlight::default();
+    let gate = Arc::new(Semaphore::new(0));
+    let key = |principal: &str| Some(RequestKey::new("/svc/Method", vec![7]).with_principal(principal));
+
+    let alice = tokio::spawn({
+        let in_flight = in_flight.clone();
+        let gate = gate.clone();
+        async move {
+            in_flight
+                .run(key("alice"), || async move {
+                    gate.acquire().await.unwrap().forget();
+                    "alice"
+                })
+                .await
+        }
+    });
+    while in_flight.is_empty() {
+        tokio::task::yield_now().await;
+    }
+
+    // Neither another caller nor a call without a key waits for alice's call.
+    assert_eq!(in_flight.run(key("bob"), || async { "bob" }).await, "bob");
+    assert_eq!(in_flight.run(None, || async { "anonymous" }).await, "anonymous");
+    gate.add_permits(1);
+    assert_eq!(alice.await.unwrap(), "alice");
+    assert_eq!(in_flight.stats("/svc/Method"), DedupStats { executed: 2, shared: 0 });
+}
@@ -0,0 +1,99 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::MethodDescriptor;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tokio_stream::Stream;
+use tonic::Response;
+use tonic::Status;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Query {
+    pub key: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Row {
+    pub key: String,
+    pub value: u64,
+}
+
+#[proto_rpc(rpc_package = "descriptor_rpc", rpc_server = true, rpc_client = false)]
+pub trait Catalog {
+    type ScanStream: Stream<Item = Result<Row, Status>> + Send;
+
+    async fn lookup(&self, request: Request<Query>) -> Result<Response<Row>, Status>;
+
+    async fn scan(&self, request: Request<Query>) -> Result<Response<Self::ScanStream>, Status>;
+
+    #[proto(route = "/descriptor_rpc.Catalog/Health")]
+    async fn ping(&self, request: Request<Query>) -> Result<Response<()>, Status>;
+}
+
+#[test]
+fn methods_describe_every_rpc() {
+    assert_eq!(
+        catalog_server::METHODS,
+        [
+            MethodDescriptor {
+                name: "Lookup",
+                route: "/descriptor_rpc.Catalog/Lookup",
+                client_streaming: false,
+                server_streaming: false,
+                request_type: "descriptor_rpc.Query",
+                response_type: "descriptor_rpc.Row",
+            },
+            MethodDescriptor {
+                name: "Scan",
+                route: "/descriptor_rpc.Catalog/Scan",
+                client_streaming: false,
+                server_streaming: true,
+                request_type: "descriptor_rpc.Query",
+                response_type: "descriptor_rpc.Row",
+            },
+            MethodDescriptor {
+                name: "Health",
+                route: "/descriptor_rpc.Catalog/Health",
+                client_streaming: false,
+                server_streaming: false,
+                request_type: "descriptor_rpc.Query",
+                response_type: "google.protobuf.Empty",
+            },
+        ]
+    );
+}
+
+#[test]
+fn server_type_exposes_service_constants() {
+    type Server = catalog_server::CatalogServer<()>;
+    assert_eq!(Server::SERVICE_NAME, "descriptor_rpc.Catalog");
+    assert_eq!(Server::METHODS, catalog_server::METHODS);
+    let ping = MethodDescriptor::by_route(Server::METHODS, "/descriptor_rpc.Catalog/Health").expect("ping is routed");
+    assert_eq!(ping.name, "Health");
+    assert!(ping.is_unary());
+    assert!(MethodDescriptor::by_route(Server::METHODS, "/descriptor_rpc.Catalog/Ping").is_none());
+}
+
+// Private services re-export their aliases only to the enclosing module.
+#[proto_rpc(rpc_package = "descriptor_rpc", rpc_server = true, rpc_client = true)]
+trait Inventory {
+    async fn count(&self, request: Request<Query>) -> Result<Response<u64>, Status>;
+}
+
+fn request_of<Req: Default>() -> Req {
+    Req::default()
+}
+
+#[test]
+fn method_io_aliases_name_the_rpc_types() {
+    let lookup: catalog_types::LookupRequest = request_of();
+    assert_eq!(lookup, Query::default());
+    let row: catalog_server::types::ScanResponse = Row { key: "a".into(), value: 1 };
+    assert_eq!(row.value, 1);
+    let (): catalog_types::HealthResponse = ();
+    let count: inventory_client::types::CountResponse = 3;
+    assert_eq!(count, 3_u64);
+    assert_eq!(request_of::<inventory_server::types::CountRequest>(), Query::default());
+}
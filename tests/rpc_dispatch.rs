@@ -0,0 +1,132 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::bytes::Bytes;
+use proto_rs::dispatch::DispatchReply;
+use proto_rs::dispatch::DispatchTable;
+use proto_rs::dispatch::ServiceDispatcher;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tokio_stream::Stream;
+use tokio_stream::StreamExt;
+use tonic::Code;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::BoxStream;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Order {
+    pub id: u64,
+    pub quantity: u32,
+}
+
+#[proto_rpc(rpc_package = "dispatch_rpc", rpc_server = true)]
+pub trait Orders {
+    type SplitStream: Stream<Item = Result<Order, Status>> + Send;
+
+    async fn place(&self, request: Request<Order>) -> Result<Response<Order>, Status>;
+
+    async fn split(&self, request: Request<Order>) -> Result<Response<Self::SplitStream>, Status>;
+}
+
+struct OrderDesk;
+
+impl Orders for OrderDesk {
+    type SplitStream = BoxStream<Order>;
+
+    async fn place(&self, request: Request<Order>) -> Result<Response<Order>, Status> {
+        let desk = request.metadata().get("desk").map(|value| value.to_str().unwrap().to_string());
+        let order = request.into_inner();
+        if order.quantity == 0 {
+            return Err(Status::invalid_argument("empty order"));
+        }
+        let mut response = Response::new(Order {
+            id: order.id + 1000,
+            ..order
+        });
+        if let Some(desk) = desk {
+            response.metadata_mut().insert("desk", desk.parse().unwrap());
+        }
+        Ok(response)
+    }
+
+    async fn split(&self, request: Request<Order>) -> Result<Response<Self::SplitStream>, Status> {
+        let order = request.into_inner();
+        let parts = (0..order.quantity).map(move |_| Ok(Order { id: order.id, quantity: 1 }));
+        Ok(Response::new(Box::pin(tokio_stream::iter(parts.collect::<Vec<_>>()))))
+    }
+}
+
+fn encoded(order: &Order) -> Request<Bytes> {
+    Request::new(Bytes::from(order.encode_to_vec()))
+}
+
+fn unary(reply: Response<DispatchReply>) -> Order {
+    match reply.into_inner() {
+        DispatchReply::Unary(bytes) => Order::decode(bytes, proto_rs::DecodeContext::default()).unwrap(),
+        DispatchReply::Stream(_) => panic!("expected a unary reply"),
+    }
+}
+
+#[tokio::test]
+async fn dispatches_unary_calls_by_method_name() {
+    let server = orders_server::OrdersServer::new(OrderDesk);
+    assert_eq!(server.service_name(), "dispatch_rpc.Orders");
+    assert_eq!(
+        server.methods().iter().map(|method| method.name).collect::<Vec<_>>(),
+        ["Place", "Split"]
+    );
+
+    let mut request = encoded(&Order { id: 1, quantity: 3 });
+    request.metadata_mut().insert("desk", "north".parse().unwrap());
+    let reply = server.dispatch("Place", request).await.unwrap();
+    assert_eq!(reply.metadata().get("desk").unwrap(), "north");
+    assert_eq!(unary(reply), Order { id: 1001, quantity: 3 });
+
+    let status = server.dispatch("Place", encoded(&Order { id: 1, quantity: 0 })).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn dispatches_streaming_calls() {
+    let server = orders_server::OrdersServer::new(OrderDesk);
+    let reply = server.dispatch("Split", encoded(&Order { id: 7, quantity: 2 })).await.unwrap();
+    let DispatchReply::Stream(stream) = reply.into_inner() else {
+        panic!("expected a stream reply");
+    };
+    let items = stream.map(|item| Order::decode(item.unwrap(), proto_rs::DecodeContext::default()).unwrap()).collect::<Vec<_>>().await;
+    assert_eq!(items, vec![Order { id: 7, quantity: 1 }; 2]);
+}
+
+#[tokio::test]
+async fn rejects_unknown_methods_and_malformed_payloads() {
+    let server = orders_server::OrdersServer::new(OrderDesk);
+    let status = server.dispatch("Cancel", encoded(&Order::default())).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+
+    let status = server.dispatch("Place", Request::new(Bytes::from_static(&[0x08]))).await.unwrap_err();
+    assert_eq!(status.code(), Code::DataLoss);
+}
+
+#[tokio::test]
+async fn table_routes_by_grpc_path() {
+    let table = DispatchTable::new().with(orders_server::OrdersServer::new(OrderDesk));
+    assert_eq!(table.service_names().collect::<Vec<_>>(), ["dispatch_rpc.Orders"]);
+
+    let reply = table.dispatch("/dispatch_rpc.Orders/Place", encoded(&Order { id: 2, quantity: 1 })).await.unwrap();
+    assert_eq!(unary(reply), Order { id: 1002, quantity: 1 });
+
+    let status = table.dispatch("/dispatch_rpc.Refunds/Issue", encoded(&Order::default())).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+}
+
+#[test]
+#[should_panic(expected = "registered twice")]
+fn table_rejects_duplicate_services() {
+    let mut table = DispatchTable::new();
+    table.register(orders_server::OrdersServer::new(OrderDesk));
+    table.register(orders_server::OrdersServer::new(OrderDesk));
+}
@@ -0,0 +1,163 @@
+#![cfg(feature = "build-schemas")]
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::ProtoEncode;
+use proto_rs::forward::ForwardTable;
+use proto_rs::forward::ForwardingService;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tokio_stream::Stream;
+use tokio_stream::StreamExt;
+use tonic::Code;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::BoxStream;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Order {
+    pub id: u64,
+    pub quantity: u32,
+}
+
+#[proto_rpc(rpc_package = "forward_rpc", rpc_server = true, rpc_client = true)]
+pub trait Orders {
+    type SplitStream: Stream<Item = Result<Order, Status>> + Send;
+
+    async fn place(&self, request: Request<Order>) -> Result<Response<Order>, Status>;
+
+    async fn split(&self, request: Request<Order>) -> Result<Response<Self::SplitStream>, Status>;
+}
+
+struct OrderDesk;
+
+impl Orders for OrderDesk {
+    type SplitStream = BoxStream<Order>;
+
+    async fn place(&self, request: Request<Order>) -> Result<Response<Order>, Status> {
+        let order = request.into_inner();
+        if order.quantity == 0 {
+            return Err(Status::invalid_argument("empty order"));
+        }
+        Ok(Response::new(Order {
+            id: order.id + 1000,
+            ..order
+        }))
+    }
+
+    async fn split(&self, request: Request<Order>) -> Result<Response<Self::SplitStream>, Status> {
+        let order = request.into_inner();
+        let parts = (0..order.quantity).map(move |_| Ok(Order { id: order.id, quantity: 1 }));
+        Ok(Response::new(Box::pin(tokio_stream::iter(parts.collect::<Vec<_>>()))))
+    }
+}
+
+// Minimal mirror of descriptor.proto, to build the set a gateway would load.
+#[proto_message(proto_path = "protos/tests/forward_descriptor.proto", explicit_tags)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FileDescriptorSet {
+    #[proto(tag = 1)]
+    pub file: Vec<FileDescriptorProto>,
+}
+
+#[proto_message(proto_path = "protos/tests/forward_descriptor.proto", explicit_tags)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FileDescriptorProto {
+    #[proto(tag = 2)]
+    pub package: String,
+    #[proto(tag = 6)]
+    pub service: Vec<ServiceDescriptorProto>,
+}
+
+#[proto_message(proto_path = "protos/tests/forward_descriptor.proto", explicit_tags)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ServiceDescriptorProto {
+    #[proto(tag = 1)]
+    pub name: String,
+    #[proto(tag = 2)]
+    pub method: Vec<MethodDescriptorProto>,
+}
+
+#[proto_message(proto_path = "protos/tests/forward_descriptor.proto", explicit_tags)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MethodDescriptorProto {
+    #[proto(tag = 1)]
+    pub name: String,
+    #[proto(tag = 2)]
+    pub input_type: String,
+    #[proto(tag = 3)]
+    pub output_type: String,
+    #[proto(tag = 6)]
+    pub server_streaming: bool,
+}
+
+fn descriptor_set(methods: &[(&str, bool)]) -> Vec<u8> {
+    let method = methods
+        .iter()
+        .map(|(name, server_streaming)| MethodDescriptorProto {
+            name: (*name).into(),
+            input_type: ".forward_rpc.Order".into(),
+            output_type: ".forward_rpc.Order".into(),
+            server_streaming: *server_streaming,
+        })
+        .collect();
+    FileDescriptorSet {
+        file: vec![FileDescriptorProto {
+            package: "forward_rpc".into(),
+            service: vec![ServiceDescriptorProto {
+                name: "Orders".into(),
+                method,
+            }],
+        }],
+    }
+    .encode_to_vec()
+}
+
+fn gateway(methods: &[(&str, bool)]) -> ForwardingService<orders_server::OrdersServer<OrderDesk>> {
+    ForwardingService::from_descriptor_set(orders_server::OrdersServer::new(OrderDesk), &descriptor_set(methods)).unwrap()
+}
+
+#[test]
+fn routes_come_from_the_descriptor_set() {
+    let table = ForwardTable::from_descriptor_set(&descriptor_set(&[("Place", false), ("Split", true)])).unwrap();
+    assert_eq!(table.len(), 2);
+    assert_eq!(table.service_names(), ["forward_rpc.Orders"]);
+    let split = table.get("/forward_rpc.Orders/Split").unwrap();
+    assert_eq!(split.input_type, "forward_rpc.Order");
+    assert!(split.server_streaming && !split.client_streaming);
+    assert!(ForwardTable::from_descriptor_set(&[0xff]).is_err());
+}
+
+#[tokio::test]
+async fn calls_are_forwarded_without_decoding() {
+    let mut client = orders_client::OrdersClient::new(gateway(&[("Place", false), ("Split", true)]));
+
+    let reply = client.place(Order { id: 1, quantity: 3 }).await.unwrap().into_inner();
+    assert_eq!(reply, Order { id: 1001, quantity: 3 });
+
+    let status = client.place(Order { id: 1, quantity: 0 }).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    let parts: Vec<Order> = client.split(Order { id: 7, quantity: 2 }).await.unwrap().into_inner().map(Result::unwrap).collect().await;
+    assert_eq!(parts, [Order { id: 7, quantity: 1 }, Order { id: 7, quantity: 1 }]);
+}
+
+#[tokio::test]
+async fn reload_changes_accepted_routes() {
+    let gateway = gateway(&[("Place", false)]);
+    let mut client = orders_client::OrdersClient::new(gateway.clone());
+
+    let Err(status) = client.split(Order { id: 7, quantity: 2 }).await else {
+        panic!("split is not forwarded before the reload");
+    };
+    assert_eq!(status.code(), Code::Unimplemented);
+
+    assert_eq!(gateway.reload(&descriptor_set(&[("Split", true)])).unwrap(), 1);
+    assert!(client.split(Order { id: 7, quantity: 2 }).await.is_ok());
+    let status = client.place(Order { id: 1, quantity: 3 }).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+
+    assert!(gateway.reload(&[0xff]).is_err());
+    assert_eq!(gateway.table().len(), 1);
+}
@@ -0,0 +1,115 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use std::collections::HashMap;
+
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tokio_stream::Stream;
+use tokio_stream::StreamExt;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::BoxStream;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct ItemQuery {
+    pub key: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct ItemReply {
+    pub value: String,
+}
+
+pub trait Store: Send + Sync + 'static {
+    fn get(&self, key: &str) -> Option<String>;
+}
+
+pub struct MemoryStore(HashMap<String, String>);
+
+impl Store for MemoryStore {
+    fn get(&self, key: &str) -> Option<String> {
+        self.0.get(key).cloned()
+    }
+}
+
+#[proto_rpc(rpc_package = "catalog_rpc", rpc_server = true, rpc_client = true)]
+pub trait Catalog<S>
+where
+    S: Store,
+{
+    type ScanStream: Stream<Item = Result<ItemReply, Status>> + Send;
+
+    async fn lookup(&self, request: Request<ItemQuery>) -> Result<Response<ItemReply>, Status>;
+
+    async fn scan(&self, request: Request<ItemQuery>) -> Result<Response<Self::ScanStream>, Status>;
+
+    // Shared helper; not an RPC.
+    fn find(&self, store: &S, key: &str) -> Option<ItemReply> {
+        store.get(key).map(|value| ItemReply { value })
+    }
+}
+
+struct CatalogService {
+    store: MemoryStore,
+}
+
+impl Catalog<MemoryStore> for CatalogService {
+    type ScanStream = BoxStream<ItemReply>;
+
+    async fn lookup(&self, request: Request<ItemQuery>) -> Result<Response<ItemReply>, Status> {
+        let key = request.into_inner().key;
+        self.find(&self.store, &key).map(Response::new).ok_or_else(|| Status::not_found(key))
+    }
+
+    async fn scan(&self, request: Request<ItemQuery>) -> Result<Response<Self::ScanStream>, Status> {
+        let prefix = request.into_inner().key;
+        let mut values: Vec<_> = self.store.0.iter().filter(|(key, _)| key.starts_with(&prefix)).map(|(_, value)| value.clone()).collect();
+        values.sort();
+        let stream = tokio_stream::iter(values.into_iter().map(|value| Ok(ItemReply { value })));
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn generic_trait_with_helpers_serves_requests() {
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let incoming = TcpListenerStream::new(listener);
+
+    let service = CatalogService {
+        store: MemoryStore(HashMap::from([
+            ("a".to_string(), "alpha".to_string()),
+            ("ab".to_string(), "alpha-beta".to_string()),
+        ])),
+    };
+    let handle = tokio::spawn(async move {
+        Server::builder()
+            .add_service(catalog_server::CatalogServer::<_, MemoryStore>::new(service))
+            .serve_with_incoming_shutdown(incoming, async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+    });
+
+    let mut client = catalog_client::CatalogClient::connect(format!("http://{addr}")).await.unwrap();
+    let reply = client.lookup(ItemQuery { key: "a".to_string() }).await.unwrap().into_inner();
+    assert_eq!(reply.value, "alpha");
+
+    let err = client.lookup(ItemQuery { key: "b".to_string() }).await.unwrap_err();
+    assert_eq!(err.code(), tonic::Code::NotFound);
+
+    let stream = client.scan(ItemQuery { key: "a".to_string() }).await.unwrap().into_inner();
+    let values: Vec<_> = stream.map(|item| item.unwrap().value).collect().await;
+    assert_eq!(values, vec!["alpha".to_string(), "alpha-beta".to_string()]);
+
+    shutdown_tx.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+}
@@ -0,0 +1,127 @@
+#![cfg(feature = "grpc-web")]
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use std::convert::Infallible;
+use std::task::Context;
+use std::task::Poll;
+
+use proto_rs::grpc_web;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tokio_stream::Stream;
+use tokio_stream::StreamExt;
+use tonic::Code;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::BoxFuture;
+use tonic::codegen::BoxStream;
+use tonic::codegen::Service;
+use tonic::codegen::http;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Query {
+    pub term: String,
+}
+
+#[proto_rpc(rpc_package = "web_rpc", rpc_server = true, rpc_client = true)]
+pub trait Search {
+    type ResultsStream: Stream<Item = Result<Query, Status>> + Send;
+
+    async fn first(&self, request: Request<Query>) -> Result<Response<Query>, Status>;
+
+    async fn results(&self, request: Request<Query>) -> Result<Response<Self::ResultsStream>, Status>;
+}
+
+struct SearchService;
+
+impl Search for SearchService {
+    type ResultsStream = BoxStream<Query>;
+
+    async fn first(&self, request: Request<Query>) -> Result<Response<Query>, Status> {
+        let query = request.into_inner();
+        if query.term.is_empty() {
+            return Err(Status::invalid_argument("empty term"));
+        }
+        let mut response = Response::new(Query {
+            term: format!("{}-1", query.term),
+        });
+        response.metadata_mut().insert("x-hits", "3".parse().unwrap());
+        Ok(response)
+    }
+
+    async fn results(&self, request: Request<Query>) -> Result<Response<Self::ResultsStream>, Status> {
+        let term = request.into_inner().term;
+        let results: Vec<_> = (1..=3)
+            .map(|n| {
+                Ok(Query {
+                    term: format!("{term}-{n}"),
+                })
+            })
+            .collect();
+        Ok(Response::new(Box::pin(tokio_stream::iter(results))))
+    }
+}
+
+/// Stands in for a browser talking to a gRPC-web proxy: every response crosses the gRPC-web
+/// framing in both directions.
+#[derive(Clone)]
+struct WebRoundTrip(search_server::SearchServer<SearchService>);
+
+impl Service<http::Request<tonic::body::Body>> for WebRoundTrip {
+    type Response = http::Response<tonic::body::Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<tonic::body::Body>) -> Self::Future {
+        let mut server = self.0.clone();
+        Box::pin(async move {
+            let Ok(response) = server.call(request).await;
+            let web = grpc_web::encode_response(response).await;
+            assert_eq!(web.headers()["content-type"], grpc_web::GRPC_WEB_CONTENT_TYPE);
+            Ok(grpc_web::decode_response(web).unwrap())
+        })
+    }
+}
+
+fn client() -> search_client::SearchClient<WebRoundTrip> {
+    search_client::SearchClient::new(WebRoundTrip(search_server::SearchServer::new(SearchService)))
+}
+
+#[tokio::test]
+async fn unary_calls_survive_grpc_web_framing() {
+    let mut client = client();
+    let response = client.first(Query { term: "rust".to_string() }).await.unwrap();
+    assert_eq!(response.metadata().get("x-hits").unwrap(), "3");
+    assert_eq!(response.into_inner().term, "rust-1");
+
+    let err = client.first(Query::default()).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert_eq!(err.message(), "empty term");
+}
+
+#[tokio::test]
+async fn streams_are_decoded_frame_by_frame() {
+    let mut client = client();
+    let results: Vec<_> =
+        client.results(Query { term: "wasm".to_string() }).await.unwrap().into_inner().map(|query| query.unwrap().term).collect().await;
+    assert_eq!(results, ["wasm-1", "wasm-2", "wasm-3"]);
+}
+
+#[test]
+fn trailers_frame_is_parsed() {
+    let mut body = vec![0x80, 0, 0, 0, 0];
+    let block = b"grpc-status: 5\r\ngrpc-message: missing\r\n";
+    body[1..5].copy_from_slice(&u32::try_from(block.len()).unwrap().to_be_bytes());
+    body.extend_from_slice(block);
+    let response = http::Response::new(tonic::codegen::Bytes::from(body));
+    assert!(grpc_web::decode_response(response).is_ok());
+
+    let truncated = http::Response::new(tonic::codegen::Bytes::from_static(&[0x00, 0, 0, 0, 9, 1]));
+    assert_eq!(grpc_web::decode_response(truncated).unwrap_err().code(), Code::Internal);
+}
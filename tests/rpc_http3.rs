@@ -0,0 +1,85 @@
+#![cfg(feature = "http3")]
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use proto_rs::http3::H3Channel;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use quinn::rustls::RootCertStore;
+use quinn::rustls::pki_types::CertificateDer;
+use quinn::rustls::pki_types::PrivateKeyDer;
+use quinn::rustls::pki_types::pem::PemObject;
+use tokio_stream::Stream;
+use tokio_stream::StreamExt;
+use tonic::Code;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::BoxStream;
+
+const CERT: &[u8] = include_bytes!("certs/localhost.crt");
+const KEY: &[u8] = include_bytes!("certs/localhost.key");
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Sample {
+    pub value: u64,
+}
+
+#[proto_rpc(rpc_package = "h3_rpc", rpc_server = true, rpc_client = true)]
+pub trait Meter {
+    type CountStream: Stream<Item = Result<Sample, Status>> + Send;
+
+    async fn double(&self, request: Request<Sample>) -> Result<Response<Sample>, Status>;
+
+    async fn count(&self, request: Request<Sample>) -> Result<Response<Self::CountStream>, Status>;
+}
+
+struct MeterService;
+
+impl Meter for MeterService {
+    type CountStream = BoxStream<Sample>;
+
+    async fn double(&self, request: Request<Sample>) -> Result<Response<Sample>, Status> {
+        let value = request.into_inner().value;
+        let value = value.checked_mul(2).ok_or_else(|| Status::out_of_range("overflow"))?;
+        Ok(Response::new(Sample { value }))
+    }
+
+    async fn count(&self, request: Request<Sample>) -> Result<Response<Self::CountStream>, Status> {
+        let samples: Vec<_> = (0..request.into_inner().value).map(|value| Ok(Sample { value })).collect();
+        Ok(Response::new(Box::pin(tokio_stream::iter(samples))))
+    }
+}
+
+fn endpoints() -> (quinn::Endpoint, quinn::Endpoint, SocketAddr) {
+    let certs = vec![CertificateDer::from_pem_slice(CERT).unwrap()];
+    let key = PrivateKeyDer::from_pem_slice(KEY).unwrap();
+    let server_config = quinn::ServerConfig::with_single_cert(certs.clone(), key).unwrap();
+    let server = quinn::Endpoint::server(server_config, "127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    let mut roots = RootCertStore::empty();
+    roots.add(certs[0].clone()).unwrap();
+    let mut client = quinn::Endpoint::client("127.0.0.1:0".parse().unwrap()).unwrap();
+    client.set_default_client_config(quinn::ClientConfig::with_root_certificates(Arc::new(roots)).unwrap());
+    (server, client, addr)
+}
+
+#[tokio::test]
+async fn calls_run_over_http3() {
+    let (server, endpoint, addr) = endpoints();
+    tokio::spawn(proto_rs::http3::serve_http3(meter_server::MeterServer::new(MeterService), server));
+
+    let channel = H3Channel::connect(&endpoint, addr, "localhost").await.unwrap();
+    let mut client = meter_client::MeterClient::new(channel);
+
+    assert_eq!(client.double(Sample { value: 21 }).await.unwrap().into_inner().value, 42);
+    let err = client.double(Sample { value: u64::MAX }).await.unwrap_err();
+    assert_eq!(err.code(), Code::OutOfRange);
+
+    let counted: Vec<_> = client.count(Sample { value: 4 }).await.unwrap().into_inner().map(|sample| sample.unwrap().value).collect().await;
+    assert_eq!(counted, [0, 1, 2, 3]);
+}
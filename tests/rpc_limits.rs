@@ -0,0 +1,118 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Job {
+    pub id: u32,
+}
+
+#[proto_rpc(rpc_package = "limits_rpc", rpc_server = true, rpc_client = true, concurrency_limit = 1)]
+pub trait SerialWorker {
+    async fn run(&self, request: Request<Job>) -> Result<Response<Job>, Status>;
+}
+
+#[proto_rpc(rpc_package = "limits_rpc", rpc_server = true, rpc_client = true, rate_limit = "2/300ms")]
+pub trait ThrottledWorker {
+    async fn run(&self, request: Request<Job>) -> Result<Response<Job>, Status>;
+}
+
+#[derive(Default)]
+struct Worker {
+    in_flight: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl SerialWorker for Worker {
+    async fn run(&self, request: Request<Job>) -> Result<Response<Job>, Status> {
+        let current = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(current, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        Ok(Response::new(request.into_inner()))
+    }
+}
+
+impl ThrottledWorker for Worker {
+    async fn run(&self, request: Request<Job>) -> Result<Response<Job>, Status> {
+        Ok(Response::new(request.into_inner()))
+    }
+}
+
+async fn serve<S>(service: S) -> (std::net::SocketAddr, tokio::sync::oneshot::Sender<()>)
+where
+    S: tonic::codegen::Service<
+            tonic::codegen::http::Request<tonic::body::Body>,
+            Response = tonic::codegen::http::Response<tonic::body::Body>,
+            Error = std::convert::Infallible,
+        > + tonic::server::NamedService
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send + 'static,
+{
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+    let incoming = TcpListenerStream::new(listener);
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(service)
+            .serve_with_incoming_shutdown(incoming, async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+    });
+
+    (addr, shutdown_tx)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrency_limit_caps_in_flight_calls() {
+    let worker = std::sync::Arc::new(Worker::default());
+    let (addr, shutdown) = serve(serial_worker_server::SerialWorkerServer::from_arc(worker.clone())).await;
+
+    let client = serial_worker_client::SerialWorkerClient::connect(format!("http://{addr}")).await.unwrap();
+    let calls: Vec<_> = (0..4)
+        .map(|id| {
+            let mut client = client.clone();
+            tokio::spawn(async move { client.run(Job { id }).await.unwrap() })
+        })
+        .collect();
+    for call in calls {
+        call.await.unwrap();
+    }
+    assert_eq!(worker.peak.load(Ordering::SeqCst), 1);
+
+    shutdown.send(()).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rate_limit_delays_calls_past_the_window() {
+    let started = Instant::now();
+    let (addr, shutdown) = serve(throttled_worker_server::ThrottledWorkerServer::new(Worker::default())).await;
+
+    let mut client = throttled_worker_client::ThrottledWorkerClient::connect(format!("http://{addr}")).await.unwrap();
+    for id in 0..3 {
+        assert_eq!(client.run(Job { id }).await.unwrap().into_inner().id, id);
+    }
+    assert!(started.elapsed() >= Duration::from_millis(300));
+
+    shutdown.send(()).unwrap();
+}
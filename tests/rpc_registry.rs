@@ -0,0 +1,89 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use proto_rs::registry::ServiceRegistry;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Note {
+    pub text: String,
+}
+
+#[proto_rpc(rpc_package = "registry_rpc", rpc_server = true, rpc_client = true)]
+pub trait Upper {
+    async fn upper(&self, request: Request<Note>) -> Result<Response<Note>, Status>;
+}
+
+#[proto_rpc(rpc_package = "registry_rpc", rpc_server = true, rpc_client = true)]
+pub trait Lower {
+    async fn lower(&self, request: Request<Note>) -> Result<Response<Note>, Status>;
+}
+
+struct Notes;
+
+impl Upper for Notes {
+    async fn upper(&self, request: Request<Note>) -> Result<Response<Note>, Status> {
+        Ok(Response::new(Note {
+            text: request.into_inner().text.to_uppercase(),
+        }))
+    }
+}
+
+impl Lower for Notes {
+    async fn lower(&self, request: Request<Note>) -> Result<Response<Note>, Status> {
+        Ok(Response::new(Note {
+            text: request.into_inner().text.to_lowercase(),
+        }))
+    }
+}
+
+fn registry() -> ServiceRegistry {
+    ServiceRegistry::new().with(upper_server::UpperServer::new(Notes)).with(lower_server::LowerServer::new(Notes))
+}
+
+#[test]
+fn registry_records_service_names() {
+    let registry = registry();
+    assert_eq!(registry.len(), 2);
+    assert_eq!(registry.names(), ["registry_rpc.Upper", "registry_rpc.Lower"]);
+}
+
+#[test]
+#[should_panic(expected = "registered twice")]
+fn registry_rejects_duplicates() {
+    let _ = registry().with(upper_server::UpperServer::new(Notes));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn serve_all_adds_every_service() {
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let incoming = TcpListenerStream::new(listener);
+
+    let router = registry().serve_all(&mut Server::builder());
+    let handle = tokio::spawn(async move {
+        router
+            .serve_with_incoming_shutdown(incoming, async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+    });
+
+    let note = Note { text: "MiXeD".to_string() };
+    let mut upper = upper_client::UpperClient::connect(format!("http://{addr}")).await.unwrap();
+    assert_eq!(upper.upper(note.clone()).await.unwrap().into_inner().text, "MIXED");
+    let mut lower = lower_client::LowerClient::connect(format!("http://{addr}")).await.unwrap();
+    assert_eq!(lower.lower(note).await.unwrap().into_inner().text, "mixed");
+
+    shutdown_tx.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+}
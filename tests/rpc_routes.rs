@@ -0,0 +1,160 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::ProtoCodec;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+
+#[cfg(feature = "build-schemas")]
+mod common;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct OwnerQuery {
+    pub id: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct OwnerReply {
+    pub name: String,
+}
+
+#[proto_rpc(
+    rpc_package = "custom.v1",
+    proto_path = "protos/tests/routes_owner.proto",
+    rpc_server = true,
+    rpc_client = true
+)]
+pub trait OwnerService {
+    #[proto(rpc_name = "LookupOwner", route = "/custom.v1.Svc/LookupOwner")]
+    async fn find_owner(&self, request: Request<OwnerQuery>) -> Result<Response<OwnerReply>, Status>;
+
+    #[proto(route = "/custom.v1.Svc/ListOwners")]
+    async fn list_owners(&self, request: Request<OwnerQuery>) -> Result<Response<OwnerReply>, Status>;
+}
+
+#[proto_rpc(rpc_package = "routes_rpc", rpc_server = true, rpc_client = true)]
+pub trait NameService {
+    #[proto(rpc_name = "GetOwnerName")]
+    async fn owner_name(&self, request: Request<OwnerQuery>) -> Result<Response<OwnerReply>, Status>;
+}
+
+struct Owners;
+
+impl OwnerService for Owners {
+    async fn find_owner(&self, request: Request<OwnerQuery>) -> Result<Response<OwnerReply>, Status> {
+        Ok(Response::new(OwnerReply {
+            name: format!("owner-{}", request.into_inner().id),
+        }))
+    }
+
+    async fn list_owners(&self, request: Request<OwnerQuery>) -> Result<Response<OwnerReply>, Status> {
+        Ok(Response::new(OwnerReply {
+            name: format!("list-{}", request.into_inner().id),
+        }))
+    }
+}
+
+impl NameService for Owners {
+    async fn owner_name(&self, request: Request<OwnerQuery>) -> Result<Response<OwnerReply>, Status> {
+        Ok(Response::new(OwnerReply {
+            name: format!("name-{}", request.into_inner().id),
+        }))
+    }
+}
+
+async fn spawn_server() -> (
+    std::net::SocketAddr,
+    tokio::sync::oneshot::Sender<()>,
+    tokio::task::JoinHandle<Result<(), tonic::transport::Error>>,
+) {
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+    let incoming = TcpListenerStream::new(listener);
+
+    let handle = tokio::spawn(async move {
+        Server::builder()
+            .add_service(owner_service_server::OwnerServiceServer::new(Owners))
+            .add_service(name_service_server::NameServiceServer::new(Owners))
+            .serve_with_incoming_shutdown(incoming, async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+    });
+
+    (addr, shutdown_tx, handle)
+}
+
+async fn raw_unary(addr: std::net::SocketAddr, path: &'static str) -> Result<OwnerReply, Status> {
+    let channel = tonic::transport::Endpoint::from_shared(format!("http://{addr}")).unwrap().connect().await.unwrap();
+    let mut grpc = tonic::client::Grpc::new(channel);
+    grpc.ready().await.unwrap();
+    let codec = ProtoCodec::<OwnerQuery, OwnerReply>::default();
+    let response = grpc
+        .unary(
+            Request::new(OwnerQuery { id: 3 }),
+            tonic::codegen::http::uri::PathAndQuery::from_static(path),
+            codec,
+        )
+        .await?;
+    Ok(response.into_inner())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn overridden_routes_roundtrip() {
+    let (addr, shutdown, handle) = spawn_server().await;
+
+    let mut client = owner_service_client::OwnerServiceClient::connect(format!("http://{addr}")).await.unwrap();
+    let reply = client.find_owner(OwnerQuery { id: 7 }).await.unwrap().into_inner();
+    assert_eq!(reply.name, "owner-7");
+    let reply = client.list_owners(OwnerQuery { id: 8 }).await.unwrap().into_inner();
+    assert_eq!(reply.name, "list-8");
+
+    let mut client = name_service_client::NameServiceClient::connect(format!("http://{addr}")).await.unwrap();
+    let reply = client.owner_name(OwnerQuery { id: 9 }).await.unwrap().into_inner();
+    assert_eq!(reply.name, "name-9");
+
+    shutdown.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn server_serves_overridden_paths_only() {
+    let (addr, shutdown, handle) = spawn_server().await;
+
+    assert_eq!(raw_unary(addr, "/custom.v1.Svc/LookupOwner").await.unwrap().name, "owner-3");
+    assert_eq!(raw_unary(addr, "/custom.v1.Svc/ListOwners").await.unwrap().name, "list-3");
+    assert_eq!(
+        raw_unary(addr, "/routes_rpc.NameService/GetOwnerName").await.unwrap().name,
+        "name-3"
+    );
+
+    let err = raw_unary(addr, "/routes_rpc.OwnerService/FindOwner").await.unwrap_err();
+    assert_eq!(err.code(), tonic::Code::Unimplemented);
+    let err = raw_unary(addr, "/routes_rpc.NameService/OwnerName").await.unwrap_err();
+    assert_eq!(err.code(), tonic::Code::Unimplemented);
+
+    shutdown.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn emitted_service_follows_routes() {
+    let proto = common::emitted_proto("rpc_routes", "protos/tests/routes_owner.proto");
+    assert!(proto.contains("package custom.v1;"), "{proto}");
+    assert!(
+        proto.contains(
+            "service Svc {\n  rpc LookupOwner(OwnerQuery) returns (OwnerReply);\n  rpc ListOwners(OwnerQuery) returns (OwnerReply);\n}"
+        ),
+        "{proto}"
+    );
+}
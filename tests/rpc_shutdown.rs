@@ -0,0 +1,190 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use std::time::Duration;
+
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use proto_rs::shutdown::Drain;
+use tokio_stream::Stream;
+use tokio_stream::StreamExt;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::BoxStream;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Tick {
+    pub seq: u32,
+}
+
+#[proto_rpc(rpc_package = "shutdown_rpc", rpc_server = true, rpc_client = true)]
+pub trait Ticker {
+    type TicksStream: Stream<Item = Result<Tick, Status>> + Send;
+
+    async fn slow(&self, request: Request<Tick>) -> Result<Response<Tick>, Status>;
+
+    async fn ticks(&self, request: Request<Tick>) -> Result<Response<Self::TicksStream>, Status>;
+
+    async fn countdown(&self, request: Request<Tick>) -> Result<Response<Self::TicksStream>, Status>;
+}
+
+struct TickerService;
+
+impl Ticker for TickerService {
+    type TicksStream = BoxStream<Tick>;
+
+    async fn slow(&self, request: Request<Tick>) -> Result<Response<Tick>, Status> {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        Ok(Response::new(request.into_inner()))
+    }
+
+    async fn ticks(&self, request: Request<Tick>) -> Result<Response<Self::TicksStream>, Status> {
+        let drain = request.extensions().get::<Drain>().cloned().expect("server inserts its drain");
+        let (tx, rx) = tokio::sync::mpsc::channel(4);
+        tokio::spawn(async move {
+            let mut seq = 0;
+            loop {
+                tokio::select! {
+                    () = drain.cancelled() => break,
+                    () = tokio::time::sleep(Duration::from_millis(10)) => {
+                        if tx.send(Ok(Tick { seq })).await.is_err() {
+                            break;
+                        }
+                        seq += 1;
+                    }
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+
+    // Ignores the drain and runs to the end.
+    async fn countdown(&self, request: Request<Tick>) -> Result<Response<Self::TicksStream>, Status> {
+        let count = request.into_inner().seq;
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        tokio::spawn(async move {
+            for seq in 0..count {
+                tokio::time::sleep(Duration::from_millis(30)).await;
+                if tx.send(Ok(Tick { seq })).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+}
+
+async fn free_addr() -> std::net::SocketAddr {
+    tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap()
+}
+
+async fn connect(addr: std::net::SocketAddr) -> ticker_client::TickerClient<tonic::transport::Channel> {
+    for _ in 0..50 {
+        if let Ok(client) = ticker_client::TickerClient::connect(format!("http://{addr}")).await {
+            return client;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("server did not start");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn shutdown_cancels_streams_and_waits_for_calls() {
+    let addr = free_addr().await;
+    let server = ticker_server::TickerServer::new(TickerService);
+    let drain = server.drain_handle();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let handle = tokio::spawn(server.serve_with_shutdown(addr, async {
+        let _ = shutdown_rx.await;
+    }));
+
+    let mut client = connect(addr).await;
+    let mut ticks = client.ticks(Tick::default()).await.unwrap().into_inner();
+    assert_eq!(ticks.next().await.unwrap().unwrap().seq, 0);
+    assert_eq!(ticks.next().await.unwrap().unwrap().seq, 1);
+
+    let mut slow_client = client.clone();
+    let slow = tokio::spawn(async move { slow_client.slow(Tick { seq: 7 }).await });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    // The open stream and the unary call.
+    assert_eq!(drain.in_flight(), 2);
+
+    shutdown_tx.send(()).unwrap();
+
+    // The in-flight unary call still completes.
+    assert_eq!(slow.await.unwrap().unwrap().into_inner().seq, 7);
+    // The stream handler observed the cancellation and ended.
+    let rest = tokio::time::timeout(Duration::from_secs(2), async {
+        let mut count = 0;
+        while let Some(Ok(_)) = ticks.next().await {
+            count += 1;
+        }
+        count
+    })
+    .await
+    .expect("stream ends after shutdown");
+    assert!(rest < 100);
+
+    tokio::time::timeout(Duration::from_secs(5), handle).await.expect("server stops").unwrap().unwrap();
+    assert!(drain.is_draining());
+    assert_eq!(drain.in_flight(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn draining_rejects_new_calls_and_waits_for_streams() {
+    use proto_rs::dispatch::ServiceDispatcher;
+    use tokio_stream::wrappers::TcpListenerStream;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = ticker_server::TickerServer::new(TickerService);
+    let dispatcher = server.clone();
+    let drain = server.drain_handle();
+    // The server keeps accepting while the drain runs, as when several services share it.
+    let handle =
+        tokio::spawn(tonic::transport::Server::builder().add_service(server).serve_with_incoming(TcpListenerStream::new(listener)));
+
+    let mut client = connect(addr).await;
+    let mut countdown = client.countdown(Tick { seq: 5 }).await.unwrap().into_inner();
+    assert_eq!(countdown.next().await.unwrap().unwrap().seq, 0);
+    // The handler has returned, the stream is still in flight.
+    assert_eq!(drain.in_flight(), 1);
+
+    let draining = tokio::spawn({
+        let drain = drain.clone();
+        async move { drain.drain().await }
+    });
+    drain.cancelled().await;
+
+    let err = client.slow(Tick { seq: 1 }).await.unwrap_err();
+    assert_eq!(err.code(), tonic::Code::Unavailable);
+    let err = dispatcher.dispatch("Slow", Request::new(tonic::codegen::Bytes::new())).await.unwrap_err();
+    assert_eq!(err.code(), tonic::Code::Unavailable);
+    assert!(!draining.is_finished());
+
+    let mut rest = Vec::new();
+    while let Some(tick) = countdown.next().await {
+        rest.push(tick.unwrap().seq);
+    }
+    assert_eq!(rest, [1, 2, 3, 4]);
+    tokio::time::timeout(Duration::from_secs(1), draining).await.expect("drain completes").unwrap();
+    assert_eq!(drain.in_flight(), 0);
+    handle.abort();
+}
+
+#[tokio::test]
+async fn drain_waits_for_guards() {
+    let drain = Drain::new();
+    let guard = drain.guard();
+    let waiting = tokio::spawn({
+        let drain = drain.clone();
+        async move { drain.drain().await }
+    });
+
+    drain.cancelled().await;
+    assert!(!waiting.is_finished());
+    drop(guard);
+    tokio::time::timeout(Duration::from_secs(1), waiting).await.expect("drain completes").unwrap();
+}
@@ -0,0 +1,136 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use proto_rs::sizes;
+use proto_rs::sizes::MessageKind;
+use proto_rs::sizes::MessageSize;
+use proto_rs::sizes::SizeTotals;
+use tokio_stream::Stream;
+use tokio_stream::StreamExt;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::BoxStream;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Chunk {
+    pub payload: String,
+}
+
+#[proto_rpc(rpc_package = "sizes_rpc", rpc_server = true, rpc_client = true)]
+pub trait Storage {
+    type ReadStream: Stream<Item = Result<Chunk, Status>> + Send;
+
+    async fn echo(&self, request: Request<Chunk>) -> Result<Response<Chunk>, Status>;
+
+    async fn read(&self, request: Request<Chunk>) -> Result<Response<Self::ReadStream>, Status>;
+}
+
+struct StorageService;
+
+impl Storage for StorageService {
+    type ReadStream = BoxStream<Chunk>;
+
+    async fn echo(&self, request: Request<Chunk>) -> Result<Response<Chunk>, Status> {
+        let payload = request.into_inner().payload.repeat(2);
+        Ok(Response::new(Chunk { payload }))
+    }
+
+    async fn read(&self, request: Request<Chunk>) -> Result<Response<Self::ReadStream>, Status> {
+        let payload = request.into_inner().payload;
+        let chunks = (1..=3).map(move |n| {
+            Ok(Chunk {
+                payload: payload.repeat(n),
+            })
+        });
+        Ok(Response::new(Box::pin(tokio_stream::iter(chunks))))
+    }
+}
+
+const ECHO_ROUTE: &str = "/sizes_rpc.Storage/Echo";
+const READ_ROUTE: &str = "/sizes_rpc.Storage/Read";
+
+fn chunk(payload: &str) -> Chunk {
+    Chunk {
+        payload: payload.to_string(),
+    }
+}
+
+fn len(payload: &str) -> usize {
+    chunk(payload).encode_to_vec().len()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn codecs_report_message_sizes_per_route() {
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    let seen = Arc::new(Mutex::new(Vec::<MessageSize>::new()));
+    let hook_seen = Arc::clone(&seen);
+    sizes::add_size_hook(move |size| hook_seen.lock().unwrap().push(*size));
+    let totals = SizeTotals::install();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let incoming = TcpListenerStream::new(listener);
+    let handle = tokio::spawn(async move {
+        Server::builder()
+            .add_service(storage_server::StorageServer::new(StorageService))
+            .serve_with_incoming_shutdown(incoming, async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+    });
+
+    let mut client = storage_client::StorageClient::connect(format!("http://{addr}")).await.unwrap();
+    let reply = client.echo(chunk("abc")).await.unwrap().into_inner();
+    assert_eq!(reply, chunk("abcabc"));
+
+    let mut stream = client.read(chunk("xy")).await.unwrap().into_inner();
+    let mut received = 0;
+    while let Some(item) = stream.next().await {
+        item.unwrap();
+        received += 1;
+    }
+    assert_eq!(received, 3);
+
+    // Once the hooks are removed nothing is reported.
+    sizes::clear_size_hooks();
+    let recorded = seen.lock().unwrap().len();
+    client.echo(chunk("abc")).await.unwrap();
+    assert_eq!(seen.lock().unwrap().len(), recorded);
+
+    shutdown_tx.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+
+    // Client and server run in this process, so every message is seen by the encoding and the
+    // decoding side.
+    let seen = seen.lock().unwrap().clone();
+    let of = |route: &str, kind: MessageKind| {
+        let mut bytes: Vec<_> = seen.iter().filter(|size| size.route == route && size.kind == kind).map(|size| size.bytes).collect();
+        bytes.sort_unstable();
+        bytes
+    };
+    assert_eq!(of(ECHO_ROUTE, MessageKind::Request), [len("abc"), len("abc")]);
+    assert_eq!(of(ECHO_ROUTE, MessageKind::Response), [len("abcabc"), len("abcabc")]);
+    assert_eq!(of(READ_ROUTE, MessageKind::Request), [len("xy"), len("xy")]);
+    assert_eq!(
+        of(READ_ROUTE, MessageKind::Response),
+        [len("xy"), len("xy"), len("xyxy"), len("xyxy"), len("xyxyxy"), len("xyxyxy")]
+    );
+
+    let echo = totals.route(ECHO_ROUTE);
+    assert_eq!(echo.requests.messages(), 2);
+    assert_eq!(echo.requests.bytes(), 2 * len("abc") as u64);
+    assert_eq!(echo.responses.bytes(), 2 * len("abcabc") as u64);
+    let routes: Vec<_> = totals.routes().into_iter().map(|(route, _)| route).collect();
+    assert_eq!(routes, [ECHO_ROUTE, READ_ROUTE]);
+}
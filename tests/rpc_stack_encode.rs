@@ -0,0 +1,73 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::ProtoEncode;
+use proto_rs::STACK_ENCODE_THRESHOLD;
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Ack {
+    pub id: u64,
+    pub note: String,
+}
+
+#[proto_rpc(rpc_package = "stack_encode_rpc", rpc_server = true, rpc_client = true)]
+pub trait Acker {
+    async fn ack(&self, request: Request<Ack>) -> Result<Response<Ack>, Status>;
+}
+
+struct AckService;
+
+impl Acker for AckService {
+    async fn ack(&self, request: Request<Ack>) -> Result<Response<Ack>, Status> {
+        let ack = request.into_inner();
+        Ok(Response::new(Ack {
+            id: ack.id + 1,
+            note: ack.note.repeat(2),
+        }))
+    }
+}
+
+fn ack(id: u64, note_len: usize) -> Ack {
+    Ack {
+        id,
+        note: "n".repeat(note_len),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn messages_on_both_sides_of_the_threshold_roundtrip() {
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let incoming = TcpListenerStream::new(listener);
+    let handle = tokio::spawn(async move {
+        Server::builder()
+            .add_service(acker_server::AckerServer::new(AckService))
+            .serve_with_incoming_shutdown(incoming, async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+    });
+
+    let mut client = acker_client::AckerClient::connect(format!("http://{addr}")).await.unwrap();
+    // empty, tiny, a reply just past the threshold, and a request past it
+    for note_len in [0, 3, STACK_ENCODE_THRESHOLD / 2, STACK_ENCODE_THRESHOLD] {
+        let request = ack(u64::MAX - 1, note_len);
+        let reply = client.ack(request.clone()).await.unwrap().into_inner();
+        assert_eq!(reply, ack(u64::MAX, 2 * note_len), "note of {note_len} bytes");
+    }
+    assert!(ack(u64::MAX - 1, STACK_ENCODE_THRESHOLD / 2).encode_to_vec().len() <= STACK_ENCODE_THRESHOLD);
+    assert!(ack(u64::MAX, STACK_ENCODE_THRESHOLD).encode_to_vec().len() > STACK_ENCODE_THRESHOLD);
+
+    shutdown_tx.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+}
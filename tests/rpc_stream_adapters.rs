@@ -0,0 +1,95 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use tokio_stream::Stream;
+use tokio_stream::StreamExt;
+use tonic::Code;
+use tonic::Response;
+use tonic::Status;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Tick {
+    pub seq: u64,
+}
+
+#[proto_rpc(rpc_package = "ticks_rpc", rpc_client = true)]
+pub trait Ticks {
+    type WatchStream: Stream<Item = Result<Tick, Status>> + Send;
+
+    async fn watch(&self, request: tonic::Request<Tick>) -> Result<Response<Self::WatchStream>, Status>;
+}
+
+// Generated clients re-export the adapters next to the client struct.
+use ticks_client::ResponseStreamExt;
+
+fn ticks(range: std::ops::Range<u64>) -> impl Stream<Item = Result<Tick, Status>> + Send + 'static {
+    tokio_stream::iter(range.map(|seq| Ok(Tick { seq })))
+}
+
+#[tokio::test]
+async fn collect_all_gathers_until_the_limit() {
+    let items = ticks(0..3).collect_all(3).await.unwrap();
+    assert_eq!(items.iter().map(|tick| tick.seq).collect::<Vec<_>>(), [0, 1, 2]);
+
+    let status = ticks(0..4).collect_all(3).await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+
+    let failing = ticks(0..1).chain(tokio_stream::once(Err(Status::unavailable("gone"))));
+    assert_eq!(failing.collect_all(10).await.unwrap_err().code(), Code::Unavailable);
+}
+
+#[tokio::test]
+async fn timeout_stream_ends_an_idle_stream() {
+    let stalled = ticks(0..2).chain(tokio_stream::pending());
+    let items = stalled.into_timeout_stream(Duration::from_millis(20)).collect::<Vec<_>>().await;
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[1].as_ref().unwrap().seq, 1);
+    assert_eq!(items[2].as_ref().unwrap_err().code(), Code::DeadlineExceeded);
+
+    let finished = ticks(0..2).into_timeout_stream(Duration::from_millis(20)).collect::<Vec<_>>().await;
+    assert!(finished.iter().all(Result::is_ok));
+}
+
+#[tokio::test]
+async fn resume_with_reopens_after_the_last_token() {
+    let reopened_from = Arc::new(AtomicU64::new(u64::MAX));
+    let seen = Arc::clone(&reopened_from);
+    let broken = ticks(0..3).chain(tokio_stream::once(Err(Status::unavailable("reset"))));
+    let resumed = broken.resume_with(
+        |tick: &Tick| tick.seq,
+        move |last: Option<&u64>, status: &Status| {
+            assert_eq!(status.code(), Code::Unavailable);
+            let next = last.map_or(0, |seq| seq + 1);
+            seen.store(next, Ordering::SeqCst);
+            Some(async move { Ok(Response::new(ticks(next..5))) })
+        },
+    );
+    let items = resumed.collect_all(10).await.unwrap();
+    assert_eq!(items.iter().map(|tick| tick.seq).collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+    assert_eq!(reopened_from.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn resume_with_passes_the_error_through_when_giving_up() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&attempts);
+    let broken = ticks(0..1).chain(tokio_stream::once(Err(Status::unavailable("reset"))));
+    let resumed = broken.resume_with(
+        |tick: &Tick| tick.seq,
+        move |_last: Option<&u64>, _status: &Status| {
+            // The first reopen fails too; the second failure is given up on.
+            (counter.fetch_add(1, Ordering::SeqCst) == 0)
+                .then_some(async { Err::<Response<tokio_stream::Empty<Result<Tick, Status>>>, _>(Status::unavailable("still down")) })
+        },
+    );
+    let items = resumed.collect::<Vec<_>>().await;
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[1].as_ref().unwrap_err().message(), "still down");
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+}
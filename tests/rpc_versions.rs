@@ -0,0 +1,126 @@
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::proto_message;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+
+#[cfg(feature = "build-schemas")]
+mod common;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Greeting {
+    pub text: String,
+}
+
+mod v1 {
+    use proto_rs::proto_rpc;
+
+    use super::*;
+
+    #[proto_rpc(
+        rpc_package = "versioned_rpc",
+        rpc_version = "v1",
+        proto_path = "protos/tests/versioned_rpc.proto",
+        rpc_server = true,
+        rpc_client = true
+    )]
+    pub trait Greeter {
+        async fn greet(&self, request: Request<Greeting>) -> Result<Response<Greeting>, Status>;
+    }
+}
+
+mod v2 {
+    use proto_rs::proto_rpc;
+
+    use super::*;
+
+    #[proto_rpc(
+        rpc_package = "versioned_rpc",
+        rpc_version = "v2",
+        proto_path = "protos/tests/versioned_rpc.proto",
+        rpc_server = true,
+        rpc_client = true
+    )]
+    pub trait Greeter {
+        async fn greet(&self, request: Request<Greeting>) -> Result<Response<Greeting>, Status>;
+    }
+}
+
+struct Greeters;
+
+impl v1::Greeter for Greeters {
+    async fn greet(&self, request: Request<Greeting>) -> Result<Response<Greeting>, Status> {
+        Ok(Response::new(Greeting {
+            text: format!("v1 {}", request.into_inner().text),
+        }))
+    }
+}
+
+impl v2::Greeter for Greeters {
+    async fn greet(&self, request: Request<Greeting>) -> Result<Response<Greeting>, Status> {
+        Ok(Response::new(Greeting {
+            text: format!("v2 {}", request.into_inner().text),
+        }))
+    }
+}
+
+#[test]
+fn version_folds_into_service_name() {
+    assert_eq!(v1::greeter_server::SERVICE_NAME, "versioned_rpc.v1.Greeter");
+    assert_eq!(v2::greeter_server::SERVICE_NAME, "versioned_rpc.v2.Greeter");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn versions_are_served_side_by_side() {
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let incoming = TcpListenerStream::new(listener);
+
+    let handle = tokio::spawn(async move {
+        Server::builder()
+            .add_service(v1::greeter_server::GreeterServer::new(Greeters))
+            .add_service(v2::greeter_server::GreeterServer::new(Greeters))
+            .serve_with_incoming_shutdown(incoming, async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+    });
+
+    let request = Greeting { text: "hi".to_string() };
+
+    let mut client = v1::greeter_client::GreeterClient::connect(format!("http://{addr}")).await.unwrap();
+    assert_eq!(client.greet(request.clone()).await.unwrap().into_inner().text, "v1 hi");
+
+    let mut client = v2::greeter_client::GreeterClient::connect(format!("http://{addr}")).await.unwrap();
+    assert_eq!(client.greet(request).await.unwrap().into_inner().text, "v2 hi");
+
+    shutdown_tx.send(()).unwrap();
+    handle.await.unwrap().unwrap();
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn version_folds_into_emitted_files() {
+    let dir = common::write_schemas_with_client("rpc_versions");
+    for version in ["v1", "v2"] {
+        let proto = dir.read(format!("protos/tests/{version}/versioned_rpc.proto"));
+        assert!(proto.contains(&format!("package versioned_rpc.{version};")), "{proto}");
+        assert!(proto.contains("service Greeter {"), "{proto}");
+    }
+    assert!(!dir.join("protos/tests/versioned_rpc.proto").exists());
+
+    let client = dir.read("client.rs");
+    let versions = client.split("pub mod versioned_rpc {").nth(1).expect("package module");
+    let v1 = versions.find("pub mod v1 {").expect("v1 module");
+    let v2 = versions.find("pub mod v2 {").expect("v2 module");
+    assert!(v1 < v2, "{client}");
+    assert!(versions[v1..v2].contains("rpc_package = \"versioned_rpc.v1\""), "{client}");
+    assert!(versions[v2..].contains("rpc_package = \"versioned_rpc.v2\""), "{client}");
+}
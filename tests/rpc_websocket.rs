@@ -0,0 +1,97 @@
+#![cfg(feature = "websocket")]
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use proto_rs::websocket::WsChannel;
+use tokio::net::TcpListener;
+use tokio_stream::Stream;
+use tokio_stream::StreamExt;
+use tonic::Code;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::codegen::BoxStream;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct Line {
+    pub text: String,
+}
+
+#[proto_rpc(rpc_package = "ws_rpc", rpc_server = true, rpc_client = true)]
+pub trait Echo {
+    type WordsStream: Stream<Item = Result<Line, Status>> + Send;
+
+    async fn echo(&self, request: Request<Line>) -> Result<Response<Line>, Status>;
+
+    async fn words(&self, request: Request<Line>) -> Result<Response<Self::WordsStream>, Status>;
+}
+
+struct EchoService;
+
+impl Echo for EchoService {
+    type WordsStream = BoxStream<Line>;
+
+    async fn echo(&self, request: Request<Line>) -> Result<Response<Line>, Status> {
+        let line = request.into_inner();
+        if line.text.is_empty() {
+            return Err(Status::invalid_argument("nothing to echo"));
+        }
+        Ok(Response::new(line))
+    }
+
+    async fn words(&self, request: Request<Line>) -> Result<Response<Self::WordsStream>, Status> {
+        let words: Vec<_> = request.into_inner().text.split_whitespace().map(|word| Ok(Line { text: word.to_string() })).collect();
+        Ok(Response::new(Box::pin(tokio_stream::iter(words))))
+    }
+}
+
+async fn spawn_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(proto_rs::websocket::accept(echo_server::EchoServer::new(EchoService), stream));
+        }
+    });
+    format!("ws://{addr}")
+}
+
+#[tokio::test]
+async fn calls_run_over_websocket() {
+    let url = spawn_server().await;
+    let mut client = echo_client::EchoClient::new(WsChannel::connect(&url).await.unwrap());
+
+    let reply = client.echo(Line { text: "hello".to_string() }).await.unwrap().into_inner();
+    assert_eq!(reply.text, "hello");
+
+    let err = client.echo(Line::default()).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+
+    let words: Vec<_> = client
+        .words(Line {
+            text: "one two three".to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .map(|line| line.unwrap().text)
+        .collect()
+        .await;
+    assert_eq!(words, ["one", "two", "three"]);
+}
+
+#[tokio::test]
+async fn concurrent_calls_share_one_connection() {
+    let url = spawn_server().await;
+    let client = echo_client::EchoClient::new(WsChannel::connect(&url).await.unwrap());
+
+    let calls = (0..16).map(|n| {
+        let mut client = client.clone();
+        tokio::spawn(async move { client.echo(Line { text: n.to_string() }).await.unwrap().into_inner().text })
+    });
+    for (n, call) in calls.collect::<Vec<_>>().into_iter().enumerate() {
+        assert_eq!(call.await.unwrap(), n.to_string());
+    }
+}
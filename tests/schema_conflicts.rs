@@ -0,0 +1,59 @@
+#![cfg(feature = "build-schemas")]
+
+use proto_rs::schemas::conflicts;
+
+mod billing {
+    use proto_rs::proto_message;
+
+    #[proto_message(proto_path = "protos/tests/conflicts_billing.proto", proto_package = "conflicts")]
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Id {
+        pub value: u64,
+    }
+
+    #[proto_message(proto_path = "protos/tests/conflicts_billing.proto", proto_package = "conflicts")]
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Cursor {
+        pub offset: u32,
+    }
+}
+
+mod users {
+    use proto_rs::proto_message;
+
+    #[proto_message(proto_path = "protos/tests/conflicts_users.proto", proto_package = "conflicts")]
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Id {
+        pub value: String,
+    }
+
+    #[proto_message(proto_path = "protos/tests/conflicts_users.proto", proto_package = "conflicts")]
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Cursor {
+        pub offset: u32,
+    }
+}
+
+#[test]
+fn differing_definitions_of_one_name_are_reported() {
+    let conflicts = conflicts();
+    let conflict = conflicts.iter().find(|conflict| conflict.full_name() == "conflicts.Id").expect("Id conflict is reported");
+    assert_eq!(conflict.definitions.len(), 2);
+    let mut modules: Vec<&str> = conflict.definitions.iter().map(|definition| definition.module_path).collect();
+    modules.sort_unstable();
+    assert_eq!(modules, ["schema_conflicts::billing", "schema_conflicts::users"]);
+    assert!(conflict.definitions.iter().any(|definition| definition.definition.contains("uint64 value = 1;")));
+    assert!(conflict.definitions.iter().any(|definition| definition.definition.contains("string value = 1;")));
+
+    let report = conflict.to_string();
+    assert!(report.starts_with("`conflicts.Id` has 2 different definitions:"), "{report}");
+    assert!(
+        report.contains("schema_conflicts::users (protos/tests/conflicts_users.proto):"),
+        "{report}"
+    );
+}
+
+#[test]
+fn identical_definitions_are_not_conflicts() {
+    assert!(!conflicts().iter().any(|conflict| conflict.full_name() == "conflicts.Cursor"));
+}
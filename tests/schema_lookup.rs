@@ -0,0 +1,67 @@
+#![cfg(feature = "build-schemas")]
+#![cfg_attr(not(feature = "stable"), feature(impl_trait_in_assoc_type))]
+
+use proto_rs::proto_message;
+use proto_rs::proto_rpc;
+use proto_rs::schemas;
+use proto_rs::schemas::ProtoEntry;
+use tonic::Response;
+
+#[proto_message(proto_path = "protos/tests/lookup.proto", proto_package = "acme.lookup.v1")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Ticket {
+    pub id: u64,
+}
+
+#[proto_message(proto_path = "protos/tests/lookup.proto", proto_package = "acme.lookup.v1")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Priority {
+    #[default]
+    Low,
+    High,
+}
+
+#[proto_message(proto_path = "protos/tests/lookup_other.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Unrelated {
+    pub note: String,
+}
+
+#[proto_rpc(
+    proto_package = "acme.lookup.v1",
+    proto_path = "protos/tests/lookup.proto",
+    rpc_server = true,
+    rpc_client = true
+)]
+pub trait Desk {
+    async fn open(&self, request: Request<Ticket>) -> Result<Response<Ticket>, Status>;
+}
+
+#[test]
+fn lookup_finds_types_and_services_by_full_name() {
+    let ticket = schemas::lookup("acme.lookup.v1.Ticket").expect("message is registered");
+    assert!(matches!(ticket.content, ProtoEntry::Struct { .. }));
+    let priority = schemas::lookup("acme.lookup.v1.Priority").expect("enum is registered");
+    assert!(matches!(priority.content, ProtoEntry::SimpleEnum { .. }));
+    let desk = schemas::lookup("acme.lookup.v1.Desk").expect("service is registered");
+    assert!(matches!(desk.content, ProtoEntry::Service { .. }));
+
+    assert!(schemas::lookup("Ticket").is_none());
+    assert!(schemas::lookup("acme.lookup.v1.Missing").is_none());
+}
+
+#[test]
+fn by_package_lists_the_package_only() {
+    let mut names: Vec<_> = schemas::by_package("acme.lookup.v1").map(|schema| schema.id.name).collect();
+    names.sort_unstable();
+    assert_eq!(names, ["Desk", "Priority", "Ticket"]);
+    assert!(schemas::by_package("acme.missing").next().is_none());
+}
+
+#[test]
+fn services_lists_each_service_once() {
+    let services: Vec<_> = schemas::services().map(|schema| schema.id.name).collect();
+    assert_eq!(services.iter().filter(|name| **name == "Desk").count(), 1);
+    assert!(services.iter().all(|name| *name != "Ticket"));
+    assert!(schemas::service_names().contains(&"acme.lookup.v1.Desk".to_string()));
+}
@@ -0,0 +1,149 @@
+#![cfg(feature = "build-schemas")]
+
+use std::collections::HashMap;
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+use proto_rs::schemas::ProtoIdentifiable;
+use proto_rs::schemas::ScrubPolicy;
+use proto_rs::schemas::scrub;
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Customer {
+    pub id: u64,
+    #[proto(sensitive)]
+    pub email: String,
+    #[proto(sensitive)]
+    pub birth_year: u32,
+    #[proto(sensitive)]
+    pub card: Vec<u8>,
+    #[proto(sensitive)]
+    pub phones: Vec<String>,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Contact {
+    Anonymous,
+    Known(Customer),
+    Guest {
+        #[proto(sensitive)]
+        name: String,
+        visits: u32,
+    },
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Request {
+    pub route: String,
+    pub customer: Option<Customer>,
+    pub contacts: Vec<Contact>,
+    pub by_region: HashMap<String, Customer>,
+    #[proto(sensitive)]
+    pub tokens: HashMap<String, String>,
+}
+
+fn customer() -> Customer {
+    Customer {
+        id: 7,
+        email: "jane@example.com".into(),
+        birth_year: 1990,
+        card: vec![4, 1, 1, 1],
+        phones: vec!["+1 555".into(), "+1 556".into()],
+    }
+}
+
+fn request() -> Request {
+    Request {
+        route: "/orders".into(),
+        customer: Some(customer()),
+        contacts: vec![
+            Contact::Anonymous,
+            Contact::Known(customer()),
+            Contact::Guest {
+                name: "Jane".into(),
+                visits: 3,
+            },
+        ],
+        by_region: HashMap::from([("eu".to_string(), customer())]),
+        tokens: HashMap::from([("session".to_string(), "secret".to_string())]),
+    }
+}
+
+fn type_name() -> String {
+    let ident = <Request as ProtoIdentifiable>::PROTO_IDENT;
+    if ident.proto_package_name.is_empty() {
+        ident.name.to_string()
+    } else {
+        format!("{}.{}", ident.proto_package_name, ident.name)
+    }
+}
+
+fn scrubbed(policy: ScrubPolicy) -> Request {
+    let bytes = scrub(&type_name(), &request().encode_to_vec(), policy).expect("request scrubs");
+    <Request as ProtoDecode>::decode(bytes.as_slice(), DecodeContext::default()).expect("scrubbed request decodes")
+}
+
+#[test]
+fn remove_drops_sensitive_fields_at_every_depth() {
+    let request = scrubbed(ScrubPolicy::Remove);
+    let expected = Customer {
+        id: 7,
+        ..Customer::default()
+    };
+    assert_eq!(request.route, "/orders");
+    assert_eq!(request.customer, Some(expected.clone()));
+    assert_eq!(
+        request.contacts,
+        vec![
+            Contact::Anonymous,
+            Contact::Known(expected.clone()),
+            Contact::Guest {
+                name: String::new(),
+                visits: 3,
+            },
+        ]
+    );
+    assert_eq!(request.by_region["eu"], expected);
+    assert!(request.tokens.is_empty());
+}
+
+#[test]
+fn mask_replaces_strings_and_bytes() {
+    let request = scrubbed(ScrubPolicy::Mask("***"));
+    let customer = request.customer.expect("customer kept");
+    assert_eq!(customer.id, 7);
+    assert_eq!(customer.email, "***");
+    assert_eq!(customer.birth_year, 0);
+    assert_eq!(customer.card, b"***");
+    assert_eq!(customer.phones, vec!["***", "***"]);
+    assert_eq!(
+        request.contacts[2],
+        Contact::Guest {
+            name: "***".into(),
+            visits: 3,
+        }
+    );
+    assert!(request.tokens.is_empty());
+}
+
+#[test]
+fn payload_without_sensitive_fields_is_unchanged() {
+    let plain = Request {
+        route: "/health".into(),
+        contacts: vec![Contact::Anonymous],
+        ..Request::default()
+    };
+    let bytes = plain.encode_to_vec();
+    assert_eq!(scrub(&type_name(), &bytes, ScrubPolicy::Remove).expect("request scrubs"), bytes);
+}
+
+#[test]
+fn unknown_type_and_malformed_bytes_are_errors() {
+    assert!(scrub("nowhere.Missing", &[], ScrubPolicy::Remove).is_err());
+    assert!(scrub(&type_name(), &[0x0A, 0x05, b'a'], ScrubPolicy::Remove).is_err());
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "build-schemas")]
+
+use proto_rs::proto_message;
+use proto_rs::schemas;
+use proto_rs::schemas::ProtoIdentifiable;
+use proto_rs::schemas::SelfDescribing;
+use proto_rs::schemas::TypeRegistry;
+
+#[proto_message(proto_path = "protos/tests/self_describing.proto", proto_package = "events.v1")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Signup {
+    pub user: String,
+}
+
+#[proto_message(proto_path = "protos/tests/self_describing.proto", proto_package = "events.v1")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Churn {
+    pub user: String,
+    pub reason: String,
+}
+
+fn signup() -> Signup {
+    Signup { user: "ada".into() }
+}
+
+#[test]
+fn envelope_roundtrips_with_its_type_url_and_hash() {
+    let envelope = SelfDescribing::new(signup());
+    assert_eq!(envelope.type_url, "type.googleapis.com/events.v1.Signup");
+    assert_eq!(
+        Some(envelope.schema_hash),
+        schemas::schema_hash(&<Signup as ProtoIdentifiable>::PROTO_IDENT)
+    );
+
+    let decoded = SelfDescribing::<Signup>::decode(&envelope.encode_to_vec()).expect("envelope decodes");
+    assert_eq!(decoded, envelope);
+    assert!(decoded.is_current());
+}
+
+#[test]
+fn envelope_rejects_another_type() {
+    let bytes = SelfDescribing::new(signup()).encode_to_vec();
+    let err = SelfDescribing::<Churn>::decode(&bytes).unwrap_err();
+    assert!(err.to_string().contains("envelope holds type.googleapis.com/events.v1.Signup, expected events.v1.Churn"));
+}
+
+#[test]
+fn schema_hash_follows_the_definition() {
+    let signup_hash = schemas::schema_hash(&<Signup as ProtoIdentifiable>::PROTO_IDENT).expect("signup is registered");
+    let churn_hash = schemas::schema_hash(&<Churn as ProtoIdentifiable>::PROTO_IDENT).expect("churn is registered");
+    assert_ne!(signup_hash, churn_hash);
+
+    let mut stale = SelfDescribing::new(signup());
+    stale.schema_hash ^= 1;
+    let decoded = SelfDescribing::<Signup>::decode(&stale.encode_to_vec()).expect("stale envelope still decodes");
+    assert!(!decoded.is_current());
+}
+
+#[test]
+fn registry_dispatches_envelopes_by_type_url() {
+    let types = TypeRegistry::new().with::<Signup>().with::<Churn>();
+    let churn = Churn {
+        user: "ada".into(),
+        reason: "price".into(),
+    };
+    let decoded = types.decode_any_registered(&SelfDescribing::new(churn.clone()).encode_to_vec()).expect("envelope decodes");
+    assert_eq!(decoded.type_url, "type.googleapis.com/events.v1.Churn");
+    assert_eq!(*decoded.value.downcast::<Churn>().expect("decoded as Churn"), churn);
+
+    let unknown = TypeRegistry::new().decode_any_registered(&SelfDescribing::new(signup()).encode_to_vec());
+    assert!(unknown.is_err());
+}
@@ -0,0 +1,63 @@
+use proto_rs::DecodeContext;
+use proto_rs::ExtensionSet;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: u64,
+    pub name: String,
+    #[proto(skip)]
+    #[serde(skip)]
+    pub session: Option<u32>,
+    #[proto(extensions = "100..200")]
+    pub extensions: ExtensionSet,
+}
+
+#[test]
+fn serde_and_protobuf_carry_the_same_fields() {
+    let profile = Profile {
+        id: 7,
+        name: "ada".into(),
+        session: Some(3),
+        ..Profile::default()
+    };
+    let json = serde_json::to_value(&profile).unwrap();
+    assert_eq!(json, serde_json::json!({ "id": 7, "name": "ada" }));
+
+    let from_json: Profile = serde_json::from_value(json).unwrap();
+    let from_proto = Profile::decode(profile.encode_to_vec().as_slice(), DecodeContext::default()).unwrap();
+    assert_eq!(from_json, from_proto);
+    assert_eq!(from_json.session, None);
+}
+
+#[cfg(feature = "serde")]
+mod flag {
+    use super::*;
+
+    #[proto_message(serde)]
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Order {
+        pub id: u64,
+        pub lines: Vec<String>,
+        #[proto(skip)]
+        pub cached_total: u64,
+    }
+
+    #[test]
+    fn serde_flag_adds_the_derives() {
+        let order = Order {
+            id: 1,
+            lines: vec!["tea".into()],
+            cached_total: 12,
+        };
+        let json = serde_json::to_string(&order).unwrap();
+        assert_eq!(json, r#"{"id":1,"lines":["tea"]}"#);
+        let decoded: Order = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, Order { cached_total: 0, ..order });
+    }
+}
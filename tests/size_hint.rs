@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+
+use proto_rs::ArchivedProtoMessageWriter;
+use proto_rs::ProtoEncode;
+use proto_rs::ProtoExt;
+use proto_rs::proto_message;
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Status {
+    #[default]
+    Pending,
+    Active,
+    Closed,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LineItem {
+    pub sku: String,
+    pub quantity: u32,
+    pub price: f64,
+    pub discount: i64,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Reading {
+    pub sensor: u32,
+    pub value: f64,
+    pub delta: i64,
+    pub status: Status,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Payment {
+    Cash,
+    Card(String),
+    Transfer { iban: String, reference: u64 },
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Order {
+    pub id: u64,
+    pub status: Status,
+    pub customer: String,
+    pub note: Option<String>,
+    pub weight: Option<NonZeroU32>,
+    pub readings: Vec<Reading>,
+    pub scores: Vec<i32>,
+    pub history: VecDeque<Status>,
+    pub attachment: Vec<u8>,
+    pub digest: [u8; 8],
+    pub by_sensor: BTreeMap<u32, Reading>,
+    pub payment: Option<Payment>,
+    pub boxed: Option<Box<LineItem>>,
+    pub shared: Option<Arc<LineItem>>,
+}
+
+fn item(n: u32) -> LineItem {
+    LineItem {
+        sku: format!("SKU-{n:05}"),
+        quantity: n,
+        price: f64::from(n) * 1.5,
+        discount: -i64::from(n),
+    }
+}
+
+fn reading(n: u32) -> Reading {
+    Reading {
+        sensor: n,
+        value: f64::from(n) / 3.0,
+        delta: -i64::from(n),
+        status: Status::Closed,
+    }
+}
+
+fn order(readings: u32) -> Order {
+    Order {
+        id: u64::MAX,
+        status: Status::Active,
+        customer: "customer name".repeat(4),
+        note: Some("leave at the door".into()),
+        weight: NonZeroU32::new(7),
+        readings: (0..readings).map(reading).collect(),
+        scores: vec![-1, 0, 1, i32::MAX, i32::MIN],
+        history: VecDeque::from([Status::Pending, Status::Active, Status::Closed]),
+        attachment: vec![0xAB; 300],
+        digest: [1, 2, 3, 4, 5, 6, 7, 8],
+        by_sensor: (0..readings).map(|n| (n, reading(n))).collect(),
+        payment: Some(Payment::Transfer {
+            iban: "DE00 0000".into(),
+            reference: 9,
+        }),
+        boxed: Some(Box::new(item(2))),
+        shared: Some(Arc::new(item(3))),
+    }
+}
+
+fn assert_bounds<T: ProtoEncode + ProtoExt>(value: &T) -> usize {
+    let hint = value.encoded_len_hint().expect("type provides a size hint");
+    let len = value.encode_to_vec().len();
+    assert!(hint >= len, "hint {hint} is below the encoded length {len}");
+    hint
+}
+
+#[test]
+fn hint_bounds_encoded_length() {
+    for readings in [0, 1, 5, 40] {
+        assert_bounds(&order(readings));
+    }
+    assert_bounds(&item(1));
+    assert_bounds(&Payment::Cash);
+    assert_bounds(&Payment::Transfer {
+        iban: String::new(),
+        reference: 0,
+    });
+    assert_bounds(&Status::Closed);
+    assert_bounds(&vec![reading(1), reading(2)]);
+    assert_bounds(&vec![-1_i64, 0, i64::MAX]);
+    assert_bounds(&HashMap::from([(1_u32, -1_i32), (2, 300)]));
+}
+
+#[test]
+fn repeated_hints_use_the_worst_case_per_element() {
+    // 3 * 10 bytes for the varints, not their actual 3 bytes
+    assert_eq!(vec![1_u64, 2, 3].encoded_len_hint(), Some(30));
+    let readings = vec![reading(1); 4];
+    assert_eq!(readings.encoded_len_hint(), Some(4 * Reading::MAX_PAYLOAD_LEN.unwrap()));
+    let widest = Reading {
+        sensor: u32::MAX,
+        value: 1.0,
+        delta: i64::MIN,
+        status: Status::Closed,
+    };
+    assert_bounds(&vec![widest; 4]);
+}
+
+#[test]
+fn unbounded_elements_have_no_hint() {
+    assert_eq!(vec![item(1)].encoded_len_hint(), None);
+    assert_eq!(vec![String::from("a")].encoded_len_hint(), None);
+    assert_eq!(HashMap::from([(String::from("k"), 1_u64)]).encoded_len_hint(), None);
+    assert_eq!(vec![vec![1_u32]].encoded_len_hint(), None);
+}
+
+#[test]
+fn default_message_hints_zero() {
+    assert_eq!(Order::default().encoded_len_hint(), Some(0));
+    assert_eq!(Status::Pending.encoded_len_hint(), Some(0));
+}
+
+#[test]
+fn hinted_messages_encode_without_growing() {
+    for readings in [1, 5, 40, 200] {
+        let order = order(readings);
+        let hint = assert_bounds(&order);
+        let raw = ArchivedProtoMessageWriter::<Order>::new(&order).expect("non-default order").to_vec_raw();
+        assert_eq!(raw.capacity(), hint, "writer reallocated for {readings} readings");
+    }
+}
+
+#[test]
+fn scalar_hints_are_exact() {
+    let line = LineItem {
+        sku: "x".into(),
+        quantity: 300,
+        price: 0.0,
+        discount: 0,
+    };
+    assert_eq!(line.encoded_len_hint(), Some(line.encode_to_vec().len()));
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "arc_swap")]
+
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::proto_message;
+use proto_rs::snapshot::encode_snapshot;
+use proto_rs::snapshot::encode_snapshot_offloaded;
+use proto_rs::snapshot::snapshot;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[proto_message]
+pub struct Level {
+    pub price: u64,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[proto_message]
+pub struct OrderBook {
+    pub version: u64,
+    pub levels: Vec<Level>,
+}
+
+fn book(version: u64, depth: u64) -> OrderBook {
+    OrderBook {
+        version,
+        levels: (0..depth).map(|price| Level { price, size: version }).collect(),
+    }
+}
+
+#[test]
+fn snapshot_encodes_the_loaded_state() {
+    let state = ArcSwap::from_pointee(book(1, 3));
+    let taken = snapshot(&state);
+    state.store(Arc::new(book(2, 3)));
+
+    assert_eq!(
+        OrderBook::decode(taken.bytes.clone(), DecodeContext::default()).unwrap(),
+        *taken.value
+    );
+    assert_eq!(taken.value.version, 1);
+    let current = OrderBook::decode(encode_snapshot(&state), DecodeContext::default()).unwrap();
+    assert_eq!(current.version, 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn large_states_encode_on_the_blocking_pool() {
+    let state = ArcSwap::from_pointee(book(3, 10_000));
+    let offloaded = encode_snapshot_offloaded(&state, 1024).await;
+    let inline = encode_snapshot_offloaded(&state, usize::MAX).await;
+    assert_eq!(offloaded, inline);
+    assert_eq!(offloaded, encode_snapshot(&state));
+    assert_eq!(OrderBook::decode(offloaded, DecodeContext::default()).unwrap().levels.len(), 10_000);
+}
@@ -0,0 +1,122 @@
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::SoaColumns;
+use proto_rs::proto_message;
+
+#[cfg(feature = "build-schemas")]
+mod common;
+
+#[proto_message(proto_path = "protos/tests/soa.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Trade {
+    pub price: u64,
+    pub size: u32,
+    pub venue: String,
+}
+
+#[proto_message(proto_path = "protos/tests/soa.proto")]
+#[proto(soa)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Trades {
+    pub price: Vec<u64>,
+    pub size: Vec<u32>,
+    pub venue: Vec<String>,
+}
+
+#[proto_message(proto_path = "protos/tests/soa.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Tape {
+    pub symbol: String,
+    pub trades: Trades,
+}
+
+// The same message with one struct per row.
+#[proto_message(proto_path = "protos/tests/soa.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RowTape {
+    pub symbol: String,
+    pub trades: Vec<Trade>,
+}
+
+fn rows(count: u64) -> Vec<Trade> {
+    (0..count)
+        .map(|idx| Trade {
+            price: 100 + idx,
+            size: u32::try_from(idx % 3).unwrap(),
+            venue: if idx % 2 == 0 { "xnas".into() } else { String::new() },
+        })
+        .collect()
+}
+
+fn columns(rows: &[Trade]) -> Trades {
+    Trades {
+        price: rows.iter().map(|trade| trade.price).collect(),
+        size: rows.iter().map(|trade| trade.size).collect(),
+        venue: rows.iter().map(|trade| trade.venue.clone()).collect(),
+    }
+}
+
+#[test]
+fn columns_encode_like_rows() {
+    // row 0 has only default values and is still written
+    let rows = rows(50);
+    let row_tape = RowTape {
+        symbol: "AAPL".into(),
+        trades: rows.clone(),
+    };
+    let tape = Tape {
+        symbol: "AAPL".into(),
+        trades: columns(&rows),
+    };
+    assert_eq!(tape.trades.rows(), 50);
+
+    let bytes = tape.encode_to_vec();
+    assert_eq!(bytes, row_tape.encode_to_vec());
+    assert!(tape.encoded_len_hint().unwrap() >= bytes.len());
+
+    assert_eq!(Tape::decode(bytes.as_slice(), DecodeContext::default()).unwrap(), tape);
+    assert_eq!(RowTape::decode(bytes.as_slice(), DecodeContext::default()).unwrap(), row_tape);
+}
+
+#[test]
+fn empty_columns_are_not_written() {
+    let tape = Tape {
+        symbol: "MSFT".into(),
+        trades: Trades::default(),
+    };
+    let bytes = tape.encode_to_vec();
+    assert_eq!(
+        bytes,
+        RowTape {
+            symbol: "MSFT".into(),
+            trades: Vec::new()
+        }
+        .encode_to_vec()
+    );
+    assert_eq!(Tape::decode(bytes.as_slice(), DecodeContext::default()).unwrap(), tape);
+}
+
+#[test]
+fn rows_stop_at_the_shortest_column() {
+    let mut trades = columns(&rows(3));
+    trades.venue.pop();
+    assert_eq!(trades.rows(), 2);
+    let decoded = Trades::decode(trades.encode_to_vec().as_slice(), DecodeContext::default()).unwrap();
+    assert_eq!(decoded, columns(&rows(2)));
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn schema_describes_one_row() {
+    let proto = common::emitted_proto("soa", "protos/tests/soa.proto");
+
+    assert!(
+        proto.contains("message Trades {\n  uint64 price = 1;\n  uint32 size = 2;\n  string venue = 3;\n}"),
+        "{proto}"
+    );
+    assert!(
+        proto.contains("message Tape {\n  string symbol = 1;\n  repeated Trades trades = 2;\n}"),
+        "{proto}"
+    );
+}
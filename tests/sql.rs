@@ -0,0 +1,109 @@
+#![cfg(feature = "sql")]
+
+use proto_rs::proto_message;
+use proto_rs::sql::RowCodec;
+use proto_rs::sql::SqlType;
+use proto_rs::sql::SqlValue;
+use proto_rs::transcode::TranscodeError;
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Status {
+    #[default]
+    Pending,
+    Filled,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OrderSnapshot {
+    pub id: u64,
+    pub account: String,
+    pub status: Status,
+    pub qty: u32,
+    pub price: f64,
+    pub note: Option<String>,
+    pub signature: Vec<u8>,
+    pub live: bool,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Batch {
+    pub id: u64,
+    pub orders: Vec<OrderSnapshot>,
+}
+
+#[test]
+fn flat_message_round_trips_through_a_row() {
+    let codec = RowCodec::<OrderSnapshot>::new().unwrap();
+    let order = OrderSnapshot {
+        id: 7,
+        account: "acc-1".into(),
+        status: Status::Filled,
+        qty: 0,
+        price: 10.5,
+        note: None,
+        signature: vec![1, 2],
+        live: true,
+    };
+
+    let row = codec.to_row(&order).unwrap();
+    assert_eq!(
+        row,
+        vec![
+            SqlValue::BigInt(7),
+            SqlValue::Text("acc-1".into()),
+            SqlValue::Int(1),
+            SqlValue::BigInt(0),
+            SqlValue::Double(10.5),
+            SqlValue::Null,
+            SqlValue::Bytes(vec![1, 2]),
+            SqlValue::Bool(true),
+        ]
+    );
+    assert_eq!(codec.from_row(row).unwrap(), order);
+
+    let with_note = OrderSnapshot {
+        note: Some(String::new()),
+        ..order
+    };
+    assert_eq!(codec.from_row(codec.to_row(&with_note).unwrap()).unwrap(), with_note);
+}
+
+#[test]
+fn statements_follow_the_columns() {
+    let codec = RowCodec::<OrderSnapshot>::new().unwrap();
+    let types: Vec<_> = codec.columns().iter().map(|column| (column.name.as_str(), column.sql_type, column.nullable)).collect();
+    assert_eq!(types[2], ("status", SqlType::Integer, false));
+    assert_eq!(types[5], ("note", SqlType::Text, true));
+
+    assert_eq!(
+        codec.create_table("order_snapshots"),
+        "CREATE TABLE IF NOT EXISTS order_snapshots (\n    \"id\" BIGINT NOT NULL,\n    \"account\" TEXT NOT NULL,\n    \"status\" INTEGER NOT NULL,\n    \"qty\" BIGINT NOT NULL,\n    \"price\" DOUBLE PRECISION NOT NULL,\n    \"note\" TEXT,\n    \"signature\" BYTEA NOT NULL,\n    \"live\" BOOLEAN NOT NULL\n)"
+    );
+    assert_eq!(
+        codec.insert("order_snapshots"),
+        "INSERT INTO order_snapshots (\"id\", \"account\", \"status\", \"qty\", \"price\", \"note\", \"signature\", \"live\") VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+    );
+}
+
+#[test]
+fn nested_messages_and_wide_values_are_rejected() {
+    let Err(TranscodeError::Invalid { path, .. }) = RowCodec::<Batch>::new() else {
+        panic!("repeated field accepted");
+    };
+    assert_eq!(path, "orders");
+
+    let codec = RowCodec::<OrderSnapshot>::new().unwrap();
+    let huge = OrderSnapshot {
+        id: u64::MAX,
+        ..Default::default()
+    };
+    assert!(matches!(codec.to_row(&huge), Err(TranscodeError::Invalid { path, .. }) if path == "id"));
+
+    let mut row = codec.to_row(&OrderSnapshot::default()).unwrap();
+    row[0] = SqlValue::Text("7".into());
+    assert!(matches!(codec.from_row(row), Err(TranscodeError::Invalid { .. })));
+    assert!(codec.from_row(vec![SqlValue::Null]).is_err());
+}
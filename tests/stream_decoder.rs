@@ -0,0 +1,125 @@
+use proto_rs::DecodeContext;
+use proto_rs::MAX_HEADER_LEN;
+use proto_rs::ProtoEncode;
+use proto_rs::encode_length_delimiter;
+use proto_rs::peek_len;
+use proto_rs::proto_message;
+use proto_rs::stream::Decoder;
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Tick {
+    pub symbol: String,
+    pub price: u64,
+    pub flags: Vec<u32>,
+}
+
+fn tick(price: u64) -> Tick {
+    Tick {
+        symbol: "BTC-USD".to_string(),
+        price,
+        flags: vec![1, 2, 300],
+    }
+}
+
+fn frame(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    encode_length_delimiter(bytes.len(), &mut out).expect("length fits");
+    out.extend_from_slice(bytes);
+    out
+}
+
+fn drain(decoder: &mut Decoder<'_, Tick>, chunk: &[u8]) -> Vec<Tick> {
+    let mut out = Vec::new();
+    let mut next = decoder.feed(chunk).expect("chunk decodes");
+    while let Some(tick) = next {
+        out.push(tick);
+        next = decoder.decode_next().expect("buffered frame decodes");
+    }
+    out
+}
+
+#[test]
+fn frames_split_at_every_byte_are_reassembled() {
+    let stream = [frame(&tick(1).encode_to_vec()), frame(&tick(2).encode_to_vec())].concat();
+    let mut decoder = Decoder::<Tick>::new();
+    let mut received = Vec::new();
+    for byte in &stream {
+        received.extend(drain(&mut decoder, std::slice::from_ref(byte)));
+    }
+    assert_eq!(received, vec![tick(1), tick(2)]);
+    assert!(decoder.is_idle());
+}
+
+#[test]
+fn one_chunk_can_complete_several_frames() {
+    let mut stream = [frame(&tick(1).encode_to_vec()), frame(&tick(2).encode_to_vec())].concat();
+    let third = frame(&tick(3).encode_to_vec());
+    let (head, tail) = third.split_at(4);
+    stream.extend_from_slice(head);
+
+    let mut decoder = Decoder::<Tick>::new();
+    assert_eq!(drain(&mut decoder, &stream), vec![tick(1), tick(2)]);
+    assert_eq!(decoder.buffered_len(), head.len());
+    assert!(!decoder.is_idle());
+    assert_eq!(drain(&mut decoder, tail), vec![tick(3)]);
+}
+
+#[test]
+fn empty_frames_decode_to_defaults() {
+    let mut decoder = Decoder::<Tick>::new();
+    assert_eq!(drain(&mut decoder, &[0, 0]), vec![Tick::default(), Tick::default()]);
+}
+
+#[test]
+fn oversized_frames_are_rejected_before_buffering() {
+    let mut decoder = Decoder::<Tick>::new().max_frame_len(8);
+    let mut length = Vec::new();
+    encode_length_delimiter(1 << 20, &mut length).expect("length fits");
+    let err = decoder.feed(&length).expect_err("frame above the limit");
+    assert!(err.to_string().contains("frame of 1048576 bytes exceeds the limit of 8"), "{err}");
+    assert_eq!(decoder.buffered_len(), 0);
+
+    let err = decoder.feed(&[0xff; 10]).expect_err("unterminated length");
+    assert!(err.to_string().contains("invalid frame length"), "{err}");
+    assert!(decoder.is_idle());
+}
+
+#[test]
+fn undecodable_frames_are_skipped() {
+    let stream = [frame(&[0x0a, 0x05, b'x']), frame(&tick(7).encode_to_vec())].concat();
+    let mut decoder = Decoder::<Tick>::new().with_context(DecodeContext::default());
+    assert!(decoder.feed(&stream).is_err());
+    assert_eq!(decoder.decode_next().expect("next frame decodes"), Some(tick(7)));
+    assert!(decoder.is_idle());
+}
+
+#[test]
+fn reset_drops_a_partial_frame() {
+    let bytes = frame(&tick(1).encode_to_vec());
+    let mut decoder = Decoder::<Tick>::new();
+    assert_eq!(decoder.feed(&bytes[..3]).expect("partial frame"), None);
+    decoder.reset();
+    assert!(decoder.is_idle());
+    assert_eq!(drain(&mut decoder, &bytes), vec![tick(1)]);
+}
+
+#[test]
+fn peek_len_reads_exactly_one_header() {
+    let bytes = frame(&tick(1).encode_to_vec());
+    let (len, header) = peek_len(&bytes).expect("complete header");
+    assert_eq!(header + len, bytes.len());
+    assert_eq!(peek_len(&[]), None);
+
+    let mut long = Vec::new();
+    encode_length_delimiter(300, &mut long).expect("length fits");
+    assert_eq!(peek_len(&long[..1]), None, "header split across reads");
+    assert_eq!(peek_len(&long), Some((300, 2)));
+    assert_eq!(peek_len(&[long.as_slice(), b"body"].concat()), Some((300, 2)));
+
+    let mut max = Vec::new();
+    encode_length_delimiter(usize::MAX, &mut max).expect("length fits");
+    assert_eq!(max.len(), MAX_HEADER_LEN);
+    assert_eq!(peek_len(&max), Some((usize::MAX, MAX_HEADER_LEN)));
+    assert_eq!(peek_len(&[0xff; MAX_HEADER_LEN]), None, "unterminated header");
+}
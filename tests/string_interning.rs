@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::intern::StringPool;
+use proto_rs::proto_message;
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Trade {
+    pub symbol: Arc<str>,
+    pub venue: Option<Arc<str>>,
+    pub tags: Vec<Arc<str>>,
+    pub price: u64,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Tape {
+    pub trades: Vec<Trade>,
+}
+
+fn trade(symbol: &str, price: u64) -> Trade {
+    Trade {
+        symbol: symbol.into(),
+        venue: Some("XNAS".into()),
+        tags: vec!["lit".into()],
+        price,
+    }
+}
+
+#[test]
+fn arc_str_fields_roundtrip() {
+    let trade = trade("AAPL", 190);
+    let bytes = trade.encode_to_vec();
+    let plain = PlainTrade {
+        symbol: "AAPL".into(),
+        venue: Some("XNAS".into()),
+        tags: vec!["lit".into()],
+        price: 190,
+    };
+    assert_eq!(bytes, plain.encode_to_vec());
+    assert_eq!(Trade::decode(bytes.as_slice(), DecodeContext::default()).unwrap(), trade);
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PlainTrade {
+    pub symbol: String,
+    pub venue: Option<String>,
+    pub tags: Vec<String>,
+    pub price: u64,
+}
+
+#[test]
+fn interned_strings_share_one_allocation() {
+    static POOL: StringPool = StringPool::new();
+    let tape = Tape {
+        trades: vec![trade("AAPL", 1), trade("MSFT", 2), trade("AAPL", 3)],
+    };
+    let bytes = tape.encode_to_vec();
+
+    let ctx = DecodeContext::default().with_interner(&POOL);
+    let decoded = Tape::decode(bytes.as_slice(), ctx).unwrap();
+    assert_eq!(decoded, tape);
+    assert!(Arc::ptr_eq(&decoded.trades[0].symbol, &decoded.trades[2].symbol));
+    assert!(Arc::ptr_eq(
+        decoded.trades[0].venue.as_ref().unwrap(),
+        decoded.trades[1].venue.as_ref().unwrap()
+    ));
+    // AAPL, MSFT, XNAS, lit
+    assert_eq!(POOL.len(), 4);
+
+    let again = Tape::decode(bytes.as_slice(), ctx).unwrap();
+    assert!(Arc::ptr_eq(&decoded.trades[1].symbol, &again.trades[1].symbol));
+
+    let uninterned = Tape::decode(bytes.as_slice(), DecodeContext::default()).unwrap();
+    assert!(!Arc::ptr_eq(&uninterned.trades[0].symbol, &uninterned.trades[2].symbol));
+}
+
+#[test]
+fn invalid_utf8_is_rejected() {
+    let bytes = [0x0a, 2, 0xff, 0xfe];
+    assert!(Trade::decode(bytes.as_slice(), DecodeContext::default()).is_err());
+}
@@ -0,0 +1,110 @@
+#![cfg(feature = "testkit")]
+
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+use proto_rs::testkit::ArbitraryConfig;
+use proto_rs::testkit::arbitrary;
+use proto_rs::testkit::arbitrary_bytes;
+use proto_rs::testkit::arbitrary_with;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Level {
+    #[default]
+    Low,
+    Mid,
+    High,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Leaf {
+    pub id: u64,
+    pub small: u8,
+    pub delta: i16,
+    pub label: String,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Ping,
+    Leaf(Leaf),
+    Move { x: i32, y: i32 },
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Node {
+    pub name: String,
+    pub level: Level,
+    pub weight: Option<NonZeroU32>,
+    pub ratio: f64,
+    pub flag: bool,
+    pub data: Vec<u8>,
+    pub digest: [u8; 4],
+    pub samples: Vec<i8>,
+    pub leaf: Option<Leaf>,
+    pub children: Vec<Node>,
+    pub index: HashMap<String, Leaf>,
+    pub events: Vec<Event>,
+}
+
+fn depth(node: &Node) -> usize {
+    node.children.iter().map(|child| depth(child) + 1).max().unwrap_or(0)
+}
+
+#[test]
+fn generated_messages_round_trip() {
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..200 {
+        let node: Node = arbitrary(&mut rng, 3).expect("generated node decodes");
+        let bytes = Node::encode_to_vec(&node);
+        let decoded = <Node as ProtoDecode>::decode(bytes.as_slice(), DecodeContext::default()).expect("re-encoded node decodes");
+        assert_eq!(decoded, node);
+        assert_eq!(node.digest.len(), 4);
+    }
+}
+
+#[test]
+fn depth_budget_limits_nesting() {
+    let mut rng = StdRng::seed_from_u64(11);
+    for budget in 0..3 {
+        for _ in 0..50 {
+            let node: Node = arbitrary(&mut rng, budget).expect("generated node decodes");
+            assert!(depth(&node) <= budget);
+        }
+    }
+    let flat: Node = arbitrary(&mut rng, 0).expect("generated node decodes");
+    assert!(flat.leaf.is_none());
+    assert!(flat.children.is_empty());
+    assert!(flat.events.iter().all(|event| *event == Event::Ping));
+}
+
+#[test]
+fn max_len_bounds_collections() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let config = ArbitraryConfig::new(2).with_max_len(2);
+    for _ in 0..50 {
+        let node: Node = arbitrary_with(&mut rng, &config).expect("generated node decodes");
+        assert!(node.name.len() <= 2);
+        assert!(node.data.len() <= 2);
+        assert!(node.children.len() <= 2);
+        assert!(node.index.len() <= 2);
+    }
+}
+
+#[test]
+fn same_seed_gives_same_bytes() {
+    let config = ArbitraryConfig::default();
+    let first = arbitrary_bytes::<Node, _>(&mut StdRng::seed_from_u64(42), &config);
+    let second = arbitrary_bytes::<Node, _>(&mut StdRng::seed_from_u64(42), &config);
+    assert_eq!(first, second);
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "testkit")]
+
+use std::collections::HashMap;
+use std::fs;
+
+use proto_rs::proto_message;
+use proto_rs::testkit::golden::GoldenSet;
+use proto_rs::testkit::golden::golden_bytes;
+use proto_rs::testkit::golden::golden_name;
+
+mod common;
+
+#[proto_message(proto_path = "protos/tests/golden.proto")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Priority {
+    #[default]
+    Low,
+    Normal,
+    Urgent,
+}
+
+#[proto_message(proto_path = "protos/tests/golden.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LineItem {
+    pub sku: String,
+    pub quantity: u32,
+    pub price: f64,
+}
+
+#[proto_message(proto_path = "protos/tests/golden.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Order {
+    pub id: u64,
+    pub priority: Priority,
+    pub items: Vec<LineItem>,
+    pub note: Option<String>,
+    pub tags: HashMap<String, u32>,
+    pub checksum: [u8; 8],
+}
+
+#[proto_message(proto_path = "protos/tests/golden.proto")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Payment {
+    Cash,
+    Card { last4: u16, network: String },
+    Voucher(LineItem),
+}
+
+#[test]
+fn wire_format_matches_golden_files() {
+    GoldenSet::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden"))
+        .check::<LineItem>()
+        .check::<Order>()
+        .check::<Payment>()
+        .finish();
+}
+
+#[test]
+fn samples_are_deterministic() {
+    assert_eq!(golden_bytes::<LineItem>(), golden_bytes::<LineItem>());
+    assert_eq!(golden_bytes::<Payment>(), golden_bytes::<Payment>());
+}
+
+#[test]
+fn missing_files_are_written() {
+    let dir = common::TempDir::new("golden_missing");
+    let set = GoldenSet::new(dir.path()).updating(false).check::<LineItem>();
+    assert_eq!(set.written(), [golden_name::<LineItem>()]);
+    assert!(set.failures().is_empty());
+    let stored = fs::read(dir.join(format!("{}.bin", golden_name::<LineItem>()))).expect("golden file written");
+    assert_eq!(stored, golden_bytes::<LineItem>().expect("sample"));
+
+    let set = GoldenSet::new(dir.path()).updating(false).check::<LineItem>();
+    assert!(set.written().is_empty());
+    set.finish();
+}
+
+#[test]
+fn drift_is_reported() {
+    let dir = common::TempDir::new("golden_drift");
+    fs::create_dir_all(dir.path()).expect("create dir");
+    let path = dir.join(format!("{}.bin", golden_name::<LineItem>()));
+    let mut bytes = golden_bytes::<LineItem>().expect("sample");
+    bytes[0] ^= 0xff;
+    fs::write(&path, &bytes).expect("write golden");
+
+    let set = GoldenSet::new(dir.path()).updating(false).check::<LineItem>();
+    assert_eq!(set.failures().len(), 1);
+    assert!(set.failures()[0].contains("differs at byte 0"), "{}", set.failures()[0]);
+
+    let set = GoldenSet::new(dir.path()).updating(true).check::<LineItem>();
+    assert!(set.failures().is_empty());
+    assert_eq!(fs::read(&path).expect("golden file"), golden_bytes::<LineItem>().expect("sample"));
+}
@@ -0,0 +1,180 @@
+#![cfg(all(feature = "testkit", feature = "transcode"))]
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+use proto_rs::testkit::golden::golden_bytes;
+use proto_rs::testkit::vectors::SAMPLE_CASE;
+use proto_rs::testkit::vectors::Vector;
+use proto_rs::testkit::vectors::VectorSet;
+use proto_rs::transcode::Transcoder;
+
+mod common;
+
+#[proto_message(proto_path = "protos/tests/vectors.proto")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Priority {
+    #[default]
+    Low,
+    Urgent,
+}
+
+#[proto_message(proto_path = "protos/tests/vectors.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Item {
+    pub sku: String,
+    pub quantity: u32,
+}
+
+#[proto_message(proto_path = "protos/tests/vectors.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Shipment {
+    pub id: u64,
+    pub priority: Priority,
+    pub items: Vec<Item>,
+    pub weights: Vec<i32>,
+    pub note: Option<String>,
+    pub tags: BTreeMap<String, u32>,
+    pub seal: Vec<u8>,
+    pub ratio: f64,
+}
+
+#[proto_message(proto_path = "protos/tests/vectors.proto")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Carrier {
+    Pickup,
+    Courier { name: String, express: bool },
+    Partner(Item),
+}
+
+fn shipment() -> Shipment {
+    Shipment {
+        id: 9_007_199_254_740_993,
+        priority: Priority::Urgent,
+        items: vec![
+            Item {
+                sku: "box \"A\"".into(),
+                quantity: 2,
+            },
+            Item::default(),
+        ],
+        weights: vec![-1, 300],
+        note: Some("fragile\nhandle with care".into()),
+        tags: BTreeMap::from([("dock".into(), 4), ("lane".into(), 0)]),
+        seal: vec![0x00, 0x41, 0xff],
+        ratio: 0.25,
+    }
+}
+
+#[test]
+fn vectors_are_written_as_bin_json_and_text() {
+    let dir = common::TempDir::new("vectors_triples");
+    let manifest =
+        VectorSet::new(dir.path()).add("full", &shipment()).unwrap().add("empty", &Shipment::default()).unwrap().finish().unwrap();
+
+    let type_dir = dir.join("vectors.Shipment");
+    assert_eq!(fs::read(type_dir.join("full.bin")).unwrap(), shipment().encode_to_vec());
+    assert_eq!(
+        fs::read_to_string(type_dir.join("full.txt")).unwrap(),
+        "# proto-message: vectors.Shipment\n\
+         id: 9007199254740993\n\
+         priority: URGENT\n\
+         items {\n  sku: \"box \\\"A\\\"\"\n  quantity: 2\n}\n\
+         items {}\n\
+         weights: -1\n\
+         weights: 300\n\
+         note: \"fragile\\nhandle with care\"\n\
+         tags {\n  key: \"dock\"\n  value: 4\n}\n\
+         tags {\n  key: \"lane\"\n}\n\
+         seal: \"\\000A\\377\"\n\
+         ratio: 0.25\n"
+    );
+    assert_eq!(
+        fs::read_to_string(type_dir.join("empty.txt")).unwrap(),
+        "# proto-message: vectors.Shipment\n"
+    );
+
+    // The JSON form encodes back to the same bytes.
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(type_dir.join("full.json")).unwrap()).unwrap();
+    assert_eq!(json["id"], "9007199254740993");
+    assert_eq!(json["seal"], "AEH/");
+    let bytes = Transcoder::of::<Shipment>().unwrap().json_to_binary(&json).unwrap();
+    assert_eq!(Shipment::decode(bytes.as_slice(), DecodeContext::default()).unwrap(), shipment());
+
+    let manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(manifest).unwrap()).unwrap();
+    assert_eq!(manifest["vectors"].as_array().unwrap().len(), 2);
+    assert_eq!(manifest["vectors"][0]["type"], "vectors.Shipment");
+    assert_eq!(manifest["vectors"][0]["txt"], "vectors.Shipment/full.txt");
+}
+
+#[test]
+fn oneof_variants_render_as_their_field() {
+    let dir = common::TempDir::new("vectors_oneof");
+    VectorSet::new(dir.path())
+        .add(
+            "courier",
+            &Carrier::Courier {
+                name: "Ana".into(),
+                express: true,
+            },
+        )
+        .unwrap()
+        .add("pickup", &Carrier::Pickup)
+        .unwrap()
+        .add(
+            "partner",
+            &Carrier::Partner(Item {
+                sku: "x".into(),
+                quantity: 1,
+            }),
+        )
+        .unwrap();
+
+    let text = |case: &str| fs::read_to_string(dir.join(format!("vectors.Carrier/{case}.txt"))).unwrap();
+    assert_eq!(
+        text("courier"),
+        "# proto-message: vectors.Carrier\ncourier {\n  name: \"Ana\"\n  express: true\n}\n"
+    );
+    // The first variant is the default and encodes as an empty message.
+    assert_eq!(text("pickup"), "# proto-message: vectors.Carrier\n");
+    assert_eq!(
+        text("partner"),
+        "# proto-message: vectors.Carrier\npartner {\n  sku: \"x\"\n  quantity: 1\n}\n"
+    );
+}
+
+#[test]
+fn samples_match_golden_bytes_and_cases_are_unique() {
+    let dir = common::TempDir::new("vectors_sample");
+    let set = VectorSet::new(dir.path())
+        .add_sample::<Shipment>()
+        .unwrap()
+        .add("again", &Item::default())
+        .unwrap()
+        .add("again", &Item::default())
+        .unwrap();
+    assert_eq!(
+        set.vectors(),
+        [
+            Vector {
+                type_name: "vectors.Shipment".into(),
+                case: SAMPLE_CASE.into()
+            },
+            Vector {
+                type_name: "vectors.Item".into(),
+                case: "again".into()
+            },
+        ]
+    );
+    assert_eq!(
+        fs::read(dir.join("vectors.Shipment/sample.bin")).unwrap(),
+        golden_bytes::<Shipment>().unwrap()
+    );
+
+    assert!(VectorSet::new(dir.path()).add("../escape", &Item::default()).is_err());
+    assert!(VectorSet::new(dir.path()).add("", &Item::default()).is_err());
+}
@@ -0,0 +1,218 @@
+#![cfg(feature = "transcode")]
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::encoding::decode_length_delimiter;
+use proto_rs::encoding::encode_length_delimiter;
+use proto_rs::encoding::length_delimiter_len;
+use proto_rs::proto_message;
+use proto_rs::schemas::ProtoIdentifiable;
+use proto_rs::transcode::TranscodeError;
+use proto_rs::transcode::Transcoder;
+use serde_json::json;
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Status {
+    #[default]
+    Pending,
+    Active,
+    Closed,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LineItem {
+    pub sku: String,
+    pub quantity: u32,
+    pub price: f64,
+    pub discount: i64,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Payment {
+    Cash,
+    Card(String),
+    Transfer { iban: String, reference: u64 },
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Order {
+    pub order_id: u64,
+    pub status: Status,
+    pub note: Option<String>,
+    pub items: Vec<LineItem>,
+    pub scores: Vec<i32>,
+    pub history: Vec<Status>,
+    pub attachment: Vec<u8>,
+    pub ratio: f32,
+    pub paid: bool,
+    pub totals: HashMap<String, u64>,
+    pub by_sku: BTreeMap<u32, LineItem>,
+    pub payments: Vec<Payment>,
+}
+
+fn order() -> Order {
+    let item = LineItem {
+        sku: "SKU-1".into(),
+        quantity: 3,
+        price: 2.5,
+        discount: -4,
+    };
+    Order {
+        order_id: u64::MAX,
+        status: Status::Active,
+        note: Some("leave at the door".into()),
+        items: vec![item.clone(), LineItem::default()],
+        scores: vec![-1, 0, i32::MAX],
+        history: vec![Status::Pending, Status::Closed],
+        attachment: vec![0, 1, 2, 0xFF],
+        ratio: 0.1,
+        paid: true,
+        totals: HashMap::from([("eur".to_string(), 10), ("usd".to_string(), 0)]),
+        by_sku: BTreeMap::from([(7, item)]),
+        payments: vec![
+            Payment::Cash,
+            Payment::Card("4111".into()),
+            Payment::Transfer {
+                iban: "DE00".into(),
+                reference: 9,
+            },
+        ],
+    }
+}
+
+fn decode(bytes: &[u8]) -> Order {
+    <Order as ProtoDecode>::decode(bytes, DecodeContext::default()).expect("transcoded order decodes")
+}
+
+#[test]
+fn binary_to_json_uses_proto3_mapping() {
+    let transcoder = Transcoder::of::<Order>().expect("order is registered");
+    let json = transcoder.binary_to_json(&order().encode_to_vec()).expect("order transcodes");
+    assert_eq!(json["orderId"], json!("18446744073709551615"));
+    assert_eq!(json["status"], json!("ACTIVE"));
+    assert_eq!(json["history"], json!(["PENDING", "CLOSED"]));
+    assert_eq!(json["items"][0]["discount"], json!("-4"));
+    assert_eq!(json["attachment"], json!("AAEC/w=="));
+    assert_eq!(json["ratio"], json!(0.1));
+    assert_eq!(json["totals"]["eur"], json!("10"));
+    assert_eq!(json["bySku"]["7"]["sku"], json!("SKU-1"));
+    assert_eq!(
+        json["payments"],
+        json!([{ "cash": {} }, { "card": "4111" }, { "transfer": { "iban": "DE00", "reference": "9" } }])
+    );
+}
+
+#[test]
+fn json_round_trips_through_binary() {
+    let transcoder = Transcoder::of::<Order>().expect("order is registered");
+    let order = order();
+    let json = transcoder.binary_to_json(&order.encode_to_vec()).expect("order transcodes");
+    let bytes = transcoder.json_to_binary(&json).expect("json transcodes");
+    assert_eq!(decode(&bytes), order);
+}
+
+#[test]
+fn json_accepts_proto_names_and_numeric_forms() {
+    let transcoder = Transcoder::of::<Order>().expect("order is registered");
+    let json = json!({
+        "order_id": 42,
+        "status": 2,
+        "scores": ["-5", 6],
+        "attachment": "AAEC_w",
+        "by_sku": { "3": { "quantity": "1" } },
+        "payments": [{ "card": "x" }],
+        "note": null,
+    });
+    let order = decode(&transcoder.json_to_binary(&json).expect("json transcodes"));
+    assert_eq!(order.order_id, 42);
+    assert_eq!(order.status, Status::Closed);
+    assert_eq!(order.scores, vec![-5, 6]);
+    assert_eq!(order.attachment, vec![0, 1, 2, 0xFF]);
+    assert_eq!(order.by_sku[&3].quantity, 1);
+    assert_eq!(order.payments, vec![Payment::Card("x".into())]);
+    assert_eq!(order.note, None);
+}
+
+#[test]
+fn invalid_json_reports_the_field_path() {
+    let transcoder = Transcoder::of::<Order>().expect("order is registered");
+    let error = transcoder.json_to_binary(&json!({ "items": [{}, { "quantity": -1 }] })).unwrap_err();
+    assert!(
+        matches!(&error, TranscodeError::Invalid { path, .. } if path == "items[1].quantity"),
+        "{error}"
+    );
+    let error = transcoder.json_to_binary(&json!({ "payments": [{ "cash": {}, "card": "x" }] })).unwrap_err();
+    assert!(matches!(error, TranscodeError::Invalid { .. }));
+}
+
+#[test]
+fn unknown_fields_are_rejected_unless_ignored() {
+    let json = json!({ "orderId": "1", "extra": true });
+    let transcoder = Transcoder::of::<Order>().expect("order is registered");
+    assert!(matches!(transcoder.json_to_binary(&json), Err(TranscodeError::Invalid { path, .. }) if path == "extra"));
+    let bytes = transcoder.ignoring_unknown_fields(true).json_to_binary(&json).expect("unknown field skipped");
+    assert_eq!(decode(&bytes).order_id, 1);
+}
+
+#[test]
+fn streams_transcode_in_both_directions() {
+    let ident = <Order as ProtoIdentifiable>::PROTO_IDENT;
+    let name = if ident.proto_package_name.is_empty() {
+        ident.name.to_string()
+    } else {
+        format!("{}.{}", ident.proto_package_name, ident.name)
+    };
+    let transcoder = Transcoder::for_name(&name).expect("order is registered by name");
+    let orders = [
+        order(),
+        Order::default(),
+        Order {
+            paid: true,
+            ..Order::default()
+        },
+    ];
+
+    let mut binary = Vec::new();
+    for order in &orders {
+        let bytes = order.encode_to_vec();
+        encode_length_delimiter(bytes.len(), &mut binary).expect("length fits");
+        binary.extend_from_slice(&bytes);
+    }
+    let mut ndjson = Vec::new();
+    assert_eq!(
+        transcoder.binary_stream_to_json(binary.as_slice(), &mut ndjson).expect("binary stream transcodes"),
+        3
+    );
+    assert_eq!(String::from_utf8(ndjson.clone()).expect("json is utf-8").lines().count(), 3);
+
+    let mut framed = Vec::new();
+    assert_eq!(
+        transcoder.json_stream_to_binary(ndjson.as_slice(), &mut framed).expect("json stream transcodes"),
+        3
+    );
+    let mut rest = framed.as_slice();
+    for expected in &orders {
+        let len = decode_length_delimiter(rest).expect("length prefix");
+        let (message, tail) = rest[length_delimiter_len(len)..].split_at(len);
+        assert_eq!(&decode(message), expected);
+        rest = tail;
+    }
+    assert!(rest.is_empty());
+}
+
+#[test]
+fn unknown_names_are_rejected() {
+    assert!(matches!(
+        Transcoder::for_name("nowhere.Missing"),
+        Err(TranscodeError::UnknownMessage(_))
+    ));
+    assert!(matches!(Transcoder::of::<Status>(), Err(TranscodeError::UnknownMessage(_))));
+}
@@ -0,0 +1,139 @@
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+#[cfg(feature = "build-schemas")]
+mod common;
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Account {
+    pub id: u64,
+    pub owner: String,
+}
+
+#[proto_message(transparent)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccountRef {
+    Id(u64),
+}
+
+#[proto_message(transparent)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Snapshot {
+    Account { account: Account },
+}
+
+#[proto_message(string_enum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Currency {
+    #[proto(name = "USD")]
+    Usd,
+    #[proto(name = "EUR")]
+    Eur,
+    #[default]
+    #[proto(name = "XXX")]
+    Unknown,
+}
+
+#[proto_message(proto_path = "protos/tests/transparent_enums.proto")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transfer {
+    pub from: AccountRef,
+    pub snapshot: Snapshot,
+    pub currency: Currency,
+    pub accepted: Vec<Currency>,
+    pub fallback: Option<Currency>,
+}
+
+/// `Transfer` as an external schema sees it
+#[proto_message(proto_path = "protos/tests/transparent_enums.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PlainTransfer {
+    pub from: u64,
+    pub snapshot: Account,
+    pub currency: String,
+    pub accepted: Vec<String>,
+    pub fallback: Option<String>,
+}
+
+fn decode<T: ProtoDecode>(bytes: &[u8]) -> Result<T, proto_rs::DecodeError> {
+    <T as ProtoDecode>::decode(bytes, DecodeContext::default())
+}
+
+fn transfer() -> Transfer {
+    Transfer {
+        from: AccountRef::Id(7),
+        snapshot: Snapshot::Account {
+            account: Account {
+                id: 9,
+                owner: "ada".into(),
+            },
+        },
+        currency: Currency::Eur,
+        accepted: vec![Currency::Usd, Currency::Unknown],
+        fallback: Some(Currency::Usd),
+    }
+}
+
+#[test]
+fn transparent_enums_encode_as_their_contents() {
+    let plain = PlainTransfer {
+        from: 7,
+        snapshot: Account {
+            id: 9,
+            owner: "ada".into(),
+        },
+        currency: "EUR".into(),
+        accepted: vec!["USD".into(), "XXX".into()],
+        fallback: Some("USD".into()),
+    };
+    assert_eq!(transfer().encode_to_vec(), plain.encode_to_vec());
+    assert_eq!(decode::<Transfer>(&plain.encode_to_vec()).expect("transfer decodes"), transfer());
+}
+
+#[test]
+fn single_variant_enums_round_trip_at_top_level() {
+    let id = AccountRef::Id(300);
+    assert_eq!(id.encode_to_vec(), 300u64.encode_to_vec());
+
+    let snapshot = transfer().snapshot;
+    assert_eq!(decode::<Snapshot>(&snapshot.encode_to_vec()).expect("snapshot decodes"), snapshot);
+}
+
+#[test]
+fn string_enums_always_write_their_name() {
+    let mut transfer = transfer();
+    transfer.currency = Currency::Unknown;
+    let plain = decode::<PlainTransfer>(&transfer.encode_to_vec()).expect("plain transfer decodes");
+    assert_eq!(plain.currency, "XXX");
+}
+
+#[test]
+fn string_enum_names_are_checked_on_decode() {
+    let mut plain = PlainTransfer {
+        currency: String::new(),
+        ..PlainTransfer::default()
+    };
+    let decoded = decode::<Transfer>(&plain.encode_to_vec()).expect("empty name decodes");
+    assert_eq!(decoded.currency, Currency::Unknown, "an absent name is the default variant");
+
+    plain.currency = "GBP".into();
+    let err = decode::<Transfer>(&plain.encode_to_vec()).expect_err("unknown names are rejected");
+    assert!(err.to_string().contains("unknown Currency name"), "{err}");
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn schema_writes_transparent_enums_as_their_contents() {
+    let proto = common::emitted_proto("transparent_enums", "protos/tests/transparent_enums.proto");
+
+    assert!(
+        proto.contains(
+            "message Transfer {\n  uint64 from = 1;\n  Account snapshot = 2;\n  string currency = 3;\n  repeated string accepted = 4;\n  optional string fallback = 5;\n}"
+        ),
+        "{proto}"
+    );
+    assert!(!proto.contains("enum Currency"), "{proto}");
+}
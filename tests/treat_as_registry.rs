@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+use std::num::Wrapping;
+use std::path::PathBuf;
+
+use proto_rs::DecodeContext;
+use proto_rs::DecodeError;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+#[cfg(feature = "build-schemas")]
+mod common;
+
+pub type PortMap = BTreeMap<u32, u64>;
+
+proto_rs::register_treat_as!(
+    std::path::PathBuf as String via crate::path_to_string / crate::path_from_string;
+    Wrapping<u16> as u32 via crate::wrapping_to_u32 / try crate::wrapping_from_u32;
+    PortMap as std::collections::BTreeMap<u32, u64>
+);
+
+#[allow(clippy::ptr_arg)]
+fn path_to_string(path: &PathBuf) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+fn path_from_string(path: String) -> PathBuf {
+    PathBuf::from(path)
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn wrapping_to_u32(value: &Wrapping<u16>) -> u32 {
+    u32::from(value.0)
+}
+
+fn wrapping_from_u32(value: u32) -> Result<Wrapping<u16>, DecodeError> {
+    u16::try_from(value).map(Wrapping).map_err(|_| DecodeError::new(format!("counter {value} out of range")))
+}
+
+fn decode<T: ProtoDecode>(bytes: &[u8]) -> Result<T, DecodeError> {
+    <T as ProtoDecode>::decode(bytes, DecodeContext::default())
+}
+
+#[proto_message(proto_path = "protos/tests/treat_as_registry.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Mount {
+    pub source: PathBuf,
+    pub generation: Wrapping<u16>,
+    pub ports: PortMap,
+}
+
+#[proto_message(proto_path = "protos/tests/treat_as_registry.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Backup {
+    pub name: String,
+    pub target: std::path::PathBuf,
+    /// An explicit conversion wins over the registered one
+    #[proto(into = "String", into_fn = "wrapping_to_string", from_fn = "wrapping_from_string")]
+    pub generation: Wrapping<u16>,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn wrapping_to_string(value: &Wrapping<u16>) -> String {
+    value.0.to_string()
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn wrapping_from_string(value: String) -> Wrapping<u16> {
+    Wrapping(value.parse().unwrap_or_default())
+}
+
+/// The wire shape of [`Mount`]
+#[proto_message(proto_path = "protos/tests/treat_as_registry.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MountWire {
+    pub source: String,
+    pub generation: u32,
+    pub ports: BTreeMap<u32, u64>,
+}
+
+/// The wire shape of [`Backup`]
+#[proto_message(proto_path = "protos/tests/treat_as_registry.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BackupWire {
+    pub name: String,
+    pub target: String,
+    pub generation: String,
+}
+
+#[test]
+fn registered_types_use_their_proxy_on_the_wire() {
+    let mount = Mount {
+        source: PathBuf::from("/srv/data"),
+        generation: Wrapping(7),
+        ports: BTreeMap::from([(80, 1), (443, 2)]),
+    };
+    let wire = MountWire {
+        source: "/srv/data".into(),
+        generation: 7,
+        ports: BTreeMap::from([(80, 1), (443, 2)]),
+    };
+
+    let bytes = mount.encode_to_vec();
+    assert_eq!(bytes, wire.encode_to_vec());
+    assert_eq!(decode::<Mount>(&bytes).unwrap(), mount);
+}
+
+#[test]
+fn registration_covers_every_spelling_of_the_type() {
+    let backup = Backup {
+        name: "nightly".into(),
+        target: PathBuf::from("/mnt/backup"),
+        generation: Wrapping(12),
+    };
+    let wire = BackupWire {
+        name: "nightly".into(),
+        target: "/mnt/backup".into(),
+        generation: "12".into(),
+    };
+
+    let bytes = backup.encode_to_vec();
+    assert_eq!(bytes, wire.encode_to_vec());
+    assert_eq!(decode::<Backup>(&bytes).unwrap(), backup);
+}
+
+#[test]
+fn fallible_conversion_errors_fail_the_decode() {
+    let wire = MountWire {
+        generation: 70_000,
+        ..MountWire::default()
+    };
+
+    let err = decode::<Mount>(&wire.encode_to_vec()).unwrap_err();
+    assert!(err.to_string().contains("counter 70000 out of range"), "{err}");
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn schema_lists_registered_fields_as_their_proxy() {
+    let proto = common::emitted_proto("treat_as_registry", "protos/tests/treat_as_registry.proto");
+
+    assert!(
+        proto.contains("message Mount {\n  string source = 1;\n  uint32 generation = 2;\n  map<uint32, uint64> ports = 3;\n}"),
+        "{proto}"
+    );
+    assert!(
+        proto.contains("message Backup {\n  string name = 1;\n  string target = 2;\n  string generation = 3;\n}"),
+        "{proto}"
+    );
+}
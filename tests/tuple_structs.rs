@@ -0,0 +1,105 @@
+use proto_rs::DecodeContext;
+use proto_rs::ProtoArchive;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+#[cfg(feature = "build-schemas")]
+mod common;
+
+#[proto_message(proto_path = "protos/tests/tuple_structs.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Point(pub i32, pub i32);
+
+#[proto_message(proto_path = "protos/tests/tuple_structs.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Segment(pub Point, pub Point, pub Option<String>, pub Vec<u32>);
+
+/// `Segment` with named fields
+#[proto_message(proto_path = "protos/tests/tuple_structs.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NamedSegment {
+    pub from: Point,
+    pub to: Point,
+    pub label: Option<String>,
+    pub weights: Vec<u32>,
+}
+
+#[proto_message(proto_path = "protos/tests/tuple_structs.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Reading(
+    #[proto(name = "sensor_id")] pub u64,
+    #[proto(skip)] pub u32,
+    pub f64,
+    #[proto(tag = 9, name = "unit")] pub String,
+);
+
+#[proto_message(proto_path = "protos/tests/tuple_structs.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Pair<A, B>(pub A, pub B);
+
+fn decode<T: ProtoDecode>(bytes: &[u8]) -> Result<T, proto_rs::DecodeError> {
+    <T as ProtoDecode>::decode(bytes, DecodeContext::default())
+}
+
+fn segment() -> Segment {
+    Segment(Point(1, -2), Point(0, 7), Some("edge".into()), vec![3, 0, 5])
+}
+
+#[test]
+fn tuple_structs_round_trip() {
+    let segment = segment();
+    let bytes = segment.encode_to_vec();
+    assert!(ProtoArchive::size_hint::<0>(&segment).unwrap() >= bytes.len());
+    assert_eq!(decode::<Segment>(&bytes).expect("segment decodes"), segment);
+
+    let pair = Pair(Point(4, 4), "four".to_string());
+    assert_eq!(decode::<Pair<Point, String>>(&pair.encode_to_vec()).expect("pair decodes"), pair);
+}
+
+#[test]
+fn tuple_fields_take_positional_tags() {
+    let named = NamedSegment {
+        from: Point(1, -2),
+        to: Point(0, 7),
+        label: Some("edge".into()),
+        weights: vec![3, 0, 5],
+    };
+    assert_eq!(segment().encode_to_vec(), named.encode_to_vec());
+    assert_eq!(
+        decode::<NamedSegment>(&segment().encode_to_vec()).expect("named segment decodes"),
+        named
+    );
+}
+
+#[test]
+fn skipped_and_tagged_tuple_fields() {
+    let reading = Reading(42, 7, 21.5, "celsius".into());
+    let bytes = reading.encode_to_vec();
+    assert_eq!(bytes[0], 1 << 3, "sensor id is field 1");
+    assert_eq!(bytes[2], (2 << 3) | 1, "the field after a skipped one takes the next tag");
+    assert_eq!(bytes[11], (9 << 3) | 2, "explicit tags are kept");
+    assert_eq!(
+        decode::<Reading>(&bytes).expect("reading decodes"),
+        Reading(42, 0, 21.5, "celsius".into())
+    );
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn schema_synthesizes_tuple_field_names() {
+    let proto = common::emitted_proto("tuple_structs", "protos/tests/tuple_structs.proto");
+
+    assert!(
+        proto.contains("message Point {\n  int32 field_0 = 1;\n  int32 field_1 = 2;\n}"),
+        "{proto}"
+    );
+    assert!(
+        proto.contains("  optional string field_2 = 3;\n  repeated uint32 field_3 = 4;\n"),
+        "{proto}"
+    );
+    assert!(
+        proto.contains("message Reading {\n  uint64 sensor_id = 1;\n  double field_1 = 2;\n  string unit = 9;\n}"),
+        "{proto}"
+    );
+}
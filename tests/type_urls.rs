@@ -0,0 +1,63 @@
+#![cfg(feature = "build-schemas")]
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+use proto_rs::schemas;
+use proto_rs::schemas::ProtoIdentifiable;
+use proto_rs::schemas::TypeRegistry;
+
+#[proto_message(proto_path = "protos/tests/type_urls.proto", proto_package = "shop.v1")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Order {
+    pub id: u64,
+    pub sku: String,
+}
+
+#[proto_message(proto_path = "protos/tests/type_urls.proto", proto_package = "shop.v1")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Refund {
+    pub order_id: u64,
+}
+
+#[test]
+fn type_urls_use_the_package_and_name() {
+    let ident = <Order as ProtoIdentifiable>::PROTO_IDENT;
+    assert_eq!(ident.full_name(), "shop.v1.Order");
+    assert_eq!(ident.type_url(), "type.googleapis.com/shop.v1.Order");
+    assert_ne!(ident.stable_id(), <Refund as ProtoIdentifiable>::PROTO_IDENT.stable_id());
+    // FNV-1a 64 of "shop.v1.Order", fixed across builds
+    assert_eq!(ident.stable_id(), 0xadb7_53e6_2304_5faa);
+
+    let schema = schemas::lookup_type_url("type.googleapis.com/shop.v1.Refund").expect("refund is registered");
+    assert_eq!(schema.id, <Refund as ProtoIdentifiable>::PROTO_IDENT);
+    assert!(schemas::lookup_type_url("example.com/types/shop.v1.Order").is_some());
+}
+
+#[test]
+fn registry_decodes_by_type_url_and_id() {
+    let types = TypeRegistry::new().with::<Order>().with::<Refund>();
+    let order = Order { id: 7, sku: "A-1".into() };
+    let bytes = order.encode_to_vec();
+
+    let decoded = types.decode("type.googleapis.com/shop.v1.Order", &bytes).expect("order decodes");
+    assert_eq!(*decoded.downcast::<Order>().expect("decoded as Order"), order);
+
+    let id = <Order as ProtoIdentifiable>::PROTO_IDENT.stable_id();
+    let decoded = types.decode_id(id, &bytes).expect("order decodes by id");
+    assert_eq!(*decoded.downcast::<Order>().expect("decoded as Order"), order);
+    assert_eq!(types.resolve_id(id).map(|ident| ident.name), Some("Order"));
+
+    let err = types.decode_with("type.googleapis.com/shop.v1.Missing", &bytes, DecodeContext::default()).unwrap_err();
+    assert!(err.to_string().contains("no type registered for type.googleapis.com/shop.v1.Missing"));
+    assert_eq!(
+        types.type_urls().collect::<Vec<_>>(),
+        ["type.googleapis.com/shop.v1.Order", "type.googleapis.com/shop.v1.Refund"]
+    );
+}
+
+#[test]
+#[should_panic(expected = "registered twice")]
+fn registry_rejects_duplicates() {
+    let _ = TypeRegistry::new().with::<Order>().with::<Order>();
+}
@@ -0,0 +1,169 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+use proto_rs::unknown_enum::UnknownEnum;
+use proto_rs::unknown_enum::add_unknown_enum_hook;
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tier {
+    #[default]
+    Free,
+    Pro,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TierV2 {
+    #[default]
+    Free,
+    Pro,
+    Enterprise,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Region {
+    #[default]
+    Eu,
+    Us,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Account {
+    pub id: u64,
+    pub tier: Tier,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AccountV2 {
+    pub id: u64,
+    pub tier: TierV2,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Team {
+    pub owner: Account,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TeamV2 {
+    pub owner: AccountV2,
+}
+
+#[proto_message]
+#[repr(i64)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Epoch {
+    #[default]
+    Genesis = 0,
+    Far = 5_000_000_000,
+}
+
+#[proto_message]
+#[repr(u64)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Digest {
+    #[default]
+    Zero = 0,
+    High = 0xffff_ffff_ffff_fff0,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Ledger {
+    pub epoch: Epoch,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PlainLedger {
+    pub epoch: i64,
+}
+
+/// `Digest` as an external schema sees it when decoded on its own
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PlainDigest {
+    pub value: u64,
+}
+
+fn recorded() -> Arc<Mutex<Vec<UnknownEnum>>> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    add_unknown_enum_hook(move |event| sink.lock().unwrap().push(*event));
+    events
+}
+
+#[test]
+fn hook_sees_enum_field_and_value_once() {
+    let events = recorded();
+    let newer = TeamV2 {
+        owner: AccountV2 {
+            id: 1,
+            tier: TierV2::Enterprise,
+        },
+    };
+    let error = <Team as ProtoDecode>::decode(newer.encode_to_vec().as_slice(), DecodeContext::default()).unwrap_err();
+    assert!(error.to_string().contains("invalid enum value"));
+
+    let events = events.lock().unwrap();
+    let tier_events = events.iter().filter(|event| event.enum_name == "Tier").collect::<Vec<_>>();
+    assert_eq!(
+        tier_events,
+        [&UnknownEnum {
+            enum_name: "Tier",
+            message: Some("Account"),
+            field: Some("tier"),
+            value: 2,
+        }]
+    );
+}
+
+#[test]
+fn enum_decoded_on_its_own_has_no_field() {
+    let events = recorded();
+    let bytes = TierV2::Enterprise.encode_to_vec();
+    assert!(<Region as ProtoDecode>::decode(bytes.as_slice(), DecodeContext::default()).is_err());
+    let events = events.lock().unwrap();
+    assert!(events.contains(&UnknownEnum {
+        enum_name: "Region",
+        message: None,
+        field: None,
+        value: 2,
+    }));
+}
+
+#[test]
+fn wide_enums_report_to_the_hooks() {
+    let events = recorded();
+    let bytes = PlainLedger { epoch: -7_000_000_000 }.encode_to_vec();
+    assert!(<Ledger as ProtoDecode>::decode(bytes.as_slice(), DecodeContext::default()).is_err());
+    let bytes = PlainDigest {
+        value: 0xffff_ffff_ffff_fff1,
+    }
+    .encode_to_vec();
+    assert!(<Digest as ProtoDecode>::decode(bytes.as_slice(), DecodeContext::default()).is_err());
+
+    let events = events.lock().unwrap();
+    assert!(events.contains(&UnknownEnum {
+        enum_name: "Epoch",
+        message: Some("Ledger"),
+        field: Some("epoch"),
+        value: -7_000_000_000,
+    }));
+    assert!(events.contains(&UnknownEnum {
+        enum_name: "Digest",
+        message: None,
+        field: None,
+        value: -15,
+    }));
+}
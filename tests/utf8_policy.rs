@@ -0,0 +1,91 @@
+use proto_rs::MaybeUtf8;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::encoding::DecodeContext;
+use proto_rs::proto_message;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct StrictFeed {
+    #[proto(tag = 1)]
+    pub id: u64,
+    #[proto(tag = 2)]
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct LossyFeed {
+    #[proto(tag = 1)]
+    pub id: u64,
+    #[proto(tag = 2, utf8 = "lossy")]
+    pub title: String,
+    #[proto(tag = 3, utf8 = "lossy")]
+    pub tags: Vec<String>,
+    #[proto(tag = 4, utf8 = "lossy")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[proto_message]
+pub struct RawFeed {
+    #[proto(tag = 1)]
+    pub id: u64,
+    #[proto(tag = 2)]
+    pub title: MaybeUtf8,
+}
+
+/// id = 7, then `payload` as field 2 with invalid UTF-8.
+fn feed_with_invalid_title() -> Vec<u8> {
+    vec![0x08, 0x07, 0x12, 0x04, b'o', b'k', 0xc3, 0x28]
+}
+
+#[test]
+fn strict_string_rejects_invalid_utf8() {
+    let bytes = feed_with_invalid_title();
+    assert!(<StrictFeed as ProtoDecode>::decode(&bytes[..], DecodeContext::default()).is_err());
+}
+
+#[test]
+fn lossy_string_replaces_invalid_sequences() {
+    let mut bytes = feed_with_invalid_title();
+    // tags: "a\xff", note: "\xfe"
+    bytes.extend_from_slice(&[0x1a, 0x02, b'a', 0xff, 0x22, 0x01, 0xfe]);
+
+    let decoded = <LossyFeed as ProtoDecode>::decode(&bytes[..], DecodeContext::default()).expect("lossy decode");
+    assert_eq!(decoded.id, 7);
+    assert_eq!(decoded.title, "ok\u{FFFD}(");
+    assert_eq!(decoded.tags, vec!["a\u{FFFD}".to_string()]);
+    assert_eq!(decoded.note.as_deref(), Some("\u{FFFD}"));
+}
+
+#[test]
+fn lossy_string_roundtrips_valid_input() {
+    let message = LossyFeed {
+        id: 1,
+        title: "héllo".to_string(),
+        tags: vec!["x".to_string(), "y".to_string()],
+        note: Some("n".to_string()),
+    };
+    let encoded = message.encode_to_vec();
+    let decoded = <LossyFeed as ProtoDecode>::decode(&encoded[..], DecodeContext::default()).expect("decode");
+    assert_eq!(decoded, message);
+}
+
+#[test]
+fn maybe_utf8_keeps_raw_bytes() {
+    let bytes = feed_with_invalid_title();
+    let decoded = <RawFeed as ProtoDecode>::decode(&bytes[..], DecodeContext::default()).expect("raw decode");
+    assert!(!decoded.title.is_utf8());
+    assert_eq!(decoded.title.as_bytes(), &[b'o', b'k', 0xc3, 0x28]);
+
+    // Re-encoding preserves the original payload byte for byte.
+    assert_eq!(decoded.encode_to_vec(), bytes);
+
+    let valid = RawFeed {
+        id: 2,
+        title: MaybeUtf8::from("fine"),
+    };
+    let decoded = <RawFeed as ProtoDecode>::decode(&valid.encode_to_vec()[..], DecodeContext::default()).expect("decode");
+    assert_eq!(decoded.title.as_str(), Some("fine"));
+}
@@ -0,0 +1,276 @@
+#![cfg(feature = "build-schemas")]
+
+use std::collections::HashMap;
+
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+use proto_rs::schemas::MismatchKind;
+use proto_rs::schemas::ProtoLabel;
+use proto_rs::schemas::SchemaMismatch;
+use proto_rs::schemas::verify_against;
+
+#[proto_message(proto_path = "protos/tests/verify.proto", proto_package = "acme.verify.v1")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tier {
+    #[default]
+    Free,
+    Paid,
+}
+
+#[proto_message(proto_path = "protos/tests/verify.proto", proto_package = "acme.verify.v1")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Account {
+    pub id: u64,
+    pub tier: Tier,
+    pub emails: Vec<String>,
+    pub nickname: Option<String>,
+    pub limits: HashMap<String, u32>,
+}
+
+#[proto_message(proto_path = "protos/tests/verify.proto", proto_package = "acme.verify.v1")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Audit {
+    pub account: Option<Account>,
+}
+
+// Minimal mirror of descriptor.proto, to build the sets the upstream repository would produce.
+#[proto_message(proto_path = "protos/tests/verify_descriptor.proto", explicit_tags)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FileDescriptorSet {
+    #[proto(tag = 1)]
+    pub file: Vec<FileDescriptorProto>,
+}
+
+#[proto_message(proto_path = "protos/tests/verify_descriptor.proto", explicit_tags)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FileDescriptorProto {
+    #[proto(tag = 2)]
+    pub package: String,
+    #[proto(tag = 4)]
+    pub message_type: Vec<DescriptorProto>,
+    #[proto(tag = 5)]
+    pub enum_type: Vec<EnumDescriptorProto>,
+}
+
+#[proto_message(proto_path = "protos/tests/verify_descriptor.proto", explicit_tags)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DescriptorProto {
+    #[proto(tag = 1)]
+    pub name: String,
+    #[proto(tag = 2)]
+    pub field: Vec<FieldDescriptorProto>,
+    #[proto(tag = 3)]
+    pub nested_type: Vec<DescriptorProto>,
+    #[proto(tag = 7)]
+    pub options: Option<MessageOptions>,
+}
+
+#[proto_message(proto_path = "protos/tests/verify_descriptor.proto", explicit_tags)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MessageOptions {
+    #[proto(tag = 7)]
+    pub map_entry: bool,
+}
+
+#[proto_message(proto_path = "protos/tests/verify_descriptor.proto", explicit_tags)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FieldDescriptorProto {
+    #[proto(tag = 1)]
+    pub name: String,
+    #[proto(tag = 3)]
+    pub number: i32,
+    #[proto(tag = 4)]
+    pub label: i32,
+    #[proto(tag = 5)]
+    pub kind: i32,
+    #[proto(tag = 6)]
+    pub type_name: String,
+    #[proto(tag = 17)]
+    pub proto3_optional: bool,
+}
+
+#[proto_message(proto_path = "protos/tests/verify_descriptor.proto", explicit_tags)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EnumDescriptorProto {
+    #[proto(tag = 1)]
+    pub name: String,
+    #[proto(tag = 2)]
+    pub value: Vec<EnumValueDescriptorProto>,
+}
+
+#[proto_message(proto_path = "protos/tests/verify_descriptor.proto", explicit_tags)]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EnumValueDescriptorProto {
+    #[proto(tag = 1)]
+    pub name: String,
+    #[proto(tag = 2)]
+    pub number: i32,
+}
+
+const OPTIONAL: i32 = 1;
+const REPEATED: i32 = 3;
+const UINT64: i32 = 4;
+const UINT32: i32 = 13;
+const STRING: i32 = 9;
+const MESSAGE: i32 = 11;
+const ENUM: i32 = 14;
+
+fn field(name: &str, number: i32, label: i32, kind: i32, type_name: &str) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        name: name.into(),
+        number,
+        label,
+        kind,
+        type_name: type_name.into(),
+        proto3_optional: false,
+    }
+}
+
+fn upstream_with(account_fields: Vec<FieldDescriptorProto>, limit_kind: i32) -> Vec<u8> {
+    let limits_entry = DescriptorProto {
+        name: "LimitsEntry".into(),
+        field: vec![field("key", 1, OPTIONAL, STRING, ""), field("value", 2, OPTIONAL, limit_kind, "")],
+        options: Some(MessageOptions { map_entry: true }),
+        ..Default::default()
+    };
+    FileDescriptorSet {
+        file: vec![FileDescriptorProto {
+            package: "acme.verify.v1".into(),
+            message_type: vec![
+                DescriptorProto {
+                    name: "Account".into(),
+                    field: account_fields,
+                    nested_type: vec![limits_entry],
+                    ..Default::default()
+                },
+                DescriptorProto {
+                    name: "Audit".into(),
+                    field: vec![field("account", 1, OPTIONAL, MESSAGE, ".acme.verify.v1.Account")],
+                    ..Default::default()
+                },
+            ],
+            enum_type: vec![EnumDescriptorProto {
+                name: "Tier".into(),
+                value: vec![
+                    EnumValueDescriptorProto {
+                        name: "TIER_FREE".into(),
+                        number: 0,
+                    },
+                    EnumValueDescriptorProto {
+                        name: "TIER_PAID".into(),
+                        number: 1,
+                    },
+                ],
+            }],
+        }],
+    }
+    .encode_to_vec()
+}
+
+fn upstream(account_fields: Vec<FieldDescriptorProto>) -> Vec<u8> {
+    upstream_with(account_fields, UINT32)
+}
+
+fn account_fields() -> Vec<FieldDescriptorProto> {
+    vec![
+        field("id", 1, OPTIONAL, UINT64, ""),
+        field("tier", 2, OPTIONAL, ENUM, ".acme.verify.v1.Tier"),
+        field("emails", 3, REPEATED, STRING, ""),
+        FieldDescriptorProto {
+            proto3_optional: true,
+            ..field("nickname", 4, OPTIONAL, STRING, "")
+        },
+        field("limits", 5, REPEATED, MESSAGE, ".acme.verify.v1.Account.LimitsEntry"),
+    ]
+}
+
+fn account_mismatches(set: &[u8]) -> Vec<MismatchKind> {
+    verify_against(set)
+        .unwrap()
+        .into_iter()
+        .filter(|mismatch| mismatch.type_name == "acme.verify.v1.Account")
+        .map(|mismatch| mismatch.kind)
+        .collect()
+}
+
+#[test]
+fn matching_descriptor_set_reports_nothing() {
+    let mismatches: Vec<SchemaMismatch> = verify_against(&upstream(account_fields()))
+        .unwrap()
+        .into_iter()
+        .filter(|mismatch| mismatch.type_name.starts_with("acme.verify.v1."))
+        .collect();
+    assert_eq!(mismatches, []);
+}
+
+#[test]
+fn drifted_fields_are_reported() {
+    let mut fields = account_fields();
+    fields[0].number = 9;
+    fields[2].label = OPTIONAL;
+    fields[3].kind = 12;
+    fields.push(field("created_at", 6, OPTIONAL, UINT64, ""));
+    fields.remove(1);
+
+    assert_eq!(
+        account_mismatches(&upstream(fields)),
+        [
+            MismatchKind::Tag {
+                field: "id".into(),
+                ours: 1,
+                upstream: 9,
+            },
+            MismatchKind::MissingField { field: "tier".into() },
+            MismatchKind::Label {
+                field: "emails".into(),
+                ours: ProtoLabel::Repeated,
+                upstream: ProtoLabel::None,
+            },
+            MismatchKind::Type {
+                field: "nickname".into(),
+                ours: "string".into(),
+                upstream: "bytes".into(),
+            },
+            MismatchKind::ExtraField {
+                field: "created_at".into(),
+                tag: 6,
+            },
+        ]
+    );
+}
+
+#[test]
+fn map_value_types_are_compared() {
+    assert_eq!(
+        account_mismatches(&upstream_with(account_fields(), UINT64)),
+        [MismatchKind::Type {
+            field: "limits".into(),
+            ours: "map<string, uint32>".into(),
+            upstream: "map<string, uint64>".into(),
+        }]
+    );
+}
+
+#[test]
+fn packages_outside_the_set_are_skipped() {
+    let set = FileDescriptorSet {
+        file: vec![FileDescriptorProto {
+            package: "acme.other".into(),
+            ..Default::default()
+        }],
+    };
+    assert_eq!(verify_against(&set.encode_to_vec()).unwrap(), []);
+}
+
+#[test]
+fn mismatches_render_readably() {
+    let mismatch = SchemaMismatch {
+        type_name: "acme.verify.v1.Account".into(),
+        kind: MismatchKind::Tag {
+            field: "id".into(),
+            ours: 1,
+            upstream: 9,
+        },
+    };
+    assert_eq!(mismatch.to_string(), "`acme.verify.v1.Account.id` has tag 1, upstream 9");
+}
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+use std::sync::Weak;
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+#[cfg(feature = "build-schemas")]
+mod common;
+
+#[proto_message(proto_path = "protos/tests/weak_fields.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Station {
+    pub name: String,
+    pub platforms: u32,
+}
+
+#[proto_message(proto_path = "protos/tests/weak_fields.proto")]
+#[derive(Debug, Default)]
+pub struct Route {
+    pub label: String,
+    pub terminus: Weak<Station>,
+    pub stops: u32,
+}
+
+/// The same message with the back reference owned
+#[proto_message(proto_path = "protos/tests/weak_fields.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OwnedRoute {
+    pub label: String,
+    pub terminus: Station,
+    pub stops: u32,
+}
+
+fn station() -> Arc<Station> {
+    Arc::new(Station {
+        name: "Central".into(),
+        platforms: 12,
+    })
+}
+
+#[test]
+fn live_weak_field_encodes_its_target() {
+    let terminus = station();
+    let route = Route {
+        label: "north".into(),
+        terminus: Arc::downgrade(&terminus),
+        stops: 9,
+    };
+    let owned = OwnedRoute {
+        label: "north".into(),
+        terminus: (*terminus).clone(),
+        stops: 9,
+    };
+
+    assert_eq!(route.encode_to_vec(), owned.encode_to_vec());
+}
+
+#[test]
+fn dangling_weak_field_encodes_the_default() {
+    let route = Route {
+        label: "north".into(),
+        terminus: Arc::downgrade(&station()),
+        stops: 9,
+    };
+    assert!(route.terminus.upgrade().is_none());
+
+    let owned = OwnedRoute {
+        label: "north".into(),
+        terminus: Station::default(),
+        stops: 9,
+    };
+    assert_eq!(route.encode_to_vec(), owned.encode_to_vec());
+}
+
+#[test]
+fn weak_field_payload_is_skipped_on_decode() {
+    let owned = OwnedRoute {
+        label: "south".into(),
+        terminus: (*station()).clone(),
+        stops: 4,
+    };
+
+    let route = <Route as ProtoDecode>::decode(owned.encode_to_vec().as_slice(), DecodeContext::default()).expect("route decodes");
+    assert_eq!(route.label, "south");
+    assert_eq!(route.stops, 4);
+    assert!(route.terminus.upgrade().is_none());
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn schema_lists_weak_fields_as_their_target() {
+    let proto = common::emitted_proto("weak_fields", "protos/tests/weak_fields.proto");
+
+    assert!(
+        proto.contains("message Route {\n  string label = 1;\n  Station terminus = 2;\n  uint32 stops = 3;\n}"),
+        "{proto}"
+    );
+}
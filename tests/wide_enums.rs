@@ -0,0 +1,111 @@
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+#[cfg(feature = "build-schemas")]
+mod common;
+
+#[proto_message]
+#[repr(u64)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Unknown = 0,
+    Created = 0x9e37_79b9_7f4a_7c15,
+    Deleted = 0xc2b2_ae3d_27d4_eb4f,
+}
+
+#[proto_message]
+#[repr(i64)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Shard {
+    West = -5_000_000_000,
+    #[default]
+    Home = 1,
+    East = 5_000_000_000,
+}
+
+#[proto_message(proto_path = "protos/tests/wide_enums.proto")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub kind: EventKind,
+    pub shard: Shard,
+    pub history: Vec<EventKind>,
+}
+
+/// `Event` as an external schema sees it
+#[proto_message(proto_path = "protos/tests/wide_enums.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PlainEvent {
+    pub kind: u64,
+    pub shard: i64,
+    pub history: Vec<u64>,
+}
+
+fn decode<T: ProtoDecode>(bytes: &[u8]) -> Result<T, proto_rs::DecodeError> {
+    <T as ProtoDecode>::decode(bytes, DecodeContext::default())
+}
+
+#[test]
+fn wide_discriminants_are_kept() {
+    assert_eq!(EventKind::Created as u64, 0x9e37_79b9_7f4a_7c15);
+    assert_eq!(Shard::West as i64, -5_000_000_000);
+    assert_eq!(
+        EventKind::try_from(0xc2b2_ae3d_27d4_eb4f_u64).expect("known value"),
+        EventKind::Deleted
+    );
+}
+
+#[test]
+fn wide_enums_encode_as_64_bit_integers() {
+    let event = Event {
+        kind: EventKind::Deleted,
+        shard: Shard::East,
+        history: vec![EventKind::Created, EventKind::Unknown],
+    };
+    let plain = PlainEvent {
+        kind: 0xc2b2_ae3d_27d4_eb4f,
+        shard: 5_000_000_000,
+        history: vec![0x9e37_79b9_7f4a_7c15, 0],
+    };
+    assert_eq!(event.encode_to_vec(), plain.encode_to_vec());
+    assert_eq!(decode::<Event>(&plain.encode_to_vec()).expect("event decodes"), event);
+}
+
+#[test]
+fn nonzero_defaults_are_always_written() {
+    let event = Event {
+        kind: EventKind::Unknown,
+        shard: Shard::Home,
+        history: Vec::new(),
+    };
+    let plain = decode::<PlainEvent>(&event.encode_to_vec()).expect("plain event decodes");
+    assert_eq!(plain.kind, 0);
+    assert_eq!(plain.shard, 1);
+
+    let decoded = decode::<Event>(&[]).expect("empty event decodes");
+    assert_eq!(decoded.kind, EventKind::Unknown);
+    assert_eq!(decoded.shard, Shard::Home);
+}
+
+#[test]
+fn unknown_wide_values_are_rejected() {
+    let plain = PlainEvent {
+        kind: 7,
+        ..PlainEvent::default()
+    };
+    let err = decode::<Event>(&plain.encode_to_vec()).expect_err("unknown values are rejected");
+    assert!(err.to_string().contains("invalid enum value"), "{err}");
+}
+
+#[cfg(feature = "build-schemas")]
+#[test]
+fn schema_writes_wide_enums_as_integers() {
+    let proto = common::emitted_proto("wide_enums", "protos/tests/wide_enums.proto");
+
+    assert!(
+        proto.contains("message Event {\n  uint64 kind = 1;\n  int64 shard = 2;\n  repeated uint64 history = 3;\n}"),
+        "{proto}"
+    );
+    assert!(!proto.contains("enum EventKind"), "{proto}");
+}
@@ -0,0 +1,144 @@
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AnalyticsEvent {
+    pub m01: u64,
+    pub m02: u64,
+    pub m03: u64,
+    pub m04: u64,
+    pub m05: u64,
+    pub m06: u64,
+    pub m07: u64,
+    pub m08: u64,
+    pub m09: u64,
+    pub m10: u64,
+    pub m11: u64,
+    pub m12: u64,
+    pub m13: u64,
+    pub m14: u64,
+    pub m15: u64,
+    pub m16: u64,
+    pub m17: u64,
+    pub m18: u64,
+    pub m19: u64,
+    #[proto(cfg = "any()")]
+    pub retired: u64,
+    pub m21: u64,
+    pub m22: u64,
+    pub m23: u64,
+    pub m24: u64,
+    pub m25: u64,
+    pub m26: u64,
+    pub m27: u64,
+    pub m28: u64,
+    pub m29: u64,
+    pub m30: u64,
+    pub m31: u64,
+    pub m32: u64,
+    pub m33: u64,
+    pub m34: u64,
+    pub m35: u64,
+    pub m36: u64,
+    #[proto(tag = 100)]
+    pub user: String,
+    #[proto(tag = 101)]
+    pub session: String,
+    #[proto(tag = 102)]
+    pub page: String,
+    #[proto(tag = 103)]
+    pub referrer: String,
+    #[proto(tag = 500)]
+    pub trace: Vec<u32>,
+}
+
+#[proto_message]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HintedEvent {
+    pub h01: u64,
+    pub h02: u64,
+    pub h03: u64,
+    pub h04: u64,
+    pub h05: u64,
+    pub h06: u64,
+    pub h07: u64,
+    pub h08: u64,
+    pub h09: u64,
+    #[proto(hot)]
+    pub h10: u64,
+    pub h11: u64,
+    pub h12: u64,
+    pub h13: u64,
+    pub h14: u64,
+    pub h15: u64,
+    pub h16: u64,
+    pub h17: u64,
+    pub h18: u64,
+    pub h19: u64,
+    #[proto(cold)]
+    pub h20: u64,
+    pub h21: u64,
+    pub h22: u64,
+    pub h23: u64,
+    pub h24: u64,
+    pub h25: u64,
+    pub h26: u64,
+    pub h27: u64,
+    pub h28: u64,
+    pub h29: u64,
+    pub h30: u64,
+    pub h31: u64,
+    pub h32: u64,
+    pub h33: u64,
+    pub h34: u64,
+    #[proto(tag = 60, cold)]
+    pub raw: Vec<u8>,
+}
+
+fn sample() -> AnalyticsEvent {
+    AnalyticsEvent {
+        m01: 1,
+        m19: 19,
+        m21: 21,
+        m36: 36,
+        user: "ada".into(),
+        referrer: "home".into(),
+        trace: vec![7, 8],
+        ..AnalyticsEvent::default()
+    }
+}
+
+#[test]
+fn wide_messages_roundtrip() {
+    let event = sample();
+    let decoded = AnalyticsEvent::decode(event.encode_to_vec().as_slice(), DecodeContext::default()).expect("event decodes");
+    assert_eq!(decoded, event);
+}
+
+#[test]
+fn tags_missing_from_a_dense_run_are_skipped() {
+    // retired = 20 is compiled out, 60 falls between runs, 104 follows the last one
+    let mut bytes = vec![0xA0, 0x01, 9, 0xE0, 0x03, 9, 0xC0, 0x06, 9];
+    bytes.extend(sample().encode_to_vec());
+    let decoded = AnalyticsEvent::decode(bytes.as_slice(), DecodeContext::default()).expect("event decodes");
+    assert_eq!(decoded, sample());
+}
+
+#[test]
+fn hinted_fields_of_wide_messages_roundtrip() {
+    let event = HintedEvent {
+        h01: 1,
+        h09: 9,
+        h10: 10,
+        h11: 11,
+        h20: 20,
+        h34: 34,
+        raw: vec![0xab; 8],
+        ..HintedEvent::default()
+    };
+    let decoded = HintedEvent::decode(event.encode_to_vec().as_slice(), DecodeContext::default()).expect("event decodes");
+    assert_eq!(decoded, event);
+}
@@ -0,0 +1,164 @@
+#![cfg(feature = "wire-trace")]
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use proto_rs::DecodeContext;
+use proto_rs::ProtoDecode;
+use proto_rs::ProtoEncode;
+use proto_rs::proto_message;
+use tracing::Event;
+use tracing::Level;
+use tracing::Metadata;
+use tracing::Subscriber;
+use tracing::field::Field;
+use tracing::field::Visit;
+use tracing::span;
+
+#[proto_message(proto_path = "protos/tests/wire_trace.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[proto_message(proto_path = "protos/tests/wire_trace.proto")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Shape {
+    pub name: String,
+    pub origin: Option<Point>,
+    pub sides: u32,
+}
+
+#[derive(Debug, Clone)]
+struct Recorded {
+    level: Level,
+    fields: BTreeMap<String, String>,
+}
+
+impl Recorded {
+    fn get(&self, name: &str) -> &str {
+        self.fields.get(name).map_or("", String::as_str)
+    }
+
+    fn num(&self, name: &str) -> usize {
+        self.get(name).parse().expect("numeric field")
+    }
+}
+
+struct FieldVisitor<'a>(&'a mut BTreeMap<String, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{value:?}"));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+}
+
+#[derive(Clone, Default)]
+struct Collector {
+    events: Arc<Mutex<Vec<Recorded>>>,
+}
+
+impl Subscriber for Collector {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target() == "proto_rs::wire"
+    }
+
+    fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = BTreeMap::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        self.events.lock().unwrap().push(Recorded {
+            level: *event.metadata().level(),
+            fields,
+        });
+    }
+
+    fn enter(&self, _span: &span::Id) {}
+
+    fn exit(&self, _span: &span::Id) {}
+}
+
+fn capture<R>(f: impl FnOnce() -> R) -> (R, Vec<Recorded>) {
+    let collector = Collector::default();
+    let events = collector.events.clone();
+    let result = tracing::subscriber::with_default(collector, f);
+    let events = events.lock().unwrap().clone();
+    (result, events)
+}
+
+fn sample() -> Shape {
+    Shape {
+        name: "square".to_string(),
+        origin: Some(Point { x: 3, y: -4 }),
+        sides: 4,
+    }
+}
+
+#[test]
+fn decoded_fields_report_tags_and_offsets() {
+    let bytes = Shape::encode_to_vec(&sample());
+    let (decoded, events) = capture(|| <Shape as ProtoDecode>::decode(bytes.as_slice(), DecodeContext::default()));
+    assert_eq!(decoded.expect("decode"), sample());
+
+    let shape_fields: Vec<_> = events.iter().filter(|event| event.get("ty").ends_with("Shape")).collect();
+    let tags: Vec<_> = shape_fields.iter().map(|event| event.get("tag")).collect();
+    assert_eq!(tags, ["1", "2", "3"]);
+    assert_eq!(shape_fields[0].num("start"), 0);
+    assert_eq!(shape_fields[2].num("end"), bytes.len());
+    for pair in shape_fields.windows(2) {
+        assert_eq!(pair[0].num("end"), pair[1].num("start"));
+    }
+    assert!(shape_fields.iter().all(|event| event.level == Level::TRACE && event.fields.contains_key("elapsed_ns")));
+
+    // Nested fields are reported with offsets inside their parent field.
+    let origin = shape_fields[1];
+    let point_fields: Vec<_> = events.iter().filter(|event| event.get("ty").ends_with("Point")).collect();
+    assert_eq!(point_fields.len(), 2);
+    for field in point_fields {
+        assert!(field.num("start") > origin.num("start"));
+        assert!(field.num("end") <= origin.num("end"));
+    }
+}
+
+#[test]
+fn decode_failures_report_the_broken_field() {
+    let mut bytes = Shape::encode_to_vec(&sample());
+    bytes.truncate(bytes.len() - 3);
+    let (decoded, events) = capture(|| <Shape as ProtoDecode>::decode(bytes.as_slice(), DecodeContext::default()));
+    assert!(decoded.is_err());
+
+    let failed: Vec<_> = events.iter().filter(|event| event.level == Level::DEBUG).collect();
+    assert!(!failed.is_empty());
+    assert!(failed.iter().all(|event| event.fields.contains_key("error")));
+    assert!(failed.iter().any(|event| event.get("ty").ends_with("Shape")));
+}
+
+#[test]
+fn encoded_messages_report_their_size() {
+    let (bytes, events) = capture(|| Shape::encode_to_vec(&sample()));
+    let encoded: Vec<_> = events.iter().filter(|event| event.get("message") == "encoded message").collect();
+    assert_eq!(encoded.len(), 1);
+    assert!(encoded[0].get("ty").ends_with("Shape"));
+    assert_eq!(encoded[0].num("len"), bytes.len());
+}
+
+#[test]
+fn decoding_without_a_subscriber_is_untraced() {
+    let bytes = Shape::encode_to_vec(&sample());
+    let decoded = <Shape as ProtoDecode>::decode(bytes.as_slice(), DecodeContext::default()).expect("decode");
+    assert_eq!(decoded, sample());
+}